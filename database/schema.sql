@@ -15,7 +15,16 @@ CREATE TABLE IF NOT EXISTS projects (
     description TEXT,
     status TEXT DEFAULT 'active' CHECK(status IN ('active', 'paused', 'completed', 'archived')),
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    color TEXT, -- v1.4: '#RRGGBB'
+    icon TEXT, -- v1.4: emoji or icon name
+    pinned BOOLEAN DEFAULT FALSE,
+    sort_order INTEGER DEFAULT 0,
+    sync_excluded BOOLEAN DEFAULT FALSE, -- v1.4: kept out of sync; git sync waits until it is let back in
+    attachment_sync TEXT DEFAULT 'full' CHECK(attachment_sync IN ('full', 'metadata', 'never')), -- v1.4: default for its attachments
+    repo_path TEXT, -- v1.4: local checkout of the project's code; component paths are relative to it
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Components: Building blocks within projects (nestable)
@@ -28,6 +37,8 @@ CREATE TABLE IF NOT EXISTS components (
     status TEXT DEFAULT 'in_progress' CHECK(status IN ('planning', 'in_progress', 'testing', 'complete', 'deprecated')),
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT, -- v1.4: who created it, from the machine's identity
     UNIQUE(project_id, name)
 );
 
@@ -40,7 +51,9 @@ CREATE TABLE IF NOT EXISTS changes (
     new_value TEXT,
     change_type TEXT CHECK(change_type IN ('config', 'code', 'architecture', 'dependency', 'documentation', 'other')),
     reason TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Problems: Issues encountered
@@ -53,7 +66,12 @@ CREATE TABLE IF NOT EXISTS problems (
     severity TEXT DEFAULT 'medium' CHECK(severity IN ('low', 'medium', 'high', 'critical')),
     root_cause TEXT,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    solved_at TIMESTAMP
+    solved_at TIMESTAMP,
+    assignee_id INTEGER REFERENCES people(id) ON DELETE SET NULL, -- v1.4
+    escalated_at TIMESTAMP, -- v1.4: set once the problem outlives its severity SLA
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Solution Attempts: The decision tree of what we tried
@@ -65,7 +83,14 @@ CREATE TABLE IF NOT EXISTS solution_attempts (
     outcome TEXT CHECK(outcome IN ('success', 'failure', 'partial', 'abandoned', 'pending')),
     confidence TEXT DEFAULT 'attempted' CHECK(confidence IN ('attempted', 'worked_once', 'verified', 'proven', 'deprecated')),
     notes TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP, -- v1.4: attempt timing
+    ended_at TIMESTAMP,
+    time_spent_minutes INTEGER,
+    paused_minutes INTEGER DEFAULT 0, -- v1.4: time between a finish and a resume, left out of time spent
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Solutions: The winning solution for a problem
@@ -76,7 +101,10 @@ CREATE TABLE IF NOT EXISTS solutions (
     summary TEXT NOT NULL,
     code_snippet TEXT,
     key_insight TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Todos: Task tracking
@@ -91,7 +119,15 @@ CREATE TABLE IF NOT EXISTS todos (
     due_date TIMESTAMP,
     blocked_by_problem_id INTEGER REFERENCES problems(id) ON DELETE SET NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    completed_at TIMESTAMP
+    completed_at TIMESTAMP,
+    assignee_id INTEGER REFERENCES people(id) ON DELETE SET NULL, -- v1.4
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT, -- v1.4: who created it, from the machine's identity
+    estimate_hours REAL, -- v1.4: estimate in hours and/or story points
+    estimate_points REAL,
+    started_at TIMESTAMP, -- v1.4: first moved to in_progress
+    time_spent_minutes INTEGER -- v1.4: logged by hand or from focus sessions
 );
 
 -- Conversations: Log of Claude interactions
@@ -105,7 +141,9 @@ CREATE TABLE IF NOT EXISTS conversations (
     problems_referenced TEXT, -- JSON array of problem IDs
     solutions_created TEXT, -- JSON array of solution IDs
     tokens_used INTEGER,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Learnings: Insights and patterns
@@ -118,7 +156,16 @@ CREATE TABLE IF NOT EXISTS learnings (
     context TEXT,
     source TEXT CHECK(source IN ('experience', 'documentation', 'conversation', 'error', 'research')),
     verified BOOLEAN DEFAULT FALSE,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    ease REAL DEFAULT 2.5, -- v1.4: spaced-repetition review state
+    review_interval_days INTEGER DEFAULT 0,
+    review_repetitions INTEGER DEFAULT 0,
+    next_review_at TIMESTAMP, -- NULL means due now
+    last_reviewed_at TIMESTAMP,
+    supersedes_learning_id INTEGER REFERENCES learnings(id) ON DELETE SET NULL, -- v1.4: winner of a resolved contradiction
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Cross References: Links between items across projects
@@ -132,7 +179,8 @@ CREATE TABLE IF NOT EXISTS cross_references (
     target_id INTEGER NOT NULL,
     relationship TEXT NOT NULL CHECK(relationship IN ('similar_to', 'derived_from', 'contradicts', 'depends_on', 'supersedes', 'related_to')),
     notes TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Sessions: Track work sessions
@@ -145,7 +193,10 @@ CREATE TABLE IF NOT EXISTS sessions (
     focus_problem_id INTEGER REFERENCES problems(id) ON DELETE SET NULL,
     summary TEXT,
     outcomes TEXT, -- JSON
-    duration_minutes INTEGER
+    duration_minutes INTEGER,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- v1.4
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- ============================================================
@@ -179,7 +230,11 @@ CREATE TABLE IF NOT EXISTS attachments (
     -- Timestamps
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    indexed_at TIMESTAMP -- When AI last processed this file
+    indexed_at TIMESTAMP, -- When AI last processed this file
+    preview_metadata TEXT, -- v1.4: JSON: image size, PDF pages, line count, CSV headers
+    sync_policy TEXT CHECK(sync_policy IN ('full', 'metadata', 'never')), -- v1.4: NULL follows the project
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
 );
 
 -- Content Locations: Where key info lives in files
@@ -205,7 +260,9 @@ CREATE TABLE IF NOT EXISTS content_locations (
     related_learning_id INTEGER REFERENCES learnings(id) ON DELETE SET NULL,
     related_component_id INTEGER REFERENCES components(id) ON DELETE SET NULL,
     
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    indexer TEXT, -- v1.4: 'sections'/'symbols' for generated locations, NULL when added by hand or AI
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Extractions: Track what was extracted from files
@@ -226,7 +283,8 @@ CREATE TABLE IF NOT EXISTS extractions (
     user_reviewed BOOLEAN DEFAULT FALSE,
     user_approved BOOLEAN,
     
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- ============================================================
@@ -244,7 +302,8 @@ CREATE TABLE IF NOT EXISTS sync_status (
     pending_changes INTEGER DEFAULT 0,
     has_conflicts BOOLEAN DEFAULT FALSE,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Sync History: Log of sync operations
@@ -256,7 +315,9 @@ CREATE TABLE IF NOT EXISTS sync_history (
     files_changed INTEGER,
     status TEXT CHECK(status IN ('success', 'failed', 'conflict')),
     error_message TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    channel TEXT DEFAULT 'git' -- v1.4: 'git', or 'lan' for records exchanged directly with a peer
 );
 
 -- ============================================================
@@ -284,7 +345,18 @@ INSERT OR IGNORE INTO settings (key, value, category) VALUES
     ('theme', '"system"', 'appearance'),
     ('sidebar_collapsed', 'false', 'appearance'),
     ('data_path', '""', 'general'),
-    ('recent_projects', '[]', 'general');
+    ('recent_projects', '[]', 'general'),
+    ('auto_check_updates', 'true', 'general'),
+    ('sla_thresholds_hours', '{"critical": 24, "high": 72, "medium": 336, "low": 720}', 'general'),
+    ('storage_quota_mb', '2048', 'general'),
+    ('timezone', '""', 'general'),
+    ('sprint_start', '""', 'general'),
+    ('report_email_enabled', 'false', 'general'),
+    ('report_email_kind', '"weekly_review"', 'general'),
+    ('report_email_hour', '16', 'general'),
+    ('smtp_security', '"starttls"', 'general'),
+    ('local_api_enabled', 'false', 'general'),
+    ('local_api_port', '47821', 'general');
 
 -- ============================================================
 -- FULL-TEXT SEARCH
@@ -314,7 +386,10 @@ CREATE TABLE IF NOT EXISTS embeddings (
     embedding_model TEXT DEFAULT 'all-MiniLM-L6-v2',
     embedding BLOB,  -- Stored as binary for efficiency
     text_hash TEXT,  -- To detect if re-embedding needed
+    model_version TEXT, -- v1.4: embedding_model_version when embedded; a new one re-embeds
+    dimensions INTEGER, -- v1.4: length of the vector (little-endian f32s)
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
     UNIQUE(content_type, content_id)
 );
 
@@ -334,6 +409,7 @@ CREATE TABLE IF NOT EXISTS project_variables (
     description TEXT,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
     UNIQUE(project_id, category, name)
 );
 
@@ -350,6 +426,7 @@ CREATE TABLE IF NOT EXISTS project_methods (
     related_component_id INTEGER REFERENCES components(id) ON DELETE SET NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
     UNIQUE(project_id, name)
 );
 
@@ -390,7 +467,8 @@ CREATE TABLE IF NOT EXISTS learned_skills (
     promoted_at TIMESTAMP,
     
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Session State: Claude's working memory at checkpoints
@@ -417,13 +495,14 @@ CREATE TABLE IF NOT EXISTS session_state (
     key_facts TEXT,               -- JSON: ["User prefers explicit over implicit", "Project uses Tauri 2.0"]
     
     -- Chain for history
-    previous_state_id INTEGER REFERENCES session_state(id),
+    previous_state_id INTEGER REFERENCES session_state(id) ON DELETE SET NULL,
     
     -- Metrics
     tool_calls_this_session INTEGER DEFAULT 0,
     estimated_tokens INTEGER,     -- Approximate context size at this point
     
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Tool Registry: Knowledge about available MCP tools
@@ -449,6 +528,7 @@ CREATE TABLE IF NOT EXISTS tool_registry (
     
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
     UNIQUE(mcp_server, tool_name)
 );
 
@@ -472,13 +552,14 @@ CREATE TABLE IF NOT EXISTS tool_usage (
     -- Context
     task_type TEXT,                -- 'read', 'write', 'search', 'create', etc.
     trigger TEXT,                  -- 'user_request', 'chained', 'automatic'
-    preceding_tool_id INTEGER REFERENCES tool_usage(id),  -- For sequence analysis
+    preceding_tool_id INTEGER REFERENCES tool_usage(id) ON DELETE SET NULL,  -- For sequence analysis
     
     -- Feedback
     was_useful BOOLEAN,            -- Did this actually help?
     user_correction TEXT,          -- If user corrected, what did they say?
     
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Behavior Patterns: Sequences and approaches that work well
@@ -516,7 +597,8 @@ CREATE TABLE IF NOT EXISTS behavior_patterns (
     
     last_used TIMESTAMP,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
 );
 
 -- Algorithm Metrics: Self-tuning feedback loop
@@ -548,6 +630,697 @@ CREATE TABLE IF NOT EXISTS algorithm_metrics (
     should_adjust BOOLEAN,         -- Should algorithm parameters change?
     suggested_adjustment TEXT,     -- What to adjust: {"checkpoint_interval": "+5 calls"}
     
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: TAGS
+-- ============================================================
+
+-- Tags: Free-form labels on todos, problems, and learnings (e.g. #bug from quick add)
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('todo', 'problem', 'learning')),
+    entity_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,             -- Lowercase, without the leading '#'
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(entity_type, entity_id, tag)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: NOTES
+-- ============================================================
+
+-- Notes: Free-form daily journal entries in markdown
+CREATE TABLE IF NOT EXISTS notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER REFERENCES projects(id) ON DELETE CASCADE, -- NULL for cross-project journal entries
+    note_date DATE NOT NULL DEFAULT (DATE('now')),
+    title TEXT,
+    body TEXT NOT NULL DEFAULT '',   -- Markdown
+    pinned BOOLEAN DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
+);
+
+-- Note Links: [[FS1-P42]]-style references parsed out of note bodies
+-- Enables: backlinks from problems, todos, learnings, ... to the notes that mention them
+CREATE TABLE IF NOT EXISTS note_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    reference TEXT NOT NULL,       -- As written, e.g. 'FS1-P42'
+    target_type TEXT NOT NULL CHECK(target_type IN ('problem', 'todo', 'learning', 'component', 'solution', 'note')),
+    target_id INTEGER NOT NULL,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(note_id, target_type, target_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: PEOPLE
+-- ============================================================
+
+-- People: Collaborators (contractors, teammates) that todos and problems can be assigned to
+CREATE TABLE IF NOT EXISTS people (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    email TEXT,
+    role TEXT,                     -- Free-form: 'owner', 'contractor', 'reviewer', ...
+    notes TEXT,
+    active BOOLEAN DEFAULT TRUE,   -- Inactive people keep their history but drop out of pickers
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: COMMENTS
+-- ============================================================
+
+-- Comments: Discussion threads on any record
+CREATE TABLE IF NOT EXISTS comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('project', 'component', 'problem', 'attempt', 'solution', 'todo', 'learning', 'note')),
+    entity_id INTEGER NOT NULL,
+    author TEXT,                   -- Person name, 'claude', or NULL for the local user
+    body TEXT NOT NULL,            -- Markdown
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: PROBLEM CHECKLISTS
+-- ============================================================
+
+-- Problem Checklist Items: Hypotheses to verify while debugging a problem
+CREATE TABLE IF NOT EXISTS problem_checklist_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    problem_id INTEGER NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+    text TEXT NOT NULL,
+    checked BOOLEAN DEFAULT FALSE,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    checked_at TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: PROBLEM ENVIRONMENTS
+-- ============================================================
+
+-- Problem Environments: Where a problem was seen and how to reproduce it
+CREATE TABLE IF NOT EXISTS problem_environments (
+    problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+    os TEXT,
+    app_version TEXT,
+    device TEXT,
+    branch TEXT,
+    repro_steps TEXT CHECK (repro_steps IS NULL OR (json_valid(repro_steps) AND json_type(repro_steps) = 'array')), -- JSON array of strings
+    captured_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: STARS
+-- ============================================================
+
+-- Stars: Records the user marked as most important
+CREATE TABLE IF NOT EXISTS stars (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('problem', 'todo', 'learning', 'attachment')),
+    entity_id INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(entity_type, entity_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: WATCHES
+-- ============================================================
+
+-- Watches: Records the user wants to hear about whenever they change
+CREATE TABLE IF NOT EXISTS watches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('problem', 'todo', 'learning', 'component')),
+    entity_id INTEGER NOT NULL,
+    notify BOOLEAN DEFAULT TRUE, -- Also raise a notification, not just the targeted event
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(entity_type, entity_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: ATTACHMENT LINKS
+-- ============================================================
+
+-- Attachment Links: Every problem/component an attachment belongs to
+-- One file can back several records without copying it; the attachments table's own
+-- problem_id/component_id columns are mirrored in here by the triggers below
+CREATE TABLE IF NOT EXISTS attachment_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    attachment_id INTEGER NOT NULL REFERENCES attachments(id) ON DELETE CASCADE,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('problem', 'component')),
+    entity_id INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(attachment_id, entity_type, entity_id)
+);
+
+-- Links for attachments created before this table existed
+INSERT INTO attachment_links (attachment_id, entity_type, entity_id)
+SELECT id, 'problem', problem_id FROM attachments WHERE problem_id IS NOT NULL
+ON CONFLICT(attachment_id, entity_type, entity_id) DO NOTHING;
+
+INSERT INTO attachment_links (attachment_id, entity_type, entity_id)
+SELECT id, 'component', component_id FROM attachments WHERE component_id IS NOT NULL
+ON CONFLICT(attachment_id, entity_type, entity_id) DO NOTHING;
+
+-- ============================================================
+-- v1.4 ADDITIONS: ANNOTATIONS
+-- ============================================================
+
+-- Annotations: Markings drawn on image attachments (e.g. circling the error in a screenshot)
+CREATE TABLE IF NOT EXISTS annotations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    attachment_id INTEGER NOT NULL REFERENCES attachments(id) ON DELETE CASCADE,
+    shape TEXT NOT NULL CHECK(shape IN ('rect', 'ellipse', 'arrow', 'line', 'freehand', 'text', 'point')),
+    coordinates TEXT NOT NULL CHECK(json_valid(coordinates)), -- JSON, in image pixels
+    label TEXT,
+    color TEXT,
+    problem_id INTEGER REFERENCES problems(id) ON DELETE SET NULL, -- What the marking points at
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: PROJECT STATS
+-- ============================================================
+
+-- Project Stats: Dashboard counters kept current by the triggers below
+-- Counts that depend on the clock (recent changes, due reviews) are still queried live
+CREATE TABLE IF NOT EXISTS project_stats (
+    project_id INTEGER PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    component_count INTEGER NOT NULL DEFAULT 0,
+    open_problems INTEGER NOT NULL DEFAULT 0,   -- open or investigating
+    solved_problems INTEGER NOT NULL DEFAULT 0,
+    pending_todos INTEGER NOT NULL DEFAULT 0,
+    learning_count INTEGER NOT NULL DEFAULT 0,
+    attachment_count INTEGER NOT NULL DEFAULT 0
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: DIAGNOSTICS
+-- ============================================================
+
+-- Slow Queries: Statements that took longer than the slow-query threshold
+CREATE TABLE IF NOT EXISTS slow_queries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sql TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    recorded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: DIRECTORY WATCHES
+-- ============================================================
+
+-- Directory Watches: Source folders whose matching files are kept attached to a project
+CREATE TABLE IF NOT EXISTS directory_watches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    globs TEXT NOT NULL CHECK(json_valid(globs)), -- JSON array of patterns relative to path, e.g. ["docs/**/*.md"]
+    enabled BOOLEAN DEFAULT TRUE,
+    last_scan_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(project_id, path)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: CAPTURES
+-- ============================================================
+
+-- Captures: Text grabbed while capture mode is on, kept for review before it becomes a learning or note
+CREATE TABLE IF NOT EXISTS captures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    source TEXT NOT NULL DEFAULT 'clipboard' CHECK(source IN ('clipboard')),
+    content TEXT NOT NULL,
+    status TEXT DEFAULT 'pending' CHECK(status IN ('pending', 'converted', 'discarded')),
+    converted_type TEXT CHECK(converted_type IN ('learning', 'note')),
+    converted_id INTEGER,
+    captured_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT, -- v1.4: who created it, from the machine's identity
+    UNIQUE(project_id, content)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: INBOX
+-- ============================================================
+
+-- Inbox Items: Captures, imported emails and AI extractions waiting to be triaged into a record
+CREATE TABLE IF NOT EXISTS inbox_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    source TEXT NOT NULL CHECK(source IN ('capture', 'email', 'extraction', 'manual')),
+    source_id INTEGER, -- The capture or extraction it came from
+    title TEXT NOT NULL,
+    content TEXT,
+    status TEXT DEFAULT 'pending' CHECK(status IN ('pending', 'converted', 'discarded')),
+    converted_type TEXT CHECK(converted_type IN ('todo', 'problem', 'learning', 'note')),
+    converted_id INTEGER,
+    triaged_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: DASHBOARDS
+-- ============================================================
+
+-- Dashboard Layouts: Named sets of widgets (stat cards, filtered lists, charts) for a project
+CREATE TABLE IF NOT EXISTS dashboard_layouts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    widgets TEXT NOT NULL DEFAULT '[]' CHECK(json_valid(widgets)), -- JSON array of widget definitions, in display order
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT, -- v1.4: who created it, from the machine's identity
+    UNIQUE(project_id, name)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: GOALS
+-- ============================================================
+
+-- Goals: Objectives for a project with a target date, measured by their key results
+CREATE TABLE IF NOT EXISTS goals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    description TEXT,
+    start_date DATE NOT NULL DEFAULT (DATE('now')), -- Linked key results count work finished from here
+    target_date DATE,
+    status TEXT DEFAULT 'active' CHECK(status IN ('active', 'achieved', 'abandoned')),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
+);
+
+-- Key Results: Measurable targets of a goal, counted from todos or problems or kept by hand
+CREATE TABLE IF NOT EXISTS key_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goal_id INTEGER NOT NULL REFERENCES goals(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    metric TEXT NOT NULL DEFAULT 'manual' CHECK(metric IN ('manual', 'todos_completed', 'problems_solved')),
+    component_id INTEGER REFERENCES components(id) ON DELETE SET NULL, -- Only count this component's todos/problems
+    target_value REAL NOT NULL CHECK(target_value > 0),
+    current_value REAL NOT NULL DEFAULT 0, -- Manual key results only; linked ones are counted
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: ANOMALY ALERTS
+-- ============================================================
+
+-- Anomaly Alerts: Unusual activity found by the periodic analysis, shown until acknowledged
+CREATE TABLE IF NOT EXISTS anomaly_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    problem_id INTEGER REFERENCES problems(id) ON DELETE CASCADE, -- attempt_pileup only
+    kind TEXT NOT NULL CHECK(kind IN ('problem_spike', 'attempt_pileup', 'inactive_project')),
+    message TEXT NOT NULL,
+    observed REAL, -- What was measured: problems in the last day, failed attempts, idle days
+    expected REAL, -- What's normal for it, where there's a baseline
+    dedupe_key TEXT UNIQUE NOT NULL, -- One alert per occurrence, however often the analysis runs
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    acknowledged_at TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: API TOKENS
+-- ============================================================
+
+-- API Tokens: Scoped bearer tokens for the local API. Only a hash of each token is kept.
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    token_hash TEXT UNIQUE NOT NULL, -- SHA-256, hex
+    token_prefix TEXT NOT NULL,      -- First characters of the token, to tell them apart
+    permission TEXT NOT NULL DEFAULT 'read' CHECK(permission IN ('read', 'write')),
+    project_id INTEGER REFERENCES projects(id) ON DELETE CASCADE, -- NULL allows every project
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- API Requests: the audit log of local API calls belongs to each machine, so it lives in
+-- that machine's local database rather than here (see LOCAL_SCHEMA in database.rs)
+
+-- ============================================================
+-- v1.4 ADDITIONS: RECENT ITEMS
+-- ============================================================
+
+-- Item Views: What was opened and how often, for frecency ranking in the command palette
+CREATE TABLE IF NOT EXISTS item_views (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('project', 'component', 'problem', 'todo', 'learning', 'attachment', 'note')),
+    entity_id INTEGER NOT NULL,
+    project_id INTEGER REFERENCES projects(id) ON DELETE CASCADE,
+    view_count INTEGER NOT NULL DEFAULT 1,
+    score REAL NOT NULL DEFAULT 1,   -- Frecency as of last_viewed_at; decays from there
+    last_viewed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(entity_type, entity_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: REFERENCES
+-- ============================================================
+
+-- Web References: Links kept alongside files ("references" is an SQL keyword)
+CREATE TABLE IF NOT EXISTS web_references (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    component_id INTEGER REFERENCES components(id) ON DELETE SET NULL,
+    problem_id INTEGER REFERENCES problems(id) ON DELETE SET NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    description TEXT,
+    favicon_url TEXT,
+    fetched_at TIMESTAMP, -- Last time the page itself was retrieved
+    archived_html_path TEXT, -- Snapshot in the project bundle
+    link_status TEXT DEFAULT 'unchecked' CHECK(link_status IN ('unchecked', 'ok', 'broken')),
+    http_status INTEGER,
+    checked_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT, -- v1.4: who created it, from the machine's identity
+    UNIQUE(project_id, url)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: NETWORK QUEUE
+-- ============================================================
+
+-- Network Operations: Outbound work (git push, Notion export, page fetches) waiting for the network
+CREATE TABLE IF NOT EXISTS network_operations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operation TEXT NOT NULL, -- git_sync, notion_export, confluence_publish, reference_metadata, reference_snapshot
+    payload TEXT NOT NULL DEFAULT '{}' CHECK(json_valid(payload)),
+    description TEXT,
+    status TEXT DEFAULT 'pending' CHECK(status IN ('pending', 'failed')), -- Done operations are removed
+    attempts INTEGER DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: PROJECT MERGE
+-- ============================================================
+
+-- Merge Bases: Each record as it was last exported to or imported from another database,
+-- so a later import can tell which side changed a field
+CREATE TABLE IF NOT EXISTS merge_bases (
+    uuid TEXT PRIMARY KEY,
+    entity_type TEXT NOT NULL, -- Table the record belongs to
+    record TEXT NOT NULL CHECK(json_valid(record)),
+    merged_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: RECORD HISTORY
+-- ============================================================
+
+-- Record Versions: Every state a problem or solution has been in, written by the version_* triggers
+CREATE TABLE IF NOT EXISTS record_versions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('problem', 'solution')),
+    entity_id INTEGER NOT NULL,
+    version INTEGER NOT NULL, -- 1 is the record as created (or as first seen, for older records)
+    snapshot TEXT NOT NULL CHECK(json_valid(snapshot)),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(entity_type, entity_id, version)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: REPORT EMAILS
+-- ============================================================
+
+-- Report Emails: Every weekly review or standup sent (or tried) by email
+CREATE TABLE IF NOT EXISTS report_emails (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL CHECK(kind IN ('weekly_review', 'standup')),
+    project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL, -- NULL covers every project
+    recipients TEXT NOT NULL, -- Comma-separated
+    subject TEXT NOT NULL,
+    status TEXT NOT NULL CHECK(status IN ('sent', 'failed')),
+    error TEXT,
+    scheduled BOOLEAN DEFAULT FALSE, -- Sent by the Friday schedule rather than on request
+    sent_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: INTEGRATIONS
+-- ============================================================
+
+-- Integration Links: External ids for records exported to other tools
+-- Lets exporters (Notion, Confluence) update the same page on re-export
+CREATE TABLE IF NOT EXISTS integration_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    integration TEXT NOT NULL,     -- 'notion', 'confluence'
+    entity_type TEXT NOT NULL,     -- 'problem', 'learning', ... or a container such as 'problem_database'
+    entity_id INTEGER NOT NULL,    -- Record id (project id for containers)
+    external_id TEXT NOT NULL,     -- Page/database id on the remote side
+    container_id TEXT,             -- Database/space the page lives in
+    synced_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(integration, entity_type, entity_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: TODO TEMPLATES
+-- ============================================================
+
+-- Todo Templates: Reusable checklists, like a release checklist, turned into todos on demand
+CREATE TABLE IF NOT EXISTS todo_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT,
+    items TEXT NOT NULL DEFAULT '[]' CHECK (json_valid(items) AND json_type(items) = 'array'), -- JSON array of todo titles
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    author TEXT -- v1.4: who created it, from the machine's identity
+);
+
+-- Todo Checklists: One use of a template in a project; its items are ordinary todos
+CREATE TABLE IF NOT EXISTS todo_checklists (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    template_id INTEGER REFERENCES todo_templates(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- Todo Checklist Items: Which checklist each of its todos belongs to, in template order
+CREATE TABLE IF NOT EXISTS todo_checklist_items (
+    todo_id INTEGER PRIMARY KEY REFERENCES todos(id) ON DELETE CASCADE,
+    checklist_id INTEGER NOT NULL REFERENCES todo_checklists(id) ON DELETE CASCADE,
+    sort_order INTEGER NOT NULL DEFAULT 0
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: COMPONENT PATHS
+-- ============================================================
+
+-- Component Paths: Which files of the project's repository belong to a component, so changes
+-- to them can be attributed to it without asking
+CREATE TABLE IF NOT EXISTS component_paths (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    component_id INTEGER NOT NULL REFERENCES components(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL, -- Glob relative to projects.repo_path, e.g. crates/core/**
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(component_id, pattern)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: DEPENDENCY SNAPSHOTS
+-- ============================================================
+
+-- Dependency Snapshots: The locked dependency versions of a project's repository at one point
+-- in time; comparing one with the snapshot before it gives the dependency changes
+CREATE TABLE IF NOT EXISTS dependency_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    repo_path TEXT NOT NULL,
+    dependencies TEXT NOT NULL CHECK (json_valid(dependencies) AND json_type(dependencies) = 'array'), -- [{lockfile, ecosystem, name, version}]
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: TEST RESULTS
+-- ============================================================
+
+-- Test Runs: The summary of one ingested test report
+CREATE TABLE IF NOT EXISTS test_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    format TEXT NOT NULL CHECK(format IN ('junit', 'nextest')),
+    name TEXT, -- The report's file name, or what the caller called it
+    total INTEGER NOT NULL DEFAULT 0,
+    passed INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    skipped INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- Test Failures: The tests that failed in a run; tests that passed are only counted
+CREATE TABLE IF NOT EXISTS test_failures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL REFERENCES test_runs(id) ON DELETE CASCADE,
+    test_name TEXT NOT NULL,
+    message TEXT,
+    output TEXT,
+    duration_ms INTEGER,
+    problem_id INTEGER REFERENCES problems(id) ON DELETE SET NULL, -- The open problem about it, linked once it fails again
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: CI STATUS
+-- ============================================================
+
+-- CI Sources: Where a project's build status is read from
+CREATE TABLE IF NOT EXISTS ci_sources (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL CHECK(provider IN ('github', 'badge')),
+    target TEXT NOT NULL, -- owner/repo for GitHub Actions, the endpoint URL for a badge
+    branch TEXT, -- GitHub: only runs on this branch
+    workflow TEXT, -- GitHub: only this workflow (file name or id)
+    enabled BOOLEAN DEFAULT TRUE,
+    last_polled_at TIMESTAMP,
+    last_error TEXT, -- From the last poll; cleared when one succeeds
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- CI Runs: Build outcomes read from a source
+CREATE TABLE IF NOT EXISTS ci_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_id INTEGER NOT NULL REFERENCES ci_sources(id) ON DELETE CASCADE,
+    external_id TEXT NOT NULL, -- The GitHub run id; badge readings use when they were read
+    status TEXT NOT NULL CHECK(status IN ('success', 'failure', 'cancelled', 'running', 'unknown')),
+    title TEXT,
+    branch TEXT,
+    commit_sha TEXT,
+    url TEXT,
+    started_at TIMESTAMP, -- When the run started, or when a badge reading was taken
+    completed_at TIMESTAMP,
+    uuid TEXT, -- v1.4: stable id matching the record across databases
+    UNIQUE(source_id, external_id)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: BENCHMARKS
+-- ============================================================
+
+-- Benchmarks: One measurement of a named benchmark, for following it over time
+CREATE TABLE IF NOT EXISTS benchmarks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    value REAL NOT NULL,
+    unit TEXT, -- ms, ns/iter, MB, ops/s...; rates are better higher, everything else lower
+    commit_sha TEXT, -- The commit measured
+    change_id INTEGER REFERENCES changes(id) ON DELETE SET NULL, -- The imported commit's change
+    regression_change_id INTEGER REFERENCES changes(id) ON DELETE SET NULL, -- Logged when it regressed
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    uuid TEXT -- v1.4: stable id matching the record across databases
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: STATS HISTORY
+-- ============================================================
+
+-- Stats History: A project's numbers once a day, for charting how its health trends
+CREATE TABLE IF NOT EXISTS stats_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    day DATE NOT NULL, -- YYYY-MM-DD on the user's clock
+    metric TEXT NOT NULL, -- open_problems, pending_todos, learning_count, attachment_bytes...
+    value REAL NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(project_id, day, metric)
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: JOB QUEUE
+-- ============================================================
+
+-- Jobs: Long-running local work (re-embedding, batched AI descriptions) run one at a time in
+-- the background, with progress the UI can follow
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL, -- reindex_embeddings, describe_attachments...
+    payload TEXT NOT NULL DEFAULT '{}' CHECK(json_valid(payload)),
+    description TEXT,
+    status TEXT NOT NULL DEFAULT 'queued' CHECK(status IN ('queued', 'running', 'done', 'failed', 'cancelled')),
+    progress INTEGER NOT NULL DEFAULT 0, -- Items done
+    total INTEGER, -- Items to do, once known
+    message TEXT, -- What it's doing now
+    result TEXT CHECK(result IS NULL OR json_valid(result)),
+    error TEXT,
+    input_tokens INTEGER NOT NULL DEFAULT 0, -- Language model use so far
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost REAL NOT NULL DEFAULT 0, -- At the llm_cost_per_million_* settings' prices
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+-- ============================================================
+-- v1.4 ADDITIONS: AI USAGE
+-- ============================================================
+
+-- AI usage: Every request to a language model or embedding provider, with what it cost at the
+-- prices in settings, for spending reports and the monthly budget
+CREATE TABLE IF NOT EXISTS ai_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider TEXT NOT NULL, -- openai, ollama... or the endpoint's host
+    model TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK(operation IN ('chat', 'embeddings')),
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost REAL NOT NULL DEFAULT 0, -- Estimated
+    command TEXT NOT NULL, -- What made the request, e.g. suggest_category
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -607,6 +1380,107 @@ CREATE INDEX IF NOT EXISTS idx_metrics_project ON algorithm_metrics(project_id);
 CREATE INDEX IF NOT EXISTS idx_metrics_type ON algorithm_metrics(metric_type);
 CREATE INDEX IF NOT EXISTS idx_metrics_session ON algorithm_metrics(session_state_id);
 
+-- v1.4 indexes
+CREATE INDEX IF NOT EXISTS idx_tags_entity ON tags(entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);
+CREATE INDEX IF NOT EXISTS idx_notes_project ON notes(project_id);
+CREATE INDEX IF NOT EXISTS idx_notes_date ON notes(note_date);
+CREATE INDEX IF NOT EXISTS idx_note_links_note ON note_links(note_id);
+CREATE INDEX IF NOT EXISTS idx_note_links_target ON note_links(target_type, target_id);
+CREATE INDEX IF NOT EXISTS idx_todos_assignee ON todos(assignee_id);
+CREATE INDEX IF NOT EXISTS idx_problems_assignee ON problems(assignee_id);
+CREATE INDEX IF NOT EXISTS idx_comments_entity ON comments(entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS idx_checklist_problem ON problem_checklist_items(problem_id, sort_order);
+CREATE INDEX IF NOT EXISTS idx_learnings_review ON learnings(next_review_at);
+CREATE INDEX IF NOT EXISTS idx_attachment_links_entity ON attachment_links(entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS idx_annotations_attachment ON annotations(attachment_id);
+CREATE INDEX IF NOT EXISTS idx_attachments_path ON attachments(project_id, file_path);
+CREATE INDEX IF NOT EXISTS idx_captures_project ON captures(project_id, status);
+CREATE INDEX IF NOT EXISTS idx_inbox_items_project ON inbox_items(project_id, status);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_inbox_items_source ON inbox_items(source, source_id) WHERE source_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_goals_project ON goals(project_id, status);
+CREATE INDEX IF NOT EXISTS idx_key_results_goal ON key_results(goal_id, sort_order);
+CREATE INDEX IF NOT EXISTS idx_anomaly_alerts_project ON anomaly_alerts(project_id, acknowledged_at);
+CREATE INDEX IF NOT EXISTS idx_api_tokens_project ON api_tokens(project_id);
+CREATE INDEX IF NOT EXISTS idx_item_views_project ON item_views(project_id, last_viewed_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_name_nocase ON projects(name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_web_references_project ON web_references(project_id);
+CREATE INDEX IF NOT EXISTS idx_network_operations_due ON network_operations(status, next_attempt_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_uuid ON projects(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_components_uuid ON components(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_changes_uuid ON changes(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_problems_uuid ON problems(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_solution_attempts_uuid ON solution_attempts(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_solutions_uuid ON solutions(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_todos_uuid ON todos(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conversations_uuid ON conversations(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_learnings_uuid ON learnings(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sessions_uuid ON sessions(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cross_references_uuid ON cross_references(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_attachments_uuid ON attachments(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_locations_uuid ON content_locations(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_extractions_uuid ON extractions(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_status_uuid ON sync_status(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_history_uuid ON sync_history(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_embeddings_uuid ON embeddings(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_project_variables_uuid ON project_variables(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_project_methods_uuid ON project_methods(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_learned_skills_uuid ON learned_skills(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_session_state_uuid ON session_state(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_registry_uuid ON tool_registry(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_usage_uuid ON tool_usage(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_behavior_patterns_uuid ON behavior_patterns(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_algorithm_metrics_uuid ON algorithm_metrics(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_uuid ON tags(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notes_uuid ON notes(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_note_links_uuid ON note_links(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_people_uuid ON people(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_comments_uuid ON comments(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_problem_checklist_items_uuid ON problem_checklist_items(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_stars_uuid ON stars(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_watches_uuid ON watches(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_attachment_links_uuid ON attachment_links(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_annotations_uuid ON annotations(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_slow_queries_uuid ON slow_queries(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_directory_watches_uuid ON directory_watches(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_captures_uuid ON captures(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_inbox_items_uuid ON inbox_items(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dashboard_layouts_uuid ON dashboard_layouts(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_goals_uuid ON goals(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_key_results_uuid ON key_results(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_anomaly_alerts_uuid ON anomaly_alerts(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_tokens_uuid ON api_tokens(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_item_views_uuid ON item_views(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_web_references_uuid ON web_references(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_network_operations_uuid ON network_operations(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_integration_links_uuid ON integration_links(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_record_versions_uuid ON record_versions(uuid);
+CREATE INDEX IF NOT EXISTS idx_report_emails_sent ON report_emails(sent_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_report_emails_uuid ON report_emails(uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_todo_templates_name_nocase ON todo_templates(name COLLATE NOCASE);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_todo_templates_uuid ON todo_templates(uuid);
+CREATE INDEX IF NOT EXISTS idx_todo_checklists_project ON todo_checklists(project_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_todo_checklists_uuid ON todo_checklists(uuid);
+CREATE INDEX IF NOT EXISTS idx_todo_checklist_items_checklist ON todo_checklist_items(checklist_id, sort_order);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_component_paths_uuid ON component_paths(uuid);
+CREATE INDEX IF NOT EXISTS idx_dependency_snapshots_project ON dependency_snapshots(project_id, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dependency_snapshots_uuid ON dependency_snapshots(uuid);
+CREATE INDEX IF NOT EXISTS idx_test_runs_project ON test_runs(project_id, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_test_runs_uuid ON test_runs(uuid);
+CREATE INDEX IF NOT EXISTS idx_test_failures_run ON test_failures(run_id);
+CREATE INDEX IF NOT EXISTS idx_test_failures_test ON test_failures(test_name);
+CREATE INDEX IF NOT EXISTS idx_test_failures_problem ON test_failures(problem_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_test_failures_uuid ON test_failures(uuid);
+CREATE INDEX IF NOT EXISTS idx_ci_sources_project ON ci_sources(project_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ci_sources_uuid ON ci_sources(uuid);
+CREATE INDEX IF NOT EXISTS idx_ci_runs_source ON ci_runs(source_id, started_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ci_runs_uuid ON ci_runs(uuid);
+CREATE INDEX IF NOT EXISTS idx_benchmarks_series ON benchmarks(project_id, name, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_benchmarks_uuid ON benchmarks(uuid);
+CREATE INDEX IF NOT EXISTS idx_stats_history_metric ON stats_history(project_id, metric, day);
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status, id);
+CREATE INDEX IF NOT EXISTS idx_ai_usage_created ON ai_usage(created_at);
+
 -- ============================================================
 -- TRIGGERS FOR AUTO-UPDATE
 -- ============================================================
@@ -637,6 +1511,14 @@ BEGIN
     UPDATE todos SET completed_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
 END;
 
+-- v1.4: When work on a todo began, for comparing with its estimate
+CREATE TRIGGER IF NOT EXISTS set_todo_started_at
+AFTER UPDATE OF status ON todos
+WHEN NEW.status = 'in_progress' AND NEW.started_at IS NULL
+BEGIN
+    UPDATE todos SET started_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
 -- v1.1 triggers
 CREATE TRIGGER IF NOT EXISTS update_attachment_timestamp 
 AFTER UPDATE ON attachments
@@ -687,3 +1569,396 @@ AFTER UPDATE ON behavior_patterns
 BEGIN
     UPDATE behavior_patterns SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
 END;
+
+-- v1.4 triggers: tags are polymorphic, so clean them up when their record goes away
+CREATE TRIGGER IF NOT EXISTS delete_todo_tags
+AFTER DELETE ON todos
+BEGIN
+    DELETE FROM tags WHERE entity_type = 'todo' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_problem_tags
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM tags WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_learning_tags
+AFTER DELETE ON learnings
+BEGIN
+    DELETE FROM tags WHERE entity_type = 'learning' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_notes_timestamp 
+AFTER UPDATE ON notes
+BEGIN
+    UPDATE notes SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_people_timestamp 
+AFTER UPDATE ON people
+BEGIN
+    UPDATE people SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_comments_timestamp 
+AFTER UPDATE ON comments
+BEGIN
+    UPDATE comments SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- v1.4 triggers: keep notes in the full-text index
+CREATE TRIGGER IF NOT EXISTS notes_fts_insert
+AFTER INSERT ON notes
+BEGIN
+    INSERT INTO memory_fts (content_type, content_id, project_id, searchable_text)
+    VALUES ('note', NEW.id, NEW.project_id, COALESCE(NEW.title, '') || ' ' || NEW.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS notes_fts_update
+AFTER UPDATE OF title, body, project_id ON notes
+BEGIN
+    DELETE FROM memory_fts WHERE content_type = 'note' AND content_id = OLD.id;
+    INSERT INTO memory_fts (content_type, content_id, project_id, searchable_text)
+    VALUES ('note', NEW.id, NEW.project_id, COALESCE(NEW.title, '') || ' ' || NEW.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS notes_fts_delete
+AFTER DELETE ON notes
+BEGIN
+    DELETE FROM memory_fts WHERE content_type = 'note' AND content_id = OLD.id;
+END;
+
+-- v1.4 triggers: drop comment threads along with their record
+CREATE TRIGGER IF NOT EXISTS delete_todo_comments
+AFTER DELETE ON todos
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'todo' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_problem_comments
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_attempt_comments
+AFTER DELETE ON solution_attempts
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'attempt' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_learning_comments
+AFTER DELETE ON learnings
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'learning' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_project_comments
+AFTER DELETE ON projects
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'project' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_component_comments
+AFTER DELETE ON components
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'component' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_solution_comments
+AFTER DELETE ON solutions
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'solution' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_note_comments
+AFTER DELETE ON notes
+BEGIN
+    DELETE FROM comments WHERE entity_type = 'note' AND entity_id = OLD.id;
+END;
+
+-- v1.4 triggers: checklist items go with their problem
+CREATE TRIGGER IF NOT EXISTS delete_problem_checklist
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM problem_checklist_items WHERE problem_id = OLD.id;
+END;
+
+-- v1.4 triggers: problem environments
+CREATE TRIGGER IF NOT EXISTS update_problem_environment_timestamp 
+AFTER UPDATE ON problem_environments
+BEGIN
+    UPDATE problem_environments SET updated_at = CURRENT_TIMESTAMP WHERE problem_id = NEW.problem_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_problem_environment
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM problem_environments WHERE problem_id = OLD.id;
+END;
+
+-- v1.4 triggers: unstar records when they're deleted
+CREATE TRIGGER IF NOT EXISTS delete_problem_star
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM stars WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_todo_star
+AFTER DELETE ON todos
+BEGIN
+    DELETE FROM stars WHERE entity_type = 'todo' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_learning_star
+AFTER DELETE ON learnings
+BEGIN
+    DELETE FROM stars WHERE entity_type = 'learning' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_attachment_star
+AFTER DELETE ON attachments
+BEGIN
+    DELETE FROM stars WHERE entity_type = 'attachment' AND entity_id = OLD.id;
+END;
+
+-- v1.4 triggers: stop watching records that are deleted
+CREATE TRIGGER IF NOT EXISTS delete_problem_watch
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM watches WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_todo_watch
+AFTER DELETE ON todos
+BEGIN
+    DELETE FROM watches WHERE entity_type = 'todo' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_learning_watch
+AFTER DELETE ON learnings
+BEGIN
+    DELETE FROM watches WHERE entity_type = 'learning' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_component_watch
+AFTER DELETE ON components
+BEGIN
+    DELETE FROM watches WHERE entity_type = 'component' AND entity_id = OLD.id;
+END;
+
+-- v1.4 triggers: keep project_stats counters current
+CREATE TRIGGER IF NOT EXISTS stats_project_insert
+AFTER INSERT ON projects
+BEGIN
+    INSERT INTO project_stats (project_id) VALUES (NEW.id) ON CONFLICT(project_id) DO NOTHING;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_component_insert
+AFTER INSERT ON components
+BEGIN
+    UPDATE project_stats SET component_count = component_count + 1 WHERE project_id = NEW.project_id;
+END;
+
+-- BEFORE, because by the time cascaded problem deletes fire the component is gone
+-- and they can no longer find their project
+CREATE TRIGGER IF NOT EXISTS stats_component_delete
+BEFORE DELETE ON components
+BEGIN
+    UPDATE project_stats SET
+        component_count = component_count - 1,
+        open_problems = open_problems - (SELECT COUNT(*) FROM problems WHERE component_id = OLD.id AND status IN ('open', 'investigating')),
+        solved_problems = solved_problems - (SELECT COUNT(*) FROM problems WHERE component_id = OLD.id AND status = 'solved')
+    WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_problem_insert
+AFTER INSERT ON problems
+BEGIN
+    UPDATE project_stats SET
+        open_problems = open_problems + (NEW.status IN ('open', 'investigating')),
+        solved_problems = solved_problems + (NEW.status = 'solved')
+    WHERE project_id = (SELECT project_id FROM components WHERE id = NEW.component_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_problem_delete
+AFTER DELETE ON problems
+BEGIN
+    UPDATE project_stats SET
+        open_problems = open_problems - (OLD.status IN ('open', 'investigating')),
+        solved_problems = solved_problems - (OLD.status = 'solved')
+    WHERE project_id = (SELECT project_id FROM components WHERE id = OLD.component_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_problem_status
+AFTER UPDATE OF status ON problems
+WHEN NEW.status != OLD.status
+BEGIN
+    UPDATE project_stats SET
+        open_problems = open_problems + (NEW.status IN ('open', 'investigating')) - (OLD.status IN ('open', 'investigating')),
+        solved_problems = solved_problems + (NEW.status = 'solved') - (OLD.status = 'solved')
+    WHERE project_id = (SELECT project_id FROM components WHERE id = NEW.component_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_todo_insert
+AFTER INSERT ON todos
+BEGIN
+    UPDATE project_stats SET pending_todos = pending_todos + (NEW.status = 'pending') WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_todo_delete
+AFTER DELETE ON todos
+BEGIN
+    UPDATE project_stats SET pending_todos = pending_todos - (OLD.status = 'pending') WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_todo_status
+AFTER UPDATE OF status ON todos
+WHEN NEW.status != OLD.status
+BEGIN
+    UPDATE project_stats SET pending_todos = pending_todos + (NEW.status = 'pending') - (OLD.status = 'pending')
+    WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_learning_insert
+AFTER INSERT ON learnings
+BEGIN
+    UPDATE project_stats SET learning_count = learning_count + 1 WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_learning_delete
+AFTER DELETE ON learnings
+BEGIN
+    UPDATE project_stats SET learning_count = learning_count - 1 WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_attachment_insert
+AFTER INSERT ON attachments
+BEGIN
+    UPDATE project_stats SET attachment_count = attachment_count + 1 WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS stats_attachment_delete
+AFTER DELETE ON attachments
+BEGIN
+    UPDATE project_stats SET attachment_count = attachment_count - 1 WHERE project_id = OLD.project_id;
+END;
+
+-- v1.4 triggers: mirror an attachment's own problem/component into attachment_links
+CREATE TRIGGER IF NOT EXISTS link_new_attachment
+AFTER INSERT ON attachments
+BEGIN
+    INSERT INTO attachment_links (attachment_id, entity_type, entity_id)
+    SELECT NEW.id, 'problem', NEW.problem_id WHERE NEW.problem_id IS NOT NULL
+    ON CONFLICT(attachment_id, entity_type, entity_id) DO NOTHING;
+    INSERT INTO attachment_links (attachment_id, entity_type, entity_id)
+    SELECT NEW.id, 'component', NEW.component_id WHERE NEW.component_id IS NOT NULL
+    ON CONFLICT(attachment_id, entity_type, entity_id) DO NOTHING;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_problem_attachment_links
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM attachment_links WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_component_attachment_links
+AFTER DELETE ON components
+BEGIN
+    DELETE FROM attachment_links WHERE entity_type = 'component' AND entity_id = OLD.id;
+END;
+
+-- v1.4 triggers: records merged across databases need to know when they last changed
+CREATE TRIGGER IF NOT EXISTS update_problem_timestamp 
+AFTER UPDATE ON problems
+BEGIN
+    UPDATE problems SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_solution_attempt_timestamp 
+AFTER UPDATE ON solution_attempts
+BEGIN
+    UPDATE solution_attempts SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_solution_timestamp 
+AFTER UPDATE ON solutions
+BEGIN
+    UPDATE solutions SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_todo_timestamp 
+AFTER UPDATE ON todos
+BEGIN
+    UPDATE todos SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_learning_timestamp 
+AFTER UPDATE ON learnings
+BEGIN
+    UPDATE learnings SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS update_session_timestamp 
+AFTER UPDATE ON sessions
+BEGIN
+    UPDATE sessions SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- v1.4 triggers: version problem and solution edits; only edits to the versioned fields count,
+-- so timestamp and uuid bookkeeping doesn't add versions. Records from before versioning get
+-- their previous state as version 1 on their first edit.
+CREATE TRIGGER IF NOT EXISTS version_problem_insert
+AFTER INSERT ON problems
+BEGIN
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    VALUES ('problem', NEW.id, 1, json_object('component_id', NEW.component_id, 'title', NEW.title, 'description', NEW.description, 'status', NEW.status, 'severity', NEW.severity, 'root_cause', NEW.root_cause, 'assignee_id', NEW.assignee_id));
+END;
+
+CREATE TRIGGER IF NOT EXISTS version_problem_update
+AFTER UPDATE ON problems
+WHEN OLD.component_id IS NOT NEW.component_id OR OLD.title IS NOT NEW.title
+    OR OLD.description IS NOT NEW.description OR OLD.status IS NOT NEW.status
+    OR OLD.severity IS NOT NEW.severity OR OLD.root_cause IS NOT NEW.root_cause
+    OR OLD.assignee_id IS NOT NEW.assignee_id
+BEGIN
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    SELECT 'problem', OLD.id, 1, json_object('component_id', OLD.component_id, 'title', OLD.title, 'description', OLD.description, 'status', OLD.status, 'severity', OLD.severity, 'root_cause', OLD.root_cause, 'assignee_id', OLD.assignee_id)
+    WHERE NOT EXISTS (SELECT 1 FROM record_versions WHERE entity_type = 'problem' AND entity_id = OLD.id);
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    SELECT 'problem', NEW.id, MAX(version) + 1, json_object('component_id', NEW.component_id, 'title', NEW.title, 'description', NEW.description, 'status', NEW.status, 'severity', NEW.severity, 'root_cause', NEW.root_cause, 'assignee_id', NEW.assignee_id)
+    FROM record_versions WHERE entity_type = 'problem' AND entity_id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_problem_versions
+AFTER DELETE ON problems
+BEGIN
+    DELETE FROM record_versions WHERE entity_type = 'problem' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS version_solution_insert
+AFTER INSERT ON solutions
+BEGIN
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    VALUES ('solution', NEW.id, 1, json_object('winning_attempt_id', NEW.winning_attempt_id, 'summary', NEW.summary, 'code_snippet', NEW.code_snippet, 'key_insight', NEW.key_insight));
+END;
+
+CREATE TRIGGER IF NOT EXISTS version_solution_update
+AFTER UPDATE ON solutions
+WHEN OLD.winning_attempt_id IS NOT NEW.winning_attempt_id OR OLD.summary IS NOT NEW.summary
+    OR OLD.code_snippet IS NOT NEW.code_snippet OR OLD.key_insight IS NOT NEW.key_insight
+BEGIN
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    SELECT 'solution', OLD.id, 1, json_object('winning_attempt_id', OLD.winning_attempt_id, 'summary', OLD.summary, 'code_snippet', OLD.code_snippet, 'key_insight', OLD.key_insight)
+    WHERE NOT EXISTS (SELECT 1 FROM record_versions WHERE entity_type = 'solution' AND entity_id = OLD.id);
+    INSERT INTO record_versions (entity_type, entity_id, version, snapshot)
+    SELECT 'solution', NEW.id, MAX(version) + 1, json_object('winning_attempt_id', NEW.winning_attempt_id, 'summary', NEW.summary, 'code_snippet', NEW.code_snippet, 'key_insight', NEW.key_insight)
+    FROM record_versions WHERE entity_type = 'solution' AND entity_id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_solution_versions
+AFTER DELETE ON solutions
+BEGIN
+    DELETE FROM record_versions WHERE entity_type = 'solution' AND entity_id = OLD.id;
+END;
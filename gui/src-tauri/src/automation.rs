@@ -0,0 +1,133 @@
+// FlowState Automation Module - flowstate:// deep-link commands
+// Lets Shortcuts, AppleScript (`open location`) and Raycast drive FlowState with URLs like
+// flowstate://run?cmd=add_todo&token=…&title=Ship%20it. Every command needs the automation
+// token, since any web page or app can open a URL. Results go back through the
+// x-callback-url parameters (x-success / x-error) when the caller passes them.
+
+use crate::database::AgendaItem;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+pub const COMMANDS: &[&str] = &["capture", "add_todo", "sync", "agenda"];
+
+#[derive(Debug)]
+pub struct Invocation {
+    pub command: String,
+    pub params: BTreeMap<String, String>,
+}
+
+impl Invocation {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+
+    pub fn require(&self, name: &str) -> Result<&str, String> {
+        self.param(name).ok_or_else(|| format!("{} needs a '{}' parameter", self.command, name))
+    }
+}
+
+/// Read a flowstate://run?cmd=…&… URL.
+pub fn parse(url: &str) -> Result<Invocation, String> {
+    let rest = url.strip_prefix("flowstate://")
+        .ok_or_else(|| format!("Not a FlowState link: {}", url))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if path.trim_end_matches('/') != "run" {
+        return Err(format!("Unknown FlowState link '{}'; expected flowstate://run?cmd=…", path));
+    }
+
+    let mut params = BTreeMap::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.insert(decode(key), decode(value));
+    }
+    let command = params.remove("cmd").unwrap_or_default();
+    if !COMMANDS.contains(&command.as_str()) {
+        return Err(format!("Unknown automation command '{}' ({})", command, COMMANDS.join(", ")));
+    }
+    Ok(Invocation { command, params })
+}
+
+/// Compare in constant time so the token can't be guessed a character at a time.
+pub fn check_token(given: Option<&str>, expected: &str) -> Result<(), String> {
+    let given = given.unwrap_or_default().as_bytes();
+    let expected = expected.as_bytes();
+    let difference = given.iter().zip(expected).fold(given.len() ^ expected.len(), |acc, (a, b)| acc | (a ^ b) as usize);
+    if difference == 0 { Ok(()) } else { Err("Missing or wrong automation token".to_string()) }
+}
+
+pub fn generate_token() -> String {
+    format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// v1.4: SHA-256 of a token in hex, which is all that's kept of local API tokens.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// `base` with `key=value` added to its query string.
+pub fn callback_url(base: &str, key: &str, value: &str) -> String {
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", base, separator, encode(key), encode(value))
+}
+
+/// Plain-text agenda, the form Shortcuts and Raycast show best.
+pub fn agenda_text(date: &str, items: &[AgendaItem]) -> String {
+    if items.is_empty() {
+        return format!("Nothing due {} and nothing in progress.", date);
+    }
+    let show_project = items.iter().any(|i| i.project_id != items[0].project_id);
+    let mut sections: Vec<(&str, Vec<String>)> = vec![("Overdue", Vec::new()), ("Due today", Vec::new()), ("In progress", Vec::new())];
+    for item in items {
+        let due = item.due_date.as_deref().and_then(|d| d.get(..10));
+        let section = match due {
+            Some(due) if due < date => 0,
+            Some(due) if due == date => 1,
+            _ => 2, // In progress, due later or undated
+        };
+        let project = if show_project { format!("[{}] ", item.project_name) } else { String::new() };
+        let priority = if matches!(item.priority.as_str(), "high" | "critical") { format!(" ({})", item.priority) } else { String::new() };
+        sections[section].1.push(format!("- {}{}{}", project, item.title, priority));
+    }
+    let mut out = format!("Agenda for {}\n", date);
+    for (title, lines) in sections.into_iter().filter(|(_, lines)| !lines.is_empty()) {
+        out.push_str(&format!("\n{}\n{}\n", title, lines.join("\n")));
+    }
+    out
+}
+
+// ============================================================
+// PERCENT-ENCODING
+// ============================================================
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
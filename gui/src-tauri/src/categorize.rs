@@ -0,0 +1,175 @@
+// FlowState Categorize Module - Suggest a category for learnings left without one
+// Keyword rules come first: each category has words that give it away, and the category with
+// the most of them in the insight wins. The language model can classify instead, shown the
+// categories and a few learnings already filed under each; anything it replies that isn't a
+// category is ignored and the rules stand.
+
+use crate::contradictions::tokenize;
+use serde::{Deserialize, Serialize};
+
+/// The categories the learnings table accepts.
+pub const CATEGORIES: &[&str] = &[
+    "pattern", "gotcha", "best_practice", "tool_tip", "architecture", "performance", "security", "other",
+];
+
+/// Insights sent to the language model in one request.
+pub const LLM_BATCH_SIZE: usize = 20;
+
+// Words that point to a category, matched against the start of each word ("auth" matches
+// "authentication"). On a tie the category listed first wins, so the specific ones lead.
+const RULES: &[(&str, &[&str])] = &[
+    ("security", &[
+        "auth", "token", "secret", "password", "credential", "encrypt", "xss", "csrf", "inject", "sanitiz",
+        "vulnerab", "permission", "cve", "tls", "ssl", "cert", "cors", "oauth", "jwt",
+    ]),
+    ("performance", &[
+        "slow", "fast", "latency", "perf", "cache", "caching", "memory", "cpu", "optimi", "throughput",
+        "benchmark", "profil", "alloc", "lazy", "index", "batch",
+    ]),
+    ("architecture", &[
+        "architect", "module", "layer", "service", "coupling", "interface", "dependenc", "boundary",
+        "monolith", "schema", "separation", "abstraction",
+    ]),
+    ("tool_tip", &[
+        "cli", "shortcut", "flag", "shell", "alias", "git", "vscode", "editor", "plugin", "ide",
+        "terminal", "keybind", "extension", "command",
+    ]),
+    ("gotcha", &[
+        "gotcha", "careful", "beware", "surpris", "unexpected", "silently", "pitfall", "trap", "caveat",
+        "quirk", "actually", "breaks", "bug", "footgun",
+    ]),
+    ("best_practice", &[
+        "always", "never", "prefer", "avoid", "practice", "convention", "recommend", "instead", "rule",
+    ]),
+    ("pattern", &[
+        "pattern", "approach", "idiom", "technique", "builder", "factory", "observer", "wrapper",
+        "strategy", "retry", "backoff",
+    ]),
+];
+
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("pattern", "a reusable way of structuring code or solving a recurring problem"),
+    ("gotcha", "a surprising behaviour or trap that caused or could cause a bug"),
+    ("best_practice", "a rule of thumb about what to do or avoid"),
+    ("tool_tip", "how to get more out of a tool, command, editor or library"),
+    ("architecture", "how the system is split up and how its parts depend on each other"),
+    ("performance", "speed, memory, caching or other resource use"),
+    ("security", "authentication, secrets, permissions or attacks"),
+    ("other", "none of the above"),
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CategorySuggestion {
+    pub category: String,
+    pub confidence: f64, // 0-1; the language model's answers count as 0.8
+    pub source: String, // rules or llm
+    pub matched: Vec<String>, // The words the rules went by
+}
+
+/// A learning's suggested category, for review before it's applied.
+#[derive(Debug, Serialize, Clone)]
+pub struct CategoryProposal {
+    pub learning_id: i64,
+    pub insight: String,
+    pub suggestion: Option<CategorySuggestion>, // None when nothing gave it away
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CategoryAssignment {
+    pub learning_id: i64,
+    pub category: String,
+}
+
+/// The category the keyword rules give `text`, if any of its words point to one.
+pub fn suggest(text: &str) -> Option<CategorySuggestion> {
+    let words = tokenize(text);
+    let mut best: Option<(&str, Vec<String>)> = None;
+    let mut total = 0;
+    for (category, keywords) in RULES {
+        let mut matched: Vec<String> = words.iter()
+            .filter(|w| keywords.iter().any(|k| w.starts_with(k)))
+            .cloned()
+            .collect();
+        matched.sort();
+        matched.dedup();
+        total += matched.len();
+        if !matched.is_empty() && best.as_ref().is_none_or(|(_, m)| matched.len() > m.len()) {
+            best = Some((category, matched));
+        }
+    }
+    best.map(|(category, matched)| {
+        // More matching words make it surer, other categories' words less so
+        let strength = (0.4 + 0.2 * matched.len() as f64).min(1.0);
+        CategorySuggestion {
+            category: category.to_string(),
+            confidence: (strength * matched.len() as f64 / total as f64 * 100.0).round() / 100.0,
+            source: "rules".to_string(),
+            matched,
+        }
+    })
+}
+
+/// Instructions for classifying numbered insights, with the project's own learnings
+/// (category, insight) as examples of how it files things.
+pub fn classify_prompt(examples: &[(String, String)]) -> String {
+    let mut prompt = String::from(
+        "You file a software developer's learnings under one category each. The categories are:\n",
+    );
+    for (category, description) in DESCRIPTIONS {
+        prompt.push_str(&format!("- {}: {}\n", category, description));
+    }
+    if !examples.is_empty() {
+        prompt.push_str("\nLearnings already filed:\n");
+        for (category, insight) in examples {
+            prompt.push_str(&format!("- [{}] {}\n", category, insight));
+        }
+    }
+    prompt.push_str(
+        "\nYou get numbered learnings. Reply with one line per learning, \"number: category\", \
+         using only the category names above, and nothing else.",
+    );
+    prompt
+}
+
+/// The numbered list the classify prompt expects.
+pub fn numbered(insights: &[&str]) -> String {
+    insights.iter()
+        .enumerate()
+        .map(|(i, insight)| format!("{}. {}", i + 1, insight.replace('\n', " ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The category the model gave each of `count` numbered learnings; None where its reply
+/// skipped one or named something that isn't a category.
+pub fn parse_reply(reply: &str, count: usize) -> Vec<Option<&'static str>> {
+    let mut categories = vec![None; count];
+    for line in reply.lines() {
+        let Some((number, answer)) = line.split_once([':', '.', ')']) else {
+            continue;
+        };
+        let Ok(number) = number.trim().trim_start_matches(['-', '*', '#']).trim().parse::<usize>() else {
+            continue;
+        };
+        if (1..=count).contains(&number) {
+            categories[number - 1] = category_named(answer);
+        }
+    }
+    categories
+}
+
+/// A category as the model wrote it: any case, with spaces or hyphens, possibly quoted.
+fn category_named(answer: &str) -> Option<&'static str> {
+    let answer = answer.trim().trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase().replace([' ', '-'], "_");
+    CATEGORIES.iter().copied().find(|c| *c == answer)
+}
+
+/// A suggestion from the model's answer.
+pub fn from_llm(category: &str) -> CategorySuggestion {
+    CategorySuggestion {
+        category: category.to_string(),
+        confidence: 0.8,
+        source: "llm".to_string(),
+        matched: Vec::new(),
+    }
+}
@@ -0,0 +1,137 @@
+// FlowState Changelog Module - Keep a Changelog Markdown from change records
+// Solved problems are listed under Fixed. Recorded changes go under Added when they brought
+// in something new (no old value) and under Changed otherwise. Changes typed "other", which
+// include the focus and shell logs, are left out.
+// Release notes are the user-facing cousin: one bullet per solved problem, from its solution's
+// summary and key insight, optionally reworded by the configured language model.
+
+use crate::database::{ChangelogItem, ReleaseNoteItem};
+use serde_json::{json, Value};
+
+pub const FORMATS: &[&str] = &["markdown", "json"];
+pub const FILE_NAME: &str = "CHANGELOG.md";
+const UNRELEASED: &str = "## [Unreleased]";
+const PREAMBLE: &str = "# Changelog\n\n\
+All notable changes to this project will be documented in this file.\n\n\
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n";
+
+pub const RELEASE_NOTES_PROMPT: &str = "You edit release notes for the users of a software project. \
+Rewrite the draft bullet points you're given in plain, user-facing language: say what got better for \
+the user, drop internal jargon, file names and stack traces, and merge bullets that describe the same fix. \
+Keep it a Markdown bullet list and reply with the list only.";
+
+/// Keep a Changelog sections, in the order they're written.
+const SECTIONS: &[&str] = &["Added", "Changed", "Fixed"];
+
+/// The section an item belongs under, or None when it stays out of the changelog.
+fn section_of(item: &ChangelogItem) -> Option<&'static str> {
+    if item.kind == "problem" {
+        return Some("Fixed");
+    }
+    match item.change_type.as_deref() {
+        None | Some("other") => None,
+        Some("code") if item.old_value.is_none() => Some("Added"),
+        Some(_) => Some("Changed"),
+    }
+}
+
+/// Items grouped by section, empty sections left out.
+fn grouped(items: &[ChangelogItem]) -> Vec<(&'static str, Vec<&ChangelogItem>)> {
+    SECTIONS.iter()
+        .map(|section| (*section, items.iter().filter(|item| section_of(item) == Some(section)).collect::<Vec<_>>()))
+        .filter(|(_, items)| !items.is_empty())
+        .collect()
+}
+
+/// One bullet: the component in bold, then what changed or what was fixed.
+fn entry(item: &ChangelogItem) -> String {
+    let first_line = |text: &str| text.lines().next().unwrap_or_default().trim().to_string();
+    let what = if item.kind == "problem" {
+        first_line(&item.title)
+    } else {
+        match (&item.old_value, &item.new_value) {
+            (Some(old), Some(new)) => format!("{}: {} → {}", item.title, first_line(old), first_line(new)),
+            (None, Some(new)) => format!("{}: {}", item.title, first_line(new)),
+            _ => item.title.clone(),
+        }
+    };
+    let why = item.detail.as_deref().map(first_line).filter(|d| !d.is_empty());
+    match why {
+        Some(why) => format!("- **{}** {} — {}", item.component, what, why),
+        None => format!("- **{}** {}", item.component, what),
+    }
+}
+
+/// The `## [Unreleased]` section for `items`.
+pub fn markdown(items: &[ChangelogItem]) -> String {
+    let mut out = format!("{}\n", UNRELEASED);
+    let sections = grouped(items);
+    if sections.is_empty() {
+        out.push_str("\n_No changes recorded._\n");
+    }
+    for (section, items) in sections {
+        out.push_str(&format!("\n### {}\n\n", section));
+        for item in items {
+            out.push_str(&entry(item));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The same grouping as `markdown`, for callers that lay it out themselves.
+pub fn json(items: &[ChangelogItem]) -> Value {
+    let sections: serde_json::Map<String, Value> = grouped(items).into_iter()
+        .map(|(section, items)| (section.to_string(), json!(items)))
+        .collect();
+    Value::Object(sections)
+}
+
+/// `existing` CHANGELOG.md content with its Unreleased section replaced by `section`, or
+/// `section` added above the newest release. A missing file starts from the usual preamble.
+pub fn merge(existing: Option<&str>, section: &str) -> String {
+    let existing = existing.filter(|text| !text.trim().is_empty()).unwrap_or(PREAMBLE);
+    let section = section.trim_end();
+
+    if let Some(start) = existing.find(UNRELEASED) {
+        let rest = &existing[start + UNRELEASED.len()..];
+        let end = rest.find("\n## ").map(|i| start + UNRELEASED.len() + i + 1).unwrap_or(existing.len());
+        let after = &existing[end..];
+        let separator = if after.is_empty() { "\n" } else { "\n\n" };
+        return format!("{}{}{}{}", &existing[..start], section, separator, after);
+    }
+    match existing.find("\n## ") {
+        Some(i) => format!("{}\n\n{}\n\n{}", existing[..i].trim_end(), section, &existing[i + 1..]),
+        None => format!("{}\n\n{}\n", existing.trim_end(), section),
+    }
+}
+
+/// Draft release notes: a heading, then a bullet per solved problem grouped by component.
+pub fn release_notes(heading: &str, items: &[ReleaseNoteItem]) -> String {
+    let mut out = format!("## {}\n\n", heading);
+    if items.is_empty() {
+        out.push_str("_No fixes in this release._\n");
+    }
+    for item in items {
+        out.push_str(&release_note(item));
+        out.push('\n');
+    }
+    out
+}
+
+/// "- **api**: Crash on empty body — Guard against empty input. Insight.", with whichever
+/// of the summary and key insight the solution has.
+fn release_note(item: &ReleaseNoteItem) -> String {
+    let explanation = [&item.summary, &item.key_insight].iter()
+        .filter_map(|text| text.as_deref())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .map(|text| if text.ends_with(['.', '!', '?']) { text } else { format!("{}.", text) })
+        .collect::<Vec<_>>()
+        .join(" ");
+    if explanation.is_empty() {
+        format!("- **{}**: {}", item.component, item.title.trim())
+    } else {
+        format!("- **{}**: {} — {}", item.component, item.title.trim(), explanation)
+    }
+}
@@ -0,0 +1,152 @@
+// FlowState CI Module - Build status read from GitHub Actions or a status badge
+// GitHub sources list a repository's workflow runs (optionally one branch or workflow);
+// badge sources read a shields.io-style endpoint ({"message": "passing"}) from any CI that
+// publishes one, which only says how things stand now, so a reading is kept when it differs
+// from the last. Runs are polled in the background and on request.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::time::Duration;
+
+pub const PROVIDERS: &[&str] = &["github", "badge"];
+const GITHUB_API: &str = "https://api.github.com";
+const RUNS_PER_POLL: u32 = 20;
+
+const PASSING_WORDS: &[&str] = &["passing", "passed", "pass", "success", "succeeded", "ok", "green", "fixed"];
+const FAILING_WORDS: &[&str] = &["failing", "failed", "fail", "failure", "error", "errored", "broken", "red"];
+const RUNNING_WORDS: &[&str] = &["running", "pending", "queued", "in progress", "building", "started"];
+
+/// One run as the provider reported it. Badge readings have no id of their own.
+#[derive(Debug, Clone)]
+pub struct FetchedRun {
+    pub external_id: Option<String>,
+    pub status: String, // success, failure, cancelled, running or unknown
+    pub title: Option<String>,
+    pub branch: Option<String>,
+    pub commit_sha: Option<String>,
+    pub url: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// `target` as stored: owner/repo for GitHub (a github.com URL is accepted too), an http(s)
+/// URL for badges.
+pub fn clean_target(provider: &str, target: &str) -> Result<String, String> {
+    let target = target.trim();
+    match provider {
+        "github" => {
+            let repo = target.trim_start_matches("https://").trim_start_matches("http://").trim_start_matches("github.com/");
+            let repo = repo.trim_end_matches('/').trim_end_matches(".git");
+            match repo.split('/').collect::<Vec<_>>()[..] {
+                [owner, name] if !owner.is_empty() && !name.is_empty() => Ok(format!("{}/{}", owner, name)),
+                _ => Err(format!("'{}' is not a GitHub repository; expected owner/repo", target)),
+            }
+        }
+        "badge" if target.starts_with("https://") || target.starts_with("http://") => Ok(target.to_string()),
+        "badge" => Err(format!("'{}' is not a badge URL; expected http(s)://...", target)),
+        _ => Err(format!("Unknown CI provider '{}'. Expected one of: {}", provider, PROVIDERS.join(", "))),
+    }
+}
+
+/// The latest workflow runs of `repo`, newest first. Public repositories need no token.
+pub fn fetch_github(repo: &str, branch: Option<&str>, workflow: Option<&str>, token: Option<&str>) -> Result<Vec<FetchedRun>, String> {
+    let url = match workflow {
+        Some(workflow) => format!("{}/repos/{}/actions/workflows/{}/runs", GITHUB_API, repo, workflow),
+        None => format!("{}/repos/{}/actions/runs", GITHUB_API, repo),
+    };
+    let mut request = ureq::get(&url)
+        .set("Accept", "application/vnd.github+json")
+        .set("User-Agent", "FlowState")
+        .query("per_page", &RUNS_PER_POLL.to_string())
+        .timeout(Duration::from_secs(30));
+    if let Some(branch) = branch {
+        request = request.query("branch", branch);
+    }
+    if let Some(token) = token {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+    let response: Value = request.call()
+        .map_err(|e| describe_error("GitHub", e))?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    let runs = response["workflow_runs"].as_array().ok_or("The GitHub response did not include workflow_runs")?;
+    Ok(runs.iter().map(github_run).collect())
+}
+
+/// The current status from a badge endpoint.
+pub fn fetch_badge(url: &str) -> Result<FetchedRun, String> {
+    let response: Value = ureq::get(url)
+        .set("User-Agent", "FlowState")
+        .timeout(Duration::from_secs(30))
+        .call()
+        .map_err(|e| describe_error("the badge endpoint", e))?
+        .into_json()
+        .map_err(|e| format!("The badge endpoint did not return JSON: {}", e))?;
+    let message = response["message"].as_str().or_else(|| response["status"].as_str()).unwrap_or_default();
+    Ok(FetchedRun {
+        external_id: None,
+        status: badge_status(message).to_string(),
+        title: response["label"].as_str().map(|l| format!("{}: {}", l, message)),
+        branch: None,
+        commit_sha: None,
+        url: None,
+        started_at: None,
+        completed_at: None,
+    })
+}
+
+/// What a badge message means: "build passing" is a success, "tests failing" a failure.
+pub fn badge_status(message: &str) -> &'static str {
+    let message = message.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+    if has(FAILING_WORDS) {
+        "failure"
+    } else if has(RUNNING_WORDS) {
+        "running"
+    } else if has(PASSING_WORDS) {
+        "success"
+    } else if message.contains("cancel") {
+        "cancelled"
+    } else {
+        "unknown"
+    }
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn github_run(run: &Value) -> FetchedRun {
+    let status = match (run["status"].as_str(), run["conclusion"].as_str()) {
+        (Some("completed"), Some("success" | "neutral" | "skipped")) => "success",
+        (Some("completed"), Some("failure" | "timed_out" | "startup_failure" | "action_required")) => "failure",
+        (Some("completed"), Some("cancelled" | "stale")) => "cancelled",
+        (Some("completed"), _) => "unknown",
+        _ => "running",
+    };
+    let time = |key: &str| run[key].as_str().and_then(|t| DateTime::parse_from_rfc3339(t).ok()).map(|t| t.with_timezone(&Utc));
+    let text = |key: &str| run[key].as_str().map(String::from);
+    FetchedRun {
+        external_id: run["id"].as_i64().map(|id| id.to_string()),
+        status: status.to_string(),
+        title: text("display_title").or_else(|| text("name")),
+        branch: text("head_branch"),
+        commit_sha: text("head_sha"),
+        url: text("html_url"),
+        started_at: time("run_started_at").or_else(|| time("created_at")),
+        completed_at: if status == "running" { None } else { time("updated_at") },
+    }
+}
+
+fn describe_error(service: &str, error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_json::<Value>().unwrap_or_default();
+            match body["message"].as_str() {
+                Some(message) => format!("{} returned {}: {}", service, code, message),
+                None => format!("{} returned {}", service, code),
+            }
+        }
+        ureq::Error::Transport(t) => format!("Failed to reach {}: {}", service, t),
+    }
+}
@@ -0,0 +1,61 @@
+// FlowState Commit Log Module - Commits read from a project's repository for import as changes
+// `git log` is run with LOG_FORMAT and --name-only: each commit starts with a record separator
+// and its fields are split by unit separators, so subjects can hold any text. The files a
+// commit touched decide which components it is filed under (see componentpaths).
+
+use chrono::{DateTime, Utc};
+
+pub const LOG_FORMAT: &str = "--format=%x1e%H%x1f%an%x1f%aI%x1f%s";
+pub const DEFAULT_MAX_COMMITS: u32 = 200;
+
+const DEPENDENCY_FILES: &[&str] = &["Cargo.toml", "Cargo.lock", "package.json", "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "go.mod", "go.sum", "requirements.txt", "pyproject.toml", "poetry.lock"];
+const DOCUMENTATION_EXTENSIONS: &[&str] = &["md", "mdx", "rst", "txt", "adoc"];
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub committed_at: DateTime<Utc>,
+    pub subject: String,
+    pub files: Vec<String>, // Relative to the repository root
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+/// Commits from `git log` output in LOG_FORMAT with --name-only, newest first as git lists them.
+pub fn parse(output: &str) -> Vec<Commit> {
+    output.split('\u{1e}')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let header: Vec<&str> = lines.next()?.split('\u{1f}').collect();
+            let [hash, author, date, subject] = header[..] else { return None };
+            let committed_at = DateTime::parse_from_rfc3339(date).ok()?.with_timezone(&Utc);
+            Some(Commit {
+                hash: hash.to_string(),
+                author: author.to_string(),
+                committed_at,
+                subject: subject.to_string(),
+                files: lines.map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// The change type for a set of files: dependency when they're all manifests or lockfiles,
+/// documentation when they're all docs, code otherwise.
+pub fn change_type(files: &[&str]) -> &'static str {
+    let name = |file: &&str| file.rsplit('/').next().unwrap_or_default().to_string();
+    if !files.is_empty() && files.iter().all(|f| DEPENDENCY_FILES.contains(&name(f).as_str())) {
+        "dependency"
+    } else if !files.is_empty() && files.iter().all(|f| {
+        f.rsplit_once('.').is_some_and(|(_, ext)| DOCUMENTATION_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+    }) {
+        "documentation"
+    } else {
+        "code"
+    }
+}
@@ -0,0 +1,66 @@
+// FlowState Component Paths Module - Which component a file in the project's repository belongs to
+// Components own globs relative to the project's repo_path (`crates/core/**`,
+// `src/auth/*.rs`). When several match, the most specific wins: the one with the most fixed
+// characters, so `crates/core/src/auth/**` takes a file from `crates/core/**`. The commit
+// importer and directory watches use this to file changes without asking.
+
+use crate::database::ComponentPath;
+use crate::dirwatch;
+use std::path::{Path, PathBuf};
+
+pub struct Attribution {
+    root: Option<PathBuf>,
+    paths: Vec<ComponentPath>,
+}
+
+impl Attribution {
+    pub fn new(repo_path: Option<&str>, paths: Vec<ComponentPath>) -> Attribution {
+        // Directory watches store canonical paths, so compare against the canonical root
+        let root = repo_path.map(|p| std::fs::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p)));
+        Attribution { root, paths }
+    }
+
+    /// The component for `path`: relative paths are taken as they are, absolute ones relative
+    /// to the repository, and files outside it belong to none.
+    pub fn component_for(&self, path: &str) -> Option<i64> {
+        let relative = self.relative(path)?;
+        self.paths.iter()
+            .filter(|p| dirwatch::glob_matches(&p.pattern, &relative))
+            .max_by_key(|p| (specificity(&p.pattern), std::cmp::Reverse(p.id)))
+            .map(|p| p.component_id)
+    }
+
+    fn relative(&self, path: &str) -> Option<String> {
+        let path = Path::new(path);
+        if path.is_relative() {
+            return Some(normalize(&path.to_string_lossy()));
+        }
+        let relative = path.strip_prefix(self.root.as_ref()?).ok()?;
+        Some(normalize(&relative.to_string_lossy()))
+    }
+}
+
+/// A pattern as stored: `/`-separated and relative, with a trailing `/` meaning everything
+/// under that folder. Patterns that climb out of the repository are refused.
+pub fn clean_pattern(pattern: &str) -> Result<String, String> {
+    let mut pattern = normalize(pattern.trim());
+    if pattern.is_empty() {
+        return Err("A component path needs a pattern, like src/auth/**".to_string());
+    }
+    if pattern.starts_with('/') || pattern.split('/').any(|segment| segment == "..") {
+        return Err(format!("'{}' must be relative to the repository, like src/auth/**", pattern));
+    }
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    Ok(pattern)
+}
+
+/// How many characters of the pattern are fixed rather than wildcards.
+fn specificity(pattern: &str) -> usize {
+    pattern.chars().filter(|c| !matches!(c, '*' | '?')).count()
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
@@ -0,0 +1,343 @@
+// FlowState Confluence Module - Publish project documents to Confluence
+// Each document (story, decisions, learnings) is one page in the configured space, written
+// in Confluence's storage format from the same blocks the PDF export lays out. Page ids are
+// kept in integration_links so publishing again updates the page instead of adding one, and
+// images go up as page attachments.
+
+use crate::database::{Conversation, Learning, Problem, Solution};
+use crate::pdf::Block;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const DOCUMENTS: &[&str] = &["story", "decisions", "learnings"];
+
+pub struct PageImage {
+    pub filename: String,
+    pub media_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// A page ready to publish: storage-format body plus the attachments it references.
+pub struct PageContent {
+    pub body: String,
+    pub images: Vec<PageImage>,
+}
+
+// ============================================================
+// DOCUMENTS
+// ============================================================
+
+pub fn page_title(project_name: &str, document: &str) -> String {
+    let name = match document {
+        "story" => "Project Story",
+        "decisions" => "Decisions",
+        _ => "Learnings",
+    };
+    format!("{} – {}", project_name, name)
+}
+
+/// Decisions recorded in conversations, newest first, then how each solved problem was fixed.
+pub fn decision_blocks(conversations: &[Conversation], solved: &[(Problem, Solution)]) -> Vec<Block> {
+    let mut blocks = vec![Block::Subtitle("Decisions taken while building the project, as recorded by FlowState.".to_string())];
+
+    let mut by_day: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for conversation in conversations {
+        let decisions: Vec<String> = conversation.key_decisions.as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        if !decisions.is_empty() {
+            let day = conversation.created_at.get(..10).unwrap_or(&conversation.created_at).to_string();
+            by_day.entry(day).or_default().extend(decisions);
+        }
+    }
+    if !by_day.is_empty() {
+        blocks.push(Block::Heading("Key decisions".to_string()));
+        for (day, decisions) in by_day.into_iter().rev() {
+            blocks.push(Block::Subheading(day));
+            blocks.push(Block::Bullets(decisions));
+        }
+    }
+
+    if !solved.is_empty() {
+        blocks.push(Block::Heading("How problems were resolved".to_string()));
+        for (problem, solution) in solved {
+            blocks.push(Block::Subheading(problem.title.clone()));
+            blocks.push(Block::Paragraph(solution.summary.clone()));
+            if let Some(insight) = solution.key_insight.as_deref().filter(|i| !i.trim().is_empty()) {
+                blocks.push(Block::Quote { text: insight.to_string(), cite: Some("Key insight".to_string()) });
+            }
+        }
+    }
+
+    if blocks.len() == 1 {
+        blocks.push(Block::Paragraph("No decisions recorded yet.".to_string()));
+    }
+    blocks
+}
+
+/// Learnings grouped by category, with their context.
+pub fn learning_blocks(learnings: &[Learning]) -> Vec<Block> {
+    let mut blocks = vec![Block::Stats(vec![
+        (learnings.len().to_string(), "learnings".to_string()),
+        (learnings.iter().filter(|l| l.verified).count().to_string(), "verified".to_string()),
+    ])];
+    let mut by_category: BTreeMap<String, Vec<&Learning>> = BTreeMap::new();
+    for learning in learnings {
+        let category = learning.category.as_deref().unwrap_or("other").replace('_', " ");
+        by_category.entry(category).or_default().push(learning);
+    }
+    for (category, items) in by_category {
+        let mut heading = category.clone();
+        if let Some(first) = heading.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        blocks.push(Block::Heading(heading));
+        for learning in items {
+            let mut cite = learning.created_at.get(..10).unwrap_or(&learning.created_at).to_string();
+            if learning.verified {
+                cite.push_str(" · verified");
+            }
+            blocks.push(Block::Quote { text: learning.insight.clone(), cite: Some(cite) });
+            if let Some(context) = learning.context.as_deref().filter(|c| !c.trim().is_empty()) {
+                blocks.push(Block::Paragraph(context.to_string()));
+            }
+        }
+    }
+    blocks
+}
+
+// ============================================================
+// STORAGE FORMAT
+// ============================================================
+
+/// Convert blocks to Confluence storage format (XHTML). Title blocks are dropped since
+/// Confluence shows the page title itself; images become numbered attachments.
+pub fn page_content(blocks: &[Block]) -> PageContent {
+    let mut body = String::new();
+    let mut images = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Title(_) => {}
+            Block::Subtitle(text) => body.push_str(&format!("<p><em>{}</em></p>", escape(text))),
+            Block::Heading(text) => body.push_str(&format!("<h2>{}</h2>", escape(text))),
+            Block::Subheading(text) => body.push_str(&format!("<h3>{}</h3>", escape(text))),
+            Block::Paragraph(text) => body.push_str(&paragraph(text)),
+            Block::Quote { text, cite } => {
+                body.push_str("<blockquote>");
+                body.push_str(&paragraph(text));
+                if let Some(cite) = cite {
+                    body.push_str(&format!("<p><em>{}</em></p>", escape(cite)));
+                }
+                body.push_str("</blockquote>");
+            }
+            Block::Code(code) => body.push_str(&format!(
+                "<ac:structured-macro ac:name=\"code\"><ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body></ac:structured-macro>",
+                // A CDATA section can't contain its own terminator; split it across two sections
+                code.replace("]]>", "]]]]><![CDATA[>"),
+            )),
+            Block::Bullets(items) => {
+                body.push_str("<ul>");
+                for item in items {
+                    body.push_str(&format!("<li>{}</li>", escape(item)));
+                }
+                body.push_str("</ul>");
+            }
+            Block::Stats(stats) => {
+                let headers: String = stats.iter().map(|(_, label)| format!("<th>{}</th>", escape(label))).collect();
+                let values: String = stats.iter().map(|(value, _)| format!("<td>{}</td>", escape(value))).collect();
+                body.push_str(&format!("<table><tbody><tr>{}</tr><tr>{}</tr></tbody></table>", headers, values));
+            }
+            Block::Image { data, caption } => {
+                // Only formats Confluence previews; anything else is left out as in the PDF
+                let Some((extension, media_type)) = image_type(data) else { continue };
+                let filename = format!("flowstate-image-{}.{}", images.len() + 1, extension);
+                body.push_str(&format!(
+                    "<p><ac:image ac:width=\"600\"><ri:attachment ri:filename=\"{}\" /></ac:image></p>",
+                    filename,
+                ));
+                if let Some(caption) = caption {
+                    body.push_str(&format!("<p><em>{}</em></p>", escape(caption)));
+                }
+                images.push(PageImage { filename, media_type, data: data.clone() });
+            }
+        }
+    }
+    PageContent { body, images }
+}
+
+fn paragraph(text: &str) -> String {
+    let lines: Vec<String> = text.lines().map(escape).collect();
+    format!("<p>{}</p>", lines.join("<br />"))
+}
+
+/// Storage format is XML: only the predefined entities are allowed, and no control characters.
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || *c == '\t')
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn image_type(data: &[u8]) -> Option<(&'static str, &'static str)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("png", "image/png"))
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(("jpg", "image/jpeg"))
+    } else {
+        None
+    }
+}
+
+// ============================================================
+// API CLIENT
+// ============================================================
+
+pub enum PageUpdate {
+    Updated,
+    Missing, // Page was deleted on the Confluence side
+}
+
+pub struct ConfluenceClient {
+    base_url: String,
+    authorization: String,
+}
+
+impl ConfluenceClient {
+    /// `base_url` is the wiki root, e.g. https://acme.atlassian.net/wiki. With an email the
+    /// token is a Cloud API token (basic auth); without one, a Server/Data Center personal
+    /// access token (bearer).
+    pub fn new(base_url: &str, email: Option<&str>, token: &str) -> Self {
+        let authorization = match email {
+            Some(email) => format!("Basic {}", STANDARD.encode(format!("{}:{}", email, token))),
+            None => format!("Bearer {}", token),
+        };
+        ConfluenceClient { base_url: base_url.trim_end_matches('/').to_string(), authorization }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        ureq::request(method, &format!("{}/rest/api{}", self.base_url, path))
+            .set("Authorization", &self.authorization)
+            .set("Accept", "application/json")
+            .timeout(Duration::from_secs(60))
+    }
+
+    /// Id of the page with this title in the space, if there is one.
+    pub fn find_page(&self, space_key: &str, title: &str) -> Result<Option<String>, String> {
+        let response: Value = self.request("GET", "/content")
+            .query("spaceKey", space_key)
+            .query("title", title)
+            .query("type", "page")
+            .call()
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        Ok(response["results"].get(0).and_then(|page| page["id"].as_str()).map(|id| id.to_string()))
+    }
+
+    pub fn create_page(&self, space_key: &str, parent_id: Option<&str>, title: &str, body: &str) -> Result<String, String> {
+        let mut page = json!({
+            "type": "page",
+            "title": title,
+            "space": { "key": space_key },
+            "body": { "storage": { "value": body, "representation": "storage" } },
+        });
+        if let Some(parent_id) = parent_id {
+            page["ancestors"] = json!([{ "id": parent_id }]);
+        }
+        let response: Value = self.request("POST", "/content")
+            .send_json(page)
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        response["id"].as_str()
+            .map(|id| id.to_string())
+            .ok_or_else(|| "Confluence response did not include a page id".to_string())
+    }
+
+    /// Replace a page's title and body. Confluence wants the next version number, so the
+    /// current one is read first.
+    pub fn update_page(&self, page_id: &str, title: &str, body: &str) -> Result<PageUpdate, String> {
+        let current: Value = match self.request("GET", &format!("/content/{}", page_id)).query("expand", "version").call() {
+            Ok(response) => response.into_json().map_err(|e| e.to_string())?,
+            Err(ureq::Error::Status(404, _)) => return Ok(PageUpdate::Missing),
+            Err(e) => return Err(describe_error(e)),
+        };
+        if current["status"].as_str().is_some_and(|status| status != "current") {
+            return Ok(PageUpdate::Missing); // In the trash
+        }
+        let version = current["version"]["number"].as_i64().unwrap_or(1);
+        self.request("PUT", &format!("/content/{}", page_id))
+            .send_json(json!({
+                "type": "page",
+                "title": title,
+                "version": { "number": version + 1, "message": "Published from FlowState" },
+                "body": { "storage": { "value": body, "representation": "storage" } },
+            }))
+            .map_err(describe_error)?;
+        Ok(PageUpdate::Updated)
+    }
+
+    /// Create or update a document page and return (page id, created). The linked page is
+    /// updated in place; without one (first publish, or it was deleted) a page already titled
+    /// like ours is adopted, so publishing never duplicates a page.
+    pub fn publish_page(
+        &self,
+        space_key: &str,
+        parent_id: Option<&str>,
+        known_page_id: Option<&str>,
+        title: &str,
+        body: &str,
+    ) -> Result<(String, bool), String> {
+        if let Some(page_id) = known_page_id {
+            if let PageUpdate::Updated = self.update_page(page_id, title, body)? {
+                return Ok((page_id.to_string(), false));
+            }
+        }
+        match self.find_page(space_key, title)? {
+            Some(page_id) => match self.update_page(&page_id, title, body)? {
+                PageUpdate::Updated => Ok((page_id, false)),
+                PageUpdate::Missing => Err(format!("Confluence page {} disappeared while publishing", page_id)),
+            },
+            None => Ok((self.create_page(space_key, parent_id, title, body)?, true)),
+        }
+    }
+
+    /// Add the attachment, or a new version of it if the page already has one by that name.
+    pub fn upload_attachment(&self, page_id: &str, image: &PageImage) -> Result<(), String> {
+        let boundary = format!("flowstate-{}", uuid::Uuid::new_v4().simple());
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            image.filename,
+            image.media_type,
+        ).into_bytes();
+        body.extend_from_slice(&image.data);
+        body.extend_from_slice(format!(
+            "\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"minorEdit\"\r\n\r\ntrue\r\n--{boundary}--\r\n"
+        ).as_bytes());
+
+        self.request("PUT", &format!("/content/{}/child/attachment", page_id))
+            .set("Content-Type", &format!("multipart/form-data; boundary={}", boundary))
+            // Attachment uploads are rejected without this (XSRF protection)
+            .set("X-Atlassian-Token", "no-check")
+            .send_bytes(&body)
+            .map_err(describe_error)?;
+        Ok(())
+    }
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let message = response.into_json::<Value>().ok()
+                .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(|m| m.to_string()))
+                .unwrap_or_default();
+            format!("Confluence API error {}: {}", code, message)
+        }
+        ureq::Error::Transport(t) => format!("Failed to reach Confluence: {}", t),
+    }
+}
@@ -0,0 +1,158 @@
+// FlowState Context Module - Shape project contexts for the frontend and AI sessions
+// Selection: a view asks only for the sections it shows, each with an optional limit.
+// Merging: for sessions that span related projects, every item is labelled with its
+// project, learnings shared between projects appear once, and an optional token budget
+// trims the least important sections first.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Selectable sections of a project context. The project itself is always included.
+pub const CONTEXT_FIELDS: &[&str] = &["components", "problems", "changes", "todos", "learnings", "notes", "references", "ci", "starred", "attachments"];
+
+/// Which sections to load, and how many items each may keep.
+pub struct Selection {
+    fields: Vec<String>,
+    limits: HashMap<String, usize>,
+}
+
+impl Selection {
+    /// Every section; attachments only when `include_files` is set.
+    pub fn all(include_files: bool) -> Self {
+        let fields = CONTEXT_FIELDS
+            .iter()
+            .filter(|f| include_files || **f != "attachments")
+            .map(|f| f.to_string())
+            .collect();
+        Selection { fields, limits: HashMap::new() }
+    }
+
+    /// `fields` of None selects everything (as `all`); unknown field names are an error.
+    pub fn new(fields: Option<Vec<String>>, limits: Option<HashMap<String, usize>>, include_files: bool) -> Result<Self, String> {
+        let mut selection = match fields {
+            Some(fields) => Selection { fields, limits: HashMap::new() },
+            None => Self::all(include_files),
+        };
+        selection.limits = limits.unwrap_or_default();
+        for field in selection.fields.iter().chain(selection.limits.keys()) {
+            if !CONTEXT_FIELDS.contains(&field.as_str()) {
+                return Err(format!("Unknown context field '{}' (expected one of: {})", field, CONTEXT_FIELDS.join(", ")));
+            }
+        }
+        Ok(selection)
+    }
+
+    pub fn wants(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f == field)
+    }
+
+    /// Cut a section down to its limit, if it has one.
+    pub fn limit<T>(&self, field: &str, mut items: Vec<T>) -> Vec<T> {
+        if let Some(limit) = self.limits.get(field) {
+            items.truncate(*limit);
+        }
+        items
+    }
+}
+
+// Sections merged across projects, most important first; the budget trims from the end
+const SECTIONS: &[&str] = &["starred", "open_problems", "high_priority_todos", "recent_learnings", "notes", "recent_changes"];
+
+// Rough size of a token in serialized JSON; good enough to keep a prompt in bounds
+pub const CHARS_PER_TOKEN: usize = 4;
+
+pub fn estimate_tokens(value: &Value) -> usize {
+    value.to_string().len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Merge per-project contexts (the shape `get_project_context` returns).
+pub fn merge(contexts: &[Value], token_budget: Option<usize>) -> Value {
+    let mut projects = Vec::new();
+    let mut sections: Vec<(&str, Vec<Value>)> = SECTIONS.iter().map(|s| (*s, Vec::new())).collect();
+
+    for context in contexts {
+        let name = context["project"]["name"].as_str().unwrap_or_default().to_string();
+        projects.push(json!({
+            "project": context["project"],
+            "components": context["components"],
+            "ci_status": context["ci_status"],
+        }));
+
+        for (section, items) in sections.iter_mut() {
+            let Some(list) = context[*section].as_array() else { continue };
+            for item in list {
+                if *section == "recent_learnings" {
+                    if let Some(existing) = items.iter_mut().find(|l| same_learning(l, item)) {
+                        add_project_label(existing, &name);
+                        continue;
+                    }
+                }
+                items.push(labelled(item, &name));
+            }
+        }
+    }
+
+    let mut merged = Map::new();
+    merged.insert("projects".to_string(), Value::Array(projects));
+    let mut used = estimate_tokens(&Value::Object(merged.clone()));
+    let mut omitted = Map::new();
+
+    for (section, items) in sections {
+        let mut kept = Vec::new();
+        let total = items.len();
+        for item in items {
+            let cost = estimate_tokens(&item) + 1;
+            if token_budget.is_some_and(|budget| used + cost > budget) {
+                break;
+            }
+            used += cost;
+            kept.push(item);
+        }
+        if kept.len() < total {
+            omitted.insert(section.to_string(), json!(total - kept.len()));
+        }
+        merged.insert(section.to_string(), Value::Array(kept));
+    }
+
+    merged.insert("estimated_tokens".to_string(), json!(used));
+    merged.insert("truncated".to_string(), json!(!omitted.is_empty()));
+    merged.insert("omitted".to_string(), Value::Object(omitted));
+    Value::Object(merged)
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn labelled(item: &Value, project: &str) -> Value {
+    let mut item = item.clone();
+    if let Some(obj) = item.as_object_mut() {
+        obj.insert("project".to_string(), json!(project));
+        obj.insert("projects".to_string(), json!([project]));
+    }
+    item
+}
+
+fn add_project_label(item: &mut Value, project: &str) {
+    if let Some(list) = item["projects"].as_array_mut() {
+        if !list.iter().any(|p| p == project) {
+            list.push(json!(project));
+        }
+    }
+}
+
+/// Learnings match when their insight text is the same ignoring case, spacing and punctuation.
+fn same_learning(a: &Value, b: &Value) -> bool {
+    let normalize = |v: &Value| -> String {
+        v["insight"]
+            .as_str()
+            .unwrap_or_default()
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let key = normalize(a);
+    !key.is_empty() && key == normalize(b)
+}
@@ -0,0 +1,113 @@
+// FlowState Contradictions Module - Spot learnings that disagree with verified ones
+// "Always pool connections" vs "Never pool connections": same topic, opposite claim.
+// Similarity is word overlap after dropping stopwords and polarity words; a conflict
+// needs enough overlap plus opposite polarity (negation or an antonym pair).
+
+use crate::database::Learning;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const MIN_SIMILARITY: f64 = 0.3;
+
+pub const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "if", "then", "of", "to", "in", "on", "for", "with", "by",
+    "at", "from", "as", "is", "are", "was", "were", "be", "been", "it", "its", "this", "that", "these",
+    "those", "you", "we", "they", "i", "when", "so", "can", "will", "should", "must", "do", "does",
+];
+
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "dont", "don't", "doesnt", "doesn't", "isnt", "isn't", "shouldnt",
+    "shouldn't", "cant", "can't", "cannot", "wont", "won't", "avoid", "without",
+];
+
+// Each pair reads as opposite claims when one side uses the first word and the other the second
+const ANTONYMS: &[(&str, &str)] = &[
+    ("always", "never"),
+    ("use", "avoid"),
+    ("enable", "disable"),
+    ("enabled", "disabled"),
+    ("faster", "slower"),
+    ("safe", "unsafe"),
+    ("works", "fails"),
+    ("required", "optional"),
+    ("sync", "async"),
+    ("before", "after"),
+    ("increase", "decrease"),
+    ("more", "less"),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LearningConflict {
+    pub learning: Learning,
+    pub similarity: f64,
+    pub reason: String,
+}
+
+/// Compare an insight against existing learnings, strongest conflict first.
+/// Only verified learnings that nothing has superseded are considered.
+pub fn find_conflicts(insight: &str, existing: &[Learning]) -> Vec<LearningConflict> {
+    let superseded: HashSet<i64> = existing.iter().filter_map(|l| l.supersedes_learning_id).collect();
+    let words = tokenize(insight);
+    let topic = topic_words(&words);
+
+    let mut conflicts: Vec<LearningConflict> = existing
+        .iter()
+        .filter(|l| l.verified && !superseded.contains(&l.id))
+        .filter_map(|learning| {
+            let other = tokenize(&learning.insight);
+            let similarity = jaccard(&topic, &topic_words(&other));
+            if similarity < MIN_SIMILARITY {
+                return None;
+            }
+            let reason = opposition(&words, &other)?;
+            Some(LearningConflict { learning: learning.clone(), similarity, reason })
+        })
+        .collect();
+
+    conflicts.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+    conflicts
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.trim_matches('\'').to_string())
+        .collect()
+}
+
+/// Words that describe what the claim is about, without the words that flip it.
+fn topic_words(words: &[String]) -> HashSet<String> {
+    words
+        .iter()
+        .filter(|w| w.len() > 1)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()) && !NEGATIONS.contains(&w.as_str()))
+        .filter(|w| !ANTONYMS.iter().any(|(a, b)| a == w || b == w))
+        .cloned()
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / a.union(b).count() as f64
+}
+
+fn opposition(a: &[String], b: &[String]) -> Option<String> {
+    let has = |words: &[String], w: &str| words.iter().any(|x| x == w);
+    for (left, right) in ANTONYMS {
+        if (has(a, left) && has(b, right) && !has(a, right)) || (has(a, right) && has(b, left) && !has(a, left)) {
+            return Some(format!("'{}' vs '{}'", left, right));
+        }
+    }
+    let negated = |words: &[String]| words.iter().filter(|w| NEGATIONS.contains(&w.as_str())).count() % 2 == 1;
+    if negated(a) != negated(b) {
+        return Some("one claim negates the other".to_string());
+    }
+    None
+}
@@ -0,0 +1,262 @@
+// FlowState Corpus Module - Plain-text export of a project for retrieval pipelines
+// One document per problem (with its attempts and solution) and per attachment, plus the
+// learnings together, each cleaned up and cut into files of at most a given number of
+// tokens. Names are predictable (problems/00042-login-times-out-1.txt) so a re-export
+// replaces the same files, and manifest.json says which record each file came from.
+
+use crate::context::CHARS_PER_TOKEN;
+use crate::database::{Attachment, Extraction, Learning, Problem, Project, Solution, SolutionAttempt};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST: &str = "manifest.json";
+
+/// Attachment types whose file contents go into the corpus as they are.
+pub const TEXT_TYPES: &[&str] = &[
+    "txt", "md", "markdown", "json", "csv", "log", "swift", "rs", "py", "js", "ts", "tsx", "jsx", "go",
+    "html", "css", "sql", "yaml", "yml", "toml", "xml",
+];
+
+pub struct CorpusProblem {
+    pub problem: Problem,
+    pub component_name: String,
+    pub attempts: Vec<SolutionAttempt>,
+    pub solution: Option<Solution>,
+}
+
+pub struct CorpusAttachment {
+    pub attachment: Attachment,
+    pub extractions: Vec<Extraction>,
+    pub text: Option<String>, // The file itself, for TEXT_TYPES that could be read
+}
+
+pub struct CorpusSource {
+    pub project: Project,
+    pub problems: Vec<CorpusProblem>,
+    pub learnings: Vec<(Learning, Option<String>)>, // With the component's name
+    pub attachments: Vec<CorpusAttachment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorpusFile {
+    pub path: String, // Relative to the corpus directory
+    pub entity_type: String, // problem, learnings or attachment
+    pub entity_id: Option<i64>, // None for the learnings, which share their files
+    pub part: usize,
+    pub parts: usize,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub project: String,
+    pub exported_at: String,
+    pub max_tokens_per_file: usize,
+    pub files: Vec<CorpusFile>,
+}
+
+/// Every file of the corpus as (manifest entry, contents); the manifest itself is left to
+/// the caller.
+pub fn build(source: &CorpusSource, max_tokens: usize) -> Vec<(CorpusFile, String)> {
+    let max_chars = max_tokens * CHARS_PER_TOKEN;
+    let mut files = Vec::new();
+    for item in &source.problems {
+        let base = file_base("problems", item.problem.id, &item.problem.title);
+        let title = format!("Problem #{}: {}", item.problem.id, item.problem.title);
+        files.extend(chunked(&base, "problem", Some(item.problem.id), &title, &problem_text(&source.project, item), max_chars));
+    }
+    if !source.learnings.is_empty() {
+        let text = source.learnings.iter().map(|(learning, component)| learning_text(learning, component.as_deref()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let title = format!("Learnings of {}", source.project.name);
+        files.extend(chunked("learnings/learnings", "learnings", None, &title, &text, max_chars));
+    }
+    for item in &source.attachments {
+        let text = attachment_text(item);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let base = file_base("attachments", item.attachment.id, &item.attachment.file_name);
+        let title = format!("Attachment: {}", item.attachment.file_name);
+        files.extend(chunked(&base, "attachment", Some(item.attachment.id), &title, &text, max_chars));
+    }
+    files
+}
+
+// ============================================================
+// DOCUMENTS
+// ============================================================
+
+fn problem_text(project: &Project, item: &CorpusProblem) -> String {
+    let problem = &item.problem;
+    let mut text = format!(
+        "Project: {}\nComponent: {}\nStatus: {}\nSeverity: {}\n",
+        project.name, item.component_name, problem.status, problem.severity,
+    );
+    section(&mut text, "Description", problem.description.as_deref());
+    section(&mut text, "Root cause", problem.root_cause.as_deref());
+    for (i, attempt) in item.attempts.iter().enumerate() {
+        let heading = format!("Attempt {} ({})", i + 1, attempt.outcome.as_deref().unwrap_or("untried"));
+        let body = match attempt.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            Some(notes) => format!("{}\n\n{}", attempt.description, notes),
+            None => attempt.description.clone(),
+        };
+        section(&mut text, &heading, Some(&body));
+    }
+    if let Some(solution) = &item.solution {
+        section(&mut text, "Solution", Some(&solution.summary));
+        section(&mut text, "Key insight", solution.key_insight.as_deref());
+        section(&mut text, "Code", solution.code_snippet.as_deref());
+    }
+    text
+}
+
+fn learning_text(learning: &Learning, component: Option<&str>) -> String {
+    let mut labels = vec![learning.category.clone().unwrap_or_else(|| "general".to_string())];
+    labels.extend(component.map(|c| c.to_string()));
+    if learning.verified {
+        labels.push("verified".to_string());
+    }
+    let mut text = format!("Learning #{} [{}]\n{}", learning.id, labels.join(", "), learning.insight.trim());
+    if let Some(context) = learning.context.as_deref().filter(|c| !c.trim().is_empty()) {
+        text.push_str(&format!("\nContext: {}", context.trim()));
+    }
+    text
+}
+
+fn attachment_text(item: &CorpusAttachment) -> String {
+    let attachment = &item.attachment;
+    let mut text = format!("File: {} ({})\n", attachment.file_name, attachment.file_type);
+    section(&mut text, "Description", attachment.user_description.as_deref().or(attachment.ai_description.as_deref()));
+    section(&mut text, "Summary", attachment.ai_summary.as_deref());
+    let snippets: Vec<String> = item.extractions.iter()
+        .filter_map(|e| e.source_snippet.as_deref().map(|s| match &e.source_location {
+            Some(location) => format!("({}) {}", location, s.trim()),
+            None => s.trim().to_string(),
+        }))
+        .collect();
+    if !snippets.is_empty() {
+        section(&mut text, "Extracted", Some(&snippets.join("\n\n")));
+    }
+    section(&mut text, "Contents", item.text.as_deref());
+    // Nothing but the file line means there's nothing to retrieve
+    if text.lines().count() <= 1 { String::new() } else { text }
+}
+
+fn section(text: &mut String, heading: &str, body: Option<&str>) {
+    if let Some(body) = body.map(clean).filter(|b| !b.is_empty()) {
+        text.push_str(&format!("\n## {}\n\n{}\n", heading, body));
+    }
+}
+
+// ============================================================
+// CLEANING AND CHUNKING
+// ============================================================
+
+/// Unix line ends, no control characters or trailing spaces, at most one blank line in a row.
+fn clean(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut out = String::new();
+    let mut blank = false;
+    for line in text.lines() {
+        let line: String = line.chars().filter(|c| !c.is_control() || *c == '\t').collect();
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Cut a document into files of at most `max_chars`, each headed with its title so a chunk
+/// retrieved on its own still says where it's from.
+fn chunked(base: &str, entity_type: &str, entity_id: Option<i64>, title: &str, text: &str, max_chars: usize) -> Vec<(CorpusFile, String)> {
+    let parts = split(&clean(text), max_chars.saturating_sub(title.len() + 24).max(CHARS_PER_TOKEN * 16));
+    let count = parts.len();
+    parts.into_iter().enumerate().map(|(i, part)| {
+        let heading = if count > 1 { format!("# {} (part {} of {})", title, i + 1, count) } else { format!("# {}", title) };
+        let contents = format!("{}\n\n{}\n", heading, part);
+        let file = CorpusFile {
+            path: format!("{}-{}.txt", base, i + 1),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            part: i + 1,
+            parts: count,
+            tokens: contents.len().div_ceil(CHARS_PER_TOKEN),
+        };
+        (file, contents)
+    }).collect()
+}
+
+/// Pieces of at most `max` bytes, broken between paragraphs where possible, then between
+/// lines, then anywhere.
+fn split(text: &str, max: usize) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        let pieces = if paragraph.len() > max { split_long(paragraph, max) } else { vec![paragraph.to_string()] };
+        for piece in pieces {
+            if !current.is_empty() && current.len() + 2 + piece.len() > max {
+                parts.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+        }
+    }
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn split_long(paragraph: &str, max: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for line in paragraph.lines() {
+        let mut line = line;
+        if line.len() > max && !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+        }
+        while line.len() > max {
+            let mut cut = max;
+            while !line.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            pieces.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if !current.is_empty() && current.len() + 1 + line.len() > max {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// "problems/00042-login-times-out": the id keeps names unique, the slug readable.
+fn file_base(dir: &str, id: i64, label: &str) -> String {
+    match slug(label) {
+        slug if slug.is_empty() => format!("{}/{:05}", dir, id),
+        slug => format!("{}/{:05}-{}", dir, id, slug),
+    }
+}
+
+fn slug(text: &str) -> String {
+    let slug = text.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    slug.chars().take(48).collect::<String>().trim_end_matches('-').to_string()
+}
@@ -1,9 +1,14 @@
 // FlowState Database Module - SQLite operations for Tauri
 // v1.1: Added Attachments, ContentLocations, Extractions, SyncStatus, Settings
 
-use rusqlite::{Connection, Result, params};
+use crate::query::Query;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags, Result, params, params_from_iter};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 
 // ============================================================
 // v1.0 DATA TYPES
@@ -17,6 +22,20 @@ pub struct Project {
     pub status: String,
     pub created_at: String,
     pub updated_at: String,
+    pub color: Option<String>, // v1.4
+    pub icon: Option<String>,
+    pub pinned: bool,
+    pub sort_order: i64,
+    #[serde(default)]
+    pub sync_excluded: bool, // v1.4: kept out of sync; git sync refuses to run while any project is
+    #[serde(default = "default_attachment_sync")]
+    pub attachment_sync: String, // v1.4: full, metadata, never; attachments can override it
+    #[serde(default)]
+    pub repo_path: Option<String>, // v1.4: local checkout of the code; component paths are relative to it
+}
+
+fn default_attachment_sync() -> String {
+    "full".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,6 +61,10 @@ pub struct Problem {
     pub root_cause: Option<String>,
     pub created_at: String,
     pub solved_at: Option<String>,
+    pub assignee_id: Option<i64>, // v1.4
+    #[serde(default)]
+    pub comment_count: i64, // v1.4
+    pub author: Option<String>, // v1.4: who logged it
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +77,11 @@ pub struct SolutionAttempt {
     pub confidence: String,
     pub notes: Option<String>,
     pub created_at: String,
+    #[serde(default)]
+    pub comment_count: i64, // v1.4
+    pub started_at: Option<String>, // v1.4
+    pub ended_at: Option<String>,
+    pub time_spent_minutes: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -79,6 +107,14 @@ pub struct Todo {
     pub due_date: Option<String>,
     pub created_at: String,
     pub completed_at: Option<String>,
+    pub assignee_id: Option<i64>, // v1.4
+    #[serde(default)]
+    pub comment_count: i64, // v1.4
+    pub author: Option<String>, // v1.4: who added it
+    pub estimate_hours: Option<f64>, // v1.4
+    pub estimate_points: Option<f64>, // v1.4
+    pub started_at: Option<String>, // v1.4: first moved to in_progress
+    pub time_spent_minutes: Option<i64>, // v1.4
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -92,6 +128,13 @@ pub struct Learning {
     pub source: String,
     pub verified: bool,
     pub created_at: String,
+    pub ease: f64, // v1.4: spaced repetition
+    pub review_interval_days: i64,
+    pub review_repetitions: i64,
+    pub next_review_at: Option<String>,
+    pub last_reviewed_at: Option<String>,
+    pub supersedes_learning_id: Option<i64>, // v1.4
+    pub author: Option<String>, // v1.4: who added it
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -104,6 +147,18 @@ pub struct Change {
     pub change_type: String,
     pub reason: Option<String>,
     pub created_at: String,
+    pub author: Option<String>, // v1.4: who logged it
+}
+
+/// v1.4: A change waiting to be written, as sent by batch loggers like editor plugins.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewChange {
+    pub component_id: i64,
+    pub field_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub change_type: Option<String>, // Defaults to "code"
+    pub reason: Option<String>,
 }
 
 // ============================================================
@@ -134,6 +189,9 @@ pub struct Attachment {
     pub created_at: String,
     pub updated_at: String,
     pub indexed_at: Option<String>,
+    pub preview_metadata: Option<String>, // v1.4: JSON object, e.g. {"kind": "image", "width": 800, ...}
+    #[serde(default)]
+    pub sync_policy: Option<String>, // v1.4: None follows the project's attachment_sync
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -155,6 +213,18 @@ pub struct ContentLocation {
     pub related_learning_id: Option<i64>,
     pub related_component_id: Option<i64>,
     pub created_at: String,
+    pub indexer: Option<String>, // v1.4: 'sections' or 'symbols' when generated; None when added by hand/AI
+}
+
+/// v1.4: A location found by a structural indexer, before it's stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexedLocation {
+    pub description: String,
+    pub category: String,
+    pub location_type: String,
+    pub start_location: String,
+    pub end_location: Option<String>,
+    pub snippet: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -202,6 +272,7 @@ pub struct SyncHistory {
     pub status: Option<String>,
     pub error_message: Option<String>,
     pub created_at: String,
+    pub channel: String, // v1.4: "git", or "lan" for records exchanged directly with a peer
 }
 
 // ============================================================
@@ -293,1278 +364,8674 @@ pub struct CrossReference {
 }
 
 // ============================================================
-// DATABASE
+// v1.4 DATA TYPES: SEVERITY SLAS
 // ============================================================
 
-pub struct Database {
-    conn: Connection,
+/// Maximum open age per severity, in hours. A missing value means that severity never goes stale.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlaThresholds {
+    pub critical: Option<f64>,
+    pub high: Option<f64>,
+    pub medium: Option<f64>,
+    pub low: Option<f64>,
 }
 
-impl Database {
-    pub fn new(path: PathBuf) -> Result<Self> {
-        let conn = Connection::open(&path)?;
-        let db = Database { conn };
-        db.init()?;
-        Ok(db)
+impl Default for SlaThresholds {
+    fn default() -> Self {
+        SlaThresholds {
+            critical: Some(24.0),
+            high: Some(72.0),
+            medium: Some(24.0 * 14.0),
+            low: Some(24.0 * 30.0),
+        }
     }
+}
 
-    fn init(&self) -> Result<()> {
-        // Create tables if they don't exist
-        self.conn.execute_batch(include_str!("../../../database/schema.sql"))?;
-        Ok(())
+impl SlaThresholds {
+    pub fn for_severity(&self, severity: &str) -> Option<f64> {
+        match severity {
+            "critical" => self.critical,
+            "high" => self.high,
+            "medium" => self.medium,
+            "low" => self.low,
+            _ => None,
+        }
     }
+}
 
-    // ============================================================
-    // PROJECT OPERATIONS
-    // ============================================================
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaleProblem {
+    pub problem: Problem,
+    pub age_hours: f64,
+    pub threshold_hours: f64,
+    pub overdue_hours: f64,
+    pub escalated: bool,
+    pub escalated_at: Option<String>,
+}
 
-    pub fn list_projects(&self, status: Option<&str>) -> Result<Vec<Project>> {
-        let sql = match status {
-            Some(_) => "SELECT id, name, description, status, created_at, updated_at 
-                        FROM projects WHERE status = ? ORDER BY updated_at DESC",
-            None => "SELECT id, name, description, status, created_at, updated_at 
-                     FROM projects ORDER BY updated_at DESC",
-        };
-        
-        let mut stmt = self.conn.prepare(sql)?;
-        
-        let projects = match status {
-            Some(s) => stmt.query_map(params![s], Self::row_to_project)?,
-            None => stmt.query_map([], Self::row_to_project)?,
-        }.collect::<Result<Vec<_>>>()?;
+// ============================================================
+// v1.4 DATA TYPES: PROBLEM BOARD
+// ============================================================
 
-        Ok(projects)
-    }
+/// Ways to split the problem board into swimlanes.
+pub const BOARD_GROUPINGS: &[&str] = &["severity", "component", "age"];
 
-    fn row_to_project(row: &rusqlite::Row) -> rusqlite::Result<Project> {
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            status: row.get(3)?,
-            created_at: row.get(4)?,
-            updated_at: row.get(5)?,
-        })
-    }
+/// Severities, most severe first, with their lane labels.
+const SEVERITY_LANES: &[(&str, &str)] = &[("critical", "Critical"), ("high", "High"), ("medium", "Medium"), ("low", "Low")];
 
-    pub fn create_project(&self, name: &str, description: Option<&str>) -> Result<Project> {
-        self.conn.execute(
-            "INSERT INTO projects (name, description) VALUES (?, ?)",
-            params![name, description],
-        )?;
-        self.get_project(self.conn.last_insert_rowid())
-    }
+/// Open age buckets for the "age" grouping: (key, label, upper bound in days).
+const AGE_LANES: &[(&str, &str, f64)] = &[
+    ("week", "Under a week", 7.0),
+    ("month", "1-4 weeks", 30.0),
+    ("quarter", "1-3 months", 90.0),
+    ("older", "Over 3 months", f64::INFINITY),
+];
 
-    pub fn get_project(&self, id: i64) -> Result<Project> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, status, created_at, updated_at FROM projects WHERE id = ?"
-        )?;
-        stmt.query_row(params![id], Self::row_to_project)
-    }
+/// Most open problems a lane should hold, by grouping then lane key (a severity, component
+/// name or age bucket). Lanes without a limit have none.
+pub type WipLimits = BTreeMap<String, BTreeMap<String, i64>>;
 
-    pub fn get_project_by_name(&self, name: &str) -> Result<Project> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, status, created_at, updated_at FROM projects WHERE name = ?"
-        )?;
-        stmt.query_row(params![name], Self::row_to_project)
-    }
+#[derive(Debug, Serialize, Clone)]
+pub struct ProblemLane {
+    pub key: String,
+    pub label: String,
+    pub count: usize,
+    pub wip_limit: Option<i64>,
+    pub over_limit: bool,
+    pub problems: Vec<Problem>, // Most severe first, then oldest
+}
 
-    pub fn update_project(&self, id: i64, name: Option<&str>, description: Option<&str>, status: Option<&str>) -> Result<Project> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if let Some(n) = name {
-            updates.push("name = ?");
-            values.push(Box::new(n.to_string()));
-        }
-        if let Some(d) = description {
-            updates.push("description = ?");
-            values.push(Box::new(d.to_string()));
-        }
-        if let Some(s) = status {
-            updates.push("status = ?");
-            values.push(Box::new(s.to_string()));
-        }
-        
-        if updates.is_empty() {
-            return self.get_project(id);
-        }
-        
-        updates.push("updated_at = CURRENT_TIMESTAMP");
-        values.push(Box::new(id));
-        
-        let sql = format!("UPDATE projects SET {} WHERE id = ?", updates.join(", "));
-        
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_project(id)
-    }
+#[derive(Debug, Serialize, Clone)]
+pub struct ProblemBoard {
+    pub project_id: i64,
+    pub group_by: String,
+    pub total: usize,
+    pub lanes: Vec<ProblemLane>,
+    pub warnings: Vec<String>, // One per lane over its WIP limit
+}
 
-    pub fn delete_project(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM projects WHERE id = ?", params![id])?;
-        Ok(())
+// ============================================================
+// v1.4 DATA TYPES: DASHBOARDS
+// ============================================================
+
+/// Numbers a stat card can show: the fields of get_project_stats.
+pub const DASHBOARD_STATS: &[&str] = &[
+    "component_count",
+    "open_problems",
+    "solved_problems",
+    "pending_todos",
+    "learning_count",
+    "due_reviews",
+    "recent_changes",
+    "attachment_count",
+];
+
+/// Records a list widget can show.
+pub const DASHBOARD_LISTS: &[&str] = &["problems", "todos", "learnings", "changes", "alerts"];
+
+/// Series a chart widget can plot, each a list of labelled counts.
+pub const DASHBOARD_CHARTS: &[&str] = &[
+    "problems_by_severity",
+    "problems_by_status",
+    "todos_by_status",
+    "todos_by_priority",
+    "learnings_by_category",
+    "changes_per_day",
+];
+
+const MAX_WIDGET_ROWS: i64 = 100;
+const MAX_CHART_DAYS: i64 = 365;
+
+/// One widget of a dashboard layout, stored as JSON with a `type` of stat, list or chart.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DashboardWidget {
+    Stat {
+        title: Option<String>,
+        metric: String, // One of DASHBOARD_STATS
+    },
+    List {
+        title: Option<String>,
+        entity: String, // One of DASHBOARD_LISTS
+        #[serde(default)]
+        filters: WidgetFilters,
+        limit: Option<i64>, // Default 10
+    },
+    Chart {
+        title: Option<String>,
+        series: String, // One of DASHBOARD_CHARTS
+        days: Option<i64>, // changes_per_day only; default 14
+    },
+}
+
+/// Narrows a list widget. Filters that don't apply to its records are ignored.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WidgetFilters {
+    pub status: Option<String>,
+    pub severity: Option<String>, // Problems
+    pub priority: Option<String>, // Todos
+    pub category: Option<String>, // Learnings
+    pub component_id: Option<i64>,
+}
+
+impl DashboardWidget {
+    /// Reject widgets that could never be evaluated, so a bad one isn't saved.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let known = |kind: &str, value: &str, allowed: &[&str]| {
+            if allowed.contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("Unknown {} '{}'. Expected one of: {}", kind, value, allowed.join(", ")))
+            }
+        };
+        match self {
+            DashboardWidget::Stat { metric, .. } => known("stat", metric, DASHBOARD_STATS),
+            DashboardWidget::List { entity, limit, .. } => {
+                known("list", entity, DASHBOARD_LISTS)?;
+                match limit {
+                    Some(limit) if !(1..=MAX_WIDGET_ROWS).contains(limit) => {
+                        Err(format!("A list shows 1 to {} records", MAX_WIDGET_ROWS))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            DashboardWidget::Chart { series, days, .. } => {
+                known("chart", series, DASHBOARD_CHARTS)?;
+                match days {
+                    Some(days) if !(1..=MAX_CHART_DAYS).contains(days) => {
+                        Err(format!("A chart covers 1 to {} days", MAX_CHART_DAYS))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
     }
+}
 
-    // ============================================================
-    // COMPONENT OPERATIONS
-    // ============================================================
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DashboardLayout {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: String,
+    pub widgets: Vec<DashboardWidget>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub author: Option<String>,
+}
 
-    pub fn list_components(&self, project_id: i64) -> Result<Vec<Component>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, parent_component_id, name, description, status, created_at, updated_at 
-             FROM components WHERE project_id = ? ORDER BY name"
-        )?;
+/// A widget with what it shows: records for a list, `{label, value}` points for a chart,
+/// `{value}` for a stat. A widget whose query failed carries the error instead.
+#[derive(Debug, Serialize, Clone)]
+pub struct WidgetData {
+    pub widget: DashboardWidget,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
 
-        let components = stmt.query_map(params![project_id], Self::row_to_component)?
-            .collect::<Result<Vec<_>>>()?;
+#[derive(Debug, Serialize, Clone)]
+pub struct DashboardData {
+    pub layout: DashboardLayout,
+    pub widgets: Vec<WidgetData>, // In layout order
+}
 
-        Ok(components)
-    }
+// ============================================================
+// v1.4 DATA TYPES: GOALS
+// ============================================================
 
-    fn row_to_component(row: &rusqlite::Row) -> rusqlite::Result<Component> {
-        Ok(Component {
-            id: row.get(0)?,
-            project_id: row.get(1)?,
-            parent_component_id: row.get(2)?,
-            name: row.get(3)?,
-            description: row.get(4)?,
-            status: row.get(5)?,
-            created_at: row.get(6)?,
-            updated_at: row.get(7)?,
-        })
-    }
+/// What a key result measures: a number kept by hand, or todos completed / problems solved
+/// in the goal's project since its start date.
+pub const KEY_RESULT_METRICS: &[&str] = &["manual", "todos_completed", "problems_solved"];
 
-    pub fn create_component(&self, project_id: i64, name: &str, description: Option<&str>, parent_id: Option<i64>) -> Result<Component> {
-        self.conn.execute(
-            "INSERT INTO components (project_id, name, description, parent_component_id) VALUES (?, ?, ?, ?)",
-            params![project_id, name, description, parent_id],
-        )?;
-        self.get_component(self.conn.last_insert_rowid())
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Goal {
+    pub id: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub start_date: String,
+    pub target_date: Option<String>,
+    pub status: String, // active, achieved, abandoned
+    pub created_at: String,
+    pub updated_at: String,
+    pub author: Option<String>,
+}
 
-    pub fn get_component(&self, id: i64) -> Result<Component> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, parent_component_id, name, description, status, created_at, updated_at 
-             FROM components WHERE id = ?"
-        )?;
-        stmt.query_row(params![id], Self::row_to_component)
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyResult {
+    pub id: i64,
+    pub goal_id: i64,
+    pub title: String,
+    pub metric: String, // One of KEY_RESULT_METRICS
+    pub component_id: Option<i64>,
+    pub target_value: f64,
+    pub current_value: f64, // Manual key results; linked ones report the count in KeyResultProgress
+    pub sort_order: i64,
+    pub created_at: String,
+}
 
-    pub fn update_component(&self, id: i64, name: Option<&str>, description: Option<&str>, status: Option<&str>) -> Result<Component> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if let Some(n) = name {
-            updates.push("name = ?");
-            values.push(Box::new(n.to_string()));
-        }
-        if let Some(d) = description {
-            updates.push("description = ?");
-            values.push(Box::new(d.to_string()));
-        }
-        if let Some(s) = status {
-            updates.push("status = ?");
-            values.push(Box::new(s.to_string()));
-        }
-        
-        if updates.is_empty() {
-            return self.get_component(id);
-        }
-        
-        updates.push("updated_at = CURRENT_TIMESTAMP");
-        values.push(Box::new(id));
-        
-        let sql = format!("UPDATE components SET {} WHERE id = ?", updates.join(", "));
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_component(id)
-    }
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyResultProgress {
+    pub key_result: KeyResult,
+    pub value: f64,
+    pub progress: f64, // 0 to 1
+}
 
-    pub fn delete_component(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM components WHERE id = ?", params![id])?;
-        Ok(())
-    }
+#[derive(Debug, Serialize, Clone)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub key_results: Vec<KeyResultProgress>,
+    pub progress: f64, // Mean of the key results, 0 to 1; 0 with none
+    pub expected_progress: Option<f64>, // Share of the time to the target date gone by
+    pub on_track: Option<bool>,
+    pub days_left: Option<i64>, // Negative once the target date has passed
+}
 
-    // ============================================================
-    // PROBLEM OPERATIONS
-    // ============================================================
+// ============================================================
+// v1.4 DATA TYPES: ESTIMATES
+// ============================================================
 
-    fn row_to_problem(row: &rusqlite::Row) -> rusqlite::Result<Problem> {
-        Ok(Problem {
-            id: row.get(0)?,
-            component_id: row.get(1)?,
-            title: row.get(2)?,
-            description: row.get(3)?,
-            status: row.get(4)?,
-            severity: row.get(5)?,
-            root_cause: row.get(6)?,
-            created_at: row.get(7)?,
-            solved_at: row.get(8)?,
-        })
-    }
+/// A finished todo's estimate next to what it took.
+#[derive(Debug, Serialize, Clone)]
+pub struct EstimateActual {
+    pub todo_id: i64,
+    pub title: String,
+    pub estimate_hours: Option<f64>,
+    pub estimate_points: Option<f64>,
+    pub actual_hours: f64,
+    pub actual_source: String, // "tracked" (logged or focus time) or "elapsed" (started to completed)
+    pub ratio: Option<f64>, // Actual over estimated hours; above 1 means it took longer
+}
 
-    pub fn get_problem(&self, id: i64) -> Result<Problem> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at 
-             FROM problems WHERE id = ?"
-        )?;
-        stmt.query_row(params![id], Self::row_to_problem)
-    }
+#[derive(Debug, Serialize, Clone)]
+pub struct EstimationAccuracy {
+    pub project_id: i64,
+    pub compared: usize, // Done todos with an estimate and a measurable actual
+    pub estimated_hours: f64, // Over the todos estimated in hours
+    pub actual_hours: f64,
+    pub overall_ratio: Option<f64>, // Total actual over total estimated hours
+    pub median_ratio: Option<f64>,
+    pub underestimated: usize, // Took over 10% longer than estimated
+    pub overestimated: usize, // Took over 10% less
+    pub hours_per_point: Option<f64>, // Over the todos estimated in points
+    pub todos: Vec<EstimateActual>, // Worst underestimates first, then the rest
+}
 
-    pub fn get_open_problems(&self, project_id: Option<i64>, component_id: Option<i64>) -> Result<Vec<Problem>> {
-        self.get_problems_by_status(project_id, component_id, Some(&["open", "investigating"]))
-    }
+// ============================================================
+// v1.4 DATA TYPES: FOCUS MODE
+// ============================================================
 
-    pub fn get_all_problems(&self, project_id: Option<i64>, component_id: Option<i64>) -> Result<Vec<Problem>> {
-        self.get_problems_by_status(project_id, component_id, None)
-    }
+/// The running focus timer, persisted in the `focus_state` setting so it survives restarts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FocusState {
+    pub session_id: i64,
+    pub project_id: i64,
+    pub todo_id: i64,
+    pub todo_title: String,
+    pub planned_minutes: i64,
+    pub started_at: String, // RFC 3339
+    pub ends_at: String,    // RFC 3339
+    #[serde(default, skip_deserializing)]
+    pub remaining_seconds: i64, // Negative once the timer has run over
+}
 
-    fn get_problems_by_status(&self, project_id: Option<i64>, component_id: Option<i64>, statuses: Option<&[&str]>) -> Result<Vec<Problem>> {
-        let status_filter = match statuses {
-            Some(s) => format!("AND p.status IN ({})", s.iter().map(|_| "?").collect::<Vec<_>>().join(",")),
-            None => String::new(),
+// ============================================================
+// v1.4 DATA TYPES: NOTES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Note {
+    pub id: i64,
+    pub project_id: Option<i64>, // None for cross-project journal entries
+    pub note_date: String,
+    pub title: Option<String>,
+    pub body: String, // Markdown
+    pub pinned: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteLink {
+    pub id: i64,
+    pub note_id: i64,
+    pub reference: String, // As written, e.g. "FS1-P42"
+    pub target_type: String,
+    pub target_id: i64,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: PEOPLE
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Person {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+    pub role: Option<String>,
+    pub notes: Option<String>,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: COMMENTS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Comment {
+    pub id: i64,
+    pub entity_type: String, // project, component, problem, attempt, solution, todo, learning, note
+    pub entity_id: i64,
+    pub author: Option<String>,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: PROBLEM CHECKLISTS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub id: i64,
+    pub problem_id: i64,
+    pub text: String,
+    pub checked: bool,
+    pub sort_order: i64,
+    pub created_at: String,
+    pub checked_at: Option<String>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: TODO TEMPLATES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TodoTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub items: Vec<String>, // Todo titles, in order
+    pub created_at: String,
+    pub updated_at: String,
+    pub author: Option<String>,
+}
+
+/// One use of a template in a project. Its items are todos of their own, so two releases run
+/// from the same template each keep their own progress.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TodoChecklist {
+    pub id: i64,
+    pub project_id: i64,
+    pub template_id: Option<i64>, // None once the template is deleted
+    pub name: String,
+    pub created_at: String,
+    pub total: i64,
+    pub done: i64,
+    pub open: i64, // Neither done nor cancelled
+    pub complete: bool, // Nothing open, and at least one item
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TodoChecklistDetail {
+    #[serde(flatten)]
+    pub checklist: TodoChecklist,
+    pub todos: Vec<Todo>, // In template order
+}
+
+// ============================================================
+// v1.4 DATA TYPES: COMPONENT PATHS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComponentPath {
+    pub id: i64,
+    pub component_id: i64,
+    pub pattern: String, // Glob relative to the project's repo_path
+    pub created_at: String,
+}
+
+/// What an import of repository commits did. A commit touching several components becomes a
+/// change on each.
+#[derive(Debug, Serialize, Clone)]
+pub struct CommitImport {
+    pub imported: usize,
+    pub already_imported: usize,
+    pub unattributed: usize, // Touched no file any component path covers
+    pub changes: Vec<Change>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: DEPENDENCY SNAPSHOTS
+// ============================================================
+
+/// The component dependency changes go to when no component path covers the lockfile.
+const DEPENDENCIES_COMPONENT: &str = "Dependencies";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencySnapshot {
+    pub id: i64,
+    pub project_id: i64,
+    pub repo_path: String,
+    pub dependency_count: i64,
+    pub created_at: String,
+}
+
+/// What taking a snapshot found. The first snapshot of a project is the baseline and logs
+/// nothing; one identical to the snapshot before it isn't stored again.
+#[derive(Debug, Serialize, Clone)]
+pub struct DependencySnapshotResult {
+    pub snapshot: DependencySnapshot, // The previous one when nothing changed
+    pub created: bool,
+    pub dependency_changes: Vec<crate::dependencies::DependencyChange>,
+    pub changes: Vec<Change>, // The change records logged for them
+}
+
+// ============================================================
+// v1.4 DATA TYPES: TEST RESULTS
+// ============================================================
+
+/// How many of a project's latest runs the flaky test report looks at by default.
+const FLAKY_TEST_WINDOW: i64 = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestRun {
+    pub id: i64,
+    pub project_id: i64,
+    pub format: String, // junit or nextest
+    pub name: Option<String>,
+    pub total: i64,
+    pub passed: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub duration_ms: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestFailure {
+    pub id: i64,
+    pub run_id: i64,
+    pub test_name: String,
+    pub message: Option<String>,
+    pub output: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub problem_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TestRunDetail {
+    #[serde(flatten)]
+    pub run: TestRun,
+    pub failures: Vec<TestFailure>,
+}
+
+/// A test that failed, passed and failed again within the runs looked at.
+#[derive(Debug, Serialize, Clone)]
+pub struct FlakyTest {
+    pub test_name: String,
+    pub failures: i64,
+    pub runs: i64, // Runs since it first failed, that one included
+    pub failure_rate: f64, // failures / runs
+    pub last_failed_at: String,
+    pub last_message: Option<String>,
+    pub problem_id: Option<i64>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: CI STATUS
+// ============================================================
+
+/// When a source has never been green, how far before its first failure to look for changes.
+const CI_LOOKBACK_HOURS: i64 = 24;
+const MAX_CI_SUSPECT_CHANGES: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CiSource {
+    pub id: i64,
+    pub project_id: i64,
+    pub provider: String, // github or badge
+    pub target: String, // owner/repo, or the badge URL
+    pub branch: Option<String>,
+    pub workflow: Option<String>,
+    pub enabled: bool,
+    pub last_polled_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CiRun {
+    pub id: i64,
+    pub source_id: i64,
+    pub external_id: String,
+    pub status: String, // success, failure, cancelled, running or unknown
+    pub title: Option<String>,
+    pub branch: Option<String>,
+    pub commit_sha: Option<String>,
+    pub url: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+/// Where a source stands, going by its newest run that passed or failed. When it's red, the
+/// changes made between the last green run and the first red one are the suspects.
+#[derive(Debug, Serialize, Clone)]
+pub struct CiStatus {
+    pub source: CiSource,
+    pub state: String, // green, red or unknown
+    pub latest_run: Option<CiRun>,
+    pub red_since: Option<CiRun>, // The first failing run after the last green one
+    pub last_green: Option<CiRun>,
+    pub culprit: Option<Change>, // The change for red_since's commit, else the newest suspect
+    pub suspect_changes: Vec<Change>, // Newest first
+    pub summary: String, // "CI red since change #42: 1a2b3c4d Bump parser"
+}
+
+// ============================================================
+// v1.4 DATA TYPES: BENCHMARKS
+// ============================================================
+
+/// How many earlier measurements a new one is compared with; their median is the baseline.
+const BENCHMARK_BASELINE_RUNS: i64 = 5;
+/// How much worse than the baseline a measurement has to be to count as a regression.
+const BENCHMARK_REGRESSION_PERCENT: f64 = 10.0;
+/// The component regressions go to when no change can be blamed for them.
+const BENCHMARKS_COMPONENT: &str = "Benchmarks";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Benchmark {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: String,
+    pub value: f64,
+    pub unit: Option<String>,
+    pub commit_sha: Option<String>,
+    pub change_id: Option<i64>, // The change imported for commit_sha
+    pub regression_change_id: Option<i64>, // The change logged because this measurement regressed
+    pub created_at: String,
+}
+
+/// A recorded measurement and how it compares with the ones before it. A regression is
+/// logged as a change on the component of the change that caused it, so it sits next to
+/// that change in the timeline.
+#[derive(Debug, Serialize, Clone)]
+pub struct BenchmarkResult {
+    pub benchmark: Benchmark,
+    pub baseline: Option<f64>, // None for a benchmark's first measurement
+    pub delta_percent: Option<f64>, // Against the baseline; positive is worse
+    pub culprit: Option<Change>, // The change for the commit measured, else the newest one since the last measurement
+    pub regression: Option<Change>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: STATS HISTORY
+// ============================================================
+
+/// Numbers kept for each project every day: the lasting ones of get_project_stats plus the
+/// bytes its attachments take up.
+pub const STATS_HISTORY_METRICS: &[&str] = &[
+    "component_count",
+    "open_problems",
+    "solved_problems",
+    "pending_todos",
+    "learning_count",
+    "attachment_count",
+    "attachment_bytes",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatsPoint {
+    pub day: String, // YYYY-MM-DD on the user's clock
+    pub value: f64,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: JOB QUEUE
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub id: i64,
+    pub kind: String, // reindex_embeddings, describe_attachments
+    pub payload: serde_json::Value,
+    pub description: Option<String>,
+    pub status: String, // queued, running, done, failed, cancelled
+    pub progress: i64,
+    pub total: Option<i64>,
+    pub message: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub input_tokens: i64, // Language model use, for jobs that call one
+    pub output_tokens: i64,
+    pub cost: f64,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: EMBEDDINGS
+// ============================================================
+
+// Each content type's records as (id, project_id, text); the text is what gets embedded
+const EMBEDDING_SOURCES: &[(&str, &str)] = &[
+    ("problem", "SELECT p.id AS id, c.project_id AS project_id, 
+        p.title || char(10) || COALESCE(p.description, '') || char(10) || COALESCE(p.root_cause, '') AS text 
+        FROM problems p JOIN components c ON c.id = p.component_id"),
+    ("solution", "SELECT s.id AS id, c.project_id AS project_id, s.summary || char(10) || COALESCE(s.key_insight, '') AS text 
+        FROM solutions s JOIN problems p ON p.id = s.problem_id JOIN components c ON c.id = p.component_id"),
+    ("learning", "SELECT id, project_id, insight || char(10) || COALESCE(context, '') AS text FROM learnings"),
+    ("change", "SELECT ch.id AS id, c.project_id AS project_id, 
+        ch.field_name || ': ' || COALESCE(ch.old_value, '') || ' -> ' || COALESCE(ch.new_value, '') || char(10) || COALESCE(ch.reason, '') AS text 
+        FROM changes ch JOIN components c ON c.id = ch.component_id"),
+    ("attachment", "SELECT id, project_id, file_name || char(10) || COALESCE(user_description, '') || char(10) || 
+        COALESCE(ai_description, '') || char(10) || COALESCE(ai_summary, '') AS text FROM attachments"),
+];
+
+// Where each content type's records live, for clearing vectors of deleted ones
+const EMBEDDING_TABLES: &[(&str, &str)] = &[
+    ("problem", "problems"),
+    ("solution", "solutions"),
+    ("learning", "learnings"),
+    ("change", "changes"),
+    ("conversation", "conversations"),
+    ("attachment", "attachments"),
+];
+
+// (content type, id) -> (model, version, text hash) of the stored vectors
+type EmbeddingFingerprints = HashMap<(String, i64), (String, Option<String>, Option<String>)>;
+
+/// Which records a re-index covers: one project or all, and some content types or all.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EmbeddingScope {
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub content_types: Vec<String>, // Empty for every one of embeddings::CONTENT_TYPES
+}
+
+/// A record's text as it would be embedded now.
+#[derive(Debug, Clone)]
+pub struct EmbeddingSource {
+    pub content_type: String,
+    pub content_id: i64,
+    pub project_id: i64,
+    pub text: String,
+    pub text_hash: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EmbeddingStatus {
+    pub model: Option<String>, // The embedding_model setting
+    pub model_version: Option<String>,
+    pub records: i64, // Records in scope
+    pub current: i64, // Embedded from their current text by the current model
+    pub outdated: i64, // Embedded, but from older text or by another model or version
+    pub missing: i64,
+    pub vectors_by_model: BTreeMap<String, i64>, // "model" or "model@version"
+}
+
+// ============================================================
+// v1.4 DATA TYPES: AI USAGE
+// ============================================================
+
+/// Requests and what they used, for one model, command or day.
+#[derive(Debug, Serialize, Clone)]
+pub struct AiUsageGroup {
+    pub key: String, // "provider/model", the command, or YYYY-MM-DD
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AiUsage {
+    pub since: String,
+    pub until: Option<String>,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+    pub by_model: Vec<AiUsageGroup>, // Costliest first
+    pub by_command: Vec<AiUsageGroup>,
+    pub by_day: Vec<AiUsageGroup>, // Oldest first
+    pub monthly_budget: Option<f64>, // The ai_monthly_budget setting
+    pub month_cost: f64, // Spent so far this month, whatever the range
+}
+
+// ============================================================
+// v1.4 DATA TYPES: PROBLEM ENVIRONMENTS
+// ============================================================
+
+const MAX_ENVIRONMENT_FIELD_LEN: usize = 200;
+const MAX_REPRO_STEPS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProblemEnvironment {
+    pub problem_id: i64,
+    pub os: Option<String>,
+    pub app_version: Option<String>,
+    pub device: Option<String>,
+    pub branch: Option<String>,
+    pub repro_steps: Vec<String>,
+    pub captured_at: String,
+    pub updated_at: String,
+}
+
+/// Environment fields as entered or captured, before they are attached to a problem.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EnvironmentInput {
+    pub os: Option<String>,
+    pub app_version: Option<String>,
+    pub device: Option<String>,
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub repro_steps: Vec<String>,
+}
+
+impl EnvironmentInput {
+    /// Trim every field, drop blanks, and reject values the UI could never have produced.
+    pub fn validated(self) -> std::result::Result<Self, String> {
+        let clean = |field: &str, value: Option<String>| -> std::result::Result<Option<String>, String> {
+            let value = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+            match value {
+                Some(v) if v.chars().count() > MAX_ENVIRONMENT_FIELD_LEN => {
+                    Err(format!("{} must be at most {} characters", field, MAX_ENVIRONMENT_FIELD_LEN))
+                }
+                Some(v) if v.contains('\n') => Err(format!("{} must be a single line", field)),
+                other => Ok(other),
+            }
         };
         
-        let sql = match (project_id, component_id) {
-            (Some(_), Some(_)) => format!(
-                "SELECT p.id, p.component_id, p.title, p.description, p.status, p.severity, p.root_cause, p.created_at, p.solved_at 
-                 FROM problems p 
-                 JOIN components c ON p.component_id = c.id 
-                 WHERE c.project_id = ? AND p.component_id = ? {}
-                 ORDER BY p.created_at DESC", status_filter
-            ),
-            (Some(_), None) => format!(
-                "SELECT p.id, p.component_id, p.title, p.description, p.status, p.severity, p.root_cause, p.created_at, p.solved_at 
-                 FROM problems p 
-                 JOIN components c ON p.component_id = c.id 
-                 WHERE c.project_id = ? {}
-                 ORDER BY p.created_at DESC", status_filter
-            ),
-            (None, Some(_)) => format!(
-                "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at 
-                 FROM problems p
-                 WHERE component_id = ? {}
-                 ORDER BY created_at DESC", status_filter
-            ),
-            (None, None) => format!(
-                "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at 
-                 FROM problems p
-                 WHERE 1=1 {}
-                 ORDER BY created_at DESC", status_filter
-            ),
+        let repro_steps: Vec<String> = self.repro_steps.iter()
+            .map(|step| step.trim().to_string())
+            .filter(|step| !step.is_empty())
+            .collect();
+        if repro_steps.len() > MAX_REPRO_STEPS {
+            return Err(format!("At most {} reproduction steps are allowed", MAX_REPRO_STEPS));
+        }
+        
+        Ok(EnvironmentInput {
+            os: clean("os", self.os)?,
+            app_version: clean("app_version", self.app_version)?,
+            device: clean("device", self.device)?,
+            branch: clean("branch", self.branch)?,
+            repro_steps,
+        })
+    }
+}
+
+// ============================================================
+// v1.4 DATA TYPES: STARS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StarredRecord {
+    pub entity_type: String, // problem, todo, learning, attachment
+    pub entity_id: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub status: Option<String>,
+    pub starred_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: WATCHES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Watch {
+    pub id: i64,
+    pub entity_type: String, // problem, todo, learning, component
+    pub entity_id: i64,
+    pub notify: bool,
+    pub created_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: ATTACHMENT LINKS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttachmentLink {
+    pub id: i64,
+    pub attachment_id: i64,
+    pub entity_type: String, // problem, component
+    pub entity_id: i64,
+    pub created_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: ANNOTATIONS
+// ============================================================
+
+pub const ANNOTATION_SHAPES: &[&str] = &["rect", "ellipse", "arrow", "line", "freehand", "text", "point"];
+
+/// A marking drawn on an image attachment. Coordinates are in image pixels, shaped by
+/// the shape: {x, y, width, height} for rect/ellipse, {points: [[x, y], ...]} otherwise.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Annotation {
+    pub id: i64,
+    pub attachment_id: i64,
+    pub shape: String,
+    pub coordinates: serde_json::Value,
+    pub label: Option<String>,
+    pub color: Option<String>,
+    pub problem_id: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Everything the GUI sends when an annotation is drawn or edited.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnnotationInput {
+    pub shape: String,
+    pub coordinates: serde_json::Value,
+    pub label: Option<String>,
+    pub color: Option<String>,
+    pub problem_id: Option<i64>,
+}
+
+impl AnnotationInput {
+    pub fn validated(self) -> std::result::Result<Self, String> {
+        if !ANNOTATION_SHAPES.contains(&self.shape.as_str()) {
+            return Err(format!("Unknown shape '{}' (expected one of: {})", self.shape, ANNOTATION_SHAPES.join(", ")));
+        }
+        if !self.coordinates.is_object() {
+            return Err("Coordinates must be an object".to_string());
+        }
+        Ok(AnnotationInput {
+            label: self.label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()),
+            color: self.color.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+            ..self
+        })
+    }
+}
+
+// ============================================================
+// v1.4 DATA TYPES: STORAGE
+// ============================================================
+
+/// Attachment storage for one project. Bundle sizes come from disk, the rest from the database.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectStorage {
+    pub project_id: i64,
+    pub project_name: String,
+    pub attachment_count: i64,
+    pub bundled_bytes: i64,  // Files in the project's bundle folder, orphans included
+    pub external_bytes: i64, // Linked files outside the bundle (not counted against the quota)
+    pub orphaned_files: i64, // Bundle files no attachment points at
+    pub orphaned_bytes: i64,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: DIAGNOSTICS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlowQuery {
+    pub id: i64,
+    pub sql: String,
+    pub duration_ms: i64,
+    pub recorded_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: DIRECTORY WATCHES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryWatch {
+    pub id: i64,
+    pub project_id: i64,
+    pub path: String,
+    pub globs: Vec<String>,
+    pub enabled: bool,
+    pub last_scan_at: Option<String>,
+    pub created_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: CAPTURES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Capture {
+    pub id: i64,
+    pub project_id: i64,
+    pub source: String,
+    pub content: String,
+    pub status: String, // pending, converted, discarded
+    pub converted_type: Option<String>, // learning, note
+    pub converted_id: Option<i64>,
+    pub captured_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: INBOX
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InboxItem {
+    pub id: i64,
+    pub project_id: i64,
+    pub source: String, // capture, email, extraction, manual
+    pub source_id: Option<i64>,
+    pub title: String,
+    pub content: Option<String>,
+    pub status: String, // pending, converted, discarded
+    pub converted_type: Option<String>, // todo, problem, learning, note
+    pub converted_id: Option<i64>,
+    pub triaged_at: Option<String>,
+    pub created_at: String,
+    pub author: Option<String>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: REFERENTIAL INTEGRITY
+// ============================================================
+
+/// Links that name their target by a type column and an id column, which SQLite can't
+/// enforce: (table, type column, id column).
+const POLYMORPHIC_LINKS: &[(&str, &str, &str)] = &[
+    ("tags", "entity_type", "entity_id"),
+    ("note_links", "target_type", "target_id"),
+    ("comments", "entity_type", "entity_id"),
+    ("stars", "entity_type", "entity_id"),
+    ("watches", "entity_type", "entity_id"),
+    ("attachment_links", "entity_type", "entity_id"),
+    ("item_views", "entity_type", "entity_id"),
+    ("cross_references", "source_type", "source_id"),
+    ("cross_references", "target_type", "target_id"),
+];
+
+/// The table behind each type those links use.
+const LINKED_ENTITY_TABLES: &[(&str, &str)] = &[
+    ("project", "projects"),
+    ("component", "components"),
+    ("problem", "problems"),
+    ("attempt", "solution_attempts"),
+    ("solution", "solutions"),
+    ("todo", "todos"),
+    ("learning", "learnings"),
+    ("attachment", "attachments"),
+    ("note", "notes"),
+    ("change", "changes"),
+];
+
+/// A row pointing at a record that no longer exists.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrphanedRow {
+    pub table: String,
+    pub row_id: i64,
+    pub column: String,
+    pub missing_from: String, // The table the link points into
+    pub repair: String,       // delete or set_null, following the relation's ON DELETE
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IntegrityReport {
+    pub foreign_keys_enabled: bool,
+    pub orphans: Vec<OrphanedRow>,
+    pub repaired: usize, // Rows deleted or unlinked
+}
+
+// ============================================================
+// v1.4 DATA TYPES: REFERENCES
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reference {
+    pub id: i64,
+    pub project_id: i64,
+    pub component_id: Option<i64>,
+    pub problem_id: Option<i64>,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub favicon_url: Option<String>,
+    pub fetched_at: Option<String>,
+    pub archived_html_path: Option<String>,
+    pub link_status: String, // unchecked, ok, broken
+    pub http_status: Option<i64>,
+    pub checked_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: NETWORK QUEUE
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkOperation {
+    pub id: i64,
+    pub operation: String, // git_sync, notion_export, reference_metadata, reference_snapshot
+    pub payload: serde_json::Value,
+    pub description: Option<String>,
+    pub status: String, // pending, failed
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: PROJECT MERGE
+// ============================================================
+
+pub const PROJECT_EXPORT_FORMAT: &str = "flowstate-project";
+const PROJECT_EXPORT_VERSION: i64 = 1;
+
+/// A project's records in portable form: ids are left out and every link to another
+/// record is that record's uuid.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectExport {
+    pub format: String,
+    pub version: i64,
+    pub exported_at: String,
+    pub project_uuid: String,
+    pub records: BTreeMap<String, Vec<serde_json::Map<String, serde_json::Value>>>, // table -> records
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MergeReport {
+    pub project_id: Option<i64>,
+    pub strategy: String, // last_writer_wins, field
+    pub dry_run: bool,
+    pub created: BTreeMap<String, i64>, // table -> records
+    pub updated: BTreeMap<String, i64>,
+    pub unchanged: BTreeMap<String, i64>,
+    pub conflicts: Vec<MergeConflict>,
+    pub renamed_project: Option<serde_json::Value>, // {from, to} when the name was taken
+    pub warnings: Vec<String>,
+}
+
+/// A field changed on both sides, and the side that was kept.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MergeConflict {
+    pub table: String,
+    pub uuid: String,
+    pub record_id: i64,
+    pub field: String,
+    pub local: serde_json::Value,
+    pub incoming: serde_json::Value,
+    pub resolution: String, // local, incoming
+}
+
+/// A table that travels with a project export.
+struct MergeTable {
+    name: &'static str,
+    // Selects the project's rows; `?` is the project id
+    scope: &'static str,
+    // (column, referenced table, required)
+    foreign_keys: &'static [(&'static str, &'static str, bool)],
+    // Unique columns that identify a record created separately on both sides
+    natural_key: &'static [&'static str],
+    // Which side changed a record last
+    modified: &'static str,
+}
+
+const COMPONENT_SCOPE: &str = "component_id IN (SELECT id FROM components WHERE project_id = ?)";
+const PROBLEM_SCOPE: &str =
+    "problem_id IN (SELECT p.id FROM problems p JOIN components c ON p.component_id = c.id WHERE c.project_id = ?)";
+
+// Ordered so every referenced table is merged before the tables pointing at it
+const MERGE_TABLES: &[MergeTable] = &[
+    MergeTable { name: "projects", scope: "id = ?", foreign_keys: &[], natural_key: &[], modified: "updated_at" },
+    MergeTable {
+        name: "components",
+        scope: "project_id = ?",
+        foreign_keys: &[("project_id", "projects", true), ("parent_component_id", "components", false)],
+        natural_key: &["project_id", "name"],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "changes",
+        scope: COMPONENT_SCOPE,
+        foreign_keys: &[("component_id", "components", true)],
+        natural_key: &[],
+        modified: "created_at",
+    },
+    MergeTable {
+        name: "problems",
+        scope: COMPONENT_SCOPE,
+        foreign_keys: &[("component_id", "components", true)],
+        natural_key: &[],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "solution_attempts",
+        scope: PROBLEM_SCOPE,
+        foreign_keys: &[("problem_id", "problems", true), ("parent_attempt_id", "solution_attempts", false)],
+        natural_key: &[],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "solutions",
+        scope: PROBLEM_SCOPE,
+        foreign_keys: &[("problem_id", "problems", true), ("winning_attempt_id", "solution_attempts", false)],
+        natural_key: &["problem_id"],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "todos",
+        scope: "project_id = ?",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("component_id", "components", false),
+            ("blocked_by_problem_id", "problems", false),
+        ],
+        natural_key: &[],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "conversations",
+        scope: "project_id = ?",
+        foreign_keys: &[("project_id", "projects", true)],
+        natural_key: &[],
+        modified: "created_at",
+    },
+    MergeTable {
+        name: "learnings",
+        scope: "project_id = ?",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("component_id", "components", false),
+            ("supersedes_learning_id", "learnings", false),
+        ],
+        natural_key: &[],
+        modified: "updated_at",
+    },
+    MergeTable {
+        name: "sessions",
+        scope: "project_id = ?",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("focus_component_id", "components", false),
+            ("focus_problem_id", "problems", false),
+        ],
+        natural_key: &[],
+        modified: "updated_at",
+    },
+];
+
+/// v1.4: What a report database holds, each table with the condition picking a project's
+/// rows (`?` is the project id). Settings, tokens and secret variables never go in.
+const REPORT_TABLES: &[(&str, &str)] = &[
+    ("projects", "id = ?"),
+    ("components", "project_id = ?"),
+    ("changes", COMPONENT_SCOPE),
+    ("problems", COMPONENT_SCOPE),
+    ("problem_environments", PROBLEM_SCOPE),
+    ("problem_checklist_items", PROBLEM_SCOPE),
+    ("solution_attempts", PROBLEM_SCOPE),
+    ("solutions", PROBLEM_SCOPE),
+    ("todos", "project_id = ?"),
+    ("learnings", "project_id = ?"),
+    ("notes", "project_id = ?"),
+    ("conversations", "project_id = ?"),
+    ("sessions", "project_id = ?"),
+    ("project_variables", "project_id = ? AND NOT COALESCE(is_secret, 0) AND category != 'credentials'"),
+    ("project_methods", "project_id = ?"),
+    ("goals", "project_id = ?"),
+    ("web_references", "project_id = ?"),
+    ("attachments", "project_id = ?"),
+    ("extractions", "attachment_id IN (SELECT id FROM attachments WHERE project_id = ?)"),
+    ("benchmarks", "project_id = ?"),
+    ("stats_history", "project_id = ?"),
+];
+
+// Columns of REPORT_TABLES left empty in a report: paths on this machine
+const REPORT_CLEARED_COLUMNS: &[(&str, &str)] = &[("projects", "repo_path"), ("attachments", "file_path")];
+
+// Columns that only mean something in the database they're in (people aren't exported)
+const LOCAL_COLUMNS: &[&str] = &["id", "uuid", "assignee_id"];
+
+// Columns only taken from an import when it creates the record: project names are unique
+// per database, so an imported project may have had to be renamed
+const CREATE_ONLY_COLUMNS: &[(&str, &str)] = &[("projects", "name")];
+
+/// State of one merge: incoming uuids matched to differently-identified local records,
+/// and links to records that hadn't been merged yet when their row was.
+#[derive(Default)]
+struct MergeRun {
+    field_level: bool,
+    aliases: HashMap<String, i64>,
+    pending_links: Vec<(&'static str, i64, &'static str, &'static str, String)>, // (table, id, column, linked table, uuid)
+    report: MergeReport,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: RECORD HISTORY
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordVersion {
+    pub id: i64,
+    pub entity_type: String, // problem, solution
+    pub entity_id: i64,
+    pub version: i64,
+    pub snapshot: serde_json::Value,
+    pub changed_fields: Vec<String>, // Compared with the version before; empty for version 1
+    pub created_at: String,
+}
+
+/// A versioned record type. The fields match the snapshots the version_* triggers in
+/// schema.sql write; each is (field, referenced table) for links.
+struct VersionedEntity {
+    entity_type: &'static str,
+    table: &'static str,
+    fields: &'static [(&'static str, Option<&'static str>)],
+}
+
+const VERSIONED_ENTITIES: &[VersionedEntity] = &[
+    VersionedEntity {
+        entity_type: "problem",
+        table: "problems",
+        fields: &[
+            ("component_id", Some("components")),
+            ("title", None),
+            ("description", None),
+            ("status", None),
+            ("severity", None),
+            ("root_cause", None),
+            ("assignee_id", Some("people")),
+        ],
+    },
+    VersionedEntity {
+        entity_type: "solution",
+        table: "solutions",
+        fields: &[
+            ("winning_attempt_id", Some("solution_attempts")),
+            ("summary", None),
+            ("code_snippet", None),
+            ("key_insight", None),
+        ],
+    },
+];
+
+// ============================================================
+// v1.4 DATA TYPES: REPORTS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportItem {
+    pub project_name: String,
+    pub title: String,
+    pub detail: Option<String>, // Solution summary, severity, priority, category or attempt count
+    pub at: Option<String>,
+}
+
+/// What happened in a period, the raw material of the weekly review and standup.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReportActivity {
+    pub since: String,
+    pub solved: Vec<ReportItem>,
+    pub opened: Vec<ReportItem>,
+    pub completed_todos: Vec<ReportItem>,
+    pub learnings: Vec<ReportItem>,
+    pub in_progress: Vec<ReportItem>, // Current, not limited to the period
+    pub blocked: Vec<ReportItem>,     // Current blocked problems and todos
+    pub session_count: i64,
+    pub session_minutes: i64,
+}
+
+/// A period's signals for a retrospective, with the period before it for comparison.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RetroActivity {
+    pub since: String,
+    pub until: String,
+    pub struggles: Vec<ReportItem>,          // Problems with several failed attempts in the period; detail is the count
+    pub quick_solves: Vec<ReportItem>,       // Solved in the period without a failed attempt
+    pub abandoned_todos: Vec<ReportItem>,    // Cancelled in the period
+    pub verified_learnings: Vec<ReportItem>, // Verified learnings touched in the period
+    pub blocked: Vec<ReportItem>,            // Current blocked problems and todos
+    pub solved: i64,
+    pub todos_done: i64,
+    pub previous_solved: i64,
+    pub previous_todos_done: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportEmail {
+    pub id: i64,
+    pub kind: String, // weekly_review, standup
+    pub project_id: Option<i64>,
+    pub recipients: String,
+    pub subject: String,
+    pub status: String, // sent, failed
+    pub error: Option<String>,
+    pub scheduled: bool,
+    pub sent_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: CHANGELOG
+// ============================================================
+
+/// A recorded change or a solved problem, the raw material of a changelog.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangelogItem {
+    pub kind: String, // change, problem
+    pub id: i64,
+    pub component: String,
+    pub change_type: Option<String>, // Changes only
+    pub title: String, // A change's field name, a problem's title
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub detail: Option<String>, // A change's reason, a problem's solution summary
+    pub at: String, // When the change was logged or the problem solved
+}
+
+/// A solved problem as it goes into release notes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseNoteItem {
+    pub problem_id: i64,
+    pub component: String,
+    pub title: String,
+    pub summary: Option<String>,
+    pub key_insight: Option<String>,
+    pub solved_at: String,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: ANOMALY ALERTS
+// ============================================================
+
+pub const ANOMALY_SPIKE_FACTOR: f64 = 3.0;      // New problems in a day against the daily average
+pub const ANOMALY_SPIKE_MIN_PROBLEMS: i64 = 3;  // So one problem in a quiet project isn't a spike
+pub const ANOMALY_FAILED_ATTEMPTS: i64 = 5;     // Default for the anomaly_failed_attempts setting
+pub const ANOMALY_IDLE_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnomalyAlert {
+    pub id: i64,
+    pub project_id: i64,
+    pub problem_id: Option<i64>,
+    pub kind: String, // problem_spike, attempt_pileup, inactive_project
+    pub message: String,
+    pub observed: Option<f64>,
+    pub expected: Option<f64>,
+    pub created_at: String,
+    pub acknowledged_at: Option<String>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: API TOKENS
+// ============================================================
+
+/// "read" tokens can only call endpoints that don't change anything; "write" tokens can call all.
+pub const API_TOKEN_PERMISSIONS: &[&str] = &["read", "write"];
+
+/// A local API token. The token itself is only shown once, when it's created.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub token_prefix: String,
+    pub permission: String,
+    pub project_id: Option<i64>, // Only this project's records; None for all
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// Local API traffic since a time, for spotting a script that's hammering it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ApiUsageStats {
+    pub since: String,
+    pub requests: i64,
+    pub errors: i64,       // Status 400 and up, rate-limited ones included
+    pub rate_limited: i64, // Status 429
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: i64,
+    pub by_endpoint: Vec<ApiUsageGroup>,
+    pub by_caller: Vec<ApiUsageGroup>, // Token names, "automation token" or "unauthenticated"
+    pub by_status: Vec<ApiUsageGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiUsageGroup {
+    pub key: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub avg_latency_ms: f64,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: LIST SORTING
+// ============================================================
+
+/// A field a list can be sorted by: its name, the SQL it sorts on and its usual direction.
+pub type SortField = (&'static str, &'static str, &'static str);
+
+pub const PROJECT_SORTS: &[SortField] = &[
+    ("name", "name COLLATE NOCASE", "asc"),
+    ("status", "status", "asc"),
+    ("created", "created_at", "desc"),
+    ("updated", "updated_at", "desc"),
+];
+
+pub const PROBLEM_SORTS: &[SortField] = &[
+    ("title", "p.title COLLATE NOCASE", "asc"),
+    ("status", "p.status", "asc"),
+    ("severity", "CASE p.severity WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 WHEN 'critical' THEN 4 END", "desc"),
+    ("created", "p.created_at", "desc"),
+    ("updated", "p.updated_at", "desc"),
+    ("solved", "p.solved_at", "desc"),
+];
+
+pub const TODO_SORTS: &[SortField] = &[
+    ("title", "title COLLATE NOCASE", "asc"),
+    ("status", "status", "asc"),
+    ("priority", "CASE priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 WHEN 'critical' THEN 4 END", "desc"),
+    ("due", "due_date", "asc"),
+    ("created", "created_at", "desc"),
+    ("updated", "updated_at", "desc"),
+    ("completed", "completed_at", "desc"),
+];
+
+pub const LEARNING_SORTS: &[SortField] = &[
+    ("category", "category", "asc"),
+    ("verified", "verified", "desc"),
+    ("created", "created_at", "desc"),
+    ("updated", "updated_at", "desc"),
+    ("next_review", "next_review_at", "asc"),
+];
+
+pub const ATTACHMENT_SORTS: &[SortField] = &[
+    ("name", "file_name COLLATE NOCASE", "asc"),
+    ("type", "file_type", "asc"),
+    ("size", "file_size", "desc"),
+    ("created", "created_at", "desc"),
+    ("updated", "updated_at", "desc"),
+];
+
+/// The ORDER BY for `sort`, "field" or "field:asc"/"field:desc", from a list's whitelist, with
+/// records missing the field last. No sort keeps the list's usual order, `default`.
+fn order_by(sort: Option<&str>, fields: &[SortField], default: &str) -> Result<String> {
+    let Some(sort) = sort.map(str::trim).filter(|s| !s.is_empty()) else { return Ok(default.to_string()) };
+    let (name, direction) = sort.split_once(':').unwrap_or((sort, ""));
+    let Some((_, column, usual)) = fields.iter().find(|(field, _, _)| field.eq_ignore_ascii_case(name)) else {
+        let known: Vec<&str> = fields.iter().map(|(field, _, _)| *field).collect();
+        return Err(rusqlite::Error::InvalidParameterName(format!("Can't sort by '{}' (try {})", name, known.join(", "))));
+    };
+    let direction = match direction.to_lowercase().as_str() {
+        "" => usual.to_uppercase(),
+        "asc" | "desc" => direction.to_uppercase(),
+        other => return Err(rusqlite::Error::InvalidParameterName(format!("Unknown sort direction '{}' (asc or desc)", other))),
+    };
+    Ok(format!("{} IS NULL, {} {}", column, column, direction))
+}
+
+// ============================================================
+// v1.4 DATA TYPES: RECENT ITEMS
+// ============================================================
+
+pub const VIEWABLE_ENTITIES: &[&str] = &["project", "component", "problem", "todo", "learning", "attachment", "note"];
+pub const FRECENCY_HALF_LIFE_DAYS: f64 = 7.0; // A view counts half as much a week later
+const MAX_ITEM_VIEWS: i64 = 1000;
+
+/// Something that was opened, ranked by how often and how lately.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentItem {
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub project_id: Option<i64>,
+    pub title: String,
+    pub view_count: i64,
+    pub last_viewed_at: String,
+    pub score: f64, // Frecency now
+}
+
+// ============================================================
+// v1.4 DATA TYPES: AUTOMATION
+// ============================================================
+
+/// An open todo on today's agenda: overdue, due today, or in progress.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgendaItem {
+    pub todo_id: i64,
+    pub project_id: i64,
+    pub project_name: String,
+    pub title: String,
+    pub priority: String,
+    pub status: String,
+    pub due_date: Option<String>,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: LAUNCHER
+// ============================================================
+
+/// A problem or todo matched by a launcher search (Raycast, Alfred).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickItem {
+    pub entity_type: String, // problem, todo
+    pub id: i64,
+    pub project_id: i64,
+    pub project_name: String,
+    pub component_name: Option<String>,
+    pub title: String,
+    pub status: String,
+    pub priority: String, // Severity for problems
+    pub due_date: Option<String>,
+}
+
+const MAX_QUICK_OPEN_CANDIDATES: i64 = 2000;
+
+/// A record the in-app command palette can jump to, before it's scored against the query.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickOpenCandidate {
+    pub entity_type: String, // project, component, problem, todo, attachment
+    pub id: i64,
+    pub title: String,
+    pub project_id: i64,
+    pub project_name: String,
+    pub component_id: Option<i64>,
+    pub status: Option<String>,
+    pub frecency: f64, // 0 when it's never been opened
+}
+
+// ============================================================
+// v1.4 DATA TYPES: SYNC PREVIEW
+// ============================================================
+
+/// Records of one table added or edited since the last sync.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordChangeCount {
+    pub table: String,
+    pub created: i64,
+    pub updated: i64, // Created earlier, edited since
+}
+
+/// How many records of one kind something happened to in one project, e.g. 2 problems solved.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectActivityCount {
+    pub project_name: String,
+    pub noun: String, // Singular: "problem", "todo"
+    pub verb: String, // "solved", "added", "updated"
+    pub count: i64,
+}
+
+// ============================================================
+// v1.4 DATA TYPES: INTEGRATIONS
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntegrationLink {
+    pub id: i64,
+    pub integration: String,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub external_id: String,
+    pub container_id: Option<String>,
+    pub synced_at: String,
+}
+
+// ============================================================
+// v1.1 DATA TYPES: LEGACY IMPORT
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LegacyImportReport {
+    pub source_path: String,
+    pub imported: BTreeMap<String, i64>, // table -> rows imported
+    pub skipped: BTreeMap<String, i64>, // table -> rows skipped
+    pub skipped_tables: Vec<serde_json::Value>, // [{table, rows}] not part of the v1.0 mapping
+    pub renamed_projects: Vec<serde_json::Value>, // [{from, to}] renamed to avoid name collisions
+    pub warnings: Vec<String>,
+}
+
+/// A v1.0 table and the foreign keys that must be remapped when importing it.
+struct LegacyTable {
+    name: &'static str,
+    // (column, referenced table, required)
+    foreign_keys: &'static [(&'static str, &'static str, bool)],
+    // (id column, type column) pairs pointing at a table chosen by the type value
+    polymorphic_keys: &'static [(&'static str, &'static str)],
+}
+
+// Ordered so every referenced table is imported before the tables pointing at it
+const LEGACY_TABLES: &[LegacyTable] = &[
+    LegacyTable { name: "projects", foreign_keys: &[], polymorphic_keys: &[] },
+    LegacyTable {
+        name: "components",
+        foreign_keys: &[("project_id", "projects", true), ("parent_component_id", "components", false)],
+        polymorphic_keys: &[],
+    },
+    LegacyTable { name: "changes", foreign_keys: &[("component_id", "components", true)], polymorphic_keys: &[] },
+    LegacyTable { name: "problems", foreign_keys: &[("component_id", "components", true)], polymorphic_keys: &[] },
+    LegacyTable {
+        name: "solution_attempts",
+        foreign_keys: &[("problem_id", "problems", true), ("parent_attempt_id", "solution_attempts", false)],
+        polymorphic_keys: &[],
+    },
+    LegacyTable {
+        name: "solutions",
+        foreign_keys: &[("problem_id", "problems", true), ("winning_attempt_id", "solution_attempts", false)],
+        polymorphic_keys: &[],
+    },
+    LegacyTable {
+        name: "todos",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("component_id", "components", false),
+            ("blocked_by_problem_id", "problems", false),
+        ],
+        polymorphic_keys: &[],
+    },
+    LegacyTable { name: "conversations", foreign_keys: &[("project_id", "projects", true)], polymorphic_keys: &[] },
+    LegacyTable {
+        name: "learnings",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("component_id", "components", false),
+            ("supersedes_learning_id", "learnings", false),
+        ],
+        polymorphic_keys: &[],
+    },
+    LegacyTable {
+        name: "cross_references",
+        foreign_keys: &[("source_project_id", "projects", true), ("target_project_id", "projects", true)],
+        polymorphic_keys: &[("source_id", "source_type"), ("target_id", "target_type")],
+    },
+    LegacyTable {
+        name: "sessions",
+        foreign_keys: &[
+            ("project_id", "projects", true),
+            ("focus_component_id", "components", false),
+            ("focus_problem_id", "problems", false),
+        ],
+        polymorphic_keys: &[],
+    },
+];
+
+/// Columns added to pre-existing tables after v1.0: (table, column, definition).
+const MAX_RECENT_PROJECTS: usize = 10;
+
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("todos", "assignee_id", "INTEGER REFERENCES people(id) ON DELETE SET NULL"),
+    ("todos", "estimate_hours", "REAL"),
+    ("todos", "estimate_points", "REAL"),
+    ("todos", "started_at", "TIMESTAMP"),
+    ("todos", "time_spent_minutes", "INTEGER"),
+    ("problems", "assignee_id", "INTEGER REFERENCES people(id) ON DELETE SET NULL"),
+    ("problems", "escalated_at", "TIMESTAMP"),
+    ("solution_attempts", "started_at", "TIMESTAMP"),
+    ("solution_attempts", "ended_at", "TIMESTAMP"),
+    ("solution_attempts", "time_spent_minutes", "INTEGER"),
+    ("solution_attempts", "paused_minutes", "INTEGER DEFAULT 0"),
+    ("learnings", "ease", "REAL DEFAULT 2.5"),
+    ("learnings", "review_interval_days", "INTEGER DEFAULT 0"),
+    ("learnings", "review_repetitions", "INTEGER DEFAULT 0"),
+    ("learnings", "next_review_at", "TIMESTAMP"),
+    ("learnings", "last_reviewed_at", "TIMESTAMP"),
+    ("learnings", "supersedes_learning_id", "INTEGER REFERENCES learnings(id) ON DELETE SET NULL"),
+    ("projects", "color", "TEXT"),
+    ("projects", "icon", "TEXT"),
+    ("projects", "pinned", "BOOLEAN DEFAULT FALSE"),
+    ("projects", "sort_order", "INTEGER DEFAULT 0"),
+    ("projects", "sync_excluded", "BOOLEAN DEFAULT FALSE"),
+    ("projects", "attachment_sync", "TEXT DEFAULT 'full' CHECK(attachment_sync IN ('full', 'metadata', 'never'))"),
+    ("projects", "repo_path", "TEXT"),
+    ("attachments", "sync_policy", "TEXT CHECK(sync_policy IN ('full', 'metadata', 'never'))"),
+    ("attachments", "preview_metadata", "TEXT"),
+    ("content_locations", "indexer", "TEXT"),
+    ("web_references", "favicon_url", "TEXT"),
+    ("problems", "updated_at", "TIMESTAMP"),
+    ("solution_attempts", "updated_at", "TIMESTAMP"),
+    ("solutions", "updated_at", "TIMESTAMP"),
+    ("todos", "updated_at", "TIMESTAMP"),
+    ("learnings", "updated_at", "TIMESTAMP"),
+    ("sessions", "updated_at", "TIMESTAMP"),
+    ("sync_history", "channel", "TEXT DEFAULT 'git'"),
+    ("embeddings", "model_version", "TEXT"),
+    ("embeddings", "dimensions", "INTEGER"),
+    ("jobs", "input_tokens", "INTEGER NOT NULL DEFAULT 0"),
+    ("jobs", "output_tokens", "INTEGER NOT NULL DEFAULT 0"),
+    ("jobs", "cost", "REAL NOT NULL DEFAULT 0"),
+];
+
+/// What existing rows get when one of ADDED_COLUMNS is created: (table, column, expression).
+const COLUMN_BACKFILLS: &[(&str, &str, &str)] = &[
+    ("problems", "updated_at", "COALESCE(solved_at, created_at)"),
+    ("solution_attempts", "updated_at", "COALESCE(ended_at, created_at)"),
+    ("solutions", "updated_at", "created_at"),
+    ("todos", "updated_at", "COALESCE(completed_at, created_at)"),
+    ("learnings", "updated_at", "COALESCE(last_reviewed_at, created_at)"),
+    ("sessions", "updated_at", "COALESCE(ended_at, started_at)"),
+];
+
+/// v1.4: Tables that belong to this machine and never sync, in the database attached as
+/// `local`: in memory until `attach_local` points it at a file.
+const LOCAL_SCHEMA: &str = "
+-- API Requests: Audit log of local API calls, kept for 30 days
+CREATE TABLE IF NOT EXISTS local.api_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    caller TEXT NOT NULL CHECK(caller IN ('automation', 'api_token', 'none')), -- 'none' when authentication failed
+    token_id INTEGER, -- api_tokens.id in the synced database
+    method TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS local.idx_api_requests_created ON api_requests(created_at);
+
+-- Webhook Deliveries: What accepted webhooks were known by, kept for 30 days to spot replays
+CREATE TABLE IF NOT EXISTS local.webhook_deliveries (
+    source TEXT NOT NULL, -- sentry
+    delivery TEXT NOT NULL, -- 'request-id:<header>' or 'sha256:<body digest>'
+    received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (source, delivery)
+);
+";
+
+/// Tables whose records carry a stable `uuid`, so they can be matched across databases and
+/// referred to from outside: every table with an integer id, except the machine-local ones.
+const UUID_TABLES: &[&str] = &[
+    "projects",
+    "components",
+    "changes",
+    "problems",
+    "solution_attempts",
+    "solutions",
+    "todos",
+    "conversations",
+    "learnings",
+    "cross_references",
+    "sessions",
+    "attachments",
+    "content_locations",
+    "extractions",
+    "sync_status",
+    "sync_history",
+    "embeddings",
+    "project_variables",
+    "project_methods",
+    "learned_skills",
+    "session_state",
+    "tool_registry",
+    "tool_usage",
+    "behavior_patterns",
+    "algorithm_metrics",
+    "tags",
+    "notes",
+    "note_links",
+    "people",
+    "comments",
+    "problem_checklist_items",
+    "stars",
+    "watches",
+    "attachment_links",
+    "annotations",
+    "slow_queries",
+    "directory_watches",
+    "captures",
+    "inbox_items",
+    "dashboard_layouts",
+    "goals",
+    "key_results",
+    "anomaly_alerts",
+    "api_tokens",
+    "item_views",
+    "web_references",
+    "network_operations",
+    "integration_links",
+    "record_versions",
+    "report_emails",
+    "todo_templates",
+    "todo_checklists",
+    "component_paths",
+    "dependency_snapshots",
+    "test_runs",
+    "test_failures",
+    "ci_sources",
+    "ci_runs",
+    "benchmarks",
+];
+
+/// Tables whose records note who created them (v1.4 collaboration): the ones people write in.
+/// Comments already have an author of their own.
+const AUTHORED_TABLES: &[&str] = &[
+    "projects",
+    "components",
+    "changes",
+    "problems",
+    "solution_attempts",
+    "solutions",
+    "todos",
+    "conversations",
+    "learnings",
+    "sessions",
+    "attachments",
+    "notes",
+    "captures",
+    "inbox_items",
+    "dashboard_layouts",
+    "goals",
+    "web_references",
+    "todo_templates",
+];
+
+// A random (version 4) UUID, generated in SQL so rows inserted by any client get one
+const UUID_SQL: &str = "lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' \
+    || substr('89ab', 1 + abs(random()) % 4, 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))";
+
+// ============================================================
+// DATABASE
+// ============================================================
+
+pub struct Database {
+    conn: Connection,
+}
+
+// Counts project_stats from scratch; add a WHERE on `p` to pick projects
+const PROJECT_STATS_SELECT: &str = "
+    SELECT p.id,
+        (SELECT COUNT(*) FROM components WHERE project_id = p.id),
+        (SELECT COUNT(*) FROM problems pr JOIN components c ON pr.component_id = c.id
+         WHERE c.project_id = p.id AND pr.status IN ('open', 'investigating')),
+        (SELECT COUNT(*) FROM problems pr JOIN components c ON pr.component_id = c.id
+         WHERE c.project_id = p.id AND pr.status = 'solved'),
+        (SELECT COUNT(*) FROM todos WHERE project_id = p.id AND status = 'pending'),
+        (SELECT COUNT(*) FROM learnings WHERE project_id = p.id),
+        (SELECT COUNT(*) FROM attachments WHERE project_id = p.id)
+    FROM projects p";
+
+// Enough for every fixed query plus the dynamically built ones in rotation
+const STATEMENT_CACHE_CAPACITY: usize = 256;
+
+impl Database {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        let mut conn = Connection::open(&path)?;
+        // v1.4: Whether SQLite enforces the schema's ON DELETE rules depends on how it was
+        // built; without them deleting a component orphans its problems and changes. The MCP
+        // server turns them on too.
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        conn.profile(Some(record_query_time));
+        let db = Database { conn };
+        db.init()?;
+        Ok(db)
+    }
+
+    fn init(&self) -> Result<()> {
+        // Columns added to existing tables have to exist before schema.sql indexes them
+        self.add_missing_columns()?;
+        self.normalize_due_dates()?;
+        self.dedupe_project_names()?;
+        let seed_todo_templates = Self::table_columns(&self.conn, "todo_templates")?.is_empty();
+        // Create tables if they don't exist
+        self.conn.execute_batch(include_str!("../../../database/schema.sql"))?;
+        self.add_uuid_triggers()?;
+        self.add_author_triggers()?;
+        if seed_todo_templates {
+            self.seed_todo_templates()?;
+        }
+        self.conn.execute_batch(&format!("ATTACH DATABASE ':memory:' AS local; {}", LOCAL_SCHEMA))?;
+        // Counters for projects created before project_stats existed
+        self.execute(
+            &format!(
+                "INSERT INTO project_stats (project_id, component_count, open_problems, solved_problems, 
+                     pending_todos, learning_count, attachment_count) 
+                 {} WHERE p.id NOT IN (SELECT project_id FROM project_stats)",
+                PROJECT_STATS_SELECT
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// v1.4: Keep the machine-local tables in the database at `path` (created if missing)
+    /// instead of memory. Request logs from before they moved out of the synced database are
+    /// carried over and dropped from it.
+    pub fn attach_local(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| rusqlite::Error::InvalidParameterName(format!("Can't create {}: {}", dir.display(), e)))?;
+        }
+        self.conn.execute_batch("DETACH DATABASE local")?;
+        self.conn.execute("ATTACH DATABASE ? AS local", params![path.to_string_lossy().to_string()])?;
+        self.conn.execute_batch(LOCAL_SCHEMA)?;
+        let synced_log: i64 = self.query_row(
+            "SELECT COUNT(*) FROM main.sqlite_master WHERE type = 'table' AND name = 'api_requests'",
+            [],
+            |row| row.get(0),
+        )?;
+        if synced_log > 0 {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(
+                "INSERT INTO local.api_requests (caller, token_id, method, endpoint, status, latency_ms, created_at) 
+                 SELECT caller, token_id, method, endpoint, status, latency_ms, created_at FROM main.api_requests; 
+                 DROP TABLE main.api_requests;",
+            )?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Run a statement through the prepared statement cache.
+    fn execute<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<usize> {
+        self.conn.prepare_cached(sql)?.execute(params)
+    }
+
+    /// Single-row query through the prepared statement cache.
+    fn query_row<T, P, F>(&self, sql: &str, params: P, f: F) -> Result<T>
+    where
+        P: rusqlite::Params,
+        F: FnOnce(&rusqlite::Row<'_>) -> Result<T>,
+    {
+        self.conn.prepare_cached(sql)?.query_row(params, f)
+    }
+
+    /// Bring databases created by older versions up to the columns schema.sql expects.
+    fn add_missing_columns(&self) -> Result<()> {
+        let uuid_columns = UUID_TABLES.iter().map(|table| (*table, "uuid", "TEXT"));
+        let author_columns = AUTHORED_TABLES.iter().map(|table| (*table, "author", "TEXT"));
+        for (table, column, definition) in ADDED_COLUMNS.iter().copied().chain(uuid_columns).chain(author_columns) {
+            let columns = Self::table_columns(&self.conn, table)?;
+            // An empty list means the table doesn't exist yet; schema.sql will create it whole
+            if !columns.is_empty() && !columns.iter().any(|c| c == column) {
+                self.conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+                let backfill = if column == "uuid" {
+                    Some(UUID_SQL)
+                } else {
+                    COLUMN_BACKFILLS.iter().find(|(t, c, _)| *t == table && *c == column).map(|(_, _, value)| *value)
+                };
+                if let Some(value) = backfill {
+                    self.backfill_column(table, column, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn backfill_column(&self, table: &str, column: &str, value: &str) -> Result<()> {
+        self.drop_timestamp_triggers(table)?;
+        self.conn.execute_batch(&format!("UPDATE {} SET {} = {}", table, column, value))
+    }
+
+    /// A backfill isn't an edit, so updated_at triggers mustn't stamp every row with today;
+    /// schema.sql recreates them right after.
+    fn drop_timestamp_triggers(&self, table: &str) -> Result<()> {
+        let timestamp_triggers: Vec<String> = self.conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ? AND name LIKE 'update_%_timestamp'")?
+            .query_map(params![table], |row| row.get(0))?
+            .collect::<Result<Vec<_>>>()?;
+        for trigger in timestamp_triggers {
+            self.conn.execute_batch(&format!("DROP TRIGGER IF EXISTS {}", trigger))?;
+        }
+        Ok(())
+    }
+
+    /// v1.4: Rewrite due dates saved before they were normalized (local "2025-06-30 17:00:00"
+    /// from quick add, phrases typed into the field) as a date or a UTC RFC 3339 time, read in
+    /// the user's timezone. Values that can't be read are left for the user to fix.
+    fn normalize_due_dates(&self) -> Result<()> {
+        if Self::table_columns(&self.conn, "todos")?.is_empty() {
+            return Ok(());
+        }
+        let pending: Vec<(i64, String)> = self.conn
+            .prepare(&format!(
+                "SELECT id, due_date FROM todos WHERE due_date IS NOT NULL 
+                   AND due_date NOT GLOB '{date}' AND due_date NOT GLOB '{date}T[0-9][0-9]:[0-9][0-9]:[0-9][0-9]Z'",
+                date = "[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]",
+            ))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let zone = self.timezone();
+        self.drop_timestamp_triggers("todos")?;
+        for (id, due_date) in pending {
+            if let Ok(normalized) = crate::timezone::normalize_due(&due_date, zone) {
+                self.execute("UPDATE todos SET due_date = ? WHERE id = ?", params![normalized, id])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// v1.4: Project names became unique ignoring case; before schema.sql adds that index,
+    /// rename the later of any names differing only in case to "Name (2)", "Name (3)"...
+    fn dedupe_project_names(&self) -> Result<()> {
+        if Self::table_columns(&self.conn, "projects")?.is_empty() {
+            return Ok(());
+        }
+        let duplicates: Vec<(i64, String)> = self.conn
+            .prepare(
+                "SELECT p.id, p.name FROM projects p 
+                 WHERE EXISTS (SELECT 1 FROM projects q WHERE q.name = p.name COLLATE NOCASE AND q.id < p.id) 
+                 ORDER BY p.id"
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        if !duplicates.is_empty() {
+            self.drop_timestamp_triggers("projects")?;
+        }
+        for (id, name) in duplicates {
+            let mut counter = 2;
+            let mut candidate = format!("{} ({})", name, counter);
+            while self.query_row("SELECT COUNT(*) FROM projects WHERE name = ? COLLATE NOCASE", params![candidate], |row| row.get::<_, i64>(0))? > 0 {
+                counter += 1;
+                candidate = format!("{} ({})", name, counter);
+            }
+            self.execute("UPDATE projects SET name = ? WHERE id = ?", params![candidate, id])?;
+        }
+        Ok(())
+    }
+
+    /// Give every new row of UUID_TABLES a uuid unless the insert brought its own.
+    fn add_uuid_triggers(&self) -> Result<()> {
+        for table in UUID_TABLES {
+            self.conn.execute_batch(&format!(
+                "CREATE TRIGGER IF NOT EXISTS set_{table}_uuid AFTER INSERT ON {table} WHEN NEW.uuid IS NULL 
+                 BEGIN UPDATE {table} SET uuid = {uuid} WHERE id = NEW.id; END;",
+                table = table,
+                uuid = UUID_SQL,
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// v1.4: Stamp new rows of AUTHORED_TABLES with this machine's author (see `set_author`).
+    /// The triggers and the name live in the connection's temp schema, since the database file
+    /// is shared through sync and each machine has its own author.
+    fn add_author_triggers(&self) -> Result<()> {
+        self.conn.execute_batch("CREATE TEMP TABLE IF NOT EXISTS author_identity (name TEXT NOT NULL)")?;
+        for table in AUTHORED_TABLES {
+            self.conn.execute_batch(&format!(
+                "CREATE TEMP TRIGGER IF NOT EXISTS set_{table}_author AFTER INSERT ON main.{table} WHEN NEW.author IS NULL
+                 BEGIN UPDATE {table} SET author = (SELECT name FROM temp.author_identity) WHERE id = NEW.id; END;",
+                table = table,
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// v1.4: Who records created from now on are attributed to; `None` leaves them unattributed.
+    pub fn set_author(&self, name: Option<&str>) -> Result<()> {
+        self.execute("DELETE FROM temp.author_identity", [])?;
+        if let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) {
+            self.execute("INSERT INTO temp.author_identity (name) VALUES (?)", params![name])?;
+        }
+        Ok(())
+    }
+
+    pub fn author(&self) -> Result<Option<String>> {
+        self.query_row("SELECT (SELECT name FROM temp.author_identity)", [], |row| row.get(0))
+    }
+
+    /// Run `f` with stamping off, for imports: their records keep the author they came with.
+    fn without_author<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let author = self.author()?;
+        self.set_author(None)?;
+        let result = f();
+        self.set_author(author.as_deref())?;
+        result
+    }
+
+    // ============================================================
+    // PROJECT OPERATIONS
+    // ============================================================
+
+    /// `sort` is "manual" (pinned first, then the user's order; the default), "recent", or a
+    /// field from PROJECT_SORTS with an optional direction ("created:asc").
+    pub fn list_projects(&self, status: Option<&str>, sort: Option<&str>) -> Result<Vec<Project>> {
+        let sort = match sort {
+            Some("manual") => None,
+            Some("recent") => Some("updated:desc"),
+            other => other,
+        };
+        let order_by = order_by(sort, PROJECT_SORTS, "pinned DESC, sort_order ASC, updated_at DESC")?;
+        let sql = match status {
+            Some(_) => format!(
+                "SELECT id, name, description, status, created_at, updated_at, color, icon, pinned, sort_order, sync_excluded, attachment_sync, repo_path 
+                 FROM projects WHERE status = ? ORDER BY {}", order_by
+            ),
+            None => format!(
+                "SELECT id, name, description, status, created_at, updated_at, color, icon, pinned, sort_order, sync_excluded, attachment_sync, repo_path 
+                 FROM projects ORDER BY {}", order_by
+            ),
+        };
+        
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        
+        let projects = match status {
+            Some(s) => stmt.query_map(params![s], Self::row_to_project)?,
+            None => stmt.query_map([], Self::row_to_project)?,
+        }.collect::<Result<Vec<_>>>()?;
+
+        Ok(projects)
+    }
+
+    fn row_to_project(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+        Ok(Project {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            status: row.get(3)?,
+            created_at: row.get(4)?,
+            updated_at: row.get(5)?,
+            color: row.get(6)?,
+            icon: row.get(7)?,
+            pinned: row.get(8)?,
+            sort_order: row.get(9)?,
+            sync_excluded: row.get(10)?,
+            attachment_sync: row.get(11)?,
+            repo_path: row.get(12)?,
+        })
+    }
+
+    pub fn create_project(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        self.check_project_name(name, None)?;
+        self.execute(
+            "INSERT INTO projects (name, description) VALUES (?, ?)",
+            params![name, description],
+        )?;
+        self.get_project(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_project(&self, id: i64) -> Result<Project> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, description, status, created_at, updated_at, color, icon, pinned, sort_order, sync_excluded, attachment_sync, repo_path 
+             FROM projects WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_project)
+    }
+
+    /// Names are unique ignoring case, so "flowstate" finds "FlowState". Should a database
+    /// still hold names that differ only in case, an exact match wins, and otherwise the
+    /// lookup is a conflict rather than a guess.
+    pub fn get_project_by_name(&self, name: &str) -> Result<Project> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, description, status, created_at, updated_at, color, icon, pinned, sort_order, sync_excluded, attachment_sync, repo_path 
+             FROM projects WHERE name = ? COLLATE NOCASE ORDER BY id"
+        )?;
+        let mut matches = stmt.query_map(params![name], Self::row_to_project)?
+            .collect::<Result<Vec<_>>>()?;
+        match matches.len() {
+            0 => Err(rusqlite::Error::QueryReturnedNoRows),
+            1 => Ok(matches.remove(0)),
+            _ => match matches.iter().position(|p| p.name == name) {
+                Some(exact) => Ok(matches.remove(exact)),
+                None => Err(Self::conflict(format!(
+                    "'{}' matches {} projects: {}",
+                    name,
+                    matches.len(),
+                    matches.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ")
+                ))),
+            },
+        }
+    }
+
+    /// v1.4: Give a project a new name, refusing one another project has in any case.
+    pub fn rename_project(&self, id: i64, name: &str) -> Result<Project> {
+        self.update_project(id, Some(name), None, None)
+    }
+
+    /// v1.4: A project name must have some text and not be taken, ignoring case, by a project
+    /// other than `own_id`.
+    fn check_project_name(&self, name: &str, own_id: Option<i64>) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("A project needs a name".to_string()));
+        }
+        let taken: Option<String> = match self.query_row(
+            "SELECT name FROM projects WHERE name = ? COLLATE NOCASE AND id IS NOT ? LIMIT 1",
+            params![name, own_id],
+            |row| row.get(0),
+        ) {
+            Ok(taken) => Some(taken),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+        match taken {
+            Some(taken) => Err(Self::conflict(format!("A project named '{}' already exists", taken))),
+            None => Ok(()),
+        }
+    }
+
+    /// v1.4: An error whose message starts "Conflict:", so callers past the command boundary,
+    /// like the local API, can tell a clash from bad input.
+    fn conflict(message: String) -> rusqlite::Error {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+            Some(format!("Conflict: {}", message)),
+        )
+    }
+
+    pub fn update_project(&self, id: i64, name: Option<&str>, description: Option<&str>, status: Option<&str>) -> Result<Project> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(n) = name {
+            self.check_project_name(n, Some(id))?;
+            updates.push("name = ?");
+            values.push(Box::new(n.to_string()));
+        }
+        if let Some(d) = description {
+            updates.push("description = ?");
+            values.push(Box::new(d.to_string()));
+        }
+        if let Some(s) = status {
+            updates.push("status = ?");
+            values.push(Box::new(s.to_string()));
+        }
+        
+        if updates.is_empty() {
+            return self.get_project(id);
+        }
+        
+        updates.push("updated_at = CURRENT_TIMESTAMP");
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE projects SET {} WHERE id = ?", updates.join(", "));
+        
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_project(id)
+    }
+
+    pub fn delete_project(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM projects WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Set or clear (empty string) a project's color and icon; `None` leaves a field alone.
+    pub fn set_project_appearance(&self, id: i64, color: Option<&str>, icon: Option<&str>) -> Result<Project> {
+        if let Some(c) = color {
+            self.execute("UPDATE projects SET color = NULLIF(?, '') WHERE id = ?", params![c, id])?;
+        }
+        if let Some(i) = icon {
+            self.execute("UPDATE projects SET icon = NULLIF(?, '') WHERE id = ?", params![i, id])?;
+        }
+        self.get_project(id)
+    }
+
+    pub fn set_project_pinned(&self, id: i64, pinned: bool) -> Result<Project> {
+        self.execute("UPDATE projects SET pinned = ? WHERE id = ?", params![pinned, id])?;
+        self.get_project(id)
+    }
+
+    pub fn set_project_sync_excluded(&self, id: i64, excluded: bool) -> Result<Project> {
+        self.execute("UPDATE projects SET sync_excluded = ? WHERE id = ?", params![excluded, id])?;
+        self.get_project(id)
+    }
+
+    pub fn set_project_attachment_sync(&self, id: i64, policy: &str) -> Result<Project> {
+        self.execute("UPDATE projects SET attachment_sync = ? WHERE id = ?", params![policy, id])?;
+        self.get_project(id)
+    }
+
+    pub fn set_project_repo_path(&self, id: i64, repo_path: Option<&str>) -> Result<Project> {
+        self.execute("UPDATE projects SET repo_path = ? WHERE id = ?", params![repo_path, id])?;
+        self.get_project(id)
+    }
+
+    /// v1.4: Projects kept out of sync. Git sync won't run while there are any, since their
+    /// records are in the database file, which is committed whole.
+    pub fn get_sync_excluded_project_ids(&self) -> Result<Vec<i64>> {
+        self.conn.prepare_cached("SELECT id FROM projects WHERE sync_excluded = 1 ORDER BY id")?
+            .query_map([], |row| row.get(0))?
+            .collect()
+    }
+
+    /// Give the listed projects sort_order 0..n in the order given.
+    pub fn reorder_projects(&self, ordered_ids: &[i64]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, id) in ordered_ids.iter().enumerate() {
+            tx.execute("UPDATE projects SET sort_order = ? WHERE id = ?", params![position as i64, id])?;
+        }
+        tx.commit()
+    }
+
+    // ============================================================
+    // COMPONENT OPERATIONS
+    // ============================================================
+
+    pub fn list_components(&self, project_id: i64) -> Result<Vec<Component>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, parent_component_id, name, description, status, created_at, updated_at 
+             FROM components WHERE project_id = ? ORDER BY name"
+        )?;
+
+        let components = stmt.query_map(params![project_id], Self::row_to_component)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(components)
+    }
+
+    fn row_to_component(row: &rusqlite::Row) -> rusqlite::Result<Component> {
+        Ok(Component {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            parent_component_id: row.get(2)?,
+            name: row.get(3)?,
+            description: row.get(4)?,
+            status: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+
+    pub fn create_component(&self, project_id: i64, name: &str, description: Option<&str>, parent_id: Option<i64>) -> Result<Component> {
+        self.execute(
+            "INSERT INTO components (project_id, name, description, parent_component_id) VALUES (?, ?, ?, ?)",
+            params![project_id, name, description, parent_id],
+        )?;
+        self.get_component(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_component(&self, id: i64) -> Result<Component> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, parent_component_id, name, description, status, created_at, updated_at 
+             FROM components WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_component)
+    }
+
+    pub fn update_component(&self, id: i64, name: Option<&str>, description: Option<&str>, status: Option<&str>) -> Result<Component> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(n) = name {
+            updates.push("name = ?");
+            values.push(Box::new(n.to_string()));
+        }
+        if let Some(d) = description {
+            updates.push("description = ?");
+            values.push(Box::new(d.to_string()));
+        }
+        if let Some(s) = status {
+            updates.push("status = ?");
+            values.push(Box::new(s.to_string()));
+        }
+        
+        if updates.is_empty() {
+            return self.get_component(id);
+        }
+        
+        updates.push("updated_at = CURRENT_TIMESTAMP");
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE components SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_component(id)
+    }
+
+    pub fn delete_component(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM components WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // PROBLEM OPERATIONS
+    // ============================================================
+
+    fn row_to_problem(row: &rusqlite::Row) -> rusqlite::Result<Problem> {
+        Ok(Problem {
+            id: row.get(0)?,
+            component_id: row.get(1)?,
+            title: row.get(2)?,
+            description: row.get(3)?,
+            status: row.get(4)?,
+            severity: row.get(5)?,
+            root_cause: row.get(6)?,
+            created_at: row.get(7)?,
+            solved_at: row.get(8)?,
+            assignee_id: row.get(9)?,
+            comment_count: row.get(10)?,
+            author: row.get(11)?,
+        })
+    }
+
+    pub fn get_problem(&self, id: i64) -> Result<Problem> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+             FROM problems p WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_problem)
+    }
+
+    pub fn get_open_problems(&self, project_id: Option<i64>, component_id: Option<i64>, assignee_id: Option<i64>) -> Result<Vec<Problem>> {
+        self.get_problems_by_status(project_id, component_id, assignee_id, Some(&["open", "investigating"]), None)
+    }
+
+    /// `sort` is a field from PROBLEM_SORTS with an optional direction; newest first by default.
+    pub fn get_all_problems(&self, project_id: Option<i64>, component_id: Option<i64>, assignee_id: Option<i64>, sort: Option<&str>) -> Result<Vec<Problem>> {
+        self.get_problems_by_status(project_id, component_id, assignee_id, None, sort)
+    }
+
+    fn get_problems_by_status(
+        &self,
+        project_id: Option<i64>,
+        component_id: Option<i64>,
+        assignee_id: Option<i64>,
+        statuses: Option<&[&str]>,
+        sort: Option<&str>
+    ) -> Result<Vec<Problem>> {
+        let mut status_filter = match statuses {
+            Some(s) => format!("AND p.status IN ({})", s.iter().map(|_| "?").collect::<Vec<_>>().join(",")),
+            None => String::new(),
+        };
+        if assignee_id.is_some() {
+            status_filter.push_str(" AND p.assignee_id = ?");
+        }
+        let order_by = order_by(sort, PROBLEM_SORTS, "p.created_at DESC")?;
+        
+        let sql = match (project_id, component_id) {
+            (Some(_), Some(_)) => format!(
+                "SELECT p.id, p.component_id, p.title, p.description, p.status, p.severity, p.root_cause, p.created_at, p.solved_at, p.assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+                 FROM problems p 
+                 JOIN components c ON p.component_id = c.id 
+                 WHERE c.project_id = ? AND p.component_id = ? {}
+                 ORDER BY {}", status_filter, order_by
+            ),
+            (Some(_), None) => format!(
+                "SELECT p.id, p.component_id, p.title, p.description, p.status, p.severity, p.root_cause, p.created_at, p.solved_at, p.assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+                 FROM problems p 
+                 JOIN components c ON p.component_id = c.id 
+                 WHERE c.project_id = ? {}
+                 ORDER BY {}", status_filter, order_by
+            ),
+            (None, Some(_)) => format!(
+                "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+                 FROM problems p
+                 WHERE component_id = ? {}
+                 ORDER BY {}", status_filter, order_by
+            ),
+            (None, None) => format!(
+                "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+                 FROM problems p
+                 WHERE 1=1 {}
+                 ORDER BY {}", status_filter, order_by
+            ),
+        };
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(pid) = project_id {
+            param_values.push(Box::new(pid));
+        }
+        if let Some(cid) = component_id {
+            param_values.push(Box::new(cid));
+        }
+        if let Some(s) = statuses {
+            for status in s {
+                param_values.push(Box::new(status.to_string()));
+            }
+        }
+        if let Some(aid) = assignee_id {
+            param_values.push(Box::new(aid));
+        }
+        
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let problems = stmt.query_map(params.as_slice(), Self::row_to_problem)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(problems)
+    }
+
+    pub fn log_problem(&self, component_id: i64, title: &str, description: Option<&str>, severity: &str) -> Result<Problem> {
+        self.execute(
+            "INSERT INTO problems (component_id, title, description, severity) VALUES (?, ?, ?, ?)",
+            params![component_id, title, description, severity],
+        )?;
+        self.get_problem(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_problem(&self, id: i64, title: Option<&str>, description: Option<&str>, status: Option<&str>, severity: Option<&str>, root_cause: Option<&str>) -> Result<Problem> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(t) = title {
+            updates.push("title = ?");
+            values.push(Box::new(t.to_string()));
+        }
+        if let Some(d) = description {
+            updates.push("description = ?");
+            values.push(Box::new(d.to_string()));
+        }
+        if let Some(s) = status {
+            updates.push("status = ?");
+            values.push(Box::new(s.to_string()));
+            if s == "solved" {
+                updates.push("solved_at = CURRENT_TIMESTAMP");
+            }
+        }
+        if let Some(sev) = severity {
+            updates.push("severity = ?");
+            values.push(Box::new(sev.to_string()));
+        }
+        if let Some(rc) = root_cause {
+            updates.push("root_cause = ?");
+            values.push(Box::new(rc.to_string()));
+        }
+        
+        if updates.is_empty() {
+            return self.get_problem(id);
+        }
+        
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE problems SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_problem(id)
+    }
+
+    pub fn delete_problem(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM problems WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // SOLUTION ATTEMPT OPERATIONS
+    // ============================================================
+
+    fn row_to_attempt(row: &rusqlite::Row) -> rusqlite::Result<SolutionAttempt> {
+        Ok(SolutionAttempt {
+            id: row.get(0)?,
+            problem_id: row.get(1)?,
+            parent_attempt_id: row.get(2)?,
+            description: row.get(3)?,
+            outcome: row.get(4)?,
+            confidence: row.get(5)?,
+            notes: row.get(6)?,
+            created_at: row.get(7)?,
+            comment_count: row.get(8)?,
+            started_at: row.get(9)?,
+            ended_at: row.get(10)?,
+            time_spent_minutes: row.get(11)?,
+        })
+    }
+
+    pub fn get_attempt(&self, id: i64) -> Result<SolutionAttempt> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, parent_attempt_id, description, outcome, confidence, notes, created_at,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'attempt' AND cm.entity_id = solution_attempts.id),
+                    started_at, ended_at, time_spent_minutes 
+             FROM solution_attempts WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_attempt)
+    }
+
+    pub fn log_attempt(&self, problem_id: i64, description: &str, parent_attempt_id: Option<i64>) -> Result<SolutionAttempt> {
+        self.execute(
+            "INSERT INTO solution_attempts (problem_id, description, parent_attempt_id) VALUES (?, ?, ?)",
+            params![problem_id, description, parent_attempt_id],
+        )?;
+        self.get_attempt(self.conn.last_insert_rowid())
+    }
+
+    pub fn mark_attempt_outcome(&self, id: i64, outcome: &str, notes: Option<&str>, confidence: Option<&str>) -> Result<SolutionAttempt> {
+        let confidence = confidence.unwrap_or("attempted");
+        self.execute(
+            "UPDATE solution_attempts SET outcome = ?, notes = ?, confidence = ? WHERE id = ?",
+            params![outcome, notes, confidence, id],
+        )?;
+        self.get_attempt(id)
+    }
+
+    /// Start (or resume) the clock on an attempt. The first start time is kept; the time it
+    /// sat finished before a resume goes into paused_minutes and isn't counted.
+    pub fn start_attempt(&self, id: i64) -> Result<SolutionAttempt> {
+        self.execute(
+            "UPDATE solution_attempts SET 
+                paused_minutes = COALESCE(paused_minutes, 0) + CASE 
+                    WHEN started_at IS NOT NULL AND ended_at IS NOT NULL 
+                        THEN MAX(CAST(ROUND((julianday(CURRENT_TIMESTAMP) - julianday(ended_at)) * 1440) AS INTEGER), 0)
+                    ELSE 0 END,
+                started_at = COALESCE(started_at, CURRENT_TIMESTAMP), 
+                ended_at = NULL 
+             WHERE id = ?",
+            params![id],
+        )?;
+        self.get_attempt(id)
+    }
+
+    /// Stop the clock. Time spent is measured from started_at, less any paused time, unless
+    /// given explicitly.
+    pub fn finish_attempt(&self, id: i64, time_spent_minutes: Option<i64>) -> Result<SolutionAttempt> {
+        self.execute(
+            "UPDATE solution_attempts SET 
+                ended_at = CURRENT_TIMESTAMP,
+                time_spent_minutes = COALESCE(?, 
+                    CAST(ROUND((julianday(CURRENT_TIMESTAMP) - julianday(started_at)) * 1440) AS INTEGER) - COALESCE(paused_minutes, 0), 
+                    time_spent_minutes)
+             WHERE id = ?",
+            params![time_spent_minutes, id],
+        )?;
+        self.get_attempt(id)
+    }
+
+    /// Minutes sunk into a problem's attempts, counting running attempts up to now.
+    pub fn get_time_sunk(&self, problem_id: i64) -> Result<i64> {
+        self.query_row(
+            "SELECT COALESCE(SUM(CASE 
+                WHEN started_at IS NOT NULL AND ended_at IS NULL 
+                    THEN CAST(ROUND((julianday(CURRENT_TIMESTAMP) - julianday(started_at)) * 1440) AS INTEGER) - COALESCE(paused_minutes, 0)
+                ELSE COALESCE(time_spent_minutes, 0) END), 0)
+             FROM solution_attempts WHERE problem_id = ?",
+            params![problem_id],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn get_attempts_for_problem(&self, problem_id: i64) -> Result<Vec<SolutionAttempt>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, parent_attempt_id, description, outcome, confidence, notes, created_at,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'attempt' AND cm.entity_id = solution_attempts.id),
+                    started_at, ended_at, time_spent_minutes 
+             FROM solution_attempts WHERE problem_id = ? ORDER BY created_at ASC"
+        )?;
+        let attempts = stmt.query_map(params![problem_id], Self::row_to_attempt)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(attempts)
+    }
+
+    // ============================================================
+    // SOLUTION OPERATIONS
+    // ============================================================
+
+    fn row_to_solution(row: &rusqlite::Row) -> rusqlite::Result<Solution> {
+        Ok(Solution {
+            id: row.get(0)?,
+            problem_id: row.get(1)?,
+            winning_attempt_id: row.get(2)?,
+            summary: row.get(3)?,
+            code_snippet: row.get(4)?,
+            key_insight: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }
+
+    pub fn get_solution(&self, id: i64) -> Result<Solution> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, winning_attempt_id, summary, code_snippet, key_insight, created_at 
+             FROM solutions WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_solution)
+    }
+
+    pub fn get_solution_for_problem(&self, problem_id: i64) -> Result<Option<Solution>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, winning_attempt_id, summary, code_snippet, key_insight, created_at 
+             FROM solutions WHERE problem_id = ?"
+        )?;
+        match stmt.query_row(params![problem_id], Self::row_to_solution) {
+            Ok(s) => Ok(Some(s)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn mark_problem_solved(&self, problem_id: i64, winning_attempt_id: Option<i64>, summary: &str, code_snippet: Option<&str>, key_insight: Option<&str>) -> Result<Solution> {
+        self.execute(
+            "UPDATE problems SET status = 'solved', solved_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![problem_id],
+        )?;
+        
+        if let Some(attempt_id) = winning_attempt_id {
+            self.execute(
+                "UPDATE solution_attempts SET outcome = 'success', confidence = 'verified' WHERE id = ?",
+                params![attempt_id],
+            )?;
+        }
+        
+        self.execute(
+            "INSERT INTO solutions (problem_id, winning_attempt_id, summary, code_snippet, key_insight) VALUES (?, ?, ?, ?, ?)",
+            params![problem_id, winning_attempt_id, summary, code_snippet, key_insight],
+        )?;
+        
+        self.get_solution(self.conn.last_insert_rowid())
+    }
+
+    // ============================================================
+    // TODO OPERATIONS
+    // ============================================================
+
+    fn row_to_todo(row: &rusqlite::Row) -> rusqlite::Result<Todo> {
+        Ok(Todo {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            component_id: row.get(2)?,
+            title: row.get(3)?,
+            description: row.get(4)?,
+            priority: row.get(5)?,
+            status: row.get(6)?,
+            due_date: row.get(7)?,
+            created_at: row.get(8)?,
+            completed_at: row.get(9)?,
+            assignee_id: row.get(10)?,
+            comment_count: row.get(11)?,
+            author: row.get(12)?,
+            estimate_hours: row.get(13)?,
+            estimate_points: row.get(14)?,
+            started_at: row.get(15)?,
+            time_spent_minutes: row.get(16)?,
+        })
+    }
+
+    pub fn get_todo(&self, id: i64) -> Result<Todo> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, title, description, priority, status, due_date, created_at, completed_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'todo' AND cm.entity_id = todos.id), author,
+                    estimate_hours, estimate_points, started_at, time_spent_minutes 
+             FROM todos WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_todo)
+    }
+
+    /// `sort` is a field from TODO_SORTS with an optional direction; by priority, then newest, by default.
+    pub fn get_todos(&self, project_id: i64, status: Option<&str>, priority: Option<&str>, assignee_id: Option<i64>, sort: Option<&str>) -> Result<Vec<Todo>> {
+        let mut sql = String::from(
+            "SELECT id, project_id, component_id, title, description, priority, status, due_date, created_at, completed_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'todo' AND cm.entity_id = todos.id), author,
+                    estimate_hours, estimate_points, started_at, time_spent_minutes 
+             FROM todos WHERE project_id = ?"
+        );
+        
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(project_id)];
+        
+        if let Some(s) = status {
+            sql.push_str(" AND status = ?");
+            param_values.push(Box::new(s.to_string()));
+        }
+        if let Some(p) = priority {
+            sql.push_str(" AND priority = ?");
+            param_values.push(Box::new(p.to_string()));
+        }
+        if let Some(a) = assignee_id {
+            sql.push_str(" AND assignee_id = ?");
+            param_values.push(Box::new(a));
+        }
+        let default = "CASE priority WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 WHEN 'low' THEN 4 END, created_at DESC";
+        sql.push_str(&format!(" ORDER BY {}", order_by(sort, TODO_SORTS, default)?));
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let todos = stmt.query_map(params.as_slice(), Self::row_to_todo)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(todos)
+    }
+
+    pub fn add_todo(&self, project_id: i64, title: &str, description: Option<&str>, priority: &str, component_id: Option<i64>, due_date: Option<&str>) -> Result<Todo> {
+        let due_date = match due_date {
+            Some(d) => self.normalize_due(d)?,
+            None => None,
+        };
+        self.execute(
+            "INSERT INTO todos (project_id, title, description, priority, component_id, due_date) VALUES (?, ?, ?, ?, ?, ?)",
+            params![project_id, title, description, priority, component_id, due_date],
+        )?;
+        self.get_todo(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_todo(&self, id: i64, title: Option<&str>, description: Option<&str>, status: Option<&str>, priority: Option<&str>, due_date: Option<&str>) -> Result<Todo> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(t) = title {
+            updates.push("title = ?");
+            values.push(Box::new(t.to_string()));
+        }
+        if let Some(d) = description {
+            updates.push("description = ?");
+            values.push(Box::new(d.to_string()));
+        }
+        if let Some(s) = status {
+            updates.push("status = ?");
+            values.push(Box::new(s.to_string()));
+            if s == "done" {
+                updates.push("completed_at = CURRENT_TIMESTAMP");
+            }
+        }
+        if let Some(p) = priority {
+            updates.push("priority = ?");
+            values.push(Box::new(p.to_string()));
+        }
+        if let Some(dd) = due_date {
+            updates.push("due_date = ?");
+            values.push(Box::new(self.normalize_due(dd)?));
+        }
+        
+        if updates.is_empty() {
+            return self.get_todo(id);
+        }
+        
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE todos SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_todo(id)
+    }
+
+    /// v1.4: A due date as stored, read in the user's timezone; blank clears it.
+    fn normalize_due(&self, due_date: &str) -> Result<Option<String>> {
+        crate::timezone::normalize_due(due_date, self.timezone()).map_err(rusqlite::Error::InvalidParameterName)
+    }
+
+    pub fn delete_todo(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM todos WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Replace a todo's estimate; either part can be left out.
+    pub fn set_todo_estimate(&self, id: i64, hours: Option<f64>, points: Option<f64>) -> Result<Todo> {
+        self.execute(
+            "UPDATE todos SET estimate_hours = ?, estimate_points = ? WHERE id = ?",
+            params![hours, points, id],
+        )?;
+        self.get_todo(id)
+    }
+
+    /// Add time spent on a todo, by hand or at the end of a focus session.
+    pub fn log_todo_time(&self, id: i64, minutes: i64) -> Result<Todo> {
+        self.execute(
+            "UPDATE todos SET time_spent_minutes = COALESCE(time_spent_minutes, 0) + ? WHERE id = ?",
+            params![minutes, id],
+        )?;
+        self.get_todo(id)
+    }
+
+    /// How a project's finished todos compared with their estimates. A todo's actual is its
+    /// logged time when there is any, otherwise the time from starting it to completing it;
+    /// todos never started and never timed have no actual and are left out.
+    pub fn get_estimation_accuracy(&self, project_id: i64) -> Result<EstimationAccuracy> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, title, estimate_hours, estimate_points, time_spent_minutes, 
+                    (julianday(completed_at) - julianday(started_at)) * 24 
+             FROM todos 
+             WHERE project_id = ? AND status = 'done' AND (estimate_hours > 0 OR estimate_points > 0) 
+             ORDER BY completed_at DESC"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<f64>>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<f64>>(5)?,
+            ))
+        })?.collect::<Result<Vec<_>>>()?;
+
+        let mut todos: Vec<EstimateActual> = rows.into_iter()
+            .filter_map(|(todo_id, title, estimate_hours, estimate_points, tracked_minutes, elapsed_hours)| {
+                let (actual_hours, actual_source) = match (tracked_minutes, elapsed_hours) {
+                    (Some(minutes), _) if minutes > 0 => (minutes as f64 / 60.0, "tracked"),
+                    (_, Some(hours)) if hours > 0.0 => (hours, "elapsed"),
+                    _ => return None,
+                };
+                let ratio = estimate_hours.filter(|h| *h > 0.0).map(|h| actual_hours / h);
+                Some(EstimateActual {
+                    todo_id,
+                    title,
+                    estimate_hours,
+                    estimate_points,
+                    actual_hours,
+                    actual_source: actual_source.to_string(),
+                    ratio,
+                })
+            })
+            .collect();
+        todos.sort_by(|a, b| b.ratio.unwrap_or(0.0).total_cmp(&a.ratio.unwrap_or(0.0)));
+
+        let in_hours: Vec<&EstimateActual> = todos.iter().filter(|t| t.ratio.is_some()).collect();
+        let estimated_hours: f64 = in_hours.iter().filter_map(|t| t.estimate_hours).sum();
+        let actual_hours: f64 = in_hours.iter().map(|t| t.actual_hours).sum();
+        let mut ratios: Vec<f64> = in_hours.iter().filter_map(|t| t.ratio).collect();
+        ratios.sort_by(f64::total_cmp);
+        let median_ratio = match ratios.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(ratios[n / 2]),
+            n => Some((ratios[n / 2 - 1] + ratios[n / 2]) / 2.0),
+        };
+
+        let pointed: Vec<&EstimateActual> = todos.iter().filter(|t| t.estimate_points.is_some_and(|p| p > 0.0)).collect();
+        let points: f64 = pointed.iter().filter_map(|t| t.estimate_points).sum();
+        let hours_per_point = (points > 0.0).then(|| pointed.iter().map(|t| t.actual_hours).sum::<f64>() / points);
+
+        Ok(EstimationAccuracy {
+            project_id,
+            compared: todos.len(),
+            estimated_hours,
+            actual_hours,
+            overall_ratio: (estimated_hours > 0.0).then(|| actual_hours / estimated_hours),
+            median_ratio,
+            underestimated: ratios.iter().filter(|r| **r > 1.1).count(),
+            overestimated: ratios.iter().filter(|r| **r < 0.9).count(),
+            hours_per_point,
+            todos,
+        })
+    }
+
+    // ============================================================
+    // LEARNING OPERATIONS  
+    // ============================================================
+
+    fn row_to_learning(row: &rusqlite::Row) -> rusqlite::Result<Learning> {
+        Ok(Learning {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            component_id: row.get(2)?,
+            category: row.get(3)?,
+            insight: row.get(4)?,
+            context: row.get(5)?,
+            source: row.get(6)?,
+            verified: row.get(7)?,
+            created_at: row.get(8)?,
+            ease: row.get(9)?,
+            review_interval_days: row.get(10)?,
+            review_repetitions: row.get(11)?,
+            next_review_at: row.get(12)?,
+            last_reviewed_at: row.get(13)?,
+            supersedes_learning_id: row.get(14)?,
+            author: row.get(15)?,
+        })
+    }
+
+    pub fn get_learning(&self, id: i64) -> Result<Learning> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, category, insight, context, source, verified, created_at,
+                    ease, review_interval_days, review_repetitions, next_review_at, last_reviewed_at, supersedes_learning_id, author 
+             FROM learnings WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_learning)
+    }
+
+    /// `sort` is a field from LEARNING_SORTS with an optional direction; newest first by default.
+    pub fn get_learnings(&self, project_id: Option<i64>, category: Option<&str>, verified_only: bool, sort: Option<&str>) -> Result<Vec<Learning>> {
+        let mut sql = String::from(
+            "SELECT id, project_id, component_id, category, insight, context, source, verified, created_at,
+                    ease, review_interval_days, review_repetitions, next_review_at, last_reviewed_at, supersedes_learning_id, author 
+             FROM learnings WHERE 1=1"
+        );
+        
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(p) = project_id {
+            sql.push_str(" AND project_id = ?");
+            param_values.push(Box::new(p));
+        }
+        if let Some(c) = category {
+            sql.push_str(" AND category = ?");
+            param_values.push(Box::new(c.to_string()));
+        }
+        if verified_only {
+            sql.push_str(" AND verified = 1");
+        }
+        sql.push_str(&format!(" ORDER BY {}", order_by(sort, LEARNING_SORTS, "created_at DESC")?));
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let learnings = stmt.query_map(params.as_slice(), Self::row_to_learning)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(learnings)
+    }
+
+    pub fn log_learning(&self, project_id: i64, insight: &str, category: Option<&str>, context: Option<&str>, component_id: Option<i64>, source: &str) -> Result<Learning> {
+        self.execute(
+            "INSERT INTO learnings (project_id, insight, category, context, component_id, source, next_review_at) 
+             VALUES (?, ?, ?, ?, ?, ?, datetime('now', '+1 day'))",
+            params![project_id, insight, category, context, component_id, source],
+        )?;
+        self.get_learning(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_learning(&self, id: i64, insight: Option<&str>, category: Option<&str>, context: Option<&str>, verified: Option<bool>) -> Result<Learning> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(i) = insight {
+            updates.push("insight = ?");
+            values.push(Box::new(i.to_string()));
+        }
+        if let Some(c) = category {
+            updates.push("category = ?");
+            values.push(Box::new(c.to_string()));
+        }
+        if let Some(ctx) = context {
+            updates.push("context = ?");
+            values.push(Box::new(ctx.to_string()));
+        }
+        if let Some(v) = verified {
+            updates.push("verified = ?");
+            values.push(Box::new(v));
+        }
+        
+        if updates.is_empty() {
+            return self.get_learning(id);
+        }
+        
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE learnings SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_learning(id)
+    }
+
+    pub fn delete_learning(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM learnings WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Settle a contradiction: the winner supersedes the loser and is the one left verified.
+    pub fn resolve_learning_conflict(&self, winner_id: i64, loser_id: i64) -> Result<Learning> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE learnings SET supersedes_learning_id = ?, verified = 1 WHERE id = ?",
+            params![loser_id, winner_id],
+        )?;
+        tx.execute("UPDATE learnings SET verified = 0 WHERE id = ?", params![loser_id])?;
+        // A learning can't be superseded by its own loser
+        tx.execute(
+            "UPDATE learnings SET supersedes_learning_id = NULL WHERE id = ? AND supersedes_learning_id = ?",
+            params![loser_id, winner_id],
+        )?;
+        tx.commit()?;
+        self.get_learning(winner_id)
+    }
+
+    /// v1.4: File learnings under reviewed categories in one go. Returns how many changed.
+    pub fn set_learning_categories(&self, assignments: &[(i64, &str)]) -> Result<usize> {
+        if let Some((_, unknown)) = assignments.iter().find(|(_, c)| !crate::categorize::CATEGORIES.contains(c)) {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Unknown category '{}' (try {})", unknown, crate::categorize::CATEGORIES.join(", ")
+            )));
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let mut changed = 0;
+        for (id, category) in assignments {
+            changed += tx.execute(
+                "UPDATE learnings SET category = ? WHERE id = ? AND category IS NOT ?",
+                params![category, id, category],
+            )?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// Learnings whose next review is due, oldest first. Learnings never scheduled count as due.
+    pub fn get_due_reviews(&self, project_id: Option<i64>, limit: i64) -> Result<Vec<Learning>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, category, insight, context, source, verified, created_at,
+                    ease, review_interval_days, review_repetitions, next_review_at, last_reviewed_at, supersedes_learning_id, author 
+             FROM learnings 
+             WHERE (?1 IS NULL OR project_id = ?1) 
+               AND (next_review_at IS NULL OR next_review_at <= CURRENT_TIMESTAMP)
+             ORDER BY next_review_at IS NOT NULL, next_review_at ASC, created_at ASC
+             LIMIT ?2"
+        )?;
+        let learnings = stmt.query_map(params![project_id, limit], Self::row_to_learning)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(learnings)
+    }
+
+    pub fn record_review(&self, id: i64, remembered: bool) -> Result<Learning> {
+        let learning = self.get_learning(id)?;
+        let (ease, interval, repetitions) = next_review_schedule(
+            learning.ease,
+            learning.review_interval_days,
+            learning.review_repetitions,
+            remembered,
+        );
+        self.execute(
+            "UPDATE learnings SET 
+                ease = ?, review_interval_days = ?, review_repetitions = ?,
+                last_reviewed_at = CURRENT_TIMESTAMP,
+                next_review_at = datetime('now', '+' || ? || ' days')
+             WHERE id = ?",
+            params![ease, interval, repetitions, interval, id],
+        )?;
+        self.get_learning(id)
+    }
+
+    // ============================================================
+    // CHANGE OPERATIONS
+    // ============================================================
+
+    fn row_to_change(row: &rusqlite::Row) -> rusqlite::Result<Change> {
+        Ok(Change {
+            id: row.get(0)?,
+            component_id: row.get(1)?,
+            field_name: row.get(2)?,
+            old_value: row.get(3)?,
+            new_value: row.get(4)?,
+            change_type: row.get(5)?,
+            reason: row.get(6)?,
+            created_at: row.get(7)?,
+            author: row.get(8)?,
+        })
+    }
+
+    /// v1.4: `author` keeps only the changes that person logged.
+    /// Changes logged from `since` up to (not including) `until`, both stored timestamps
+    /// (see `timezone::stored`); no `until` runs to now.
+    pub fn get_recent_changes(&self, project_id: Option<i64>, component_id: Option<i64>, since: &str, until: Option<&str>, author: Option<&str>) -> Result<Vec<Change>> {
+        let mut sql = String::from(
+            "SELECT ch.id, ch.component_id, ch.field_name, ch.old_value, ch.new_value, ch.change_type, ch.reason, ch.created_at, ch.author 
+             FROM changes ch"
+        );
+        
+        let mut conditions: Vec<String> = Vec::new();
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if project_id.is_some() || component_id.is_some() {
+            sql.push_str(" JOIN components c ON ch.component_id = c.id");
+        }
+        
+        if let Some(pid) = project_id {
+            conditions.push("c.project_id = ?".to_string());
+            param_values.push(Box::new(pid));
+        }
+        if let Some(cid) = component_id {
+            conditions.push("ch.component_id = ?".to_string());
+            param_values.push(Box::new(cid));
+        }
+        if let Some(author) = author {
+            conditions.push("ch.author = ?".to_string());
+            param_values.push(Box::new(author.to_string()));
+        }
+        conditions.push("ch.created_at >= ?".to_string());
+        param_values.push(Box::new(since.to_string()));
+        if let Some(until) = until {
+            conditions.push("ch.created_at < ?".to_string());
+            param_values.push(Box::new(until.to_string()));
+        }
+        
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        
+        sql.push_str(" ORDER BY ch.created_at DESC");
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let changes = stmt.query_map(params.as_slice(), Self::row_to_change)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(changes)
+    }
+
+    pub fn get_all_changes(&self, project_id: Option<i64>, component_id: Option<i64>) -> Result<Vec<Change>> {
+        let mut sql = String::from(
+            "SELECT ch.id, ch.component_id, ch.field_name, ch.old_value, ch.new_value, ch.change_type, ch.reason, ch.created_at, ch.author 
+             FROM changes ch"
+        );
+        
+        let mut conditions = Vec::new();
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if project_id.is_some() || component_id.is_some() {
+            sql.push_str(" JOIN components c ON ch.component_id = c.id");
+        }
+        
+        if let Some(pid) = project_id {
+            conditions.push("c.project_id = ?".to_string());
+            param_values.push(Box::new(pid));
+        }
+        if let Some(cid) = component_id {
+            conditions.push("ch.component_id = ?".to_string());
+            param_values.push(Box::new(cid));
+        }
+        
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        
+        sql.push_str(" ORDER BY ch.created_at DESC");
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let changes = stmt.query_map(params.as_slice(), Self::row_to_change)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(changes)
+    }
+
+    /// Insert many changes in one transaction; all or nothing.
+    pub fn log_changes_batch(&self, changes: &[NewChange]) -> Result<Vec<Change>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut ids = Vec::with_capacity(changes.len());
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO changes (component_id, field_name, old_value, new_value, change_type, reason) VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            for change in changes {
+                insert.execute(params![
+                    change.component_id,
+                    change.field_name,
+                    change.old_value,
+                    change.new_value,
+                    change.change_type.as_deref().unwrap_or("code"),
+                    change.reason,
+                ])?;
+                ids.push(tx.last_insert_rowid());
+            }
+        }
+        tx.commit()?;
+        
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, component_id, field_name, old_value, new_value, change_type, reason, created_at, author 
+             FROM changes WHERE id = ?"
+        )?;
+        ids.iter().map(|id| stmt.query_row(params![id], Self::row_to_change)).collect()
+    }
+
+    pub fn log_change(&self, component_id: i64, field_name: &str, old_value: Option<&str>, new_value: Option<&str>, change_type: &str, reason: Option<&str>) -> Result<Change> {
+        self.execute(
+            "INSERT INTO changes (component_id, field_name, old_value, new_value, change_type, reason) VALUES (?, ?, ?, ?, ?, ?)",
+            params![component_id, field_name, old_value, new_value, change_type, reason],
+        )?;
+        self.get_change(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_change(&self, id: i64) -> Result<Change> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, component_id, field_name, old_value, new_value, change_type, reason, created_at, author 
+             FROM changes WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_change)
+    }
+
+    // ============================================================
+    // v1.1: ATTACHMENT OPERATIONS
+    // ============================================================
+
+    fn row_to_attachment(row: &rusqlite::Row) -> rusqlite::Result<Attachment> {
+        Ok(Attachment {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            component_id: row.get(2)?,
+            problem_id: row.get(3)?,
+            file_name: row.get(4)?,
+            file_path: row.get(5)?,
+            file_type: row.get(6)?,
+            file_size: row.get(7)?,
+            file_hash: row.get(8)?,
+            is_external: row.get(9)?,
+            user_description: row.get(10)?,
+            tags: row.get(11)?,
+            ai_description: row.get(12)?,
+            ai_summary: row.get(13)?,
+            content_extracted: row.get(14)?,
+            created_at: row.get(15)?,
+            updated_at: row.get(16)?,
+            indexed_at: row.get(17)?,
+            preview_metadata: row.get(18)?,
+            sync_policy: row.get(19)?,
+        })
+    }
+
+    pub fn get_attachment(&self, id: i64) -> Result<Attachment> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, problem_id, file_name, file_path, file_type, 
+                    file_size, file_hash, is_external, user_description, tags, ai_description, 
+                    ai_summary, content_extracted, created_at, updated_at, indexed_at, preview_metadata, sync_policy 
+             FROM attachments WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_attachment)
+    }
+
+    /// `sort` is a field from ATTACHMENT_SORTS with an optional direction; newest first by default.
+    pub fn get_attachments(&self, project_id: i64, component_id: Option<i64>, problem_id: Option<i64>, sort: Option<&str>) -> Result<Vec<Attachment>> {
+        let mut sql = String::from(
+            "SELECT id, project_id, component_id, problem_id, file_name, file_path, file_type, 
+                    file_size, file_hash, is_external, user_description, tags, ai_description, 
+                    ai_summary, content_extracted, created_at, updated_at, indexed_at, preview_metadata, sync_policy 
+             FROM attachments WHERE project_id = ?"
+        );
+        
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(project_id)];
+        
+        // v1.4: Matched through attachment_links, so a file linked to several records shows on each
+        if let Some(cid) = component_id {
+            sql.push_str(" AND id IN (SELECT attachment_id FROM attachment_links WHERE entity_type = 'component' AND entity_id = ?)");
+            param_values.push(Box::new(cid));
+        }
+        if let Some(pid) = problem_id {
+            sql.push_str(" AND id IN (SELECT attachment_id FROM attachment_links WHERE entity_type = 'problem' AND entity_id = ?)");
+            param_values.push(Box::new(pid));
+        }
+        
+        sql.push_str(&format!(" ORDER BY {}", order_by(sort, ATTACHMENT_SORTS, "created_at DESC")?));
+        
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let attachments = stmt.query_map(params.as_slice(), Self::row_to_attachment)?
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(attachments)
+    }
+
+    pub fn create_attachment(
+        &self,
+        project_id: i64,
+        file_name: &str,
+        file_path: &str,
+        file_type: &str,
+        file_size: Option<i64>,
+        file_hash: Option<&str>,
+        is_external: bool,
+        component_id: Option<i64>,
+        problem_id: Option<i64>,
+        user_description: Option<&str>,
+        tags: Option<&str>,
+    ) -> Result<Attachment> {
+        self.execute(
+            "INSERT INTO attachments (project_id, component_id, problem_id, file_name, file_path, 
+             file_type, file_size, file_hash, is_external, user_description, tags) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![project_id, component_id, problem_id, file_name, file_path, 
+                    file_type, file_size, file_hash, is_external, user_description, tags],
+        )?;
+        self.get_attachment(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_attachment(
+        &self,
+        id: i64,
+        user_description: Option<&str>,
+        tags: Option<&str>,
+        ai_description: Option<&str>,
+        ai_summary: Option<&str>,
+        content_extracted: Option<bool>,
+    ) -> Result<Attachment> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(ud) = user_description {
+            updates.push("user_description = ?");
+            values.push(Box::new(ud.to_string()));
+        }
+        if let Some(t) = tags {
+            updates.push("tags = ?");
+            values.push(Box::new(t.to_string()));
+        }
+        if let Some(ad) = ai_description {
+            updates.push("ai_description = ?");
+            values.push(Box::new(ad.to_string()));
+        }
+        if let Some(as_) = ai_summary {
+            updates.push("ai_summary = ?");
+            values.push(Box::new(as_.to_string()));
+        }
+        if let Some(ce) = content_extracted {
+            updates.push("content_extracted = ?");
+            values.push(Box::new(ce));
+        }
+        
+        // Always update indexed_at if AI fields are being set
+        if ai_description.is_some() || ai_summary.is_some() {
+            updates.push("indexed_at = CURRENT_TIMESTAMP");
+        }
+        
+        if updates.is_empty() {
+            return self.get_attachment(id);
+        }
+        
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE attachments SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_attachment(id)
+    }
+
+    pub fn delete_attachment(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM attachments WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// v1.4: Store the metadata the Files view shows on an attachment's card.
+    pub fn set_attachment_preview(&self, id: i64, preview_metadata: Option<&str>) -> Result<Attachment> {
+        self.execute(
+            "UPDATE attachments SET preview_metadata = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![preview_metadata, id],
+        )?;
+        self.get_attachment(id)
+    }
+
+    /// v1.4: The attachment a project already has for a file, if any.
+    pub fn get_attachment_by_path(&self, project_id: i64, file_path: &str) -> Result<Option<Attachment>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, problem_id, file_name, file_path, file_type, 
+                    file_size, file_hash, is_external, user_description, tags, ai_description, 
+                    ai_summary, content_extracted, created_at, updated_at, indexed_at, preview_metadata, sync_policy 
+             FROM attachments WHERE project_id = ? AND file_path = ? ORDER BY id LIMIT 1"
+        )?;
+        let mut rows = stmt.query_map(params![project_id, file_path], Self::row_to_attachment)?;
+        rows.next().transpose()
+    }
+
+    /// v1.4: Record new contents for an attachment's file.
+    pub fn refresh_attachment_file(&self, id: i64, file_size: Option<i64>, file_hash: Option<&str>) -> Result<Attachment> {
+        self.execute(
+            "UPDATE attachments SET file_size = ?, file_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![file_size, file_hash, id],
+        )?;
+        self.get_attachment(id)
+    }
+
+    /// v1.4: Override the project's sync policy for one attachment; `None` goes back to it.
+    pub fn set_attachment_sync_policy(&self, id: i64, policy: Option<&str>) -> Result<Attachment> {
+        self.execute("UPDATE attachments SET sync_policy = ? WHERE id = ?", params![policy, id])?;
+        self.get_attachment(id)
+    }
+
+    /// v1.4: Bundled attachments whose files don't go out with a regular sync, with their
+    /// effective policy ("metadata" or "never"). Projects kept out of sync entirely aren't listed.
+    pub fn get_limited_sync_attachments(&self) -> Result<Vec<(Attachment, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT a.id, a.project_id, a.component_id, a.problem_id, a.file_name, a.file_path, a.file_type, 
+                    a.file_size, a.file_hash, a.is_external, a.user_description, a.tags, a.ai_description, 
+                    a.ai_summary, a.content_extracted, a.created_at, a.updated_at, a.indexed_at, a.preview_metadata, 
+                    a.sync_policy, COALESCE(a.sync_policy, p.attachment_sync, 'full') AS policy 
+             FROM attachments a JOIN projects p ON p.id = a.project_id 
+             WHERE a.is_external = 0 AND p.sync_excluded = 0 
+               AND COALESCE(a.sync_policy, p.attachment_sync, 'full') != 'full' 
+             ORDER BY a.id"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((Self::row_to_attachment(row)?, row.get(20)?)))?;
+        rows.collect()
+    }
+
+    /// v1.4: Point an attachment at where its file now lives on this machine.
+    pub fn set_attachment_file_path(&self, id: i64, file_path: &str) -> Result<Attachment> {
+        self.execute("UPDATE attachments SET file_path = ? WHERE id = ?", params![file_path, id])?;
+        self.get_attachment(id)
+    }
+
+    /// v1.4: Attach an existing attachment to another problem or component in its project.
+    pub fn link_attachment(&self, attachment_id: i64, entity_type: &str, entity_id: i64) -> Result<AttachmentLink> {
+        let target_project: i64 = match entity_type {
+            "problem" => self.query_row(
+                "SELECT c.project_id FROM problems p JOIN components c ON p.component_id = c.id WHERE p.id = ?",
+                params![entity_id],
+                |row| row.get(0)
+            )?,
+            "component" => self.query_row("SELECT project_id FROM components WHERE id = ?", params![entity_id], |row| row.get(0))?,
+            other => return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                Some(format!("Attachments link to problems or components, not '{}'", other)),
+            )),
+        };
+        if self.get_attachment(attachment_id)?.project_id != target_project {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                Some(format!("Attachment {} belongs to another project than {} {}", attachment_id, entity_type, entity_id)),
+            ));
+        }
+        
+        self.execute(
+            "INSERT INTO attachment_links (attachment_id, entity_type, entity_id) VALUES (?, ?, ?)
+             ON CONFLICT(attachment_id, entity_type, entity_id) DO NOTHING",
+            params![attachment_id, entity_type, entity_id],
+        )?;
+        self.query_row(
+            "SELECT id, attachment_id, entity_type, entity_id, created_at FROM attachment_links 
+             WHERE attachment_id = ? AND entity_type = ? AND entity_id = ?",
+            params![attachment_id, entity_type, entity_id],
+            Self::row_to_attachment_link
+        )
+    }
+
+    /// Unlinking the record an attachment was created on clears that column too, so it stays unlinked.
+    pub fn unlink_attachment(&self, attachment_id: i64, entity_type: &str, entity_id: i64) -> Result<()> {
+        self.execute(
+            "DELETE FROM attachment_links WHERE attachment_id = ? AND entity_type = ? AND entity_id = ?",
+            params![attachment_id, entity_type, entity_id],
+        )?;
+        let column = if entity_type == "problem" { "problem_id" } else { "component_id" };
+        self.execute(
+            &format!("UPDATE attachments SET {0} = NULL WHERE id = ? AND {0} = ?", column),
+            params![attachment_id, entity_id],
+        )?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: ANNOTATION OPERATIONS
+    // ============================================================
+
+    fn row_to_annotation(row: &rusqlite::Row) -> rusqlite::Result<Annotation> {
+        let coordinates: String = row.get(3)?;
+        Ok(Annotation {
+            id: row.get(0)?,
+            attachment_id: row.get(1)?,
+            shape: row.get(2)?,
+            coordinates: serde_json::from_str(&coordinates).unwrap_or(serde_json::Value::Null),
+            label: row.get(4)?,
+            color: row.get(5)?,
+            problem_id: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+
+    pub fn get_annotation(&self, id: i64) -> Result<Annotation> {
+        self.query_row(
+            "SELECT id, attachment_id, shape, coordinates, label, color, problem_id, created_at, updated_at 
+             FROM annotations WHERE id = ?",
+            params![id],
+            Self::row_to_annotation
+        )
+    }
+
+    pub fn get_annotations(&self, attachment_id: i64) -> Result<Vec<Annotation>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, shape, coordinates, label, color, problem_id, created_at, updated_at 
+             FROM annotations WHERE attachment_id = ? ORDER BY id ASC"
+        )?;
+        let annotations = stmt.query_map(params![attachment_id], Self::row_to_annotation)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(annotations)
+    }
+
+    /// Every annotation on the project's images, for exports.
+    pub fn get_project_annotations(&self, project_id: i64) -> Result<Vec<Annotation>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT an.id, an.attachment_id, an.shape, an.coordinates, an.label, an.color, an.problem_id, 
+                    an.created_at, an.updated_at 
+             FROM annotations an JOIN attachments a ON an.attachment_id = a.id 
+             WHERE a.project_id = ? ORDER BY an.attachment_id ASC, an.id ASC"
+        )?;
+        let annotations = stmt.query_map(params![project_id], Self::row_to_annotation)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(annotations)
+    }
+
+    /// Only image attachments can be annotated.
+    pub fn create_annotation(&self, attachment_id: i64, input: &AnnotationInput) -> Result<Annotation> {
+        let attachment = self.get_attachment(attachment_id)?;
+        if !matches!(attachment.file_type.to_lowercase().as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp") {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                Some(format!("'{}' is not an image and can't be annotated", attachment.file_name)),
+            ));
+        }
+        self.execute(
+            "INSERT INTO annotations (attachment_id, shape, coordinates, label, color, problem_id) 
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![attachment_id, input.shape, input.coordinates.to_string(), input.label, input.color, input.problem_id],
+        )?;
+        self.get_annotation(self.conn.last_insert_rowid())
+    }
+
+    /// Replace an annotation's shape, position, label and link.
+    pub fn update_annotation(&self, id: i64, input: &AnnotationInput) -> Result<Annotation> {
+        self.execute(
+            "UPDATE annotations SET shape = ?, coordinates = ?, label = ?, color = ?, problem_id = ?, 
+                    updated_at = CURRENT_TIMESTAMP 
+             WHERE id = ?",
+            params![input.shape, input.coordinates.to_string(), input.label, input.color, input.problem_id, id],
+        )?;
+        self.get_annotation(id)
+    }
+
+    pub fn delete_annotation(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM annotations WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    pub fn get_attachment_links(&self, attachment_id: i64) -> Result<Vec<AttachmentLink>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, entity_type, entity_id, created_at FROM attachment_links 
+             WHERE attachment_id = ? ORDER BY created_at ASC, id ASC"
+        )?;
+        let links = stmt.query_map(params![attachment_id], Self::row_to_attachment_link)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(links)
+    }
+
+    fn row_to_attachment_link(row: &rusqlite::Row) -> rusqlite::Result<AttachmentLink> {
+        Ok(AttachmentLink {
+            id: row.get(0)?,
+            attachment_id: row.get(1)?,
+            entity_type: row.get(2)?,
+            entity_id: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    /// v1.4: Paths of every file copied into a bundle, across all projects.
+    pub fn get_bundled_file_paths(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT file_path FROM attachments WHERE is_external = FALSE 
+             UNION SELECT archived_html_path FROM web_references WHERE archived_html_path IS NOT NULL"
+        )?;
+        let paths = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+        Ok(paths)
+    }
+
+    /// v1.4: Database side of storage accounting; disk sizes are left at zero for the caller.
+    pub fn get_attachment_storage(&self, project_id: Option<i64>) -> Result<Vec<ProjectStorage>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT p.id, p.name, COUNT(a.id), 
+                    COALESCE(SUM(CASE WHEN a.is_external THEN a.file_size ELSE 0 END), 0) 
+             FROM projects p LEFT JOIN attachments a ON a.project_id = p.id 
+             WHERE ?1 IS NULL OR p.id = ?1 
+             GROUP BY p.id ORDER BY p.name COLLATE NOCASE"
+        )?;
+        let usage = stmt.query_map(params![project_id], |row| {
+            Ok(ProjectStorage {
+                project_id: row.get(0)?,
+                project_name: row.get(1)?,
+                attachment_count: row.get(2)?,
+                bundled_bytes: 0,
+                external_bytes: row.get(3)?,
+                orphaned_files: 0,
+                orphaned_bytes: 0,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(usage)
+    }
+
+    // ============================================================
+    // v1.1: CONTENT LOCATION OPERATIONS
+    // ============================================================
+
+    fn row_to_content_location(row: &rusqlite::Row) -> rusqlite::Result<ContentLocation> {
+        Ok(ContentLocation {
+            id: row.get(0)?,
+            attachment_id: row.get(1)?,
+            description: row.get(2)?,
+            category: row.get(3)?,
+            location_type: row.get(4)?,
+            start_location: row.get(5)?,
+            end_location: row.get(6)?,
+            snippet: row.get(7)?,
+            related_problem_id: row.get(8)?,
+            related_solution_id: row.get(9)?,
+            related_learning_id: row.get(10)?,
+            related_component_id: row.get(11)?,
+            created_at: row.get(12)?,
+            indexer: row.get(13)?,
+        })
+    }
+
+    pub fn get_content_location(&self, id: i64) -> Result<ContentLocation> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, description, category, location_type, start_location, 
+                    end_location, snippet, related_problem_id, related_solution_id, 
+                    related_learning_id, related_component_id, created_at, indexer 
+             FROM content_locations WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_content_location)
+    }
+
+    pub fn get_content_locations_for_attachment(&self, attachment_id: i64) -> Result<Vec<ContentLocation>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, description, category, location_type, start_location, 
+                    end_location, snippet, related_problem_id, related_solution_id, 
+                    related_learning_id, related_component_id, created_at, indexer 
+             FROM content_locations WHERE attachment_id = ? 
+             ORDER BY CAST(start_location AS INTEGER), start_location"
+        )?;
+        let locations = stmt.query_map(params![attachment_id], Self::row_to_content_location)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(locations)
+    }
+
+    pub fn create_content_location(
+        &self,
+        attachment_id: i64,
+        description: &str,
+        category: Option<&str>,
+        location_type: &str,
+        start_location: &str,
+        end_location: Option<&str>,
+        snippet: Option<&str>,
+        related_problem_id: Option<i64>,
+        related_solution_id: Option<i64>,
+        related_learning_id: Option<i64>,
+        related_component_id: Option<i64>,
+    ) -> Result<ContentLocation> {
+        self.execute(
+            "INSERT INTO content_locations (attachment_id, description, category, location_type, 
+             start_location, end_location, snippet, related_problem_id, related_solution_id, 
+             related_learning_id, related_component_id) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![attachment_id, description, category, location_type, start_location, 
+                    end_location, snippet, related_problem_id, related_solution_id, 
+                    related_learning_id, related_component_id],
+        )?;
+        self.get_content_location(self.conn.last_insert_rowid())
+    }
+
+    pub fn delete_content_location(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM content_locations WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// v1.4: Swap an indexer's previous locations for a fresh set. Locations added by hand
+    /// or by other indexers are left alone. Links to problems, solutions, learnings and
+    /// components follow a location to its replacement with the same description, so a
+    /// problem pinned to a function stays pinned after the file is re-indexed.
+    pub fn replace_indexed_locations(&self, attachment_id: i64, indexer: &str, locations: &[IndexedLocation]) -> Result<Vec<ContentLocation>> {
+        type Links = (Option<i64>, Option<i64>, Option<i64>, Option<i64>);
+        let tx = self.conn.unchecked_transaction()?;
+        let links: HashMap<String, Links> = {
+            let mut stmt = tx.prepare_cached(
+                "SELECT description, related_problem_id, related_solution_id, related_learning_id, related_component_id 
+                 FROM content_locations WHERE attachment_id = ? AND indexer = ? 
+                 AND COALESCE(related_problem_id, related_solution_id, related_learning_id, related_component_id) IS NOT NULL"
+            )?;
+            let rows = stmt.query_map(params![attachment_id, indexer], |row| {
+                Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+            })?;
+            rows.collect::<Result<_>>()?
+        };
+        tx.execute(
+            "DELETE FROM content_locations WHERE attachment_id = ? AND indexer = ?",
+            params![attachment_id, indexer],
+        )?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO content_locations (attachment_id, description, category, location_type, 
+                 start_location, end_location, snippet, indexer, related_problem_id, related_solution_id, 
+                 related_learning_id, related_component_id) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            for l in locations {
+                let (problem, solution, learning, component) = links.get(&l.description).copied().unwrap_or_default();
+                insert.execute(params![
+                    attachment_id, l.description, l.category, l.location_type,
+                    l.start_location, l.end_location, l.snippet, indexer,
+                    problem, solution, learning, component
+                ])?;
+            }
+        }
+        tx.commit()?;
+        
+        Ok(self.get_content_locations_for_attachment(attachment_id)?
+            .into_iter()
+            .filter(|l| l.indexer.as_deref() == Some(indexer))
+            .collect())
+    }
+
+    /// v1.4: Indexed code symbols of a project whose qualified name contains `query`.
+    /// Exact names ("search", "Database::search") rank ahead of partial matches.
+    pub fn search_symbols(&self, project_id: i64, query: &str, limit: i64) -> Result<Vec<ContentLocation>> {
+        let name = query.trim().to_lowercase();
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT l.id, l.attachment_id, l.description, l.category, l.location_type, l.start_location, 
+                    l.end_location, l.snippet, l.related_problem_id, l.related_solution_id, 
+                    l.related_learning_id, l.related_component_id, l.created_at, l.indexer 
+             FROM content_locations l 
+             JOIN attachments a ON l.attachment_id = a.id 
+             WHERE a.project_id = ?1 AND l.indexer = 'symbols' AND INSTR(LOWER(l.description), ?2) > 0 
+             ORDER BY (LOWER(l.description) = ?2 
+                       OR LOWER(l.description) LIKE '%::' || ?2 
+                       OR LOWER(l.description) LIKE '%.' || ?2) DESC, 
+                      LENGTH(l.description), l.description 
+             LIMIT ?3"
+        )?;
+        let locations = stmt.query_map(params![project_id, name, limit], Self::row_to_content_location)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(locations)
+    }
+
+    /// v1.4: Point a location at the problem and/or component it's about, e.g. pin a problem
+    /// to the function that causes it. None clears the link.
+    pub fn link_content_location(&self, id: i64, problem_id: Option<i64>, component_id: Option<i64>) -> Result<ContentLocation> {
+        self.execute(
+            "UPDATE content_locations SET related_problem_id = ?, related_component_id = ? WHERE id = ?",
+            params![problem_id, component_id, id],
+        )?;
+        self.get_content_location(id)
+    }
+
+    // ============================================================
+    // v1.1: EXTRACTION OPERATIONS
+    // ============================================================
+
+    fn row_to_extraction(row: &rusqlite::Row) -> rusqlite::Result<Extraction> {
+        Ok(Extraction {
+            id: row.get(0)?,
+            attachment_id: row.get(1)?,
+            record_type: row.get(2)?,
+            record_id: row.get(3)?,
+            source_location: row.get(4)?,
+            source_snippet: row.get(5)?,
+            confidence: row.get(6)?,
+            user_reviewed: row.get(7)?,
+            user_approved: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+
+    pub fn get_extraction(&self, id: i64) -> Result<Extraction> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, record_type, record_id, source_location, source_snippet, 
+                    confidence, user_reviewed, user_approved, created_at 
+             FROM extractions WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_extraction)
+    }
+
+    pub fn get_extractions_for_attachment(&self, attachment_id: i64) -> Result<Vec<Extraction>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attachment_id, record_type, record_id, source_location, source_snippet, 
+                    confidence, user_reviewed, user_approved, created_at 
+             FROM extractions WHERE attachment_id = ? ORDER BY created_at"
+        )?;
+        let extractions = stmt.query_map(params![attachment_id], Self::row_to_extraction)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(extractions)
+    }
+
+    pub fn create_extraction(
+        &self,
+        attachment_id: i64,
+        record_type: &str,
+        record_id: i64,
+        source_location: Option<&str>,
+        source_snippet: Option<&str>,
+        confidence: Option<f64>,
+    ) -> Result<Extraction> {
+        self.execute(
+            "INSERT INTO extractions (attachment_id, record_type, record_id, source_location, 
+             source_snippet, confidence) VALUES (?, ?, ?, ?, ?, ?)",
+            params![attachment_id, record_type, record_id, source_location, source_snippet, confidence],
+        )?;
+        self.get_extraction(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_extraction_review(&self, id: i64, user_reviewed: bool, user_approved: Option<bool>) -> Result<Extraction> {
+        self.execute(
+            "UPDATE extractions SET user_reviewed = ?, user_approved = ? WHERE id = ?",
+            params![user_reviewed, user_approved, id],
+        )?;
+        self.get_extraction(id)
+    }
+
+    pub fn delete_extraction(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM extractions WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.1: SYNC STATUS OPERATIONS
+    // ============================================================
+
+    fn row_to_sync_status(row: &rusqlite::Row) -> rusqlite::Result<SyncStatus> {
+        Ok(SyncStatus {
+            id: row.get(0)?,
+            device_name: row.get(1)?,
+            device_id: row.get(2)?,
+            remote_url: row.get(3)?,
+            last_sync_at: row.get(4)?,
+            last_sync_commit: row.get(5)?,
+            pending_changes: row.get(6)?,
+            has_conflicts: row.get(7)?,
+            created_at: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    }
+
+    pub fn get_sync_status(&self) -> Result<Option<SyncStatus>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, device_name, device_id, remote_url, last_sync_at, last_sync_commit, 
+                    pending_changes, has_conflicts, created_at, updated_at 
+             FROM sync_status LIMIT 1"
+        )?;
+        match stmt.query_row([], Self::row_to_sync_status) {
+            Ok(s) => Ok(Some(s)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn create_sync_status(&self, device_name: &str, device_id: &str) -> Result<SyncStatus> {
+        self.execute(
+            "INSERT INTO sync_status (device_name, device_id) VALUES (?, ?)",
+            params![device_name, device_id],
+        )?;
+        self.get_sync_status().map(|opt| opt.unwrap())
+    }
+
+    pub fn update_sync_status(
+        &self,
+        remote_url: Option<&str>,
+        last_sync_at: Option<&str>,
+        last_sync_commit: Option<&str>,
+        pending_changes: Option<i64>,
+        has_conflicts: Option<bool>,
+    ) -> Result<SyncStatus> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(url) = remote_url {
+            updates.push("remote_url = ?");
+            values.push(Box::new(url.to_string()));
+        }
+        if let Some(sync_at) = last_sync_at {
+            updates.push("last_sync_at = ?");
+            values.push(Box::new(sync_at.to_string()));
+        }
+        if let Some(commit) = last_sync_commit {
+            updates.push("last_sync_commit = ?");
+            values.push(Box::new(commit.to_string()));
+        }
+        if let Some(pc) = pending_changes {
+            updates.push("pending_changes = ?");
+            values.push(Box::new(pc));
+        }
+        if let Some(hc) = has_conflicts {
+            updates.push("has_conflicts = ?");
+            values.push(Box::new(hc));
+        }
+        
+        if updates.is_empty() {
+            return self.get_sync_status().map(|opt| opt.unwrap());
+        }
+        
+        let sql = format!("UPDATE sync_status SET {}", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_sync_status().map(|opt| opt.unwrap())
+    }
+
+    // ============================================================
+    // v1.1: SYNC HISTORY OPERATIONS
+    // ============================================================
+
+    fn row_to_sync_history(row: &rusqlite::Row) -> rusqlite::Result<SyncHistory> {
+        Ok(SyncHistory {
+            id: row.get(0)?,
+            device_id: row.get(1)?,
+            operation: row.get(2)?,
+            commit_hash: row.get(3)?,
+            files_changed: row.get(4)?,
+            status: row.get(5)?,
+            error_message: row.get(6)?,
+            created_at: row.get(7)?,
+            channel: row.get::<_, Option<String>>(8)?.unwrap_or_else(|| "git".to_string()),
+        })
+    }
+
+    pub fn get_sync_history(&self, limit: i32) -> Result<Vec<SyncHistory>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, device_id, operation, commit_hash, files_changed, status, error_message, created_at, channel 
+             FROM sync_history ORDER BY created_at DESC LIMIT ?"
+        )?;
+        let history = stmt.query_map(params![limit], Self::row_to_sync_history)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(history)
+    }
+
+    pub fn log_sync_operation(
+        &self,
+        device_id: &str,
+        operation: &str,
+        commit_hash: Option<&str>,
+        files_changed: Option<i64>,
+        status: &str,
+        error_message: Option<&str>,
+    ) -> Result<SyncHistory> {
+        self.execute(
+            "INSERT INTO sync_history (device_id, operation, commit_hash, files_changed, status, error_message) 
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![device_id, operation, commit_hash, files_changed, status, error_message],
+        )?;
+        self.get_sync_history_entry(self.conn.last_insert_rowid())
+    }
+
+    /// v1.4: A LAN exchange with the peer `device_id`: "push" for records sent to it, "pull"
+    /// for records received, counted in `records`.
+    pub fn log_lan_sync(&self, device_id: &str, operation: &str, records: i64, status: &str, error_message: Option<&str>) -> Result<SyncHistory> {
+        self.execute(
+            "INSERT INTO sync_history (device_id, operation, files_changed, status, error_message, channel) 
+             VALUES (?, ?, ?, ?, ?, 'lan')",
+            params![device_id, operation, records, status, error_message],
+        )?;
+        self.get_sync_history_entry(self.conn.last_insert_rowid())
+    }
+
+    fn get_sync_history_entry(&self, id: i64) -> Result<SyncHistory> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, device_id, operation, commit_hash, files_changed, status, error_message, created_at, channel 
+             FROM sync_history WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_sync_history)
+    }
+
+    // ============================================================
+    // v1.1: SETTINGS OPERATIONS
+    // ============================================================
+
+    fn row_to_setting(row: &rusqlite::Row) -> rusqlite::Result<Setting> {
+        Ok(Setting {
+            key: row.get(0)?,
+            value: row.get(1)?,
+            category: row.get(2)?,
+            updated_at: row.get(3)?,
+        })
+    }
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare_cached("SELECT value FROM settings WHERE key = ?")?;
+        match stmt.query_row(params![key], |row| row.get::<_, String>(0)) {
+            Ok(v) => Ok(Some(v)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Settings are seeded as JSON but the GUI saves plain strings; accept both.
+    pub fn get_setting_value(&self, key: &str) -> Result<Option<serde_json::Value>> {
+        Ok(self.get_setting(key)?.map(|raw| {
+            serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw))
+        }))
+    }
+
+    /// v1.4: The user's timezone setting; the system's zone when unset or unreadable.
+    pub fn timezone(&self) -> crate::timezone::Zone {
+        let name = match self.get_setting_value(crate::timezone::SETTING) {
+            Ok(Some(serde_json::Value::String(name))) => name,
+            _ => String::new(),
+        };
+        crate::timezone::Zone::parse(&name).unwrap_or(crate::timezone::Zone::System)
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str, category: Option<&str>) -> Result<()> {
+        let category = category.unwrap_or("general");
+        self.execute(
+            "INSERT OR REPLACE INTO settings (key, value, category, updated_at) 
+             VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
+            params![key, value, category],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_all_settings(&self) -> Result<Vec<Setting>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, value, category, updated_at FROM settings ORDER BY category, key"
+        )?;
+        let settings = stmt.query_map([], Self::row_to_setting)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(settings)
+    }
+
+    pub fn get_settings_by_category(&self, category: &str) -> Result<Vec<Setting>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, value, category, updated_at FROM settings WHERE category = ? ORDER BY key"
+        )?;
+        let settings = stmt.query_map(params![category], Self::row_to_setting)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(settings)
+    }
+
+    pub fn delete_setting(&self, key: &str) -> Result<()> {
+        self.execute("DELETE FROM settings WHERE key = ?", params![key])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: RECENT PROJECTS
+    // ============================================================
+
+    /// Move a project to the front of the `recent_projects` setting (a JSON array of ids).
+    pub fn record_project_opened(&self, project_id: i64) -> Result<()> {
+        let mut ids = self.recent_project_ids()?;
+        ids.retain(|id| *id != project_id);
+        ids.insert(0, project_id);
+        ids.truncate(MAX_RECENT_PROJECTS);
+        let value = serde_json::to_string(&ids).unwrap_or_else(|_| "[]".to_string());
+        self.set_setting("recent_projects", &value, Some("general"))
+    }
+
+    /// Recently opened projects, most recent first. Deleted projects drop out.
+    pub fn get_recent_projects(&self, limit: usize) -> Result<Vec<Project>> {
+        let mut projects = Vec::new();
+        for id in self.recent_project_ids()? {
+            match self.get_project(id) {
+                Ok(project) => projects.push(project),
+                Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+                Err(e) => return Err(e),
+            }
+            if projects.len() >= limit {
+                break;
+            }
+        }
+        Ok(projects)
+    }
+
+    pub fn clear_recent_projects(&self) -> Result<()> {
+        self.set_setting("recent_projects", "[]", Some("general"))
+    }
+
+    fn recent_project_ids(&self) -> Result<Vec<i64>> {
+        Ok(self.get_setting_value("recent_projects")?
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+
+    // ============================================================
+    // v1.4: RECENT ITEM OPERATIONS
+    // ============================================================
+
+    /// A frecency score `age_days` after it was last bumped.
+    fn decayed(score: f64, age_days: f64) -> f64 {
+        score * 0.5_f64.powf(age_days.max(0.0) / FRECENCY_HALF_LIFE_DAYS)
+    }
+
+    /// A viewable record's title and project, or QueryReturnedNoRows once it's gone.
+    fn viewed_item(&self, entity_type: &str, entity_id: i64) -> Result<(String, Option<i64>)> {
+        let sql = match entity_type {
+            "project" => "SELECT name, id FROM projects WHERE id = ?",
+            "component" => "SELECT name, project_id FROM components WHERE id = ?",
+            "problem" => "SELECT p.title, c.project_id FROM problems p JOIN components c ON c.id = p.component_id WHERE p.id = ?",
+            "todo" => "SELECT title, project_id FROM todos WHERE id = ?",
+            "learning" => "SELECT insight, project_id FROM learnings WHERE id = ?",
+            "attachment" => "SELECT file_name, project_id FROM attachments WHERE id = ?",
+            "note" => "SELECT COALESCE(title, note_date), project_id FROM notes WHERE id = ?",
+            other => return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Unknown entity type '{}' (expected one of {})", other, VIEWABLE_ENTITIES.join(", ")
+            ))),
+        };
+        self.query_row(sql, params![entity_id], |row| {
+            let title: String = row.get(0)?;
+            Ok((title.lines().next().unwrap_or_default().to_string(), row.get(1)?))
+        })
+    }
+
+    /// Count a view of a record. Its score decays by half every FRECENCY_HALF_LIFE_DAYS and
+    /// each view adds one, so something opened daily this week outranks last month's regular.
+    pub fn record_view(&self, entity_type: &str, entity_id: i64) -> Result<()> {
+        let (_, project_id) = self.viewed_item(entity_type, entity_id)?;
+        let previous = self.query_row(
+            "SELECT score, julianday('now') - julianday(last_viewed_at) FROM item_views WHERE entity_type = ? AND entity_id = ?",
+            params![entity_type, entity_id],
+            |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
+        );
+        let score = match previous {
+            Ok((score, age_days)) => Self::decayed(score, age_days) + 1.0,
+            Err(rusqlite::Error::QueryReturnedNoRows) => 1.0,
+            Err(e) => return Err(e),
+        };
+        self.execute(
+            "INSERT INTO item_views (entity_type, entity_id, project_id, score) VALUES (?, ?, ?, ?)
+             ON CONFLICT(entity_type, entity_id) DO UPDATE SET
+                project_id = excluded.project_id,
+                view_count = view_count + 1,
+                score = excluded.score,
+                last_viewed_at = CURRENT_TIMESTAMP",
+            params![entity_type, entity_id, project_id, score],
+        )?;
+        self.execute(
+            "DELETE FROM item_views WHERE id NOT IN (SELECT id FROM item_views ORDER BY last_viewed_at DESC LIMIT ?)",
+            params![MAX_ITEM_VIEWS],
+        )?;
+        Ok(())
+    }
+
+    /// Viewed records by frecency, highest first. Records deleted since are dropped here.
+    pub fn get_recent_items(&self, project_id: Option<i64>, limit: usize) -> Result<Vec<RecentItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT entity_type, entity_id, view_count, last_viewed_at, score, julianday('now') - julianday(last_viewed_at)
+             FROM item_views WHERE ?1 IS NULL OR project_id = ?1"
+        )?;
+        let mut views = stmt.query_map(params![project_id], |row| {
+            Ok(RecentItem {
+                entity_type: row.get(0)?,
+                entity_id: row.get(1)?,
+                project_id: None,
+                title: String::new(),
+                view_count: row.get(2)?,
+                last_viewed_at: row.get(3)?,
+                score: Self::decayed(row.get(4)?, row.get(5)?),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+        views.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.last_viewed_at.cmp(&a.last_viewed_at)));
+
+        let mut items = Vec::new();
+        for mut item in views {
+            if items.len() >= limit {
+                break;
+            }
+            match self.viewed_item(&item.entity_type, item.entity_id) {
+                Ok((title, project_id)) => {
+                    item.title = title;
+                    item.project_id = project_id;
+                    items.push(item);
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    self.execute(
+                        "DELETE FROM item_views WHERE entity_type = ? AND entity_id = ?",
+                        params![item.entity_type, item.entity_id],
+                    )?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(items)
+    }
+
+    // ============================================================
+    // v1.1: LEGACY (v1.0) DATABASE IMPORT
+    // ============================================================
+
+    /// Copy every v1.0 table from another FlowState database into this one, remapping ids.
+    pub fn import_legacy_database(&self, path: &Path) -> Result<LegacyImportReport> {
+        self.without_author(|| self.import_legacy_tables(path))
+    }
+
+    fn import_legacy_tables(&self, path: &Path) -> Result<LegacyImportReport> {
+        let legacy = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        
+        let legacy_tables: Vec<String> = legacy
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>>>()?;
+        
+        if !legacy_tables.iter().any(|t| t == "projects") {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_NOTADB),
+                Some("Not a FlowState database: no projects table".to_string()),
+            ));
+        }
+        
+        let mut report = LegacyImportReport {
+            source_path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let mut id_maps: HashMap<&'static str, HashMap<i64, i64>> = HashMap::new();
+        
+        let tx = self.conn.unchecked_transaction()?;
+        for spec in LEGACY_TABLES {
+            if legacy_tables.iter().any(|t| t == spec.name) {
+                self.import_legacy_table(&legacy, spec, &mut id_maps, &mut report)?;
+            }
+        }
+        tx.commit()?;
+        
+        // Anything outside the v1.0 mapping is reported rather than silently dropped.
+        // FTS shadow tables are derived data and rebuilt from the imported rows.
+        for table in &legacy_tables {
+            if LEGACY_TABLES.iter().any(|spec| spec.name == table) || table.starts_with("memory_fts") {
+                continue;
+            }
+            let rows: i64 = legacy.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")), [], |row| row.get(0))?;
+            if rows > 0 {
+                report.skipped_tables.push(serde_json::json!({ "table": table, "rows": rows }));
+            }
+        }
+        
+        Ok(report)
+    }
+
+    fn import_legacy_table(
+        &self,
+        legacy: &Connection,
+        spec: &LegacyTable,
+        id_maps: &mut HashMap<&'static str, HashMap<i64, i64>>,
+        report: &mut LegacyImportReport,
+    ) -> Result<()> {
+        let target_columns = Self::table_columns(&self.conn, spec.name)?;
+        let columns: Vec<String> = Self::table_columns(legacy, spec.name)?
+            .into_iter()
+            .filter(|c| c != "id" && c != "uuid" && target_columns.contains(c))
+            .collect();
+        
+        let mut stmt = legacy.prepare(&format!("SELECT id, {} FROM {} ORDER BY id", columns.join(", "), spec.name))?;
+        let mut remaining = stmt.query_map([], |row| {
+            let values = (0..columns.len())
+                .map(|i| row.get::<_, Value>(i + 1))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((row.get::<_, i64>(0)?, values))
+        })?.collect::<Result<Vec<_>>>()?;
+        
+        let insert_sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            spec.name,
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+        let self_reference = spec.foreign_keys.iter()
+            .find(|(_, table, _)| *table == spec.name)
+            .and_then(|(col, _, _)| columns.iter().position(|c| c == col));
+        
+        let mut imported = 0;
+        let mut skipped = 0;
+        let mut force = false;
+        
+        // Rows pointing at another row of the same table wait until that row is in;
+        // when a pass makes no progress the leftovers are imported with the link cleared.
+        while !remaining.is_empty() {
+            let mut deferred = Vec::new();
+            let pending = remaining.len();
+            
+            for (old_id, mut values) in remaining {
+                if let (Some(idx), false) = (self_reference, force) {
+                    if let Value::Integer(parent) = values[idx] {
+                        let parent_mapped = id_maps.get(spec.name).is_some_and(|m| m.contains_key(&parent));
+                        if !parent_mapped {
+                            deferred.push((old_id, values));
+                            continue;
+                        }
+                    }
+                }
+                
+                if let Err(reason) = Self::remap_legacy_row(spec, &columns, &mut values, id_maps) {
+                    skipped += 1;
+                    report.warnings.push(format!("{} #{} skipped: {}", spec.name, old_id, reason));
+                    continue;
+                }
+                
+                if spec.name == "projects" {
+                    self.dedupe_legacy_project_name(&columns, &mut values, report)?;
+                }
+                
+                match self.execute(&insert_sql, params_from_iter(values.iter())) {
+                    Ok(_) => {
+                        id_maps.entry(spec.name).or_default().insert(old_id, self.conn.last_insert_rowid());
+                        imported += 1;
+                    }
+                    Err(e) => {
+                        skipped += 1;
+                        report.warnings.push(format!("{} #{} skipped: {}", spec.name, old_id, e));
+                    }
+                }
+            }
+            
+            force = deferred.len() == pending;
+            remaining = deferred;
+        }
+        
+        report.imported.insert(spec.name.to_string(), imported);
+        if skipped > 0 {
+            report.skipped.insert(spec.name.to_string(), skipped);
+        }
+        Ok(())
+    }
+
+    fn remap_legacy_row(
+        spec: &LegacyTable,
+        columns: &[String],
+        values: &mut [Value],
+        id_maps: &HashMap<&'static str, HashMap<i64, i64>>,
+    ) -> std::result::Result<(), String> {
+        for &(column, table, required) in spec.foreign_keys {
+            let Some(idx) = columns.iter().position(|c| c == column) else { continue };
+            if let Value::Integer(old_ref) = values[idx] {
+                match id_maps.get(table).and_then(|m| m.get(&old_ref)) {
+                    Some(&new_ref) => values[idx] = Value::Integer(new_ref),
+                    None if required => return Err(format!("{} {} was not imported", table, old_ref)),
+                    None => values[idx] = Value::Null,
+                }
+            }
+        }
+        
+        for &(id_column, type_column) in spec.polymorphic_keys {
+            let (Some(id_idx), Some(type_idx)) = (
+                columns.iter().position(|c| c == id_column),
+                columns.iter().position(|c| c == type_column),
+            ) else { continue };
+            
+            let table = match &values[type_idx] {
+                Value::Text(t) => match t.as_str() {
+                    "problem" => "problems",
+                    "solution" => "solutions",
+                    "learning" => "learnings",
+                    "component" => "components",
+                    "change" => "changes",
+                    other => return Err(format!("unknown {} '{}'", type_column, other)),
+                },
+                _ => return Err(format!("missing {}", type_column)),
+            };
+            if let Value::Integer(old_ref) = values[id_idx] {
+                match id_maps.get(table).and_then(|m| m.get(&old_ref)) {
+                    Some(&new_ref) => values[id_idx] = Value::Integer(new_ref),
+                    None => return Err(format!("{} {} was not imported", table, old_ref)),
+                }
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Project names are unique, so an imported project that collides gets a suffix.
+    fn dedupe_legacy_project_name(&self, columns: &[String], values: &mut [Value], report: &mut LegacyImportReport) -> Result<()> {
+        let Some(idx) = columns.iter().position(|c| c == "name") else { return Ok(()) };
+        let Value::Text(original) = values[idx].clone() else { return Ok(()) };
+        
+        let mut candidate = original.clone();
+        let mut counter = 1;
+        while self.query_row("SELECT COUNT(*) FROM projects WHERE name = ? COLLATE NOCASE", params![candidate], |row| row.get::<_, i64>(0))? > 0 {
+            candidate = if counter == 1 {
+                format!("{} (v1.0 import)", original)
+            } else {
+                format!("{} (v1.0 import {})", original, counter)
+            };
+            counter += 1;
+        }
+        
+        if candidate != original {
+            report.renamed_projects.push(serde_json::json!({ "from": original, "to": candidate }));
+            values[idx] = Value::Text(candidate);
+        }
+        Ok(())
+    }
+
+    fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt.query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(columns)
+    }
+
+    // ============================================================
+    // v1.4: SEVERITY SLA OPERATIONS
+    // ============================================================
+
+    pub fn get_sla_thresholds(&self) -> Result<SlaThresholds> {
+        Ok(self.get_setting_value("sla_thresholds_hours")?
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn set_sla_thresholds(&self, thresholds: &SlaThresholds) -> Result<()> {
+        let value = serde_json::to_string(thresholds).unwrap_or_default();
+        self.set_setting("sla_thresholds_hours", &value, Some("general"))
+    }
+
+    /// Unsolved problems older than their severity's threshold, most overdue first.
+    pub fn get_stale_problems(&self, project_id: Option<i64>) -> Result<Vec<StaleProblem>> {
+        let thresholds = self.get_sla_thresholds()?;
+        let problems = self.get_problems_by_status(project_id, None, None, Some(&["open", "investigating", "blocked"]), None)?;
+        
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT (julianday('now') - julianday(created_at)) * 24.0, escalated_at FROM problems WHERE id = ?"
+        )?;
+        let mut stale = Vec::new();
+        for problem in problems {
+            let Some(threshold_hours) = thresholds.for_severity(&problem.severity) else { continue };
+            let (age_hours, escalated_at): (Option<f64>, Option<String>) =
+                stmt.query_row(params![problem.id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            let age_hours = age_hours.unwrap_or(0.0);
+            if age_hours <= threshold_hours {
+                continue;
+            }
+            stale.push(StaleProblem {
+                problem,
+                age_hours,
+                threshold_hours,
+                overdue_hours: age_hours - threshold_hours,
+                escalated: escalated_at.is_some(),
+                escalated_at,
+            });
+        }
+        stale.sort_by(|a, b| b.overdue_hours.partial_cmp(&a.overdue_hours).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(stale)
+    }
+
+    /// Flag stale problems that haven't been escalated yet and return just those.
+    pub fn escalate_stale_problems(&self) -> Result<Vec<StaleProblem>> {
+        let mut newly_escalated = Vec::new();
+        for mut item in self.get_stale_problems(None)? {
+            if item.escalated {
+                continue;
+            }
+            self.execute(
+                "UPDATE problems SET escalated_at = CURRENT_TIMESTAMP WHERE id = ? AND escalated_at IS NULL",
+                params![item.problem.id],
+            )?;
+            item.escalated = true;
+            item.escalated_at = self.query_row(
+                "SELECT escalated_at FROM problems WHERE id = ?",
+                params![item.problem.id],
+                |row| row.get(0),
+            )?;
+            newly_escalated.push(item);
+        }
+        Ok(newly_escalated)
+    }
+
+    // ============================================================
+    // v1.4: PROBLEM BOARD OPERATIONS
+    // ============================================================
+
+    pub fn get_wip_limits(&self) -> Result<WipLimits> {
+        Ok(self.get_setting_value("problem_wip_limits")?
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn set_wip_limits(&self, limits: &WipLimits) -> Result<()> {
+        let value = serde_json::to_string(limits).unwrap_or_default();
+        self.set_setting("problem_wip_limits", &value, Some("general"))
+    }
+
+    /// A project's unsolved problems in swimlanes by `group_by` (one of BOARD_GROUPINGS).
+    /// Severity and age lanes are always all there, in order; component lanes only for
+    /// components with open problems, busiest first.
+    pub fn get_problem_board(&self, project_id: i64, group_by: &str) -> Result<ProblemBoard> {
+        if !BOARD_GROUPINGS.contains(&group_by) {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Unknown grouping '{}'. Expected one of: {}", group_by, BOARD_GROUPINGS.join(", ")
+            )));
+        }
+        let mut problems = self.get_problems_by_status(Some(project_id), None, None, Some(&["open", "investigating", "blocked"]), None)?;
+        let details: HashMap<i64, (String, f64)> = self.conn
+            .prepare_cached(
+                "SELECT p.id, c.name, julianday('now') - julianday(p.created_at) 
+                 FROM problems p JOIN components c ON p.component_id = c.id 
+                 WHERE c.project_id = ?"
+            )?
+            .query_map(params![project_id], |row| {
+                Ok((row.get(0)?, (row.get(1)?, row.get::<_, Option<f64>>(2)?.unwrap_or(0.0))))
+            })?
+            .collect::<Result<_>>()?;
+        let severity_rank = |severity: &str| {
+            SEVERITY_LANES.iter().position(|(s, _)| *s == severity).unwrap_or(SEVERITY_LANES.len())
+        };
+        problems.sort_by(|a, b| {
+            severity_rank(&a.severity).cmp(&severity_rank(&b.severity)).then_with(|| a.created_at.cmp(&b.created_at))
+        });
+        
+        let lane_of = |problem: &Problem| -> String {
+            let (component, age_days) = details.get(&problem.id).cloned().unwrap_or_default();
+            match group_by {
+                "severity" => problem.severity.clone(),
+                "component" => component,
+                _ => AGE_LANES.iter()
+                    .find(|(_, _, days)| age_days < *days)
+                    .map(|(key, _, _)| key.to_string())
+                    .unwrap_or_default(),
+            }
+        };
+        let mut lanes: Vec<(String, String)> = match group_by {
+            "severity" => SEVERITY_LANES.iter().map(|(key, label)| (key.to_string(), label.to_string())).collect(),
+            "age" => AGE_LANES.iter().map(|(key, label, _)| (key.to_string(), label.to_string())).collect(),
+            _ => Vec::new(),
+        };
+        let mut grouped: HashMap<String, Vec<Problem>> = HashMap::new();
+        for problem in problems {
+            let lane = lane_of(&problem);
+            if !lanes.iter().any(|(key, _)| *key == lane) {
+                lanes.push((lane.clone(), lane.clone()));
+            }
+            grouped.entry(lane).or_default().push(problem);
+        }
+        if group_by == "component" {
+            lanes.sort_by(|a, b| {
+                let count = |key: &String| grouped.get(key).map(Vec::len).unwrap_or(0);
+                count(&b.0).cmp(&count(&a.0)).then_with(|| a.0.cmp(&b.0))
+            });
+        }
+        
+        let limits = self.get_wip_limits()?.remove(group_by).unwrap_or_default();
+        let mut board = ProblemBoard { project_id, group_by: group_by.to_string(), total: 0, lanes: Vec::new(), warnings: Vec::new() };
+        for (key, label) in lanes {
+            let problems = grouped.remove(&key).unwrap_or_default();
+            let wip_limit = limits.get(&key).copied();
+            let over_limit = wip_limit.is_some_and(|limit| problems.len() as i64 > limit);
+            if let Some(limit) = wip_limit.filter(|_| over_limit) {
+                board.warnings.push(format!("{}: {} open problems, over the WIP limit of {}", label, problems.len(), limit));
+            }
+            board.total += problems.len();
+            board.lanes.push(ProblemLane { key, label, count: problems.len(), wip_limit, over_limit, problems });
+        }
+        Ok(board)
+    }
+
+    // ============================================================
+    // v1.4: DASHBOARD OPERATIONS
+    // ============================================================
+
+    fn row_to_dashboard_layout(row: &rusqlite::Row) -> rusqlite::Result<DashboardLayout> {
+        let widgets: String = row.get(3)?;
+        Ok(DashboardLayout {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            name: row.get(2)?,
+            widgets: serde_json::from_str(&widgets).unwrap_or_default(),
+            created_at: row.get(4)?,
+            updated_at: row.get(5)?,
+            author: row.get(6)?,
+        })
+    }
+
+    pub fn create_dashboard_layout(&self, project_id: i64, name: &str, widgets: &[DashboardWidget]) -> Result<DashboardLayout> {
+        let widgets = serde_json::to_string(widgets).unwrap_or_else(|_| "[]".to_string());
+        self.execute(
+            "INSERT INTO dashboard_layouts (project_id, name, widgets) VALUES (?, ?, ?)",
+            params![project_id, name, widgets],
+        )?;
+        self.get_dashboard_layout(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_dashboard_layout(&self, id: i64) -> Result<DashboardLayout> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, name, widgets, created_at, updated_at, author FROM dashboard_layouts WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_dashboard_layout)
+    }
+
+    pub fn get_dashboard_layouts(&self, project_id: i64) -> Result<Vec<DashboardLayout>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, name, widgets, created_at, updated_at, author FROM dashboard_layouts 
+             WHERE project_id = ? ORDER BY name COLLATE NOCASE"
+        )?;
+        let layouts = stmt.query_map(params![project_id], Self::row_to_dashboard_layout)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(layouts)
+    }
+
+    /// Rename a layout and/or replace its widgets; whatever is None stays.
+    pub fn update_dashboard_layout(&self, id: i64, name: Option<&str>, widgets: Option<&[DashboardWidget]>) -> Result<DashboardLayout> {
+        let widgets = widgets.map(|w| serde_json::to_string(w).unwrap_or_else(|_| "[]".to_string()));
+        self.execute(
+            "UPDATE dashboard_layouts SET name = COALESCE(?, name), widgets = COALESCE(?, widgets), 
+             updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![name, widgets, id],
+        )?;
+        self.get_dashboard_layout(id)
+    }
+
+    pub fn delete_dashboard_layout(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM dashboard_layouts WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Evaluate every widget of a layout, so the dashboard draws from one call. One widget
+    /// failing only blanks that widget.
+    pub fn get_dashboard_data(&self, layout_id: i64) -> Result<DashboardData> {
+        let layout = self.get_dashboard_layout(layout_id)?;
+        let mut stats = None;
+        let widgets = layout.widgets.iter()
+            .map(|widget| match self.evaluate_widget(layout.project_id, widget, &mut stats) {
+                Ok(data) => WidgetData { widget: widget.clone(), data: Some(data), error: None },
+                Err(e) => WidgetData { widget: widget.clone(), data: None, error: Some(e.to_string()) },
+            })
+            .collect();
+        Ok(DashboardData { layout, widgets })
+    }
+
+    /// `stats` holds the project's stats once a stat card has needed them.
+    fn evaluate_widget(
+        &self,
+        project_id: i64,
+        widget: &DashboardWidget,
+        stats: &mut Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let unknown = |kind: &str, value: &str| rusqlite::Error::InvalidParameterName(format!("Unknown {} '{}'", kind, value));
+        match widget {
+            DashboardWidget::Stat { metric, .. } => {
+                if stats.is_none() {
+                    *stats = Some(self.get_project_stats(project_id)?);
+                }
+                let value = stats.as_ref().and_then(|s| s.get(metric)).ok_or_else(|| unknown("stat", metric))?;
+                Ok(serde_json::json!({ "value": value }))
+            }
+            DashboardWidget::List { entity, filters, limit, .. } => {
+                let limit = limit.unwrap_or(10).clamp(1, MAX_WIDGET_ROWS) as usize;
+                let matches = |wanted: &Option<String>, value: &str| wanted.as_deref().is_none_or(|w| w == value);
+                let in_component = |component_id: Option<i64>| filters.component_id.is_none() || filters.component_id == component_id;
+                let records = match entity.as_str() {
+                    "problems" => serde_json::json!(self.get_all_problems(Some(project_id), filters.component_id, None, None)?
+                        .into_iter()
+                        .filter(|p| matches(&filters.status, &p.status) && matches(&filters.severity, &p.severity))
+                        .take(limit)
+                        .collect::<Vec<_>>()),
+                    "todos" => serde_json::json!(self.get_todos(project_id, filters.status.as_deref(), filters.priority.as_deref(), None, None)?
+                        .into_iter()
+                        .filter(|t| in_component(t.component_id))
+                        .take(limit)
+                        .collect::<Vec<_>>()),
+                    "learnings" => serde_json::json!(self.get_learnings(Some(project_id), filters.category.as_deref(), false, None)?
+                        .into_iter()
+                        .filter(|l| in_component(l.component_id))
+                        .take(limit)
+                        .collect::<Vec<_>>()),
+                    "changes" => serde_json::json!(self.get_all_changes(Some(project_id), filters.component_id)?
+                        .into_iter()
+                        .take(limit)
+                        .collect::<Vec<_>>()),
+                    "alerts" => serde_json::json!(self.get_anomaly_alerts(Some(project_id), false)?
+                        .into_iter()
+                        .take(limit)
+                        .collect::<Vec<_>>()),
+                    other => return Err(unknown("list", other)),
+                };
+                Ok(records)
+            }
+            DashboardWidget::Chart { series, days, .. } => {
+                let sql = match series.as_str() {
+                    "problems_by_severity" => "SELECT p.severity, COUNT(*) FROM problems p JOIN components c ON p.component_id = c.id 
+                         WHERE c.project_id = ?1 AND p.status IN ('open', 'investigating', 'blocked') 
+                         GROUP BY p.severity ORDER BY CASE p.severity WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 ELSE 4 END",
+                    "problems_by_status" => "SELECT p.status, COUNT(*) FROM problems p JOIN components c ON p.component_id = c.id 
+                         WHERE c.project_id = ?1 GROUP BY p.status ORDER BY COUNT(*) DESC",
+                    "todos_by_status" => "SELECT status, COUNT(*) FROM todos WHERE project_id = ?1 GROUP BY status ORDER BY COUNT(*) DESC",
+                    "todos_by_priority" => "SELECT priority, COUNT(*) FROM todos WHERE project_id = ?1 AND status NOT IN ('done', 'cancelled') 
+                         GROUP BY priority ORDER BY CASE priority WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 ELSE 4 END",
+                    "learnings_by_category" => "SELECT COALESCE(category, 'uncategorized'), COUNT(*) FROM learnings WHERE project_id = ?1 
+                         GROUP BY 1 ORDER BY COUNT(*) DESC",
+                    // Every day of the window, quiet ones included, oldest first
+                    "changes_per_day" => "WITH RECURSIVE days(day) AS (
+                             SELECT date('now', ?2) UNION ALL SELECT date(day, '+1 day') FROM days WHERE day < date('now')
+                         )
+                         SELECT day, (SELECT COUNT(*) FROM changes ch JOIN components c ON ch.component_id = c.id 
+                                      WHERE c.project_id = ?1 AND date(ch.created_at) = day) 
+                         FROM days",
+                    other => return Err(unknown("chart", other)),
+                };
+                let window = format!("-{} days", days.unwrap_or(14).clamp(1, MAX_CHART_DAYS) - 1);
+                let mut stmt = self.conn.prepare_cached(sql)?;
+                let points = if series == "changes_per_day" {
+                    stmt.query_map(params![project_id, window], Self::row_to_chart_point)?.collect::<Result<Vec<_>>>()?
+                } else {
+                    stmt.query_map(params![project_id], Self::row_to_chart_point)?.collect::<Result<Vec<_>>>()?
+                };
+                Ok(serde_json::Value::Array(points))
+            }
+        }
+    }
+
+    fn row_to_chart_point(row: &rusqlite::Row) -> rusqlite::Result<serde_json::Value> {
+        let label: String = row.get(0)?;
+        let value: i64 = row.get(1)?;
+        Ok(serde_json::json!({ "label": label, "value": value }))
+    }
+
+    // ============================================================
+    // v1.4: GOAL OPERATIONS
+    // ============================================================
+
+    fn row_to_goal(row: &rusqlite::Row) -> rusqlite::Result<Goal> {
+        Ok(Goal {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            title: row.get(2)?,
+            description: row.get(3)?,
+            start_date: row.get(4)?,
+            target_date: row.get(5)?,
+            status: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+            author: row.get(9)?,
+        })
+    }
+
+    fn row_to_key_result(row: &rusqlite::Row) -> rusqlite::Result<KeyResult> {
+        Ok(KeyResult {
+            id: row.get(0)?,
+            goal_id: row.get(1)?,
+            title: row.get(2)?,
+            metric: row.get(3)?,
+            component_id: row.get(4)?,
+            target_value: row.get(5)?,
+            current_value: row.get(6)?,
+            sort_order: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    }
+
+    /// `start_date` defaults to today; linked key results only count work finished from then.
+    pub fn create_goal(
+        &self,
+        project_id: i64,
+        title: &str,
+        description: Option<&str>,
+        start_date: Option<&str>,
+        target_date: Option<&str>,
+    ) -> Result<Goal> {
+        self.execute(
+            "INSERT INTO goals (project_id, title, description, start_date, target_date) 
+             VALUES (?, ?, ?, COALESCE(?, DATE('now')), ?)",
+            params![project_id, title, description, start_date, target_date],
+        )?;
+        self.get_goal(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_goal(&self, id: i64) -> Result<Goal> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, title, description, start_date, target_date, status, created_at, updated_at, author 
+             FROM goals WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_goal)
+    }
+
+    /// Soonest target date first, goals without one last.
+    pub fn get_goals(&self, project_id: i64, status: Option<&str>) -> Result<Vec<Goal>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, title, description, start_date, target_date, status, created_at, updated_at, author 
+             FROM goals WHERE project_id = ? AND (? IS NULL OR status = ?) 
+             ORDER BY target_date IS NULL, target_date, created_at"
+        )?;
+        let goals = stmt.query_map(params![project_id, status, status], Self::row_to_goal)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(goals)
+    }
+
+    pub fn update_goal(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        description: Option<&str>,
+        target_date: Option<&str>,
+        status: Option<&str>,
+    ) -> Result<Goal> {
+        self.execute(
+            "UPDATE goals SET title = COALESCE(?, title), description = COALESCE(?, description), 
+             target_date = COALESCE(?, target_date), status = COALESCE(?, status), updated_at = CURRENT_TIMESTAMP 
+             WHERE id = ?",
+            params![title, description, target_date, status, id],
+        )?;
+        self.get_goal(id)
+    }
+
+    pub fn delete_goal(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM goals WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    pub fn get_key_result(&self, id: i64) -> Result<KeyResult> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, goal_id, title, metric, component_id, target_value, current_value, sort_order, created_at 
+             FROM key_results WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_key_result)
+    }
+
+    pub fn get_key_results(&self, goal_id: i64) -> Result<Vec<KeyResult>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, goal_id, title, metric, component_id, target_value, current_value, sort_order, created_at 
+             FROM key_results WHERE goal_id = ? ORDER BY sort_order ASC, id ASC"
+        )?;
+        let key_results = stmt.query_map(params![goal_id], Self::row_to_key_result)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(key_results)
+    }
+
+    /// Append a key result to a goal. `component_id` narrows a linked one to that component.
+    pub fn add_key_result(
+        &self,
+        goal_id: i64,
+        title: &str,
+        metric: &str,
+        target_value: f64,
+        component_id: Option<i64>,
+    ) -> Result<KeyResult> {
+        self.execute(
+            "INSERT INTO key_results (goal_id, title, metric, target_value, component_id, sort_order) 
+             VALUES (?, ?, ?, ?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM key_results WHERE goal_id = ?))",
+            params![goal_id, title, metric, target_value, component_id, goal_id],
+        )?;
+        self.get_key_result(self.conn.last_insert_rowid())
+    }
+
+    /// `current_value` only means something for manual key results.
+    pub fn update_key_result(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        target_value: Option<f64>,
+        current_value: Option<f64>,
+    ) -> Result<KeyResult> {
+        self.execute(
+            "UPDATE key_results SET title = COALESCE(?, title), target_value = COALESCE(?, target_value), 
+             current_value = COALESCE(?, current_value) WHERE id = ?",
+            params![title, target_value, current_value, id],
+        )?;
+        self.get_key_result(id)
+    }
+
+    pub fn delete_key_result(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM key_results WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// A goal with each key result's current value, counted for linked ones, and whether
+    /// the goal is keeping pace with its target date.
+    pub fn get_goal_progress(&self, goal_id: i64) -> Result<GoalProgress> {
+        let goal = self.get_goal(goal_id)?;
+        let mut key_results = Vec::new();
+        for key_result in self.get_key_results(goal_id)? {
+            let value = match key_result.metric.as_str() {
+                "todos_completed" => self.query_row(
+                    "SELECT COUNT(*) FROM todos 
+                     WHERE project_id = ?1 AND status = 'done' AND completed_at >= ?2 
+                       AND (?3 IS NULL OR component_id = ?3)",
+                    params![goal.project_id, goal.start_date, key_result.component_id],
+                    |row| row.get::<_, i64>(0),
+                )? as f64,
+                "problems_solved" => self.query_row(
+                    "SELECT COUNT(*) FROM problems p JOIN components c ON p.component_id = c.id 
+                     WHERE c.project_id = ?1 AND p.status = 'solved' AND p.solved_at >= ?2 
+                       AND (?3 IS NULL OR p.component_id = ?3)",
+                    params![goal.project_id, goal.start_date, key_result.component_id],
+                    |row| row.get::<_, i64>(0),
+                )? as f64,
+                _ => key_result.current_value,
+            };
+            let progress = (value / key_result.target_value).clamp(0.0, 1.0);
+            key_results.push(KeyResultProgress { key_result, value, progress });
+        }
+        let progress = if key_results.is_empty() {
+            0.0
+        } else {
+            key_results.iter().map(|kr| kr.progress).sum::<f64>() / key_results.len() as f64
+        };
+
+        // (days since the start, days from start to target, days left), when there's a target
+        let timing: Option<(f64, f64, f64)> = self.query_row(
+            "SELECT julianday(DATE('now')) - julianday(start_date), julianday(target_date) - julianday(start_date), 
+                    julianday(target_date) - julianday(DATE('now')) 
+             FROM goals WHERE id = ?",
+            params![goal_id],
+            |row| {
+                let elapsed: f64 = row.get(0)?;
+                let span: Option<f64> = row.get(1)?;
+                let left: Option<f64> = row.get(2)?;
+                Ok(span.zip(left).map(|(span, left)| (elapsed, span, left)))
+            },
+        )?;
+        let expected_progress = timing.map(|(elapsed, span, _)| {
+            if span <= 0.0 { 1.0 } else { (elapsed / span).clamp(0.0, 1.0) }
+        });
+        Ok(GoalProgress {
+            on_track: expected_progress.map(|expected| goal.status == "achieved" || progress >= expected),
+            days_left: timing.map(|(_, _, left)| left as i64),
+            goal,
+            key_results,
+            progress,
+            expected_progress,
+        })
+    }
+
+    // ============================================================
+    // v1.4: PEOPLE OPERATIONS
+    // ============================================================
+
+    fn row_to_person(row: &rusqlite::Row) -> rusqlite::Result<Person> {
+        Ok(Person {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            email: row.get(2)?,
+            role: row.get(3)?,
+            notes: row.get(4)?,
+            active: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+
+    pub fn get_person(&self, id: i64) -> Result<Person> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, email, role, notes, active, created_at, updated_at FROM people WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_person)
+    }
+
+    pub fn list_people(&self, include_inactive: bool) -> Result<Vec<Person>> {
+        let sql = if include_inactive {
+            "SELECT id, name, email, role, notes, active, created_at, updated_at FROM people ORDER BY name"
+        } else {
+            "SELECT id, name, email, role, notes, active, created_at, updated_at FROM people WHERE active = 1 ORDER BY name"
+        };
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let people = stmt.query_map([], Self::row_to_person)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(people)
+    }
+
+    pub fn create_person(&self, name: &str, email: Option<&str>, role: Option<&str>, notes: Option<&str>) -> Result<Person> {
+        self.execute(
+            "INSERT INTO people (name, email, role, notes) VALUES (?, ?, ?, ?)",
+            params![name, email, role, notes],
+        )?;
+        self.get_person(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_person(&self, id: i64, name: Option<&str>, email: Option<&str>, role: Option<&str>, notes: Option<&str>, active: Option<bool>) -> Result<Person> {
+        let mut updates = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        
+        if let Some(n) = name {
+            updates.push("name = ?");
+            values.push(Box::new(n.to_string()));
+        }
+        if let Some(e) = email {
+            updates.push("email = ?");
+            values.push(Box::new(e.to_string()));
+        }
+        if let Some(r) = role {
+            updates.push("role = ?");
+            values.push(Box::new(r.to_string()));
+        }
+        if let Some(n) = notes {
+            updates.push("notes = ?");
+            values.push(Box::new(n.to_string()));
+        }
+        if let Some(a) = active {
+            updates.push("active = ?");
+            values.push(Box::new(a));
+        }
+        
+        if updates.is_empty() {
+            return self.get_person(id);
+        }
+        
+        values.push(Box::new(id));
+        
+        let sql = format!("UPDATE people SET {} WHERE id = ?", updates.join(", "));
+        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        self.execute(&sql, params.as_slice())?;
+        
+        self.get_person(id)
+    }
+
+    pub fn delete_person(&self, id: i64) -> Result<()> {
+        // Unassign explicitly; ON DELETE SET NULL only applies with foreign keys enabled
+        self.execute("UPDATE todos SET assignee_id = NULL WHERE assignee_id = ?", params![id])?;
+        self.execute("UPDATE problems SET assignee_id = NULL WHERE assignee_id = ?", params![id])?;
+        self.execute("DELETE FROM people WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Assign a todo, or clear its assignee with `person_id` None.
+    pub fn assign_todo(&self, todo_id: i64, person_id: Option<i64>) -> Result<Todo> {
+        self.execute("UPDATE todos SET assignee_id = ? WHERE id = ?", params![person_id, todo_id])?;
+        self.get_todo(todo_id)
+    }
+
+    pub fn assign_problem(&self, problem_id: i64, person_id: Option<i64>) -> Result<Problem> {
+        self.execute("UPDATE problems SET assignee_id = ? WHERE id = ?", params![person_id, problem_id])?;
+        self.get_problem(problem_id)
+    }
+
+    /// Everything assigned to a person across projects. Finished work is left out unless asked for.
+    pub fn get_work_for_person(&self, person_id: i64, include_closed: bool) -> Result<serde_json::Value> {
+        let person = self.get_person(person_id)?;
+        
+        let todo_filter = if include_closed { "" } else { " AND status NOT IN ('done', 'cancelled')" };
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT id, project_id, component_id, title, description, priority, status, due_date, created_at, completed_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'todo' AND cm.entity_id = todos.id), author,
+                    estimate_hours, estimate_points, started_at, time_spent_minutes 
+             FROM todos WHERE assignee_id = ?{}
+             ORDER BY CASE priority WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 WHEN 'low' THEN 4 END, due_date IS NULL, due_date",
+            todo_filter
+        ))?;
+        let todos = stmt.query_map(params![person_id], Self::row_to_todo)?
+            .collect::<Result<Vec<_>>>()?;
+        
+        let problem_filter = if include_closed { "" } else { " AND status NOT IN ('solved', 'wont_fix')" };
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT id, component_id, title, description, status, severity, root_cause, created_at, solved_at, assignee_id,
+                    (SELECT COUNT(*) FROM comments cm WHERE cm.entity_type = 'problem' AND cm.entity_id = p.id), p.author 
+             FROM problems p WHERE assignee_id = ?{}
+             ORDER BY CASE severity WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 WHEN 'low' THEN 4 END, created_at DESC",
+            problem_filter
+        ))?;
+        let problems = stmt.query_map(params![person_id], Self::row_to_problem)?
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(serde_json::json!({
+            "person": person,
+            "todos": todos,
+            "problems": problems,
+        }))
+    }
+
+    // ============================================================
+    // v1.4: COMMENT OPERATIONS
+    // ============================================================
+
+    fn row_to_comment(row: &rusqlite::Row) -> rusqlite::Result<Comment> {
+        Ok(Comment {
+            id: row.get(0)?,
+            entity_type: row.get(1)?,
+            entity_id: row.get(2)?,
+            author: row.get(3)?,
+            body: row.get(4)?,
+            created_at: row.get(5)?,
+            updated_at: row.get(6)?,
+        })
+    }
+
+    pub fn get_comment(&self, id: i64) -> Result<Comment> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, entity_type, entity_id, author, body, created_at, updated_at FROM comments WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_comment)
+    }
+
+    /// A record's thread, oldest first.
+    pub fn get_comments(&self, entity_type: &str, entity_id: i64) -> Result<Vec<Comment>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, entity_type, entity_id, author, body, created_at, updated_at 
+             FROM comments WHERE entity_type = ? AND entity_id = ? ORDER BY created_at ASC, id ASC"
+        )?;
+        let comments = stmt.query_map(params![entity_type, entity_id], Self::row_to_comment)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(comments)
+    }
+
+    pub fn add_comment(&self, entity_type: &str, entity_id: i64, author: Option<&str>, body: &str) -> Result<Comment> {
+        self.execute(
+            "INSERT INTO comments (entity_type, entity_id, author, body) VALUES (?, ?, ?, ?)",
+            params![entity_type, entity_id, author, body],
+        )?;
+        self.get_comment(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_comment(&self, id: i64, body: &str) -> Result<Comment> {
+        self.execute("UPDATE comments SET body = ? WHERE id = ?", params![body, id])?;
+        self.get_comment(id)
+    }
+
+    pub fn delete_comment(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM comments WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: PROBLEM CHECKLIST OPERATIONS
+    // ============================================================
+
+    fn row_to_checklist_item(row: &rusqlite::Row) -> rusqlite::Result<ChecklistItem> {
+        Ok(ChecklistItem {
+            id: row.get(0)?,
+            problem_id: row.get(1)?,
+            text: row.get(2)?,
+            checked: row.get(3)?,
+            sort_order: row.get(4)?,
+            created_at: row.get(5)?,
+            checked_at: row.get(6)?,
+        })
+    }
+
+    pub fn get_checklist_item(&self, id: i64) -> Result<ChecklistItem> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, text, checked, sort_order, created_at, checked_at 
+             FROM problem_checklist_items WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_checklist_item)
+    }
+
+    pub fn get_checklist_items(&self, problem_id: i64) -> Result<Vec<ChecklistItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, problem_id, text, checked, sort_order, created_at, checked_at 
+             FROM problem_checklist_items WHERE problem_id = ? ORDER BY sort_order ASC, id ASC"
+        )?;
+        let items = stmt.query_map(params![problem_id], Self::row_to_checklist_item)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(items)
+    }
+
+    /// Append an item to the end of a problem's checklist.
+    pub fn add_checklist_item(&self, problem_id: i64, text: &str) -> Result<ChecklistItem> {
+        self.execute(
+            "INSERT INTO problem_checklist_items (problem_id, text, sort_order) 
+             VALUES (?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM problem_checklist_items WHERE problem_id = ?))",
+            params![problem_id, text, problem_id],
+        )?;
+        self.get_checklist_item(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_checklist_item(&self, id: i64, text: Option<&str>, checked: Option<bool>) -> Result<ChecklistItem> {
+        if let Some(t) = text {
+            self.execute("UPDATE problem_checklist_items SET text = ? WHERE id = ?", params![t, id])?;
+        }
+        if let Some(c) = checked {
+            self.execute(
+                "UPDATE problem_checklist_items 
+                 SET checked = ?, checked_at = CASE WHEN ? THEN COALESCE(checked_at, CURRENT_TIMESTAMP) ELSE NULL END 
+                 WHERE id = ?",
+                params![c, c, id],
+            )?;
+        }
+        self.get_checklist_item(id)
+    }
+
+    pub fn delete_checklist_item(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM problem_checklist_items WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Rewrite sort order from `ordered_ids`. Items left out keep their relative order after the listed ones.
+    pub fn reorder_checklist_items(&self, problem_id: i64, ordered_ids: &[i64]) -> Result<Vec<ChecklistItem>> {
+        let current = self.get_checklist_items(problem_id)?;
+        // An id listed twice keeps its first place
+        let mut seen = HashSet::new();
+        let mut order: Vec<i64> = ordered_ids.iter()
+            .copied()
+            .filter(|id| current.iter().any(|item| item.id == *id) && seen.insert(*id))
+            .collect();
+        order.extend(current.iter().map(|item| item.id).filter(|id| !ordered_ids.contains(id)));
+        
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, id) in order.iter().enumerate() {
+            tx.execute(
+                "UPDATE problem_checklist_items SET sort_order = ? WHERE id = ? AND problem_id = ?",
+                params![position as i64, id, problem_id],
+            )?;
+        }
+        tx.commit()?;
+        
+        self.get_checklist_items(problem_id)
+    }
+
+    /// v1.4: Log a problem and its starting checklist together, as a template does.
+    pub fn log_problem_with_checklist(&self, component_id: i64, title: &str, description: &str, severity: &str, checklist: &[&str]) -> Result<Problem> {
+        let tx = self.conn.unchecked_transaction()?;
+        let problem = self.log_problem(component_id, title, Some(description), severity)?;
+        for text in checklist {
+            self.add_checklist_item(problem.id, text)?;
+        }
+        tx.commit()?;
+        Ok(problem)
+    }
+
+    // ============================================================
+    // v1.4: TODO TEMPLATE OPERATIONS
+    // ============================================================
+
+    fn row_to_todo_template(row: &rusqlite::Row) -> rusqlite::Result<TodoTemplate> {
+        let items: String = row.get(3)?;
+        Ok(TodoTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            items: serde_json::from_str(&items).unwrap_or_default(),
+            created_at: row.get(4)?,
+            updated_at: row.get(5)?,
+            author: row.get(6)?,
+        })
+    }
+
+    pub fn get_todo_template(&self, id: i64) -> Result<TodoTemplate> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, description, items, created_at, updated_at, author FROM todo_templates WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_todo_template)
+    }
+
+    pub fn get_todo_templates(&self) -> Result<Vec<TodoTemplate>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, description, items, created_at, updated_at, author 
+             FROM todo_templates ORDER BY name COLLATE NOCASE"
+        )?;
+        let templates = stmt.query_map([], Self::row_to_todo_template)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(templates)
+    }
+
+    /// Items trimmed, blanks dropped, as JSON; a template needs a name nobody else has and at
+    /// least one item.
+    fn check_todo_template(&self, name: Option<&str>, items: Option<&[String]>, own_id: Option<i64>) -> Result<Option<String>> {
+        if let Some(name) = name {
+            if name.trim().is_empty() {
+                return Err(rusqlite::Error::InvalidParameterName("A todo template needs a name".to_string()));
+            }
+            let taken: i64 = self.query_row(
+                "SELECT COUNT(*) FROM todo_templates WHERE name = ? COLLATE NOCASE AND id IS NOT ?",
+                params![name.trim(), own_id],
+                |row| row.get(0),
+            )?;
+            if taken > 0 {
+                return Err(Self::conflict(format!("A todo template named '{}' already exists", name.trim())));
+            }
+        }
+        let Some(items) = items else { return Ok(None) };
+        let items: Vec<&str> = items.iter().map(|item| item.trim()).filter(|item| !item.is_empty()).collect();
+        if items.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("A todo template needs at least one item".to_string()));
+        }
+        Ok(Some(serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string())))
+    }
+
+    pub fn create_todo_template(&self, name: &str, description: Option<&str>, items: &[String]) -> Result<TodoTemplate> {
+        let items = self.check_todo_template(Some(name), Some(items), None)?;
+        self.execute(
+            "INSERT INTO todo_templates (name, description, items) VALUES (?, ?, ?)",
+            params![name.trim(), description, items],
+        )?;
+        self.get_todo_template(self.conn.last_insert_rowid())
+    }
+
+    /// `items` replaces the whole list. Checklists already made from the template keep theirs.
+    pub fn update_todo_template(&self, id: i64, name: Option<&str>, description: Option<&str>, items: Option<&[String]>) -> Result<TodoTemplate> {
+        let items = self.check_todo_template(name, items, Some(id))?;
+        self.execute(
+            "UPDATE todo_templates SET name = COALESCE(?, name), description = COALESCE(?, description), 
+             items = COALESCE(?, items), updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![name.map(str::trim), description, items, id],
+        )?;
+        self.get_todo_template(id)
+    }
+
+    pub fn delete_todo_template(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM todo_templates WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// The starter templates, for a database whose todo_templates table was just created.
+    fn seed_todo_templates(&self) -> Result<()> {
+        for (name, description, items) in crate::templates::TODO_TEMPLATES {
+            let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+            self.create_todo_template(name, Some(description), &items)?;
+        }
+        Ok(())
+    }
+
+    /// Make a checklist in a project from a template: one todo per item, in order. `name`
+    /// defaults to the template's; `component_id` and `due_date` apply to every todo.
+    pub fn instantiate_todo_template(
+        &self,
+        template_id: i64,
+        project_id: i64,
+        name: Option<&str>,
+        component_id: Option<i64>,
+        due_date: Option<&str>,
+    ) -> Result<TodoChecklistDetail> {
+        let template = self.get_todo_template(template_id)?;
+        let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(template.name.as_str());
+        let tx = self.conn.unchecked_transaction()?;
+        self.execute(
+            "INSERT INTO todo_checklists (project_id, template_id, name) VALUES (?, ?, ?)",
+            params![project_id, template_id, name],
+        )?;
+        let checklist_id = self.conn.last_insert_rowid();
+        for (position, item) in template.items.iter().enumerate() {
+            let todo = self.add_todo(project_id, item, None, "medium", component_id, due_date)?;
+            self.execute(
+                "INSERT INTO todo_checklist_items (todo_id, checklist_id, sort_order) VALUES (?, ?, ?)",
+                params![todo.id, checklist_id, position as i64],
+            )?;
+        }
+        tx.commit()?;
+        self.get_todo_checklist(checklist_id)
+    }
+
+    fn row_to_todo_checklist(row: &rusqlite::Row) -> rusqlite::Result<TodoChecklist> {
+        let total: i64 = row.get(5)?;
+        let open: i64 = row.get(7)?;
+        Ok(TodoChecklist {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            template_id: row.get(2)?,
+            name: row.get(3)?,
+            created_at: row.get(4)?,
+            total,
+            done: row.get(6)?,
+            open,
+            complete: total > 0 && open == 0,
+        })
+    }
+
+    pub fn get_todo_checklist(&self, id: i64) -> Result<TodoChecklistDetail> {
+        let checklist = self.query_row(
+            "SELECT c.id, c.project_id, c.template_id, c.name, c.created_at, COUNT(t.id), 
+                    COALESCE(SUM(t.status = 'done'), 0), COALESCE(SUM(t.status NOT IN ('done', 'cancelled')), 0) 
+             FROM todo_checklists c 
+             LEFT JOIN todo_checklist_items i ON i.checklist_id = c.id 
+             LEFT JOIN todos t ON t.id = i.todo_id 
+             WHERE c.id = ? GROUP BY c.id",
+            params![id],
+            Self::row_to_todo_checklist,
+        )?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT todo_id FROM todo_checklist_items WHERE checklist_id = ? ORDER BY sort_order, todo_id"
+        )?;
+        let todo_ids = stmt.query_map(params![id], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>>>()?;
+        let todos = todo_ids.into_iter()
+            .map(|todo_id| self.get_todo(todo_id))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TodoChecklistDetail { checklist, todos })
+    }
+
+    /// A project's checklists with their progress, unfinished ones first, newest first within each.
+    pub fn get_todo_checklists(&self, project_id: i64) -> Result<Vec<TodoChecklist>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT c.id, c.project_id, c.template_id, c.name, c.created_at, COUNT(t.id), 
+                    COALESCE(SUM(t.status = 'done'), 0), COALESCE(SUM(t.status NOT IN ('done', 'cancelled')), 0) AS open 
+             FROM todo_checklists c 
+             LEFT JOIN todo_checklist_items i ON i.checklist_id = c.id 
+             LEFT JOIN todos t ON t.id = i.todo_id 
+             WHERE c.project_id = ? GROUP BY c.id 
+             ORDER BY open = 0, c.created_at DESC, c.id DESC"
+        )?;
+        let checklists = stmt.query_map(params![project_id], Self::row_to_todo_checklist)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(checklists)
+    }
+
+    /// Delete a checklist and, unless `keep_todos`, the todos it made.
+    pub fn delete_todo_checklist(&self, id: i64, keep_todos: bool) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if !keep_todos {
+            self.execute(
+                "DELETE FROM todos WHERE id IN (SELECT todo_id FROM todo_checklist_items WHERE checklist_id = ?)",
+                params![id],
+            )?;
+        }
+        self.execute("DELETE FROM todo_checklists WHERE id = ?", params![id])?;
+        tx.commit()
+    }
+
+    // ============================================================
+    // v1.4: COMPONENT PATH OPERATIONS
+    // ============================================================
+
+    fn row_to_component_path(row: &rusqlite::Row) -> rusqlite::Result<ComponentPath> {
+        Ok(ComponentPath {
+            id: row.get(0)?,
+            component_id: row.get(1)?,
+            pattern: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+
+    pub fn get_component_paths(&self, project_id: i64) -> Result<Vec<ComponentPath>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT cp.id, cp.component_id, cp.pattern, cp.created_at 
+             FROM component_paths cp JOIN components c ON c.id = cp.component_id 
+             WHERE c.project_id = ? ORDER BY cp.pattern, cp.id"
+        )?;
+        let paths = stmt.query_map(params![project_id], Self::row_to_component_path)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(paths)
+    }
+
+    /// Map `pattern` to a component; adding a mapping it already has is a no-op.
+    pub fn add_component_path(&self, component_id: i64, pattern: &str) -> Result<ComponentPath> {
+        self.execute(
+            "INSERT INTO component_paths (component_id, pattern) VALUES (?, ?) ON CONFLICT(component_id, pattern) DO NOTHING",
+            params![component_id, pattern],
+        )?;
+        self.query_row(
+            "SELECT id, component_id, pattern, created_at FROM component_paths WHERE component_id = ? AND pattern = ?",
+            params![component_id, pattern],
+            Self::row_to_component_path,
+        )
+    }
+
+    pub fn delete_component_path(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM component_paths WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// The project's component paths, ready to say which component a file belongs to.
+    pub fn component_attribution(&self, project_id: i64) -> Result<crate::componentpaths::Attribution> {
+        let project = self.get_project(project_id)?;
+        Ok(crate::componentpaths::Attribution::new(project.repo_path.as_deref(), self.get_component_paths(project_id)?))
+    }
+
+    /// File commits as changes on the components whose paths they touched, dated when they were
+    /// made and credited to their author. Commits imported before, found through their
+    /// integration link, are skipped.
+    pub fn import_commits(&self, project_id: i64, commits: &[crate::commitlog::Commit]) -> Result<CommitImport> {
+        let attribution = self.component_attribution(project_id)?;
+        let mut import = CommitImport { imported: 0, already_imported: 0, unattributed: 0, changes: Vec::new() };
+        let tx = self.conn.unchecked_transaction()?;
+        // Oldest first, so change ids follow commit order
+        for commit in commits.iter().rev() {
+            let seen: i64 = self.query_row(
+                "SELECT COUNT(*) FROM integration_links WHERE integration = 'git' AND entity_type = 'change' AND external_id = ?",
+                params![commit.hash],
+                |row| row.get(0),
+            )?;
+            if seen > 0 {
+                import.already_imported += 1;
+                continue;
+            }
+            let mut by_component: BTreeMap<i64, Vec<&str>> = BTreeMap::new();
+            for file in &commit.files {
+                if let Some(component_id) = attribution.component_for(file) {
+                    by_component.entry(component_id).or_default().push(file);
+                }
+            }
+            if by_component.is_empty() {
+                import.unattributed += 1;
+                continue;
+            }
+            for (component_id, files) in by_component {
+                let mut listed = files.iter().take(10).copied().collect::<Vec<_>>().join(", ");
+                if files.len() > 10 {
+                    listed.push_str(&format!(" and {} more", files.len() - 10));
+                }
+                self.execute(
+                    "INSERT INTO changes (component_id, field_name, old_value, new_value, change_type, reason, created_at, author) 
+                     VALUES (?, 'commit', NULL, ?, ?, ?, ?, ?)",
+                    params![
+                        component_id,
+                        format!("{} {}", commit.short_hash(), commit.subject),
+                        crate::commitlog::change_type(&files),
+                        listed,
+                        crate::timezone::stored(commit.committed_at),
+                        commit.author,
+                    ],
+                )?;
+                let change_id = self.conn.last_insert_rowid();
+                self.upsert_integration_link("git", "change", change_id, &commit.hash, None)?;
+                import.changes.push(self.get_change(change_id)?);
+            }
+            import.imported += 1;
+        }
+        tx.commit()?;
+        Ok(import)
+    }
+
+    /// Create the accepted suggestions as components, nested as suggested, each mapped to
+    /// everything under its path, and remember `repo_path` as where the project's code lives.
+    /// A suggestion naming a component the project already has maps its path to that one.
+    pub fn import_repo_components(
+        &self,
+        project_id: i64,
+        repo_path: &str,
+        suggestions: &[crate::reposcan::SuggestedComponent],
+    ) -> Result<Vec<Component>> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.set_project_repo_path(project_id, Some(repo_path))?;
+        let mut imported = Vec::new();
+        let mut pending: Vec<(Option<i64>, &crate::reposcan::SuggestedComponent)> = suggestions.iter().rev().map(|s| (None, s)).collect();
+        while let Some((parent_id, suggestion)) = pending.pop() {
+            let existing = match self.query_row(
+                "SELECT id FROM components WHERE project_id = ? AND name = ? COLLATE NOCASE ORDER BY id LIMIT 1",
+                params![project_id, suggestion.name],
+                |row| row.get::<_, i64>(0),
+            ) {
+                Ok(id) => Some(id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+            let component = match existing {
+                Some(id) => self.get_component(id)?,
+                None => self.create_component(project_id, &suggestion.name, suggestion.description.as_deref(), parent_id)?,
+            };
+            self.add_component_path(component.id, &format!("{}/**", suggestion.path.trim_end_matches('/')))?;
+            pending.extend(suggestion.children.iter().rev().map(|child| (Some(component.id), child)));
+            imported.push(component);
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+
+    // ============================================================
+    // v1.4: DEPENDENCY SNAPSHOT OPERATIONS
+    // ============================================================
+
+    fn row_to_dependency_snapshot(row: &rusqlite::Row) -> rusqlite::Result<DependencySnapshot> {
+        Ok(DependencySnapshot {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            repo_path: row.get(2)?,
+            dependency_count: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    pub fn get_dependency_snapshot(&self, id: i64) -> Result<DependencySnapshot> {
+        self.query_row(
+            "SELECT id, project_id, repo_path, json_array_length(dependencies), created_at FROM dependency_snapshots WHERE id = ?",
+            params![id],
+            Self::row_to_dependency_snapshot,
+        )
+    }
+
+    /// The project's snapshots, newest first.
+    pub fn get_dependency_snapshots(&self, project_id: i64) -> Result<Vec<DependencySnapshot>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, repo_path, json_array_length(dependencies), created_at 
+             FROM dependency_snapshots WHERE project_id = ? ORDER BY id DESC"
+        )?;
+        let snapshots = stmt.query_map(params![project_id], Self::row_to_dependency_snapshot)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(snapshots)
+    }
+
+    pub fn get_snapshot_dependencies(&self, id: i64) -> Result<Vec<crate::dependencies::Dependency>> {
+        let json: String = self.query_row("SELECT dependencies FROM dependency_snapshots WHERE id = ?", params![id], |row| row.get(0))?;
+        Ok(serde_json::from_str(&json).unwrap_or_default())
+    }
+
+    pub fn delete_dependency_snapshot(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM dependency_snapshots WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Store `dependencies` as the project's latest snapshot and log what changed since the one
+    /// before as dependency changes, on the component whose paths cover the lockfile or else
+    /// on the project's Dependencies component, created the first time it's needed.
+    pub fn snapshot_dependencies(
+        &self,
+        project_id: i64,
+        repo_path: &str,
+        dependencies: &[crate::dependencies::Dependency],
+    ) -> Result<DependencySnapshotResult> {
+        let previous = match self.query_row(
+            "SELECT id FROM dependency_snapshots WHERE project_id = ? ORDER BY id DESC LIMIT 1",
+            params![project_id],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(id) => Some((id, self.get_snapshot_dependencies(id)?)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+        if let Some((id, before)) = &previous {
+            if before == dependencies {
+                return Ok(DependencySnapshotResult {
+                    snapshot: self.get_dependency_snapshot(*id)?,
+                    created: false,
+                    dependency_changes: Vec::new(),
+                    changes: Vec::new(),
+                });
+            }
+        }
+        let json = serde_json::to_string(dependencies).unwrap_or_else(|_| "[]".to_string());
+        let dependency_changes = previous
+            .map(|(_, before)| crate::dependencies::diff(&before, dependencies))
+            .unwrap_or_default();
+        let attribution = self.component_attribution(project_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+        self.execute(
+            "INSERT INTO dependency_snapshots (project_id, repo_path, dependencies) VALUES (?, ?, ?)",
+            params![project_id, repo_path, json],
+        )?;
+        let snapshot = self.get_dependency_snapshot(self.conn.last_insert_rowid())?;
+        let mut fallback = None;
+        let mut changes = Vec::new();
+        for change in &dependency_changes {
+            let component_id = match (attribution.component_for(&change.lockfile), fallback) {
+                (Some(id), _) | (None, Some(id)) => id,
+                (None, None) => {
+                    let id = self.dependencies_component(project_id)?;
+                    fallback = Some(id);
+                    id
+                }
+            };
+            changes.push(self.log_change(
+                component_id,
+                &change.name,
+                change.old_version.as_deref(),
+                change.new_version.as_deref(),
+                "dependency",
+                Some(&format!("{} ({})", change.summary(), change.lockfile)),
+            )?);
+        }
+        tx.commit()?;
+        Ok(DependencySnapshotResult { snapshot, created: true, dependency_changes, changes })
+    }
+
+    fn dependencies_component(&self, project_id: i64) -> Result<i64> {
+        match self.query_row(
+            "SELECT id FROM components WHERE project_id = ? AND name = ? COLLATE NOCASE ORDER BY id LIMIT 1",
+            params![project_id, DEPENDENCIES_COMPONENT],
+            |row| row.get(0),
+        ) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let description = "Third-party packages locked by the project's Cargo.lock and package-lock.json files";
+                Ok(self.create_component(project_id, DEPENDENCIES_COMPONENT, Some(description), None)?.id)
+            }
+            other => other,
+        }
+    }
+
+    // ============================================================
+    // v1.4: TEST RESULT OPERATIONS
+    // ============================================================
+
+    fn row_to_test_run(row: &rusqlite::Row) -> rusqlite::Result<TestRun> {
+        Ok(TestRun {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            format: row.get(2)?,
+            name: row.get(3)?,
+            total: row.get(4)?,
+            passed: row.get(5)?,
+            failed: row.get(6)?,
+            skipped: row.get(7)?,
+            duration_ms: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+
+    fn row_to_test_failure(row: &rusqlite::Row) -> rusqlite::Result<TestFailure> {
+        Ok(TestFailure {
+            id: row.get(0)?,
+            run_id: row.get(1)?,
+            test_name: row.get(2)?,
+            message: row.get(3)?,
+            output: row.get(4)?,
+            duration_ms: row.get(5)?,
+            problem_id: row.get(6)?,
+        })
+    }
+
+    pub fn get_test_run(&self, id: i64) -> Result<TestRunDetail> {
+        let run = self.query_row(
+            "SELECT id, project_id, format, name, total, passed, failed, skipped, duration_ms, created_at FROM test_runs WHERE id = ?",
+            params![id],
+            Self::row_to_test_run,
+        )?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, run_id, test_name, message, output, duration_ms, problem_id FROM test_failures WHERE run_id = ? ORDER BY test_name, id"
+        )?;
+        let failures = stmt.query_map(params![id], Self::row_to_test_failure)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TestRunDetail { run, failures })
+    }
+
+    /// The project's runs, newest first.
+    pub fn get_test_runs(&self, project_id: i64, limit: i64) -> Result<Vec<TestRun>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, format, name, total, passed, failed, skipped, duration_ms, created_at 
+             FROM test_runs WHERE project_id = ? ORDER BY id DESC LIMIT ?"
+        )?;
+        let runs = stmt.query_map(params![project_id, limit], Self::row_to_test_run)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    pub fn delete_test_run(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM test_runs WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Store a parsed report as a run of the project. A test failing again after an earlier
+    /// run is linked, with its earlier failures, to the open problem about it: the one its
+    /// failures were linked to before, or else the newest open problem naming the test.
+    pub fn ingest_test_run(&self, project_id: i64, format: &str, name: Option<&str>, run: &crate::testresults::ParsedRun) -> Result<TestRunDetail> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.execute(
+            "INSERT INTO test_runs (project_id, format, name, total, passed, failed, skipped, duration_ms) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![project_id, format, name, run.total, run.passed, run.failed, run.skipped, run.duration_ms],
+        )?;
+        let run_id = self.conn.last_insert_rowid();
+        for failure in &run.failures {
+            self.execute(
+                "INSERT INTO test_failures (run_id, test_name, message, output, duration_ms) VALUES (?, ?, ?, ?, ?)",
+                params![run_id, failure.test_name, failure.message, failure.output, failure.duration_ms],
+            )?;
+        }
+        let failing: BTreeSet<&str> = run.failures.iter().map(|f| f.test_name.as_str()).collect();
+        for test_name in failing {
+            let earlier: Option<Option<i64>> = match self.query_row(
+                "SELECT f.problem_id FROM test_failures f JOIN test_runs r ON r.id = f.run_id 
+                 WHERE r.project_id = ? AND f.test_name = ? AND f.run_id < ? ORDER BY f.id DESC LIMIT 1",
+                params![project_id, test_name, run_id],
+                |row| row.get(0),
+            ) {
+                Ok(problem_id) => Some(problem_id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+            let Some(linked) = earlier else { continue };
+            let still_open = |problem_id: i64| -> Result<bool> {
+                self.query_row(
+                    "SELECT COUNT(*) FROM problems WHERE id = ? AND status NOT IN ('solved', 'wont_fix')",
+                    params![problem_id],
+                    |row| row.get::<_, i64>(0).map(|n| n > 0),
+                )
+            };
+            let problem_id = match linked {
+                Some(id) if still_open(id)? => Some(id),
+                _ => match self.query_row(
+                    "SELECT p.id FROM problems p JOIN components c ON c.id = p.component_id 
+                     WHERE c.project_id = ? AND p.status NOT IN ('solved', 'wont_fix') 
+                       AND (instr(p.title, ?2) > 0 OR instr(COALESCE(p.description, ''), ?2) > 0) 
+                     ORDER BY p.id DESC LIMIT 1",
+                    params![project_id, test_name],
+                    |row| row.get::<_, i64>(0),
+                ) {
+                    Ok(id) => Some(id),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                    Err(e) => return Err(e),
+                },
+            };
+            if let Some(problem_id) = problem_id {
+                self.execute(
+                    "UPDATE test_failures SET problem_id = ? 
+                     WHERE test_name = ? AND problem_id IS NULL 
+                       AND run_id IN (SELECT id FROM test_runs WHERE project_id = ?)",
+                    params![problem_id, test_name, project_id],
+                )?;
+            }
+        }
+        tx.commit()?;
+        self.get_test_run(run_id)
+    }
+
+    /// Tests that failed, then passed, then failed again in the project's latest `window` runs
+    /// (FLAKY_TEST_WINDOW by default), flakiest first. A test missing from a run's failures
+    /// counts as passing it.
+    pub fn get_flaky_tests(&self, project_id: i64, window: Option<i64>) -> Result<Vec<FlakyTest>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, created_at FROM test_runs WHERE project_id = ? ORDER BY id DESC LIMIT ?"
+        )?;
+        let mut runs = stmt.query_map(params![project_id, window.unwrap_or(FLAKY_TEST_WINDOW).max(1)], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?.collect::<Result<Vec<_>>>()?;
+        runs.reverse();
+        let Some((oldest, _)) = runs.first() else { return Ok(Vec::new()) };
+        let position: HashMap<i64, usize> = runs.iter().enumerate().map(|(i, (id, _))| (*id, i)).collect();
+
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT f.run_id, f.test_name, f.message, f.problem_id FROM test_failures f JOIN test_runs r ON r.id = f.run_id 
+             WHERE r.project_id = ? AND r.id >= ? ORDER BY f.run_id, f.id"
+        )?;
+        let rows = stmt.query_map(params![project_id, oldest], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?, row.get::<_, Option<i64>>(3)?))
+        })?.collect::<Result<Vec<_>>>()?;
+        #[derive(Default)]
+        struct Failures {
+            failed_in: Vec<usize>, // Positions in `runs`
+            last_message: Option<String>,
+            problem_id: Option<i64>,
+        }
+        let mut by_test: BTreeMap<String, Failures> = BTreeMap::new();
+        for (run_id, test_name, message, problem_id) in rows {
+            let failures = by_test.entry(test_name).or_default();
+            let at = position[&run_id];
+            if failures.failed_in.last() != Some(&at) {
+                failures.failed_in.push(at);
+            }
+            failures.last_message = message;
+            failures.problem_id = problem_id.or(failures.problem_id);
+        }
+
+        let mut flaky: Vec<FlakyTest> = by_test.into_iter()
+            .filter_map(|(test_name, Failures { failed_in, last_message, problem_id })| {
+                let (first, last) = (*failed_in.first()?, *failed_in.last()?);
+                let passed_between = last - first + 1 - failed_in.len();
+                if passed_between == 0 {
+                    return None;
+                }
+                let runs_since = (runs.len() - first) as i64;
+                Some(FlakyTest {
+                    test_name,
+                    failures: failed_in.len() as i64,
+                    runs: runs_since,
+                    failure_rate: failed_in.len() as f64 / runs_since as f64,
+                    last_failed_at: runs[last].1.clone(),
+                    last_message,
+                    problem_id,
+                })
+            })
+            .collect();
+        flaky.sort_by(|a, b| {
+            b.failure_rate.total_cmp(&a.failure_rate)
+                .then(b.failures.cmp(&a.failures))
+                .then_with(|| a.test_name.cmp(&b.test_name))
+        });
+        Ok(flaky)
+    }
+
+    // ============================================================
+    // v1.4: CI STATUS OPERATIONS
+    // ============================================================
+
+    fn row_to_ci_source(row: &rusqlite::Row) -> rusqlite::Result<CiSource> {
+        Ok(CiSource {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            provider: row.get(2)?,
+            target: row.get(3)?,
+            branch: row.get(4)?,
+            workflow: row.get(5)?,
+            enabled: row.get(6)?,
+            last_polled_at: row.get(7)?,
+            last_error: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+
+    fn row_to_ci_run(row: &rusqlite::Row) -> rusqlite::Result<CiRun> {
+        Ok(CiRun {
+            id: row.get(0)?,
+            source_id: row.get(1)?,
+            external_id: row.get(2)?,
+            status: row.get(3)?,
+            title: row.get(4)?,
+            branch: row.get(5)?,
+            commit_sha: row.get(6)?,
+            url: row.get(7)?,
+            started_at: row.get(8)?,
+            completed_at: row.get(9)?,
+        })
+    }
+
+    pub fn get_ci_source(&self, id: i64) -> Result<CiSource> {
+        self.query_row(
+            "SELECT id, project_id, provider, target, branch, workflow, enabled, last_polled_at, last_error, created_at FROM ci_sources WHERE id = ?",
+            params![id],
+            Self::row_to_ci_source,
+        )
+    }
+
+    /// One project's sources, or every project's.
+    pub fn get_ci_sources(&self, project_id: Option<i64>) -> Result<Vec<CiSource>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, provider, target, branch, workflow, enabled, last_polled_at, last_error, created_at 
+             FROM ci_sources WHERE ?1 IS NULL OR project_id = ?1 ORDER BY project_id, id"
+        )?;
+        let sources = stmt.query_map(params![project_id], Self::row_to_ci_source)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(sources)
+    }
+
+    pub fn add_ci_source(&self, project_id: i64, provider: &str, target: &str, branch: Option<&str>, workflow: Option<&str>) -> Result<CiSource> {
+        self.execute(
+            "INSERT INTO ci_sources (project_id, provider, target, branch, workflow) VALUES (?, ?, ?, ?, ?)",
+            params![project_id, provider, target, branch, workflow],
+        )?;
+        self.get_ci_source(self.conn.last_insert_rowid())
+    }
+
+    pub fn set_ci_source_enabled(&self, id: i64, enabled: bool) -> Result<CiSource> {
+        self.execute("UPDATE ci_sources SET enabled = ? WHERE id = ?", params![enabled, id])?;
+        self.get_ci_source(id)
+    }
+
+    pub fn delete_ci_source(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM ci_sources WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// A source's runs, newest first.
+    pub fn get_ci_runs(&self, source_id: i64, limit: i64) -> Result<Vec<CiRun>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, source_id, external_id, status, title, branch, commit_sha, url, started_at, completed_at 
+             FROM ci_runs WHERE source_id = ? ORDER BY started_at DESC, id DESC LIMIT ?"
+        )?;
+        let runs = stmt.query_map(params![source_id, limit], Self::row_to_ci_run)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    /// Store what a poll found. Runs seen before are updated (a running one may have
+    /// finished); a badge reading is only kept when it differs from the last one.
+    pub fn record_ci_runs(&self, source_id: i64, runs: &[crate::ci::FetchedRun]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for run in runs {
+            let started_at = run.started_at.map(crate::timezone::stored);
+            let completed_at = run.completed_at.map(crate::timezone::stored);
+            let external_id = match &run.external_id {
+                Some(id) => id.clone(),
+                None => {
+                    let last = self.get_ci_runs(source_id, 1)?;
+                    if last.first().is_some_and(|r| r.status == run.status) {
+                        continue;
+                    }
+                    crate::timezone::stored(chrono::Utc::now())
+                }
+            };
+            let started_at = started_at.or_else(|| run.external_id.is_none().then(|| external_id.clone()));
+            self.execute(
+                "INSERT INTO ci_runs (source_id, external_id, status, title, branch, commit_sha, url, started_at, completed_at) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) 
+                 ON CONFLICT(source_id, external_id) DO UPDATE SET 
+                    status = excluded.status, 
+                    title = excluded.title, 
+                    started_at = excluded.started_at, 
+                    completed_at = excluded.completed_at",
+                params![source_id, external_id, run.status, run.title, run.branch, run.commit_sha, run.url, started_at, completed_at],
+            )?;
+        }
+        self.execute("UPDATE ci_sources SET last_polled_at = CURRENT_TIMESTAMP, last_error = NULL WHERE id = ?", params![source_id])?;
+        tx.commit()
+    }
+
+    pub fn record_ci_error(&self, source_id: i64, error: &str) -> Result<()> {
+        self.execute("UPDATE ci_sources SET last_polled_at = CURRENT_TIMESTAMP, last_error = ? WHERE id = ?", params![error, source_id])?;
+        Ok(())
+    }
+
+    pub fn get_ci_status(&self, source_id: i64) -> Result<CiStatus> {
+        let source = self.get_ci_source(source_id)?;
+        let runs = self.get_ci_runs(source_id, 200)?;
+        let latest_run = runs.first().cloned();
+        let mut decided = runs.into_iter().filter(|r| r.status == "success" || r.status == "failure").peekable();
+        let state = match decided.peek().map(|r| r.status.as_str()) {
+            Some("success") => "green",
+            Some(_) => "red",
+            None => "unknown",
+        };
+        let mut red_since = None;
+        while let Some(run) = decided.next_if(|r| r.status == "failure") {
+            red_since = Some(run);
+        }
+        let last_green = decided.next();
+
+        let mut status = CiStatus {
+            source,
+            state: state.to_string(),
+            latest_run,
+            red_since: None,
+            last_green,
+            culprit: None,
+            suspect_changes: Vec::new(),
+            summary: match state {
+                "green" => "CI green".to_string(),
+                _ => "No CI results yet".to_string(),
+            },
+        };
+        let Some(red) = red_since else { return Ok(status) };
+        let red_at = red.started_at.clone().unwrap_or_else(|| crate::timezone::stored(chrono::Utc::now()));
+        let since = match status.last_green.as_ref().and_then(|g| g.started_at.clone()) {
+            Some(green_at) => green_at,
+            None => crate::timezone::parse_stored(&red_at)
+                .map(|t| crate::timezone::stored(t - chrono::Duration::hours(CI_LOOKBACK_HOURS)))
+                .unwrap_or_else(|| red_at.clone()),
+        };
+        let mut suspects = self.get_recent_changes(Some(status.source.project_id), None, &since, Some(&red_at), None)?;
+        suspects.truncate(MAX_CI_SUSPECT_CHANGES);
+        let built = match &red.commit_sha {
+            Some(sha) => self.change_for_commit(status.source.project_id, sha)?,
+            None => None,
+        };
+        status.culprit = built.or_else(|| suspects.first().cloned());
+        status.summary = match &status.culprit {
+            Some(change) => format!("CI red since change #{}: {}", change.id, describe_change(change)),
+            None => format!("CI red since {}", red_at),
+        };
+        if suspects.len() > 1 {
+            status.summary.push_str(&format!(" ({} changes since the last green run)", suspects.len()));
+        }
+        status.suspect_changes = suspects;
+        status.red_since = Some(red);
+        Ok(status)
+    }
+
+    pub fn get_project_ci_status(&self, project_id: i64) -> Result<Vec<CiStatus>> {
+        self.get_ci_sources(Some(project_id))?.iter().map(|s| self.get_ci_status(s.id)).collect()
+    }
+
+    // ============================================================
+    // v1.4: BENCHMARK OPERATIONS
+    // ============================================================
+
+    fn row_to_benchmark(row: &rusqlite::Row) -> rusqlite::Result<Benchmark> {
+        Ok(Benchmark {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            name: row.get(2)?,
+            value: row.get(3)?,
+            unit: row.get(4)?,
+            commit_sha: row.get(5)?,
+            change_id: row.get(6)?,
+            regression_change_id: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    }
+
+    pub fn get_benchmark(&self, id: i64) -> Result<Benchmark> {
+        self.query_row(
+            "SELECT id, project_id, name, value, unit, commit_sha, change_id, regression_change_id, created_at FROM benchmarks WHERE id = ?",
+            params![id],
+            Self::row_to_benchmark,
+        )
+    }
+
+    /// Every measurement of a benchmark, oldest first, in one project or all of them.
+    pub fn get_benchmark_series(&self, project_id: Option<i64>, name: &str) -> Result<Vec<Benchmark>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, name, value, unit, commit_sha, change_id, regression_change_id, created_at 
+             FROM benchmarks WHERE name = ?1 AND (?2 IS NULL OR project_id = ?2) ORDER BY created_at, id"
+        )?;
+        let series = stmt.query_map(params![name, project_id], Self::row_to_benchmark)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(series)
+    }
+
+    /// The latest measurement of each of a project's benchmarks, by name.
+    pub fn get_latest_benchmarks(&self, project_id: i64) -> Result<Vec<Benchmark>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, name, value, unit, commit_sha, change_id, regression_change_id, created_at 
+             FROM benchmarks WHERE id IN (SELECT MAX(id) FROM benchmarks WHERE project_id = ? GROUP BY name) 
+             ORDER BY name COLLATE NOCASE"
+        )?;
+        let benchmarks = stmt.query_map(params![project_id], Self::row_to_benchmark)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(benchmarks)
+    }
+
+    pub fn delete_benchmark(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM benchmarks WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Store a measurement and compare it with the median of the benchmark's last few. When
+    /// it's worse by more than BENCHMARK_REGRESSION_PERCENT, a change is logged for the
+    /// regression on the culprit's component, or on the project's Benchmarks component.
+    pub fn record_benchmark(
+        &self,
+        project_id: i64,
+        name: &str,
+        value: f64,
+        unit: Option<&str>,
+        commit_sha: Option<&str>,
+    ) -> Result<BenchmarkResult> {
+        let previous = {
+            let mut stmt = self.conn.prepare_cached(
+                "SELECT value, created_at FROM benchmarks WHERE project_id = ? AND name = ? ORDER BY created_at DESC, id DESC LIMIT ?"
+            )?;
+            let rows = stmt.query_map(params![project_id, name, BENCHMARK_BASELINE_RUNS], |row| {
+                Ok((row.get::<_, f64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+        let baseline = median(previous.iter().map(|(value, _)| *value).collect());
+        let delta_percent = baseline.filter(|b| *b != 0.0).map(|b| {
+            let delta = (value - b) / b.abs() * 100.0;
+            if higher_is_better(unit) { -delta } else { delta }
+        });
+        let built = match commit_sha {
+            Some(sha) => self.change_for_commit(project_id, sha)?,
+            None => None,
         };
 
-        let mut stmt = self.conn.prepare(&sql)?;
+        let tx = self.conn.unchecked_transaction()?;
+        self.execute(
+            "INSERT INTO benchmarks (project_id, name, value, unit, commit_sha, change_id) VALUES (?, ?, ?, ?, ?, ?)",
+            params![project_id, name, value, unit, commit_sha, built.as_ref().map(|c| c.id)],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        let mut culprit = built;
+        let mut regression = None;
+        if let (Some(b), Some(delta)) = (baseline, delta_percent.filter(|d| *d > BENCHMARK_REGRESSION_PERCENT)) {
+            if culprit.is_none() {
+                let since = &previous[0].1;
+                culprit = self.get_recent_changes(Some(project_id), None, since, None, None)?.into_iter().next();
+            }
+            let component_id = match &culprit {
+                Some(change) => change.component_id,
+                None => self.benchmarks_component(project_id)?,
+            };
+            let mut reason = format!(
+                "{} regressed {:.1}% against the median of its last {} measurements",
+                name, delta, previous.len(),
+            );
+            if let Some(change) = &culprit {
+                reason.push_str(&format!(", after change #{}: {}", change.id, describe_change(change)));
+            }
+            let change = self.log_change(
+                component_id,
+                &format!("benchmark {}", name),
+                Some(&format_measurement(b, unit)),
+                Some(&format_measurement(value, unit)),
+                "other",
+                Some(&reason),
+            )?;
+            self.execute("UPDATE benchmarks SET regression_change_id = ? WHERE id = ?", params![change.id, id])?;
+            regression = Some(change);
+        }
+        tx.commit()?;
+        Ok(BenchmarkResult { benchmark: self.get_benchmark(id)?, baseline, delta_percent, culprit, regression })
+    }
+
+    fn benchmarks_component(&self, project_id: i64) -> Result<i64> {
+        match self.query_row(
+            "SELECT id FROM components WHERE project_id = ? AND name = ? COLLATE NOCASE ORDER BY id LIMIT 1",
+            params![project_id, BENCHMARKS_COMPONENT],
+            |row| row.get(0),
+        ) {
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let description = "Performance regressions no change could be blamed for";
+                Ok(self.create_component(project_id, BENCHMARKS_COMPONENT, Some(description), None)?.id)
+            }
+            other => other,
+        }
+    }
+
+    /// The change imported for a commit of the project; `sha` may be abbreviated to 7 or
+    /// more characters.
+    fn change_for_commit(&self, project_id: i64, sha: &str) -> Result<Option<Change>> {
+        let sha = sha.trim().to_lowercase();
+        if sha.len() < 7 {
+            return Ok(None);
+        }
+        match self.query_row(
+            "SELECT l.entity_id FROM integration_links l JOIN changes ch ON ch.id = l.entity_id JOIN components c ON c.id = ch.component_id 
+             WHERE l.integration = 'git' AND l.entity_type = 'change' AND substr(l.external_id, 1, length(?1)) = ?1 AND c.project_id = ?2 
+             ORDER BY l.entity_id LIMIT 1",
+            params![sha, project_id],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(id) => Ok(Some(self.get_change(id)?)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // ============================================================
+    // v1.4: SENTRY OPERATIONS
+    // ============================================================
+
+    /// The problem for a Sentry issue, created on the component linked to the issue's Sentry
+    /// project the first time the issue is seen and given `status` after that. Returns whether
+    /// it was created.
+    pub fn upsert_sentry_problem(&self, issue: &crate::sentry::Issue, status: Option<&str>) -> Result<(Problem, bool)> {
+        if let Some(id) = self.find_integration_entity("sentry", "problem", &issue.id)? {
+            match self.get_problem(id) {
+                Ok(_) => {
+                    let problem = self.update_problem(id, Some(&issue.title), None, status, None, None)?;
+                    self.upsert_integration_link("sentry", "problem", id, &issue.id, issue.project_slug.as_deref())?;
+                    return Ok((problem, false));
+                }
+                // Deleted since; the issue starts over as a new problem
+                Err(rusqlite::Error::QueryReturnedNoRows) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let slug = issue.project_slug.as_deref().unwrap_or_default();
+        let component_id = self.find_integration_entity("sentry", "component", slug)?.ok_or_else(|| {
+            rusqlite::Error::InvalidParameterName(format!("No component is linked to the Sentry project '{}'", slug))
+        })?;
+        let tx = self.conn.unchecked_transaction()?;
+        let mut problem = self.log_problem(component_id, &issue.title, Some(&issue.description()), issue.severity())?;
+        if let Some(status) = status.filter(|s| *s != problem.status) {
+            problem = self.update_problem(problem.id, None, None, Some(status), None, None)?;
+        }
+        self.upsert_integration_link("sentry", "problem", problem.id, &issue.id, issue.project_slug.as_deref())?;
+        tx.commit()?;
+        Ok((problem, true))
+    }
+
+    // ============================================================
+    // v1.4: PROBLEM ENVIRONMENT OPERATIONS
+    // ============================================================
+
+    pub fn get_problem_environment(&self, problem_id: i64) -> Result<Option<ProblemEnvironment>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT problem_id, os, app_version, device, branch, repro_steps, captured_at, updated_at 
+             FROM problem_environments WHERE problem_id = ?"
+        )?;
+        let mut rows = stmt.query_map(params![problem_id], |row| {
+            let steps: Option<String> = row.get(5)?;
+            Ok(ProblemEnvironment {
+                problem_id: row.get(0)?,
+                os: row.get(1)?,
+                app_version: row.get(2)?,
+                device: row.get(3)?,
+                branch: row.get(4)?,
+                repro_steps: steps
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                captured_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    /// Replace a problem's environment. Callers validate `env` first.
+    pub fn set_problem_environment(&self, problem_id: i64, env: &EnvironmentInput) -> Result<ProblemEnvironment> {
+        let steps = if env.repro_steps.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&env.repro_steps).unwrap_or_else(|_| "[]".to_string()))
+        };
+        self.execute(
+            "INSERT INTO problem_environments (problem_id, os, app_version, device, branch, repro_steps) 
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(problem_id) DO UPDATE SET 
+                os = excluded.os, app_version = excluded.app_version, device = excluded.device, 
+                branch = excluded.branch, repro_steps = excluded.repro_steps",
+            params![problem_id, env.os, env.app_version, env.device, env.branch, steps],
+        )?;
+        self.get_problem_environment(problem_id).map(|opt| opt.unwrap())
+    }
+
+    pub fn clear_problem_environment(&self, problem_id: i64) -> Result<()> {
+        self.execute("DELETE FROM problem_environments WHERE problem_id = ?", params![problem_id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: NOTE OPERATIONS
+    // ============================================================
+
+    fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+        Ok(Note {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            note_date: row.get(2)?,
+            title: row.get(3)?,
+            body: row.get(4)?,
+            pinned: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+
+    pub fn get_note(&self, id: i64) -> Result<Note> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, note_date, title, body, pinned, created_at, updated_at 
+             FROM notes WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_note)
+    }
+
+    /// Notes newest first, pinned notes on top. `project_id` None lists every note.
+    pub fn get_notes(&self, project_id: Option<i64>, date_from: Option<&str>, date_to: Option<&str>, pinned_only: bool) -> Result<Vec<Note>> {
+        let mut sql = String::from(
+            "SELECT id, project_id, note_date, title, body, pinned, created_at, updated_at FROM notes WHERE 1=1"
+        );
         
         let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
-        if let Some(pid) = project_id {
-            param_values.push(Box::new(pid));
+        if let Some(p) = project_id {
+            sql.push_str(" AND project_id = ?");
+            param_values.push(Box::new(p));
         }
-        if let Some(cid) = component_id {
-            param_values.push(Box::new(cid));
+        if let Some(from) = date_from {
+            sql.push_str(" AND note_date >= ?");
+            param_values.push(Box::new(from.to_string()));
         }
-        if let Some(s) = statuses {
-            for status in s {
-                param_values.push(Box::new(status.to_string()));
-            }
+        if let Some(to) = date_to {
+            sql.push_str(" AND note_date <= ?");
+            param_values.push(Box::new(to.to_string()));
         }
-        
+        if pinned_only {
+            sql.push_str(" AND pinned = 1");
+        }
+        sql.push_str(" ORDER BY pinned DESC, note_date DESC, id DESC");
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
         let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let problems = stmt.query_map(params.as_slice(), Self::row_to_problem)?
+        let notes = stmt.query_map(params.as_slice(), Self::row_to_note)?
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(problems)
+        Ok(notes)
     }
 
-    pub fn log_problem(&self, component_id: i64, title: &str, description: Option<&str>, severity: &str) -> Result<Problem> {
-        self.conn.execute(
-            "INSERT INTO problems (component_id, title, description, severity) VALUES (?, ?, ?, ?)",
-            params![component_id, title, description, severity],
+    /// Pinned notes plus anything dated within the last `days` days, for project context.
+    pub fn get_context_notes(&self, project_id: i64, days: i32) -> Result<Vec<Note>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, note_date, title, body, pinned, created_at, updated_at 
+             FROM notes 
+             WHERE project_id = ? AND (pinned = 1 OR note_date >= DATE('now', ?))
+             ORDER BY pinned DESC, note_date DESC, id DESC"
         )?;
-        self.get_problem(self.conn.last_insert_rowid())
+        let notes = stmt.query_map(params![project_id, format!("-{} days", days)], Self::row_to_note)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(notes)
     }
 
-    pub fn update_problem(&self, id: i64, title: Option<&str>, description: Option<&str>, status: Option<&str>, severity: Option<&str>, root_cause: Option<&str>) -> Result<Problem> {
+    pub fn create_note(&self, project_id: Option<i64>, note_date: Option<&str>, title: Option<&str>, body: &str, pinned: bool) -> Result<Note> {
+        self.execute(
+            "INSERT INTO notes (project_id, note_date, title, body, pinned) VALUES (?, COALESCE(?, DATE('now')), ?, ?, ?)",
+            params![project_id, note_date, title, body, pinned],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.refresh_note_links(id, body)?;
+        self.get_note(id)
+    }
+
+    pub fn update_note(&self, id: i64, note_date: Option<&str>, title: Option<&str>, body: Option<&str>, pinned: Option<bool>) -> Result<Note> {
         let mut updates = Vec::new();
         let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
+        if let Some(d) = note_date {
+            updates.push("note_date = ?");
+            values.push(Box::new(d.to_string()));
+        }
         if let Some(t) = title {
             updates.push("title = ?");
             values.push(Box::new(t.to_string()));
         }
-        if let Some(d) = description {
-            updates.push("description = ?");
-            values.push(Box::new(d.to_string()));
-        }
-        if let Some(s) = status {
-            updates.push("status = ?");
-            values.push(Box::new(s.to_string()));
-            if s == "solved" {
-                updates.push("solved_at = CURRENT_TIMESTAMP");
-            }
-        }
-        if let Some(sev) = severity {
-            updates.push("severity = ?");
-            values.push(Box::new(sev.to_string()));
+        if let Some(b) = body {
+            updates.push("body = ?");
+            values.push(Box::new(b.to_string()));
         }
-        if let Some(rc) = root_cause {
-            updates.push("root_cause = ?");
-            values.push(Box::new(rc.to_string()));
+        if let Some(p) = pinned {
+            updates.push("pinned = ?");
+            values.push(Box::new(p));
         }
         
         if updates.is_empty() {
-            return self.get_problem(id);
+            return self.get_note(id);
         }
         
         values.push(Box::new(id));
         
-        let sql = format!("UPDATE problems SET {} WHERE id = ?", updates.join(", "));
+        let sql = format!("UPDATE notes SET {} WHERE id = ?", updates.join(", "));
         let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
+        self.execute(&sql, params.as_slice())?;
         
-        self.get_problem(id)
+        if let Some(b) = body {
+            self.refresh_note_links(id, b)?;
+        }
+        self.get_note(id)
     }
 
-    pub fn delete_problem(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM problems WHERE id = ?", params![id])?;
+    pub fn delete_note(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM note_links WHERE note_id = ? OR (target_type = 'note' AND target_id = ?)", params![id, id])?;
+        self.execute("DELETE FROM notes WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    pub fn get_note_links(&self, note_id: i64) -> Result<Vec<NoteLink>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, note_id, reference, target_type, target_id FROM note_links WHERE note_id = ? ORDER BY id"
+        )?;
+        let links = stmt.query_map(params![note_id], |row| {
+            Ok(NoteLink {
+                id: row.get(0)?,
+                note_id: row.get(1)?,
+                reference: row.get(2)?,
+                target_type: row.get(3)?,
+                target_id: row.get(4)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(links)
+    }
+
+    /// Notes that reference a record, newest first.
+    pub fn get_backlinks(&self, target_type: &str, target_id: i64) -> Result<Vec<Note>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT n.id, n.project_id, n.note_date, n.title, n.body, n.pinned, n.created_at, n.updated_at 
+             FROM notes n 
+             JOIN note_links l ON l.note_id = n.id 
+             WHERE l.target_type = ? AND l.target_id = ?
+             ORDER BY n.note_date DESC, n.id DESC"
+        )?;
+        let notes = stmt.query_map(params![target_type, target_id], Self::row_to_note)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(notes)
+    }
+
+    /// Re-parse `[[...]]` references in a note body, keeping only ones that point at real records.
+    fn refresh_note_links(&self, note_id: i64, body: &str) -> Result<()> {
+        self.execute("DELETE FROM note_links WHERE note_id = ?", params![note_id])?;
+        for (reference, target_type, target_id) in parse_note_references(body) {
+            let table = match target_type {
+                "problem" => "problems",
+                "todo" => "todos",
+                "learning" => "learnings",
+                "component" => "components",
+                "solution" => "solutions",
+                _ => "notes",
+            };
+            let exists: bool = self.query_row(
+                &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table),
+                params![target_id],
+                |row| row.get(0),
+            )?;
+            if exists {
+                self.execute(
+                    "INSERT OR IGNORE INTO note_links (note_id, reference, target_type, target_id) VALUES (?, ?, ?, ?)",
+                    params![note_id, reference, target_type, target_id],
+                )?;
+            }
+        }
         Ok(())
     }
 
     // ============================================================
-    // SOLUTION ATTEMPT OPERATIONS
+    // v1.4: TAG OPERATIONS
     // ============================================================
 
-    fn row_to_attempt(row: &rusqlite::Row) -> rusqlite::Result<SolutionAttempt> {
-        Ok(SolutionAttempt {
+    fn normalize_tag(tag: &str) -> String {
+        tag.trim().trim_start_matches('#').to_lowercase()
+    }
+
+    pub fn get_tags(&self, entity_type: &str, entity_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT tag FROM tags WHERE entity_type = ? AND entity_id = ? ORDER BY tag"
+        )?;
+        let rows = stmt.query_map(params![entity_type, entity_id], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Attach tags to a record, ignoring ones it already has. Returns the record's full tag list.
+    pub fn add_tags(&self, entity_type: &str, entity_id: i64, tags: &[String]) -> Result<Vec<String>> {
+        for tag in tags {
+            let tag = Self::normalize_tag(tag);
+            if tag.is_empty() {
+                continue;
+            }
+            self.execute(
+                "INSERT OR IGNORE INTO tags (entity_type, entity_id, tag) VALUES (?, ?, ?)",
+                params![entity_type, entity_id, tag],
+            )?;
+        }
+        self.get_tags(entity_type, entity_id)
+    }
+
+    pub fn remove_tag(&self, entity_type: &str, entity_id: i64, tag: &str) -> Result<()> {
+        self.execute(
+            "DELETE FROM tags WHERE entity_type = ? AND entity_id = ? AND tag = ?",
+            params![entity_type, entity_id, Self::normalize_tag(tag)],
+        )?;
+        Ok(())
+    }
+
+    /// Create the todo, problem, or learning a parsed capture string describes and tag it.
+    /// Problems have no due date of their own, so one given for a problem goes on a todo
+    /// for it instead; learnings have none, and the parse already warns that it's dropped.
+    pub fn quick_add(&self, project_id: i64, parsed: &crate::quickadd::QuickAdd) -> Result<serde_json::Value> {
+        let mut todo = None;
+        let (record, record_id) = match parsed.kind.as_str() {
+            "problem" => {
+                let component_id = parsed.component_id.ok_or_else(|| {
+                    rusqlite::Error::InvalidParameterName("Problems need a component; add @component".to_string())
+                })?;
+                let severity = parsed.priority.as_deref().unwrap_or("medium");
+                let problem = self.log_problem(component_id, &parsed.title, None, severity)?;
+                if let Some(due_date) = parsed.due_date.as_deref() {
+                    let description = format!("Deadline for problem #{}", problem.id);
+                    todo = Some(self.add_todo(
+                        project_id, &parsed.title, Some(&description), severity, Some(component_id), Some(due_date)
+                    )?);
+                }
+                (serde_json::json!(problem), problem.id)
+            }
+            "learning" => {
+                let learning = self.log_learning(
+                    project_id, &parsed.title, parsed.category.as_deref(), None, parsed.component_id, "experience"
+                )?;
+                (serde_json::json!(learning), learning.id)
+            }
+            _ => {
+                let priority = parsed.priority.as_deref().unwrap_or("medium");
+                let todo = self.add_todo(
+                    project_id, &parsed.title, None, priority, parsed.component_id, parsed.due_date.as_deref()
+                )?;
+                (serde_json::json!(todo), todo.id)
+            }
+        };
+        let tags = self.add_tags(&parsed.kind, record_id, &parsed.tags)?;
+        
+        Ok(serde_json::json!({
+            "interpretation": parsed,
+            "record": record,
+            "todo": todo,
+            "tags": tags,
+        }))
+    }
+
+    // ============================================================
+    // v1.4: STAR OPERATIONS
+    // ============================================================
+
+    pub fn star_record(&self, entity_type: &str, entity_id: i64) -> Result<()> {
+        self.execute(
+            "INSERT INTO stars (entity_type, entity_id) VALUES (?, ?) 
+             ON CONFLICT(entity_type, entity_id) DO NOTHING",
+            params![entity_type, entity_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn unstar_record(&self, entity_type: &str, entity_id: i64) -> Result<()> {
+        self.execute(
+            "DELETE FROM stars WHERE entity_type = ? AND entity_id = ?",
+            params![entity_type, entity_id],
+        )?;
+        Ok(())
+    }
+
+    /// Starred records with enough of each to render a list, most recently starred first.
+    pub fn list_starred(&self, project_id: Option<i64>) -> Result<Vec<StarredRecord>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.entity_type, s.entity_id, r.project_id, r.title, r.status, s.created_at
+             FROM stars s
+             JOIN (
+                SELECT 'problem' AS entity_type, p.id, c.project_id, p.title, p.status 
+                    FROM problems p JOIN components c ON p.component_id = c.id
+                UNION ALL SELECT 'todo', id, project_id, title, status FROM todos
+                UNION ALL SELECT 'learning', id, project_id, insight, NULL FROM learnings
+                UNION ALL SELECT 'attachment', id, project_id, file_name, NULL FROM attachments
+             ) r ON r.entity_type = s.entity_type AND r.id = s.entity_id
+             WHERE ?1 IS NULL OR r.project_id = ?1
+             ORDER BY s.created_at DESC, s.id DESC"
+        )?;
+        let starred = stmt.query_map(params![project_id], |row| {
+            Ok(StarredRecord {
+                entity_type: row.get(0)?,
+                entity_id: row.get(1)?,
+                project_id: row.get(2)?,
+                title: row.get(3)?,
+                status: row.get(4)?,
+                starred_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+        Ok(starred)
+    }
+
+    // ============================================================
+    // v1.4: WATCH OPERATIONS
+    // ============================================================
+
+    fn row_to_watch(row: &rusqlite::Row) -> rusqlite::Result<Watch> {
+        Ok(Watch {
             id: row.get(0)?,
-            problem_id: row.get(1)?,
-            parent_attempt_id: row.get(2)?,
-            description: row.get(3)?,
-            outcome: row.get(4)?,
-            confidence: row.get(5)?,
-            notes: row.get(6)?,
-            created_at: row.get(7)?,
+            entity_type: row.get(1)?,
+            entity_id: row.get(2)?,
+            notify: row.get(3)?,
+            created_at: row.get(4)?,
         })
     }
 
-    pub fn get_attempt(&self, id: i64) -> Result<SolutionAttempt> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, problem_id, parent_attempt_id, description, outcome, confidence, notes, created_at 
-             FROM solution_attempts WHERE id = ?"
+    /// Start watching a record, or change whether an existing watch notifies.
+    pub fn watch_record(&self, entity_type: &str, entity_id: i64, notify: bool) -> Result<Watch> {
+        self.execute(
+            "INSERT INTO watches (entity_type, entity_id, notify) VALUES (?, ?, ?) 
+             ON CONFLICT(entity_type, entity_id) DO UPDATE SET notify = excluded.notify",
+            params![entity_type, entity_id, notify],
         )?;
-        stmt.query_row(params![id], Self::row_to_attempt)
+        self.get_watch(entity_type, entity_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
     }
 
-    pub fn log_attempt(&self, problem_id: i64, description: &str, parent_attempt_id: Option<i64>) -> Result<SolutionAttempt> {
-        self.conn.execute(
-            "INSERT INTO solution_attempts (problem_id, description, parent_attempt_id) VALUES (?, ?, ?)",
-            params![problem_id, description, parent_attempt_id],
+    pub fn unwatch_record(&self, entity_type: &str, entity_id: i64) -> Result<()> {
+        self.execute(
+            "DELETE FROM watches WHERE entity_type = ? AND entity_id = ?",
+            params![entity_type, entity_id],
         )?;
-        self.get_attempt(self.conn.last_insert_rowid())
+        Ok(())
     }
 
-    pub fn mark_attempt_outcome(&self, id: i64, outcome: &str, notes: Option<&str>, confidence: Option<&str>) -> Result<SolutionAttempt> {
-        let confidence = confidence.unwrap_or("attempted");
-        self.conn.execute(
-            "UPDATE solution_attempts SET outcome = ?, notes = ?, confidence = ? WHERE id = ?",
-            params![outcome, notes, confidence, id],
+    pub fn get_watch(&self, entity_type: &str, entity_id: i64) -> Result<Option<Watch>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, entity_type, entity_id, notify, created_at FROM watches WHERE entity_type = ? AND entity_id = ?"
         )?;
-        self.get_attempt(id)
+        let mut rows = stmt.query_map(params![entity_type, entity_id], Self::row_to_watch)?;
+        rows.next().transpose()
     }
 
-    pub fn get_attempts_for_problem(&self, problem_id: i64) -> Result<Vec<SolutionAttempt>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, problem_id, parent_attempt_id, description, outcome, confidence, notes, created_at 
-             FROM solution_attempts WHERE problem_id = ? ORDER BY created_at ASC"
+    pub fn list_watches(&self) -> Result<Vec<Watch>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, entity_type, entity_id, notify, created_at FROM watches ORDER BY created_at DESC"
         )?;
-        let attempts = stmt.query_map(params![problem_id], Self::row_to_attempt)?
+        let watches = stmt.query_map([], Self::row_to_watch)?
             .collect::<Result<Vec<_>>>()?;
-        Ok(attempts)
+        Ok(watches)
+    }
+
+    // ============================================================
+    // v1.4: DIRECTORY WATCH OPERATIONS
+    // ============================================================
+
+    fn row_to_directory_watch(row: &rusqlite::Row) -> rusqlite::Result<DirectoryWatch> {
+        let globs: String = row.get(3)?;
+        Ok(DirectoryWatch {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            path: row.get(2)?,
+            globs: serde_json::from_str(&globs).unwrap_or_default(),
+            enabled: row.get(4)?,
+            last_scan_at: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }
+
+    /// Watch a directory, or replace the patterns of an existing watch on it.
+    pub fn create_directory_watch(&self, project_id: i64, path: &str, globs: &[String]) -> Result<DirectoryWatch> {
+        let globs = serde_json::to_string(globs).unwrap_or_else(|_| "[]".to_string());
+        self.execute(
+            "INSERT INTO directory_watches (project_id, path, globs) VALUES (?, ?, ?) 
+             ON CONFLICT(project_id, path) DO UPDATE SET globs = excluded.globs, enabled = TRUE",
+            params![project_id, path, globs],
+        )?;
+        let id = self.query_row(
+            "SELECT id FROM directory_watches WHERE project_id = ? AND path = ?",
+            params![project_id, path],
+            |row| row.get(0),
+        )?;
+        self.get_directory_watch(id)
+    }
+
+    pub fn get_directory_watch(&self, id: i64) -> Result<DirectoryWatch> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, path, globs, enabled, last_scan_at, created_at FROM directory_watches WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_directory_watch)
+    }
+
+    /// A project's watches, or every watch when no project is given.
+    pub fn get_directory_watches(&self, project_id: Option<i64>) -> Result<Vec<DirectoryWatch>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, path, globs, enabled, last_scan_at, created_at FROM directory_watches 
+             WHERE ?1 IS NULL OR project_id = ?1 ORDER BY created_at"
+        )?;
+        let watches = stmt.query_map(params![project_id], Self::row_to_directory_watch)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(watches)
+    }
+
+    pub fn set_directory_watch_enabled(&self, id: i64, enabled: bool) -> Result<DirectoryWatch> {
+        self.execute("UPDATE directory_watches SET enabled = ? WHERE id = ?", params![enabled, id])?;
+        self.get_directory_watch(id)
+    }
+
+    pub fn mark_directory_watch_scanned(&self, id: i64) -> Result<()> {
+        self.execute("UPDATE directory_watches SET last_scan_at = CURRENT_TIMESTAMP WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Attachments already made stay attached.
+    pub fn delete_directory_watch(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM directory_watches WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: CAPTURE OPERATIONS
+    // ============================================================
+
+    fn row_to_capture(row: &rusqlite::Row) -> rusqlite::Result<Capture> {
+        Ok(Capture {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            source: row.get(2)?,
+            content: row.get(3)?,
+            status: row.get(4)?,
+            converted_type: row.get(5)?,
+            converted_id: row.get(6)?,
+            captured_at: row.get(7)?,
+        })
+    }
+
+    /// Record captured text. Capturing the same text again only moves it back to the top;
+    /// a capture that was already reviewed stays reviewed.
+    pub fn create_capture(&self, project_id: i64, source: &str, content: &str) -> Result<Capture> {
+        self.execute(
+            "INSERT INTO captures (project_id, source, content) VALUES (?, ?, ?) 
+             ON CONFLICT(project_id, content) DO UPDATE SET captured_at = CURRENT_TIMESTAMP",
+            params![project_id, source, content],
+        )?;
+        let id = self.query_row(
+            "SELECT id FROM captures WHERE project_id = ? AND content = ?",
+            params![project_id, content],
+            |row| row.get(0),
+        )?;
+        self.get_capture(id)
+    }
+
+    pub fn get_capture(&self, id: i64) -> Result<Capture> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, source, content, status, converted_type, converted_id, captured_at 
+             FROM captures WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_capture)
+    }
+
+    /// Newest first; pending captures only unless a status is given.
+    pub fn get_captures(&self, project_id: i64, status: Option<&str>, limit: i64) -> Result<Vec<Capture>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, source, content, status, converted_type, converted_id, captured_at 
+             FROM captures WHERE project_id = ? AND status = COALESCE(?, 'pending') 
+             ORDER BY captured_at DESC, id DESC LIMIT ?"
+        )?;
+        let captures = stmt.query_map(params![project_id, status, limit], Self::row_to_capture)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(captures)
+    }
+
+    pub fn set_capture_status(&self, id: i64, status: &str) -> Result<Capture> {
+        self.execute("UPDATE captures SET status = ? WHERE id = ?", params![status, id])?;
+        self.get_capture(id)
+    }
+
+    pub fn mark_capture_converted(&self, id: i64, converted_type: &str, converted_id: i64) -> Result<Capture> {
+        self.execute(
+            "UPDATE captures SET status = 'converted', converted_type = ?, converted_id = ? WHERE id = ?",
+            params![converted_type, converted_id, id],
+        )?;
+        self.get_capture(id)
+    }
+
+    /// Drop reviewed-away captures, or every capture of the project when `all` is set.
+    pub fn clear_captures(&self, project_id: i64, all: bool) -> Result<usize> {
+        self.execute(
+            "DELETE FROM captures WHERE project_id = ? AND (? OR status = 'discarded')",
+            params![project_id, all],
+        )
     }
 
     // ============================================================
-    // SOLUTION OPERATIONS
+    // v1.4: INBOX OPERATIONS
     // ============================================================
 
-    fn row_to_solution(row: &rusqlite::Row) -> rusqlite::Result<Solution> {
-        Ok(Solution {
+    fn row_to_inbox_item(row: &rusqlite::Row) -> rusqlite::Result<InboxItem> {
+        Ok(InboxItem {
             id: row.get(0)?,
-            problem_id: row.get(1)?,
-            winning_attempt_id: row.get(2)?,
-            summary: row.get(3)?,
-            code_snippet: row.get(4)?,
-            key_insight: row.get(5)?,
-            created_at: row.get(6)?,
+            project_id: row.get(1)?,
+            source: row.get(2)?,
+            source_id: row.get(3)?,
+            title: row.get(4)?,
+            content: row.get(5)?,
+            status: row.get(6)?,
+            converted_type: row.get(7)?,
+            converted_id: row.get(8)?,
+            triaged_at: row.get(9)?,
+            created_at: row.get(10)?,
+            author: row.get(11)?,
         })
     }
 
-    pub fn get_solution(&self, id: i64) -> Result<Solution> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, problem_id, winning_attempt_id, summary, code_snippet, key_insight, created_at 
-             FROM solutions WHERE id = ?"
+    /// Put something in the project's inbox. An item for a capture or attachment that's
+    /// already there is returned as it is rather than added twice.
+    pub fn add_inbox_item(
+        &self,
+        project_id: i64,
+        source: &str,
+        source_id: Option<i64>,
+        title: &str,
+        content: Option<&str>,
+    ) -> Result<InboxItem> {
+        self.execute(
+            "INSERT INTO inbox_items (project_id, source, source_id, title, content) VALUES (?, ?, ?, ?, ?) 
+             ON CONFLICT(source, source_id) WHERE source_id IS NOT NULL DO NOTHING",
+            params![project_id, source, source_id, title, content],
         )?;
-        stmt.query_row(params![id], Self::row_to_solution)
+        let id = match source_id {
+            Some(source_id) => self.query_row(
+                "SELECT id FROM inbox_items WHERE source = ? AND source_id = ?",
+                params![source, source_id],
+                |row| row.get(0),
+            )?,
+            None => self.conn.last_insert_rowid(),
+        };
+        self.get_inbox_item(id)
     }
 
-    pub fn get_solution_for_problem(&self, problem_id: i64) -> Result<Option<Solution>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, problem_id, winning_attempt_id, summary, code_snippet, key_insight, created_at 
-             FROM solutions WHERE problem_id = ?"
+    pub fn get_inbox_item(&self, id: i64) -> Result<InboxItem> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, source, source_id, title, content, status, converted_type, converted_id, 
+                    triaged_at, created_at, author 
+             FROM inbox_items WHERE id = ?"
         )?;
-        match stmt.query_row(params![problem_id], Self::row_to_solution) {
-            Ok(s) => Ok(Some(s)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+        stmt.query_row(params![id], Self::row_to_inbox_item)
     }
 
-    pub fn mark_problem_solved(&self, problem_id: i64, winning_attempt_id: Option<i64>, summary: &str, code_snippet: Option<&str>, key_insight: Option<&str>) -> Result<Solution> {
-        self.conn.execute(
-            "UPDATE problems SET status = 'solved', solved_at = CURRENT_TIMESTAMP WHERE id = ?",
-            params![problem_id],
+    /// Oldest first, so the inbox is worked through in arrival order; pending items only
+    /// unless a status is given.
+    pub fn list_inbox(&self, project_id: i64, status: Option<&str>, source: Option<&str>, limit: i64) -> Result<Vec<InboxItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, source, source_id, title, content, status, converted_type, converted_id, 
+                    triaged_at, created_at, author 
+             FROM inbox_items 
+             WHERE project_id = ? AND status = COALESCE(?, 'pending') AND (? IS NULL OR source = ?) 
+             ORDER BY created_at, id LIMIT ?"
         )?;
-        
-        if let Some(attempt_id) = winning_attempt_id {
-            self.conn.execute(
-                "UPDATE solution_attempts SET outcome = 'success', confidence = 'verified' WHERE id = ?",
-                params![attempt_id],
-            )?;
-        }
-        
-        self.conn.execute(
-            "INSERT INTO solutions (problem_id, winning_attempt_id, summary, code_snippet, key_insight) VALUES (?, ?, ?, ?, ?)",
-            params![problem_id, winning_attempt_id, summary, code_snippet, key_insight],
+        let items = stmt.query_map(params![project_id, status, source, source, limit], Self::row_to_inbox_item)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(items)
+    }
+
+    /// Record what triage did with an item: the record it became, or discarded when
+    /// `converted_type` is None.
+    pub fn record_triage(&self, id: i64, converted_type: Option<&str>, converted_id: Option<i64>) -> Result<InboxItem> {
+        let status = if converted_type.is_some() { "converted" } else { "discarded" };
+        self.execute(
+            "UPDATE inbox_items SET status = ?, converted_type = ?, converted_id = ?, triaged_at = CURRENT_TIMESTAMP 
+             WHERE id = ?",
+            params![status, converted_type, converted_id, id],
         )?;
-        
-        self.get_solution(self.conn.last_insert_rowid())
+        self.get_inbox_item(id)
+    }
+
+    /// Close the pending inbox item for a capture that was converted or discarded from the
+    /// capture list instead.
+    pub fn settle_capture_inbox_item(&self, capture: &Capture) -> Result<()> {
+        self.execute(
+            "UPDATE inbox_items SET status = ?, converted_type = ?, converted_id = ?, triaged_at = CURRENT_TIMESTAMP 
+             WHERE source = 'capture' AND source_id = ? AND status = 'pending'",
+            params![capture.status, capture.converted_type, capture.converted_id, capture.id],
+        )?;
+        Ok(())
     }
 
     // ============================================================
-    // TODO OPERATIONS
+    // v1.4: REFERENCE OPERATIONS
     // ============================================================
 
-    fn row_to_todo(row: &rusqlite::Row) -> rusqlite::Result<Todo> {
-        Ok(Todo {
+    fn row_to_reference(row: &rusqlite::Row) -> rusqlite::Result<Reference> {
+        Ok(Reference {
             id: row.get(0)?,
             project_id: row.get(1)?,
             component_id: row.get(2)?,
-            title: row.get(3)?,
-            description: row.get(4)?,
-            priority: row.get(5)?,
-            status: row.get(6)?,
-            due_date: row.get(7)?,
-            created_at: row.get(8)?,
-            completed_at: row.get(9)?,
+            problem_id: row.get(3)?,
+            url: row.get(4)?,
+            title: row.get(5)?,
+            description: row.get(6)?,
+            fetched_at: row.get(7)?,
+            archived_html_path: row.get(8)?,
+            link_status: row.get(9)?,
+            http_status: row.get(10)?,
+            checked_at: row.get(11)?,
+            created_at: row.get(12)?,
+            updated_at: row.get(13)?,
+            favicon_url: row.get(14)?,
         })
     }
 
-    pub fn get_todo(&self, id: i64) -> Result<Todo> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, component_id, title, description, priority, status, due_date, created_at, completed_at 
-             FROM todos WHERE id = ?"
+    pub fn create_reference(
+        &self,
+        project_id: i64,
+        url: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        component_id: Option<i64>,
+        problem_id: Option<i64>,
+    ) -> Result<Reference> {
+        self.execute(
+            "INSERT INTO web_references (project_id, url, title, description, component_id, problem_id) 
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![project_id, url, title, description, component_id, problem_id],
         )?;
-        stmt.query_row(params![id], Self::row_to_todo)
+        self.get_reference(self.conn.last_insert_rowid())
     }
 
-    pub fn get_todos(&self, project_id: i64, status: Option<&str>, priority: Option<&str>) -> Result<Vec<Todo>> {
-        let mut sql = String::from(
-            "SELECT id, project_id, component_id, title, description, priority, status, due_date, created_at, completed_at 
-             FROM todos WHERE project_id = ?"
-        );
-        
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(project_id)];
-        
-        if let Some(s) = status {
-            sql.push_str(" AND status = ?");
-            param_values.push(Box::new(s.to_string()));
-        }
-        if let Some(p) = priority {
-            sql.push_str(" AND priority = ?");
-            param_values.push(Box::new(p.to_string()));
-        }
-        sql.push_str(" ORDER BY CASE priority WHEN 'critical' THEN 1 WHEN 'high' THEN 2 WHEN 'medium' THEN 3 WHEN 'low' THEN 4 END, created_at DESC");
+    pub fn get_reference(&self, id: i64) -> Result<Reference> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, problem_id, url, title, description, fetched_at, 
+                    archived_html_path, link_status, http_status, checked_at, created_at, updated_at, favicon_url 
+             FROM web_references WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_reference)
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let todos = stmt.query_map(params.as_slice(), Self::row_to_todo)?
+    /// A project's references, newest first, optionally only those on a component or problem.
+    pub fn get_references(&self, project_id: i64, component_id: Option<i64>, problem_id: Option<i64>) -> Result<Vec<Reference>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, component_id, problem_id, url, title, description, fetched_at, 
+                    archived_html_path, link_status, http_status, checked_at, created_at, updated_at, favicon_url 
+             FROM web_references 
+             WHERE project_id = ?1 AND (?2 IS NULL OR component_id = ?2) AND (?3 IS NULL OR problem_id = ?3) 
+             ORDER BY created_at DESC, id DESC"
+        )?;
+        let references = stmt.query_map(params![project_id, component_id, problem_id], Self::row_to_reference)?
             .collect::<Result<Vec<_>>>()?;
+        Ok(references)
+    }
 
-        Ok(todos)
+    /// Only the fields given are changed.
+    pub fn update_reference(
+        &self,
+        id: i64,
+        url: Option<&str>,
+        title: Option<&str>,
+        description: Option<&str>,
+        component_id: Option<i64>,
+        problem_id: Option<i64>,
+    ) -> Result<Reference> {
+        self.execute(
+            "UPDATE web_references SET url = COALESCE(?, url), title = COALESCE(?, title), 
+             description = COALESCE(?, description), component_id = COALESCE(?, component_id), 
+             problem_id = COALESCE(?, problem_id), updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![url, title, description, component_id, problem_id, id],
+        )?;
+        self.get_reference(id)
     }
 
-    pub fn add_todo(&self, project_id: i64, title: &str, description: Option<&str>, priority: &str, component_id: Option<i64>, due_date: Option<&str>) -> Result<Todo> {
-        self.conn.execute(
-            "INSERT INTO todos (project_id, title, description, priority, component_id, due_date) VALUES (?, ?, ?, ?, ?, ?)",
-            params![project_id, title, description, priority, component_id, due_date],
+    pub fn delete_reference(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM web_references WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    pub fn set_reference_snapshot(&self, id: i64, archived_html_path: &str) -> Result<Reference> {
+        self.execute(
+            "UPDATE web_references SET archived_html_path = ?, fetched_at = CURRENT_TIMESTAMP, 
+             updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![archived_html_path, id],
         )?;
-        self.get_todo(self.conn.last_insert_rowid())
+        self.get_reference(id)
     }
 
-    pub fn update_todo(&self, id: i64, title: Option<&str>, description: Option<&str>, status: Option<&str>, priority: Option<&str>, due_date: Option<&str>) -> Result<Todo> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if let Some(t) = title {
-            updates.push("title = ?");
-            values.push(Box::new(t.to_string()));
-        }
-        if let Some(d) = description {
-            updates.push("description = ?");
-            values.push(Box::new(d.to_string()));
-        }
-        if let Some(s) = status {
-            updates.push("status = ?");
-            values.push(Box::new(s.to_string()));
-            if s == "done" {
-                updates.push("completed_at = CURRENT_TIMESTAMP");
-            }
-        }
-        if let Some(p) = priority {
-            updates.push("priority = ?");
-            values.push(Box::new(p.to_string()));
-        }
-        if let Some(dd) = due_date {
-            updates.push("due_date = ?");
-            values.push(Box::new(dd.to_string()));
-        }
-        
-        if updates.is_empty() {
-            return self.get_todo(id);
-        }
-        
-        values.push(Box::new(id));
-        
-        let sql = format!("UPDATE todos SET {} WHERE id = ?", updates.join(", "));
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_todo(id)
+    /// Store fetched page metadata. Without `overwrite` only empty fields are filled, so a
+    /// title the user typed wins over the page's.
+    pub fn apply_reference_metadata(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        description: Option<&str>,
+        favicon_url: Option<&str>,
+        overwrite: bool,
+    ) -> Result<Reference> {
+        let sql = if overwrite {
+            "UPDATE web_references SET title = COALESCE(?, title), description = COALESCE(?, description), 
+             favicon_url = COALESCE(?, favicon_url), fetched_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP 
+             WHERE id = ?"
+        } else {
+            "UPDATE web_references SET title = COALESCE(title, ?), description = COALESCE(description, ?), 
+             favicon_url = COALESCE(favicon_url, ?), fetched_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP 
+             WHERE id = ?"
+        };
+        self.execute(sql, params![title, description, favicon_url, id])?;
+        self.get_reference(id)
     }
 
-    pub fn delete_todo(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM todos WHERE id = ?", params![id])?;
-        Ok(())
+    pub fn set_reference_check(&self, id: i64, link_status: &str, http_status: Option<i64>) -> Result<Reference> {
+        self.execute(
+            "UPDATE web_references SET link_status = ?, http_status = ?, checked_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![link_status, http_status, id],
+        )?;
+        self.get_reference(id)
     }
 
     // ============================================================
-    // LEARNING OPERATIONS  
+    // v1.4: RECORD HISTORY OPERATIONS
     // ============================================================
 
-    fn row_to_learning(row: &rusqlite::Row) -> rusqlite::Result<Learning> {
-        Ok(Learning {
-            id: row.get(0)?,
-            project_id: row.get(1)?,
-            component_id: row.get(2)?,
-            category: row.get(3)?,
-            insight: row.get(4)?,
-            context: row.get(5)?,
-            source: row.get(6)?,
-            verified: row.get(7)?,
-            created_at: row.get(8)?,
+    fn versioned_entity(entity_type: &str) -> Result<&'static VersionedEntity> {
+        VERSIONED_ENTITIES.iter().find(|e| e.entity_type == entity_type).ok_or_else(|| {
+            rusqlite::Error::InvalidParameterName(format!("{} records aren't versioned (problem or solution)", entity_type))
         })
     }
 
-    pub fn get_learning(&self, id: i64) -> Result<Learning> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, component_id, category, insight, context, source, verified, created_at 
-             FROM learnings WHERE id = ?"
-        )?;
-        stmt.query_row(params![id], Self::row_to_learning)
+    /// Table holding an entity type's records, for resolving ids given as uuids.
+    pub fn versioned_table(entity_type: &str) -> Result<&'static str> {
+        Ok(Self::versioned_entity(entity_type)?.table)
     }
 
-    pub fn get_learnings(&self, project_id: Option<i64>, category: Option<&str>, verified_only: bool) -> Result<Vec<Learning>> {
-        let mut sql = String::from(
-            "SELECT id, project_id, component_id, category, insight, context, source, verified, created_at FROM learnings WHERE 1=1"
-        );
-        
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    /// Every version of a record, newest first, each with the fields it changed.
+    pub fn get_record_history(&self, entity_type: &str, entity_id: i64) -> Result<Vec<RecordVersion>> {
+        Self::versioned_entity(entity_type)?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, entity_type, entity_id, version, snapshot, created_at 
+             FROM record_versions WHERE entity_type = ? AND entity_id = ? ORDER BY version"
+        )?;
+        let mut versions = stmt.query_map(params![entity_type, entity_id], |row| {
+            let snapshot: String = row.get(4)?;
+            Ok(RecordVersion {
+                id: row.get(0)?,
+                entity_type: row.get(1)?,
+                entity_id: row.get(2)?,
+                version: row.get(3)?,
+                snapshot: serde_json::from_str(&snapshot).unwrap_or_default(),
+                changed_fields: Vec::new(),
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
         
-        if let Some(p) = project_id {
-            sql.push_str(" AND project_id = ?");
-            param_values.push(Box::new(p));
-        }
-        if let Some(c) = category {
-            sql.push_str(" AND category = ?");
-            param_values.push(Box::new(c.to_string()));
-        }
-        if verified_only {
-            sql.push_str(" AND verified = 1");
+        for i in 1..versions.len() {
+            let (before, after) = versions.split_at_mut(i);
+            let (previous, current) = (&before[i - 1].snapshot, &mut after[0]);
+            if let Some(fields) = current.snapshot.as_object() {
+                current.changed_fields = fields.iter()
+                    .filter(|(field, value)| previous.get(field.as_str()) != Some(*value))
+                    .map(|(field, _)| field.clone())
+                    .collect();
+            }
         }
-        sql.push_str(" ORDER BY created_at DESC");
-
-        let mut stmt = self.conn.prepare(&sql)?;
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let learnings = stmt.query_map(params.as_slice(), Self::row_to_learning)?
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(learnings)
+        versions.reverse();
+        Ok(versions)
     }
 
-    pub fn log_learning(&self, project_id: i64, insight: &str, category: Option<&str>, context: Option<&str>, component_id: Option<i64>, source: &str) -> Result<Learning> {
-        self.conn.execute(
-            "INSERT INTO learnings (project_id, insight, category, context, component_id, source) VALUES (?, ?, ?, ?, ?, ?)",
-            params![project_id, insight, category, context, component_id, source],
+    /// Put a record back the way it was at `version`. The revert is an edit like any other,
+    /// so it becomes the newest version (returned) and can itself be undone. Links to records
+    /// deleted since are cleared, except the owning component, which stays as it is.
+    pub fn revert_to_version(&self, entity_type: &str, entity_id: i64, version: i64) -> Result<RecordVersion> {
+        let entity = Self::versioned_entity(entity_type)?;
+        let snapshot: String = self.query_row(
+            "SELECT snapshot FROM record_versions WHERE entity_type = ? AND entity_id = ? AND version = ?",
+            params![entity_type, entity_id, version],
+            |row| row.get(0),
         )?;
-        self.get_learning(self.conn.last_insert_rowid())
-    }
-
-    pub fn update_learning(&self, id: i64, insight: Option<&str>, category: Option<&str>, context: Option<&str>, verified: Option<bool>) -> Result<Learning> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
-        if let Some(i) = insight {
-            updates.push("insight = ?");
-            values.push(Box::new(i.to_string()));
-        }
-        if let Some(c) = category {
-            updates.push("category = ?");
-            values.push(Box::new(c.to_string()));
-        }
-        if let Some(ctx) = context {
-            updates.push("context = ?");
-            values.push(Box::new(ctx.to_string()));
-        }
-        if let Some(v) = verified {
-            updates.push("verified = ?");
-            values.push(Box::new(v));
+        let assignments: Vec<String> = entity.fields.iter().map(|(field, link)| {
+            let value = format!("json_extract(?1, '$.{}')", field);
+            match link {
+                Some("components") => format!("{f} = COALESCE((SELECT id FROM components WHERE id = {v}), {f})", f = field, v = value),
+                Some(table) => format!("{} = (SELECT id FROM {} WHERE id = {})", field, table, value),
+                None => format!("{} = {}", field, value),
+            }
+        }).collect();
+        let sql = format!("UPDATE {} SET {} WHERE id = ?2", entity.table, assignments.join(", "));
+        if self.execute(&sql, params![snapshot, entity_id])? == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
         }
         
-        if updates.is_empty() {
-            return self.get_learning(id);
+        self.get_record_history(entity_type, entity_id)?
+            .into_iter()
+            .next()
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    // ============================================================
+    // v1.4: RECORD UUIDS
+    // ============================================================
+
+    /// The id of the record of `table` (one of UUID_TABLES) with this uuid.
+    pub fn find_by_uuid(&self, table: &str, uuid: &str) -> Result<Option<i64>> {
+        if !UUID_TABLES.contains(&table) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("{} has no uuids", table)));
+        }
+        match self.query_row(&format!("SELECT id FROM {} WHERE uuid = ?", table), params![uuid.trim().to_lowercase()], |row| row.get(0)) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
         }
-        
-        values.push(Box::new(id));
-        
-        let sql = format!("UPDATE learnings SET {} WHERE id = ?", updates.join(", "));
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_learning(id)
     }
 
-    pub fn delete_learning(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM learnings WHERE id = ?", params![id])?;
-        Ok(())
+    pub fn uuid_for_id(&self, table: &str, id: i64) -> Result<Option<String>> {
+        if !UUID_TABLES.contains(&table) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("{} has no uuids", table)));
+        }
+        match self.query_row(&format!("SELECT uuid FROM {} WHERE id = ?", table), params![id], |row| row.get(0)) {
+            Ok(uuid) => Ok(uuid),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     // ============================================================
-    // CHANGE OPERATIONS
+    // v1.4: PROJECT EXPORT & MERGE OPERATIONS
     // ============================================================
 
-    fn row_to_change(row: &rusqlite::Row) -> rusqlite::Result<Change> {
-        Ok(Change {
-            id: row.get(0)?,
-            component_id: row.get(1)?,
-            field_name: row.get(2)?,
-            old_value: row.get(3)?,
-            new_value: row.get(4)?,
-            change_type: row.get(5)?,
-            reason: row.get(6)?,
-            created_at: row.get(7)?,
+    /// A project and everything under it in portable form. The exported records become the
+    /// merge bases, so when they come back edited the merge knows what changed on which side.
+    pub fn export_project(&self, project_id: i64) -> Result<ProjectExport> {
+        self.export_project_records(project_id, None)
+    }
+
+    /// v1.4: For LAN sync, each project's records created or updated at or after `since` (a
+    /// `YYYY-MM-DD HH:MM:SS` database timestamp). Projects kept out of sync, and those with
+    /// nothing new, are left out. Merging these works like merging a whole export, since records
+    /// are matched by uuid.
+    pub fn export_changes_since(&self, since: &str) -> Result<Vec<ProjectExport>> {
+        // Checked and rewritten so it can go into the SQL as a literal
+        let since = chrono::NaiveDateTime::parse_from_str(since, "%Y-%m-%d %H:%M:%S")
+            .map_err(|e| rusqlite::Error::InvalidParameterName(format!("Bad timestamp {}: {}", since, e)))?
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let project_ids: Vec<i64> = self.conn
+            .prepare_cached("SELECT id FROM projects WHERE sync_excluded = 0 ORDER BY id")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>>>()?;
+        let mut exports = Vec::new();
+        for project_id in project_ids {
+            let export = self.export_project_records(project_id, Some(&since))?;
+            if export.records.values().any(|rows| !rows.is_empty()) {
+                exports.push(export);
+            }
+        }
+        Ok(exports)
+    }
+
+    /// The database's clock, which `export_changes_since` compares against.
+    pub fn current_timestamp(&self) -> Result<String> {
+        self.query_row("SELECT CURRENT_TIMESTAMP", [], |row| row.get(0))
+    }
+
+    fn export_project_records(&self, project_id: i64, since: Option<&str>) -> Result<ProjectExport> {
+        let project_uuid: String = self.query_row("SELECT uuid FROM projects WHERE id = ?", params![project_id], |row| row.get(0))?;
+        let mut records = BTreeMap::new();
+        let tx = self.conn.unchecked_transaction()?;
+        for spec in MERGE_TABLES {
+            let condition = match since {
+                Some(since) => format!("{} AND {} >= '{}' ORDER BY id", spec.scope, spec.modified, since),
+                None => format!("{} ORDER BY id", spec.scope),
+            };
+            let rows: Vec<_> = self.portable_records(spec, &condition, project_id)?
+                .into_iter()
+                .map(|(_, record)| record)
+                .collect();
+            for record in &rows {
+                self.save_merge_base(spec.name, record)?;
+            }
+            records.insert(spec.name.to_string(), rows);
+        }
+        tx.commit()?;
+        Ok(ProjectExport {
+            format: PROJECT_EXPORT_FORMAT.to_string(),
+            version: PROJECT_EXPORT_VERSION,
+            exported_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            project_uuid,
+            records,
         })
     }
 
-    pub fn get_recent_changes(&self, project_id: Option<i64>, component_id: Option<i64>, hours: i32) -> Result<Vec<Change>> {
-        let time_filter = format!("ch.created_at >= datetime('now', '-{} hours')", hours);
-        
-        let mut sql = String::from(
-            "SELECT ch.id, ch.component_id, ch.field_name, ch.old_value, ch.new_value, ch.change_type, ch.reason, ch.created_at 
-             FROM changes ch"
-        );
-        
-        let mut conditions: Vec<String> = Vec::new();
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if project_id.is_some() || component_id.is_some() {
-            sql.push_str(" JOIN components c ON ch.component_id = c.id");
+    /// v1.4: Copy a project's rows of REPORT_TABLES into a new SQLite file at `path`, for
+    /// browsing with any SQLite tool. Only the data comes along: no indexes, triggers or
+    /// search tables, and no REPORT_CLEARED_COLUMNS. Returns how many rows each table got.
+    pub fn export_report_db(&self, project_id: i64, path: &std::path::Path) -> Result<BTreeMap<String, i64>> {
+        self.get_project(project_id)?;
+        // Not through the statement cache, which would keep statements on the detached file
+        self.conn.execute("ATTACH DATABASE ? AS report", params![path.to_string_lossy().to_string()])?;
+        let copied = (|| -> Result<BTreeMap<String, i64>> {
+            let tx = self.conn.unchecked_transaction()?;
+            let mut counts = BTreeMap::new();
+            for (table, scope) in REPORT_TABLES {
+                self.conn.execute(&format!("CREATE TABLE report.{t} AS SELECT * FROM main.{t} WHERE {}", scope, t = table), params![project_id])?;
+                let rows: i64 = self.conn.query_row(&format!("SELECT COUNT(*) FROM report.{}", table), [], |row| row.get(0))?;
+                counts.insert(table.to_string(), rows);
+            }
+            for (table, column) in REPORT_CLEARED_COLUMNS {
+                self.conn.execute(&format!("UPDATE report.{} SET {} = NULL", table, column), [])?;
+            }
+            tx.commit()?;
+            Ok(counts)
+        })();
+        self.conn.execute_batch("DETACH DATABASE report")?;
+        copied
+    }
+
+    /// Merge an export into this database. Records are matched by uuid (components by name and
+    /// solutions by problem when the uuid is new here); unknown ones are created, known ones
+    /// merged field by field against the last exchanged version:
+    /// - "last_writer_wins": when both sides changed a record, the more recently updated one
+    ///   wins whole; the other side's edits are listed as conflicts
+    /// - "field": each field keeps whichever side changed it; fields both sides changed go to
+    ///   the more recent side and are listed as conflicts
+    ///
+    /// A dry run reports without writing anything.
+    pub fn merge_project_export(&self, export: &ProjectExport, strategy: &str, dry_run: bool) -> Result<MergeReport> {
+        self.without_author(|| self.merge_export_records(export, strategy, dry_run))
+    }
+
+    fn merge_export_records(&self, export: &ProjectExport, strategy: &str, dry_run: bool) -> Result<MergeReport> {
+        if export.format != PROJECT_EXPORT_FORMAT || export.version > PROJECT_EXPORT_VERSION {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISMATCH),
+                Some(format!("Not a FlowState project export (version {} or earlier)", PROJECT_EXPORT_VERSION)),
+            ));
         }
+        let field_level = match strategy {
+            "last_writer_wins" => false,
+            "field" => true,
+            other => return Err(rusqlite::Error::InvalidParameterName(format!("Unknown merge strategy: {}", other))),
+        };
+        let mut run = MergeRun {
+            field_level,
+            report: MergeReport { strategy: strategy.to_string(), dry_run, ..Default::default() },
+            ..Default::default()
+        };
         
-        if let Some(pid) = project_id {
-            conditions.push("c.project_id = ?".to_string());
-            param_values.push(Box::new(pid));
+        let tx = self.conn.unchecked_transaction()?;
+        for spec in MERGE_TABLES {
+            for record in export.records.get(spec.name).into_iter().flatten() {
+                self.merge_record(spec, record, &mut run)?;
+            }
         }
-        if let Some(cid) = component_id {
-            conditions.push("ch.component_id = ?".to_string());
-            param_values.push(Box::new(cid));
+        for (table, id, column, linked_table, uuid) in std::mem::take(&mut run.pending_links) {
+            match self.id_for_uuid(linked_table, &uuid, &run)? {
+                Some(linked) => {
+                    self.execute(&format!("UPDATE {} SET {} = ? WHERE id = ?", table, column), params![linked, id])?;
+                }
+                None => run.report.warnings.push(format!("{} #{}: {} {} is not in the export", table, id, linked_table, uuid)),
+            }
         }
+        run.report.project_id = self.id_for_uuid("projects", &export.project_uuid, &run)?;
         
-        conditions.push(time_filter);
+        if dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+        Ok(run.report)
+    }
+
+    fn merge_record(&self, spec: &MergeTable, incoming: &serde_json::Map<String, serde_json::Value>, run: &mut MergeRun) -> Result<()> {
+        let Some(uuid) = incoming.get("uuid").and_then(|v| v.as_str()) else {
+            run.report.warnings.push(format!("{} record without a uuid skipped", spec.name));
+            return Ok(());
+        };
+        let columns = Self::table_columns(&self.conn, spec.name)?;
         
-        if !conditions.is_empty() {
-            sql.push_str(" WHERE ");
-            sql.push_str(&conditions.join(" AND "));
+        // The incoming record in local terms: links resolved to ids (compared by the local
+        // record's uuid), columns this database doesn't have dropped
+        let mut values: Vec<(String, Value)> = Vec::new();
+        let mut comparable = serde_json::Map::new();
+        let mut unresolved: Vec<(&'static str, &'static str, String)> = Vec::new();
+        for (column, value) in incoming {
+            if LOCAL_COLUMNS.contains(&column.as_str()) || !columns.contains(column) {
+                continue;
+            }
+            let link = spec.foreign_keys.iter().find(|(c, _, _)| c == column);
+            let (sql_value, compared) = match (link, value.as_str()) {
+                (Some(&(column, table, required)), Some(linked)) => match self.id_for_uuid(table, linked, run)? {
+                    Some(id) => (Value::Integer(id), serde_json::json!(self.uuid_for_id(table, id)?)),
+                    None if required => {
+                        run.report.warnings.push(format!("{} {} skipped: {} {} is not here", spec.name, uuid, table, linked));
+                        return Ok(());
+                    }
+                    None => {
+                        unresolved.push((column, table, linked.to_string()));
+                        (Value::Null, value.clone())
+                    }
+                },
+                (Some(_), None) => (Value::Null, serde_json::Value::Null),
+                (None, _) => (json_to_sql(value), value.clone()),
+            };
+            values.push((column.clone(), sql_value));
+            comparable.insert(column.clone(), compared);
         }
         
-        sql.push_str(" ORDER BY ch.created_at DESC");
-
-        let mut stmt = self.conn.prepare(&sql)?;
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let changes = stmt.query_map(params.as_slice(), Self::row_to_change)?
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(changes)
+        let local_id = match self.id_for_uuid(spec.name, uuid, run)? {
+            Some(id) => Some(id),
+            None => self.match_natural_key(spec, &values)?,
+        };
+        let (id, fields): (i64, Vec<String>) = match local_id {
+            None => match self.insert_merged_record(spec, uuid, &mut values, run) {
+                Ok(id) => {
+                    *run.report.created.entry(spec.name.to_string()).or_insert(0) += 1;
+                    (id, values.iter().map(|(c, _)| c.clone()).collect())
+                }
+                Err(e) => {
+                    run.report.warnings.push(format!("{} {} skipped: {}", spec.name, uuid, e));
+                    return Ok(());
+                }
+            },
+            Some(id) => {
+                run.aliases.insert(uuid.to_string(), id);
+                let base = self.merge_base(uuid)?;
+                let fields = self.merge_fields(spec, id, uuid, &comparable, base.as_ref(), run)?;
+                let changes: Vec<&(String, Value)> = values.iter().filter(|(c, _)| fields.contains(c)).collect();
+                if changes.is_empty() {
+                    *run.report.unchanged.entry(spec.name.to_string()).or_insert(0) += 1;
+                } else {
+                    let assignments: Vec<String> = changes.iter().map(|(c, _)| format!("{} = ?", c)).collect();
+                    let sql = format!("UPDATE {} SET {} WHERE id = ?", spec.name, assignments.join(", "));
+                    let params = changes.iter().map(|(_, v)| v.clone()).chain(std::iter::once(Value::Integer(id)));
+                    if let Err(e) = self.execute(&sql, params_from_iter(params)) {
+                        run.report.warnings.push(format!("{} {} not updated: {}", spec.name, uuid, e));
+                        return Ok(());
+                    }
+                    *run.report.updated.entry(spec.name.to_string()).or_insert(0) += 1;
+                }
+                (id, fields)
+            }
+        };
+        
+        for (column, table, linked) in unresolved {
+            if fields.iter().any(|f| f == column) {
+                run.pending_links.push((spec.name, id, column, table, linked));
+            }
+        }
+        self.save_merge_base(spec.name, incoming)
     }
 
-    pub fn get_all_changes(&self, project_id: Option<i64>, component_id: Option<i64>) -> Result<Vec<Change>> {
-        let mut sql = String::from(
-            "SELECT ch.id, ch.component_id, ch.field_name, ch.old_value, ch.new_value, ch.change_type, ch.reason, ch.created_at 
-             FROM changes ch"
-        );
+    /// The fields to take from the incoming record, recording conflicts along the way.
+    fn merge_fields(
+        &self,
+        spec: &MergeTable,
+        id: i64,
+        uuid: &str,
+        incoming: &serde_json::Map<String, serde_json::Value>,
+        base: Option<&serde_json::Map<String, serde_json::Value>>,
+        run: &mut MergeRun,
+    ) -> Result<Vec<String>> {
+        let Some((_, local)) = self.portable_records(spec, "id = ?", id)?.pop() else { return Ok(Vec::new()) };
+        let null = serde_json::Value::Null;
+        let diffs: Vec<(&String, &serde_json::Value, &serde_json::Value, Option<&serde_json::Value>)> = incoming
+            .iter()
+            .filter(|(field, _)| field.as_str() != spec.modified && !CREATE_ONLY_COLUMNS.contains(&(spec.name, field.as_str())))
+            .map(|(field, theirs)| (field, local.get(field).unwrap_or(&null), theirs, base.map(|b| b.get(field).unwrap_or(&null))))
+            .filter(|(_, ours, theirs, _)| ours != theirs)
+            .collect();
         
-        let mut conditions = Vec::new();
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        // Timestamps are all UTC "YYYY-MM-DD HH:MM:SS", so they compare as text
+        let timestamp = |record: &serde_json::Map<String, serde_json::Value>| {
+            record.get(spec.modified).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        };
+        let incoming_newer = timestamp(incoming) > timestamp(&local);
         
-        if project_id.is_some() || component_id.is_some() {
-            sql.push_str(" JOIN components c ON ch.component_id = c.id");
-        }
+        let mut taken = Vec::new();
+        let mut conflict = |field: &String, ours: &serde_json::Value, theirs: &serde_json::Value, take: bool| {
+            run.report.conflicts.push(MergeConflict {
+                table: spec.name.to_string(),
+                uuid: uuid.to_string(),
+                record_id: id,
+                field: field.clone(),
+                local: ours.clone(),
+                incoming: theirs.clone(),
+                resolution: if take { "incoming" } else { "local" }.to_string(),
+            });
+        };
         
-        if let Some(pid) = project_id {
-            conditions.push("c.project_id = ?".to_string());
-            param_values.push(Box::new(pid));
+        if run.field_level {
+            for &(field, ours, theirs, base) in &diffs {
+                // Without a base, an empty field is taken to be one the other side filled in
+                let one_sided = match base {
+                    Some(base) if ours == base => Some(true),
+                    Some(base) if theirs == base => Some(false),
+                    None if ours.is_null() => Some(true),
+                    None if theirs.is_null() => Some(false),
+                    _ => None,
+                };
+                let take = one_sided.unwrap_or(incoming_newer);
+                if one_sided.is_none() {
+                    conflict(field, ours, theirs, take);
+                }
+                if take {
+                    taken.push(field.clone());
+                }
+            }
+        } else {
+            let ours_changed = diffs.iter().any(|&(_, ours, _, base)| base.is_none_or(|b| ours != b));
+            let theirs_changed = diffs.iter().any(|&(_, _, theirs, base)| base.is_none_or(|b| theirs != b));
+            let take = !ours_changed || (theirs_changed && incoming_newer);
+            for &(field, ours, theirs, base) in &diffs {
+                // Every edit the losing side made is a conflict, even where the winner didn't touch the field
+                let lost = if take { ours } else { theirs };
+                if ours_changed && theirs_changed && base.is_none_or(|b| lost != b) {
+                    conflict(field, ours, theirs, take);
+                }
+                if take {
+                    taken.push(field.clone());
+                }
+            }
         }
-        if let Some(cid) = component_id {
-            conditions.push("ch.component_id = ?".to_string());
-            param_values.push(Box::new(cid));
+        Ok(taken)
+    }
+
+    fn insert_merged_record(&self, spec: &MergeTable, uuid: &str, values: &mut [(String, Value)], run: &mut MergeRun) -> Result<i64> {
+        if spec.name == "projects" {
+            if let Some((_, Value::Text(name))) = values.iter_mut().find(|(c, _)| c == "name") {
+                let original = name.clone();
+                let mut counter = 1;
+                while self.query_row("SELECT COUNT(*) FROM projects WHERE name = ? COLLATE NOCASE", params![*name], |row| row.get::<_, i64>(0))? > 0 {
+                    *name = if counter == 1 { format!("{} (imported)", original) } else { format!("{} (imported {})", original, counter) };
+                    counter += 1;
+                }
+                if *name != original {
+                    run.report.renamed_project = Some(serde_json::json!({ "from": original, "to": name }));
+                }
+            }
         }
-        
-        if !conditions.is_empty() {
-            sql.push_str(" WHERE ");
-            sql.push_str(&conditions.join(" AND "));
+        let columns: Vec<&str> = values.iter().map(|(c, _)| c.as_str()).chain(std::iter::once("uuid")).collect();
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            spec.name,
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+        let params = values.iter().map(|(_, v)| v.clone()).chain(std::iter::once(Value::Text(uuid.to_string())));
+        self.execute(&sql, params_from_iter(params))?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// A record of this database with the same natural key, e.g. a component both sides
+    /// created under the same name.
+    fn match_natural_key(&self, spec: &MergeTable, values: &[(String, Value)]) -> Result<Option<i64>> {
+        if spec.natural_key.is_empty() {
+            return Ok(None);
         }
+        let mut key = Vec::new();
+        for column in spec.natural_key {
+            match values.iter().find(|(c, _)| c == column) {
+                Some((_, value)) if *value != Value::Null => key.push(value.clone()),
+                _ => return Ok(None),
+            }
+        }
+        let conditions: Vec<String> = spec.natural_key.iter().map(|c| format!("{} = ?", c)).collect();
+        let sql = format!("SELECT id FROM {} WHERE {}", spec.name, conditions.join(" AND "));
+        match self.query_row(&sql, params_from_iter(key), |row| row.get(0)) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Rows of a merge table as portable records, keyed by their local id.
+    fn portable_records(
+        &self,
+        spec: &MergeTable,
+        condition: &str,
+        param: i64,
+    ) -> Result<Vec<(i64, serde_json::Map<String, serde_json::Value>)>> {
+        let columns: Vec<String> = Self::table_columns(&self.conn, spec.name)?
+            .into_iter()
+            .filter(|c| !LOCAL_COLUMNS.contains(&c.as_str()))
+            .collect();
+        let sql = format!("SELECT id, uuid, {} FROM {} WHERE {}", columns.join(", "), spec.name, condition);
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let mut rows = stmt.query_map(params![param], |row| {
+            let mut record = serde_json::Map::new();
+            record.insert("uuid".to_string(), serde_json::json!(row.get::<_, Option<String>>(1)?));
+            for (i, column) in columns.iter().enumerate() {
+                record.insert(column.clone(), sql_to_json(row.get(i + 2)?));
+            }
+            Ok((row.get::<_, i64>(0)?, record))
+        })?.collect::<Result<Vec<_>>>()?;
         
-        sql.push_str(" ORDER BY ch.created_at DESC");
+        for (_, record) in &mut rows {
+            for &(column, table, _) in spec.foreign_keys {
+                if let Some(linked) = record.get(column).and_then(|v| v.as_i64()) {
+                    record.insert(column.to_string(), serde_json::json!(self.uuid_for_id(table, linked)?));
+                }
+            }
+        }
+        Ok(rows)
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let changes = stmt.query_map(params.as_slice(), Self::row_to_change)?
-            .collect::<Result<Vec<_>>>()?;
+    fn id_for_uuid(&self, table: &str, uuid: &str, run: &MergeRun) -> Result<Option<i64>> {
+        match run.aliases.get(uuid) {
+            Some(&id) => Ok(Some(id)),
+            None => self.find_by_uuid(table, uuid),
+        }
+    }
 
-        Ok(changes)
+    fn merge_base(&self, uuid: &str) -> Result<Option<serde_json::Map<String, serde_json::Value>>> {
+        match self.query_row("SELECT record FROM merge_bases WHERE uuid = ?", params![uuid], |row| row.get::<_, String>(0)) {
+            Ok(record) => Ok(serde_json::from_str(&record).ok()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
-    pub fn log_change(&self, component_id: i64, field_name: &str, old_value: Option<&str>, new_value: Option<&str>, change_type: &str, reason: Option<&str>) -> Result<Change> {
-        self.conn.execute(
-            "INSERT INTO changes (component_id, field_name, old_value, new_value, change_type, reason) VALUES (?, ?, ?, ?, ?, ?)",
-            params![component_id, field_name, old_value, new_value, change_type, reason],
-        )?;
-        
-        let id = self.conn.last_insert_rowid();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, component_id, field_name, old_value, new_value, change_type, reason, created_at 
-             FROM changes WHERE id = ?"
+    fn save_merge_base(&self, table: &str, record: &serde_json::Map<String, serde_json::Value>) -> Result<()> {
+        let Some(uuid) = record.get("uuid").and_then(|v| v.as_str()) else { return Ok(()) };
+        self.execute(
+            "INSERT INTO merge_bases (uuid, entity_type, record) VALUES (?, ?, ?) 
+             ON CONFLICT(uuid) DO UPDATE SET entity_type = excluded.entity_type, record = excluded.record, 
+                 merged_at = CURRENT_TIMESTAMP",
+            params![uuid, table, serde_json::Value::Object(record.clone()).to_string()],
         )?;
-        stmt.query_row(params![id], Self::row_to_change)
+        Ok(())
     }
 
     // ============================================================
-    // v1.1: ATTACHMENT OPERATIONS
+    // v1.4: REPORT OPERATIONS
     // ============================================================
 
-    fn row_to_attachment(row: &rusqlite::Row) -> rusqlite::Result<Attachment> {
-        Ok(Attachment {
-            id: row.get(0)?,
-            project_id: row.get(1)?,
-            component_id: row.get(2)?,
-            problem_id: row.get(3)?,
-            file_name: row.get(4)?,
-            file_path: row.get(5)?,
-            file_type: row.get(6)?,
-            file_size: row.get(7)?,
-            file_hash: row.get(8)?,
-            is_external: row.get(9)?,
-            user_description: row.get(10)?,
-            tags: row.get(11)?,
-            ai_description: row.get(12)?,
-            ai_summary: row.get(13)?,
-            content_extracted: row.get(14)?,
-            created_at: row.get(15)?,
-            updated_at: row.get(16)?,
-            indexed_at: row.get(17)?,
+    /// Activity since `since` (UTC, database format) in one project or all of them.
+    pub fn get_report_activity(&self, project_id: Option<i64>, since: &str) -> Result<ReportActivity> {
+        let session_totals: (i64, i64) = self.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(duration_minutes), 0) FROM sessions 
+             WHERE (?1 IS NULL OR project_id = ?1) AND started_at >= ?2",
+            params![project_id, since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(ReportActivity {
+            since: since.to_string(),
+            solved: self.report_items(
+                "SELECT pr.name, p.title, s.summary, p.solved_at FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 LEFT JOIN solutions s ON s.problem_id = p.id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND p.status = 'solved' AND p.solved_at >= ?2 
+                 ORDER BY p.solved_at",
+                params![project_id, since],
+            )?,
+            opened: self.report_items(
+                "SELECT pr.name, p.title, p.severity, p.created_at FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND p.created_at >= ?2 
+                 ORDER BY p.created_at",
+                params![project_id, since],
+            )?,
+            completed_todos: self.report_items(
+                "SELECT pr.name, t.title, t.priority, t.completed_at FROM todos t 
+                 JOIN projects pr ON pr.id = t.project_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND t.status = 'done' AND t.completed_at >= ?2 
+                 ORDER BY t.completed_at",
+                params![project_id, since],
+            )?,
+            learnings: self.report_items(
+                "SELECT pr.name, l.insight, l.category, l.created_at FROM learnings l 
+                 JOIN projects pr ON pr.id = l.project_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND l.created_at >= ?2 
+                 ORDER BY l.created_at",
+                params![project_id, since],
+            )?,
+            in_progress: self.report_items(
+                "SELECT pr.name, t.title, t.priority, t.due_date FROM todos t 
+                 JOIN projects pr ON pr.id = t.project_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND t.status = 'in_progress' 
+                 ORDER BY CASE t.priority WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END, t.due_date",
+                params![project_id],
+            )?,
+            blocked: self.report_items(
+                "SELECT pr.name, p.title, 'problem, ' || p.severity, p.updated_at FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND p.status = 'blocked' 
+                 UNION ALL 
+                 SELECT pr.name, t.title, 'todo' || COALESCE(', waiting on ' || bp.title, ''), t.updated_at FROM todos t 
+                 JOIN projects pr ON pr.id = t.project_id LEFT JOIN problems bp ON bp.id = t.blocked_by_problem_id 
+                 WHERE (?1 IS NULL OR pr.id = ?1) AND t.status = 'blocked' 
+                 ORDER BY 4",
+                params![project_id],
+            )?,
+            session_count: session_totals.0,
+            session_minutes: session_totals.1,
         })
     }
 
-    pub fn get_attachment(&self, id: i64) -> Result<Attachment> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, component_id, problem_id, file_name, file_path, file_type, 
-                    file_size, file_hash, is_external, user_description, tags, ai_description, 
-                    ai_summary, content_extracted, created_at, updated_at, indexed_at 
-             FROM attachments WHERE id = ?"
-        )?;
-        stmt.query_row(params![id], Self::row_to_attachment)
+    /// What a retrospective looks at in a project between `since` and `until`. `previous_since`
+    /// starts the period before, which ends at `since`. Problems count as struggles from
+    /// `min_failures` failed or abandoned attempts.
+    pub fn get_retro_activity(&self, project_id: i64, previous_since: &str, since: &str, until: &str, min_failures: i64) -> Result<RetroActivity> {
+        let throughput = |from: &str, to: &str| -> Result<(i64, i64)> {
+            self.query_row(
+                "SELECT (SELECT COUNT(*) FROM problems p JOIN components c ON c.id = p.component_id 
+                         WHERE c.project_id = ?1 AND p.status = 'solved' AND p.solved_at >= ?2 AND p.solved_at < ?3), 
+                        (SELECT COUNT(*) FROM todos t 
+                         WHERE t.project_id = ?1 AND t.status = 'done' AND t.completed_at >= ?2 AND t.completed_at < ?3)",
+                params![project_id, from, to],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+        };
+        let (solved, todos_done) = throughput(since, until)?;
+        let (previous_solved, previous_todos_done) = throughput(previous_since, since)?;
+        Ok(RetroActivity {
+            since: since.to_string(),
+            until: until.to_string(),
+            struggles: self.report_items(
+                "SELECT pr.name, p.title, CAST(COUNT(*) AS TEXT), MAX(a.created_at) FROM solution_attempts a 
+                 JOIN problems p ON p.id = a.problem_id JOIN components c ON c.id = p.component_id 
+                 JOIN projects pr ON pr.id = c.project_id 
+                 WHERE pr.id = ?1 AND a.outcome IN ('failure', 'abandoned') AND a.created_at >= ?2 AND a.created_at < ?3 
+                 GROUP BY p.id HAVING COUNT(*) >= ?4 
+                 ORDER BY COUNT(*) DESC, p.title",
+                params![project_id, since, until, min_failures],
+            )?,
+            quick_solves: self.report_items(
+                "SELECT pr.name, p.title, p.severity, p.solved_at FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE pr.id = ?1 AND p.status = 'solved' AND p.solved_at >= ?2 AND p.solved_at < ?3 
+                   AND NOT EXISTS (SELECT 1 FROM solution_attempts a WHERE a.problem_id = p.id AND a.outcome IN ('failure', 'abandoned')) 
+                 ORDER BY CASE p.severity WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END, p.solved_at",
+                params![project_id, since, until],
+            )?,
+            abandoned_todos: self.report_items(
+                "SELECT pr.name, t.title, t.priority, t.updated_at FROM todos t 
+                 JOIN projects pr ON pr.id = t.project_id 
+                 WHERE pr.id = ?1 AND t.status = 'cancelled' AND t.updated_at >= ?2 AND t.updated_at < ?3 
+                 ORDER BY t.updated_at",
+                params![project_id, since, until],
+            )?,
+            verified_learnings: self.report_items(
+                "SELECT pr.name, l.insight, l.category, l.updated_at FROM learnings l 
+                 JOIN projects pr ON pr.id = l.project_id 
+                 WHERE pr.id = ?1 AND l.verified = 1 AND l.updated_at >= ?2 AND l.updated_at < ?3 
+                 ORDER BY l.updated_at",
+                params![project_id, since, until],
+            )?,
+            blocked: self.report_items(
+                "SELECT pr.name, p.title, 'problem, ' || p.severity, p.updated_at FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE pr.id = ?1 AND p.status = 'blocked' 
+                 UNION ALL 
+                 SELECT pr.name, t.title, 'todo', t.updated_at FROM todos t 
+                 JOIN projects pr ON pr.id = t.project_id 
+                 WHERE pr.id = ?1 AND t.status = 'blocked' 
+                 ORDER BY 4",
+                params![project_id],
+            )?,
+            solved,
+            todos_done,
+            previous_solved,
+            previous_todos_done,
+        })
     }
 
-    pub fn get_attachments(&self, project_id: i64, component_id: Option<i64>, problem_id: Option<i64>) -> Result<Vec<Attachment>> {
-        let mut sql = String::from(
-            "SELECT id, project_id, component_id, problem_id, file_name, file_path, file_type, 
-                    file_size, file_hash, is_external, user_description, tags, ai_description, 
-                    ai_summary, content_extracted, created_at, updated_at, indexed_at 
-             FROM attachments WHERE project_id = ?"
-        );
-        
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(project_id)];
-        
-        if let Some(cid) = component_id {
-            sql.push_str(" AND component_id = ?");
-            param_values.push(Box::new(cid));
-        }
-        if let Some(pid) = problem_id {
-            sql.push_str(" AND problem_id = ?");
-            param_values.push(Box::new(pid));
-        }
-        
-        sql.push_str(" ORDER BY created_at DESC");
-        
-        let mut stmt = self.conn.prepare(&sql)?;
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
-        let attachments = stmt.query_map(params.as_slice(), Self::row_to_attachment)?
-            .collect::<Result<Vec<_>>>()?;
-        
-        Ok(attachments)
+    /// A project's changes and solved problems from `since` until `until` (or now), oldest first.
+    pub fn get_changelog_items(&self, project_id: i64, since: &str, until: Option<&str>) -> Result<Vec<ChangelogItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT 'change', ch.id, c.name, ch.change_type, ch.field_name, ch.old_value, ch.new_value, ch.reason, ch.created_at 
+             FROM changes ch JOIN components c ON c.id = ch.component_id 
+             WHERE c.project_id = ?1 AND ch.created_at >= ?2 AND (?3 IS NULL OR ch.created_at < ?3) 
+             UNION ALL 
+             SELECT 'problem', p.id, c.name, NULL, p.title, NULL, NULL, s.summary, p.solved_at 
+             FROM problems p JOIN components c ON c.id = p.component_id LEFT JOIN solutions s ON s.problem_id = p.id 
+             WHERE c.project_id = ?1 AND p.status = 'solved' AND p.solved_at >= ?2 AND (?3 IS NULL OR p.solved_at < ?3) 
+             ORDER BY 9, 2"
+        )?;
+        let items = stmt.query_map(params![project_id, since, until], |row| {
+            Ok(ChangelogItem {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                component: row.get(2)?,
+                change_type: row.get(3)?,
+                title: row.get(4)?,
+                old_value: row.get(5)?,
+                new_value: row.get(6)?,
+                detail: row.get(7)?,
+                at: row.get(8)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(items)
     }
 
-    pub fn create_attachment(
-        &self,
-        project_id: i64,
-        file_name: &str,
-        file_path: &str,
-        file_type: &str,
-        file_size: Option<i64>,
-        file_hash: Option<&str>,
-        is_external: bool,
-        component_id: Option<i64>,
-        problem_id: Option<i64>,
-        user_description: Option<&str>,
-        tags: Option<&str>,
-    ) -> Result<Attachment> {
-        self.conn.execute(
-            "INSERT INTO attachments (project_id, component_id, problem_id, file_name, file_path, 
-             file_type, file_size, file_hash, is_external, user_description, tags) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![project_id, component_id, problem_id, file_name, file_path, 
-                    file_type, file_size, file_hash, is_external, user_description, tags],
+    /// A project's problems solved between two dates (inclusive; `until` defaults to today),
+    /// leaving out any tagged "internal".
+    pub fn get_release_note_items(&self, project_id: i64, since: &str, until: Option<&str>) -> Result<Vec<ReleaseNoteItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT p.id, c.name, p.title, s.summary, s.key_insight, p.solved_at 
+             FROM problems p JOIN components c ON c.id = p.component_id LEFT JOIN solutions s ON s.problem_id = p.id 
+             WHERE c.project_id = ?1 AND p.status = 'solved' 
+               AND DATE(p.solved_at) >= DATE(?2) AND DATE(p.solved_at) <= DATE(COALESCE(?3, 'now')) 
+               AND NOT EXISTS (SELECT 1 FROM tags t WHERE t.entity_type = 'problem' AND t.entity_id = p.id AND t.tag = 'internal') 
+             ORDER BY c.name, p.solved_at"
         )?;
-        self.get_attachment(self.conn.last_insert_rowid())
+        let items = stmt.query_map(params![project_id, since, until], |row| {
+            Ok(ReleaseNoteItem {
+                problem_id: row.get(0)?,
+                component: row.get(1)?,
+                title: row.get(2)?,
+                summary: row.get(3)?,
+                key_insight: row.get(4)?,
+                solved_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(items)
     }
 
-    pub fn update_attachment(
+    fn report_items<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<ReportItem>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let items = stmt.query_map(params, |row| {
+            Ok(ReportItem {
+                project_name: row.get(0)?,
+                title: row.get(1)?,
+                detail: row.get(2)?,
+                at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(items)
+    }
+
+    fn row_to_report_email(row: &rusqlite::Row) -> rusqlite::Result<ReportEmail> {
+        Ok(ReportEmail {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            project_id: row.get(2)?,
+            recipients: row.get(3)?,
+            subject: row.get(4)?,
+            status: row.get(5)?,
+            error: row.get(6)?,
+            scheduled: row.get(7)?,
+            sent_at: row.get(8)?,
+        })
+    }
+
+    pub fn log_report_email(
         &self,
-        id: i64,
-        user_description: Option<&str>,
-        tags: Option<&str>,
-        ai_description: Option<&str>,
-        ai_summary: Option<&str>,
-        content_extracted: Option<bool>,
-    ) -> Result<Attachment> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if let Some(ud) = user_description {
-            updates.push("user_description = ?");
-            values.push(Box::new(ud.to_string()));
-        }
-        if let Some(t) = tags {
-            updates.push("tags = ?");
-            values.push(Box::new(t.to_string()));
-        }
-        if let Some(ad) = ai_description {
-            updates.push("ai_description = ?");
-            values.push(Box::new(ad.to_string()));
-        }
-        if let Some(as_) = ai_summary {
-            updates.push("ai_summary = ?");
-            values.push(Box::new(as_.to_string()));
-        }
-        if let Some(ce) = content_extracted {
-            updates.push("content_extracted = ?");
-            values.push(Box::new(ce));
-        }
-        
-        // Always update indexed_at if AI fields are being set
-        if ai_description.is_some() || ai_summary.is_some() {
-            updates.push("indexed_at = CURRENT_TIMESTAMP");
-        }
-        
-        if updates.is_empty() {
-            return self.get_attachment(id);
-        }
-        
-        values.push(Box::new(id));
-        
-        let sql = format!("UPDATE attachments SET {} WHERE id = ?", updates.join(", "));
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_attachment(id)
+        kind: &str,
+        project_id: Option<i64>,
+        recipients: &str,
+        subject: &str,
+        error: Option<&str>,
+        scheduled: bool,
+    ) -> Result<ReportEmail> {
+        self.execute(
+            "INSERT INTO report_emails (kind, project_id, recipients, subject, status, error, scheduled) 
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![kind, project_id, recipients, subject, if error.is_some() { "failed" } else { "sent" }, error, scheduled],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.query_row(
+            "SELECT id, kind, project_id, recipients, subject, status, error, scheduled, sent_at FROM report_emails WHERE id = ?",
+            params![id],
+            Self::row_to_report_email,
+        )
+    }
+
+    /// Send history, newest first.
+    pub fn get_report_emails(&self, limit: i64) -> Result<Vec<ReportEmail>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, kind, project_id, recipients, subject, status, error, scheduled, sent_at 
+             FROM report_emails ORDER BY sent_at DESC, id DESC LIMIT ?"
+        )?;
+        let emails = stmt.query_map(params![limit], Self::row_to_report_email)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(emails)
     }
 
-    pub fn delete_attachment(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM attachments WHERE id = ?", params![id])?;
-        Ok(())
+    /// Scheduled sends made during a local calendar day, given as its UTC bounds (see
+    /// `Zone::day_bounds`).
+    pub fn get_scheduled_report_emails(&self, day_start: &str, day_end: &str) -> Result<Vec<ReportEmail>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, kind, project_id, recipients, subject, status, error, scheduled, sent_at 
+             FROM report_emails WHERE scheduled AND sent_at >= ? AND sent_at < ? ORDER BY id"
+        )?;
+        let emails = stmt.query_map(params![day_start, day_end], Self::row_to_report_email)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(emails)
     }
 
     // ============================================================
-    // v1.1: CONTENT LOCATION OPERATIONS
+    // v1.4: ANOMALY ALERT OPERATIONS
     // ============================================================
 
-    fn row_to_content_location(row: &rusqlite::Row) -> rusqlite::Result<ContentLocation> {
-        Ok(ContentLocation {
+    /// Look for unusual activity in active projects and record an alert for each new finding:
+    /// a day with several times the usual new problems, an open problem that has piled up
+    /// failed attempts, or a week without any activity. Each occurrence is only alerted once,
+    /// so this can run as often as it likes. Returns the new alerts.
+    pub fn detect_anomalies(&self) -> Result<Vec<AnomalyAlert>> {
+        let failed_attempts = self.get_setting_value("anomaly_failed_attempts")?
+            .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+            .unwrap_or(ANOMALY_FAILED_ATTEMPTS);
+        let last_id: i64 = self.query_row("SELECT COALESCE(MAX(id), 0) FROM anomaly_alerts", [], |row| row.get(0))?;
+
+        // New problems in the last day against the daily average of the four weeks before
+        self.execute(
+            "INSERT OR IGNORE INTO anomaly_alerts (project_id, kind, message, observed, expected, dedupe_key) 
+             SELECT id, 'problem_spike', 
+                    CASE WHEN baseline * 28 < 1 THEN printf('%d new problems in the last day, after none in the four weeks before', recent) 
+                         ELSE printf('%d new problems in the last day, against %.2f a day normally', recent, baseline) END, 
+                    recent, baseline, 'problem_spike:' || id || ':' || DATE('now') 
+             FROM (SELECT pr.id, 
+                          (SELECT COUNT(*) FROM problems p JOIN components c ON c.id = p.component_id 
+                           WHERE c.project_id = pr.id AND p.created_at >= DATETIME('now', '-1 day')) AS recent, 
+                          (SELECT COUNT(*) FROM problems p JOIN components c ON c.id = p.component_id 
+                           WHERE c.project_id = pr.id AND p.created_at >= DATETIME('now', '-29 days') 
+                             AND p.created_at < DATETIME('now', '-1 day')) / 28.0 AS baseline 
+                   FROM projects pr WHERE pr.status = 'active') 
+             WHERE recent >= ?1 AND recent >= ?2 * baseline",
+            params![ANOMALY_SPIKE_MIN_PROBLEMS, ANOMALY_SPIKE_FACTOR],
+        )?;
+        self.execute(
+            "INSERT OR IGNORE INTO anomaly_alerts (project_id, problem_id, kind, message, observed, dedupe_key) 
+             SELECT c.project_id, p.id, 'attempt_pileup', 
+                    printf('\"%s\" has %d failed attempts and is still unsolved', p.title, COUNT(*)), 
+                    COUNT(*), 'attempt_pileup:' || p.id 
+             FROM problems p JOIN components c ON c.id = p.component_id 
+             JOIN projects pr ON pr.id = c.project_id 
+             JOIN solution_attempts a ON a.problem_id = p.id AND a.outcome IN ('failure', 'abandoned') 
+             WHERE pr.status = 'active' AND p.status NOT IN ('solved', 'wont_fix') 
+             GROUP BY p.id HAVING COUNT(*) >= ?1",
+            params![failed_attempts],
+        )?;
+        // Keyed by the last activity, so a project that wakes up and goes quiet again alerts again
+        self.execute(
+            "INSERT OR IGNORE INTO anomaly_alerts (project_id, kind, message, observed, dedupe_key) 
+             SELECT id, 'inactive_project', printf('No activity for %d days', idle), idle, 
+                    'inactive_project:' || id || ':' || last_activity 
+             FROM (SELECT id, last_activity, CAST(julianday('now') - julianday(last_activity) AS INTEGER) AS idle 
+                   FROM (SELECT pr.id, MAX(pr.created_at, 
+                                COALESCE((SELECT MAX(ch.created_at) FROM changes ch JOIN components c ON c.id = ch.component_id WHERE c.project_id = pr.id), ''), 
+                                COALESCE((SELECT MAX(p.updated_at) FROM problems p JOIN components c ON c.id = p.component_id WHERE c.project_id = pr.id), ''), 
+                                COALESCE((SELECT MAX(a.created_at) FROM solution_attempts a JOIN problems p ON p.id = a.problem_id 
+                                          JOIN components c ON c.id = p.component_id WHERE c.project_id = pr.id), ''), 
+                                COALESCE((SELECT MAX(t.updated_at) FROM todos t WHERE t.project_id = pr.id), ''), 
+                                COALESCE((SELECT MAX(l.updated_at) FROM learnings l WHERE l.project_id = pr.id), ''), 
+                                COALESCE((SELECT MAX(s.started_at) FROM sessions s WHERE s.project_id = pr.id), '')) AS last_activity 
+                         FROM projects pr WHERE pr.status = 'active')) 
+             WHERE idle >= ?1",
+            params![ANOMALY_IDLE_DAYS],
+        )?;
+
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, problem_id, kind, message, observed, expected, created_at, acknowledged_at 
+             FROM anomaly_alerts WHERE id > ? ORDER BY id"
+        )?;
+        let alerts = stmt.query_map(params![last_id], Self::row_to_anomaly_alert)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(alerts)
+    }
+
+    /// Newest first. Acknowledged alerts are left out unless asked for.
+    pub fn get_anomaly_alerts(&self, project_id: Option<i64>, include_acknowledged: bool) -> Result<Vec<AnomalyAlert>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, problem_id, kind, message, observed, expected, created_at, acknowledged_at 
+             FROM anomaly_alerts WHERE (?1 IS NULL OR project_id = ?1) AND (?2 OR acknowledged_at IS NULL) 
+             ORDER BY created_at DESC, id DESC"
+        )?;
+        let alerts = stmt.query_map(params![project_id, include_acknowledged], Self::row_to_anomaly_alert)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(alerts)
+    }
+
+    pub fn acknowledge_anomaly_alert(&self, id: i64) -> Result<AnomalyAlert> {
+        self.execute(
+            "UPDATE anomaly_alerts SET acknowledged_at = CURRENT_TIMESTAMP WHERE id = ? AND acknowledged_at IS NULL",
+            params![id],
+        )?;
+        self.query_row(
+            "SELECT id, project_id, problem_id, kind, message, observed, expected, created_at, acknowledged_at 
+             FROM anomaly_alerts WHERE id = ?",
+            params![id],
+            Self::row_to_anomaly_alert,
+        )
+    }
+
+    fn row_to_anomaly_alert(row: &rusqlite::Row) -> rusqlite::Result<AnomalyAlert> {
+        Ok(AnomalyAlert {
             id: row.get(0)?,
-            attachment_id: row.get(1)?,
-            description: row.get(2)?,
-            category: row.get(3)?,
-            location_type: row.get(4)?,
-            start_location: row.get(5)?,
-            end_location: row.get(6)?,
-            snippet: row.get(7)?,
-            related_problem_id: row.get(8)?,
-            related_solution_id: row.get(9)?,
-            related_learning_id: row.get(10)?,
-            related_component_id: row.get(11)?,
-            created_at: row.get(12)?,
+            project_id: row.get(1)?,
+            problem_id: row.get(2)?,
+            kind: row.get(3)?,
+            message: row.get(4)?,
+            observed: row.get(5)?,
+            expected: row.get(6)?,
+            created_at: row.get(7)?,
+            acknowledged_at: row.get(8)?,
         })
     }
 
-    pub fn get_content_location(&self, id: i64) -> Result<ContentLocation> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, attachment_id, description, category, location_type, start_location, 
-                    end_location, snippet, related_problem_id, related_solution_id, 
-                    related_learning_id, related_component_id, created_at 
-             FROM content_locations WHERE id = ?"
+    // ============================================================
+    // v1.4: API TOKEN OPERATIONS
+    // ============================================================
+
+    pub fn create_api_token(&self, name: &str, token_hash: &str, token_prefix: &str, permission: &str, project_id: Option<i64>) -> Result<ApiToken> {
+        self.execute(
+            "INSERT INTO api_tokens (name, token_hash, token_prefix, permission, project_id) VALUES (?, ?, ?, ?, ?)",
+            params![name, token_hash, token_prefix, permission, project_id],
         )?;
-        stmt.query_row(params![id], Self::row_to_content_location)
+        self.get_api_token(self.conn.last_insert_rowid())
     }
 
-    pub fn get_content_locations_for_attachment(&self, attachment_id: i64) -> Result<Vec<ContentLocation>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, attachment_id, description, category, location_type, start_location, 
-                    end_location, snippet, related_problem_id, related_solution_id, 
-                    related_learning_id, related_component_id, created_at 
-             FROM content_locations WHERE attachment_id = ? ORDER BY start_location"
+    pub fn get_api_token(&self, id: i64) -> Result<ApiToken> {
+        self.query_row(
+            "SELECT id, name, token_prefix, permission, project_id, created_at, last_used_at, revoked_at 
+             FROM api_tokens WHERE id = ?",
+            params![id],
+            Self::row_to_api_token,
+        )
+    }
+
+    /// Newest first; revoked tokens only when asked for.
+    pub fn list_api_tokens(&self, include_revoked: bool) -> Result<Vec<ApiToken>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, name, token_prefix, permission, project_id, created_at, last_used_at, revoked_at 
+             FROM api_tokens WHERE ?1 OR revoked_at IS NULL ORDER BY id DESC"
         )?;
-        let locations = stmt.query_map(params![attachment_id], Self::row_to_content_location)?
+        let tokens = stmt.query_map(params![include_revoked], Self::row_to_api_token)?
             .collect::<Result<Vec<_>>>()?;
-        Ok(locations)
+        Ok(tokens)
     }
 
-    pub fn create_content_location(
-        &self,
-        attachment_id: i64,
-        description: &str,
-        category: Option<&str>,
-        location_type: &str,
-        start_location: &str,
-        end_location: Option<&str>,
-        snippet: Option<&str>,
-        related_problem_id: Option<i64>,
-        related_solution_id: Option<i64>,
-        related_learning_id: Option<i64>,
-        related_component_id: Option<i64>,
-    ) -> Result<ContentLocation> {
-        self.conn.execute(
-            "INSERT INTO content_locations (attachment_id, description, category, location_type, 
-             start_location, end_location, snippet, related_problem_id, related_solution_id, 
-             related_learning_id, related_component_id) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![attachment_id, description, category, location_type, start_location, 
-                    end_location, snippet, related_problem_id, related_solution_id, 
-                    related_learning_id, related_component_id],
+    pub fn revoke_api_token(&self, id: i64) -> Result<ApiToken> {
+        self.execute(
+            "UPDATE api_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE id = ? AND revoked_at IS NULL",
+            params![id],
         )?;
-        self.get_content_location(self.conn.last_insert_rowid())
+        self.get_api_token(id)
     }
 
-    pub fn delete_content_location(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM content_locations WHERE id = ?", params![id])?;
+    /// The unrevoked token with this hash, marked as used just now.
+    pub fn use_api_token(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+        let id: Option<i64> = self.query_row(
+            "SELECT MAX(id) FROM api_tokens WHERE token_hash = ? AND revoked_at IS NULL",
+            params![token_hash],
+            |row| row.get(0),
+        )?;
+        let Some(id) = id else { return Ok(None) };
+        self.execute("UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?", params![id])?;
+        self.get_api_token(id).map(Some)
+    }
+
+    /// Record one local API request, and drop entries older than 30 days.
+    pub fn log_api_request(&self, caller: &str, token_id: Option<i64>, method: &str, endpoint: &str, status: u16, latency_ms: i64) -> Result<()> {
+        self.execute(
+            "INSERT INTO api_requests (caller, token_id, method, endpoint, status, latency_ms) VALUES (?, ?, ?, ?, ?, ?)",
+            params![caller, token_id, method, endpoint, status, latency_ms],
+        )?;
+        self.execute("DELETE FROM api_requests WHERE created_at < DATETIME('now', '-30 days')", [])?;
         Ok(())
     }
 
-    // ============================================================
-    // v1.1: EXTRACTION OPERATIONS
-    // ============================================================
+    /// Note a webhook delivery under each of the names it's known by. False, and nothing noted,
+    /// when one of them was seen in the last 30 days.
+    pub fn claim_webhook_delivery(&self, source: &str, deliveries: &[String]) -> Result<bool> {
+        self.execute("DELETE FROM webhook_deliveries WHERE received_at < DATETIME('now', '-30 days')", [])?;
+        for delivery in deliveries {
+            let seen: i64 = self.query_row(
+                "SELECT COUNT(*) FROM webhook_deliveries WHERE source = ? AND delivery = ?",
+                params![source, delivery],
+                |row| row.get(0),
+            )?;
+            if seen > 0 {
+                return Ok(false);
+            }
+        }
+        for delivery in deliveries {
+            self.execute(
+                "INSERT OR IGNORE INTO webhook_deliveries (source, delivery) VALUES (?, ?)",
+                params![source, delivery],
+            )?;
+        }
+        Ok(true)
+    }
 
-    fn row_to_extraction(row: &rusqlite::Row) -> rusqlite::Result<Extraction> {
-        Ok(Extraction {
+    /// Forget a delivery that wasn't applied, so the sender's retry goes through.
+    pub fn release_webhook_delivery(&self, source: &str, deliveries: &[String]) -> Result<()> {
+        for delivery in deliveries {
+            self.execute(
+                "DELETE FROM webhook_deliveries WHERE source = ? AND delivery = ?",
+                params![source, delivery],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Requests since `since` (a database timestamp), optionally one token's only.
+    pub fn get_api_usage_stats(&self, since: &str, token_id: Option<i64>) -> Result<ApiUsageStats> {
+        let (requests, errors, rate_limited, avg_latency_ms, max_latency_ms) = self.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(status >= 400), 0), COALESCE(SUM(status = 429), 0), 
+                    COALESCE(AVG(latency_ms), 0), COALESCE(MAX(latency_ms), 0) 
+             FROM api_requests WHERE created_at >= ?1 AND (?2 IS NULL OR token_id = ?2)",
+            params![since, token_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )?;
+        let group = |key: &str| -> Result<Vec<ApiUsageGroup>> {
+            let sql = format!(
+                "SELECT {} AS key, COUNT(*), SUM(r.status >= 400), AVG(r.latency_ms) 
+                 FROM api_requests r LEFT JOIN api_tokens t ON t.id = r.token_id 
+                 WHERE r.created_at >= ?1 AND (?2 IS NULL OR r.token_id = ?2) 
+                 GROUP BY key ORDER BY COUNT(*) DESC",
+                key,
+            );
+            let mut stmt = self.conn.prepare_cached(&sql)?;
+            let groups = stmt.query_map(params![since, token_id], |row| {
+                Ok(ApiUsageGroup {
+                    key: row.get(0)?,
+                    requests: row.get(1)?,
+                    errors: row.get(2)?,
+                    avg_latency_ms: row.get(3)?,
+                })
+            })?.collect::<Result<Vec<_>>>()?;
+            Ok(groups)
+        };
+        Ok(ApiUsageStats {
+            since: since.to_string(),
+            requests,
+            errors,
+            rate_limited,
+            avg_latency_ms,
+            max_latency_ms,
+            by_endpoint: group("r.method || ' ' || r.endpoint")?,
+            by_caller: group(
+                "CASE r.caller WHEN 'automation' THEN 'automation token' WHEN 'none' THEN 'unauthenticated' 
+                 ELSE COALESCE(t.name, 'deleted token') END"
+            )?,
+            by_status: group("CAST(r.status AS TEXT)")?,
+        })
+    }
+
+    /// The project a todo or problem belongs to.
+    pub fn record_project_id(&self, entity_type: &str, id: i64) -> Result<i64> {
+        let sql = match entity_type {
+            "todo" => "SELECT project_id FROM todos WHERE id = ?",
+            "problem" => "SELECT c.project_id FROM problems p JOIN components c ON c.id = p.component_id WHERE p.id = ?",
+            other => return Err(rusqlite::Error::InvalidParameterName(format!("Unknown record type '{}'", other))),
+        };
+        self.query_row(sql, params![id], |row| row.get(0))
+    }
+
+    fn row_to_api_token(row: &rusqlite::Row) -> rusqlite::Result<ApiToken> {
+        Ok(ApiToken {
             id: row.get(0)?,
-            attachment_id: row.get(1)?,
-            record_type: row.get(2)?,
-            record_id: row.get(3)?,
-            source_location: row.get(4)?,
-            source_snippet: row.get(5)?,
-            confidence: row.get(6)?,
-            user_reviewed: row.get(7)?,
-            user_approved: row.get(8)?,
-            created_at: row.get(9)?,
+            name: row.get(1)?,
+            token_prefix: row.get(2)?,
+            permission: row.get(3)?,
+            project_id: row.get(4)?,
+            created_at: row.get(5)?,
+            last_used_at: row.get(6)?,
+            revoked_at: row.get(7)?,
         })
     }
 
-    pub fn get_extraction(&self, id: i64) -> Result<Extraction> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, attachment_id, record_type, record_id, source_location, source_snippet, 
-                    confidence, user_reviewed, user_approved, created_at 
-             FROM extractions WHERE id = ?"
+    // ============================================================
+    // v1.4: AUTOMATION OPERATIONS
+    // ============================================================
+
+    /// Open todos due on or before `local_date` (YYYY-MM-DD) plus those in progress, most
+    /// overdue first. `project_id` None covers every active project. Timed due dates count
+    /// when they fall before `day_end`, the UTC RFC 3339 time the local day ends.
+    pub fn get_agenda(&self, project_id: Option<i64>, local_date: &str, day_end: &str) -> Result<Vec<AgendaItem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT t.id, t.project_id, p.name, t.title, t.priority, t.status, t.due_date 
+             FROM todos t JOIN projects p ON p.id = t.project_id 
+             WHERE t.status IN ('pending', 'in_progress', 'blocked') 
+               AND (?1 IS NULL AND p.status = 'active' OR t.project_id = ?1) 
+               AND (length(t.due_date) = 10 AND t.due_date <= ?2 OR length(t.due_date) > 10 AND t.due_date < ?3 
+                    OR t.status = 'in_progress') 
+             ORDER BY t.due_date IS NULL, t.due_date, 
+                      CASE t.priority WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END, t.id"
         )?;
-        stmt.query_row(params![id], Self::row_to_extraction)
+        let items = stmt.query_map(params![project_id, local_date, day_end], |row| {
+            Ok(AgendaItem {
+                todo_id: row.get(0)?,
+                project_id: row.get(1)?,
+                project_name: row.get(2)?,
+                title: row.get(3)?,
+                priority: row.get(4)?,
+                status: row.get(5)?,
+                due_date: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(items)
     }
 
-    pub fn get_extractions_for_attachment(&self, attachment_id: i64) -> Result<Vec<Extraction>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, attachment_id, record_type, record_id, source_location, source_snippet, 
-                    confidence, user_reviewed, user_approved, created_at 
-             FROM extractions WHERE attachment_id = ? ORDER BY created_at"
-        )?;
-        let extractions = stmt.query_map(params![attachment_id], Self::row_to_extraction)?
-            .collect::<Result<Vec<_>>>()?;
-        Ok(extractions)
+    // ============================================================
+    // v1.4: LAUNCHER OPERATIONS
+    // ============================================================
+
+    /// Problems and todos whose titles contain every word of `query`. Open items come first,
+    /// then titles starting with the query, then the most recent.
+    pub fn quick_search(&self, query: &str, limit: i64) -> Result<Vec<QuickItem>> {
+        let escape = |text: &str| crate::query::escape_like(&text.to_lowercase());
+        let words: Vec<String> = query.split_whitespace().take(8).map(|w| format!("%{}%", escape(w))).collect();
+        let matches = |column: &str| -> String {
+            if words.is_empty() {
+                return "1".to_string();
+            }
+            (0..words.len())
+                .map(|i| format!("LOWER({}) LIKE ?{} ESCAPE '\\'", column, i + 3))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+        let sql = format!(
+            "SELECT * FROM (
+                SELECT 'problem' AS entity_type, pr.id, p.id, p.name, c.name, pr.title, pr.status, pr.severity, NULL AS due_date,
+                       pr.status IN ('solved', 'wont_fix') AS closed, pr.created_at
+                FROM problems pr JOIN components c ON c.id = pr.component_id JOIN projects p ON p.id = c.project_id
+                WHERE {}
+                UNION ALL
+                SELECT 'todo', t.id, p.id, p.name, c.name, t.title, t.status, t.priority, t.due_date,
+                       t.status IN ('done', 'cancelled'), t.created_at
+                FROM todos t JOIN projects p ON p.id = t.project_id LEFT JOIN components c ON c.id = t.component_id
+                WHERE {}
+             )
+             ORDER BY closed, LOWER(title) LIKE ?1 ESCAPE '\\' DESC, created_at DESC
+             LIMIT ?2",
+            matches("pr.title"),
+            matches("t.title"),
+        );
+        let prefix = format!("{}%", escape(query.trim()));
+        let mut values: Vec<&dyn rusqlite::ToSql> = vec![&prefix, &limit];
+        values.extend(words.iter().map(|w| w as &dyn rusqlite::ToSql));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let items = stmt.query_map(values.as_slice(), |row| {
+            Ok(QuickItem {
+                entity_type: row.get(0)?,
+                id: row.get(1)?,
+                project_id: row.get(2)?,
+                project_name: row.get(3)?,
+                component_name: row.get(4)?,
+                title: row.get(5)?,
+                status: row.get(6)?,
+                priority: row.get(7)?,
+                due_date: row.get(8)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(items)
     }
 
-    pub fn create_extraction(
-        &self,
-        attachment_id: i64,
-        record_type: &str,
-        record_id: i64,
-        source_location: Option<&str>,
-        source_snippet: Option<&str>,
-        confidence: Option<f64>,
-    ) -> Result<Extraction> {
-        self.conn.execute(
-            "INSERT INTO extractions (attachment_id, record_type, record_id, source_location, 
-             source_snippet, confidence) VALUES (?, ?, ?, ?, ?, ?)",
-            params![attachment_id, record_type, record_id, source_location, source_snippet, confidence],
+    /// Records whose name matches `pattern`, a LIKE pattern escaped with backslashes, the
+    /// lately opened ones first. Scoring them is up to the caller.
+    pub fn get_quick_open_candidates(&self, pattern: &str) -> Result<Vec<QuickOpenCandidate>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT x.entity_type, x.id, x.title, x.project_id, pr.name, x.component_id, x.status,
+                    v.score, julianday('now') - julianday(v.last_viewed_at)
+             FROM (
+                SELECT 'project' AS entity_type, id, name AS title, id AS project_id, NULL AS component_id, status FROM projects
+                UNION ALL SELECT 'component', id, name, project_id, id, status FROM components
+                UNION ALL SELECT 'problem', p.id, p.title, c.project_id, p.component_id, p.status
+                    FROM problems p JOIN components c ON c.id = p.component_id
+                UNION ALL SELECT 'todo', id, title, project_id, component_id, status FROM todos
+                UNION ALL SELECT 'attachment', id, file_name, project_id, component_id, NULL FROM attachments
+             ) x
+             JOIN projects pr ON pr.id = x.project_id
+             LEFT JOIN item_views v ON v.entity_type = x.entity_type AND v.entity_id = x.id
+             WHERE x.title LIKE ?1 ESCAPE '\\'
+             ORDER BY v.last_viewed_at IS NULL, v.last_viewed_at DESC
+             LIMIT ?2"
         )?;
-        self.get_extraction(self.conn.last_insert_rowid())
+        let candidates = stmt.query_map(params![pattern, MAX_QUICK_OPEN_CANDIDATES], |row| {
+            let score: Option<f64> = row.get(7)?;
+            let age_days: Option<f64> = row.get(8)?;
+            Ok(QuickOpenCandidate {
+                entity_type: row.get(0)?,
+                id: row.get(1)?,
+                title: row.get(2)?,
+                project_id: row.get(3)?,
+                project_name: row.get(4)?,
+                component_id: row.get(5)?,
+                status: row.get(6)?,
+                frecency: score.map(|score| Self::decayed(score, age_days.unwrap_or_default())).unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+        Ok(candidates)
     }
 
-    pub fn update_extraction_review(&self, id: i64, user_reviewed: bool, user_approved: Option<bool>) -> Result<Extraction> {
-        self.conn.execute(
-            "UPDATE extractions SET user_reviewed = ?, user_approved = ? WHERE id = ?",
-            params![user_reviewed, user_approved, id],
-        )?;
-        self.get_extraction(id)
+    // ============================================================
+    // v1.4: SYNC PREVIEW OPERATIONS
+    // ============================================================
+
+    /// Per synced table, the records created and edited after `since` (any timestamp SQLite
+    /// reads). Tables without changes are left out; deletions leave no trace to count.
+    pub fn count_changed_records(&self, since: &str) -> Result<Vec<RecordChangeCount>> {
+        let since: Option<String> = self.query_row("SELECT datetime(?)", params![since], |row| row.get(0))?;
+        let since = since.unwrap_or_else(|| "1970-01-01 00:00:00".to_string());
+        let mut counts = Vec::new();
+        for table in UUID_TABLES {
+            let columns = Self::table_columns(&self.conn, table)?;
+            if !columns.iter().any(|c| c == "created_at") {
+                continue;
+            }
+            let edited = if columns.iter().any(|c| c == "updated_at") { "updated_at > ?1" } else { "0" };
+            let (created, updated): (i64, i64) = self.query_row(
+                &format!(
+                    "SELECT COALESCE(SUM(created_at > ?1), 0), COALESCE(SUM(created_at <= ?1 AND {}), 0) FROM {}",
+                    edited, table
+                ),
+                params![since],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            if created + updated > 0 {
+                counts.push(RecordChangeCount { table: table.to_string(), created, updated });
+            }
+        }
+        Ok(counts)
     }
 
-    pub fn delete_extraction(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM extractions WHERE id = ?", params![id])?;
-        Ok(())
+    /// What happened in each project after `since`, for describing a sync. Records created or
+    /// finished since then aren't also counted as updated.
+    pub fn count_project_activity(&self, since: &str) -> Result<Vec<ProjectActivityCount>> {
+        let since: Option<String> = self.query_row("SELECT datetime(?)", params![since], |row| row.get(0))?;
+        let since = since.unwrap_or_else(|| "1970-01-01 00:00:00".to_string());
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT name, noun, verb, count FROM (
+                 SELECT pr.name, 0 AS rank, 'problem' AS noun, 'solved' AS verb, COUNT(*) AS count FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE p.status = 'solved' AND p.solved_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 1, 'problem', 'opened', COUNT(*) FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE p.created_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 2, 'problem', 'updated', COUNT(*) FROM problems p 
+                 JOIN components c ON c.id = p.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE p.updated_at > ?1 AND p.created_at <= ?1 AND NOT COALESCE(p.solved_at > ?1, 0) GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 3, 'todo', 'completed', COUNT(*) FROM todos t JOIN projects pr ON pr.id = t.project_id 
+                 WHERE t.status = 'done' AND t.completed_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 4, 'todo', 'added', COUNT(*) FROM todos t JOIN projects pr ON pr.id = t.project_id 
+                 WHERE t.created_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 5, 'todo', 'updated', COUNT(*) FROM todos t JOIN projects pr ON pr.id = t.project_id 
+                 WHERE t.updated_at > ?1 AND t.created_at <= ?1 AND NOT COALESCE(t.completed_at > ?1, 0) GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 6, 'component', 'added', COUNT(*) FROM components c JOIN projects pr ON pr.id = c.project_id 
+                 WHERE c.created_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 7, 'component', 'updated', COUNT(*) FROM components c JOIN projects pr ON pr.id = c.project_id 
+                 WHERE c.updated_at > ?1 AND c.created_at <= ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 8, 'change', 'logged', COUNT(*) FROM changes ch 
+                 JOIN components c ON c.id = ch.component_id JOIN projects pr ON pr.id = c.project_id 
+                 WHERE ch.created_at > ?1 GROUP BY pr.id 
+                 UNION ALL 
+                 SELECT pr.name, 9, 'learning', 'added', COUNT(*) FROM learnings l JOIN projects pr ON pr.id = l.project_id 
+                 WHERE l.created_at > ?1 GROUP BY pr.id 
+             ) ORDER BY SUM(count) OVER (PARTITION BY name) DESC, name, rank",
+        )?;
+        let counts = stmt.query_map(params![since], |row| {
+            Ok(ProjectActivityCount {
+                project_name: row.get(0)?,
+                noun: row.get(1)?,
+                verb: row.get(2)?,
+                count: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(counts)
     }
 
     // ============================================================
-    // v1.1: SYNC STATUS OPERATIONS
+    // v1.4: NETWORK QUEUE OPERATIONS
     // ============================================================
 
-    fn row_to_sync_status(row: &rusqlite::Row) -> rusqlite::Result<SyncStatus> {
-        Ok(SyncStatus {
+    fn row_to_network_operation(row: &rusqlite::Row) -> rusqlite::Result<NetworkOperation> {
+        let payload: String = row.get(2)?;
+        Ok(NetworkOperation {
             id: row.get(0)?,
-            device_name: row.get(1)?,
-            device_id: row.get(2)?,
-            remote_url: row.get(3)?,
-            last_sync_at: row.get(4)?,
-            last_sync_commit: row.get(5)?,
-            pending_changes: row.get(6)?,
-            has_conflicts: row.get(7)?,
+            operation: row.get(1)?,
+            payload: serde_json::from_str(&payload).unwrap_or_default(),
+            description: row.get(3)?,
+            status: row.get(4)?,
+            attempts: row.get(5)?,
+            last_error: row.get(6)?,
+            next_attempt_at: row.get(7)?,
             created_at: row.get(8)?,
-            updated_at: row.get(9)?,
         })
     }
 
-    pub fn get_sync_status(&self) -> Result<Option<SyncStatus>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, device_name, device_id, remote_url, last_sync_at, last_sync_commit, 
-                    pending_changes, has_conflicts, created_at, updated_at 
-             FROM sync_status LIMIT 1"
+    /// Queue an operation for when the network is back. The same operation with the same
+    /// payload is only queued once; queuing it again makes it due right away.
+    pub fn queue_network_operation(&self, operation: &str, payload: &serde_json::Value, description: Option<&str>) -> Result<NetworkOperation> {
+        let payload = payload.to_string();
+        let existing: Option<i64> = self.query_row(
+            "SELECT id FROM network_operations WHERE operation = ? AND payload = ?",
+            params![operation, payload],
+            |row| row.get(0),
+        ).ok();
+        let id = match existing {
+            Some(id) => {
+                self.execute(
+                    "UPDATE network_operations SET status = 'pending', next_attempt_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    params![id],
+                )?;
+                id
+            }
+            None => {
+                self.execute(
+                    "INSERT INTO network_operations (operation, payload, description) VALUES (?, ?, ?)",
+                    params![operation, payload, description],
+                )?;
+                self.conn.last_insert_rowid()
+            }
+        };
+        self.get_network_operation(id)
+    }
+
+    pub fn get_network_operation(&self, id: i64) -> Result<NetworkOperation> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, operation, payload, description, status, attempts, last_error, next_attempt_at, created_at 
+             FROM network_operations WHERE id = ?"
         )?;
-        match stmt.query_row([], Self::row_to_sync_status) {
-            Ok(s) => Ok(Some(s)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+        stmt.query_row(params![id], Self::row_to_network_operation)
     }
 
-    pub fn create_sync_status(&self, device_name: &str, device_id: &str) -> Result<SyncStatus> {
-        self.conn.execute(
-            "INSERT INTO sync_status (device_name, device_id) VALUES (?, ?)",
-            params![device_name, device_id],
+    /// Oldest first; failed (given up) operations only when asked for.
+    pub fn get_network_operations(&self, include_failed: bool) -> Result<Vec<NetworkOperation>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, operation, payload, description, status, attempts, last_error, next_attempt_at, created_at 
+             FROM network_operations WHERE status = 'pending' OR ? ORDER BY created_at, id"
         )?;
-        self.get_sync_status().map(|opt| opt.unwrap())
+        let operations = stmt.query_map(params![include_failed], Self::row_to_network_operation)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(operations)
     }
 
-    pub fn update_sync_status(
-        &self,
-        remote_url: Option<&str>,
-        last_sync_at: Option<&str>,
-        last_sync_commit: Option<&str>,
-        pending_changes: Option<i64>,
-        has_conflicts: Option<bool>,
-    ) -> Result<SyncStatus> {
-        let mut updates = Vec::new();
-        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-        
-        if let Some(url) = remote_url {
-            updates.push("remote_url = ?");
-            values.push(Box::new(url.to_string()));
-        }
-        if let Some(sync_at) = last_sync_at {
-            updates.push("last_sync_at = ?");
-            values.push(Box::new(sync_at.to_string()));
-        }
-        if let Some(commit) = last_sync_commit {
-            updates.push("last_sync_commit = ?");
-            values.push(Box::new(commit.to_string()));
-        }
-        if let Some(pc) = pending_changes {
-            updates.push("pending_changes = ?");
-            values.push(Box::new(pc));
-        }
-        if let Some(hc) = has_conflicts {
-            updates.push("has_conflicts = ?");
-            values.push(Box::new(hc));
-        }
-        
-        if updates.is_empty() {
-            return self.get_sync_status().map(|opt| opt.unwrap());
-        }
-        
-        let sql = format!("UPDATE sync_status SET {}", updates.join(", "));
-        let params: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
-        self.conn.execute(&sql, params.as_slice())?;
-        
-        self.get_sync_status().map(|opt| opt.unwrap())
+    /// Pending operations whose backoff has run out.
+    pub fn get_due_network_operations(&self) -> Result<Vec<NetworkOperation>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, operation, payload, description, status, attempts, last_error, next_attempt_at, created_at 
+             FROM network_operations WHERE status = 'pending' AND next_attempt_at <= CURRENT_TIMESTAMP 
+             ORDER BY created_at, id"
+        )?;
+        let operations = stmt.query_map([], Self::row_to_network_operation)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(operations)
+    }
+
+    /// Count a failed try and schedule the next one, or park the operation as failed once it
+    /// has used up its attempts.
+    pub fn record_network_failure(&self, id: i64, error: &str, retry_in_secs: i64, max_attempts: i64) -> Result<NetworkOperation> {
+        self.execute(
+            "UPDATE network_operations SET attempts = attempts + 1, last_error = ?, 
+             next_attempt_at = datetime('now', '+' || ? || ' seconds'), 
+             status = CASE WHEN attempts + 1 >= ? THEN 'failed' ELSE 'pending' END 
+             WHERE id = ?",
+            params![error, retry_in_secs, max_attempts, id],
+        )?;
+        self.get_network_operation(id)
+    }
+
+    /// Make an operation due now, reviving it if it had failed.
+    pub fn retry_network_operation(&self, id: i64) -> Result<NetworkOperation> {
+        self.execute(
+            "UPDATE network_operations SET status = 'pending', attempts = 0, next_attempt_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![id],
+        )?;
+        self.get_network_operation(id)
+    }
+
+    /// Remove an operation, whether it completed or was cancelled.
+    pub fn delete_network_operation(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM network_operations WHERE id = ?", params![id])?;
+        Ok(())
     }
 
     // ============================================================
-    // v1.1: SYNC HISTORY OPERATIONS
+    // v1.4: DIAGNOSTICS
     // ============================================================
 
-    fn row_to_sync_history(row: &rusqlite::Row) -> rusqlite::Result<SyncHistory> {
-        Ok(SyncHistory {
-            id: row.get(0)?,
-            device_id: row.get(1)?,
-            operation: row.get(2)?,
-            commit_hash: row.get(3)?,
-            files_changed: row.get(4)?,
-            status: row.get(5)?,
-            error_message: row.get(6)?,
-            created_at: row.get(7)?,
-        })
+    /// Slowest first. Pending slow queries from the profiler are stored before reading.
+    pub fn get_slow_queries(&self, limit: i64) -> Result<Vec<SlowQuery>> {
+        self.store_buffered_slow_queries()?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, sql, duration_ms, recorded_at FROM slow_queries 
+             ORDER BY duration_ms DESC, recorded_at DESC LIMIT ?"
+        )?;
+        let queries = stmt.query_map(params![limit], |row| {
+            Ok(SlowQuery {
+                id: row.get(0)?,
+                sql: row.get(1)?,
+                duration_ms: row.get(2)?,
+                recorded_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(queries)
     }
 
-    pub fn get_sync_history(&self, limit: i32) -> Result<Vec<SyncHistory>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, device_id, operation, commit_hash, files_changed, status, error_message, created_at 
-             FROM sync_history ORDER BY created_at DESC LIMIT ?"
-        )?;
-        let history = stmt.query_map(params![limit], Self::row_to_sync_history)?
-            .collect::<Result<Vec<_>>>()?;
-        Ok(history)
+    pub fn clear_slow_queries(&self) -> Result<()> {
+        if let Ok(mut buffer) = SLOW_QUERY_BUFFER.lock() {
+            buffer.clear();
+        }
+        self.execute("DELETE FROM slow_queries", [])?;
+        Ok(())
     }
 
-    pub fn log_sync_operation(
-        &self,
-        device_id: &str,
-        operation: &str,
-        commit_hash: Option<&str>,
-        files_changed: Option<i64>,
-        status: &str,
-        error_message: Option<&str>,
-    ) -> Result<SyncHistory> {
-        self.conn.execute(
-            "INSERT INTO sync_history (device_id, operation, commit_hash, files_changed, status, error_message) 
-             VALUES (?, ?, ?, ?, ?, ?)",
-            params![device_id, operation, commit_hash, files_changed, status, error_message],
-        )?;
-        
-        let id = self.conn.last_insert_rowid();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, device_id, operation, commit_hash, files_changed, status, error_message, created_at 
-             FROM sync_history WHERE id = ?"
+    fn store_buffered_slow_queries(&self) -> Result<()> {
+        let pending = match SLOW_QUERY_BUFFER.lock() {
+            Ok(mut buffer) => std::mem::take(&mut *buffer),
+            Err(_) => return Ok(()),
+        };
+        for (sql, duration_ms) in pending {
+            self.execute("INSERT INTO slow_queries (sql, duration_ms) VALUES (?, ?)", params![sql, duration_ms])?;
+        }
+        // Keep the table to the most recent entries
+        self.execute(
+            "DELETE FROM slow_queries WHERE id <= (SELECT id FROM slow_queries ORDER BY id DESC LIMIT 1 OFFSET ?)",
+            params![MAX_STORED_SLOW_QUERIES],
         )?;
-        stmt.query_row(params![id], Self::row_to_sync_history)
+        Ok(())
     }
 
     // ============================================================
-    // v1.1: SETTINGS OPERATIONS
+    // v1.4: INTEGRATION LINK OPERATIONS
     // ============================================================
 
-    fn row_to_setting(row: &rusqlite::Row) -> rusqlite::Result<Setting> {
-        Ok(Setting {
-            key: row.get(0)?,
-            value: row.get(1)?,
-            category: row.get(2)?,
-            updated_at: row.get(3)?,
+    fn row_to_integration_link(row: &rusqlite::Row) -> rusqlite::Result<IntegrationLink> {
+        Ok(IntegrationLink {
+            id: row.get(0)?,
+            integration: row.get(1)?,
+            entity_type: row.get(2)?,
+            entity_id: row.get(3)?,
+            external_id: row.get(4)?,
+            container_id: row.get(5)?,
+            synced_at: row.get(6)?,
         })
     }
 
-    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
-        let mut stmt = self.conn.prepare("SELECT value FROM settings WHERE key = ?")?;
-        match stmt.query_row(params![key], |row| row.get::<_, String>(0)) {
-            Ok(v) => Ok(Some(v)),
+    pub fn get_integration_link(&self, integration: &str, entity_type: &str, entity_id: i64) -> Result<Option<IntegrationLink>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, integration, entity_type, entity_id, external_id, container_id, synced_at 
+             FROM integration_links WHERE integration = ? AND entity_type = ? AND entity_id = ?"
+        )?;
+        match stmt.query_row(params![integration, entity_type, entity_id], Self::row_to_integration_link) {
+            Ok(link) => Ok(Some(link)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e),
         }
     }
 
-    pub fn set_setting(&self, key: &str, value: &str, category: Option<&str>) -> Result<()> {
-        let category = category.unwrap_or("general");
-        self.conn.execute(
-            "INSERT OR REPLACE INTO settings (key, value, category, updated_at) 
-             VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
-            params![key, value, category],
-        )?;
-        Ok(())
+    pub fn get_integration_links(&self, integration: &str, entity_type: Option<&str>) -> Result<Vec<IntegrationLink>> {
+        let mut sql = String::from(
+            "SELECT id, integration, entity_type, entity_id, external_id, container_id, synced_at 
+             FROM integration_links WHERE integration = ?"
+        );
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(integration.to_string())];
+        
+        if let Some(et) = entity_type {
+            sql.push_str(" AND entity_type = ?");
+            param_values.push(Box::new(et.to_string()));
+        }
+        sql.push_str(" ORDER BY entity_type, entity_id");
+        
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|v| v.as_ref()).collect();
+        let links = stmt.query_map(params.as_slice(), Self::row_to_integration_link)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(links)
     }
 
-    pub fn get_all_settings(&self) -> Result<Vec<Setting>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT key, value, category, updated_at FROM settings ORDER BY category, key"
-        )?;
-        let settings = stmt.query_map([], Self::row_to_setting)?
-            .collect::<Result<Vec<_>>>()?;
-        Ok(settings)
+    /// The record linked to `external_id`; the oldest link wins when several share it.
+    pub fn find_integration_entity(&self, integration: &str, entity_type: &str, external_id: &str) -> Result<Option<i64>> {
+        match self.query_row(
+            "SELECT entity_id FROM integration_links WHERE integration = ? AND entity_type = ? AND external_id = ? ORDER BY id LIMIT 1",
+            params![integration, entity_type, external_id],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
-    pub fn get_settings_by_category(&self, category: &str) -> Result<Vec<Setting>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT key, value, category, updated_at FROM settings WHERE category = ? ORDER BY key"
+    pub fn upsert_integration_link(
+        &self,
+        integration: &str,
+        entity_type: &str,
+        entity_id: i64,
+        external_id: &str,
+        container_id: Option<&str>,
+    ) -> Result<IntegrationLink> {
+        self.execute(
+            "INSERT INTO integration_links (integration, entity_type, entity_id, external_id, container_id) 
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(integration, entity_type, entity_id) DO UPDATE SET 
+                external_id = excluded.external_id,
+                container_id = excluded.container_id,
+                synced_at = CURRENT_TIMESTAMP",
+            params![integration, entity_type, entity_id, external_id, container_id],
         )?;
-        let settings = stmt.query_map(params![category], Self::row_to_setting)?
-            .collect::<Result<Vec<_>>>()?;
-        Ok(settings)
+        self.get_integration_link(integration, entity_type, entity_id)
+            .map(|opt| opt.unwrap())
     }
 
-    pub fn delete_setting(&self, key: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM settings WHERE key = ?", params![key])?;
+    pub fn delete_integration_link(&self, id: i64) -> Result<()> {
+        self.execute("DELETE FROM integration_links WHERE id = ?", params![id])?;
         Ok(())
     }
 
+    // ============================================================
+    // v1.4: FILTER OPERATIONS
+    // ============================================================
+
+    /// Ids of the schema's records that match a compiled filter expression.
+    pub fn filtered_ids(&self, schema: &crate::filters::FilterSchema, project_id: Option<i64>, filter: &crate::filters::Compiled) -> Result<HashSet<i64>> {
+        let mut sql = format!("SELECT {} FROM {} WHERE 1 = 1", schema.id_column, schema.from);
+        let mut values = Vec::new();
+        if let Some(pid) = project_id {
+            sql.push_str(&format!(" AND {} = ?", schema.project_column));
+            values.push(Value::Integer(pid));
+        }
+        sql.push_str(&filter.sql);
+        values.extend(filter.params.iter().cloned());
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let ids = stmt.query_map(params_from_iter(values), |row| row.get(0))?
+            .collect::<Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// How many of the matching records have each value of each facet, in one query: facet
+    /// name to value to count. Records without a value (a todo with no component) count
+    /// under "none"; tag counts are of tagged records, so they needn't add up.
+    pub fn get_facet_counts(
+        &self,
+        schema: &crate::filters::FilterSchema,
+        project_id: Option<i64>,
+        facets: &[&str],
+        filter: &crate::filters::Compiled,
+    ) -> Result<BTreeMap<String, BTreeMap<String, i64>>> {
+        let mut scope = String::from("WHERE 1 = 1");
+        let mut scope_values = Vec::new();
+        if let Some(pid) = project_id {
+            scope.push_str(&format!(" AND {} = ?", schema.project_column));
+            scope_values.push(Value::Integer(pid));
+        }
+        scope.push_str(&filter.sql);
+        scope_values.extend(filter.params.iter().cloned());
+
+        let mut selects = Vec::new();
+        let mut values = Vec::new();
+        for name in facets {
+            let field = schema.fields.iter().find(|f| f.name == *name).ok_or_else(|| {
+                rusqlite::Error::InvalidParameterName(format!("Unknown facet '{}' for {}s", name, schema.entity))
+            })?;
+            let select = match (field.name, crate::filters::facet_column(field)) {
+                ("tag", _) => {
+                    values.push(Value::Text(schema.entity.to_string()));
+                    format!(
+                        "SELECT 'tag', tg.tag, COUNT(*) FROM {} JOIN tags tg ON tg.entity_type = ? AND tg.entity_id = {} {} GROUP BY 2",
+                        schema.from, schema.id_column, scope
+                    )
+                }
+                (_, Some(column)) => format!("SELECT '{}', {}, COUNT(*) FROM {} {} GROUP BY 2", field.name, column, schema.from, scope),
+                (_, None) => return Err(rusqlite::Error::InvalidParameterName(format!("Can't count by '{}'", name))),
+            };
+            selects.push(select);
+            values.extend(scope_values.iter().cloned());
+        }
+
+        let mut counts: BTreeMap<String, BTreeMap<String, i64>> = facets.iter().map(|f| (f.to_string(), BTreeMap::new())).collect();
+        if selects.is_empty() {
+            return Ok(counts);
+        }
+        let mut stmt = self.conn.prepare(&selects.join(" UNION ALL "))?;
+        let rows = stmt.query_map(params_from_iter(values), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (facet, value, count) = row?;
+            counts.entry(facet).or_default().insert(value.unwrap_or_else(|| "none".to_string()), count);
+        }
+        Ok(counts)
+    }
+
     // ============================================================
     // SEARCH OPERATIONS
     // ============================================================
 
+    /// Problems, learnings, solutions, attachments, notes and references mentioning `query`,
+    /// up to `limit` of each. Every value is bound (see query::Query), and % or _ in the
+    /// query match themselves.
     pub fn search(&self, query: &str, project_id: Option<i64>, limit: i32) -> Result<Vec<serde_json::Value>> {
-        let search_term = format!("%{}%", query.to_lowercase());
+        let limit = i64::from(limit);
         let mut results = Vec::new();
 
         // Search problems
-        let sql = match project_id {
-            Some(pid) => format!(
-                "SELECT 'problem' as type, p.id, p.title, p.description, p.status, c.project_id
-                 FROM problems p
-                 JOIN components c ON p.component_id = c.id
-                 WHERE c.project_id = {} AND (LOWER(p.title) LIKE ? OR LOWER(p.description) LIKE ?)
-                 LIMIT {}", pid, limit
-            ),
-            None => format!(
-                "SELECT 'problem' as type, p.id, p.title, p.description, p.status, c.project_id
-                 FROM problems p
-                 JOIN components c ON p.component_id = c.id
-                 WHERE LOWER(p.title) LIKE ? OR LOWER(p.description) LIKE ?
-                 LIMIT {}", limit
-            ),
-        };
-
-        let mut stmt = self.conn.prepare(&sql)?;
-        let problem_results = stmt.query_map(params![&search_term, &search_term], |row| {
+        let problems = Query::select(
+            "SELECT 'problem' as type, p.id, p.title, p.description, p.status, c.project_id
+             FROM problems p
+             JOIN components c ON p.component_id = c.id"
+        )
+            .filter_opt("c.project_id = ?", project_id)
+            .contains(&["p.title", "p.description"], query)
+            .limit(limit);
+        results.extend(problems.query_map(&self.conn, |row| {
             Ok(serde_json::json!({
                 "type": row.get::<_, String>(0)?,
                 "id": row.get::<_, i64>(1)?,
@@ -1573,30 +9040,17 @@ impl Database {
                 "status": row.get::<_, String>(4)?,
                 "project_id": row.get::<_, i64>(5)?,
             }))
-        })?;
-
-        for result in problem_results {
-            results.push(result?);
-        }
+        })?);
 
         // Search learnings
-        let sql = match project_id {
-            Some(pid) => format!(
-                "SELECT 'learning' as type, id, insight, context, category, project_id
-                 FROM learnings
-                 WHERE project_id = {} AND (LOWER(insight) LIKE ? OR LOWER(context) LIKE ?)
-                 LIMIT {}", pid, limit
-            ),
-            None => format!(
-                "SELECT 'learning' as type, id, insight, context, category, project_id
-                 FROM learnings
-                 WHERE LOWER(insight) LIKE ? OR LOWER(context) LIKE ?
-                 LIMIT {}", limit
-            ),
-        };
-
-        let mut stmt = self.conn.prepare(&sql)?;
-        let learning_results = stmt.query_map(params![&search_term, &search_term], |row| {
+        let learnings = Query::select(
+            "SELECT 'learning' as type, id, insight, context, category, project_id
+             FROM learnings"
+        )
+            .filter_opt("project_id = ?", project_id)
+            .contains(&["insight", "context"], query)
+            .limit(limit);
+        results.extend(learnings.query_map(&self.conn, |row| {
             Ok(serde_json::json!({
                 "type": row.get::<_, String>(0)?,
                 "id": row.get::<_, i64>(1)?,
@@ -1605,164 +9059,715 @@ impl Database {
                 "category": row.get::<_, Option<String>>(4)?,
                 "project_id": row.get::<_, i64>(5)?,
             }))
-        })?;
+        })?);
+
+        // Search solutions
+        let solutions = Query::select(
+            "SELECT 'solution' as type, s.id, s.summary, s.key_insight, p.title as problem_title, c.project_id
+             FROM solutions s
+             JOIN problems p ON s.problem_id = p.id
+             JOIN components c ON p.component_id = c.id"
+        )
+            .filter_opt("c.project_id = ?", project_id)
+            .contains(&["s.summary", "s.key_insight"], query)
+            .limit(limit);
+        results.extend(solutions.query_map(&self.conn, |row| {
+            Ok(serde_json::json!({
+                "type": row.get::<_, String>(0)?,
+                "id": row.get::<_, i64>(1)?,
+                "title": row.get::<_, String>(2)?,
+                "snippet": row.get::<_, Option<String>>(3)?,
+                "problem_title": row.get::<_, String>(4)?,
+                "project_id": row.get::<_, i64>(5)?,
+            }))
+        })?);
+
+        // v1.1: Search attachments
+        let attachments = Query::select(
+            "SELECT 'attachment' as type, id, file_name, user_description, ai_summary, project_id
+             FROM attachments"
+        )
+            .filter_opt("project_id = ?", project_id)
+            .contains(&["file_name", "user_description", "ai_summary"], query)
+            .limit(limit);
+        results.extend(attachments.query_map(&self.conn, |row| {
+            Ok(serde_json::json!({
+                "type": row.get::<_, String>(0)?,
+                "id": row.get::<_, i64>(1)?,
+                "title": row.get::<_, String>(2)?,
+                "snippet": row.get::<_, Option<String>>(3)?,
+                "ai_summary": row.get::<_, Option<String>>(4)?,
+                "project_id": row.get::<_, i64>(5)?,
+            }))
+        })?);
+
+        // v1.4: Search notes through the full-text index (each word quoted so FTS syntax can't leak in)
+        let fts_query = query.split_whitespace()
+            .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !fts_query.is_empty() {
+            let notes = Query::select(
+                "SELECT 'note' as type, n.id, COALESCE(n.title, n.note_date), 
+                        snippet(memory_fts, 3, '', '', '...', 24), n.pinned, n.project_id
+                 FROM memory_fts
+                 JOIN notes n ON n.id = memory_fts.content_id"
+            )
+                .filter("memory_fts MATCH ? AND memory_fts.content_type = 'note'", [Value::Text(fts_query)])
+                .filter_opt("n.project_id = ?", project_id)
+                .order_by("rank")
+                .limit(limit);
+            results.extend(notes.query_map(&self.conn, |row| {
+                Ok(serde_json::json!({
+                    "type": row.get::<_, String>(0)?,
+                    "id": row.get::<_, i64>(1)?,
+                    "title": row.get::<_, String>(2)?,
+                    "snippet": row.get::<_, Option<String>>(3)?,
+                    "pinned": row.get::<_, bool>(4)?,
+                    "project_id": row.get::<_, Option<i64>>(5)?,
+                }))
+            })?);
+        }
+
+        // v1.4: Search web references
+        let references = Query::select(
+            "SELECT 'reference' as type, id, COALESCE(title, url), description, url, link_status, project_id
+             FROM web_references"
+        )
+            .filter_opt("project_id = ?", project_id)
+            .contains(&["url", "title", "description"], query)
+            .limit(limit);
+        results.extend(references.query_map(&self.conn, |row| {
+            Ok(serde_json::json!({
+                "type": row.get::<_, String>(0)?,
+                "id": row.get::<_, i64>(1)?,
+                "title": row.get::<_, String>(2)?,
+                "snippet": row.get::<_, Option<String>>(3)?,
+                "url": row.get::<_, String>(4)?,
+                "link_status": row.get::<_, String>(5)?,
+                "project_id": row.get::<_, i64>(6)?,
+            }))
+        })?);
+
+        Ok(results)
+    }
+
+    // ============================================================
+    // PROBLEM TREE (for Decision Tree visualization)
+    // ============================================================
+
+    pub fn get_problem_tree(&self, problem_id: i64) -> Result<serde_json::Value> {
+        let problem = self.get_problem(problem_id)?;
+        let attempts = self.get_attempts_for_problem(problem_id)?;
+        let solution = self.get_solution_for_problem(problem_id)?;
+        
+        let learnings = self.get_learnings(None, None, false, None)?
+            .into_iter()
+            .filter(|l| l.component_id == Some(problem.component_id))
+            .collect::<Vec<_>>();
+        
+        let checklist = self.get_checklist_items(problem_id)?;
+        let environment = self.get_problem_environment(problem_id)?;
+        
+        Ok(serde_json::json!({
+            "problem": problem,
+            "attempts": attempts,
+            "solution": solution,
+            "learnings": learnings,
+            "checklist": checklist,
+            "environment": environment,
+        }))
+    }
+
+    // ============================================================
+    // PROJECT STATS (for Dashboard)
+    // ============================================================
+
+    /// Dashboard numbers. Most come from the trigger-maintained project_stats row;
+    /// the time-dependent ones are counted here.
+    pub fn get_project_stats(&self, project_id: i64) -> Result<serde_json::Value> {
+        let counters = || self.query_row(
+            "SELECT component_count, open_problems, solved_problems, pending_todos, learning_count, attachment_count 
+             FROM project_stats WHERE project_id = ?",
+            params![project_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+        );
+        let (component_count, open_problems, solved_problems, pending_todos, learning_count, attachment_count): (i64, i64, i64, i64, i64, i64) =
+            match counters() {
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    self.recompute_stats(project_id)?;
+                    counters()?
+                }
+                other => other?,
+            };
+
+        let recent_changes: i64 = self.query_row(
+            "SELECT COUNT(*) FROM changes ch JOIN components c ON ch.component_id = c.id 
+             WHERE c.project_id = ? AND ch.created_at >= datetime('now', '-24 hours')",
+            params![project_id],
+            |row| row.get(0)
+        )?;
+
+        // v1.4: Learnings waiting for a spaced-repetition review
+        let due_reviews: i64 = self.query_row(
+            "SELECT COUNT(*) FROM learnings 
+             WHERE project_id = ? AND (next_review_at IS NULL OR next_review_at <= CURRENT_TIMESTAMP)",
+            params![project_id],
+            |row| row.get(0)
+        )?;
+
+        Ok(serde_json::json!({
+            "component_count": component_count,
+            "open_problems": open_problems,
+            "solved_problems": solved_problems,
+            "pending_todos": pending_todos,
+            "learning_count": learning_count,
+            "due_reviews": due_reviews,
+            "recent_changes": recent_changes,
+            "attachment_count": attachment_count,
+        }))
+    }
+
+    /// Rebuild a project's counters from the tables, in case they've drifted.
+    pub fn recompute_stats(&self, project_id: i64) -> Result<()> {
+        self.execute(
+            &format!(
+                "INSERT INTO project_stats (project_id, component_count, open_problems, solved_problems, 
+                     pending_todos, learning_count, attachment_count) 
+                 {} WHERE p.id = ?
+                 ON CONFLICT(project_id) DO UPDATE SET 
+                     component_count = excluded.component_count, open_problems = excluded.open_problems, 
+                     solved_problems = excluded.solved_problems, pending_todos = excluded.pending_todos, 
+                     learning_count = excluded.learning_count, attachment_count = excluded.attachment_count",
+                PROJECT_STATS_SELECT
+            ),
+            params![project_id],
+        )?;
+        Ok(())
+    }
+
+    // ============================================================
+    // v1.4: STATS HISTORY OPERATIONS
+    // ============================================================
+
+    /// Store every project's STATS_HISTORY_METRICS as the numbers for `day`, replacing any
+    /// taken earlier that day. Returns how many projects were snapshotted.
+    pub fn snapshot_stats(&self, day: &str) -> Result<usize> {
+        let projects: Vec<i64> = {
+            let mut stmt = self.conn.prepare_cached("SELECT id FROM projects")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+            ids
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        for &project_id in &projects {
+            let stats = self.get_project_stats(project_id)?;
+            let attachment_bytes: i64 = self.query_row(
+                "SELECT COALESCE(SUM(file_size), 0) FROM attachments WHERE project_id = ?",
+                params![project_id],
+                |row| row.get(0),
+            )?;
+            for metric in STATS_HISTORY_METRICS {
+                let value = match *metric {
+                    "attachment_bytes" => attachment_bytes as f64,
+                    _ => stats.get(*metric).and_then(|v| v.as_f64()).unwrap_or_default(),
+                };
+                self.execute(
+                    "INSERT INTO stats_history (project_id, day, metric, value) VALUES (?, ?, ?, ?) 
+                     ON CONFLICT(project_id, day, metric) DO UPDATE SET value = excluded.value, created_at = CURRENT_TIMESTAMP",
+                    params![project_id, day, metric, value],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(projects.len())
+    }
+
+    /// Whether stats were already snapshotted for `day`, so the nightly job takes one a day.
+    pub fn has_stats_snapshot(&self, day: &str) -> Result<bool> {
+        self.query_row("SELECT EXISTS(SELECT 1 FROM stats_history WHERE day = ?)", params![day], |row| row.get(0))
+    }
+
+    /// One metric of a project a day at a time, oldest first; `since` and `until` are
+    /// YYYY-MM-DD days and both included.
+    pub fn get_stats_history(&self, project_id: i64, metric: &str, since: &str, until: &str) -> Result<Vec<StatsPoint>> {
+        if !STATS_HISTORY_METRICS.contains(&metric) {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Unknown metric '{}' (try {})", metric, STATS_HISTORY_METRICS.join(", ")
+            )));
+        }
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT day, value FROM stats_history WHERE project_id = ? AND metric = ? AND day BETWEEN ? AND ? ORDER BY day"
+        )?;
+        let points = stmt.query_map(params![project_id, metric, since, until], |row| {
+            Ok(StatsPoint { day: row.get(0)?, value: row.get(1)? })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(points)
+    }
+
+    // ============================================================
+    // v1.4: JOB QUEUE OPERATIONS
+    // ============================================================
+
+    fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<Job> {
+        let payload: String = row.get(2)?;
+        let result: Option<String> = row.get(8)?;
+        Ok(Job {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            payload: serde_json::from_str(&payload).unwrap_or_default(),
+            description: row.get(3)?,
+            status: row.get(4)?,
+            progress: row.get(5)?,
+            total: row.get(6)?,
+            message: row.get(7)?,
+            result: result.and_then(|r| serde_json::from_str(&r).ok()),
+            error: row.get(9)?,
+            input_tokens: row.get(10)?,
+            output_tokens: row.get(11)?,
+            cost: row.get(12)?,
+            created_at: row.get(13)?,
+            started_at: row.get(14)?,
+            finished_at: row.get(15)?,
+        })
+    }
+
+    pub fn get_job(&self, id: i64) -> Result<Job> {
+        self.query_row(
+            "SELECT id, kind, payload, description, status, progress, total, message, result, error, input_tokens, output_tokens, cost, 
+                    created_at, started_at, finished_at 
+             FROM jobs WHERE id = ?",
+            params![id],
+            Self::row_to_job,
+        )
+    }
+
+    /// The newest jobs first, unfinished ones before the rest.
+    pub fn get_jobs(&self, limit: i64) -> Result<Vec<Job>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, kind, payload, description, status, progress, total, message, result, error, input_tokens, output_tokens, cost, 
+                    created_at, started_at, finished_at 
+             FROM jobs ORDER BY status IN ('queued', 'running') DESC, id DESC LIMIT ?"
+        )?;
+        let jobs = stmt.query_map(params![limit], Self::row_to_job)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(jobs)
+    }
 
-        for result in learning_results {
-            results.push(result?);
+    /// Queue a job. The same job with the same payload is only queued once while it waits
+    /// or runs; queuing it again returns that one.
+    pub fn create_job(&self, kind: &str, payload: &serde_json::Value, description: Option<&str>) -> Result<Job> {
+        let payload = payload.to_string();
+        match self.query_row(
+            "SELECT id FROM jobs WHERE kind = ? AND payload = ? AND status IN ('queued', 'running') ORDER BY id LIMIT 1",
+            params![kind, payload],
+            |row| row.get(0),
+        ) {
+            Ok(id) => self.get_job(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                self.execute(
+                    "INSERT INTO jobs (kind, payload, description) VALUES (?, ?, ?)",
+                    params![kind, payload, description],
+                )?;
+                self.get_job(self.conn.last_insert_rowid())
+            }
+            Err(e) => Err(e),
         }
+    }
 
-        // Search solutions
-        let sql = match project_id {
-            Some(pid) => format!(
-                "SELECT 'solution' as type, s.id, s.summary, s.key_insight, p.title as problem_title, c.project_id
-                 FROM solutions s
-                 JOIN problems p ON s.problem_id = p.id
-                 JOIN components c ON p.component_id = c.id
-                 WHERE c.project_id = {} AND (LOWER(s.summary) LIKE ? OR LOWER(s.key_insight) LIKE ?)
-                 LIMIT {}", pid, limit
-            ),
-            None => format!(
-                "SELECT 'solution' as type, s.id, s.summary, s.key_insight, p.title as problem_title, c.project_id
-                 FROM solutions s
-                 JOIN problems p ON s.problem_id = p.id
-                 JOIN components c ON p.component_id = c.id
-                 WHERE LOWER(s.summary) LIKE ? OR LOWER(s.key_insight) LIKE ?
-                 LIMIT {}", limit
-            ),
+    /// Mark the oldest queued job running and return it.
+    pub fn start_next_job(&self) -> Result<Option<Job>> {
+        let id: i64 = match self.query_row("SELECT id FROM jobs WHERE status = 'queued' ORDER BY id LIMIT 1", [], |row| row.get(0)) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
         };
+        self.execute(
+            "UPDATE jobs SET status = 'running', started_at = CURRENT_TIMESTAMP, progress = 0, error = NULL WHERE id = ?",
+            params![id],
+        )?;
+        self.get_job(id).map(Some)
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let solution_results = stmt.query_map(params![&search_term, &search_term], |row| {
-            Ok(serde_json::json!({
-                "type": row.get::<_, String>(0)?,
-                "id": row.get::<_, i64>(1)?,
-                "title": row.get::<_, String>(2)?,
-                "snippet": row.get::<_, Option<String>>(3)?,
-                "problem_title": row.get::<_, String>(4)?,
-                "project_id": row.get::<_, i64>(5)?,
-            }))
-        })?;
+    /// Jobs the app quit in the middle of run again from the start.
+    pub fn requeue_interrupted_jobs(&self) -> Result<usize> {
+        self.execute("UPDATE jobs SET status = 'queued', message = NULL WHERE status = 'running'", [])
+    }
 
-        for result in solution_results {
-            results.push(result?);
-        }
+    pub fn update_job_progress(&self, id: i64, progress: i64, total: Option<i64>, message: Option<&str>) -> Result<Job> {
+        self.execute(
+            "UPDATE jobs SET progress = ?, total = COALESCE(?, total), message = ? WHERE id = ?",
+            params![progress, total, message, id],
+        )?;
+        self.get_job(id)
+    }
 
-        // v1.1: Search attachments
-        let sql = match project_id {
-            Some(pid) => format!(
-                "SELECT 'attachment' as type, id, file_name, user_description, ai_summary, project_id
-                 FROM attachments
-                 WHERE project_id = {} AND (LOWER(file_name) LIKE ? OR LOWER(user_description) LIKE ? OR LOWER(ai_summary) LIKE ?)
-                 LIMIT {}", pid, limit
-            ),
-            None => format!(
-                "SELECT 'attachment' as type, id, file_name, user_description, ai_summary, project_id
-                 FROM attachments
-                 WHERE LOWER(file_name) LIKE ? OR LOWER(user_description) LIKE ? OR LOWER(ai_summary) LIKE ?
-                 LIMIT {}", limit
-            ),
+    /// Record how a running job ended; a job cancelled while it ran stays cancelled.
+    pub fn finish_job(&self, id: i64, outcome: std::result::Result<&serde_json::Value, &str>) -> Result<Job> {
+        let (status, result, error) = match outcome {
+            Ok(result) => ("done", Some(result.to_string()), None),
+            Err(error) => ("failed", None, Some(error)),
         };
+        self.execute(
+            "UPDATE jobs SET status = ?, result = ?, error = ?, finished_at = CURRENT_TIMESTAMP WHERE id = ? AND status = 'running'",
+            params![status, result, error, id],
+        )?;
+        self.get_job(id)
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let attachment_results = stmt.query_map(params![&search_term, &search_term, &search_term], |row| {
-            Ok(serde_json::json!({
-                "type": row.get::<_, String>(0)?,
-                "id": row.get::<_, i64>(1)?,
-                "title": row.get::<_, String>(2)?,
-                "snippet": row.get::<_, Option<String>>(3)?,
-                "ai_summary": row.get::<_, Option<String>>(4)?,
-                "project_id": row.get::<_, i64>(5)?,
-            }))
-        })?;
+    /// Add a language model request's tokens and cost to a job's totals.
+    pub fn add_job_usage(&self, id: i64, input_tokens: i64, output_tokens: i64, cost: f64) -> Result<()> {
+        self.execute(
+            "UPDATE jobs SET input_tokens = input_tokens + ?, output_tokens = output_tokens + ?, cost = cost + ? WHERE id = ?",
+            params![input_tokens, output_tokens, cost, id],
+        )?;
+        Ok(())
+    }
 
-        for result in attachment_results {
-            results.push(result?);
-        }
+    /// What jobs of a kind started since `since` have spent on the language model.
+    pub fn get_job_cost_since(&self, kind: &str, since: &str) -> Result<f64> {
+        self.query_row(
+            "SELECT COALESCE(SUM(cost), 0) FROM jobs WHERE kind = ? AND started_at >= ?",
+            params![kind, since],
+            |row| row.get(0),
+        )
+    }
 
-        Ok(results)
+    /// Cancel a queued job, or ask a running one to stop before its next step.
+    pub fn cancel_job(&self, id: i64) -> Result<Job> {
+        self.execute(
+            "UPDATE jobs SET status = 'cancelled', finished_at = CURRENT_TIMESTAMP WHERE id = ? AND status IN ('queued', 'running')",
+            params![id],
+        )?;
+        self.get_job(id)
     }
 
     // ============================================================
-    // PROBLEM TREE (for Decision Tree visualization)
+    // v1.4: AI USAGE OPERATIONS
     // ============================================================
 
-    pub fn get_problem_tree(&self, problem_id: i64) -> Result<serde_json::Value> {
-        let problem = self.get_problem(problem_id)?;
-        let attempts = self.get_attempts_for_problem(problem_id)?;
-        let solution = self.get_solution_for_problem(problem_id)?;
-        
-        let learnings = self.get_learnings(None, None, false)?
-            .into_iter()
-            .filter(|l| l.component_id == Some(problem.component_id))
-            .collect::<Vec<_>>();
-        
-        Ok(serde_json::json!({
-            "problem": problem,
-            "attempts": attempts,
-            "solution": solution,
-            "learnings": learnings,
-        }))
+    /// Log one provider request and what it cost, against the command that made it.
+    pub fn record_ai_usage(&self, call: &crate::llm::Call, cost: f64, command: &str) -> Result<()> {
+        self.execute(
+            "INSERT INTO ai_usage (provider, model, operation, input_tokens, output_tokens, cost, command) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![call.provider, call.model, call.operation, call.usage.input_tokens, call.usage.output_tokens, cost, command],
+        )?;
+        Ok(())
+    }
+
+    /// What every request since `since` cost.
+    pub fn get_ai_cost_since(&self, since: &str) -> Result<f64> {
+        self.query_row("SELECT COALESCE(SUM(cost), 0) FROM ai_usage WHERE created_at >= ?", params![since], |row| row.get(0))
+    }
+
+    /// Requests between `since` and `until` (now when None), totalled and grouped. The budget
+    /// fields are left for the caller, which knows the month.
+    pub fn get_ai_usage(&self, since: &str, until: Option<&str>) -> Result<AiUsage> {
+        let group = |key: &str, order: &str| -> Result<Vec<AiUsageGroup>> {
+            let mut stmt = self.conn.prepare_cached(&format!(
+                "SELECT {key}, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(cost) FROM ai_usage 
+                 WHERE created_at >= ?1 AND (?2 IS NULL OR created_at < ?2) GROUP BY 1 ORDER BY {order}"
+            ))?;
+            let groups = stmt.query_map(params![since, until], |row| {
+                Ok(AiUsageGroup {
+                    key: row.get(0)?,
+                    calls: row.get(1)?,
+                    input_tokens: row.get(2)?,
+                    output_tokens: row.get(3)?,
+                    cost: row.get(4)?,
+                })
+            })?;
+            groups.collect()
+        };
+        let by_model = group("provider || '/' || model", "5 DESC, 1")?;
+        Ok(AiUsage {
+            since: since.to_string(),
+            until: until.map(String::from),
+            calls: by_model.iter().map(|g| g.calls).sum(),
+            input_tokens: by_model.iter().map(|g| g.input_tokens).sum(),
+            output_tokens: by_model.iter().map(|g| g.output_tokens).sum(),
+            cost: by_model.iter().map(|g| g.cost).sum(),
+            by_command: group("command", "5 DESC, 1")?,
+            by_day: group("date(created_at)", "1")?,
+            by_model,
+            monthly_budget: None,
+            month_cost: 0.0,
+        })
     }
 
     // ============================================================
-    // PROJECT STATS (for Dashboard)
+    // v1.4: EMBEDDING OPERATIONS
     // ============================================================
 
-    pub fn get_project_stats(&self, project_id: i64) -> Result<serde_json::Value> {
-        let component_count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM components WHERE project_id = ?",
-            params![project_id],
-            |row| row.get(0)
-        )?;
+    /// The records in `scope` with the text each would be embedded from now.
+    pub fn get_embedding_sources(&self, scope: &EmbeddingScope) -> Result<Vec<EmbeddingSource>> {
+        for content_type in &scope.content_types {
+            if !crate::embeddings::CONTENT_TYPES.contains(&content_type.as_str()) {
+                return Err(rusqlite::Error::InvalidParameterName(format!(
+                    "Unknown content type '{}' (try {})", content_type, crate::embeddings::CONTENT_TYPES.join(", ")
+                )));
+            }
+        }
+        let mut sources = Vec::new();
+        for (content_type, sql) in EMBEDDING_SOURCES {
+            if !scope.content_types.is_empty() && !scope.content_types.iter().any(|t| t == content_type) {
+                continue;
+            }
+            let mut stmt = self.conn.prepare_cached(&format!(
+                "SELECT id, project_id, text FROM ({}) WHERE ?1 IS NULL OR project_id = ?1 ORDER BY id", sql
+            ))?;
+            let rows = stmt.query_map(params![scope.project_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?))
+            })?;
+            for row in rows {
+                let (content_id, project_id, text) = row?;
+                let text = crate::embeddings::prepare(&text);
+                sources.push(EmbeddingSource {
+                    content_type: content_type.to_string(),
+                    content_id,
+                    project_id,
+                    text_hash: crate::embeddings::text_hash(&text),
+                    text,
+                });
+            }
+        }
+        Ok(sources)
+    }
 
-        let open_problems: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM problems p JOIN components c ON p.component_id = c.id 
-             WHERE c.project_id = ? AND p.status IN ('open', 'investigating')",
-            params![project_id],
-            |row| row.get(0)
-        )?;
+    /// The records in `scope` whose vector is missing, was made from different text, or was
+    /// made by another model or model version; every record in scope with `force`.
+    pub fn get_stale_embeddings(
+        &self,
+        scope: &EmbeddingScope,
+        model: &str,
+        model_version: Option<&str>,
+        force: bool,
+    ) -> Result<Vec<EmbeddingSource>> {
+        let sources = self.get_embedding_sources(scope)?;
+        if force {
+            return Ok(sources);
+        }
+        let existing = self.embedding_fingerprints(scope.project_id)?;
+        Ok(sources.into_iter()
+            .filter(|s| {
+                let fingerprint = (model.to_string(), model_version.map(String::from), Some(s.text_hash.clone()));
+                existing.get(&(s.content_type.clone(), s.content_id)) != Some(&fingerprint)
+            })
+            .collect())
+    }
 
-        let solved_problems: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM problems p JOIN components c ON p.component_id = c.id 
-             WHERE c.project_id = ? AND p.status = 'solved'",
-            params![project_id],
-            |row| row.get(0)
+    fn embedding_fingerprints(&self, project_id: Option<i64>) -> Result<EmbeddingFingerprints> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT content_type, content_id, COALESCE(embedding_model, ''), model_version, text_hash FROM embeddings 
+             WHERE embedding IS NOT NULL AND (?1 IS NULL OR project_id = ?1)"
         )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?, row.get(4)?)))
+        })?;
+        rows.collect()
+    }
 
-        let pending_todos: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM todos WHERE project_id = ? AND status = 'pending'",
-            params![project_id],
-            |row| row.get(0)
+    pub fn store_embedding(&self, source: &EmbeddingSource, model: &str, model_version: Option<&str>, vector: &[f32]) -> Result<()> {
+        self.execute(
+            "INSERT INTO embeddings (content_type, content_id, project_id, embedding_model, model_version, embedding, dimensions, text_hash) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?) 
+             ON CONFLICT(content_type, content_id) DO UPDATE SET 
+                 project_id = excluded.project_id, embedding_model = excluded.embedding_model, 
+                 model_version = excluded.model_version, embedding = excluded.embedding, 
+                 dimensions = excluded.dimensions, text_hash = excluded.text_hash, created_at = CURRENT_TIMESTAMP",
+            params![
+                source.content_type,
+                source.content_id,
+                source.project_id,
+                model,
+                model_version,
+                crate::embeddings::to_blob(vector),
+                vector.len() as i64,
+                source.text_hash,
+            ],
         )?;
+        Ok(())
+    }
 
-        let learning_count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM learnings WHERE project_id = ?",
-            params![project_id],
-            |row| row.get(0)
+    /// The vectors `model` (at `model_version`) made for one project's records of a content
+    /// type, by record id.
+    pub fn get_embedding_vectors(
+        &self,
+        project_id: i64,
+        content_type: &str,
+        model: &str,
+        model_version: Option<&str>,
+    ) -> Result<HashMap<i64, Vec<f32>>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT content_id, embedding FROM embeddings 
+             WHERE project_id = ? AND content_type = ? AND embedding_model = ? AND model_version IS ? AND embedding IS NOT NULL"
         )?;
+        let rows = stmt.query_map(params![project_id, content_type, model, model_version], |row| {
+            Ok((row.get::<_, i64>(0)?, crate::embeddings::from_blob(&row.get::<_, Vec<u8>>(1)?)))
+        })?;
+        rows.collect()
+    }
 
-        let recent_changes: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM changes ch JOIN components c ON ch.component_id = c.id 
-             WHERE c.project_id = ? AND ch.created_at >= datetime('now', '-24 hours')",
-            params![project_id],
-            |row| row.get(0)
-        )?;
+    /// Drop the vectors of records that have been deleted. Returns how many went.
+    pub fn delete_orphaned_embeddings(&self) -> Result<usize> {
+        let conditions: Vec<String> = EMBEDDING_TABLES.iter()
+            .map(|(content_type, table)| format!("(content_type = '{}' AND content_id NOT IN (SELECT id FROM {}))", content_type, table))
+            .collect();
+        self.execute(&format!("DELETE FROM embeddings WHERE {}", conditions.join(" OR ")), [])
+    }
 
-        // v1.1: Count attachments
-        let attachment_count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM attachments WHERE project_id = ?",
-            params![project_id],
-            |row| row.get(0)
-        )?;
+    /// How much of `scope` is embedded by `model` (None when no model is configured).
+    pub fn get_embedding_status(&self, scope: &EmbeddingScope, model: Option<&str>, model_version: Option<&str>) -> Result<EmbeddingStatus> {
+        let sources = self.get_embedding_sources(scope)?;
+        let existing = self.embedding_fingerprints(scope.project_id)?;
+        let mut status = EmbeddingStatus {
+            model: model.map(String::from),
+            model_version: model_version.map(String::from),
+            records: sources.len() as i64,
+            current: 0,
+            outdated: 0,
+            missing: 0,
+            vectors_by_model: BTreeMap::new(),
+        };
+        for source in &sources {
+            match existing.get(&(source.content_type.clone(), source.content_id)) {
+                None => status.missing += 1,
+                Some((m, v, hash)) if Some(m.as_str()) == model && v.as_deref() == model_version && hash.as_deref() == Some(&source.text_hash) => {
+                    status.current += 1
+                }
+                Some(_) => status.outdated += 1,
+            }
+        }
+        for (m, v, _) in existing.values() {
+            let label = match v {
+                Some(version) => format!("{}@{}", m, version),
+                None => m.clone(),
+            };
+            *status.vectors_by_model.entry(label).or_default() += 1;
+        }
+        Ok(status)
+    }
+
+    // ============================================================
+    // v1.4: REFERENTIAL INTEGRITY OPERATIONS
+    // ============================================================
+
+    /// Rows whose links point at deleted records: foreign keys SQLite reports (left behind
+    /// while enforcement was off) and type/id links it can't check. With `repair`, each is
+    /// handled the way deleting its target would have: a cascading or required link deletes
+    /// the row, any other is cleared.
+    pub fn check_referential_integrity(&self, repair: bool) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport {
+            foreign_keys_enabled: self.conn.pragma_query_value(None, "foreign_keys", |row| row.get(0))?,
+            ..Default::default()
+        };
+
+        let violations: Vec<(String, Option<i64>, String, i64)> = self.conn
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        for (table, row_id, parent, key) in violations {
+            let Some(row_id) = row_id else { continue };
+            let (column, on_delete): (String, String) = self.query_row(
+                "SELECT \"from\", on_delete FROM pragma_foreign_key_list(?) WHERE id = ? ORDER BY seq LIMIT 1",
+                params![table, key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            let required: bool = self.query_row(
+                "SELECT \"notnull\" FROM pragma_table_info(?) WHERE name = ?",
+                params![table, column],
+                |row| row.get(0),
+            )?;
+            let repair = if required || on_delete.eq_ignore_ascii_case("CASCADE") { "delete" } else { "set_null" };
+            report.orphans.push(OrphanedRow { table, row_id, column, missing_from: parent, repair: repair.to_string() });
+        }
+
+        for (table, type_column, id_column) in POLYMORPHIC_LINKS {
+            for (entity_type, parent) in LINKED_ENTITY_TABLES {
+                let sql = format!(
+                    "SELECT rowid FROM {} WHERE {} = ? AND {} NOT IN (SELECT id FROM {})",
+                    table, type_column, id_column, parent
+                );
+                let row_ids: Vec<i64> = self.conn.prepare_cached(&sql)?
+                    .query_map(params![entity_type], |row| row.get(0))?
+                    .collect::<Result<Vec<_>>>()?;
+                report.orphans.extend(row_ids.into_iter().map(|row_id| OrphanedRow {
+                    table: table.to_string(),
+                    row_id,
+                    column: id_column.to_string(),
+                    missing_from: parent.to_string(),
+                    repair: "delete".to_string(),
+                }));
+            }
+        }
+
+        if repair && !report.orphans.is_empty() {
+            let tx = self.conn.unchecked_transaction()?;
+            for orphan in &report.orphans {
+                // Table and column names come from SQLite's own catalog or the lists above
+                let sql = match orphan.repair.as_str() {
+                    "delete" => format!("DELETE FROM {} WHERE rowid = ?", orphan.table),
+                    _ => format!("UPDATE {} SET {} = NULL WHERE rowid = ?", orphan.table, orphan.column),
+                };
+                report.repaired += self.conn.execute(&sql, params![orphan.row_id])?;
+            }
+            tx.commit()?;
+        }
+        Ok(report)
+    }
+
+    // ============================================================
+    // v1.4: CONTEXT DELTA
+    // ============================================================
 
+    /// What changed in a project after `since` (any SQLite-readable timestamp, e.g. RFC 3339).
+    pub fn get_context_delta(&self, project_id: i64, since: &str) -> Result<serde_json::Value> {
+        // Normalize to the "YYYY-MM-DD HH:MM:SS" UTC form the tables store, so string compares work
+        let since: String = self.query_row("SELECT datetime(?)", params![since], |row| row.get::<_, Option<String>>(0))?
+            .ok_or_else(|| rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISMATCH),
+                Some(format!("Unreadable timestamp: {}", since)),
+            ))?;
+        let after = |ts: &str| ts > since.as_str();
+        
+        let problems = self.get_all_problems(Some(project_id), None, None, None)?;
+        let new_problems: Vec<&Problem> = problems.iter().filter(|p| after(&p.created_at)).collect();
+        let solved_problems: Vec<&Problem> = problems.iter()
+            .filter(|p| p.solved_at.as_deref().is_some_and(after))
+            .collect();
+        
+        let learnings = self.get_learnings(Some(project_id), None, false, None)?;
+        let new_learnings: Vec<&Learning> = learnings.iter().filter(|l| after(&l.created_at)).collect();
+        
+        let todos = self.get_todos(project_id, None, None, None, None)?;
+        let completed_todos: Vec<&Todo> = todos.iter()
+            .filter(|t| t.completed_at.as_deref().is_some_and(after))
+            .collect();
+        let new_todos: Vec<&Todo> = todos.iter().filter(|t| after(&t.created_at)).collect();
+        
+        let attachments = self.get_attachments(project_id, None, None, None)?;
+        let new_attachments: Vec<&Attachment> = attachments.iter().filter(|a| after(&a.created_at)).collect();
+        
+        let change_count: i64 = self.query_row(
+            "SELECT COUNT(*) FROM changes ch JOIN components c ON ch.component_id = c.id 
+             WHERE c.project_id = ? AND ch.created_at > ?",
+            params![project_id, since],
+            |row| row.get(0),
+        )?;
+        
+        let is_empty = new_problems.is_empty() && solved_problems.is_empty() && new_learnings.is_empty()
+            && completed_todos.is_empty() && new_todos.is_empty() && new_attachments.is_empty() && change_count == 0;
+        
         Ok(serde_json::json!({
-            "component_count": component_count,
-            "open_problems": open_problems,
+            "project_id": project_id,
+            "since": since,
+            "is_empty": is_empty,
+            "new_problems": new_problems,
             "solved_problems": solved_problems,
-            "pending_todos": pending_todos,
-            "learning_count": learning_count,
-            "recent_changes": recent_changes,
-            "attachment_count": attachment_count,
+            "new_learnings": new_learnings,
+            "new_todos": new_todos,
+            "completed_todos": completed_todos,
+            "new_attachments": new_attachments,
+            "change_count": change_count,
         }))
     }
 
@@ -1779,7 +9784,7 @@ impl Database {
         is_secret: bool,
         description: Option<&str>,
     ) -> Result<ProjectVariable> {
-        self.conn.execute(
+        self.execute(
             "INSERT INTO project_variables (project_id, category, name, value, is_secret, description)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![project_id, category, name, value, is_secret, description],
@@ -1789,7 +9794,7 @@ impl Database {
     }
 
     pub fn get_project_variable(&self, id: i64) -> Result<ProjectVariable> {
-        self.conn.query_row(
+        self.query_row(
             "SELECT id, project_id, category, name, value, is_secret, description, created_at, updated_at
              FROM project_variables WHERE id = ?",
             params![id],
@@ -1813,7 +9818,7 @@ impl Database {
         let mut variables = Vec::new();
         
         if let Some(cat) = category {
-            let mut stmt = self.conn.prepare(
+            let mut stmt = self.conn.prepare_cached(
                 "SELECT id, project_id, category, name, value, is_secret, description, created_at, updated_at
                  FROM project_variables WHERE project_id = ? AND category = ? ORDER BY category, name"
             )?;
@@ -1834,7 +9839,7 @@ impl Database {
                 variables.push(row?);
             }
         } else {
-            let mut stmt = self.conn.prepare(
+            let mut stmt = self.conn.prepare_cached(
                 "SELECT id, project_id, category, name, value, is_secret, description, created_at, updated_at
                  FROM project_variables WHERE project_id = ? ORDER BY category, name"
             )?;
@@ -1868,7 +9873,7 @@ impl Database {
         is_secret: Option<bool>,
         description: Option<&str>,
     ) -> Result<ProjectVariable> {
-        self.conn.execute(
+        self.execute(
             "UPDATE project_variables SET 
              category = COALESCE(?1, category),
              name = COALESCE(?2, name),
@@ -1883,7 +9888,7 @@ impl Database {
     }
 
     pub fn delete_project_variable(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM project_variables WHERE id = ?", params![id])?;
+        self.execute("DELETE FROM project_variables WHERE id = ?", params![id])?;
         Ok(())
     }
 
@@ -1901,7 +9906,7 @@ impl Database {
         code_example: Option<&str>,
         related_component_id: Option<i64>,
     ) -> Result<ProjectMethod> {
-        self.conn.execute(
+        self.execute(
             "INSERT INTO project_methods (project_id, name, description, category, steps, code_example, related_component_id)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![project_id, name, description, category, steps, code_example, related_component_id],
@@ -1911,7 +9916,7 @@ impl Database {
     }
 
     pub fn get_project_method(&self, id: i64) -> Result<ProjectMethod> {
-        self.conn.query_row(
+        self.query_row(
             "SELECT id, project_id, name, description, category, steps, code_example, related_component_id, created_at, updated_at
              FROM project_methods WHERE id = ?",
             params![id],
@@ -1936,7 +9941,7 @@ impl Database {
         let mut methods = Vec::new();
         
         if let Some(cat) = category {
-            let mut stmt = self.conn.prepare(
+            let mut stmt = self.conn.prepare_cached(
                 "SELECT id, project_id, name, description, category, steps, code_example, related_component_id, created_at, updated_at
                  FROM project_methods WHERE project_id = ? AND category = ? ORDER BY name"
             )?;
@@ -1958,7 +9963,7 @@ impl Database {
                 methods.push(row?);
             }
         } else {
-            let mut stmt = self.conn.prepare(
+            let mut stmt = self.conn.prepare_cached(
                 "SELECT id, project_id, name, description, category, steps, code_example, related_component_id, created_at, updated_at
                  FROM project_methods WHERE project_id = ? ORDER BY category, name"
             )?;
@@ -1994,7 +9999,7 @@ impl Database {
         code_example: Option<&str>,
         related_component_id: Option<i64>,
     ) -> Result<ProjectMethod> {
-        self.conn.execute(
+        self.execute(
             "UPDATE project_methods SET 
              name = COALESCE(?1, name),
              description = COALESCE(?2, description),
@@ -2010,7 +10015,7 @@ impl Database {
     }
 
     pub fn delete_project_method(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM project_methods WHERE id = ?", params![id])?;
+        self.execute("DELETE FROM project_methods WHERE id = ?", params![id])?;
         Ok(())
     }
 
@@ -2020,7 +10025,7 @@ impl Database {
 
     pub fn get_conversations(&self, project_id: i64, limit: Option<i32>) -> Result<Vec<Conversation>> {
         let limit = limit.unwrap_or(50);
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare_cached(
             "SELECT id, project_id, session_id, user_prompt_summary, assistant_response_summary,
                     key_decisions, problems_referenced, solutions_created, tokens_used, created_at
              FROM conversations WHERE project_id = ? ORDER BY created_at DESC LIMIT ?"
@@ -2054,25 +10059,13 @@ impl Database {
 
     pub fn get_sessions_list(&self, project_id: i64, limit: Option<i32>) -> Result<Vec<Session>> {
         let limit = limit.unwrap_or(50);
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare_cached(
             "SELECT id, project_id, started_at, ended_at, focus_component_id, focus_problem_id,
                     summary, outcomes, duration_minutes
              FROM sessions WHERE project_id = ? ORDER BY started_at DESC LIMIT ?"
         )?;
         
-        let rows = stmt.query_map(params![project_id, limit], |row| {
-            Ok(Session {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                started_at: row.get(2)?,
-                ended_at: row.get(3)?,
-                focus_component_id: row.get(4)?,
-                focus_problem_id: row.get(5)?,
-                summary: row.get(6)?,
-                outcomes: row.get(7)?,
-                duration_minutes: row.get(8)?,
-            })
-        })?;
+        let rows = stmt.query_map(params![project_id, limit], Self::row_to_session)?;
         
         let mut sessions = Vec::new();
         for row in rows {
@@ -2081,12 +10074,81 @@ impl Database {
         Ok(sessions)
     }
 
+    fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<Session> {
+        Ok(Session {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            started_at: row.get(2)?,
+            ended_at: row.get(3)?,
+            focus_component_id: row.get(4)?,
+            focus_problem_id: row.get(5)?,
+            summary: row.get(6)?,
+            outcomes: row.get(7)?,
+            duration_minutes: row.get(8)?,
+        })
+    }
+
+    // ============================================================
+    // v1.4: FOCUS SESSIONS
+    // ============================================================
+
+    pub fn get_session(&self, id: i64) -> Result<Session> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, project_id, started_at, ended_at, focus_component_id, focus_problem_id,
+                    summary, outcomes, duration_minutes
+             FROM sessions WHERE id = ?"
+        )?;
+        stmt.query_row(params![id], Self::row_to_session)
+    }
+
+    pub fn start_session(&self, project_id: i64, focus_component_id: Option<i64>, summary: Option<&str>) -> Result<Session> {
+        self.execute(
+            "INSERT INTO sessions (project_id, focus_component_id, summary) VALUES (?, ?, ?)",
+            params![project_id, focus_component_id, summary],
+        )?;
+        self.get_session(self.conn.last_insert_rowid())
+    }
+
+    /// Close a session, recording its wall-clock length in whole minutes.
+    pub fn end_session(&self, id: i64, summary: Option<&str>, outcomes: Option<&str>) -> Result<Session> {
+        self.execute(
+            "UPDATE sessions SET
+                ended_at = CURRENT_TIMESTAMP,
+                duration_minutes = CAST(ROUND((julianday(CURRENT_TIMESTAMP) - julianday(started_at)) * 1440) AS INTEGER),
+                summary = COALESCE(?, summary),
+                outcomes = COALESCE(?, outcomes)
+             WHERE id = ?",
+            params![summary, outcomes, id],
+        )?;
+        self.get_session(id)
+    }
+
+    pub fn get_focus_state(&self) -> Result<Option<FocusState>> {
+        let raw = match self.get_setting("focus_state")? {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        let mut focus: FocusState = match serde_json::from_str(&raw) {
+            Ok(focus) => focus,
+            Err(_) => return Ok(None), // Cleared ("null") or unreadable
+        };
+        if let Ok(ends_at) = chrono::DateTime::parse_from_rfc3339(&focus.ends_at) {
+            focus.remaining_seconds = (ends_at.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds();
+        }
+        Ok(Some(focus))
+    }
+
+    pub fn set_focus_state(&self, focus: Option<&FocusState>) -> Result<()> {
+        let value = serde_json::to_string(&focus).unwrap_or_else(|_| "null".to_string());
+        self.set_setting("focus_state", &value, Some("general"))
+    }
+
     // ============================================================
     // v1.2: CROSS REFERENCES (read-only)
     // ============================================================
 
     pub fn get_cross_references(&self, project_id: i64) -> Result<Vec<CrossReference>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare_cached(
             "SELECT id, source_project_id, source_type, source_id, target_project_id, target_type,
                     target_id, relationship, notes, created_at
              FROM cross_references WHERE source_project_id = ? OR target_project_id = ?
@@ -2116,15 +10178,253 @@ impl Database {
     }
 }
 
+// ============================================================
+// QUERY INSTRUMENTATION
+// ============================================================
+
+const SLOW_QUERY_MS: u128 = 50;
+const MAX_BUFFERED_SLOW_QUERIES: usize = 200;
+const MAX_STORED_SLOW_QUERIES: i64 = 500;
+
+// The profile hook is a plain fn and fires mid-statement, so slow queries wait here
+// until the next get_slow_queries writes them out
+static SLOW_QUERY_BUFFER: Mutex<Vec<(String, i64)>> = Mutex::new(Vec::new());
+
+fn record_query_time(sql: &str, duration: Duration) {
+    // Writing the diagnostics themselves shouldn't show up in them
+    if duration.as_millis() <= SLOW_QUERY_MS || sql.contains("slow_queries") {
+        return;
+    }
+    if let Ok(mut buffer) = SLOW_QUERY_BUFFER.lock() {
+        if buffer.len() < MAX_BUFFERED_SLOW_QUERIES {
+            buffer.push((sql.trim().to_string(), duration.as_millis() as i64));
+        }
+    }
+}
+
+// ============================================================
+// PORTABLE VALUES
+// ============================================================
+
+fn sql_to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null | Value::Blob(_) => serde_json::Value::Null,
+        Value::Integer(i) => serde_json::json!(i),
+        Value::Real(f) => serde_json::json!(f),
+        Value::Text(t) => serde_json::Value::String(t),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => n.as_i64().map(Value::Integer).unwrap_or_else(|| Value::Real(n.as_f64().unwrap_or_default())),
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+// ============================================================
+// SPACED REPETITION SCHEDULE
+// ============================================================
+
+/// SM-2 with a pass/fail grade: a pass is graded 4 (ease unchanged), a lapse
+/// restarts the sequence and costs 0.2 ease, never dropping below 1.3.
+/// Returns (ease, interval_days, repetitions).
+fn next_review_schedule(ease: f64, interval_days: i64, repetitions: i64, remembered: bool) -> (f64, i64, i64) {
+    if !remembered {
+        return ((ease - 0.2).max(1.3), 1, 0);
+    }
+    let repetitions = repetitions + 1;
+    let interval = match repetitions {
+        1 => 1,
+        2 => 6,
+        _ => ((interval_days.max(1) as f64) * ease).round() as i64,
+    };
+    (ease, interval, repetitions)
+}
+
+// ============================================================
+// NOTE REFERENCE PARSING
+// ============================================================
+
+/// Pull record references out of `[[...]]` spans: `[[FS1-P42]]` (project 1, problem 42)
+/// or the short form `[[P42]]`. Ids are global, so the project prefix is informational.
+fn parse_note_references(body: &str) -> Vec<(String, &'static str, i64)> {
+    let mut refs = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else { break };
+        let reference = rest[..end].trim();
+        rest = &rest[end + 2..];
+
+        let local = match reference.split_once('-') {
+            Some((project, local)) if project.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("FS"))
+                && project[2..].parse::<i64>().is_ok() => local,
+            Some(_) => continue,
+            None => reference,
+        };
+        let mut chars = local.chars();
+        let target_type = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('P') => "problem",
+            Some('T') => "todo",
+            Some('L') => "learning",
+            Some('C') => "component",
+            Some('S') => "solution",
+            Some('N') => "note",
+            _ => continue,
+        };
+        if let Ok(id) = chars.as_str().parse::<i64>() {
+            refs.push((reference.to_string(), target_type, id));
+        }
+    }
+    refs
+}
+
+// ============================================================
+// CI STATUS SUMMARY
+// ============================================================
+
+/// A change in a line: an imported commit's "hash subject", or "field: new value".
+fn describe_change(change: &Change) -> String {
+    match (change.field_name.as_str(), change.new_value.as_deref()) {
+        ("commit", Some(commit)) => commit.to_string(),
+        (field, Some(value)) => format!("{}: {}", field, value),
+        (field, None) => format!("{} removed", field),
+    }
+}
+
+// ============================================================
+// BENCHMARK HELPERS
+// ============================================================
+
+/// Rates (ops/s, MB/s, req/sec, Hz) are better higher; times and sizes lower.
+fn higher_is_better(unit: Option<&str>) -> bool {
+    let unit = unit.unwrap_or_default().trim().to_lowercase();
+    unit.contains("/s") || unit.contains("per s") || unit.ends_with("ps") || unit.ends_with("hz")
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[middle - 1] + values[middle]) / 2.0 } else { values[middle] })
+}
+
+/// "12.5 ms": at most three decimals, trailing zeros dropped.
+fn format_measurement(value: f64, unit: Option<&str>) -> String {
+    let number = format!("{:.3}", value).trim_end_matches('0').trim_end_matches('.').to_string();
+    match unit.filter(|u| !u.is_empty()) {
+        Some(unit) => format!("{} {}", number, unit),
+        None => number,
+    }
+}
+
 // ============================================================
 // DATABASE PATH HELPER
 // ============================================================
 
 pub fn get_default_db_path() -> PathBuf {
-    let data_dir = dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("flowstate");
+    let data_dir = crate::platform::data_dir();
     
     std::fs::create_dir_all(&data_dir).ok();
     data_dir.join("flowstate.db")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRAFTED: &str = "x'); DROP TABLE problems; --";
+
+    fn database(name: &str) -> Database {
+        let dir = std::env::temp_dir().join(format!("flowstate-test-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("flowstate.db");
+        let _ = std::fs::remove_file(&path);
+        Database::new(path).unwrap()
+    }
+
+    #[test]
+    fn search_treats_crafted_text_as_data() {
+        let db = database("search-crafted");
+        let project = db.create_project(CRAFTED, Some("100% \"quoted\" o'clock")).unwrap();
+        let component = db.create_component(project.id, CRAFTED, None, None).unwrap();
+        db.log_problem(component.id, CRAFTED, Some("CPU at 100%"), "high").unwrap();
+        db.log_problem(component.id, "Plain problem", Some("snake_case"), "low").unwrap();
+        db.log_learning(project.id, "Escape 50% of inputs", None, Some("o'clock"), None, "experience").unwrap();
+
+        let titles = |query: &str| -> Vec<String> {
+            db.search(query, Some(project.id), 50).unwrap()
+                .iter()
+                .map(|r| r["title"].as_str().unwrap_or_default().to_string())
+                .collect()
+        };
+        assert_eq!(titles(CRAFTED), vec![CRAFTED.to_string()]);
+        assert_eq!(titles("100%"), vec![CRAFTED.to_string()]);
+        assert_eq!(titles("%"), vec![CRAFTED.to_string(), "Escape 50% of inputs".to_string()]);
+        assert_eq!(titles("_"), vec!["Plain problem".to_string()]);
+        assert_eq!(titles("o'clock"), vec!["Escape 50% of inputs".to_string()]);
+        assert!(db.get_project(project.id).is_ok());
+        assert_eq!(db.get_all_problems(Some(project.id), None, None, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn fuzzed_search_input_never_errors() {
+        let db = database("search-fuzz");
+        let project = db.create_project(CRAFTED, None).unwrap();
+        let component = db.create_component(project.id, "Core", None, None).unwrap();
+        db.log_problem(component.id, "Quote ' and percent % and underscore _", None, "medium").unwrap();
+        let pieces = ["'", "\"", "%", "_", "\\", ";", "--", "*", "(", ")", "NEAR", "OR", "AND", "^", ":", "?", "é", " ", "x"];
+        // A small linear congruential generator keeps the cases the same on every run
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..500 {
+            let mut input = String::new();
+            for _ in 0..(seed % 7) {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                input.push_str(pieces[(seed >> 33) as usize % pieces.len()]);
+            }
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            for project_id in [None, Some(project.id)] {
+                let results = db.search(&input, project_id, 20)
+                    .unwrap_or_else(|e| panic!("search for {:?} failed: {}", input, e));
+                let needle = input.to_lowercase();
+                for result in results.iter().filter(|r| r["type"] == "problem") {
+                    let title = result["title"].as_str().unwrap_or_default().to_lowercase();
+                    assert!(title.contains(&needle), "{:?} matched {:?}", input, title);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quick_add_keeps_a_problems_due_date_on_a_todo() {
+        let db = database("quick-add");
+        let project = db.create_project("Quick add", None).unwrap();
+        let component = db.create_component(project.id, "auth", None, None).unwrap();
+        let components = db.list_components(project.id).unwrap();
+        let parsed = crate::quickadd::interpret("fix login crash !high @auth due friday #bug", db.timezone().now(), &components);
+        assert_eq!((parsed.kind.as_str(), parsed.title.as_str()), ("problem", "fix login crash"));
+        assert!(parsed.due_date.is_some());
+
+        let created = db.quick_add(project.id, &parsed).unwrap();
+        assert_eq!(created["record"]["severity"], "high");
+        assert_eq!(created["record"]["component_id"], component.id);
+        assert_eq!(created["tags"], serde_json::json!(["bug"]));
+        assert_eq!(created["todo"]["due_date"].as_str(), parsed.due_date.as_deref());
+        assert_eq!(created["todo"]["priority"], "high");
+    }
+
+    #[test]
+    fn note_references_skip_malformed_project_prefixes() {
+        let refs = parse_note_references("See [[Fé1-P42]], [[é]], [[FS1-P42]] and [[t7]]");
+        assert_eq!(refs, vec![
+            ("FS1-P42".to_string(), "problem", 42),
+            ("t7".to_string(), "todo", 7),
+        ]);
+    }
+}
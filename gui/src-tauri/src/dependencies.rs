@@ -0,0 +1,186 @@
+// FlowState Dependencies Module - Locked dependency versions, and what changed between two looks
+// Every Cargo.lock and package-lock.json in a repository is read (workspace crates and the
+// root package are the project itself and are left out). Comparing a snapshot with the one
+// before gives "upgraded tokio 1.32.0 → 1.38.0", which is logged as a change: exactly the
+// context wanted when asking what changed before things broke.
+
+use crate::dirwatch;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const LOCKFILES: &[&str] = &["Cargo.lock", "package-lock.json"];
+const MAX_LOCKFILE_DEPTH: usize = 4;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dependency {
+    pub lockfile: String, // Relative to the repository root
+    pub ecosystem: String, // cargo or npm
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyChange {
+    pub lockfile: String,
+    pub ecosystem: String,
+    pub name: String,
+    pub kind: String, // added, removed, upgraded, downgraded, changed
+    pub old_version: Option<String>, // Several versions locked at once are joined with ", "
+    pub new_version: Option<String>,
+}
+
+impl DependencyChange {
+    /// "upgraded tokio 1.32.0 → 1.38.0"
+    pub fn summary(&self) -> String {
+        match (&self.old_version, &self.new_version) {
+            (Some(old), Some(new)) => format!("{} {} {} → {}", self.kind, self.name, old, new),
+            (None, Some(new)) => format!("added {} {}", self.name, new),
+            (Some(old), None) => format!("removed {} {}", self.name, old),
+            (None, None) => format!("{} {}", self.kind, self.name),
+        }
+    }
+}
+
+/// Every locked dependency under `root`, sorted. No lockfile at all is an error, since a
+/// snapshot of nothing would read as every dependency removed.
+pub fn scan(root: &Path) -> Result<Vec<Dependency>, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let lockfiles = find_lockfiles(root);
+    if lockfiles.is_empty() {
+        return Err(format!("No Cargo.lock or package-lock.json under {}", root.display()));
+    }
+    let mut dependencies = Vec::new();
+    for lockfile in lockfiles {
+        let text = std::fs::read_to_string(root.join(&lockfile)).map_err(|e| format!("Failed to read {}: {}", lockfile, e))?;
+        let parsed = if lockfile.ends_with("Cargo.lock") {
+            parse_cargo_lock(&text)
+        } else {
+            parse_package_lock(&text)
+        };
+        let packages = parsed.map_err(|e| format!("Failed to read {}: {}", lockfile, e))?;
+        let ecosystem = if lockfile.ends_with("Cargo.lock") { "cargo" } else { "npm" };
+        dependencies.extend(packages.into_iter().map(|(name, version)| Dependency {
+            lockfile: lockfile.clone(),
+            ecosystem: ecosystem.to_string(),
+            name,
+            version,
+        }));
+    }
+    dependencies.sort();
+    dependencies.dedup();
+    Ok(dependencies)
+}
+
+/// What changed from `old` to `new`, per lockfile and package, sorted.
+pub fn diff(old: &[Dependency], new: &[Dependency]) -> Vec<DependencyChange> {
+    type Key = (String, String, String);
+    let versions = |dependencies: &[Dependency]| -> BTreeMap<Key, Vec<String>> {
+        let mut versions: BTreeMap<Key, Vec<String>> = BTreeMap::new();
+        for d in dependencies {
+            versions.entry((d.lockfile.clone(), d.ecosystem.clone(), d.name.clone())).or_default().push(d.version.clone());
+        }
+        versions
+    };
+    let (old, new) = (versions(old), versions(new));
+    let mut keys: Vec<&Key> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (before, after) = (old.get(key), new.get(key));
+            let kind = match (before, after) {
+                (Some(b), Some(a)) if b == a => return None,
+                (None, _) => "added",
+                (_, None) => "removed",
+                (Some(b), Some(a)) => match (b.as_slice(), a.as_slice()) {
+                    ([b], [a]) if version_key(a) > version_key(b) => "upgraded",
+                    ([b], [a]) if version_key(a) < version_key(b) => "downgraded",
+                    _ => "changed",
+                },
+            };
+            Some(DependencyChange {
+                lockfile: key.0.clone(),
+                ecosystem: key.1.clone(),
+                name: key.2.clone(),
+                kind: kind.to_string(),
+                old_version: before.map(|v| v.join(", ")),
+                new_version: after.map(|v| v.join(", ")),
+            })
+        })
+        .collect()
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn find_lockfiles(root: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut pending = vec![(String::new(), 0)];
+    while let Some((relative, depth)) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(root.join(&relative)) else { continue };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = if relative.is_empty() { name.clone() } else { format!("{}/{}", relative, name) };
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                if depth + 1 < MAX_LOCKFILE_DEPTH && !name.starts_with('.') && !dirwatch::SKIPPED_DIRS.contains(&name.as_str()) {
+                    pending.push((path, depth + 1));
+                }
+            } else if LOCKFILES.contains(&name.as_str()) {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// (name, version) of the registry and git packages; path crates have no `source`.
+fn parse_cargo_lock(text: &str) -> Result<Vec<(String, String)>, String> {
+    let lock: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    let packages = lock.get("package").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    Ok(packages.iter()
+        .filter(|p| p.get("source").is_some())
+        .filter_map(|p| Some((p.get("name")?.as_str()?.to_string(), p.get("version")?.as_str()?.to_string())))
+        .collect())
+}
+
+/// Lockfile v2/v3 list installed paths under `packages` ("node_modules/a/node_modules/b");
+/// v1 nests `dependencies`. Links to workspace packages are the project's own code.
+fn parse_package_lock(text: &str) -> Result<Vec<(String, String)>, String> {
+    let lock: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let mut packages = Vec::new();
+    if let Some(installed) = lock.get("packages").and_then(|p| p.as_object()) {
+        for (path, package) in installed {
+            let Some((_, name)) = path.rsplit_once("node_modules/") else { continue };
+            if package.get("link").and_then(|l| l.as_bool()) == Some(true) {
+                continue;
+            }
+            if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                packages.push((name.to_string(), version.to_string()));
+            }
+        }
+    } else {
+        let mut pending: Vec<&serde_json::Value> = lock.get("dependencies").into_iter().collect();
+        while let Some(dependencies) = pending.pop() {
+            for (name, package) in dependencies.as_object().into_iter().flatten() {
+                if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                    packages.push((name.clone(), version.to_string()));
+                }
+                pending.extend(package.get("dependencies"));
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// The numbers in a version, for ordering: "1.38.0" before "1.4.0" would be wrong as text.
+fn version_key(version: &str) -> Vec<u64> {
+    version.split(['.', '-', '+'])
+        .map(|part| part.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0))
+        .collect()
+}
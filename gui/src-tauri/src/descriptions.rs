@@ -0,0 +1,157 @@
+// FlowState Descriptions Module - AI descriptions for attachments, several files per request
+// Forty screenshots imported at once shouldn't mean forty requests: small files are grouped,
+// each numbered in the prompt, and the model answers one line per number. Big files still go
+// on their own. Images are sent for models that can see them; text files as an excerpt.
+
+use crate::llm::{Part, Usage};
+use serde::Serialize;
+
+/// Files at most this size share a request.
+pub const SMALL_FILE_BYTES: u64 = 512 * 1024;
+/// Nothing larger is sent at all.
+pub const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_BATCH_FILES: usize = 8;
+const MAX_BATCH_BYTES: u64 = 2 * 1024 * 1024;
+const TEXT_EXCERPT_CHARS: usize = 4000;
+const OUTPUT_TOKENS_PER_FILE: u32 = 120;
+// What an image costs in input tokens, roughly, for budgeting before the request is made
+const IMAGE_TOKEN_ESTIMATE: i64 = 1000;
+
+pub const SYSTEM_PROMPT: &str = "You describe files attached to a software project's notes \
+so they can be found again by searching. You get numbered files. For each reply with one line, \
+\"number: description\", where the description is one or two sentences on what the file shows \
+or contains: the screen, error, diagram or data, with any names, versions or messages in it. \
+Reply with those lines only.";
+
+const IMAGE_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+];
+
+const TEXT_TYPES: &[&str] = &[
+    "txt", "md", "log", "csv", "tsv", "json", "yaml", "yml", "toml", "xml", "html", "css", "sql", "sh",
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "swift", "c", "h", "cpp", "hpp", "cs", "rb", "php",
+];
+
+#[derive(Debug, Clone)]
+pub enum FileKind {
+    Image { mime: String },
+    Text,
+}
+
+/// An attachment waiting for its description.
+#[derive(Debug, Clone)]
+pub struct PendingFile {
+    pub id: i64,
+    pub name: String,
+    pub path: String,
+    pub kind: FileKind,
+    pub size: u64,
+}
+
+/// Token and cost totals for a job.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct Spend {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+impl Spend {
+    pub fn add(&mut self, usage: &Usage, cost: f64) {
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost += cost;
+    }
+}
+
+/// How a file would be sent, or None for a type the model can't read.
+pub fn kind_of(file_type: &str) -> Option<FileKind> {
+    let ext = file_type.to_lowercase();
+    if let Some((_, mime)) = IMAGE_TYPES.iter().find(|(e, _)| *e == ext) {
+        return Some(FileKind::Image { mime: mime.to_string() });
+    }
+    TEXT_TYPES.contains(&ext.as_str()).then_some(FileKind::Text)
+}
+
+/// Group files into requests: small ones together, up to a count and a total size, large
+/// ones alone. Order is kept within each group.
+pub fn batches(files: Vec<PendingFile>) -> Vec<Vec<PendingFile>> {
+    let mut batches: Vec<Vec<PendingFile>> = Vec::new();
+    let mut current: Vec<PendingFile> = Vec::new();
+    let mut current_bytes = 0;
+    for file in files {
+        if file.size > SMALL_FILE_BYTES {
+            batches.push(vec![file]);
+            continue;
+        }
+        if current.len() == MAX_BATCH_FILES || current_bytes + file.size > MAX_BATCH_BYTES {
+            batches.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current_bytes += file.size;
+        current.push(file);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// The prompt for a batch, from each file's bytes (None where it couldn't be read).
+pub fn prompt(batch: &[PendingFile], contents: &[Option<Vec<u8>>]) -> Vec<Part> {
+    let mut parts = Vec::new();
+    for (i, (file, content)) in batch.iter().zip(contents).enumerate() {
+        let heading = format!("File {}: {}", i + 1, file.name);
+        match (&file.kind, content) {
+            (FileKind::Image { mime }, Some(data)) => {
+                parts.push(Part::Text(heading));
+                parts.push(Part::Image { mime: mime.clone(), data: data.clone() });
+            }
+            (FileKind::Text, Some(data)) => {
+                let text: String = String::from_utf8_lossy(data).chars().take(TEXT_EXCERPT_CHARS).collect();
+                parts.push(Part::Text(format!("{}\n```\n{}\n```", heading, text)));
+            }
+            // Going by the name alone beats renumbering the batch
+            (_, None) => parts.push(Part::Text(format!("{} (contents unavailable)", heading))),
+        }
+    }
+    parts
+}
+
+pub fn max_tokens(batch: &[PendingFile]) -> u32 {
+    OUTPUT_TOKENS_PER_FILE * batch.len() as u32
+}
+
+/// Roughly what a batch will use, before it's sent.
+pub fn estimate(batch: &[PendingFile]) -> Usage {
+    let input: i64 = batch.iter()
+        .map(|file| match file.kind {
+            FileKind::Image { .. } => IMAGE_TOKEN_ESTIMATE,
+            FileKind::Text => (file.size as i64).min(TEXT_EXCERPT_CHARS as i64) / crate::context::CHARS_PER_TOKEN as i64,
+        })
+        .sum();
+    Usage { input_tokens: input + 150, output_tokens: max_tokens(batch) as i64 }
+}
+
+/// The description for each of `count` numbered files; None where the reply skipped one.
+pub fn parse_reply(reply: &str, count: usize) -> Vec<Option<String>> {
+    let mut descriptions = vec![None; count];
+    for line in reply.lines() {
+        let Some((number, text)) = line.split_once([':', '.', ')']) else {
+            continue;
+        };
+        let number = number.trim().trim_start_matches(['-', '*', '#']).trim();
+        let number = number.strip_prefix("File").unwrap_or(number).trim();
+        let (Ok(number), text) = (number.parse::<usize>(), text.trim()) else {
+            continue;
+        };
+        if (1..=count).contains(&number) && !text.is_empty() {
+            descriptions[number - 1] = Some(text.to_string());
+        }
+    }
+    descriptions
+}
@@ -0,0 +1,99 @@
+// FlowState Directory Watch Module - Find the files a directory watch keeps attached
+// Watches are polled: each scan walks the directory and returns the files matching the
+// watch's globs with their modification times, so the caller can tell which ones changed
+// since it last looked. VCS, dependency and build folders are never entered.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const SKIPPED_DIRS: &[&str] = &[".git", ".hg", ".svn", "node_modules", "target", ".venv", "venv", "__pycache__", ".next"];
+// A misconfigured watch on a home directory shouldn't attach the whole disk
+pub const MAX_FILES_PER_SCAN: usize = 2000;
+
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// Files under `root` matching any of `globs` (relative, `/`-separated: `docs/**/*.md`,
+/// `src/*.{rs,toml}`), sorted by path and capped at MAX_FILES_PER_SCAN.
+pub fn scan(root: &Path, globs: &[String]) -> Vec<ScannedFile> {
+    let patterns: Vec<String> = globs.iter().flat_map(|g| expand_braces(g.trim().trim_start_matches("./"))).collect();
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !SKIPPED_DIRS.contains(&name.to_string_lossy().as_ref()) {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let Some(relative) = relative_path(root, &path) else { continue };
+            if patterns.iter().any(|p| glob_matches(p, &relative)) {
+                let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+                found.push(ScannedFile { path, modified });
+            }
+        }
+    }
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found.truncate(MAX_FILES_PER_SCAN);
+    found
+}
+
+/// `**` matches any number of directories (including none), `*` and `?` stay within one.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => {
+            !path.is_empty() && match_segment(segment.as_bytes(), path[0].as_bytes()) && match_segments(rest, &path[1..])
+        }
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+/// `*.{md,txt}` -> `*.md`, `*.txt` (nested braces aren't supported).
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (pattern.find('{'), pattern.find('}')) else {
+        return vec![pattern.to_string()];
+    };
+    if close < open {
+        return vec![pattern.to_string()];
+    }
+    pattern[open + 1..close]
+        .split(',')
+        .flat_map(|option| expand_braces(&format!("{}{}{}", &pattern[..open], option, &pattern[close + 1..])))
+        .collect()
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    Some(parts.join("/"))
+}
@@ -0,0 +1,50 @@
+// FlowState Embeddings Module - Vectors for problems, solutions, learnings, changes and attachments
+// Vectors come from any OpenAI-compatible /embeddings endpoint and are stored as little-endian
+// f32 blobs next to the model that made them and a hash of the text they were made from, so
+// a changed record or a different model re-embeds just the vectors it affects.
+
+use sha2::{Digest, Sha256};
+
+/// Content types that get vectors, as the embeddings table names them.
+pub const CONTENT_TYPES: &[&str] = &["problem", "solution", "learning", "change", "attachment"];
+
+/// Texts sent to the endpoint in one request.
+pub const BATCH_SIZE: usize = 32;
+
+// Most embedding models take 512-8192 tokens; anything past this is cut rather than rejected
+const MAX_TEXT_CHARS: usize = 8000;
+
+/// The text to embed, cut to a size every model accepts.
+pub fn prepare(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((cut, _)) => text[..cut].to_string(),
+        None => text.to_string(),
+    }
+}
+
+pub fn text_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// Cosine similarity, 0 when either vector is all zeros or they differ in length.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+pub fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
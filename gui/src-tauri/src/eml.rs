@@ -0,0 +1,194 @@
+// FlowState Eml Module - Read saved emails (.eml) into inbox items
+// Enough of RFC 5322 and MIME to get a subject, a sender, a date and a readable body out of
+// what a mail client saves: folded headers, multipart messages (the first text/plain part,
+// else the first text/html with its tags dropped) and quoted-printable or base64 bodies.
+// Encoded-word subjects (=?UTF-8?B?...?=) are decoded too.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+const MAX_BODY_CHARS: usize = 20_000;
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub date: Option<String>,
+    pub body: String,
+}
+
+impl Email {
+    /// The body with the sender and date on top, as the inbox item's content.
+    pub fn content(&self) -> String {
+        let mut header = Vec::new();
+        if let Some(from) = &self.from {
+            header.push(format!("From: {}", from));
+        }
+        if let Some(date) = &self.date {
+            header.push(format!("Date: {}", date));
+        }
+        if header.is_empty() {
+            return self.body.clone();
+        }
+        format!("{}\n\n{}", header.join("\n"), self.body)
+    }
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Email, String> {
+    let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+    let (headers, body) = split_message(&text);
+    if headers.is_empty() {
+        return Err("This doesn't look like an email: it has no headers".to_string());
+    }
+    let header = |name: &str| {
+        headers.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| decode_words(v))
+            .filter(|v| !v.is_empty())
+    };
+    let body = text_of(&headers, body, 0).unwrap_or_default();
+    Ok(Email {
+        subject: header("Subject"),
+        from: header("From"),
+        date: header("Date"),
+        body: body.trim().chars().take(MAX_BODY_CHARS).collect(),
+    })
+}
+
+/// Headers, unfolded, and what follows the blank line after them.
+fn split_message(text: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = text.split_once("\n\n").unwrap_or((text, ""));
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+/// The readable text of a part, looking inside multiparts for a plain-text part first.
+fn text_of(headers: &[(String, String)], body: &str, depth: usize) -> Option<String> {
+    let content_type = headers.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case("Content-Type"))
+        .map(|(_, v)| v.as_str())
+        .unwrap_or("text/plain");
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+
+    if mime.starts_with("multipart/") {
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        let boundary = parameter(content_type, "boundary")?;
+        let parts: Vec<(Vec<(String, String)>, &str)> = body.split(&format!("--{}", boundary))
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            .map(|part| split_message(part.trim_start_matches('\n')))
+            .collect();
+        let plain = parts.iter().find_map(|(h, b)| {
+            let inner = h.iter().find(|(n, _)| n.eq_ignore_ascii_case("Content-Type")).map(|(_, v)| v.to_lowercase());
+            let wanted = inner.as_deref().is_none_or(|t| t.starts_with("text/plain") || t.starts_with("multipart/"));
+            if wanted { text_of(h, b, depth + 1) } else { None }
+        });
+        return plain.or_else(|| parts.iter().find_map(|(h, b)| text_of(h, b, depth + 1)));
+    }
+    if !mime.starts_with("text/") {
+        return None;
+    }
+
+    let encoding = headers.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case("Content-Transfer-Encoding"))
+        .map(|(_, v)| v.trim().to_lowercase())
+        .unwrap_or_default();
+    let decoded = match encoding.as_str() {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            String::from_utf8_lossy(&STANDARD.decode(compact).ok()?).to_string()
+        }
+        "quoted-printable" => String::from_utf8_lossy(&quoted_printable(body)).to_string(),
+        _ => body.to_string(),
+    };
+    Some(if mime == "text/html" { strip_tags(&decoded) } else { decoded })
+}
+
+/// A `name=value` parameter of a header, quoted or not.
+fn parameter(header: &str, name: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn quoted_printable(body: &str) -> Vec<u8> {
+    let bytes = body.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'=' if bytes.get(i + 1) == Some(&b'\n') => i += 2, // Soft line break
+            b'=' => match bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()) {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 3;
+                }
+                None => {
+                    out.push(b'=');
+                    i += 1;
+                }
+            },
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Encoded words in a header: `=?charset?B?...?=` (base64) or `=?charset?Q?...?=`.
+/// Only UTF-8 and ASCII-compatible charsets come out right; others are read as UTF-8.
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let Some(end) = rest[start + 2..].find("?=").map(|e| start + 2 + e) else { break };
+        let mut fields = rest[start + 2..end].splitn(3, '?');
+        let (Some(_charset), Some(encoding), Some(text)) = (fields.next(), fields.next(), fields.next()) else { break };
+        let decoded = match encoding.to_ascii_uppercase().as_str() {
+            "B" => STANDARD.decode(text).ok(),
+            "Q" => Some(quoted_printable(&text.replace('_', " "))),
+            _ => None,
+        };
+        let Some(decoded) = decoded else { break };
+        // Whitespace between two encoded words is dropped
+        let before = &rest[..start];
+        if !(after_word && before.trim().is_empty()) {
+            out.push_str(before);
+        }
+        out.push_str(&String::from_utf8_lossy(&decoded));
+        after_word = true;
+        rest = &rest[end + 2..];
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ").replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"")
+}
@@ -0,0 +1,398 @@
+// FlowState Filters Module - One filter language for list commands, the filter bar and saved searches
+// `status:open,investigating severity>=high created:<7d component:auth -tag:wontfix login` keeps
+// open or investigating problems of high severity or worse, less than a week old, on the auth
+// component, not tagged wontfix, mentioning "login". Terms are ANDed; commas within a term OR.
+// Relative times read as ages (created:<7d is under seven days old); +3d is three days ahead,
+// so due:<+3d is due within three days. The expression is compiled to a SQL condition with
+// bound parameters against one entity's FilterSchema.
+
+use crate::timezone::{self, Zone};
+use rusqlite::types::Value;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// One space-separated part of an expression. Free text has no field.
+#[derive(Debug, Clone, Serialize)]
+pub struct Term {
+    pub negated: bool,
+    pub field: Option<String>,
+    pub op: Op,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FieldKind {
+    Enum(&'static [&'static str]),    // Any of these values
+    Ordered(&'static [&'static str]), // Lowest first, so >= and < work
+    Date,
+    Bool,
+    Name, // Case-insensitive text equality
+    Tags, // Tag on the record, from the tags table
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub column: &'static str,
+}
+
+/// How an entity's records are filtered: its table (with the alias the columns use) and fields.
+pub struct FilterSchema {
+    pub entity: &'static str, // As in the tags table
+    pub from: &'static str,
+    pub id_column: &'static str,
+    pub project_column: &'static str,
+    pub text_columns: &'static [&'static str],
+    pub fields: &'static [Field],
+}
+
+const SEVERITIES: &[&str] = &["low", "medium", "high", "critical"];
+
+pub const PROBLEMS: FilterSchema = FilterSchema {
+    entity: "problem",
+    from: "problems p JOIN components c ON c.id = p.component_id",
+    id_column: "p.id",
+    project_column: "c.project_id",
+    text_columns: &["p.title", "p.description"],
+    fields: &[
+        Field { name: "status", kind: FieldKind::Enum(&["open", "investigating", "blocked", "solved", "wont_fix"]), column: "p.status" },
+        Field { name: "severity", kind: FieldKind::Ordered(SEVERITIES), column: "p.severity" },
+        Field { name: "component", kind: FieldKind::Name, column: "c.name" },
+        Field { name: "created", kind: FieldKind::Date, column: "p.created_at" },
+        Field { name: "updated", kind: FieldKind::Date, column: "p.updated_at" },
+        Field { name: "solved", kind: FieldKind::Date, column: "p.solved_at" },
+        Field { name: "tag", kind: FieldKind::Tags, column: "p.id" },
+    ],
+};
+
+pub const TODOS: FilterSchema = FilterSchema {
+    entity: "todo",
+    from: "todos t LEFT JOIN components c ON c.id = t.component_id",
+    id_column: "t.id",
+    project_column: "t.project_id",
+    text_columns: &["t.title", "t.description"],
+    fields: &[
+        Field { name: "status", kind: FieldKind::Enum(&["pending", "in_progress", "blocked", "done", "cancelled"]), column: "t.status" },
+        Field { name: "priority", kind: FieldKind::Ordered(SEVERITIES), column: "t.priority" },
+        Field { name: "component", kind: FieldKind::Name, column: "c.name" },
+        Field { name: "created", kind: FieldKind::Date, column: "t.created_at" },
+        Field { name: "updated", kind: FieldKind::Date, column: "t.updated_at" },
+        Field { name: "due", kind: FieldKind::Date, column: "t.due_date" },
+        Field { name: "completed", kind: FieldKind::Date, column: "t.completed_at" },
+        Field { name: "tag", kind: FieldKind::Tags, column: "t.id" },
+    ],
+};
+
+pub const LEARNINGS: FilterSchema = FilterSchema {
+    entity: "learning",
+    from: "learnings l LEFT JOIN components c ON c.id = l.component_id",
+    id_column: "l.id",
+    project_column: "l.project_id",
+    text_columns: &["l.insight", "l.context"],
+    fields: &[
+        Field {
+            name: "category",
+            kind: FieldKind::Enum(&["pattern", "gotcha", "best_practice", "tool_tip", "architecture", "performance", "security", "other"]),
+            column: "l.category",
+        },
+        Field { name: "verified", kind: FieldKind::Bool, column: "l.verified" },
+        Field { name: "component", kind: FieldKind::Name, column: "c.name" },
+        Field { name: "created", kind: FieldKind::Date, column: "l.created_at" },
+        Field { name: "updated", kind: FieldKind::Date, column: "l.updated_at" },
+        Field { name: "tag", kind: FieldKind::Tags, column: "l.id" },
+    ],
+};
+
+/// The schema for an entity name, singular or plural.
+pub fn schema(entity: &str) -> Result<&'static FilterSchema, String> {
+    match entity.trim_end_matches('s') {
+        "problem" => Ok(&PROBLEMS),
+        "todo" => Ok(&TODOS),
+        "learning" => Ok(&LEARNINGS),
+        _ => Err(format!("Can't filter {} (problem, todo or learning)", entity)),
+    }
+}
+
+/// The SQL a field's values are counted by, for fields with a handful of values. Dates have
+/// too many to make filter chips of, and tags are counted through the tags table instead.
+pub fn facet_column(field: &Field) -> Option<String> {
+    match field.kind {
+        FieldKind::Enum(_) | FieldKind::Ordered(_) | FieldKind::Name => Some(field.column.to_string()),
+        FieldKind::Bool => Some(format!("CASE WHEN COALESCE({}, 0) THEN 'true' ELSE 'false' END", field.column)),
+        FieldKind::Date | FieldKind::Tags => None,
+    }
+}
+
+/// A condition to AND onto a query over the schema's `from`, with its parameters in order.
+#[derive(Debug, Default)]
+pub struct Compiled {
+    pub sql: String, // Empty, or " AND …"
+    pub params: Vec<Value>,
+}
+
+/// Longest first, so ">=" isn't read as ">" followed by "=".
+const OPERATORS: &[(&str, Op)] = &[
+    (":>=", Op::Ge), (":<=", Op::Le), (":!=", Op::Ne), (":>", Op::Gt), (":<", Op::Lt),
+    (">=", Op::Ge), ("<=", Op::Le), ("!=", Op::Ne), (">", Op::Gt), ("<", Op::Lt),
+    (":", Op::Eq), ("=", Op::Eq),
+];
+
+/// Split an expression into terms. Double quotes keep spaces in a value or phrase.
+pub fn parse(expression: &str) -> Result<Vec<Term>, String> {
+    let mut terms = Vec::new();
+    for token in tokenize(expression)? {
+        let (negated, body) = match token.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, token.as_str()),
+        };
+        let field_len = body.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(body.len());
+        let operator = OPERATORS.iter().find(|(symbol, _)| body[field_len..].starts_with(symbol));
+        let term = match operator {
+            Some((symbol, op)) if field_len > 0 => {
+                let value = body[field_len + symbol.len()..].trim_matches('"');
+                let values: Vec<String> = value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+                if values.is_empty() {
+                    return Err(format!("'{}' needs a value", token));
+                }
+                if values.len() > 1 && !matches!(op, Op::Eq | Op::Ne) {
+                    return Err(format!("'{}': only : and != take several values", token));
+                }
+                Term { negated, field: Some(body[..field_len].to_lowercase()), op: *op, values }
+            }
+            _ => Term { negated, field: None, op: Op::Eq, values: vec![body.trim_matches('"').to_string()] },
+        };
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in expression.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unclosed quote in filter".to_string());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// The SQL condition for `terms` over `schema`, with days read in `zone`. Fails on fields
+/// the entity doesn't have.
+pub fn compile(terms: &[Term], schema: &FilterSchema, zone: Zone) -> Result<Compiled, String> {
+    let mut compiled = Compiled::default();
+    for term in terms {
+        let condition = match &term.field {
+            None => {
+                let like = format!("%{}%", term.values[0].to_lowercase());
+                let columns: Vec<String> = schema.text_columns.iter()
+                    .map(|column| {
+                        compiled.params.push(Value::Text(like.clone()));
+                        format!("LOWER(COALESCE({}, '')) LIKE ?", column)
+                    })
+                    .collect();
+                format!("({})", columns.join(" OR "))
+            }
+            Some(name) => {
+                let field = schema.fields.iter().find(|f| f.name == name).ok_or_else(|| {
+                    let known: Vec<&str> = schema.fields.iter().map(|f| f.name).collect();
+                    format!("Unknown filter field '{}' for {}s ({})", name, schema.entity, known.join(", "))
+                })?;
+                field_condition(field, term, schema, zone, &mut compiled.params)?
+            }
+        };
+        if term.negated {
+            compiled.sql.push_str(&format!(" AND NOT COALESCE({}, 0)", condition));
+        } else {
+            compiled.sql.push_str(&format!(" AND {}", condition));
+        }
+    }
+    Ok(compiled)
+}
+
+fn field_condition(field: &Field, term: &Term, schema: &FilterSchema, zone: Zone, params: &mut Vec<Value>) -> Result<String, String> {
+    let bad = |value: &str, expected: &str| format!("{}: '{}' isn't {}", field.name, value, expected);
+    let placeholders = |n: usize| vec!["?"; n].join(", ");
+    let membership = |column: &str, count: usize, op: Op| {
+        let not = if op == Op::Ne { "NOT " } else { "" };
+        format!("{} {}IN ({})", column, not, placeholders(count))
+    };
+    match field.kind {
+        FieldKind::Enum(allowed) | FieldKind::Ordered(allowed) => {
+            let values: Vec<String> = term.values.iter().map(|v| v.to_lowercase()).collect();
+            if let Some(unknown) = values.iter().find(|v| !allowed.contains(&v.as_str())) {
+                return Err(bad(unknown, &format!("one of {}", allowed.join(", "))));
+            }
+            let selected: Vec<&str> = match (field.kind, term.op) {
+                (_, Op::Eq | Op::Ne) => values.iter().map(String::as_str).collect(),
+                (FieldKind::Ordered(_), op) => {
+                    let rank = allowed.iter().position(|a| *a == values[0]).unwrap_or(0);
+                    allowed.iter().enumerate()
+                        .filter(|(i, _)| match op {
+                            Op::Lt => *i < rank,
+                            Op::Le => *i <= rank,
+                            Op::Gt => *i > rank,
+                            _ => *i >= rank,
+                        })
+                        .map(|(_, a)| *a)
+                        .collect()
+                }
+                _ => return Err(format!("{} can't be compared with < or >", field.name)),
+            };
+            if selected.is_empty() {
+                return Ok("0".to_string());
+            }
+            params.extend(selected.iter().map(|v| Value::Text(v.to_string())));
+            let op = if term.op == Op::Ne { Op::Ne } else { Op::Eq };
+            Ok(membership(field.column, selected.len(), op))
+        }
+        FieldKind::Name => {
+            if !matches!(term.op, Op::Eq | Op::Ne) {
+                return Err(format!("{} can't be compared with < or >", field.name));
+            }
+            params.extend(term.values.iter().map(|v| Value::Text(v.to_lowercase())));
+            Ok(membership(&format!("LOWER({})", field.column), term.values.len(), term.op))
+        }
+        FieldKind::Tags => {
+            if !matches!(term.op, Op::Eq | Op::Ne) {
+                return Err(format!("{} can't be compared with < or >", field.name));
+            }
+            params.push(Value::Text(schema.entity.to_string()));
+            params.extend(term.values.iter().map(|v| Value::Text(v.trim_start_matches('#').to_lowercase())));
+            let not = if term.op == Op::Ne { "NOT " } else { "" };
+            Ok(format!(
+                "{}EXISTS (SELECT 1 FROM tags tg WHERE tg.entity_type = ? AND tg.entity_id = {} AND tg.tag IN ({}))",
+                not,
+                field.column,
+                placeholders(term.values.len()),
+            ))
+        }
+        FieldKind::Bool => {
+            let value = match term.values[0].to_lowercase().as_str() {
+                "true" | "yes" | "1" => true,
+                "false" | "no" | "0" => false,
+                other => return Err(bad(other, "true or false")),
+            };
+            let wanted = if term.op == Op::Ne { !value } else { value };
+            Ok(format!("COALESCE({}, 0) = {}", field.column, if wanted { 1 } else { 0 }))
+        }
+        FieldKind::Date => {
+            if term.values.len() > 1 {
+                return Err(format!("{} takes one date", field.name));
+            }
+            let point = date_value(&term.values[0], zone).ok_or_else(|| bad(&term.values[0], "a date (2025-06-30, today, 7d, +3d)"))?;
+            let column = format!("DATETIME({})", field.column);
+            let sql = match point {
+                // A day-only due date is the same day everywhere, so it's compared as a date;
+                // timestamps against when that day starts and ends on the user's clock
+                DatePoint::Day(day, (start, end)) => {
+                    let timed = match term.op {
+                        Op::Eq => format!("{c} >= '{}' AND {c} < '{}'", start, end, c = column),
+                        Op::Ne => format!("({c} < '{}' OR {c} >= '{}')", start, end, c = column),
+                        Op::Lt => format!("{} < '{}'", column, start),
+                        Op::Le => format!("{} < '{}'", column, end),
+                        Op::Gt => format!("{} >= '{}'", column, end),
+                        Op::Ge => format!("{} >= '{}'", column, start),
+                    };
+                    format!(
+                        "(LENGTH({c}) = 10 AND {c} {} '{}' OR LENGTH({c}) > 10 AND {})",
+                        sql_operator(term.op),
+                        day,
+                        timed,
+                        c = field.column,
+                    )
+                }
+                DatePoint::Moment(moment, is_age) => {
+                    // An age compares the other way round: under seven days old is after seven days ago
+                    let op = match (term.op, is_age) {
+                        (Op::Eq, true) => Op::Gt,
+                        (Op::Lt, true) => Op::Gt,
+                        (Op::Le, true) => Op::Ge,
+                        (Op::Gt, true) => Op::Lt,
+                        (Op::Ge, true) => Op::Le,
+                        (op, _) => op,
+                    };
+                    match op {
+                        Op::Eq => format!("DATE({}) = DATE({})", column, moment),
+                        Op::Ne => format!("DATE({}) != DATE({})", column, moment),
+                        op => format!("{} {} {}", column, sql_operator(op), moment),
+                    }
+                }
+            };
+            Ok(sql)
+        }
+    }
+}
+
+fn sql_operator(op: Op) -> &'static str {
+    match op {
+        Op::Eq => "=",
+        Op::Ne => "!=",
+        Op::Lt => "<",
+        Op::Le => "<=",
+        Op::Gt => ">",
+        Op::Ge => ">=",
+    }
+}
+
+enum DatePoint {
+    Day(String, (String, String)), // YYYY-MM-DD, and when it starts and ends in the zone as stored timestamps
+    Moment(String, bool),          // SQL for the moment, and whether the value was an age
+}
+
+/// A date term's value. Only fixed strings and a number go into the SQL, so nothing from
+/// the expression is spliced in as text.
+fn date_value(value: &str, zone: Zone) -> Option<DatePoint> {
+    let value = value.to_lowercase();
+    let day = match value.as_str() {
+        "now" => return Some(DatePoint::Moment("DATETIME('now')".to_string(), false)),
+        "today" => Some(zone.today()),
+        _ => chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok(),
+    };
+    if let Some(day) = day {
+        let (start, end) = zone.day_bounds(day);
+        return Some(DatePoint::Day(day.format("%Y-%m-%d").to_string(), (timezone::stored(start), timezone::stored(end))));
+    }
+    let (ahead, amount) = match value.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, value.as_str()),
+    };
+    let unit_at = amount.find(|c: char| !c.is_ascii_digit())?;
+    let count: i64 = amount[..unit_at].parse().ok()?;
+    let modifier = match &amount[unit_at..] {
+        "h" => format!("{} hours", count),
+        "d" => format!("{} days", count),
+        "w" => format!("{} days", count * 7),
+        "m" => format!("{} months", count),
+        "y" => format!("{} years", count),
+        _ => return None,
+    };
+    let sign = if ahead { "+" } else { "-" };
+    Some(DatePoint::Moment(format!("DATETIME('now', '{}{}')", sign, modifier), !ahead))
+}
@@ -0,0 +1,88 @@
+// FlowState Highlight Module - Where a search matched, so the frontend can mark it up
+// Offsets count characters (Unicode code points, what Array.from gives in JavaScript), and
+// ranges are [start, end) pairs. Each word of the query is matched on its own, case-insensitively,
+// the way the note index matches; a whole-phrase match is covered by its words.
+
+use serde::Serialize;
+
+const CONTEXT_RADIUS: usize = 60; // Characters kept either side of the first match
+const MAX_WORD_SNAP: usize = 15;  // How far a window edge moves to avoid cutting a word
+
+/// A stretch of a longer text around its first match.
+#[derive(Debug, Serialize, Clone)]
+pub struct Context {
+    pub text: String,
+    pub offset: usize,             // Where `text` starts in the full text
+    pub matches: Vec<[usize; 2]>,  // Relative to `text`
+    pub clipped_start: bool,       // Text was cut before, so the UI can show an ellipsis
+    pub clipped_end: bool,
+}
+
+/// The distinct lowercase words of a query, quotes dropped.
+pub fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split_whitespace() {
+        let word = word.trim_matches('"').to_lowercase();
+        if !word.is_empty() && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Every place a term occurs in `text`, in order, overlapping ranges merged.
+pub fn matches(text: &str, terms: &[String]) -> Vec<[usize; 2]> {
+    let lower: Vec<char> = text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+    let mut ranges: Vec<[usize; 2]> = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term.chars().collect();
+        if term.is_empty() || term.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - term.len() {
+            if lower[start..start + term.len()] == term[..] {
+                ranges.push([start, start + term.len()]);
+            }
+        }
+    }
+    ranges.sort();
+    let mut merged: Vec<[usize; 2]> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range[0] <= last[1] => last[1] = last[1].max(range[1]),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The window of `text` around the first of `ranges`, edges moved to word breaks when one is
+/// close. None when nothing matched.
+pub fn context(text: &str, ranges: &[[usize; 2]]) -> Option<Context> {
+    let first = ranges.first()?;
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut start = first[0].saturating_sub(CONTEXT_RADIUS);
+    if start > 0 {
+        if let Some(space) = (start..(start + MAX_WORD_SNAP).min(first[0])).find(|&i| chars[i].is_whitespace()) {
+            start = space + 1;
+        }
+    }
+    let mut end = (first[1] + CONTEXT_RADIUS).min(chars.len());
+    if end < chars.len() {
+        if let Some(space) = (end.saturating_sub(MAX_WORD_SNAP).max(first[1])..end).rev().find(|&i| chars[i].is_whitespace()) {
+            end = space;
+        }
+    }
+
+    Some(Context {
+        text: chars[start..end].iter().collect(),
+        offset: start,
+        matches: ranges.iter()
+            .filter(|r| r[0] >= start && r[1] <= end)
+            .map(|r| [r[0] - start, r[1] - start])
+            .collect(),
+        clipped_start: start > 0,
+        clipped_end: end < chars.len(),
+    })
+}
@@ -0,0 +1,99 @@
+// FlowState Hosting Module - Creating the sync repository on GitHub or GitLab
+// One call makes an empty repository under the token's account and returns its HTTPS URL. Git
+// pushes there with the same token through an Authorization header given on each invocation
+// (`-c http.<url>.extraHeader`), so the token never ends up in a remote URL, .git/config or
+// the synced folder.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub const PROVIDERS: &[&str] = &["github", "gitlab"];
+pub const GITLAB_URL: &str = "https://gitlab.com";
+const GITHUB_API: &str = "https://api.github.com";
+
+pub struct CreatedRepository {
+    pub clone_url: String, // HTTPS
+    pub web_url: String,
+    pub full_name: String, // owner/name
+}
+
+/// Create an empty repository named `name` on `provider`. `gitlab_url` points at a
+/// self-hosted GitLab instead of gitlab.com.
+pub fn create_repository(
+    provider: &str,
+    token: &str,
+    name: &str,
+    private: bool,
+    gitlab_url: Option<&str>,
+) -> Result<CreatedRepository, String> {
+    match provider {
+        "github" => {
+            let response: Value = ureq::post(&format!("{}/user/repos", GITHUB_API))
+                .set("Authorization", &format!("Bearer {}", token))
+                .set("Accept", "application/vnd.github+json")
+                .set("User-Agent", "FlowState")
+                .timeout(Duration::from_secs(30))
+                .send_json(json!({
+                    "name": name,
+                    "private": private,
+                    "auto_init": false,
+                    "description": "FlowState data",
+                }))
+                .map_err(|e| describe_error("GitHub", e))?
+                .into_json()
+                .map_err(|e| e.to_string())?;
+            repository(&response, "clone_url", "html_url", "full_name")
+        }
+        "gitlab" => {
+            let base_url = gitlab_url.unwrap_or(GITLAB_URL).trim_end_matches('/');
+            let response: Value = ureq::post(&format!("{}/api/v4/projects", base_url))
+                .set("PRIVATE-TOKEN", token)
+                .timeout(Duration::from_secs(30))
+                .send_json(json!({
+                    "name": name,
+                    "visibility": if private { "private" } else { "public" },
+                    "description": "FlowState data",
+                }))
+                .map_err(|e| describe_error("GitLab", e))?
+                .into_json()
+                .map_err(|e| e.to_string())?;
+            repository(&response, "http_url_to_repo", "web_url", "path_with_namespace")
+        }
+        _ => Err(format!("Unknown provider '{}'. Expected one of: {}", provider, PROVIDERS.join(", "))),
+    }
+}
+
+/// The header git sends to authenticate HTTPS pushes and fetches with `token`.
+pub fn git_auth_header(provider: &str, token: &str) -> String {
+    let user = if provider == "gitlab" { "oauth2" } else { "x-access-token" };
+    format!("Authorization: Basic {}", STANDARD.encode(format!("{}:{}", user, token)))
+}
+
+fn repository(response: &Value, clone_key: &str, web_key: &str, name_key: &str) -> Result<CreatedRepository, String> {
+    let field = |key: &str| {
+        response[key].as_str()
+            .map(String::from)
+            .ok_or_else(|| format!("The hosting response did not include {}", key))
+    };
+    Ok(CreatedRepository {
+        clone_url: field(clone_key)?,
+        web_url: field(web_key)?,
+        full_name: field(name_key)?,
+    })
+}
+
+fn describe_error(provider: &str, error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            // GitHub puts details in errors[].message; GitLab's message may be an object of field errors
+            let body = response.into_json::<Value>().unwrap_or_default();
+            let message = body["errors"][0]["message"].as_str()
+                .or_else(|| body["message"].as_str())
+                .map(String::from)
+                .unwrap_or_else(|| body["message"].to_string());
+            format!("{} API error {}: {}", provider, code, message)
+        }
+        ureq::Error::Transport(t) => format!("Failed to reach {}: {}", provider, t),
+    }
+}
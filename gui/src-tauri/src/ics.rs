@@ -0,0 +1,166 @@
+// FlowState ICS Module - Calendar feed for due todos and milestones
+// Produces an RFC 5545 calendar that Apple Calendar and Google Calendar can import.
+// FlowState has no milestone table; solved problems are the project's milestones,
+// the same events StoryMode turns into breakthrough chapters.
+
+use crate::database::{Problem, Todo};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+
+const PRODID: &str = concat!("-//FlowState//FlowState ", env!("CARGO_PKG_VERSION"), "//EN");
+const MAX_LINE_OCTETS: usize = 75;
+
+enum When {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime), // UTC, as stored by SQLite CURRENT_TIMESTAMP
+}
+
+pub struct CalendarFeed {
+    name: String,
+    stamp: String,
+    lines: Vec<String>,
+}
+
+impl CalendarFeed {
+    pub fn new(name: &str) -> Self {
+        CalendarFeed {
+            name: name.to_string(),
+            stamp: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Add a VTODO for a todo with a due date. Todos without one are skipped.
+    pub fn add_todo(&mut self, todo: &Todo, project_name: Option<&str>) {
+        let Some(due) = todo.due_date.as_deref().and_then(parse_when) else { return };
+
+        let status = match todo.status.as_str() {
+            "in_progress" => "IN-PROCESS",
+            "done" => "COMPLETED",
+            "cancelled" => "CANCELLED",
+            _ => "NEEDS-ACTION",
+        };
+        let priority = match todo.priority.as_str() {
+            "critical" => 1,
+            "high" => 3,
+            "low" => 9,
+            _ => 5,
+        };
+
+        self.push("BEGIN:VTODO");
+        self.push(&format!("UID:todo-{}@flowstate", todo.id));
+        self.push(&format!("DTSTAMP:{}", self.stamp));
+        self.push(&format!("SUMMARY:{}", escape_text(&with_project(&todo.title, project_name))));
+        if let Some(description) = todo.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            self.push(&format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        self.push(&format_when("DUE", &due));
+        self.push(&format!("STATUS:{}", status));
+        self.push(&format!("PRIORITY:{}", priority));
+        if let Some(When::DateTime(completed)) = todo.completed_at.as_deref().and_then(parse_when) {
+            self.push(&format!("COMPLETED:{}", completed.format("%Y%m%dT%H%M%SZ")));
+        }
+        self.push("END:VTODO");
+    }
+
+    /// Add an all-day VEVENT on the date a problem was solved. Open problems are skipped.
+    pub fn add_milestone(&mut self, problem: &Problem, project_name: Option<&str>) {
+        let Some(solved) = problem.solved_at.as_deref().and_then(parse_when) else { return };
+        let day = match solved {
+            When::Date(d) => d,
+            When::DateTime(dt) => dt.date(),
+        };
+        let next_day = day.succ_opt().unwrap_or(day);
+        let summary = format!("Solved: {}", problem.title);
+
+        self.push("BEGIN:VEVENT");
+        self.push(&format!("UID:milestone-{}@flowstate", problem.id));
+        self.push(&format!("DTSTAMP:{}", self.stamp));
+        self.push(&format!("SUMMARY:{}", escape_text(&with_project(&summary, project_name))));
+        if let Some(root_cause) = problem.root_cause.as_deref().filter(|r| !r.trim().is_empty()) {
+            self.push(&format!("DESCRIPTION:{}", escape_text(&format!("Root cause: {}", root_cause))));
+        }
+        self.push(&format_when("DTSTART", &When::Date(day)));
+        self.push(&format_when("DTEND", &When::Date(next_day)));
+        self.push(&format!("CATEGORIES:{}", escape_text(&problem.severity)));
+        self.push("TRANSP:TRANSPARENT");
+        self.push("END:VEVENT");
+    }
+
+    pub fn finish(self) -> String {
+        let mut out = String::new();
+        let header = [
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!("PRODID:{}", PRODID),
+            "CALSCALE:GREGORIAN".to_string(),
+            "METHOD:PUBLISH".to_string(),
+            format!("X-WR-CALNAME:{}", escape_text(&self.name)),
+        ];
+        for line in header.iter().chain(self.lines.iter()) {
+            out.push_str(&fold_line(line));
+        }
+        out.push_str("END:VCALENDAR\r\n");
+        out
+    }
+
+    fn push(&mut self, line: &str) {
+        self.lines.push(line.to_string());
+    }
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn with_project(summary: &str, project_name: Option<&str>) -> String {
+    match project_name {
+        Some(project) => format!("[{}] {}", project, summary),
+        None => summary.to_string(),
+    }
+}
+
+/// Accepts the date shapes FlowState stores: "2026-02-01", "2026-02-01 09:30:00",
+/// "2026-02-01T09:30:00" and RFC 3339 strings with an offset.
+fn parse_when(value: &str) -> Option<When> {
+    let value = value.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(When::DateTime(dt.naive_utc()));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(When::DateTime(dt));
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().map(When::Date)
+}
+
+fn format_when(property: &str, when: &When) -> String {
+    match when {
+        When::Date(d) => format!("{};VALUE=DATE:{}", property, d.format("%Y%m%d")),
+        When::DateTime(dt) => format!("{}:{}", property, dt.format("%Y%m%dT%H%M%SZ")),
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold content lines longer than 75 octets without splitting UTF-8 characters.
+fn fold_line(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
@@ -0,0 +1,319 @@
+// FlowState LAN Sync Module - Record changes straight between devices on one network
+// Devices with LAN sync on announce themselves over mDNS (_flowstate._tcp) and push the records
+// they've changed to every peer they find, a few seconds after the change. The peer merges them
+// like a project export, matching records by uuid. It's a shortcut, not a replacement: the git
+// remote stays the source of truth, and deletions only travel through it.
+// Each message is a one-line JSON header, signed with the shared lan_sync_key (HMAC-SHA256)
+// and naming the length and SHA-256 of the batch after it, which is what keeps other machines
+// on the network out: nothing past the few-KB header is read until its signature checks out.
+// Records aren't encrypted on the wire.
+
+use crate::database::ProjectExport;
+use chrono::{DateTime, Utc};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const SERVICE_TYPE: &str = "_flowstate._tcp.local.";
+pub const DEFAULT_PORT: u16 = 47822;
+pub const PUSH_INTERVAL: Duration = Duration::from_secs(3);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_HEADER_BYTES: u64 = 4 * 1024;
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_CONNECTIONS: usize = 4; // Answered at once; more are closed straight away
+const MAX_REPLY_BYTES: u64 = 64 * 1024;
+const MAX_AGE_SECONDS: i64 = 300; // Older batches are refused, so a recorded one can't be replayed later
+
+/// The records one device sends another in one push.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Batch {
+    pub device: String,
+    pub name: Option<String>,
+    pub sent_at: String, // RFC 3339
+    pub exports: Vec<ProjectExport>,
+}
+
+impl Batch {
+    pub fn record_count(&self) -> usize {
+        self.exports.iter().flat_map(|export| export.records.values()).map(Vec::len).sum()
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Peer {
+    pub device: String,
+    pub name: Option<String>,
+    pub address: SocketAddr,
+    pub last_sent: Option<String>, // Database time the last successful push covered changes up to
+    pub last_error: Option<String>,
+}
+
+pub struct LanSync {
+    pub port: u16,
+    daemon: ServiceDaemon,
+    peers: Arc<Mutex<BTreeMap<String, Peer>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl LanSync {
+    /// Listen for batches on `port` on every interface, announce this device and start looking
+    /// for others. `receive` gets each batch that passes the signature check and returns how
+    /// many records it merged.
+    pub fn start<F>(device: &str, name: Option<&str>, port: u16, key: &str, receive: F) -> Result<LanSync, String>
+    where
+        F: Fn(Batch) -> Result<usize, String> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|e| format!("LAN sync couldn't listen on port {}: {}", port, e))?;
+        let daemon = ServiceDaemon::new().map_err(|e| format!("Couldn't start mDNS: {}", e))?;
+        let mut properties = vec![("device", device)];
+        if let Some(name) = name {
+            properties.push(("name", name));
+        }
+        let host = format!("flowstate-{}.local.", device.split('-').next().unwrap_or(device));
+        let service = ServiceInfo::new(SERVICE_TYPE, device, &host, "", port, &properties[..])
+            .map_err(|e| format!("Couldn't announce this device: {}", e))?
+            .enable_addr_auto();
+        daemon.register(service).map_err(|e| format!("Couldn't announce this device: {}", e))?;
+        let events = daemon.browse(SERVICE_TYPE).map_err(|e| format!("Couldn't look for other devices: {}", e))?;
+
+        let lan = LanSync {
+            port,
+            daemon,
+            peers: Arc::new(Mutex::new(BTreeMap::new())),
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+
+        let stopped = lan.stopped.clone();
+        let key = key.to_string();
+        let receive = Arc::new(receive);
+        let active = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Some(slot) = Slot::claim(&active) else { continue };
+                let key = key.clone();
+                let receive = receive.clone();
+                std::thread::spawn(move || {
+                    let _ = answer(stream, &key, receive.as_ref());
+                    drop(slot);
+                });
+            }
+        });
+
+        // The browse channel closes when the daemon shuts down, which ends this thread
+        let peers = lan.peers.clone();
+        let own_device = device.to_string();
+        std::thread::spawn(move || {
+            while let Ok(event) = events.recv() {
+                let Ok(mut peers) = peers.lock() else { break };
+                match event {
+                    ServiceEvent::ServiceResolved(info) => {
+                        let Some(device) = info.get_property_val_str("device") else { continue };
+                        let Some(ip) = pick_address(info.get_addresses()) else { continue };
+                        if device == own_device {
+                            continue;
+                        }
+                        let address = SocketAddr::new(ip, info.get_port());
+                        let name = info.get_property_val_str("name").map(String::from);
+                        let peer = peers.entry(device.to_string()).or_insert_with(|| Peer {
+                            device: device.to_string(),
+                            name: None,
+                            address,
+                            last_sent: None,
+                            last_error: None,
+                        });
+                        peer.address = address;
+                        peer.name = name;
+                    }
+                    ServiceEvent::ServiceRemoved(_, fullname) => {
+                        if let Some(device) = fullname.strip_suffix(&format!(".{}", SERVICE_TYPE)) {
+                            peers.remove(device);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        Ok(lan)
+    }
+
+    pub fn peers(&self) -> Vec<Peer> {
+        self.peers.lock().map(|peers| peers.values().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Change what's kept about a peer, if it's still around.
+    pub fn update_peer<F: FnOnce(&mut Peer)>(&self, device: &str, update: F) {
+        if let Ok(mut peers) = self.peers.lock() {
+            if let Some(peer) = peers.get_mut(device) {
+                update(peer);
+            }
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Stop announcing and listening. The listener only notices on its next connection, so
+    /// this makes one.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = self.daemon.shutdown();
+        let _ = TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], self.port)), CONNECT_TIMEOUT);
+    }
+}
+
+/// One of the `MAX_CONNECTIONS` connections answered at once, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn claim(active: &Arc<AtomicUsize>) -> Option<Slot> {
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Slot(active.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// What a header's signature covers: the batch's length, send time and digest.
+fn header_message(length: u64, sent_at: &str, digest: &str) -> String {
+    format!("{}\n{}\n{}", length, sent_at, digest)
+}
+
+/// Send `batch` to the peer at `address`. Returns how many records it merged.
+pub fn send(address: SocketAddr, key: &str, batch: &Batch) -> Result<usize, String> {
+    let payload = serde_json::to_vec(batch).map_err(|e| e.to_string())?;
+    let length = payload.len() as u64;
+    let digest = format!("{:x}", Sha256::digest(&payload));
+    let header = json!({
+        "length": length,
+        "sent_at": batch.sent_at,
+        "digest": digest,
+        "signature": sign(key, header_message(length, &batch.sent_at, &digest).as_bytes()),
+    });
+
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| format!("Couldn't reach {}: {}", address, e))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    writeln!(stream, "{}", header)
+        .and_then(|_| stream.write_all(&payload))
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("Couldn't send to {}: {}", address, e))?;
+
+    let mut reply = String::new();
+    BufReader::new(stream.take(MAX_REPLY_BYTES))
+        .read_line(&mut reply)
+        .map_err(|e| format!("No reply from {}: {}", address, e))?;
+    let reply: Value = serde_json::from_str(&reply).map_err(|_| format!("Unreadable reply from {}", address))?;
+    match reply["error"].as_str() {
+        Some(error) => Err(format!("{} refused the changes: {}", address, error)),
+        None => Ok(reply["merged"].as_u64().unwrap_or(0) as usize),
+    }
+}
+
+fn answer<F: Fn(Batch) -> Result<usize, String>>(stream: TcpStream, key: &str, receive: &F) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    (&mut reader).take(MAX_HEADER_BYTES).read_line(&mut line)?;
+    if line.trim().is_empty() {
+        return Ok(()); // A wake-up from `stop`, or a port scan
+    }
+    // Only a signed header gets the batch after it read
+    let batch = verify_header(&line, key).and_then(|header| {
+        let mut payload = vec![0; header.length as usize];
+        reader.read_exact(&mut payload).map_err(|e| format!("Incomplete batch: {}", e))?;
+        verify_payload(&header, &payload)
+    });
+    let reply = match batch.and_then(receive) {
+        Ok(merged) => json!({ "merged": merged }),
+        Err(e) => json!({ "error": e }),
+    };
+    let mut stream = &stream;
+    writeln!(stream, "{}", reply)?;
+    stream.flush()
+}
+
+struct Header {
+    length: u64,
+    sent_at: String,
+    digest: String,
+}
+
+/// The header of a message, if it's signed with `key`, recent, and announces a batch of an
+/// acceptable size.
+fn verify_header(line: &str, key: &str) -> Result<Header, String> {
+    let header: Value = serde_json::from_str(line).map_err(|_| "Malformed message".to_string())?;
+    let length = header["length"].as_u64().ok_or("Malformed message")?;
+    let sent_at = header["sent_at"].as_str().ok_or("Malformed message")?;
+    let digest = header["digest"].as_str().ok_or("Malformed message")?;
+    let signature = header["signature"].as_str().unwrap_or_default();
+    if !same(signature.as_bytes(), sign(key, header_message(length, sent_at, digest).as_bytes()).as_bytes()) {
+        return Err("Wrong LAN sync key".to_string());
+    }
+    let sent = DateTime::parse_from_rfc3339(sent_at).map_err(|_| "Batch has no send time".to_string())?;
+    if (Utc::now() - sent.with_timezone(&Utc)).num_seconds().abs() > MAX_AGE_SECONDS {
+        return Err("Batch is too old, or the two clocks disagree".to_string());
+    }
+    if length > MAX_MESSAGE_BYTES {
+        return Err("Batch is too large".to_string());
+    }
+    Ok(Header { length, sent_at: sent_at.to_string(), digest: digest.to_string() })
+}
+
+/// The batch a verified header announced, if `payload` is it.
+fn verify_payload(header: &Header, payload: &[u8]) -> Result<Batch, String> {
+    if format!("{:x}", Sha256::digest(payload)) != header.digest {
+        return Err("Batch doesn't match its header".to_string());
+    }
+    let batch: Batch = serde_json::from_slice(payload).map_err(|e| format!("Unreadable batch: {}", e))?;
+    if batch.sent_at != header.sent_at {
+        return Err("Batch doesn't match its header".to_string());
+    }
+    Ok(batch)
+}
+
+/// HMAC-SHA256 of `message` under `key`, in hex.
+pub fn sign(key: &str, message: &[u8]) -> String {
+    const BLOCK: usize = 64;
+    let mut key_block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        key_block[..32].copy_from_slice(&Sha256::digest(key.as_bytes()));
+    } else {
+        key_block[..key.len()].copy_from_slice(key.as_bytes());
+    }
+    let padded = |byte: u8| key_block.iter().map(|k| k ^ byte).collect::<Vec<u8>>();
+    let inner = Sha256::new().chain_update(padded(0x36)).chain_update(message).finalize();
+    let outer = Sha256::new().chain_update(padded(0x5c)).chain_update(inner).finalize();
+    format!("{:x}", outer)
+}
+
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// An IPv4 address when the peer has one; they're the likelier to be reachable on a home LAN.
+fn pick_address(addresses: &HashSet<IpAddr>) -> Option<IpAddr> {
+    addresses.iter().find(|ip| ip.is_ipv4()).or_else(|| addresses.iter().next()).copied()
+}
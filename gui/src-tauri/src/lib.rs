@@ -1,16 +1,102 @@
 // FlowState - Tauri Application Entry Point
 // v1.1: Complete implementation with file handling, Git sync, and settings commands
+// v1.4: Mobile builds register a reduced command set, so desktop-only commands sit unused there
+#![cfg_attr(mobile, allow(dead_code))]
+// Tauri commands take their arguments flat, one per field the frontend sends
+#![allow(clippy::too_many_arguments)]
 
+mod automation;
+mod categorize;
+mod changelog;
+mod ci;
+mod commitlog;
+mod componentpaths;
+mod confluence;
+mod context;
+mod corpus;
+mod contradictions;
 mod database;
+mod dependencies;
+mod descriptions;
+mod dirwatch;
+mod embeddings;
+mod eml;
+mod filters;
+mod highlight;
+mod hosting;
+mod ics;
+mod lansync;
+mod llm;
+mod localapi;
+mod locks;
+mod logparse;
+mod mailer;
+mod network;
+mod notion;
+mod pdf;
+mod platform;
+mod preview;
+mod quickadd;
+mod quickopen;
+mod query;
+mod reports;
+mod reposcan;
+mod secrets;
+mod sections;
+mod sentry;
+mod shellhist;
+mod shortcuts;
+mod site;
+mod story;
+mod streaming;
+mod symbols;
+mod syncjob;
+mod templates;
+mod testresults;
+mod themes;
+mod timezone;
+mod updater;
+mod webref;
 
 use database::{Database, get_default_db_path};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Mutex;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tauri::{
-    menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder, PredefinedMenuItem},
-    State, Manager, Emitter,
-};
+use tauri::{State, Manager, Emitter};
+#[cfg(desktop)]
+use tauri::menu::{Menu, MenuBuilder, MenuItem, MenuItemBuilder, SubmenuBuilder, PredefinedMenuItem};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_opener::OpenerExt;
+
+const MENU_RECENT_LIMIT: usize = 10;
+const MENU_PROJECT_LIMIT: usize = 20;
+const RECENT_ITEMS_LIMIT: usize = 20;
+const QUICK_OPEN_LIMIT: usize = 30;
+const CHANGE_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const DIRECTORY_SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+const CLIPBOARD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+const NETWORK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+const REPORT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+const ANOMALY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const CI_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+const STATS_SNAPSHOT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const JOB_CANCELLED: &str = "Job cancelled";
+const STATS_HISTORY_DAYS: i32 = 90; // How far back a stats history goes without a range
+const FILTERED_SEARCH_LIMIT: i32 = 1000; // Results of each type a filtered search narrows down to its limit
+const MAX_STORY_IMAGES: usize = 24;
+const CORPUS_TOKENS_PER_FILE: usize = 800;
+const MIN_CORPUS_TOKENS_PER_FILE: usize = 100; // Room for the heading each file repeats
+const MAX_CORPUS_FILE_BYTES: i64 = 5 * 1024 * 1024; // Larger text attachments are described, not copied
+const MAX_STORY_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_CAPTURE_MIN_CHARS: usize = 40;
+const MAX_CAPTURE_CHARS: usize = 20_000;
+const SYNC_IGNORE_START: &str = "# FlowState: kept out of sync (managed; change it in project and attachment settings)";
+const SYNC_IGNORE_END: &str = "# FlowState: end of sync exclusions";
+const ATTACHMENT_SYNC_POLICIES: &[&str] = &["full", "metadata", "never"];
+const ATTACHMENTS_BRANCH: &str = "flowstate-attachments";
+const STALE_INDEX_LOCK: std::time::Duration = std::time::Duration::from_secs(10 * 60); // No git holds the index this long
 
 // ============================================================
 // APP STATE
@@ -18,6 +104,48 @@ use tauri::{
 
 struct AppState {
     db: Mutex<Database>,
+    change_queue: Mutex<Vec<database::NewChange>>, // v1.4: coalesced change writes
+    results: Mutex<streaming::ResultStore>,        // v1.4: large results handed over in chunks
+    // v1.4: Modification times seen by each directory watch, so unchanged files aren't rehashed
+    scanned_files: Mutex<HashMap<i64, HashMap<PathBuf, std::time::SystemTime>>>,
+    clipboard_capture: Mutex<Option<ClipboardCapture>>, // v1.4: on only until stopped or the app quits
+    local_api_port: Mutex<Option<u16>>, // v1.4: set once the local API is listening
+    sync_job: Mutex<Option<std::sync::Arc<syncjob::Control>>>, // v1.4: the sync in progress, for cancelling
+    lan_sync: Mutex<Option<std::sync::Arc<lansync::LanSync>>>, // v1.4: set while LAN sync is running
+    api_limiter: localapi::RateLimiter, // v1.4: per-caller request budget of the local API
+}
+
+struct ClipboardCapture {
+    project_id: i64,
+    min_chars: usize,
+    last_text: Option<String>, // What was on the clipboard at the last poll
+}
+
+// ============================================================
+// RECORD IDS
+// ============================================================
+
+/// v1.4: Commands reading, updating or deleting one record take its numeric id or its uuid.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RecordId {
+    Id(i64),
+    Uuid(String),
+}
+
+impl RecordId {
+    fn resolve(self, db: &Database, table: &str) -> Result<i64, String> {
+        match self {
+            RecordId::Id(id) => Ok(id),
+            // Ids sometimes arrive as strings from URLs and deep links
+            RecordId::Uuid(text) => match text.trim().parse::<i64>() {
+                Ok(id) => Ok(id),
+                Err(_) => db.find_by_uuid(table, &text)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("No record in {} with uuid {}", table, text)),
+            },
+        }
+    }
 }
 
 // ============================================================
@@ -25,71 +153,309 @@ struct AppState {
 // ============================================================
 
 #[tauri::command]
-fn list_projects(state: State<AppState>, status: Option<String>) -> Result<Vec<database::Project>, String> {
+fn list_projects(state: State<AppState>, status: Option<String>, sort: Option<String>) -> Result<Vec<database::Project>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.list_projects(status.as_deref()).map_err(|e| e.to_string())
+    db.list_projects(status.as_deref(), sort.as_deref()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn create_project(state: State<AppState>, name: String, description: Option<String>) -> Result<database::Project, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_project(&name, description.as_deref()).map_err(|e| e.to_string())
+fn create_project(app: tauri::AppHandle, state: State<AppState>, name: String, description: Option<String>) -> Result<database::Project, String> {
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.create_project(&name, description.as_deref()).map_err(|e| e.to_string())?
+    };
+    let _ = rebuild_menu(&app);
+    Ok(project)
 }
 
 #[tauri::command]
-fn get_project(state: State<AppState>, id: i64) -> Result<database::Project, String> {
+fn get_project(state: State<AppState>, id: RecordId) -> Result<database::Project, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "projects")?;
     db.get_project(id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 fn update_project(
+    app: tauri::AppHandle,
     state: State<AppState>,
-    id: i64,
+    id: RecordId,
     name: Option<String>,
     description: Option<String>,
     status: Option<String>
 ) -> Result<database::Project, String> {
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let id = id.resolve(&db, "projects")?;
+        check_lock(&app, &db, "project", id)?;
+        db.update_project(id, name.as_deref(), description.as_deref(), status.as_deref())
+            .map_err(|e| e.to_string())?
+    };
+    // v1.4: Names and statuses show up in the native menu
+    let _ = rebuild_menu(&app);
+    Ok(project)
+}
+
+/// v1.4: Rename a project. A name another project already has, in any case, is refused with a
+/// "Conflict:" error; changing only the case of its own name is fine.
+#[tauri::command]
+fn rename_project(app: tauri::AppHandle, state: State<AppState>, id: RecordId, name: String) -> Result<database::Project, String> {
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let id = id.resolve(&db, "projects")?;
+        check_lock(&app, &db, "project", id)?;
+        db.rename_project(id, name.trim()).map_err(|e| e.to_string())?
+    };
+    let _ = rebuild_menu(&app);
+    Ok(project)
+}
+
+#[tauri::command]
+fn delete_project(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let id = id.resolve(&db, "projects")?;
+        check_lock(&app, &db, "project", id)?;
+        db.delete_project(id).map_err(|e| e.to_string())?;
+    }
+    let _ = rebuild_menu(&app);
+    Ok(())
+}
+
+/// v1.4: Color is '#RGB' or '#RRGGBB'; icon is an emoji or icon name. Empty strings clear them.
+#[tauri::command]
+fn set_project_appearance(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: i64,
+    color: Option<String>,
+    icon: Option<String>
+) -> Result<database::Project, String> {
+    let color = color.map(|c| c.trim().to_lowercase());
+    if let Some(c) = color.as_deref().filter(|c| !c.is_empty()) {
+        let hex = c.strip_prefix('#').unwrap_or("");
+        if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(format!("'{}' is not a hex color like #3b82f6", c));
+        }
+    }
+    let icon = icon.map(|i| i.trim().to_string());
+    if icon.as_ref().is_some_and(|i| i.chars().count() > 32) {
+        return Err("Icon must be an emoji or a short icon name".to_string());
+    }
+    
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_project_appearance(id, color.as_deref(), icon.as_deref()).map_err(|e| e.to_string())?
+    };
+    let _ = rebuild_menu(&app);
+    Ok(project)
+}
+
+#[tauri::command]
+fn pin_project(app: tauri::AppHandle, state: State<AppState>, id: i64, pinned: bool) -> Result<database::Project, String> {
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_project_pinned(id, pinned).map_err(|e| e.to_string())?
+    };
+    let _ = rebuild_menu(&app);
+    Ok(project)
+}
+
+/// v1.4: Keep a project out of sync, or let it back in. Its folder goes in the .gitignore
+/// right away so `git_status` reflects it, and LAN sync leaves its records out. Git sync
+/// commits flowstate.db whole, records and all, so it refuses to run while any project is
+/// kept out rather than push them.
+#[tauri::command]
+fn set_project_sync(state: State<AppState>, project_id: i64, enabled: bool) -> Result<database::Project, String> {
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_project_sync_excluded(project_id, !enabled).map_err(|e| e.to_string())?
+    };
+    refresh_sync_ignore(&state)?;
+    Ok(project)
+}
+
+/// v1.4: How a project's attachments sync unless one says otherwise: "full" (with the rest of
+/// the data), "metadata" (the record syncs, the file is fetched on demand) or "never".
+#[tauri::command]
+fn set_project_attachment_sync(state: State<AppState>, project_id: i64, policy: String) -> Result<database::Project, String> {
+    check_attachment_sync_policy(&policy)?;
+    let project = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_project_attachment_sync(project_id, &policy).map_err(|e| e.to_string())?
+    };
+    refresh_sync_ignore(&state)?;
+    Ok(project)
+}
+
+#[tauri::command]
+fn reorder_projects(app: tauri::AppHandle, state: State<AppState>, ordered_ids: Vec<i64>) -> Result<Vec<database::Project>, String> {
+    let projects = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.reorder_projects(&ordered_ids).map_err(|e| e.to_string())?;
+        db.list_projects(None, None).map_err(|e| e.to_string())?
+    };
+    let _ = rebuild_menu(&app);
+    Ok(projects)
+}
+
+/// v1.4: Called by the UI whenever a project is opened; feeds File > Open Recent.
+#[tauri::command]
+fn record_project_opened(app: tauri::AppHandle, state: State<AppState>, project_id: i64) -> Result<(), String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.record_project_opened(project_id).map_err(|e| e.to_string())?;
+    }
+    rebuild_menu(&app)
+}
+
+#[tauri::command]
+fn get_recent_projects(state: State<AppState>, limit: Option<usize>) -> Result<Vec<database::Project>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_project(id, name.as_deref(), description.as_deref(), status.as_deref())
-        .map_err(|e| e.to_string())
+    db.get_recent_projects(limit.unwrap_or(MENU_RECENT_LIMIT)).map_err(|e| e.to_string())
+}
+
+/// v1.4: Called by the UI whenever a record is opened; feeds the command palette's ranking.
+#[tauri::command]
+fn record_view(state: State<AppState>, entity: String, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, &format!("{}s", entity))?;
+    db.record_view(&entity, id).map_err(|e| e.to_string())
 }
 
+/// v1.4: What's been opened most, and most lately, for "jump back in".
 #[tauri::command]
-fn delete_project(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_recent_items(state: State<AppState>, project_id: Option<i64>, limit: Option<usize>) -> Result<Vec<database::RecentItem>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_project(id).map_err(|e| e.to_string())
+    db.get_recent_items(project_id, limit.unwrap_or(RECENT_ITEMS_LIMIT)).map_err(|e| e.to_string())
 }
 
+/// v1.4: `fields` picks sections (components, problems, changes, todos, learnings, notes,
+/// references, ci, starred, attachments) and `limits` caps them; sections left out aren't
+/// loaded at all.
 #[tauri::command]
-fn get_project_context(state: State<AppState>, project_name: String, hours: Option<i32>, include_files: Option<bool>) -> Result<serde_json::Value, String> {
+fn get_project_context(
+    state: State<AppState>,
+    project_name: String,
+    hours: Option<i32>,
+    include_files: Option<bool>,
+    fields: Option<Vec<String>>,
+    limits: Option<HashMap<String, usize>>
+) -> Result<serde_json::Value, String> {
+    let selection = context::Selection::new(fields, limits, include_files.unwrap_or(true))?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    build_project_context(&db, &project_name, hours.unwrap_or(48), &selection)
+}
+
+/// v1.4: Contexts for several projects merged into one, each item labelled with its project.
+/// Learnings shared between projects appear once; `token_budget` trims the least important sections.
+#[tauri::command]
+fn get_multi_project_context(
+    state: State<AppState>,
+    project_names: Vec<String>,
+    hours: Option<i32>,
+    token_budget: Option<usize>
+) -> Result<serde_json::Value, String> {
+    if project_names.is_empty() {
+        return Err("No projects given".to_string());
+    }
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let hours = hours.unwrap_or(48);
-    let include_files = include_files.unwrap_or(true);
-    
-    let project = db.get_project_by_name(&project_name).map_err(|e| e.to_string())?;
-    let components = db.list_components(project.id).map_err(|e| e.to_string())?;
-    let open_problems = db.get_open_problems(Some(project.id), None).map_err(|e| e.to_string())?;
-    let recent_changes = db.get_recent_changes(Some(project.id), None, hours).map_err(|e| e.to_string())?;
-    let high_priority_todos = db.get_todos(project.id, None, None).map_err(|e| e.to_string())?;
-    let recent_learnings = db.get_learnings(Some(project.id), None, false).map_err(|e| e.to_string())?;
-    
-    // v1.1: Include attachments if requested
-    let attachments = if include_files {
-        db.get_attachments(project.id, None, None).map_err(|e| e.to_string())?
+    
+    let mut contexts = Vec::new();
+    for (i, name) in project_names.iter().enumerate() {
+        if project_names[..i].contains(name) {
+            continue;
+        }
+        contexts.push(build_project_context(&db, name, hours, &context::Selection::all(false))?);
+    }
+    Ok(context::merge(&contexts, token_budget))
+}
+
+fn build_project_context(db: &Database, project_name: &str, hours: i32, selection: &context::Selection) -> Result<serde_json::Value, String> {
+    let project = db.get_project_by_name(project_name).map_err(|e| e.to_string())?;
+    let mut context = serde_json::Map::new();
+    context.insert("project".to_string(), serde_json::json!(project));
+    
+    // v1.4: Starred items lead each list (stable sort keeps the existing order otherwise)
+    let starred = if ["starred", "problems", "todos", "learnings"].iter().any(|f| selection.wants(f)) {
+        db.list_starred(Some(project.id)).map_err(|e| e.to_string())?
     } else {
         Vec::new()
     };
+    let is_starred = |entity: &str, id: i64| starred.iter().any(|s| s.entity_type == entity && s.entity_id == id);
     
-    Ok(serde_json::json!({
-        "project": project,
-        "components": components,
-        "open_problems": open_problems,
-        "recent_changes": recent_changes,
-        "high_priority_todos": high_priority_todos,
-        "recent_learnings": recent_learnings,
-        "attachments": attachments,
-    }))
+    if selection.wants("components") {
+        let components = db.list_components(project.id).map_err(|e| e.to_string())?;
+        context.insert("components".to_string(), serde_json::json!(selection.limit("components", components)));
+    }
+    if selection.wants("problems") {
+        let mut open_problems = db.get_open_problems(Some(project.id), None, None).map_err(|e| e.to_string())?;
+        open_problems.sort_by_key(|p| !is_starred("problem", p.id));
+        context.insert("open_problems".to_string(), serde_json::json!(selection.limit("problems", open_problems)));
+    }
+    if selection.wants("changes") {
+        let recent_changes = db.get_recent_changes(Some(project.id), None, &timezone::hours_ago(hours.into()), None, None).map_err(|e| e.to_string())?;
+        context.insert("recent_changes".to_string(), serde_json::json!(selection.limit("changes", recent_changes)));
+    }
+    if selection.wants("todos") {
+        let mut high_priority_todos = db.get_todos(project.id, None, None, None, None).map_err(|e| e.to_string())?;
+        high_priority_todos.sort_by_key(|t| !is_starred("todo", t.id));
+        context.insert("high_priority_todos".to_string(), serde_json::json!(selection.limit("todos", high_priority_todos)));
+    }
+    if selection.wants("learnings") {
+        let mut recent_learnings = db.get_learnings(Some(project.id), None, false, None).map_err(|e| e.to_string())?;
+        recent_learnings.sort_by_key(|l| !is_starred("learning", l.id));
+        context.insert("recent_learnings".to_string(), serde_json::json!(selection.limit("learnings", recent_learnings)));
+    }
+    if selection.wants("notes") {
+        // v1.4: Pinned notes plus journal entries from the same window as recent changes
+        let notes = db.get_context_notes(project.id, (hours + 23) / 24).map_err(|e| e.to_string())?;
+        context.insert("notes".to_string(), serde_json::json!(selection.limit("notes", notes)));
+    }
+    if selection.wants("references") {
+        let references = db.get_references(project.id, None, None).map_err(|e| e.to_string())?;
+        context.insert("references".to_string(), serde_json::json!(selection.limit("references", references)));
+    }
+    if selection.wants("ci") {
+        // v1.4: "CI red since change X" belongs next to the changes that may have caused it
+        let ci_status = db.get_project_ci_status(project.id).map_err(|e| e.to_string())?;
+        context.insert("ci_status".to_string(), serde_json::json!(selection.limit("ci", ci_status)));
+    }
+    if selection.wants("attachments") {
+        // v1.1: Attachments are the heaviest section, so only views that show them ask
+        let attachments = db.get_attachments(project.id, None, None, None).map_err(|e| e.to_string())?;
+        context.insert("attachments".to_string(), serde_json::json!(selection.limit("attachments", attachments)));
+    }
+    if selection.wants("starred") {
+        context.insert("starred".to_string(), serde_json::json!(selection.limit("starred", starred)));
+    }
+    
+    Ok(serde_json::Value::Object(context))
+}
+
+/// v1.4: What changed since a timestamp or a session. With neither, since the end of the
+/// project's last finished session, falling back to the last 24 hours.
+#[tauri::command]
+fn get_context_delta(
+    state: State<AppState>,
+    project_id: i64,
+    since_timestamp: Option<String>,
+    since_session_id: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let since = match (since_timestamp, since_session_id) {
+        (Some(ts), _) => ts,
+        (None, Some(session_id)) => {
+            let session = db.get_session(session_id).map_err(|e| e.to_string())?;
+            session.ended_at.unwrap_or(session.started_at)
+        }
+        (None, None) => db.get_sessions_list(project_id, Some(20)).map_err(|e| e.to_string())?
+            .into_iter()
+            .find_map(|s| s.ended_at)
+            .unwrap_or_else(|| (chrono::Utc::now() - chrono::Duration::hours(24)).format("%Y-%m-%d %H:%M:%S").to_string()),
+    };
+    db.get_context_delta(project_id, &since).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -98,6 +464,60 @@ fn get_project_stats(state: State<AppState>, project_id: i64) -> Result<serde_js
     db.get_project_stats(project_id).map_err(|e| e.to_string())
 }
 
+/// v1.4: Rebuild the dashboard counters from the tables and return the fresh stats.
+#[tauri::command]
+fn recompute_stats(state: State<AppState>, project_id: i64) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.recompute_stats(project_id).map_err(|e| e.to_string())?;
+    db.get_project_stats(project_id).map_err(|e| e.to_string())
+}
+
+/// v1.4: One of a project's daily stats (database::STATS_HISTORY_METRICS) over a named
+/// `range`, else `since`/`until`, else the last 90 days, for charting how it trends.
+#[tauri::command]
+fn get_stats_history(
+    state: State<AppState>,
+    project_id: i64,
+    metric: String,
+    range: Option<String>,
+    since: Option<String>,
+    until: Option<String>
+) -> Result<Vec<database::StatsPoint>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let zone = db.timezone();
+    let (since, until) = time_window(&db, range.as_deref(), since.as_deref(), until.as_deref(), STATS_HISTORY_DAYS * 24)?;
+    // Named ranges end at the first moment after them, which is already the next day
+    let last_day = until.map(|until| zone.localize(until - chrono::Duration::seconds(1)).date_naive()).unwrap_or_else(|| zone.today());
+    let first_day = zone.localize(since).date_naive();
+    db.get_stats_history(project_id, metric.trim(), &first_day.to_string(), &last_day.to_string()).map_err(|e| e.to_string())
+}
+
+/// v1.4: Take today's stats snapshot now instead of waiting for the nightly one; it replaces
+/// any taken earlier today. Returns how many projects were snapshotted.
+#[tauri::command]
+fn snapshot_stats(state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.snapshot_stats(&db.timezone().today().to_string()).map_err(|e| e.to_string())
+}
+
+/// The nightly stats snapshot: the first check of each day on the user's clock records it.
+fn snapshot_stats_daily(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let today = db.timezone().today().to_string();
+    if !db.has_stats_snapshot(&today).map_err(|e| e.to_string())? {
+        db.snapshot_stats(&today).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// v1.4: Find rows linking to deleted records, and with `repair` delete or unlink them.
+#[tauri::command]
+fn check_referential_integrity(state: State<AppState>, repair: Option<bool>) -> Result<database::IntegrityReport, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.check_referential_integrity(repair.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
 // ============================================================
 // COMPONENT COMMANDS
 // ============================================================
@@ -122,1311 +542,7959 @@ fn create_component(
 }
 
 #[tauri::command]
-fn get_component(state: State<AppState>, id: i64) -> Result<database::Component, String> {
+fn get_component(state: State<AppState>, id: RecordId) -> Result<database::Component, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "components")?;
     db.get_component(id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 fn update_component(
+    app: tauri::AppHandle,
     state: State<AppState>,
-    id: i64,
+    id: RecordId,
     name: Option<String>,
     description: Option<String>,
     status: Option<String>
 ) -> Result<database::Component, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "components")?;
+    check_lock(&app, &db, "component", id)?;
     db.update_component(id, name.as_deref(), description.as_deref(), status.as_deref())
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_component(state: State<AppState>, id: i64) -> Result<(), String> {
+fn delete_component(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "components")?;
+    check_lock(&app, &db, "component", id)?;
     db.delete_component(id).map_err(|e| e.to_string())
 }
 
 // ============================================================
-// CHANGE COMMANDS
+// v1.4: COMPONENT IMPORT COMMANDS
 // ============================================================
 
+/// v1.4: Propose components for a project from a repository's layout: Cargo and npm workspace
+/// members and the folders under src/. Suggestions matching a component the project already
+/// has say so. Nothing is created until create_components_from_repo.
 #[tauri::command]
-fn log_change(
+fn suggest_components_from_repo(
     state: State<AppState>,
-    component_id: i64,
-    field_name: String,
-    old_value: Option<String>,
-    new_value: Option<String>,
-    change_type: Option<String>,
-    reason: Option<String>
-) -> Result<database::Change, String> {
+    project_id: i64,
+    repo_path: String
+) -> Result<Vec<reposcan::SuggestedComponent>, String> {
+    let mut suggestions = reposcan::suggest(Path::new(&repo_path))?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let change_type = change_type.unwrap_or_else(|| "code".to_string());
-    db.log_change(component_id, &field_name, old_value.as_deref(), new_value.as_deref(), &change_type, reason.as_deref())
-        .map_err(|e| e.to_string())
+    let components = db.list_components(project_id).map_err(|e| e.to_string())?;
+    let mut pending: Vec<&mut reposcan::SuggestedComponent> = suggestions.iter_mut().collect();
+    while let Some(suggestion) = pending.pop() {
+        suggestion.existing_component_id = components.iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&suggestion.name))
+            .map(|c| c.id);
+        pending.extend(suggestion.children.iter_mut());
+    }
+    Ok(suggestions)
 }
 
+/// v1.4: Create the suggestions the user kept (edited or not), with a path mapping for each
+/// so later changes under it are attributed to the component.
 #[tauri::command]
-fn get_recent_changes(
+fn create_components_from_repo(
     state: State<AppState>,
-    project_id: Option<i64>,
-    component_id: Option<i64>,
-    hours: Option<i32>
-) -> Result<Vec<database::Change>, String> {
+    project_id: i64,
+    repo_path: String,
+    components: Vec<reposcan::SuggestedComponent>
+) -> Result<Vec<database::Component>, String> {
+    if !Path::new(&repo_path).is_dir() {
+        return Err(format!("{} is not a directory", repo_path));
+    }
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let hours = hours.unwrap_or(24);
-    db.get_recent_changes(project_id, component_id, hours).map_err(|e| e.to_string())
+    db.import_repo_components(project_id, &repo_path, &components).map_err(|e| e.to_string())
 }
 
+// ============================================================
+// v1.4: COMPONENT PATH COMMANDS
+// ============================================================
+
+/// v1.4: Where the project's code is checked out; component paths are relative to it.
 #[tauri::command]
-fn get_all_changes(
-    state: State<AppState>,
-    project_id: Option<i64>,
-    component_id: Option<i64>
-) -> Result<Vec<database::Change>, String> {
+fn set_project_repo_path(state: State<AppState>, project_id: i64, repo_path: Option<String>) -> Result<database::Project, String> {
+    let repo_path = repo_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &repo_path {
+        if !Path::new(path).is_dir() {
+            return Err(format!("{} is not a directory", path));
+        }
+    }
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_changes(project_id, component_id).map_err(|e| e.to_string())
+    db.set_project_repo_path(project_id, repo_path.as_deref()).map_err(|e| e.to_string())
 }
 
-// ============================================================
-// PROBLEM COMMANDS
-// ============================================================
+#[tauri::command]
+fn get_component_paths(state: State<AppState>, project_id: i64) -> Result<Vec<database::ComponentPath>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_component_paths(project_id).map_err(|e| e.to_string())
+}
 
+/// `pattern` is a glob relative to the repository (`src/auth/**`); a trailing `/` covers the
+/// whole folder.
 #[tauri::command]
-fn log_problem(
-    state: State<AppState>,
-    component_id: i64,
-    title: String,
-    description: Option<String>,
-    severity: Option<String>
-) -> Result<database::Problem, String> {
+fn add_component_path(state: State<AppState>, component_id: RecordId, pattern: String) -> Result<database::ComponentPath, String> {
+    let pattern = componentpaths::clean_pattern(&pattern)?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let severity = severity.unwrap_or_else(|| "medium".to_string());
-    db.log_problem(component_id, &title, description.as_deref(), &severity)
-        .map_err(|e| e.to_string())
+    let component_id = component_id.resolve(&db, "components")?;
+    db.add_component_path(component_id, &pattern).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_problem(state: State<AppState>, id: i64) -> Result<database::Problem, String> {
+fn delete_component_path(state: State<AppState>, id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_problem(id).map_err(|e| e.to_string())
+    let id = id.resolve(&db, "component_paths")?;
+    db.delete_component_path(id).map_err(|e| e.to_string())
 }
 
+/// The component a file belongs to by the project's component paths, if any. `path` is
+/// absolute or relative to the repository.
 #[tauri::command]
-fn get_open_problems(
-    state: State<AppState>,
-    project_id: Option<i64>,
-    component_id: Option<i64>
-) -> Result<Vec<database::Problem>, String> {
+fn get_component_for_path(state: State<AppState>, project_id: i64, path: String) -> Result<Option<database::Component>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_open_problems(project_id, component_id).map_err(|e| e.to_string())
+    let attribution = db.component_attribution(project_id).map_err(|e| e.to_string())?;
+    attribution.component_for(&path)
+        .map(|id| db.get_component(id))
+        .transpose()
+        .map_err(|e| e.to_string())
 }
 
+/// v1.4: Import the repository's commits as changes, each filed under the components whose
+/// paths it touched. `since` is anything `git log --since` takes ("2 weeks ago",
+/// "2025-06-01"); commits imported before are skipped, so running it again is safe.
 #[tauri::command]
-fn get_all_problems(
+fn import_commits(
     state: State<AppState>,
-    project_id: Option<i64>,
-    component_id: Option<i64>
-) -> Result<Vec<database::Problem>, String> {
+    project_id: i64,
+    since: Option<String>,
+    max_count: Option<u32>
+) -> Result<database::CommitImport, String> {
+    let repo_path = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_project(project_id).map_err(|e| e.to_string())?.repo_path
+    };
+    let repo_path = repo_path.ok_or("Set the project's repository path before importing commits")?;
+    let mut args = vec![
+        "log".to_string(),
+        "--no-merges".to_string(),
+        "--name-only".to_string(),
+        commitlog::LOG_FORMAT.to_string(),
+        format!("--max-count={}", max_count.unwrap_or(commitlog::DEFAULT_MAX_COMMITS).max(1)),
+    ];
+    if let Some(since) = since.filter(|s| !s.trim().is_empty()) {
+        args.push(format!("--since={}", since.trim()));
+    }
+    let output = platform::git()?
+        .args(&args)
+        .current_dir(&repo_path)
+        .output()
+        .map_err(|e| format!("Failed to run git log: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    let commits = commitlog::parse(&String::from_utf8_lossy(&output.stdout));
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_problems(project_id, component_id).map_err(|e| e.to_string())
+    db.import_commits(project_id, &commits).map_err(|e| e.to_string())
 }
 
+// ============================================================
+// v1.4: DEPENDENCY SNAPSHOT COMMANDS
+// ============================================================
+
+/// v1.4: Record the versions locked by every Cargo.lock and package-lock.json in the
+/// repository (the project's repo_path unless another is given) and log what changed since
+/// the last snapshot as dependency changes.
 #[tauri::command]
-fn update_problem(
+fn snapshot_dependencies(
     state: State<AppState>,
-    id: i64,
-    title: Option<String>,
-    description: Option<String>,
-    status: Option<String>,
-    severity: Option<String>,
-    root_cause: Option<String>
-) -> Result<database::Problem, String> {
+    project_id: i64,
+    repo_path: Option<String>
+) -> Result<database::DependencySnapshotResult, String> {
+    let repo_path = match repo_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(path) => path,
+        None => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.get_project(project_id).map_err(|e| e.to_string())?.repo_path
+                .ok_or("Set the project's repository path or give one to snapshot")?
+        }
+    };
+    let found = dependencies::scan(Path::new(&repo_path))?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_problem(id, title.as_deref(), description.as_deref(), status.as_deref(), severity.as_deref(), root_cause.as_deref())
-        .map_err(|e| e.to_string())
+    db.snapshot_dependencies(project_id, &repo_path, &found).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_problem(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_dependency_snapshots(state: State<AppState>, project_id: i64) -> Result<Vec<database::DependencySnapshot>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_problem(id).map_err(|e| e.to_string())
+    db.get_dependency_snapshots(project_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_problem_tree(state: State<AppState>, problem_id: i64) -> Result<serde_json::Value, String> {
+fn get_snapshot_dependencies(state: State<AppState>, id: RecordId) -> Result<Vec<dependencies::Dependency>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_problem_tree(problem_id).map_err(|e| e.to_string())
+    let id = id.resolve(&db, "dependency_snapshots")?;
+    db.get_snapshot_dependencies(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_dependency_snapshot(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "dependency_snapshots")?;
+    db.delete_dependency_snapshot(id).map_err(|e| e.to_string())
 }
 
 // ============================================================
-// SOLUTION ATTEMPT COMMANDS
+// v1.4: TEST RESULT COMMANDS
 // ============================================================
 
+/// v1.4: Store a test report as a run of the project. `format` is junit or nextest; the
+/// report is read from `path`, or given as `content`.
 #[tauri::command]
-fn log_attempt(
+fn ingest_test_results(
     state: State<AppState>,
-    problem_id: i64,
-    description: String,
-    parent_attempt_id: Option<i64>
-) -> Result<database::SolutionAttempt, String> {
+    project_id: i64,
+    format: String,
+    path: Option<String>,
+    content: Option<String>
+) -> Result<database::TestRunDetail, String> {
+    let (content, name) = match (path.filter(|p| !p.trim().is_empty()), content) {
+        (Some(path), _) => {
+            let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let name = Path::new(&path).file_name().map(|n| n.to_string_lossy().to_string());
+            (content, name)
+        }
+        (None, Some(content)) => (content, None),
+        (None, None) => return Err("Give the path of a test report or its content".to_string()),
+    };
+    let run = testresults::parse(&format, &content)?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.log_attempt(problem_id, &description, parent_attempt_id)
-        .map_err(|e| e.to_string())
+    db.ingest_test_run(project_id, &format, name.as_deref(), &run).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn mark_attempt_outcome(
-    state: State<AppState>,
-    id: i64,
-    outcome: String,
-    notes: Option<String>,
-    confidence: Option<String>
-) -> Result<database::SolutionAttempt, String> {
+fn get_test_runs(state: State<AppState>, project_id: i64, limit: Option<i64>) -> Result<Vec<database::TestRun>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.mark_attempt_outcome(id, &outcome, notes.as_deref(), confidence.as_deref())
-        .map_err(|e| e.to_string())
+    db.get_test_runs(project_id, limit.unwrap_or(50)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_attempts_for_problem(state: State<AppState>, problem_id: i64) -> Result<Vec<database::SolutionAttempt>, String> {
+fn get_test_run(state: State<AppState>, id: RecordId) -> Result<database::TestRunDetail, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_attempts_for_problem(problem_id).map_err(|e| e.to_string())
+    let id = id.resolve(&db, "test_runs")?;
+    db.get_test_run(id).map_err(|e| e.to_string())
 }
 
-// ============================================================
-// SOLUTION COMMANDS
-// ============================================================
-
 #[tauri::command]
-fn mark_problem_solved(
-    state: State<AppState>,
-    problem_id: i64,
-    winning_attempt_id: Option<i64>,
-    summary: String,
-    code_snippet: Option<String>,
-    key_insight: Option<String>
-) -> Result<database::Solution, String> {
+fn delete_test_run(state: State<AppState>, id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.mark_problem_solved(problem_id, winning_attempt_id, &summary, code_snippet.as_deref(), key_insight.as_deref())
-        .map_err(|e| e.to_string())
+    let id = id.resolve(&db, "test_runs")?;
+    db.delete_test_run(id).map_err(|e| e.to_string())
 }
 
+/// v1.4: Tests that failed, passed and failed again within the project's latest `window` runs.
 #[tauri::command]
-fn get_solution_for_problem(state: State<AppState>, problem_id: i64) -> Result<Option<database::Solution>, String> {
+fn get_flaky_tests(state: State<AppState>, project_id: i64, window: Option<i64>) -> Result<Vec<database::FlakyTest>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_solution_for_problem(problem_id).map_err(|e| e.to_string())
+    db.get_flaky_tests(project_id, window).map_err(|e| e.to_string())
 }
 
 // ============================================================
-// TODO COMMANDS
+// v1.4: CI STATUS COMMANDS
 // ============================================================
 
+/// v1.4: Read a CI status from GitHub Actions (`target` owner/repo, optionally one branch or
+/// workflow) or a shields.io-style badge endpoint (`target` its URL). Polled every few
+/// minutes; GitHub uses the github_token setting when there is one.
 #[tauri::command]
-fn add_todo(
+fn add_ci_source(
     state: State<AppState>,
     project_id: i64,
-    title: String,
-    description: Option<String>,
-    priority: Option<String>,
-    component_id: Option<i64>,
-    due_date: Option<String>
-) -> Result<database::Todo, String> {
+    provider: String,
+    target: String,
+    branch: Option<String>,
+    workflow: Option<String>
+) -> Result<database::CiSource, String> {
+    let target = ci::clean_target(&provider, &target)?;
+    let branch = branch.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+    let workflow = workflow.map(|w| w.trim().to_string()).filter(|w| !w.is_empty());
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let priority = priority.unwrap_or_else(|| "medium".to_string());
-    db.add_todo(project_id, &title, description.as_deref(), &priority, component_id, due_date.as_deref())
-        .map_err(|e| e.to_string())
+    db.add_ci_source(project_id, &provider, &target, branch.as_deref(), workflow.as_deref()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_todo(state: State<AppState>, id: i64) -> Result<database::Todo, String> {
+fn get_ci_sources(state: State<AppState>, project_id: i64) -> Result<Vec<database::CiSource>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_todo(id).map_err(|e| e.to_string())
+    db.get_ci_sources(Some(project_id)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_todos(
-    state: State<AppState>,
-    project_id: i64,
-    status: Option<String>,
-    priority: Option<String>
-) -> Result<Vec<database::Todo>, String> {
+fn set_ci_source_enabled(state: State<AppState>, id: RecordId, enabled: bool) -> Result<database::CiSource, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_todos(project_id, status.as_deref(), priority.as_deref())
-        .map_err(|e| e.to_string())
+    let id = id.resolve(&db, "ci_sources")?;
+    db.set_ci_source_enabled(id, enabled).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn update_todo(
-    state: State<AppState>,
-    id: i64,
-    title: Option<String>,
-    description: Option<String>,
-    status: Option<String>,
-    priority: Option<String>,
-    due_date: Option<String>
-) -> Result<database::Todo, String> {
+fn delete_ci_source(state: State<AppState>, id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_todo(id, title.as_deref(), description.as_deref(), status.as_deref(), priority.as_deref(), due_date.as_deref())
-        .map_err(|e| e.to_string())
+    let id = id.resolve(&db, "ci_sources")?;
+    db.delete_ci_source(id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_todo(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_ci_runs(state: State<AppState>, source_id: RecordId, limit: Option<i64>) -> Result<Vec<database::CiRun>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_todo(id).map_err(|e| e.to_string())
+    let source_id = source_id.resolve(&db, "ci_sources")?;
+    db.get_ci_runs(source_id, limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// v1.4: Each source's state; a red one names the change it went red after.
+#[tauri::command]
+fn get_ci_status(state: State<AppState>, project_id: i64) -> Result<Vec<database::CiStatus>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_project_ci_status(project_id).map_err(|e| e.to_string())
+}
+
+/// v1.4: Poll the project's CI sources now rather than waiting for the background poll.
+#[tauri::command]
+fn poll_ci(app: tauri::AppHandle, project_id: i64) -> Result<Vec<database::CiStatus>, String> {
+    poll_ci_sources(&app, Some(project_id))
+}
+
+/// Poll the enabled CI sources of one project, or of all of them, and store what they
+/// report; a failed poll is kept as the source's last_error. Sources that turned red or
+/// green go out as `ci-status-changed`.
+fn poll_ci_sources(app: &tauri::AppHandle, project_id: Option<i64>) -> Result<Vec<database::CiStatus>, String> {
+    let state = app.state::<AppState>();
+    let (sources, token) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (db.get_ci_sources(project_id).map_err(|e| e.to_string())?, setting_string(&db, "github_token"))
+    };
+    let mut statuses = Vec::new();
+    for source in sources.into_iter().filter(|s| s.enabled) {
+        // Fetched without holding the database
+        let fetched = match source.provider.as_str() {
+            "github" => ci::fetch_github(&source.target, source.branch.as_deref(), source.workflow.as_deref(), token.as_deref()),
+            _ => ci::fetch_badge(&source.target).map(|run| vec![run]),
+        };
+        let (before, status) = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let before = db.get_ci_status(source.id).map_err(|e| e.to_string())?.state;
+            match &fetched {
+                Ok(runs) => db.record_ci_runs(source.id, runs),
+                Err(e) => db.record_ci_error(source.id, e),
+            }.map_err(|e| e.to_string())?;
+            (before, db.get_ci_status(source.id).map_err(|e| e.to_string())?)
+        };
+        if status.state != before && status.state != "unknown" {
+            let _ = app.emit("ci-status-changed", &status);
+        }
+        statuses.push(status);
+    }
+    Ok(statuses)
 }
 
 // ============================================================
-// LEARNING COMMANDS
+// v1.4: SENTRY COMMANDS
 // ============================================================
 
+/// v1.4: Issues of the Sentry project `sentry_project` (its slug) become problems on this
+/// component. A component follows one Sentry project; linking again replaces it.
 #[tauri::command]
-fn log_learning(
-    state: State<AppState>,
-    project_id: i64,
-    insight: String,
-    category: Option<String>,
-    context: Option<String>,
-    component_id: Option<i64>,
-    source: Option<String>
-) -> Result<database::Learning, String> {
+fn link_sentry_project(state: State<AppState>, component_id: RecordId, sentry_project: String) -> Result<database::IntegrationLink, String> {
+    let slug = sentry_project.trim();
+    if slug.is_empty() {
+        return Err("Give the Sentry project's slug".to_string());
+    }
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let source = source.unwrap_or_else(|| "experience".to_string());
-    db.log_learning(project_id, &insight, category.as_deref(), context.as_deref(), component_id, &source)
-        .map_err(|e| e.to_string())
+    let component_id = component_id.resolve(&db, "components")?;
+    db.upsert_integration_link("sentry", "component", component_id, slug, None).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_learning(state: State<AppState>, id: i64) -> Result<database::Learning, String> {
+fn unlink_sentry_project(state: State<AppState>, component_id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_learning(id).map_err(|e| e.to_string())
+    let component_id = component_id.resolve(&db, "components")?;
+    if let Some(link) = db.get_integration_link("sentry", "component", component_id).map_err(|e| e.to_string())? {
+        db.delete_integration_link(link.id).map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
+/// Components and the Sentry projects they follow (entity_id and external_id).
 #[tauri::command]
-fn get_learnings(
-    state: State<AppState>,
-    project_id: Option<i64>,
-    category: Option<String>,
-    verified_only: Option<bool>
-) -> Result<Vec<database::Learning>, String> {
+fn get_sentry_project_links(state: State<AppState>) -> Result<Vec<database::IntegrationLink>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let verified_only = verified_only.unwrap_or(false);
-    db.get_learnings(project_id, category.as_deref(), verified_only)
-        .map_err(|e| e.to_string())
+    db.get_integration_links("sentry", Some("component")).map_err(|e| e.to_string())
+}
+
+/// Sentry webhooks on the local API. They're signed rather than carrying a bearer token:
+/// the signature is checked against the sentry_client_secret setting, after the delivery's
+/// timestamp is checked for being recent, and Sentry gets a rate limit budget of its own.
+/// Neither the timestamp nor the Request-ID is signed, so deliveries are remembered for 30
+/// days by both the Request-ID and a digest of the body, and one seen before is ignored.
+/// Locks on a problem apply to the updates as to edits in the app.
+fn handle_sentry_webhook(app: &tauri::AppHandle, request: &localapi::Request) -> localapi::Response {
+    use sha2::{Digest, Sha256};
+    
+    let state = app.state::<AppState>();
+    if let Err(retry_after) = state.api_limiter.allow("sentry") {
+        return localapi::Response::too_many_requests(retry_after);
+    }
+    let (secret, api) = match state.db.lock() {
+        Ok(db) if !local_api_enabled(&db) => return localapi::Response::error(503, "The local API is turned off"),
+        Ok(db) => {
+            let api = setting_string(&db, "sentry_token").map(|token| {
+                (setting_string(&db, "sentry_url").unwrap_or_else(|| sentry::SENTRY_URL.to_string()), token)
+            });
+            (setting_string(&db, "sentry_client_secret"), api)
+        }
+        Err(e) => return localapi::Response::error(503, &e.to_string()),
+    };
+    if request.method != "POST" {
+        return localapi::Response::error(405, "Use POST");
+    }
+    let Some(secret) = secret else {
+        return localapi::Response::error(503, "Sentry webhooks aren't set up (setting: sentry_client_secret)");
+    };
+    let timestamp = request.headers.get("sentry-hook-timestamp").map(|t| t.as_str());
+    if let Err(e) = sentry::check_timestamp(timestamp) {
+        return localapi::Response::error(401, &e);
+    }
+    let signature = request.headers.get("sentry-hook-signature").map(|s| s.as_str());
+    if let Err(e) = sentry::verify_signature(&secret, &request.body, signature) {
+        return localapi::Response::error(401, &e);
+    }
+    let body = match request.json() {
+        Ok(body) => body,
+        Err(e) => return localapi::Response::error(400, &e),
+    };
+    let resource = request.headers.get("sentry-hook-resource").map(|r| r.as_str()).unwrap_or_default();
+    // The signature covers the body only, so a replay is spotted by having been seen before
+    let mut deliveries = vec![format!("sha256:{:x}", Sha256::digest(&request.body))];
+    deliveries.extend(request.headers.get("request-id").map(|id| format!("request-id:{}", id.trim())));
+    match state.db.lock().map(|db| db.claim_webhook_delivery("sentry", &deliveries)) {
+        Ok(Ok(true)) => {}
+        Ok(Ok(false)) => return localapi::Response::ok(serde_json::json!({ "ignored": "Already received" })),
+        Ok(Err(e)) => return localapi::Response::error(503, &e.to_string()),
+        Err(e) => return localapi::Response::error(503, &e.to_string()),
+    }
+    let result = ingest_sentry_webhook(app, &state, resource, &body, api);
+    // Sentry retries a delivery that failed, and the retry should be let through
+    if result.is_err() {
+        if let Ok(db) = state.db.lock() {
+            let _ = db.release_webhook_delivery("sentry", &deliveries);
+        }
+    }
+    match result {
+        Ok(value) => localapi::Response::ok(value),
+        Err(LockError::Held(e)) => localapi::Response::error(409, &e),
+        Err(LockError::Other(e)) => localapi::Response::error(422, &e),
+    }
+}
+
+fn ingest_sentry_webhook(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    resource: &str,
+    body: &serde_json::Value,
+    api: Option<(String, String)>
+) -> Result<serde_json::Value, LockError> {
+    match sentry::parse_webhook(resource, body)? {
+        sentry::Webhook::Issue { action, issue } => {
+            let (problem, created) = {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                // An update overwrites the problem's title and status, like an edit in the app
+                if let Some(id) = db.find_integration_entity("sentry", "problem", &issue.id).map_err(|e| e.to_string())? {
+                    if db.get_problem(id).is_ok() {
+                        check_lock(app, &db, "problem", id)?;
+                    }
+                }
+                let status = sentry::problem_status(&action, issue.status.as_deref());
+                db.upsert_sentry_problem(&issue, status).map_err(|e| e.to_string())?
+            };
+            // A new issue's stack trace, from its latest event; fetched without holding the database
+            let mut attachment = None;
+            if let (true, Some((base_url, token))) = (created, api) {
+                match sentry::fetch_latest_event(&base_url, &token, &issue.id) {
+                    Ok(event) => {
+                        let event_id = event["eventID"].as_str().or_else(|| event["id"].as_str()).unwrap_or(&issue.id).to_string();
+                        if let Some(trace) = sentry::stack_trace(&event) {
+                            let db = state.db.lock().map_err(|e| e.to_string())?;
+                            attachment = attach_stack_trace(&db, &problem, &event_id, &trace)?;
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to fetch the stack trace of Sentry issue {}: {}", issue.id, e),
+                }
+            }
+            Ok(serde_json::json!({ "problem": problem, "created": created, "stack_trace": attachment }))
+        }
+        sentry::Webhook::Event { issue_id, event_id, stack_trace } => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let problem = match db.find_integration_entity("sentry", "problem", &issue_id).map_err(|e| e.to_string())? {
+                Some(id) => db.get_problem(id).map_err(|e| e.to_string())?,
+                None => return Ok(serde_json::json!({ "ignored": format!("No problem for Sentry issue {} yet", issue_id) })),
+            };
+            let attachment = match stack_trace {
+                Some(trace) => attach_stack_trace(&db, &problem, event_id.as_deref().unwrap_or(&issue_id), &trace)?,
+                None => None,
+            };
+            Ok(serde_json::json!({ "problem": problem, "created": false, "stack_trace": attachment }))
+        }
+        sentry::Webhook::Other(resource) => Ok(serde_json::json!({ "ignored": resource })),
+    }
+}
+
+/// Save a Sentry stack trace as a text attachment of the problem, once per event.
+fn attach_stack_trace(db: &Database, problem: &database::Problem, event_id: &str, trace: &str) -> Result<Option<database::Attachment>, String> {
+    let project_id = db.get_component(problem.component_id).map_err(|e| e.to_string())?.project_id;
+    let file_name = format!("sentry-{}.txt", event_id);
+    let existing = db.get_attachments(project_id, None, Some(problem.id), None).map_err(|e| e.to_string())?;
+    if existing.iter().any(|a| a.file_name == file_name) {
+        return Ok(None);
+    }
+    let bundle = project_bundle_dir(project_id);
+    std::fs::create_dir_all(&bundle).map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+    let file_path = bundle.join(&file_name);
+    std::fs::write(&file_path, trace).map_err(|e| format!("Failed to write stack trace: {}", e))?;
+    let file_path = file_path.to_string_lossy().to_string();
+    let attachment = db.create_attachment(
+        project_id,
+        &file_name,
+        &file_path,
+        "txt",
+        Some(trace.len() as i64),
+        calculate_file_hash(&file_path).ok().as_deref(),
+        false,
+        Some(problem.component_id),
+        Some(problem.id),
+        Some(&format!("Stack trace from Sentry event {}", event_id)),
+        None,
+    ).map_err(|e| e.to_string())?;
+    index_attachment(db, attachment).map(Some)
 }
 
+// ============================================================
+// v1.4: JOB QUEUE COMMANDS
+// ============================================================
+
+/// v1.4: Recent background jobs, unfinished ones first. Progress also arrives as
+/// `job-progress` events, and `job-finished` when a job ends.
 #[tauri::command]
-fn update_learning(
-    state: State<AppState>,
-    id: i64,
-    insight: Option<String>,
-    category: Option<String>,
-    context: Option<String>,
-    verified: Option<bool>
-) -> Result<database::Learning, String> {
+fn get_jobs(state: State<AppState>, limit: Option<i64>) -> Result<Vec<database::Job>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_learning(id, insight.as_deref(), category.as_deref(), context.as_deref(), verified)
-        .map_err(|e| e.to_string())
+    db.get_jobs(limit.unwrap_or(50)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_learning(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_job(state: State<AppState>, id: i64) -> Result<database::Job, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_learning(id).map_err(|e| e.to_string())
+    db.get_job(id).map_err(|e| e.to_string())
+}
+
+/// v1.4: A queued job won't start; a running one stops before its next step, keeping the
+/// work it already did.
+#[tauri::command]
+fn cancel_job(state: State<AppState>, id: i64) -> Result<database::Job, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.cancel_job(id).map_err(|e| e.to_string())
+}
+
+/// Run the oldest queued job, if there is one. Returns whether there was.
+fn run_next_job(app: &tauri::AppHandle) -> Result<bool, String> {
+    let state = app.state::<AppState>();
+    let job = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.start_next_job().map_err(|e| e.to_string())?
+    };
+    let Some(job) = job else {
+        return Ok(false);
+    };
+    let _ = app.emit("job-progress", &job);
+    let outcome = match job.kind.as_str() {
+        "reindex_embeddings" => reindex_embeddings_job(app, &job),
+        "describe_attachments" => describe_attachments_job(app, &job),
+        other => Err(format!("Unknown job kind '{}'", other)),
+    };
+    let finished = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.finish_job(job.id, outcome.as_ref().map_err(|e| e.as_str())).map_err(|e| e.to_string())?
+    };
+    let _ = app.emit("job-finished", &finished);
+    Ok(true)
+}
+
+/// Report a running job's progress. Errs with JOB_CANCELLED once the job has been cancelled,
+/// so the job stops at its next report.
+fn report_job_progress(app: &tauri::AppHandle, id: i64, progress: i64, total: Option<i64>, message: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let job = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.update_job_progress(id, progress, total, Some(message)).map_err(|e| e.to_string())?
+    };
+    let _ = app.emit("job-progress", &job);
+    if job.status == "cancelled" {
+        return Err(JOB_CANCELLED.to_string());
+    }
+    Ok(())
 }
 
 // ============================================================
-// SEARCH COMMAND
+// v1.4: EMBEDDING COMMANDS
 // ============================================================
 
+/// v1.4: Queue re-embedding of what `scope` covers (default: everything). Only vectors that
+/// are missing, were made from text that has changed since, or by another embedding_model or
+/// embedding_model_version are made again, unless `force`. Returns the job to follow.
 #[tauri::command]
-fn search(
-    state: State<AppState>,
-    query: String,
-    project_id: Option<i64>,
-    limit: Option<i32>,
-    include_file_content: Option<bool>
-) -> Result<Vec<serde_json::Value>, String> {
+fn reindex_embeddings(state: State<AppState>, scope: Option<database::EmbeddingScope>, force: Option<bool>) -> Result<database::Job, String> {
+    let scope = scope.unwrap_or_default();
+    let force = force.unwrap_or(false);
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let limit = limit.unwrap_or(10);
-    // include_file_content is for future semantic search in files
-    let _ = include_file_content;
-    db.search(&query, project_id, limit).map_err(|e| e.to_string())
+    let (_, model, _) = embedding_client(&state, &db)?;
+    if let Some(unknown) = scope.content_types.iter().find(|t| !embeddings::CONTENT_TYPES.contains(&t.as_str())) {
+        return Err(format!("Unknown content type '{}' (try {})", unknown, embeddings::CONTENT_TYPES.join(", ")));
+    }
+    let description = match scope.project_id {
+        Some(project_id) => format!("Re-index embeddings of project #{} with {}", project_id, model),
+        None => format!("Re-index embeddings with {}", model),
+    };
+    db.create_job("reindex_embeddings", &serde_json::json!({ "scope": scope, "force": force }), Some(&description))
+        .map_err(|e| e.to_string())
+}
+
+/// v1.4: How many records have vectors from their current text and the current model,
+/// and how many would a re-index redo.
+#[tauri::command]
+fn get_embedding_status(state: State<AppState>, scope: Option<database::EmbeddingScope>) -> Result<database::EmbeddingStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let model = setting_string(&db, "embedding_model");
+    let version = setting_string(&db, "embedding_model_version");
+    db.get_embedding_status(&scope.unwrap_or_default(), model.as_deref(), version.as_deref()).map_err(|e| e.to_string())
+}
+
+/// The embedding endpoint with its model and model version. It's the language model's
+/// endpoint unless embedding_base_url (and embedding_api_key) point elsewhere. Requests are logged in ai_usage at embedding_cost_per_million.
+fn embedding_client<'a>(state: &'a AppState, db: &Database) -> Result<(llm::LlmClient<'a>, String, Option<String>), String> {
+    let model = setting_string(db, "embedding_model")
+        .ok_or("No embedding model configured (setting: embedding_model)")?;
+    let base_url = setting_string(db, "embedding_base_url").or_else(|| setting_string(db, "llm_base_url"));
+    let api_key = setting_string(db, "embedding_api_key").or_else(|| setting_string(db, "llm_api_key"));
+    let prices = llm::Prices { input: setting_number(db, "embedding_cost_per_million").unwrap_or(0.0), output: 0.0 };
+    let client = llm::LlmClient::new(base_url.as_deref(), api_key.as_deref(), &model)
+        .metered(UsageMeter { state, command: "reindex_embeddings", prices });
+    Ok((client, model, setting_string(db, "embedding_model_version")))
+}
+
+fn reindex_embeddings_job(app: &tauri::AppHandle, job: &database::Job) -> Result<serde_json::Value, String> {
+    let scope: database::EmbeddingScope = serde_json::from_value(job.payload["scope"].clone()).unwrap_or_default();
+    let force = job.payload["force"].as_bool().unwrap_or(false);
+    let state = app.state::<AppState>();
+    let (client, model, version, removed, stale) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let (client, model, version) = embedding_client(&state, &db)?;
+        let removed = db.delete_orphaned_embeddings().map_err(|e| e.to_string())?;
+        let stale = db.get_stale_embeddings(&scope, &model, version.as_deref(), force).map_err(|e| e.to_string())?;
+        (client, model, version, removed, stale)
+    };
+    let total = stale.len() as i64;
+    report_job_progress(app, job.id, 0, Some(total), &format!("{} records to embed", total))?;
+    let mut embedded = 0;
+    for batch in stale.chunks(embeddings::BATCH_SIZE) {
+        // The endpoint is called without holding the database
+        let texts: Vec<String> = batch.iter().map(|source| source.text.clone()).collect();
+        let vectors = client.embed(&texts)?;
+        {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            for (source, vector) in batch.iter().zip(&vectors) {
+                db.store_embedding(source, &model, version.as_deref(), vector).map_err(|e| e.to_string())?;
+            }
+        }
+        embedded += batch.len() as i64;
+        report_job_progress(app, job.id, embedded, None, &format!("Embedded {} of {}", embedded, total))?;
+    }
+    Ok(serde_json::json!({
+        "model": model,
+        "model_version": version,
+        "embedded": embedded,
+        "removed": removed,
+    }))
 }
 
 // ============================================================
-// STORY GENERATION COMMANDS
+// v1.4: AI USAGE COMMANDS
 // ============================================================
 
+/// v1.4: Language model and embedding requests in a time window (a named `range`, default
+/// this_month, else `since`/`until`), with tokens and estimated cost by model, command and
+/// day, and this month's spend against the ai_monthly_budget setting.
 #[tauri::command]
-fn generate_project_story(state: State<AppState>, project_id: i64) -> Result<serde_json::Value, String> {
+fn get_ai_usage(
+    state: State<AppState>,
+    range: Option<String>,
+    since: Option<String>,
+    until: Option<String>
+) -> Result<database::AiUsage, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    // Gather all project data
-    let project = db.get_project(project_id).map_err(|e| e.to_string())?;
-    let components = db.list_components(project_id).map_err(|e| e.to_string())?;
-    let all_problems = db.get_all_problems(Some(project_id), None).map_err(|e| e.to_string())?;
-    let all_changes = db.get_all_changes(Some(project_id), None).map_err(|e| e.to_string())?;
-    let learnings = db.get_learnings(Some(project_id), None, false).map_err(|e| e.to_string())?;
-    let todos = db.get_todos(project_id, None, None).map_err(|e| e.to_string())?;
-    let stats = db.get_project_stats(project_id).map_err(|e| e.to_string())?;
-    let attachments = db.get_attachments(project_id, None, None).map_err(|e| e.to_string())?;
-    
-    // Count solved vs open problems
-    let solved_count = all_problems.iter().filter(|p| p.status == "solved").count();
-    let open_count = all_problems.iter().filter(|p| p.status != "solved").count();
-    
-    Ok(serde_json::json!({
-        "project": project,
-        "components": components,
-        "problems": all_problems,
-        "changes": all_changes,
-        "learnings": learnings,
-        "todos": todos,
-        "attachments": attachments,
-        "stats": stats,
-        "summary": {
-            "total_problems": all_problems.len(),
-            "solved_problems": solved_count,
-            "open_problems": open_count,
-            "total_changes": all_changes.len(),
-            "total_learnings": learnings.len(),
-            "total_components": components.len(),
-            "total_attachments": attachments.len(),
-        }
-    }))
+    let range = range.or_else(|| since.is_none().then(|| "this_month".to_string()));
+    let (since, until) = time_window(&db, range.as_deref(), since.as_deref(), until.as_deref(), 24)?;
+    let mut usage = db.get_ai_usage(&timezone::stored(since), until.map(timezone::stored).as_deref())
+        .map_err(|e| e.to_string())?;
+    (usage.monthly_budget, usage.month_cost) = ai_month_spend(&db)?;
+    Ok(usage)
 }
 
+// ============================================================
+// v1.4: ATTACHMENT DESCRIPTION COMMANDS
+// ============================================================
+
+/// v1.4: Queue AI descriptions for a project's attachments: the given ones, or every one
+/// without a description yet (all of them with `overwrite`). Small files share requests.
+/// The job stops for the day once ai_describe_daily_budget is spent, and reports the tokens
+/// and cost it used. Returns the job to follow.
 #[tauri::command]
-fn generate_problem_journey(state: State<AppState>, problem_id: i64) -> Result<serde_json::Value, String> {
+fn describe_attachments(
+    state: State<AppState>,
+    project_id: i64,
+    attachment_ids: Option<Vec<i64>>,
+    overwrite: Option<bool>
+) -> Result<database::Job, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    let problem = db.get_problem(problem_id).map_err(|e| e.to_string())?;
-    let attempts = db.get_attempts_for_problem(problem_id).map_err(|e| e.to_string())?;
-    let solution = db.get_solution_for_problem(problem_id).map_err(|e| e.to_string())?;
-    
-    // Build the journey with timeline
-    let mut journey_steps = Vec::new();
-    
-    // Add problem creation as first step
-    journey_steps.push(serde_json::json!({
-        "type": "problem_identified",
-        "title": problem.title.clone(),
-        "description": problem.description.clone(),
-        "timestamp": problem.created_at.clone(),
-        "severity": problem.severity.clone(),
-    }));
-    
-    // Add each attempt
-    for attempt in &attempts {
-        journey_steps.push(serde_json::json!({
-            "type": "attempt",
-            "id": attempt.id,
-            "description": attempt.description.clone(),
-            "outcome": attempt.outcome.clone(),
-            "notes": attempt.notes.clone(),
-            "timestamp": attempt.created_at.clone(),
-            "parent_attempt_id": attempt.parent_attempt_id,
-        }));
-    }
-    
-    // Add solution if exists
-    if let Some(sol) = &solution {
-        journey_steps.push(serde_json::json!({
-            "type": "solved",
-            "summary": sol.summary.clone(),
-            "key_insight": sol.key_insight.clone(),
-            "code_snippet": sol.code_snippet.clone(),
-            "timestamp": sol.created_at.clone(),
-            "winning_attempt_id": sol.winning_attempt_id,
-        }));
+    llm_client(&state, &db, "describe_attachments")?;
+    queue_describe_job(&db, project_id, attachment_ids, overwrite.unwrap_or(false))
+}
+
+fn queue_describe_job(db: &Database, project_id: i64, attachment_ids: Option<Vec<i64>>, overwrite: bool) -> Result<database::Job, String> {
+    let description = match &attachment_ids {
+        Some(ids) => format!("Describe {} files in project #{}", ids.len(), project_id),
+        None => format!("Describe new files in project #{}", project_id),
+    };
+    db.create_job(
+        "describe_attachments",
+        &serde_json::json!({ "project_id": project_id, "attachment_ids": attachment_ids, "overwrite": overwrite }),
+        Some(&description),
+    ).map_err(|e| e.to_string())
+}
+
+fn describe_attachments_job(app: &tauri::AppHandle, job: &database::Job) -> Result<serde_json::Value, String> {
+    let project_id = job.payload["project_id"].as_i64().ok_or("The job names no project")?;
+    let ids: Option<Vec<i64>> = serde_json::from_value(job.payload["attachment_ids"].clone()).unwrap_or_default();
+    let overwrite = job.payload["overwrite"].as_bool().unwrap_or(false);
+    let state = app.state::<AppState>();
+    let (client, prices, budget, spent_today, files, skipped) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let client = llm_client(&state, &db, "describe_attachments")?;
+        let budget = setting_number(&db, "ai_describe_daily_budget").filter(|b| *b > 0.0);
+        let zone = db.timezone();
+        let (day_start, _) = zone.day_bounds(zone.today());
+        let spent_today = db.get_job_cost_since("describe_attachments", &timezone::stored(day_start))
+            .map_err(|e| e.to_string())?;
+        let mut files = Vec::new();
+        let mut skipped = 0;
+        for attachment in db.get_attachments(project_id, None, None, None).map_err(|e| e.to_string())? {
+            let wanted = ids.as_ref().is_none_or(|ids| ids.contains(&attachment.id));
+            let described = attachment.ai_description.as_deref().is_some_and(|d| !d.trim().is_empty());
+            if !wanted || (described && !overwrite) {
+                continue;
+            }
+            let size = attachment.file_size.unwrap_or_default().max(0) as u64;
+            match descriptions::kind_of(&attachment.file_type) {
+                Some(kind) if size <= descriptions::MAX_FILE_BYTES => files.push(descriptions::PendingFile {
+                    id: attachment.id,
+                    name: attachment.file_name,
+                    path: attachment.file_path,
+                    kind,
+                    size,
+                }),
+                _ => skipped += 1,
+            }
+        }
+        (client, llm_prices(&db), budget, spent_today, files, skipped)
+    };
+
+    let total = files.len() as i64;
+    report_job_progress(app, job.id, 0, Some(total), &format!("{} files to describe", total))?;
+    let mut spend = descriptions::Spend::default();
+    let (mut done, mut described, mut requests) = (0, 0, 0);
+    let mut budget_reached = false;
+    for batch in descriptions::batches(files) {
+        // Stop before a request that would take today's spend past the budget
+        if let Some(budget) = budget {
+            if spent_today + spend.cost + prices.cost(&descriptions::estimate(&batch)) > budget {
+                budget_reached = true;
+                break;
+            }
+        }
+        // Files are read and the model asked without holding the database
+        let contents: Vec<Option<Vec<u8>>> = batch.iter().map(|file| std::fs::read(&file.path).ok()).collect();
+        let (reply, usage) = client.complete_parts(
+            descriptions::SYSTEM_PROMPT,
+            &descriptions::prompt(&batch, &contents),
+            descriptions::max_tokens(&batch),
+        )?;
+        let cost = prices.cost(&usage);
+        spend.add(&usage, cost);
+        requests += 1;
+        {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.add_job_usage(job.id, usage.input_tokens, usage.output_tokens, cost).map_err(|e| e.to_string())?;
+            for (file, description) in batch.iter().zip(descriptions::parse_reply(&reply, batch.len())) {
+                if let Some(description) = description {
+                    db.update_attachment(file.id, None, None, Some(&description), None, None).map_err(|e| e.to_string())?;
+                    described += 1;
+                }
+            }
+        }
+        done += batch.len() as i64;
+        report_job_progress(app, job.id, done, None, &format!("Described {} of {} files", done, total))?;
     }
-    
     Ok(serde_json::json!({
-        "problem": problem,
-        "attempts": attempts,
-        "solution": solution,
-        "journey": journey_steps,
-        "stats": {
-            "total_attempts": attempts.len(),
-            "failed_attempts": attempts.iter().filter(|a| a.outcome.as_deref() == Some("failure")).count(),
-            "is_solved": solution.is_some(),
-        }
+        "described": described,
+        "skipped": skipped,
+        "remaining": total - done,
+        "budget_reached": budget_reached,
+        "requests": requests,
+        "input_tokens": spend.input_tokens,
+        "output_tokens": spend.output_tokens,
+        "cost": spend.cost,
     }))
 }
 
 // ============================================================
-// v1.1: FILE ATTACHMENT COMMANDS
+// v1.4: THEME COMMANDS
 // ============================================================
 
+/// v1.4: Group a project's learnings or problems into themes by how alike their vectors
+/// are, each labelled with its most telling words, or named by the language model with
+/// `llm_labels`. `k` defaults to about √(n/2). Records without a current vector are left
+/// out and counted; re-indexing embeddings brings them in.
 #[tauri::command]
-fn attach_file(
+fn cluster_records(
     state: State<AppState>,
     project_id: i64,
-    file_path: String,
-    component_id: Option<i64>,
-    problem_id: Option<i64>,
-    user_description: Option<String>,
-    copy_to_bundle: Option<bool>
-) -> Result<database::Attachment, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    let path = Path::new(&file_path);
-    let file_name = path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-    
-    let file_type = path.extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-    
-    // Get file size
-    let file_size = std::fs::metadata(&file_path)
-        .map(|m| m.len() as i64)
-        .ok();
-    
-    // Calculate file hash (SHA256)
-    let file_hash = calculate_file_hash(&file_path).ok();
-    
-    let copy_to_bundle = copy_to_bundle.unwrap_or(true);
-    let is_external = !copy_to_bundle;
-    
-    // If copying to bundle, copy the file
-    let final_path = if copy_to_bundle {
-        copy_file_to_project_bundle(&file_path, project_id)?
-    } else {
-        file_path.clone()
+    entity: String,
+    k: Option<usize>,
+    llm_labels: Option<bool>
+) -> Result<themes::Themes, String> {
+    let entity = match entity.trim().to_lowercase().as_str() {
+        "learning" | "learnings" => "learning",
+        "problem" | "problems" => "problem",
+        other => return Err(format!("Can't cluster '{}' (try learnings or problems)", other)),
     };
-    
-    db.create_attachment(
-        project_id,
-        &file_name,
-        &final_path,
-        &file_type,
-        file_size,
-        file_hash.as_deref(),
-        is_external,
-        component_id,
-        problem_id,
-        user_description.as_deref(),
-        None, // tags
-    ).map_err(|e| e.to_string())
+    let (unembedded, items, model, client) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let model = setting_string(&db, "embedding_model")
+            .ok_or("No embedding model configured (setting: embedding_model)")?;
+        let version = setting_string(&db, "embedding_model_version");
+        let scope = database::EmbeddingScope { project_id: Some(project_id), content_types: vec![entity.to_string()] };
+        let sources = db.get_embedding_sources(&scope).map_err(|e| e.to_string())?;
+        let mut vectors = db.get_embedding_vectors(project_id, entity, &model, version.as_deref())
+            .map_err(|e| e.to_string())?;
+        let total = sources.len();
+        let items: Vec<themes::Item> = sources.into_iter()
+            .filter_map(|source| {
+                Some(themes::Item {
+                    vector: vectors.remove(&source.content_id)?,
+                    id: source.content_id,
+                    title: first_line_title(&source.text),
+                    text: source.text,
+                })
+            })
+            .collect();
+        let client = if llm_labels.unwrap_or(false) { Some(llm_client(&state, &db, "cluster_records")?) } else { None };
+        (total - items.len(), items, model, client)
+    };
+    let k = k.unwrap_or_else(|| themes::default_k(items.len())).clamp(1, themes::MAX_THEMES * 4);
+    let mut clusters = themes::cluster(&items, k);
+    if let Some(client) = client {
+        // The model is asked without holding the database; a theme it can't name keeps its terms
+        for theme in &mut clusters {
+            let titles: Vec<String> = theme.members.iter().take(20).map(|m| format!("- {}", m.title)).collect();
+            if let Ok(label) = client.complete(themes::LABEL_PROMPT, &titles.join("\n"), 20) {
+                theme.label = label.trim_matches(|c| c == '"' || c == '.').to_string();
+            }
+        }
+    }
+    Ok(themes::Themes { entity: entity.to_string(), model, k, themes: clusters, unembedded })
 }
 
+// ============================================================
+// v1.4: BENCHMARK COMMANDS
+// ============================================================
+
+/// v1.4: Record one measurement of a benchmark, optionally with the commit it ran on. One
+/// well off the median of the last few is logged as a change beside the change behind it.
 #[tauri::command]
-fn get_attachments(
+fn record_benchmark(
     state: State<AppState>,
     project_id: i64,
-    component_id: Option<i64>,
-    problem_id: Option<i64>
-) -> Result<Vec<database::Attachment>, String> {
+    name: String,
+    value: f64,
+    unit: Option<String>,
+    commit: Option<String>
+) -> Result<database::BenchmarkResult, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Give the benchmark a name".to_string());
+    }
+    if !value.is_finite() {
+        return Err(format!("{} isn't a measurement", value));
+    }
+    let unit = unit.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    let commit = commit.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_attachments(project_id, component_id, problem_id).map_err(|e| e.to_string())
+    db.record_benchmark(project_id, name, value, unit.as_deref(), commit.as_deref()).map_err(|e| e.to_string())
 }
 
+/// v1.4: A benchmark's measurements oldest first, for charting; across projects unless one is given.
 #[tauri::command]
-fn get_attachment(state: State<AppState>, id: i64) -> Result<database::Attachment, String> {
+fn get_benchmark_series(state: State<AppState>, name: String, project_id: Option<i64>) -> Result<Vec<database::Benchmark>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_attachment(id).map_err(|e| e.to_string())
+    db.get_benchmark_series(project_id, name.trim()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn update_attachment(
-    state: State<AppState>,
-    id: i64,
-    user_description: Option<String>,
-    tags: Option<String>,
-    ai_description: Option<String>,
-    ai_summary: Option<String>,
-    content_extracted: Option<bool>
-) -> Result<database::Attachment, String> {
+fn get_latest_benchmarks(state: State<AppState>, project_id: i64) -> Result<Vec<database::Benchmark>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_attachment(
-        id,
-        user_description.as_deref(),
-        tags.as_deref(),
-        ai_description.as_deref(),
-        ai_summary.as_deref(),
-        content_extracted,
-    ).map_err(|e| e.to_string())
+    db.get_latest_benchmarks(project_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn remove_attachment(state: State<AppState>, id: i64, delete_file: Option<bool>) -> Result<(), String> {
+fn delete_benchmark(state: State<AppState>, id: RecordId) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    // Get attachment first to check if we need to delete the file
-    if delete_file.unwrap_or(false) {
-        if let Ok(attachment) = db.get_attachment(id) {
-            if !attachment.is_external {
-                // Delete the file from bundle
-                let _ = std::fs::remove_file(&attachment.file_path);
-            }
-        }
-    }
-    
-    db.delete_attachment(id).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn read_file_content(file_path: String, file_type: String) -> Result<serde_json::Value, String> {
-    let path = Path::new(&file_path);
-    
-    if !path.exists() {
-        return Err("File not found".to_string());
-    }
-    
-    match file_type.as_str() {
-        "txt" | "md" | "json" | "swift" | "rs" | "py" | "js" | "ts" | "html" | "css" | "sql" | "yaml" | "yml" | "toml" | "xml" => {
-            // Text files
-            let content = std::fs::read_to_string(&file_path)
-                .map_err(|e| e.to_string())?;
-            Ok(serde_json::json!({
-                "type": "text",
-                "content": content,
-                "size": content.len(),
-            }))
-        },
-        "png" | "jpg" | "jpeg" | "gif" | "webp" => {
-            // Image files - return base64
-            let content = std::fs::read(&file_path)
-                .map_err(|e| e.to_string())?;
-            let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &content);
-            Ok(serde_json::json!({
-                "type": "image",
-                "content": base64,
-                "size": content.len(),
-                "mime_type": format!("image/{}", file_type),
-            }))
-        },
-        "pdf" => {
-            // PDF files - return base64 for now
-            let content = std::fs::read(&file_path)
-                .map_err(|e| e.to_string())?;
-            let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &content);
-            Ok(serde_json::json!({
-                "type": "pdf",
-                "content": base64,
-                "size": content.len(),
-            }))
-        },
-        _ => {
-            // Binary files - return info only
-            let metadata = std::fs::metadata(&file_path)
-                .map_err(|e| e.to_string())?;
-            Ok(serde_json::json!({
-                "type": "binary",
-                "size": metadata.len(),
-                "message": "Binary file content not readable as text",
-            }))
-        }
-    }
+    let id = id.resolve(&db, "benchmarks")?;
+    db.delete_benchmark(id).map_err(|e| e.to_string())
 }
 
 // ============================================================
-// v1.1: CONTENT LOCATION COMMANDS
+// CHANGE COMMANDS
 // ============================================================
 
 #[tauri::command]
-fn get_content_locations(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::ContentLocation>, String> {
+fn log_change(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    component_id: i64,
+    field_name: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    change_type: Option<String>,
+    reason: Option<String>
+) -> Result<database::Change, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_content_locations_for_attachment(attachment_id).map_err(|e| e.to_string())
+    let change_type = change_type.unwrap_or_else(|| "code".to_string());
+    let change = db.log_change(component_id, &field_name, old_value.as_deref(), new_value.as_deref(), &change_type, reason.as_deref())
+        .map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "component", component_id, "change_logged", serde_json::json!(change));
+    Ok(change)
 }
 
+/// v1.4: Log many changes in one transaction with a single `changes-logged` event.
 #[tauri::command]
-fn create_content_location(
+fn log_changes_batch(
+    app: tauri::AppHandle,
     state: State<AppState>,
-    attachment_id: i64,
-    description: String,
-    category: Option<String>,
-    location_type: String,
-    start_location: String,
-    end_location: Option<String>,
-    snippet: Option<String>,
-    related_problem_id: Option<i64>,
-    related_solution_id: Option<i64>,
-    related_learning_id: Option<i64>,
-    related_component_id: Option<i64>
-) -> Result<database::ContentLocation, String> {
+    changes: Vec<database::NewChange>
+) -> Result<Vec<database::Change>, String> {
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_content_location(
-        attachment_id,
-        &description,
-        category.as_deref(),
-        &location_type,
-        &start_location,
-        end_location.as_deref(),
-        snippet.as_deref(),
-        related_problem_id,
-        related_solution_id,
-        related_learning_id,
-        related_component_id,
-    ).map_err(|e| e.to_string())
+    let logged = db.log_changes_batch(&changes).map_err(|e| e.to_string())?;
+    announce_changes(&app, &db, &logged);
+    Ok(logged)
 }
 
+/// v1.4: Queue a change for the coalescer, which writes queued changes together every
+/// CHANGE_FLUSH_INTERVAL. Meant for high-frequency loggers that don't need the row back.
 #[tauri::command]
-fn delete_content_location(state: State<AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_content_location(id).map_err(|e| e.to_string())
+fn queue_change(state: State<AppState>, change: database::NewChange) -> Result<usize, String> {
+    let mut queue = state.change_queue.lock().map_err(|e| e.to_string())?;
+    queue.push(change);
+    Ok(queue.len())
 }
 
-// ============================================================
-// v1.1: EXTRACTION COMMANDS
-// ============================================================
+/// v1.4: Write queued changes now instead of waiting for the next flush.
+#[tauri::command]
+fn flush_change_queue(app: tauri::AppHandle) -> Result<usize, String> {
+    flush_queued_changes(&app)
+}
 
+/// Changes in a time window: a named `range` ("today", "this week", "this sprint"; see
+/// timezone::RANGES), else `since`/`until` (dates or times, read in the user's timezone), else
+/// the last `hours` (default 24).
 #[tauri::command]
-fn get_extractions(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::Extraction>, String> {
+fn get_recent_changes(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    component_id: Option<i64>,
+    hours: Option<i32>,
+    author: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    range: Option<String>
+) -> Result<Vec<database::Change>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_extractions_for_attachment(attachment_id).map_err(|e| e.to_string())
+    let (since, until) = time_window(&db, range.as_deref(), since.as_deref(), until.as_deref(), hours.unwrap_or(24))?;
+    let changes = db.get_recent_changes(
+        project_id,
+        component_id,
+        &timezone::stored(since),
+        until.map(timezone::stored).as_deref(),
+        author.as_deref(),
+    ).map_err(|e| e.to_string())?;
+    Ok(localize_changes(changes, db.timezone()))
 }
 
 #[tauri::command]
-fn create_extraction(
+fn get_all_changes(
     state: State<AppState>,
-    attachment_id: i64,
-    record_type: String,
-    record_id: i64,
-    source_location: Option<String>,
-    source_snippet: Option<String>,
-    confidence: Option<f64>
-) -> Result<database::Extraction, String> {
+    project_id: Option<i64>,
+    component_id: Option<i64>
+) -> Result<Vec<database::Change>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_extraction(
-        attachment_id,
-        &record_type,
-        record_id,
-        source_location.as_deref(),
-        source_snippet.as_deref(),
-        confidence,
-    ).map_err(|e| e.to_string())
+    let changes = db.get_all_changes(project_id, component_id).map_err(|e| e.to_string())?;
+    Ok(localize_changes(changes, db.timezone()))
+}
+
+/// v1.4: Where a time-windowed query starts and, unless it runs to now, ends. A named range
+/// wins over since/until, which win over the last `hours`.
+fn time_window(
+    db: &Database,
+    range: Option<&str>,
+    since: Option<&str>,
+    until: Option<&str>,
+    hours: i32,
+) -> Result<(chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>), String> {
+    let zone = db.timezone();
+    if let Some(range) = range {
+        let (start, end) = timezone::named_range(range, zone, sprint_start(db)?)?;
+        return Ok((start, Some(end)));
+    }
+    let until = until.map(|until| timezone::parse_bound(until, zone, true)).transpose()?;
+    let since = match since {
+        Some(since) => timezone::parse_bound(since, zone, false)?,
+        None => until.unwrap_or_else(chrono::Utc::now) - chrono::Duration::hours(hours.max(1).into()),
+    };
+    if until.is_some_and(|until| until <= since) {
+        return Err("until must be after since".to_string());
+    }
+    Ok((since, until))
+}
+
+/// v1.4: The sprint_start setting, which fixes where every sprint begins.
+fn sprint_start(db: &Database) -> Result<chrono::NaiveDate, String> {
+    match setting_string(db, timezone::SPRINT_START_SETTING) {
+        Some(date) => chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid {} setting '{}' (expected YYYY-MM-DD)", timezone::SPRINT_START_SETTING, date)),
+        None => Ok(timezone::default_sprint_start()),
+    }
+}
+
+/// v1.4: Change times as RFC 3339 on the user's clock rather than bare UTC.
+fn localize_changes(mut changes: Vec<database::Change>, zone: timezone::Zone) -> Vec<database::Change> {
+    for change in &mut changes {
+        change.created_at = timezone::display(&change.created_at, zone);
+    }
+    changes
 }
 
+// ============================================================
+// PROBLEM COMMANDS
+// ============================================================
+
 #[tauri::command]
-fn update_extraction_review(
+fn log_problem(
     state: State<AppState>,
-    id: i64,
-    user_reviewed: bool,
-    user_approved: Option<bool>
-) -> Result<database::Extraction, String> {
+    component_id: i64,
+    title: String,
+    description: Option<String>,
+    severity: Option<String>
+) -> Result<database::Problem, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_extraction_review(id, user_reviewed, user_approved).map_err(|e| e.to_string())
+    let severity = severity.unwrap_or_else(|| "medium".to_string());
+    db.log_problem(component_id, &title, description.as_deref(), &severity)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_extraction(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_problem(state: State<AppState>, id: RecordId) -> Result<database::Problem, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_extraction(id).map_err(|e| e.to_string())
+    let id = id.resolve(&db, "problems")?;
+    db.get_problem(id).map_err(|e| e.to_string())
 }
 
-// ============================================================
-// v1.1: GIT SYNC COMMANDS
-// ============================================================
-
 #[tauri::command]
-fn git_init(data_path: Option<String>) -> Result<serde_json::Value, String> {
-    let path = data_path.unwrap_or_else(get_flowstate_data_path);
-    
-    // Check if already initialized
-    let git_dir = Path::new(&path).join(".git");
-    if git_dir.exists() {
-        return Ok(serde_json::json!({
-            "status": "already_initialized",
-            "path": path,
+fn get_open_problems(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    component_id: Option<i64>,
+    assignee_id: Option<i64>
+) -> Result<Vec<database::Problem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_open_problems(project_id, component_id, assignee_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_all_problems(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    component_id: Option<i64>,
+    assignee_id: Option<i64>,
+    filter: Option<String>,
+    sort: Option<String>
+) -> Result<Vec<database::Problem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut problems = db.get_all_problems(project_id, component_id, assignee_id, sort.as_deref()).map_err(|e| e.to_string())?;
+    if let Some(ids) = filter_ids(&db, &filters::PROBLEMS, project_id, filter.as_deref())? {
+        problems.retain(|p| ids.contains(&p.id));
+    }
+    Ok(problems)
+}
+
+/// v1.4: Ids of the records matching a filter expression, or None when there's no filter.
+fn filter_ids(
+    db: &Database,
+    schema: &filters::FilterSchema,
+    project_id: Option<i64>,
+    filter: Option<&str>
+) -> Result<Option<std::collections::HashSet<i64>>, String> {
+    let Some(expression) = filter.filter(|f| !f.trim().is_empty()) else { return Ok(None) };
+    let compiled = filters::compile(&filters::parse(expression)?, schema, db.timezone())?;
+    db.filtered_ids(schema, project_id, &compiled).map(Some).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_problem(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: RecordId,
+    title: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    severity: Option<String>,
+    root_cause: Option<String>
+) -> Result<database::Problem, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "problems")?;
+    check_lock(&app, &db, "problem", id)?;
+    let previous_status = db.get_problem(id).map_err(|e| e.to_string())?.status;
+    let problem = db.update_problem(id, title.as_deref(), description.as_deref(), status.as_deref(), severity.as_deref(), root_cause.as_deref())
+        .map_err(|e| e.to_string())?;
+    let change = if problem.status != previous_status { "status_changed" } else { "updated" };
+    notify_watchers(&app, &db, "problem", id, change, serde_json::json!(problem));
+    Ok(problem)
+}
+
+#[tauri::command]
+fn delete_problem(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "problems")?;
+    check_lock(&app, &db, "problem", id)?;
+    db.delete_problem(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_problem_tree(state: State<AppState>, problem_id: i64) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_problem_tree(problem_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// SOLUTION ATTEMPT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn log_attempt(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    problem_id: i64,
+    description: String,
+    parent_attempt_id: Option<i64>
+) -> Result<database::SolutionAttempt, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attempt = db.log_attempt(problem_id, &description, parent_attempt_id)
+        .map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "problem", problem_id, "attempt_logged", serde_json::json!(attempt));
+    Ok(attempt)
+}
+
+#[tauri::command]
+fn mark_attempt_outcome(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: i64,
+    outcome: String,
+    notes: Option<String>,
+    confidence: Option<String>
+) -> Result<database::SolutionAttempt, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attempt = db.mark_attempt_outcome(id, &outcome, notes.as_deref(), confidence.as_deref())
+        .map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "problem", attempt.problem_id, "attempt_outcome", serde_json::json!(attempt));
+    Ok(attempt)
+}
+
+#[tauri::command]
+fn start_attempt(state: State<AppState>, id: i64) -> Result<database::SolutionAttempt, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.start_attempt(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn finish_attempt(
+    state: State<AppState>,
+    id: i64,
+    time_spent_minutes: Option<i64>,
+    outcome: Option<String>,
+    notes: Option<String>,
+    confidence: Option<String>
+) -> Result<database::SolutionAttempt, String> {
+    if time_spent_minutes.is_some_and(|m| m < 0) {
+        return Err("Time spent can't be negative".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attempt = db.finish_attempt(id, time_spent_minutes).map_err(|e| e.to_string())?;
+    match outcome {
+        Some(outcome) => db.mark_attempt_outcome(id, &outcome, notes.as_deref(), confidence.as_deref())
+            .map_err(|e| e.to_string()),
+        None => Ok(attempt),
+    }
+}
+
+#[tauri::command]
+fn get_attempts_for_problem(state: State<AppState>, problem_id: i64) -> Result<Vec<database::SolutionAttempt>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_attempts_for_problem(problem_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// SOLUTION COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn mark_problem_solved(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    problem_id: i64,
+    winning_attempt_id: Option<i64>,
+    summary: String,
+    code_snippet: Option<String>,
+    key_insight: Option<String>
+) -> Result<database::Solution, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    check_lock(&app, &db, "problem", problem_id)?;
+    if let Some(previous) = db.get_solution_for_problem(problem_id).map_err(|e| e.to_string())? {
+        check_lock(&app, &db, "solution", previous.id)?;
+    }
+    let solution = db.mark_problem_solved(problem_id, winning_attempt_id, &summary, code_snippet.as_deref(), key_insight.as_deref())
+        .map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "problem", problem_id, "status_changed", serde_json::json!(solution));
+    Ok(solution)
+}
+
+#[tauri::command]
+fn get_solution_for_problem(state: State<AppState>, problem_id: i64) -> Result<Option<database::Solution>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_solution_for_problem(problem_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// TODO COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn add_todo(
+    state: State<AppState>,
+    project_id: i64,
+    title: String,
+    description: Option<String>,
+    priority: Option<String>,
+    component_id: Option<i64>,
+    due_date: Option<String>
+) -> Result<database::Todo, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let priority = priority.unwrap_or_else(|| "medium".to_string());
+    db.add_todo(project_id, &title, description.as_deref(), &priority, component_id, due_date.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_todo(state: State<AppState>, id: RecordId) -> Result<database::Todo, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todos")?;
+    db.get_todo(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_todos(
+    state: State<AppState>,
+    project_id: i64,
+    status: Option<String>,
+    priority: Option<String>,
+    assignee_id: Option<i64>,
+    filter: Option<String>,
+    sort: Option<String>
+) -> Result<Vec<database::Todo>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut todos = db.get_todos(project_id, status.as_deref(), priority.as_deref(), assignee_id, sort.as_deref())
+        .map_err(|e| e.to_string())?;
+    if let Some(ids) = filter_ids(&db, &filters::TODOS, Some(project_id), filter.as_deref())? {
+        todos.retain(|t| ids.contains(&t.id));
+    }
+    Ok(todos)
+}
+
+#[tauri::command]
+fn update_todo(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: RecordId,
+    title: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    due_date: Option<String>
+) -> Result<database::Todo, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todos")?;
+    check_lock(&app, &db, "todo", id)?;
+    let previous_status = db.get_todo(id).map_err(|e| e.to_string())?.status;
+    let todo = db.update_todo(id, title.as_deref(), description.as_deref(), status.as_deref(), priority.as_deref(), due_date.as_deref())
+        .map_err(|e| e.to_string())?;
+    let change = if todo.status != previous_status { "status_changed" } else { "updated" };
+    notify_watchers(&app, &db, "todo", id, change, serde_json::json!(todo));
+    Ok(todo)
+}
+
+#[tauri::command]
+fn delete_todo(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todos")?;
+    check_lock(&app, &db, "todo", id)?;
+    db.delete_todo(id).map_err(|e| e.to_string())
+}
+
+/// Set a todo's estimate in hours, story points, or both; a part left out is cleared.
+#[tauri::command]
+fn set_todo_estimate(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: RecordId,
+    hours: Option<f64>,
+    points: Option<f64>
+) -> Result<database::Todo, String> {
+    if hours.is_some_and(|h| h < 0.0) || points.is_some_and(|p| p < 0.0) {
+        return Err("Estimates can't be negative".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todos")?;
+    check_lock(&app, &db, "todo", id)?;
+    db.set_todo_estimate(id, hours, points).map_err(|e| e.to_string())
+}
+
+/// Record time spent on a todo outside a focus session (those log their own).
+#[tauri::command]
+fn log_todo_time(state: State<AppState>, id: RecordId, minutes: i64) -> Result<database::Todo, String> {
+    if minutes <= 0 {
+        return Err("Logged time must be a positive number of minutes".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todos")?;
+    db.log_todo_time(id, minutes).map_err(|e| e.to_string())
+}
+
+/// Estimates against actuals over a project's finished todos.
+#[tauri::command]
+fn get_estimation_accuracy(state: State<AppState>, project_id: i64) -> Result<database::EstimationAccuracy, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_estimation_accuracy(project_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// LEARNING COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn log_learning(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    project_id: i64,
+    insight: String,
+    category: Option<String>,
+    context: Option<String>,
+    component_id: Option<i64>,
+    source: Option<String>
+) -> Result<database::Learning, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let source = source.unwrap_or_else(|| "experience".to_string());
+    let learning = db.log_learning(project_id, &insight, category.as_deref(), context.as_deref(), component_id, &source)
+        .map_err(|e| e.to_string())?;
+    
+    // v1.4: Flag contradictions with verified learnings so the UI can ask which one wins
+    let existing = db.get_learnings(Some(project_id), None, true, None).map_err(|e| e.to_string())?;
+    let conflicts = contradictions::find_conflicts(&learning.insight, &existing);
+    if !conflicts.is_empty() {
+        let _ = app.emit("learning-conflicts", serde_json::json!({
+            "learning_id": learning.id,
+            "conflicts": conflicts,
         }));
     }
     
-    // Run git init
-    let output = Command::new("git")
-        .args(["init"])
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to run git: {}", e))?;
+    Ok(learning)
+}
+
+#[tauri::command]
+fn get_learning(state: State<AppState>, id: RecordId) -> Result<database::Learning, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "learnings")?;
+    db.get_learning(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_learnings(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    category: Option<String>,
+    verified_only: Option<bool>,
+    sort: Option<String>
+) -> Result<Vec<database::Learning>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let verified_only = verified_only.unwrap_or(false);
+    db.get_learnings(project_id, category.as_deref(), verified_only, sort.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_learning(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: RecordId,
+    insight: Option<String>,
+    category: Option<String>,
+    context: Option<String>,
+    verified: Option<bool>
+) -> Result<database::Learning, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "learnings")?;
+    check_lock(&app, &db, "learning", id)?;
+    let learning = db.update_learning(id, insight.as_deref(), category.as_deref(), context.as_deref(), verified)
+        .map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "learning", id, "updated", serde_json::json!(learning));
+    Ok(learning)
+}
+
+#[tauri::command]
+fn delete_learning(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "learnings")?;
+    check_lock(&app, &db, "learning", id)?;
+    db.delete_learning(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn find_conflicting_learnings(
+    state: State<AppState>,
+    insight: String,
+    project_id: Option<i64>
+) -> Result<Vec<contradictions::LearningConflict>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let existing = db.get_learnings(project_id, None, true, None).map_err(|e| e.to_string())?;
+    Ok(contradictions::find_conflicts(&insight, &existing))
+}
+
+#[tauri::command]
+fn resolve_learning_conflict(
+    state: State<AppState>,
+    winner_id: i64,
+    loser_id: i64
+) -> Result<database::Learning, String> {
+    if winner_id == loser_id {
+        return Err("A learning can't supersede itself".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.resolve_learning_conflict(winner_id, loser_id).map_err(|e| e.to_string())
+}
+
+/// v1.4: A category for an insight from keyword rules, or from the language model with
+/// `use_llm`, shown how the project's learnings are filed already. None when nothing fits.
+#[tauri::command]
+fn suggest_category(
+    state: State<AppState>,
+    insight: String,
+    project_id: Option<i64>,
+    use_llm: Option<bool>
+) -> Result<Option<categorize::CategorySuggestion>, String> {
+    let rules = categorize::suggest(&insight);
+    if !use_llm.unwrap_or(false) {
+        return Ok(rules);
+    }
+    let (client, examples) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (llm_client(&state, &db, "suggest_category")?, category_examples(&db, project_id)?)
+    };
+    // The model is asked without holding the database; if it fails the rules answer
+    let reply = client.complete(&categorize::classify_prompt(&examples), &categorize::numbered(&[insight.as_str()]), 20);
+    Ok(match reply.map(|r| categorize::parse_reply(&r, 1)) {
+        Ok(categories) => categories[0].map(categorize::from_llm).or(rules),
+        Err(_) => rules,
+    })
+}
+
+/// v1.4: Suggested categories for a project's uncategorized learnings, to review and pass
+/// (as amended) to apply_learning_categories. Nothing is changed here. With `use_llm` the
+/// language model classifies them in batches; those it can't place fall back to the rules.
+#[tauri::command]
+fn auto_categorize_learnings(
+    state: State<AppState>,
+    project_id: i64,
+    use_llm: Option<bool>
+) -> Result<Vec<categorize::CategoryProposal>, String> {
+    let (learnings, llm) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let learnings: Vec<database::Learning> = db.get_learnings(Some(project_id), None, false, Some("created:asc"))
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|l| l.category.is_none())
+            .collect();
+        let llm = if use_llm.unwrap_or(false) && !learnings.is_empty() {
+            Some((llm_client(&state, &db, "auto_categorize_learnings")?, category_examples(&db, Some(project_id))?))
+        } else {
+            None
+        };
+        (learnings, llm)
+    };
+    let mut proposals: Vec<categorize::CategoryProposal> = learnings.iter()
+        .map(|l| categorize::CategoryProposal {
+            learning_id: l.id,
+            insight: l.insight.clone(),
+            suggestion: categorize::suggest(&format!("{}\n{}", l.insight, l.context.as_deref().unwrap_or_default())),
+        })
+        .collect();
+    if let Some((client, examples)) = llm {
+        let system = categorize::classify_prompt(&examples);
+        for batch in proposals.chunks_mut(categorize::LLM_BATCH_SIZE) {
+            let insights: Vec<&str> = batch.iter().map(|p| p.insight.as_str()).collect();
+            let reply = client.complete(&system, &categorize::numbered(&insights), 20 * batch.len() as u32)?;
+            let categories = categorize::parse_reply(&reply, insights.len());
+            for (proposal, category) in batch.iter_mut().zip(categories) {
+                if let Some(category) = category {
+                    proposal.suggestion = Some(categorize::from_llm(category));
+                }
+            }
+        }
+    }
+    Ok(proposals)
+}
+
+/// v1.4: File learnings under the categories reviewed from auto_categorize_learnings.
+/// Returns how many changed.
+#[tauri::command]
+fn apply_learning_categories(state: State<AppState>, assignments: Vec<categorize::CategoryAssignment>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let assignments: Vec<(i64, &str)> = assignments.iter().map(|a| (a.learning_id, a.category.as_str())).collect();
+    db.set_learning_categories(&assignments).map_err(|e| e.to_string())
+}
+
+/// A few of the project's categorized learnings from each category, newest first, to show
+/// the language model how things are filed.
+fn category_examples(db: &Database, project_id: Option<i64>) -> Result<Vec<(String, String)>, String> {
+    const PER_CATEGORY: usize = 2;
+    let mut examples = Vec::new();
+    for category in categorize::CATEGORIES {
+        let learnings = db.get_learnings(project_id, Some(category), false, None).map_err(|e| e.to_string())?;
+        examples.extend(learnings.into_iter().take(PER_CATEGORY).map(|l| (category.to_string(), first_line_title(&l.insight))));
+    }
+    Ok(examples)
+}
+
+#[tauri::command]
+fn get_due_reviews(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    limit: Option<i64>
+) -> Result<Vec<database::Learning>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_due_reviews(project_id, limit.unwrap_or(20)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn record_review(state: State<AppState>, id: i64, remembered: bool) -> Result<database::Learning, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.record_review(id, remembered).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// SEARCH COMMAND
+// ============================================================
+
+#[tauri::command]
+fn search(
+    state: State<AppState>,
+    query: String,
+    project_id: Option<i64>,
+    limit: Option<i32>,
+    include_file_content: Option<bool>,
+    filter: Option<String>
+) -> Result<Vec<serde_json::Value>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(10);
+    // include_file_content is for future semantic search in files
+    let _ = include_file_content;
+
+    // v1.4: Free text in the filter joins the query; its field terms narrow the results to
+    // the types that have those fields
+    let terms = filters::parse(filter.as_deref().unwrap_or_default())?;
+    let (words, terms): (Vec<_>, Vec<_>) = terms.into_iter().partition(|t| t.field.is_none() && !t.negated);
+    let query = std::iter::once(query.trim().to_string())
+        .chain(words.into_iter().map(|t| t.values.join(" ")))
+        .filter(|q| !q.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut results = if terms.is_empty() {
+        db.search(&query, project_id, limit).map_err(|e| e.to_string())?
+    } else {
+        filtered_search(&db, &query, &terms, project_id, limit)?
+    };
+    highlight_results(&mut results, &query);
+    Ok(results)
+}
+
+/// v1.4: Search results narrowed to the records that match field terms of a filter.
+fn filtered_search(
+    db: &Database,
+    query: &str,
+    terms: &[filters::Term],
+    project_id: Option<i64>,
+    limit: i32
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut matching = HashMap::new();
+    let mut first_error = None;
+    let zone = db.timezone();
+    for schema in [&filters::PROBLEMS, &filters::LEARNINGS] {
+        match filters::compile(terms, schema, zone) {
+            Ok(compiled) => {
+                let ids = db.filtered_ids(schema, project_id, &compiled).map_err(|e| e.to_string())?;
+                matching.insert(schema.entity, ids);
+            }
+            Err(e) => { first_error.get_or_insert(e); }
+        }
+    }
+    if matching.is_empty() {
+        return Err(first_error.unwrap_or_default());
+    }
+    let mut per_type: HashMap<String, i32> = HashMap::new();
+    let results = db.search(query, project_id, FILTERED_SEARCH_LIMIT).map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|result| {
+            let entity = result["type"].as_str().unwrap_or_default();
+            let id = result["id"].as_i64().unwrap_or_default();
+            matching.get(entity).is_some_and(|ids| ids.contains(&id))
+        })
+        .filter(|result| {
+            let count = per_type.entry(result["type"].as_str().unwrap_or_default().to_string()).or_default();
+            *count += 1;
+            *count <= limit
+        })
+        .collect();
+    Ok(results)
+}
+
+/// v1.4: Give each result `highlights`, the character ranges the query's words matched in
+/// its title and snippet, and `context`, the stretch of the snippet around its first match.
+fn highlight_results(results: &mut [serde_json::Value], query: &str) {
+    let terms = highlight::terms(query);
+    for result in results.iter_mut() {
+        let mut highlights = serde_json::Map::new();
+        for field in ["title", "snippet"] {
+            if let Some(text) = result[field].as_str() {
+                highlights.insert(field.to_string(), serde_json::json!(highlight::matches(text, &terms)));
+            }
+        }
+        let context = result["snippet"].as_str()
+            .and_then(|snippet| highlight::context(snippet, &highlight::matches(snippet, &terms)));
+        result["highlights"] = serde_json::Value::Object(highlights);
+        result["context"] = serde_json::json!(context);
+    }
+}
+
+/// v1.4: Counts behind the filter bar's chips, e.g. {"status": {"open": 4, "solved": 9}}.
+/// Leaving out `facets` counts by every field that has a handful of values, plus tags;
+/// `filter` counts only the records the current filter expression matches.
+#[tauri::command]
+fn get_facet_counts(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    entity: String,
+    facets: Option<Vec<String>>,
+    filter: Option<String>
+) -> Result<BTreeMap<String, BTreeMap<String, i64>>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let schema = filters::schema(&entity)?;
+    let facets: Vec<&str> = match &facets {
+        Some(facets) => facets.iter().map(String::as_str).collect(),
+        None => schema.fields.iter()
+            .filter(|f| f.name == "tag" || filters::facet_column(f).is_some())
+            .map(|f| f.name)
+            .collect(),
+    };
+    let compiled = filters::compile(&filters::parse(filter.as_deref().unwrap_or_default())?, schema, db.timezone())?;
+    db.get_facet_counts(schema, project_id, &facets, &compiled).map_err(|e| e.to_string())
+}
+
+/// v1.4: Command palette search over project, component, problem, todo and attachment
+/// names. Each item carries what the palette needs to navigate: entity_type, id,
+/// project_id, component_id, plus the matched character positions for highlighting.
+#[tauri::command]
+fn quick_open(state: State<AppState>, query: String, limit: Option<usize>) -> Result<Vec<quickopen::QuickOpenItem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let candidates = db.get_quick_open_candidates(&quickopen::like_pattern(&query)).map_err(|e| e.to_string())?;
+    Ok(quickopen::rank(&query, candidates, limit.unwrap_or(QUICK_OPEN_LIMIT).clamp(1, 200)))
+}
+
+// ============================================================
+// STORY GENERATION COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn generate_project_story(state: State<AppState>, project_id: i64) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    build_project_story(&db, project_id)
+}
+
+fn build_project_story(db: &Database, project_id: i64) -> Result<serde_json::Value, String> {
+    // Gather all project data
+    let project = db.get_project(project_id).map_err(|e| e.to_string())?;
+    let components = db.list_components(project_id).map_err(|e| e.to_string())?;
+    let all_problems = db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())?;
+    let all_changes = db.get_all_changes(Some(project_id), None).map_err(|e| e.to_string())?;
+    let learnings = db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?;
+    let todos = db.get_todos(project_id, None, None, None, None).map_err(|e| e.to_string())?;
+    let stats = db.get_project_stats(project_id).map_err(|e| e.to_string())?;
+    let attachments = db.get_attachments(project_id, None, None, None).map_err(|e| e.to_string())?;
+    let annotations = db.get_project_annotations(project_id).map_err(|e| e.to_string())?;
+    
+    // Count solved vs open problems
+    let solved_count = all_problems.iter().filter(|p| p.status == "solved").count();
+    let open_count = all_problems.iter().filter(|p| p.status != "solved").count();
+    
+    Ok(serde_json::json!({
+        "project": project,
+        "components": components,
+        "problems": all_problems,
+        "changes": all_changes,
+        "learnings": learnings,
+        "todos": todos,
+        "attachments": attachments,
+        "annotations": annotations,
+        "stats": stats,
+        "summary": {
+            "total_problems": all_problems.len(),
+            "solved_problems": solved_count,
+            "open_problems": open_count,
+            "total_changes": all_changes.len(),
+            "total_learnings": learnings.len(),
+            "total_components": components.len(),
+            "total_attachments": attachments.len(),
+        }
+    }))
+}
+
+#[tauri::command]
+fn generate_problem_journey(state: State<AppState>, problem_id: i64) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    
+    let problem = db.get_problem(problem_id).map_err(|e| e.to_string())?;
+    let attempts = db.get_attempts_for_problem(problem_id).map_err(|e| e.to_string())?;
+    let solution = db.get_solution_for_problem(problem_id).map_err(|e| e.to_string())?;
+    let checklist = db.get_checklist_items(problem_id).map_err(|e| e.to_string())?;
+    let time_sunk_minutes = db.get_time_sunk(problem_id).map_err(|e| e.to_string())?;
+    
+    // Build the journey with timeline
+    let mut journey_steps = Vec::new();
+    
+    // Add problem creation as first step
+    journey_steps.push(serde_json::json!({
+        "type": "problem_identified",
+        "title": problem.title.clone(),
+        "description": problem.description.clone(),
+        "timestamp": problem.created_at.clone(),
+        "severity": problem.severity.clone(),
+    }));
+    
+    // Add each attempt
+    for attempt in &attempts {
+        journey_steps.push(serde_json::json!({
+            "type": "attempt",
+            "id": attempt.id,
+            "description": attempt.description.clone(),
+            "outcome": attempt.outcome.clone(),
+            "notes": attempt.notes.clone(),
+            "timestamp": attempt.created_at.clone(),
+            "parent_attempt_id": attempt.parent_attempt_id,
+            "time_spent_minutes": attempt.time_spent_minutes,
+        }));
+    }
+    
+    // v1.4: Add each verified checklist hypothesis
+    for item in checklist.iter().filter(|i| i.checked) {
+        journey_steps.push(serde_json::json!({
+            "type": "checklist_checked",
+            "id": item.id,
+            "description": item.text.clone(),
+            "timestamp": item.checked_at.clone().unwrap_or_else(|| item.created_at.clone()),
+        }));
+    }
+    
+    // Add solution if exists
+    if let Some(sol) = &solution {
+        journey_steps.push(serde_json::json!({
+            "type": "solved",
+            "summary": sol.summary.clone(),
+            "key_insight": sol.key_insight.clone(),
+            "code_snippet": sol.code_snippet.clone(),
+            "timestamp": sol.created_at.clone(),
+            "winning_attempt_id": sol.winning_attempt_id,
+        }));
+    }
+    
+    // Keep the timeline chronological now that checklist steps interleave with attempts
+    journey_steps.sort_by(|a, b| {
+        a["timestamp"].as_str().unwrap_or("").cmp(b["timestamp"].as_str().unwrap_or(""))
+    });
+    
+    Ok(serde_json::json!({
+        "problem": problem,
+        "attempts": attempts,
+        "solution": solution,
+        "checklist": checklist,
+        "journey": journey_steps,
+        "stats": {
+            "total_attempts": attempts.len(),
+            "failed_attempts": attempts.iter().filter(|a| a.outcome.as_deref() == Some("failure")).count(),
+            "is_solved": solution.is_some(),
+            "checklist_total": checklist.len(),
+            "checklist_checked": checklist.iter().filter(|i| i.checked).count(),
+            "time_sunk_minutes": time_sunk_minutes,
+            "failed_minutes": attempts.iter()
+                .filter(|a| matches!(a.outcome.as_deref(), Some("failure") | Some("abandoned")))
+                .filter_map(|a| a.time_spent_minutes)
+                .sum::<i64>(),
+            "attempts_in_progress": attempts.iter().filter(|a| a.started_at.is_some() && a.ended_at.is_none()).count(),
+        }
+    }))
+}
+
+/// v1.4: Write the project story as a PDF for people who won't open FlowState. `template`
+/// is "narrative" (default, StoryMode's chapters) or "retrospective". JPEG and PNG
+/// attachments are embedded: a problem's images with that problem, the rest at the end.
+#[tauri::command]
+fn export_story_pdf(
+    state: State<AppState>,
+    project_id: i64,
+    template: Option<String>,
+    path: String
+) -> Result<serde_json::Value, String> {
+    let template = template.unwrap_or_else(|| "narrative".to_string());
+    let (source, embedded) = gather_story_source(&state, project_id)?;
+    
+    let blocks = story::blocks(&template, &source)?;
+    let bytes = pdf::render(&format!("{} — Project Story", source.project.name), &blocks)?;
+    std::fs::write(&path, &bytes).map_err(|e| format!("Failed to write PDF: {}", e))?;
+    Ok(serde_json::json!({
+        "path": path,
+        "template": template,
+        "size_bytes": bytes.len(),
+        "images": embedded,
+    }))
+}
+
+/// The project story's records plus its image attachments (returned with the number of
+/// images embedded). Shared by the PDF export and the Confluence publisher.
+fn gather_story_source(state: &AppState, project_id: i64) -> Result<(story::StorySource, usize), String> {
+    // Gather records under the lock; image files are read after it's released
+    let (mut source, attachments) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut problems = Vec::new();
+        for problem in db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())? {
+            let attempts = db.get_attempts_for_problem(problem.id).map_err(|e| e.to_string())?;
+            problems.push(story::StoryProblem {
+                solution: db.get_solution_for_problem(problem.id).map_err(|e| e.to_string())?,
+                attempts: attempts.len(),
+                failed_attempts: attempts.iter().filter(|a| a.outcome.as_deref() == Some("failure")).count(),
+                minutes: db.get_time_sunk(problem.id).map_err(|e| e.to_string())?,
+                images: Vec::new(),
+                problem,
+            });
+        }
+        let attachments: Vec<database::Attachment> = db.get_attachments(project_id, None, None, None)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|a| matches!(a.file_type.to_lowercase().as_str(), "png" | "jpg" | "jpeg"))
+            .take(MAX_STORY_IMAGES)
+            .collect();
+        let source = story::StorySource {
+            project: db.get_project(project_id).map_err(|e| e.to_string())?,
+            components: db.list_components(project_id).map_err(|e| e.to_string())?,
+            problems,
+            learnings: db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?,
+            todos: db.get_todos(project_id, None, None, None, None).map_err(|e| e.to_string())?,
+            images: Vec::new(),
+        };
+        (source, attachments)
+    };
+    
+    let mut embedded = 0;
+    for attachment in attachments {
+        // Missing or oversized files are left out rather than failing the export
+        if !std::fs::metadata(&attachment.file_path).is_ok_and(|m| m.len() <= MAX_STORY_IMAGE_BYTES) {
+            continue;
+        }
+        let Ok(data) = std::fs::read(&attachment.file_path) else { continue };
+        let image = story::StoryImage {
+            data,
+            caption: attachment.user_description.clone()
+                .or_else(|| attachment.ai_description.clone())
+                .unwrap_or_else(|| attachment.file_name.clone()),
+        };
+        let owner = attachment.problem_id.and_then(|id| source.problems.iter_mut().find(|p| p.problem.id == id));
+        match owner {
+            Some(problem) => problem.images.push(image),
+            None => source.images.push(image),
+        }
+        embedded += 1;
+    }
+    Ok((source, embedded))
+}
+
+/// v1.4: Write the project as a static HTML site into `out_dir` (created if needed):
+/// overview, per-problem journeys, learnings, timeline and client-side search. The folder
+/// can be opened from disk or pushed to a GitHub Pages branch as is.
+#[tauri::command]
+fn export_static_site(
+    state: State<AppState>,
+    project_id: i64,
+    out_dir: String
+) -> Result<serde_json::Value, String> {
+    let source = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let components = db.list_components(project_id).map_err(|e| e.to_string())?;
+        let mut problems = Vec::new();
+        for problem in db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())? {
+            problems.push(site::SiteProblem {
+                component_name: components.iter()
+                    .find(|c| c.id == problem.component_id)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default(),
+                attempts: db.get_attempts_for_problem(problem.id).map_err(|e| e.to_string())?,
+                solution: db.get_solution_for_problem(problem.id).map_err(|e| e.to_string())?,
+                checklist: db.get_checklist_items(problem.id).map_err(|e| e.to_string())?,
+                problem,
+            });
+        }
+        site::SiteSource {
+            project: db.get_project(project_id).map_err(|e| e.to_string())?,
+            components,
+            problems,
+            learnings: db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?,
+            changes: db.get_all_changes(Some(project_id), None).map_err(|e| e.to_string())?,
+        }
+    };
+    
+    let root = std::path::Path::new(&out_dir);
+    let files = site::build(&source);
+    for (relative, contents) in &files {
+        let path = root.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(serde_json::json!({
+        "path": out_dir,
+        "pages": files.iter().filter(|(name, _)| name.ends_with(".html")).count(),
+        "files": files.len(),
+    }))
+}
+
+/// v1.4: Write a project as plain text files for a RAG pipeline or a Claude Project:
+/// problems with their attempts and solutions, learnings, and what's known of each
+/// attachment (descriptions, extracted snippets, the text of text files), cut into files
+/// of at most `max_tokens_per_file` (default CORPUS_TOKENS_PER_FILE). Files a previous
+/// export into `path` wrote are replaced.
+#[tauri::command]
+fn export_ai_corpus(
+    state: State<AppState>,
+    project_id: i64,
+    path: String,
+    max_tokens_per_file: Option<usize>
+) -> Result<serde_json::Value, String> {
+    let max_tokens = max_tokens_per_file.unwrap_or(CORPUS_TOKENS_PER_FILE);
+    if max_tokens < MIN_CORPUS_TOKENS_PER_FILE {
+        return Err(format!("max_tokens_per_file must be at least {}", MIN_CORPUS_TOKENS_PER_FILE));
+    }
+    let mut source = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let components = db.list_components(project_id).map_err(|e| e.to_string())?;
+        let component_name = |id: Option<i64>| components.iter().find(|c| Some(c.id) == id).map(|c| c.name.clone());
+        let mut problems = Vec::new();
+        for problem in db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())? {
+            problems.push(corpus::CorpusProblem {
+                component_name: component_name(Some(problem.component_id)).unwrap_or_default(),
+                attempts: db.get_attempts_for_problem(problem.id).map_err(|e| e.to_string())?,
+                solution: db.get_solution_for_problem(problem.id).map_err(|e| e.to_string())?,
+                problem,
+            });
+        }
+        let learnings = db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|learning| {
+                let component = component_name(learning.component_id);
+                (learning, component)
+            })
+            .collect();
+        let mut attachments = Vec::new();
+        for attachment in db.get_attachments(project_id, None, None, None).map_err(|e| e.to_string())? {
+            attachments.push(corpus::CorpusAttachment {
+                extractions: db.get_extractions_for_attachment(attachment.id).map_err(|e| e.to_string())?,
+                text: None,
+                attachment,
+            });
+        }
+        corpus::CorpusSource {
+            project: db.get_project(project_id).map_err(|e| e.to_string())?,
+            problems,
+            learnings,
+            attachments,
+        }
+    };
+    // Files are read without holding the database
+    for item in &mut source.attachments {
+        let kind = item.attachment.file_type.to_lowercase();
+        let small = item.attachment.file_size.is_none_or(|size| size <= MAX_CORPUS_FILE_BYTES);
+        if corpus::TEXT_TYPES.contains(&kind.as_str()) && small {
+            item.text = std::fs::read(&item.attachment.file_path).ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        }
+    }
+
+    let root = std::path::Path::new(&path);
+    let manifest_path = root.join(corpus::MANIFEST);
+    if let Ok(previous) = std::fs::read_to_string(&manifest_path) {
+        if let Ok(previous) = serde_json::from_str::<corpus::Manifest>(&previous) {
+            for file in previous.files {
+                let _ = std::fs::remove_file(root.join(&file.path));
+            }
+        }
+    }
+    let files = corpus::build(&source, max_tokens);
+    for (file, contents) in &files {
+        let file_path = root.join(&file.path);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&file_path, contents).map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))?;
+    }
+    let manifest = corpus::Manifest {
+        project: source.project.name.clone(),
+        exported_at: timezone::rfc3339(chrono::Utc::now()),
+        max_tokens_per_file: max_tokens,
+        files: files.into_iter().map(|(file, _)| file).collect(),
+    };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(&manifest_path, json).map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+    Ok(serde_json::json!({
+        "path": path,
+        "files": manifest.files.len(),
+        "tokens": manifest.files.iter().map(|f| f.tokens).sum::<usize>(),
+    }))
+}
+
+// ============================================================
+// v1.1: FILE ATTACHMENT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn attach_file(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    project_id: i64,
+    file_path: String,
+    component_id: Option<i64>,
+    problem_id: Option<i64>,
+    user_description: Option<String>,
+    copy_to_bundle: Option<bool>
+) -> Result<database::Attachment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    
+    let path = Path::new(&file_path);
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    
+    let file_type = path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    
+    // Get file size
+    let file_size = std::fs::metadata(&file_path)
+        .map(|m| m.len() as i64)
+        .ok();
+    
+    // Calculate file hash (SHA256)
+    let file_hash = calculate_file_hash(&file_path).ok();
+    
+    let copy_to_bundle = copy_to_bundle.unwrap_or(true);
+    let is_external = !copy_to_bundle;
+    
+    // If copying to bundle, copy the file
+    let final_path = if copy_to_bundle {
+        copy_file_to_project_bundle(&file_path, project_id)?
+    } else {
+        file_path.clone()
+    };
+    
+    let attachment = db.create_attachment(
+        project_id,
+        &file_name,
+        &final_path,
+        &file_type,
+        file_size,
+        file_hash.as_deref(),
+        is_external,
+        component_id,
+        problem_id,
+        user_description.as_deref(),
+        None, // tags
+    ).map_err(|e| e.to_string())?;
+    
+    let attachment = index_attachment(&db, attachment)?;
+    
+    // v1.4: One queued job picks up every file attached before it runs, so an import of
+    // many files is described in a few requests
+    let auto_describe = setting_string(&db, "ai_auto_describe_files").as_deref() == Some("true");
+    if auto_describe && setting_string(&db, "llm_model").is_some() && descriptions::kind_of(&file_type).is_some() {
+        let _ = queue_describe_job(&db, project_id, None, false);
+    }
+    
+    // v1.4: Only bundled copies count against the quota
+    if copy_to_bundle {
+        if let Ok(usage) = storage_usage(&db, None) {
+            if usage["over_quota"].as_bool().unwrap_or(false) {
+                let _ = app.emit("storage-quota-exceeded", &usage);
+            }
+        }
+    }
+    
+    Ok(attachment)
+}
+
+/// v1.4: Everything read from a file's contents when it's attached or changes: card metadata
+/// (dimensions, pages, lines, headers), then headings, notebook cells and code symbols as
+/// content locations. A parse failure doesn't fail the attach.
+fn index_attachment(db: &Database, attachment: database::Attachment) -> Result<database::Attachment, String> {
+    let metadata = preview::extract(Path::new(&attachment.file_path), &attachment.file_type);
+    let attachment = db.set_attachment_preview(attachment.id, metadata.map(|m| m.to_string()).as_deref())
+        .map_err(|e| e.to_string())?;
+    if let Some(locations) = section_locations(&attachment) {
+        let _ = db.replace_indexed_locations(attachment.id, sections::INDEXER, &locations);
+    }
+    if let Some(locations) = symbol_locations(&attachment) {
+        let _ = db.replace_indexed_locations(attachment.id, symbols::INDEXER, &locations);
+    }
+    Ok(attachment)
+}
+
+#[tauri::command]
+fn get_attachments(
+    state: State<AppState>,
+    project_id: i64,
+    component_id: Option<i64>,
+    problem_id: Option<i64>,
+    sort: Option<String>
+) -> Result<Vec<database::Attachment>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_attachments(project_id, component_id, problem_id, sort.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_attachment(state: State<AppState>, id: RecordId) -> Result<database::Attachment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "attachments")?;
+    db.get_attachment(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_attachment(
+    state: State<AppState>,
+    id: RecordId,
+    user_description: Option<String>,
+    tags: Option<String>,
+    ai_description: Option<String>,
+    ai_summary: Option<String>,
+    content_extracted: Option<bool>
+) -> Result<database::Attachment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "attachments")?;
+    db.update_attachment(
+        id,
+        user_description.as_deref(),
+        tags.as_deref(),
+        ai_description.as_deref(),
+        ai_summary.as_deref(),
+        content_extracted,
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn remove_attachment(state: State<AppState>, id: RecordId, delete_file: Option<bool>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "attachments")?;
+    
+    // Get attachment first to check if we need to delete the file
+    if delete_file.unwrap_or(false) {
+        if let Ok(attachment) = db.get_attachment(id) {
+            if !attachment.is_external {
+                // Delete the file from bundle
+                let _ = std::fs::remove_file(&attachment.file_path);
+            }
+        }
+    }
+    
+    db.delete_attachment(id).map_err(|e| e.to_string())
+}
+
+/// v1.4: Re-read an attachment's card metadata, e.g. for files attached before it existed
+/// or external files that changed since.
+#[tauri::command]
+fn refresh_attachment_preview(state: State<AppState>, id: i64) -> Result<database::Attachment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attachment = db.get_attachment(id).map_err(|e| e.to_string())?;
+    let metadata = preview::extract(Path::new(&attachment.file_path), &attachment.file_type);
+    db.set_attachment_preview(id, metadata.map(|m| m.to_string()).as_deref()).map_err(|e| e.to_string())
+}
+
+/// v1.4: Rebuild the heading/cell content locations of a markdown or notebook attachment.
+/// Locations added by hand or by other indexers are left alone.
+#[tauri::command]
+fn index_attachment_sections(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::ContentLocation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attachment = db.get_attachment(attachment_id).map_err(|e| e.to_string())?;
+    let locations = section_locations(&attachment)
+        .ok_or_else(|| format!("{} is not a readable markdown file or notebook", attachment.file_name))?;
+    db.replace_indexed_locations(attachment_id, sections::INDEXER, &locations).map_err(|e| e.to_string())
+}
+
+/// None for files that aren't markdown or notebooks, or can't be read.
+fn section_locations(attachment: &database::Attachment) -> Option<Vec<database::IndexedLocation>> {
+    let kind = attachment.file_type.to_lowercase();
+    if !matches!(kind.as_str(), "md" | "markdown" | "ipynb") {
+        return None;
+    }
+    let bytes = std::fs::read(&attachment.file_path).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    if kind == "ipynb" {
+        sections::notebook_sections(&text)
+    } else {
+        Some(sections::markdown_sections(&text))
+    }
+}
+
+/// v1.4: Rebuild the function/class/type content locations of a source code attachment.
+/// Links from problems to a symbol survive as long as the symbol keeps its name.
+#[tauri::command]
+fn index_attachment_symbols(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::ContentLocation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attachment = db.get_attachment(attachment_id).map_err(|e| e.to_string())?;
+    if !symbols::supports(&attachment.file_type) {
+        return Err(format!("Symbol indexing isn't available for .{} files", attachment.file_type));
+    }
+    let locations = symbol_locations(&attachment)
+        .ok_or_else(|| format!("Failed to parse {}", attachment.file_name))?;
+    db.replace_indexed_locations(attachment_id, symbols::INDEXER, &locations).map_err(|e| e.to_string())
+}
+
+/// None for unsupported languages or files that can't be read.
+fn symbol_locations(attachment: &database::Attachment) -> Option<Vec<database::IndexedLocation>> {
+    if !symbols::supports(&attachment.file_type) {
+        return None;
+    }
+    let bytes = std::fs::read(&attachment.file_path).ok()?;
+    symbols::code_symbols(&String::from_utf8_lossy(&bytes), &attachment.file_type)
+}
+
+/// v1.4: Find crashes and errors in a log attachment and propose a problem for each distinct
+/// one. Nothing is created: each proposal carries the problem fields and content locations
+/// (line ranges) ready for log_problem and create_content_location once the user accepts it.
+#[tauri::command]
+fn analyze_log_attachment(
+    state: State<AppState>,
+    attachment_id: i64,
+    rules: Option<Vec<logparse::LogRule>>
+) -> Result<serde_json::Value, String> {
+    let attachment = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_attachment(attachment_id).map_err(|e| e.to_string())?
+    };
+    let bytes = std::fs::read(&attachment.file_path).map_err(|e| format!("Failed to read {}: {}", attachment.file_name, e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let findings = logparse::analyze(&text, rules.as_deref().unwrap_or_default());
+    
+    let proposals: Vec<serde_json::Value> = findings.iter().map(|finding| {
+        let first = &finding.locations[0];
+        serde_json::json!({
+            "kind": finding.kind,
+            "occurrences": finding.occurrences,
+            "problem": {
+                "title": finding.title,
+                "description": format!(
+                    "Seen {} time(s) in {}, first at line {}.\n\n```\n{}\n```",
+                    finding.occurrences, attachment.file_name, first.start_line, first.snippet
+                ),
+                "severity": finding.severity,
+            },
+            "content_locations": finding.locations.iter().map(|l| serde_json::json!({
+                "attachment_id": attachment_id,
+                "description": finding.title,
+                "category": "data",
+                "location_type": "line",
+                "start_location": l.start_line.to_string(),
+                "end_location": l.end_line.to_string(),
+                "snippet": l.snippet,
+            })).collect::<Vec<_>>(),
+        })
+    }).collect();
+    
+    Ok(serde_json::json!({
+        "attachment_id": attachment_id,
+        "line_count": text.lines().count(),
+        "proposals": proposals,
+    }))
+}
+
+// ============================================================
+// v1.4: DIRECTORY WATCH COMMANDS
+// ============================================================
+
+/// Keep files under `path` matching `globs` (e.g. `docs/**/*.md`) attached to the project.
+/// Matching files are attached in place (not copied) and refreshed whenever they change;
+/// the first scan runs in the background and reports through `directory-files-synced`.
+#[tauri::command]
+fn watch_project_directory(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    project_id: i64,
+    path: String,
+    globs: Vec<String>
+) -> Result<database::DirectoryWatch, String> {
+    let globs: Vec<String> = globs.into_iter().map(|g| g.trim().to_string()).filter(|g| !g.is_empty()).collect();
+    if globs.is_empty() {
+        return Err("At least one file pattern is required".to_string());
+    }
+    let dir = std::fs::canonicalize(&path).map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", path));
+    }
+    
+    let watch = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.create_directory_watch(project_id, &dir.to_string_lossy(), &globs).map_err(|e| e.to_string())?
+    };
+    // New patterns may match files the previous scan skipped
+    if let Ok(mut scanned) = state.scanned_files.lock() {
+        scanned.remove(&watch.id);
+    }
+    
+    let handle = app.clone();
+    let initial = watch.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = sync_directory_watch(&handle, &initial) {
+            eprintln!("Failed to scan {}: {}", initial.path, e);
+        }
+    });
+    Ok(watch)
+}
+
+#[tauri::command]
+fn get_directory_watches(state: State<AppState>, project_id: Option<i64>) -> Result<Vec<database::DirectoryWatch>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_directory_watches(project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_directory_watch_enabled(state: State<AppState>, id: i64, enabled: bool) -> Result<database::DirectoryWatch, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_directory_watch_enabled(id, enabled).map_err(|e| e.to_string())
+}
+
+/// Stop watching; files already attached stay attached.
+#[tauri::command]
+fn unwatch_project_directory(state: State<AppState>, id: i64) -> Result<(), String> {
+    if let Ok(mut scanned) = state.scanned_files.lock() {
+        scanned.remove(&id);
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_directory_watch(id).map_err(|e| e.to_string())
+}
+
+/// Scan a watch right away instead of waiting for the next poll.
+#[tauri::command]
+fn sync_directory_watch_now(app: tauri::AppHandle, state: State<AppState>, id: i64) -> Result<serde_json::Value, String> {
+    let watch = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_directory_watch(id).map_err(|e| e.to_string())?
+    };
+    sync_directory_watch(&app, &watch)
+}
+
+/// Attach new matching files and refresh changed ones. Files are only hashed when their
+/// modification time moved, and only re-indexed when the hash differs from the attachment's.
+fn sync_directory_watch(app: &tauri::AppHandle, watch: &database::DirectoryWatch) -> Result<serde_json::Value, String> {
+    let state = app.state::<AppState>();
+    let files = dirwatch::scan(Path::new(&watch.path), &watch.globs);
+    
+    let mut scanned = state.scanned_files.lock().map_err(|e| e.to_string())?;
+    let seen = scanned.entry(watch.id).or_default();
+    let changed: Vec<(dirwatch::ScannedFile, String, Option<String>, Option<i64>)> = files
+        .into_iter()
+        .filter(|f| seen.get(&f.path) != Some(&f.modified))
+        .map(|f| {
+            let path = f.path.to_string_lossy().to_string();
+            let hash = calculate_file_hash(&path).ok();
+            let size = std::fs::metadata(&f.path).map(|m| m.len() as i64).ok();
+            (f, path, hash, size)
+        })
+        .collect();
+    
+    let mut attached = Vec::new();
+    let mut refreshed = Vec::new();
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        // v1.4: New files go to the component whose paths cover them
+        let attribution = db.component_attribution(watch.project_id).map_err(|e| e.to_string())?;
+        for (file, path, hash, size) in changed {
+            match db.get_attachment_by_path(watch.project_id, &path).map_err(|e| e.to_string())? {
+                Some(existing) if existing.file_hash == hash => {}
+                Some(existing) => {
+                    let attachment = db.refresh_attachment_file(existing.id, size, hash.as_deref()).map_err(|e| e.to_string())?;
+                    refreshed.push(index_attachment(&db, attachment)?.id);
+                }
+                None => {
+                    let file_name = file.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "unknown".to_string());
+                    let file_type = file.path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "unknown".to_string());
+                    let attachment = db.create_attachment(
+                        watch.project_id, &file_name, &path, &file_type, size, hash.as_deref(),
+                        true, attribution.component_for(&path), None, None, None,
+                    ).map_err(|e| e.to_string())?;
+                    attached.push(index_attachment(&db, attachment)?.id);
+                }
+            }
+            seen.insert(file.path, file.modified);
+        }
+        db.mark_directory_watch_scanned(watch.id).map_err(|e| e.to_string())?;
+    }
+    
+    let summary = serde_json::json!({
+        "watch_id": watch.id,
+        "project_id": watch.project_id,
+        "attached": attached,
+        "refreshed": refreshed,
+    });
+    if !attached.is_empty() || !refreshed.is_empty() {
+        let _ = app.emit("directory-files-synced", &summary);
+    }
+    Ok(summary)
+}
+
+// ============================================================
+// v1.4: ANNOTATION COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_annotations(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::Annotation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_annotations(attachment_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_annotation(
+    state: State<AppState>,
+    attachment_id: i64,
+    annotation: database::AnnotationInput
+) -> Result<database::Annotation, String> {
+    let annotation = annotation.validated()?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_annotation(attachment_id, &annotation).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_annotation(
+    state: State<AppState>,
+    id: RecordId,
+    annotation: database::AnnotationInput
+) -> Result<database::Annotation, String> {
+    let annotation = annotation.validated()?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "annotations")?;
+    db.update_annotation(id, &annotation).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_annotation(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "annotations")?;
+    db.delete_annotation(id).map_err(|e| e.to_string())
+}
+
+/// v1.4: Which record an attachment (un)link call points at; exactly one must be given.
+fn attachment_link_target(problem_id: Option<i64>, component_id: Option<i64>) -> Result<(&'static str, i64), String> {
+    match (problem_id, component_id) {
+        (Some(id), None) => Ok(("problem", id)),
+        (None, Some(id)) => Ok(("component", id)),
+        _ => Err("Give either a problem_id or a component_id".to_string()),
+    }
+}
+
+/// v1.4: Show an existing attachment on another problem or component without copying it.
+#[tauri::command]
+fn link_attachment(
+    state: State<AppState>,
+    attachment_id: i64,
+    problem_id: Option<i64>,
+    component_id: Option<i64>
+) -> Result<database::AttachmentLink, String> {
+    let (entity_type, entity_id) = attachment_link_target(problem_id, component_id)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.link_attachment(attachment_id, entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn unlink_attachment(
+    state: State<AppState>,
+    attachment_id: i64,
+    problem_id: Option<i64>,
+    component_id: Option<i64>
+) -> Result<(), String> {
+    let (entity_type, entity_id) = attachment_link_target(problem_id, component_id)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.unlink_attachment(attachment_id, entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_attachment_links(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::AttachmentLink>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_attachment_links(attachment_id).map_err(|e| e.to_string())
+}
+
+/// v1.4: Bundle storage per project plus the global total and quota. Without a project,
+/// folders left behind by deleted projects are counted too.
+#[tauri::command]
+fn get_storage_usage(state: State<AppState>, project_id: Option<i64>) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    storage_usage(&db, project_id)
+}
+
+/// v1.4: Remove bundle files no attachment points at, including the folders of deleted
+/// projects when no project is given. `dry_run` reports without deleting.
+#[tauri::command]
+fn cleanup_orphaned_files(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    dry_run: Option<bool>
+) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let dry_run = dry_run.unwrap_or(false);
+    let referenced = bundled_file_paths(&db)?;
+    let existing: Vec<i64> = db.list_projects(None, None).map_err(|e| e.to_string())?
+        .iter()
+        .map(|p| p.id)
+        .collect();
+    
+    let dirs = match project_id {
+        Some(id) => vec![(id, project_bundle_dir(id))],
+        None => bundle_project_ids().into_iter().map(|id| (id, project_bundle_dir(id))).collect(),
+    };
+    
+    let mut removed = Vec::new();
+    let mut freed_bytes = 0u64;
+    let mut failed = Vec::new();
+    for (id, dir) in dirs {
+        let project_deleted = !existing.contains(&id);
+        for (path, size) in bundle_files(&dir) {
+            if !project_deleted && referenced.contains(&path) {
+                continue;
+            }
+            if !dry_run {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    failed.push(serde_json::json!({ "path": path, "error": e.to_string() }));
+                    continue;
+                }
+            }
+            freed_bytes += size;
+            removed.push(path);
+        }
+        if project_deleted && !dry_run {
+            // Only succeeds once they're empty, so nothing unexpected gets swept away
+            let _ = std::fs::remove_dir(&dir);
+            if let Some(project_dir) = dir.parent() {
+                let _ = std::fs::remove_dir(project_dir);
+            }
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "dry_run": dry_run,
+        "removed": removed,
+        "freed_bytes": freed_bytes,
+        "failed": failed,
+    }))
+}
+
+#[tauri::command]
+fn read_file_content(file_path: String, file_type: String) -> Result<serde_json::Value, String> {
+    let path = Path::new(&file_path);
+    
+    if !path.exists() {
+        return Err("File not found".to_string());
+    }
+    
+    match file_type.as_str() {
+        "txt" | "md" | "json" | "swift" | "rs" | "py" | "js" | "ts" | "html" | "css" | "sql" | "yaml" | "yml" | "toml" | "xml" => {
+            // Text files
+            let content = std::fs::read_to_string(&file_path)
+                .map_err(|e| e.to_string())?;
+            Ok(serde_json::json!({
+                "type": "text",
+                "content": content,
+                "size": content.len(),
+            }))
+        },
+        "png" | "jpg" | "jpeg" | "gif" | "webp" => {
+            // Image files - return base64
+            let content = std::fs::read(&file_path)
+                .map_err(|e| e.to_string())?;
+            let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &content);
+            Ok(serde_json::json!({
+                "type": "image",
+                "content": base64,
+                "size": content.len(),
+                "mime_type": format!("image/{}", file_type),
+            }))
+        },
+        "pdf" => {
+            // PDF files - return base64 for now
+            let content = std::fs::read(&file_path)
+                .map_err(|e| e.to_string())?;
+            let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &content);
+            Ok(serde_json::json!({
+                "type": "pdf",
+                "content": base64,
+                "size": content.len(),
+            }))
+        },
+        _ => {
+            // Binary files - return info only
+            let metadata = std::fs::metadata(&file_path)
+                .map_err(|e| e.to_string())?;
+            Ok(serde_json::json!({
+                "type": "binary",
+                "size": metadata.len(),
+                "message": "Binary file content not readable as text",
+            }))
+        }
+    }
+}
+
+// ============================================================
+// v1.1: CONTENT LOCATION COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_content_locations(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::ContentLocation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_content_locations_for_attachment(attachment_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_content_location(
+    state: State<AppState>,
+    attachment_id: i64,
+    description: String,
+    category: Option<String>,
+    location_type: String,
+    start_location: String,
+    end_location: Option<String>,
+    snippet: Option<String>,
+    related_problem_id: Option<i64>,
+    related_solution_id: Option<i64>,
+    related_learning_id: Option<i64>,
+    related_component_id: Option<i64>
+) -> Result<database::ContentLocation, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_content_location(
+        attachment_id,
+        &description,
+        category.as_deref(),
+        &location_type,
+        &start_location,
+        end_location.as_deref(),
+        snippet.as_deref(),
+        related_problem_id,
+        related_solution_id,
+        related_learning_id,
+        related_component_id,
+    ).map_err(|e| e.to_string())
+}
+
+/// v1.4: Find functions, classes and types across a project's indexed code attachments.
+#[tauri::command]
+fn search_symbols(
+    state: State<AppState>,
+    project_id: i64,
+    query: String,
+    limit: Option<i64>
+) -> Result<Vec<database::ContentLocation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.search_symbols(project_id, &query, limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// v1.4: Pin a location (typically an indexed symbol) to a problem and/or component.
+#[tauri::command]
+fn link_content_location(
+    state: State<AppState>,
+    id: i64,
+    problem_id: Option<i64>,
+    component_id: Option<i64>
+) -> Result<database::ContentLocation, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.link_content_location(id, problem_id, component_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_content_location(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "content_locations")?;
+    db.delete_content_location(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.1: EXTRACTION COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_extractions(state: State<AppState>, attachment_id: i64) -> Result<Vec<database::Extraction>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_extractions_for_attachment(attachment_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_extraction(
+    state: State<AppState>,
+    attachment_id: i64,
+    record_type: String,
+    record_id: i64,
+    source_location: Option<String>,
+    source_snippet: Option<String>,
+    confidence: Option<f64>
+) -> Result<database::Extraction, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let extraction = db.create_extraction(
+        attachment_id,
+        &record_type,
+        record_id,
+        source_location.as_deref(),
+        source_snippet.as_deref(),
+        confidence,
+    ).map_err(|e| e.to_string())?;
+    // v1.4: What the model pulled out waits in the inbox until someone looks at it
+    let attachment = db.get_attachment(attachment_id).map_err(|e| e.to_string())?;
+    let snippet = source_snippet.as_deref().filter(|s| !s.trim().is_empty());
+    let title = snippet.map(first_line_title)
+        .unwrap_or_else(|| format!("{} extracted from {}", record_type, attachment.file_name));
+    db.add_inbox_item(attachment.project_id, "extraction", Some(extraction.id), &title, snippet)
+        .map_err(|e| e.to_string())?;
+    Ok(extraction)
+}
+
+#[tauri::command]
+fn update_extraction_review(
+    state: State<AppState>,
+    id: RecordId,
+    user_reviewed: bool,
+    user_approved: Option<bool>
+) -> Result<database::Extraction, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "extractions")?;
+    db.update_extraction_review(id, user_reviewed, user_approved).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_extraction(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "extractions")?;
+    db.delete_extraction(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.1: GIT SYNC COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn git_init(state: State<AppState>, data_path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    
+    // Check if already initialized
+    let git_dir = Path::new(&path).join(".git");
+    if git_dir.exists() {
+        return Ok(serde_json::json!({
+            "status": "already_initialized",
+            "path": path,
+        }));
+    }
+    
+    // Run git init
+    let output = platform::git()?
+        .args(["init"])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    
+    // Create .gitignore
+    let gitignore_path = Path::new(&path).join(".gitignore");
+    let gitignore_content = r#"# OS files
+.DS_Store
+Thumbs.db
+
+# Temporary files
+*.sqlite-journal
+*.sqlite-wal
+*.sqlite-shm
+*.tmp
+*.bak
+
+# Local backups
+*.local-backup-*
+"#;
+    std::fs::write(gitignore_path, gitignore_content)
+        .map_err(|e| format!("Failed to create .gitignore: {}", e))?;
+    write_sync_ignore(&path, &sync_scope(&state)?.ignored())?;
+    
+    // Initial commit
+    let _ = platform::git()?
+        .args(["add", "."])
+        .current_dir(&path)
+        .output();
+    
+    let _ = platform::git()?
+        .args(["commit", "-m", "FlowState initialized"])
+        .current_dir(&path)
+        .output();
+    
+    Ok(serde_json::json!({
+        "status": "initialized",
+        "path": path,
+    }))
+}
+
+#[tauri::command]
+fn git_status(state: State<AppState>, data_path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    
+    // Check if git is initialized
+    let git_dir = Path::new(&path).join(".git");
+    if !git_dir.exists() {
+        return Ok(serde_json::json!({
+            "initialized": false,
+            "status": "not_initialized",
+        }));
+    }
+    
+    // Get status
+    let output = platform::git()?
+        .args(["status", "--porcelain"])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to run git status: {}", e))?;
+    
+    // Files kept out of sync that are still tracked leave with the next sync; they aren't pending
+    let scope = sync_scope(&state)?;
+    let ignored = scope.ignored();
+    let changes = String::from_utf8_lossy(&output.stdout);
+    let pending_changes = changes.lines()
+        .filter(|line| !line.get(3..).is_some_and(|file| ignored.iter().any(|path| file.starts_with(path.as_str()))))
+        .count();
+    
+    // Check for remote
+    let remote_output = platform::git()?
+        .args(["remote", "get-url", "origin"])
+        .current_dir(&path)
+        .output()
+        .ok();
+    
+    let remote_url = remote_output
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    
+    // Get last commit
+    let log_output = platform::git()?
+        .args(["log", "-1", "--format=%H|%s|%ai"])
+        .current_dir(&path)
+        .output()
+        .ok();
+    
+    let last_commit = log_output
+        .filter(|o| o.status.success())
+        .map(|o| {
+            let s = String::from_utf8_lossy(&o.stdout);
+            let parts: Vec<&str> = s.trim().split('|').collect();
+            if parts.len() >= 3 {
+                serde_json::json!({
+                    "hash": parts[0],
+                    "message": parts[1],
+                    "date": parts[2],
+                })
+            } else {
+                serde_json::json!(null)
+            }
+        });
+    
+    Ok(serde_json::json!({
+        "initialized": true,
+        "pending_changes": pending_changes,
+        "has_changes": pending_changes > 0,
+        "excluded_projects": scope.excluded_projects.len(),
+        "unsynced_attachments": scope.unsynced_files.len(),
+        "remote_url": remote_url,
+        "has_remote": remote_url.is_some(),
+        "last_commit": last_commit,
+    }))
+}
+
+/// v1.4: What `git_sync` would commit and push, without doing either: each file with its size,
+/// record counts when the database changed, anything that looks like a credential, and why the
+/// sync would be refused (`blocked`), if it would. Pass `confirmation` to `git_sync` to go
+/// ahead; it no longer matches once the files change.
+#[tauri::command]
+fn git_sync_preview(state: State<AppState>, data_path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    sync_preview(&state, &path)
+}
+
+fn sync_preview(state: &AppState, path: &str) -> Result<serde_json::Value, String> {
+    sync_preview_for(state, path, false)
+}
+
+/// The preview, with `new_remote` for a first push to a remote that isn't added yet: every
+/// committed file goes up, as it will once the remote is there.
+fn sync_preview_for(state: &AppState, path: &str, new_remote: bool) -> Result<serde_json::Value, String> {
+    use sha2::{Sha256, Digest};
+    
+    if !Path::new(path).join(".git").exists() {
+        return Err("Git not initialized. Run git_init first.".to_string());
+    }
+    let git = |args: &[&str]| -> Result<std::process::Output, String> {
+        platform::git()?
+            .args(args)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))
+    };
+    
+    // -uall lists files inside new folders; -z keeps unusual names intact
+    let status_output = git(&["status", "--porcelain", "-z", "-uall"])?;
+    if !status_output.status.success() {
+        return Err(format!("git status failed: {}", String::from_utf8_lossy(&status_output.stderr)));
+    }
+    let scope = sync_scope(state)?;
+    let ignored = scope.ignored();
+    let status = String::from_utf8_lossy(&status_output.stdout);
+    let mut entries = status.split('\0');
+    let mut fingerprint = Sha256::new();
+    let mut files = Vec::new();
+    let mut findings = Vec::new();
+    let mut total_bytes = 0;
+    let mut database_changed = false;
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, file) = entry.split_at(2);
+        let file = &file[1..];
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next(); // The original path
+        }
+        if ignored.iter().any(|path| file.starts_with(path.as_str())) {
+            continue;
+        }
+        let change = match code {
+            "??" => "added",
+            _ if code.contains('A') => "added",
+            _ if code.contains('D') => "deleted",
+            _ if code.contains('R') => "renamed",
+            _ => "modified",
+        };
+        let size = std::fs::metadata(Path::new(path).join(file)).ok().map(|m| m.len());
+        // By content, so a save that changes nothing doesn't spoil the confirmation. The
+        // database is written to all the time; its records stand in for it below.
+        let content = if file == "flowstate.db" { None } else { file_digest(&Path::new(path).join(file)) };
+        fingerprint.update(format!("{}\t{}\t{:?}\n", code, file, content));
+        total_bytes += size.unwrap_or(0);
+        
+        if change != "deleted" && size.is_some_and(|s| s <= secrets::MAX_SCAN_BYTES) {
+            if let Ok(content) = std::fs::read(Path::new(path).join(file)) {
+                findings.extend(secrets::scan_file(file, &content));
+            }
+        }
+        database_changed |= file == "flowstate.db";
+        files.push(serde_json::json!({ "path": file, "change": change, "size": size }));
+    }
+    
+    // Commits the primary remote doesn't have yet go up too; on a new remote that's everything
+    let remote = primary_remote(path).ok();
+    let pushed = remote.as_ref()
+        .map(|remote| format!("{}/main", remote))
+        .filter(|branch| git(&["rev-parse", "--verify", "-q", branch]).is_ok_and(|o| o.status.success()));
+    let committed = match &pushed {
+        Some(branch) => git(&["diff", "--name-only", "-z", branch, "HEAD"]),
+        None => git(&["ls-tree", "-r", "-z", "--name-only", "HEAD"]),
+    };
+    if let Some(output) = committed.ok().filter(|o| o.status.success() && (remote.is_some() || new_remote)) {
+        let listed: Vec<String> = files.iter().filter_map(|f| f["path"].as_str().map(String::from)).collect();
+        for file in String::from_utf8_lossy(&output.stdout).split('\0').filter(|f| !f.is_empty()) {
+            if listed.iter().any(|l| l == file) || ignored.iter().any(|path| file.starts_with(path.as_str())) {
+                continue;
+            }
+            let size = std::fs::metadata(Path::new(path).join(file)).ok().map(|m| m.len());
+            total_bytes += size.unwrap_or(0);
+            if size.is_some_and(|s| s <= secrets::MAX_SCAN_BYTES) {
+                if let Ok(content) = std::fs::read(Path::new(path).join(file)) {
+                    findings.extend(secrets::scan_file(file, &content));
+                }
+            }
+            database_changed |= file == "flowstate.db";
+            files.push(serde_json::json!({ "path": file, "change": "unpushed", "size": size }));
+        }
+    }
+    
+    // The database is one binary file, so say what changed inside it since the last commit
+    let records = if database_changed {
+        let last_commit = last_commit_time(path);
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        // Credentials are kept out of the database now, but one pulled from an older device may be in it
+        for key in secrets::SECRET_SETTINGS {
+            if db.get_setting(key).ok().flatten().is_some() {
+                findings.push(secrets::Finding {
+                    path: "flowstate.db".to_string(),
+                    line: None,
+                    kind: "stored_credential".to_string(),
+                    excerpt: format!("setting {}", key),
+                });
+            }
+        }
+        Some(db.count_changed_records(last_commit.as_deref().unwrap_or("1970-01-01")).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    fingerprint.update(serde_json::to_string(&records).map_err(|e| e.to_string())?);
+    
+    let head = git(&["rev-parse", "HEAD"]).ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    fingerprint.update(head.unwrap_or_default());
+    let unpushed_commits = pushed.and_then(|branch| git(&["rev-list", "--count", &format!("{}..HEAD", branch)]).ok())
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse::<i64>().ok());
+    
+    Ok(serde_json::json!({
+        "files": files,
+        "file_count": files.len(),
+        "total_bytes": total_bytes,
+        "records": records,
+        "findings": findings,
+        "unpushed_commits": unpushed_commits,
+        "blocked": (!scope.excluded_projects.is_empty()).then(|| excluded_projects_error(scope.excluded_projects.len())),
+        "confirmation": format!("{:x}", fingerprint.finalize()),
+    }))
+}
+
+/// SHA-256 of a file's content, or None if it can't be read (deleted, say).
+fn file_digest(path: &Path) -> Option<String> {
+    use sha2::{Sha256, Digest};
+    
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// v1.4: When the last commit in the data folder was made, if there is one.
+fn last_commit_time(path: &str) -> Option<String> {
+    platform::git().ok()?
+        .args(["log", "-1", "--format=%cI"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+// ============================================================
+// v1.4: SYNC COMMIT MESSAGES
+// ============================================================
+
+const SUMMARY_PROJECTS: usize = 3;
+const COMMIT_MESSAGE_PROMPT: &str = "You write git commit messages for a developer's project journal. \
+Rewrite the summary you're given as one short, natural commit subject line (at most 72 characters). \
+Keep the project names and numbers. Reply with the subject line only.";
+
+/// "2 problems solved, 5 todos updated in ProjectX; 1 learning added in Notes", busiest
+/// projects first. None when no records changed.
+fn summarize_activity(activity: &[database::ProjectActivityCount]) -> Option<String> {
+    let mut projects: Vec<(&str, Vec<String>)> = Vec::new();
+    for count in activity {
+        let noun = if count.count == 1 { count.noun.clone() } else { format!("{}s", count.noun) };
+        let part = format!("{} {} {}", count.count, noun, count.verb);
+        match projects.last_mut() {
+            Some((name, parts)) if *name == count.project_name => parts.push(part),
+            _ => projects.push((count.project_name.as_str(), vec![part])),
+        }
+    }
+    if projects.is_empty() {
+        return None;
+    }
+    let mut summary = projects.iter()
+        .take(SUMMARY_PROJECTS)
+        .map(|(name, parts)| format!("{} in {}", parts.join(", "), name))
+        .collect::<Vec<_>>()
+        .join("; ");
+    if projects.len() > SUMMARY_PROJECTS {
+        let more = projects.len() - SUMMARY_PROJECTS;
+        summary.push_str(&format!("; and {} more project{}", more, if more == 1 { "" } else { "s" }));
+    }
+    Some(summary)
+}
+
+/// A commit message describing what changed since the last commit, the timestamped default
+/// when only files changed, and with `polish` also a rewrite by the configured language model.
+fn sync_commit_message(state: &AppState, path: &str, polish: bool) -> Result<serde_json::Value, String> {
+    let since = last_commit_time(path);
+    let (summary, client) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let activity = db.count_project_activity(since.as_deref().unwrap_or("1970-01-01")).map_err(|e| e.to_string())?;
+        let client = if polish { Some(llm_client(state, &db, "sync_commit_message")?) } else { None };
+        (summarize_activity(&activity), client)
+    };
+    let message = summary.clone().unwrap_or_else(|| {
+        format!("FlowState sync - {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"))
+    });
+    let polished = match (&client, &summary) {
+        (Some(client), Some(summary)) => Some(client.complete(COMMIT_MESSAGE_PROMPT, summary, 100)?),
+        _ => None,
+    };
+    Ok(serde_json::json!({
+        "message": polished.as_ref().unwrap_or(&message),
+        "summary": summary,
+        "polished": polished,
+        "since": since,
+    }))
+}
+
+/// v1.4: The commit message a sync would use, summarising the records changed since the last
+/// commit. `polish` has the configured language model reword it.
+#[tauri::command]
+fn generate_sync_commit_message(
+    state: State<AppState>,
+    data_path: Option<String>,
+    polish: Option<bool>
+) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    sync_commit_message(&state, &path, polish.unwrap_or(false))
+}
+
+/// Message for a sync given none: the summary, polished when the `sync_commit_message` setting
+/// is "llm" and the model can be reached.
+fn default_commit_message(state: &AppState, path: &str) -> Option<String> {
+    let polish = {
+        let db = state.db.lock().ok()?;
+        setting_string(&db, "sync_commit_message").as_deref() == Some("llm")
+    };
+    let generated = if polish {
+        sync_commit_message(state, path, true).or_else(|_| sync_commit_message(state, path, false))
+    } else {
+        sync_commit_message(state, path, false)
+    };
+    generated.ok()?["message"].as_str().map(String::from)
+}
+
+/// Commit, pull and push. A push that fails while offline is queued and retried once the
+/// network is back (status "queued"). v1.4: Changes are only committed with the
+/// `confirmation` from a `git_sync_preview` of exactly those changes; a retry that finds
+/// others ends with status "needs_confirmation" instead of pushing them. The sync runs in the
+/// background: this returns once it has started, progress arrives as `sync-progress` events,
+/// and it ends with `sync-completed` (the result), `sync-failed` or `sync-cancelled`.
+#[tauri::command]
+fn git_sync(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    data_path: Option<String>,
+    commit_message: Option<String>,
+    confirmation: Option<String>
+) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let preview = sync_preview(&state, &path)?;
+    if preview["file_count"] != 0 && confirmation.as_deref() != preview["confirmation"].as_str() {
+        return Err(match confirmation {
+            Some(_) => "Files changed since the preview; preview the sync again".to_string(),
+            None => "Preview the sync (git_sync_preview) and confirm it first".to_string(),
+        });
+    }
+    let control = begin_sync(&app)?;
+    std::thread::spawn(move || {
+        let _ = run_sync(&app, &path, commit_message, &control);
+    });
+    Ok(serde_json::json!({ "status": "started" }))
+}
+
+/// v1.4: Stop the running sync. Between stages it stops before the next one; during a pull or
+/// push git is killed and an interrupted pull is rolled back. Returns false if no sync is running.
+#[tauri::command]
+fn cancel_sync(state: State<AppState>) -> Result<bool, String> {
+    let running = state.sync_job.lock().map_err(|e| e.to_string())?;
+    match running.as_ref() {
+        Some(control) => {
+            control.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// v1.4: Claim the one sync allowed at a time; its progress goes out as `sync-progress` events.
+fn begin_sync(app: &tauri::AppHandle) -> Result<std::sync::Arc<syncjob::Control>, String> {
+    let handle = app.clone();
+    claim_sync(&app.state::<AppState>(), syncjob::Control::new(move |progress| {
+        let _ = handle.emit("sync-progress", progress);
+    }))
+}
+
+/// v1.4: Make `control` the running sync, unless one already is. `release_sync` ends it.
+fn claim_sync(state: &AppState, control: syncjob::Control) -> Result<std::sync::Arc<syncjob::Control>, String> {
+    let mut running = state.sync_job.lock().map_err(|e| e.to_string())?;
+    if running.is_some() {
+        return Err("A sync is already running".to_string());
+    }
+    let control = std::sync::Arc::new(control);
+    *running = Some(control.clone());
+    Ok(control)
+}
+
+fn release_sync(state: &AppState) {
+    if let Ok(mut running) = state.sync_job.lock() {
+        *running = None;
+    }
+}
+
+/// v1.4: Run a sync claimed with `begin_sync` to the end, announce how it ended, and release it.
+fn run_sync(
+    app: &tauri::AppHandle,
+    path: &str,
+    commit_message: Option<String>,
+    control: &syncjob::Control,
+) -> Result<serde_json::Value, String> {
+    let state = app.state::<AppState>();
+    let commit_message = commit_message.or_else(|| default_commit_message(&state, path));
+    let result = sync_scope(&state)
+        .and_then(|scope| git_sync_at(path, &scope, commit_message, control))
+        .and_then(|mut result| {
+            if result["pushed"] == false && !network::is_online() {
+                // What was confirmed is committed now; the retry pushes it as long as nothing
+                // else turns up in the meantime
+                let confirmation = sync_preview(&state, path)?["confirmation"].clone();
+                let payload = serde_json::json!({ "data_path": path, "confirmation": confirmation });
+                let operation = queue_network_operation(&state, "git_sync", payload, "Push FlowState data")?;
+                result["status"] = serde_json::json!("queued");
+                result["queued_operation_id"] = serde_json::json!(operation.id);
+            }
+            Ok(result)
+        });
+    release_sync(&state);
+    
+    match &result {
+        Ok(result) => {
+            let _ = app.emit("sync-completed", result);
+        }
+        Err(e) if control.is_cancelled() => {
+            let _ = app.emit("sync-cancelled", serde_json::json!({ "message": e }));
+        }
+        Err(e) => {
+            let _ = app.emit("sync-failed", serde_json::json!({ "error": e }));
+        }
+    }
+    result
+}
+
+fn git_sync_at(path: &str, scope: &SyncScope, commit_message: Option<String>, control: &syncjob::Control) -> Result<serde_json::Value, String> {
+    // Check if git is initialized
+    let git_dir = Path::new(path).join(".git");
+    if !git_dir.exists() {
+        return Err("Git not initialized. Run git_init first.".to_string());
+    }
+    
+    // v1.4: An excluded project's records are in flowstate.db, which would be committed whole
+    if !scope.excluded_projects.is_empty() {
+        return Err(excluded_projects_error(scope.excluded_projects.len()));
+    }
+    
+    control.stage(syncjob::Stage::Staging, "Staging changes")?;
+    forget_saved_auth_headers(path);
+    
+    // v1.4: Expired locks are removed here so the commit removes them for everyone
+    locks::expire_stale(&Path::new(path).join(locks::DIR));
+    
+    // v1.4: Ignore excluded project folders and attachments that don't sync in full, and stop
+    // tracking whatever was committed before
+    let ignored = scope.ignored();
+    write_sync_ignore(path, &ignored)?;
+    if !ignored.is_empty() {
+        let untrack_output = platform::git()?
+            .args(["--literal-pathspecs", "rm", "-r", "--cached", "--ignore-unmatch", "-q", "--"])
+            .args(&ignored)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to untrack files kept out of sync: {}", e))?;
+        if !untrack_output.status.success() {
+            return Err(format!("git rm failed: {}", String::from_utf8_lossy(&untrack_output.stderr)));
+        }
+    }
+    
+    // Add all changes
+    let add_output = platform::git()?
+        .args(["add", "."])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to git add: {}", e))?;
+    
+    if !add_output.status.success() {
+        return Err(format!("git add failed: {}", String::from_utf8_lossy(&add_output.stderr)));
+    }
+    
+    // Check if there are changes to commit
+    let status_output = platform::git()?
+        .args(["status", "--porcelain"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to check status: {}", e))?;
+    
+    let has_changes = !String::from_utf8_lossy(&status_output.stdout).is_empty();
+    
+    if has_changes {
+        // Commit
+        control.stage(syncjob::Stage::Committing, "Committing changes")?;
+        let message = commit_message.unwrap_or_else(|| {
+            format!("FlowState sync - {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"))
+        });
+        
+        let commit_output = platform::git()?
+            .args(["commit", "-m", &message])
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to git commit: {}", e))?;
+        
+        if !commit_output.status.success() {
+            return Err(format!("git commit failed: {}", String::from_utf8_lossy(&commit_output.stderr)));
+        }
+    }
+    
+    // v1.4: Pull from the primary remote, then push to every enabled one
+    let remotes: Vec<SyncRemote> = list_sync_remotes(path)?.into_iter().filter(|r| r.enabled).collect();
+    
+    if let Some(primary) = remotes.first() {
+        // Pull with rebase
+        control.stage(syncjob::Stage::Pulling, &format!("Pulling from {}", primary.name))?;
+        let mut pull = remote_git(path, &primary.name)?;
+        pull.args(["pull", "--rebase", "--progress", &primary.name, "main"]);
+        let pull_started = std::time::SystemTime::now();
+        let pull_output = control.run(syncjob::Stage::Pulling, pull);
+        if control.is_cancelled() {
+            abort_interrupted_pull(path, pull_started);
+            return Err(syncjob::CANCELLED.to_string());
+        }
+        
+        if let Ok(output) = pull_output {
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if stderr.contains("conflict") {
+                    return Ok(serde_json::json!({
+                        "status": "conflict",
+                        "message": "Sync conflict detected. Your local changes have been preserved.",
+                        "committed": has_changes,
+                    }));
+                }
+                // Ignore other pull errors (e.g., no remote tracking)
+            }
+        }
+        
+        // Push
+        let mut remote_results = Vec::new();
+        let mut pushed_to = Vec::new();
+        for remote in &remotes {
+            control.stage(syncjob::Stage::Pushing, &format!("Pushing to {}", remote.name))?;
+            let mut push = remote_git(path, &remote.name)?;
+            push.args(["push", "--progress", &remote.name, "main"]);
+            let push_output = control.run(syncjob::Stage::Pushing, push);
+            // Killing a push leaves nothing to clean up; the commit stays local until the next sync
+            if control.is_cancelled() {
+                return Err(syncjob::CANCELLED.to_string());
+            }
+            let error = match push_output {
+                Ok(output) if output.status.success() => None,
+                Ok(output) => Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+                Err(e) => Some(e),
+            };
+            if error.is_none() {
+                pushed_to.push(remote.name.as_str());
+            }
+            remote_results.push(serde_json::json!({ "name": remote.name, "pushed": error.is_none(), "error": error }));
+        }
+        let pushed = pushed_to.len() == remotes.len();
+        
+        // v1.4: Metadata-only attachments go to their own branch for fetching on demand
+        let attachments_pushed = if !pushed_to.is_empty() && !scope.on_demand_files.is_empty() {
+            control.stage(syncjob::Stage::Attachments, "Pushing attachments")?;
+            Some(push_on_demand_files(path, &scope.on_demand_files, &pushed_to).is_ok())
+        } else {
+            None
+        };
+        
+        return Ok(serde_json::json!({
+            "status": "synced",
+            "committed": has_changes,
+            "pushed": pushed,
+            "remotes": remote_results,
+            "attachments_pushed": attachments_pushed,
+        }));
+    }
+    
+    Ok(serde_json::json!({
+        "status": "committed_local",
+        "committed": has_changes,
+        "message": "Changes committed locally. No remote configured or enabled.",
+    }))
+}
+
+/// v1.4: Put the repository back as it was before a pull, started at `started`, that was killed
+/// part way. A rebase in progress can only be the pull's own (git won't pull over one). The
+/// index lock is only removed when it's stale: taken while the killed pull ran (the pull has
+/// been waited for, so whoever took it is gone) or older than STALE_INDEX_LOCK. A fresher one
+/// belongs to some other git that's still running.
+fn abort_interrupted_pull(path: &str, started: std::time::SystemTime) {
+    let git_dir = Path::new(path).join(".git");
+    let index_lock = git_dir.join("index.lock");
+    let taken = std::fs::metadata(&index_lock).and_then(|m| m.modified()).ok();
+    let stale = taken.is_some_and(|taken| {
+        taken >= started || taken.elapsed().is_ok_and(|age| age > STALE_INDEX_LOCK)
+    });
+    if stale {
+        let _ = std::fs::remove_file(&index_lock);
+    }
+    if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        if let Ok(mut git) = platform::git() {
+            let _ = git.args(["rebase", "--abort"]).current_dir(path).output();
+        }
+    }
+}
+
+#[tauri::command]
+fn git_set_remote(data_path: Option<String>, remote_url: String) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    
+    // Check if remote exists
+    let check_output = platform::git()?
+        .args(["remote", "get-url", "origin"])
+        .current_dir(&path)
+        .output();
+    
+    let has_existing_remote = check_output
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    
+    // Set or update remote
+    let args = if has_existing_remote {
+        vec!["remote", "set-url", "origin", &remote_url]
+    } else {
+        vec!["remote", "add", "origin", &remote_url]
+    };
+    
+    let output = platform::git()?
+        .args(&args)
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to set remote: {}", e))?;
+    
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    
+    Ok(serde_json::json!({
+        "status": "success",
+        "remote_url": remote_url,
+        "action": if has_existing_remote { "updated" } else { "added" },
+    }))
+}
+
+/// Clone a data folder. v1.4: `depth` (or the sync_clone_depth setting) fetches only that many
+/// recent commits; 0 or neither means the full history.
+#[tauri::command]
+fn git_clone(
+    state: State<AppState>,
+    remote_url: String,
+    local_path: Option<String>,
+    depth: Option<u32>
+) -> Result<serde_json::Value, String> {
+    let path = local_path.unwrap_or_else(get_flowstate_data_path);
+    let depth = match depth {
+        Some(depth) => depth,
+        None => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            setting_string(&db, "sync_clone_depth").and_then(|d| d.parse().ok()).unwrap_or(0)
+        }
+    };
+    
+    // Check if path already exists and has content
+    let path_obj = Path::new(&path);
+    if path_obj.exists() && path_obj.read_dir().map(|mut d| d.next().is_some()).unwrap_or(false) {
+        return Err("Target directory is not empty".to_string());
+    }
+    
+    // Create parent directory if needed
+    if let Some(parent) = path_obj.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    // Clone
+    let mut clone = platform::git()?;
+    clone.arg("clone");
+    if depth > 0 {
+        clone.args(["--depth", &depth.to_string()]);
+    }
+    let output = clone
+        .args([&remote_url, &path])
+        .output()
+        .map_err(|e| format!("Failed to git clone: {}", e))?;
+    
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    
+    Ok(serde_json::json!({
+        "status": "cloned",
+        "path": path,
+        "remote_url": remote_url,
+        "depth": if depth > 0 { Some(depth) } else { None },
+    }))
+}
+
+/// v1.4: Fetch the history a shallow clone left out.
+#[tauri::command]
+fn git_unshallow(data_path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    if !is_shallow(&path)? {
+        return Ok(serde_json::json!({ "status": "complete", "message": "The repository already has its full history" }));
+    }
+    let remote = primary_remote(&path)?;
+    let output = remote_git(&path, &remote)?
+        .args(["fetch", "-q", "--unshallow", &remote])
+        .output()
+        .map_err(|e| format!("Failed to run git fetch: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git fetch --unshallow failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(serde_json::json!({ "status": "unshallowed", "size": repo_size(&path)? }))
+}
+
+/// v1.4: How much space the repository takes, whether it's shallow, and the largest files in
+/// its history (usually attachments) as candidates for pruning.
+#[tauri::command]
+fn git_repo_size(data_path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let mut report = repo_size(&path)?;
+    report["largest_files"] = serde_json::json!(largest_blobs(&path, 10)?);
+    Ok(report)
+}
+
+/// v1.4: Shrink the local repository. `keep_commits` cuts this clone's history down to that many
+/// recent commits, which drops old versions of attachments with it; the remotes keep everything,
+/// so nothing is rewritten or force-pushed and `git_unshallow` brings it back. Either way,
+/// unreachable objects are removed and the packs recompressed.
+#[tauri::command]
+fn git_prune_history(data_path: Option<String>, keep_commits: Option<u32>) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let run = |mut command: std::process::Command, args: &[&str]| -> Result<(), String> {
+        let output = command
+            .args(args)
+            .current_dir(&path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+        if !output.status.success() {
+            return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    };
+    let git = |args: &[&str]| run(platform::git()?, args);
+    let before = repo_size(&path)?;
+    
+    if let Some(keep) = keep_commits.filter(|k| *k > 0) {
+        let remote = primary_remote(&path)?;
+        let depth = format!("--depth={}", keep);
+        run(remote_git(&path, &remote)?, &["fetch", "-q", &depth, &remote, "main"])?;
+        // The attachments branch only needs its latest files
+        let _ = run(remote_git(&path, &remote)?, &["fetch", "-q", "--depth=1", &remote, ATTACHMENTS_BRANCH]);
+        // Old reflog entries would keep the cut history alive
+        git(&["reflog", "expire", "--expire=now", "--all"])?;
+        git(&["gc", "-q", "--prune=now"])?;
+    } else {
+        git(&["gc", "-q"])?;
+    }
+    
+    let after = repo_size(&path)?;
+    let freed = before["total_bytes"].as_u64().unwrap_or(0).saturating_sub(after["total_bytes"].as_u64().unwrap_or(0));
+    Ok(serde_json::json!({ "before": before, "after": after, "freed_bytes": freed }))
+}
+
+fn is_shallow(path: &str) -> Result<bool, String> {
+    let output = platform::git()?
+        .args(["rev-parse", "--is-shallow-repository"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git rev-parse: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+/// Object storage from `git count-objects -v`, in bytes, with the commit count.
+fn repo_size(path: &str) -> Result<serde_json::Value, String> {
+    let output = platform::git()?
+        .args(["count-objects", "-v"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git count-objects: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git count-objects failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let field = |name: &str| -> u64 {
+        text.lines()
+            .filter_map(|line| line.split_once(": "))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .unwrap_or(0)
+    };
+    // Sizes are reported in KiB
+    let loose_bytes = field("size") * 1024;
+    let pack_bytes = field("size-pack") * 1024;
+    let garbage_bytes = field("size-garbage") * 1024;
+    let commits = platform::git()?
+        .args(["rev-list", "--count", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse::<u64>().ok());
+    Ok(serde_json::json!({
+        "loose_objects": field("count"),
+        "loose_bytes": loose_bytes,
+        "packed_objects": field("in-pack"),
+        "pack_bytes": pack_bytes,
+        "garbage_bytes": garbage_bytes,
+        "total_bytes": loose_bytes + pack_bytes + garbage_bytes,
+        "commits": commits,
+        "shallow": is_shallow(path)?,
+    }))
+}
+
+/// The `limit` largest files anywhere in the history, by uncompressed size.
+fn largest_blobs(path: &str, limit: usize) -> Result<Vec<serde_json::Value>, String> {
+    use std::io::Write;
+    
+    let objects = platform::git()?
+        .args(["rev-list", "--objects", "--all"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git rev-list: {}", e))?;
+    if !objects.status.success() {
+        return Err(format!("git rev-list failed: {}", String::from_utf8_lossy(&objects.stderr).trim()));
+    }
+    let mut check = platform::git()?
+        .args(["cat-file", "--batch-check=%(objecttype) %(objectsize) %(objectname) %(rest)"])
+        .current_dir(path)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git cat-file: {}", e))?;
+    // Write from another thread so a full stdout pipe can't stall the write
+    let mut stdin = check.stdin.take().ok_or("git cat-file has no stdin")?;
+    let list = objects.stdout;
+    let writer = std::thread::spawn(move || stdin.write_all(&list));
+    let output = check.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+    
+    let mut blobs: Vec<(u64, String, String)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ' ');
+            if parts.next()? != "blob" {
+                return None;
+            }
+            let size = parts.next()?.parse().ok()?;
+            let id = parts.next()?.to_string();
+            Some((size, id, parts.next().unwrap_or_default().to_string()))
+        })
+        .collect();
+    blobs.sort_by_key(|blob| std::cmp::Reverse(blob.0));
+    Ok(blobs.into_iter()
+        .take(limit)
+        .map(|(size, id, file)| serde_json::json!({ "path": file, "size": size, "object": id }))
+        .collect())
+}
+
+#[tauri::command]
+fn git_history(data_path: Option<String>, limit: Option<i32>) -> Result<Vec<serde_json::Value>, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let limit = limit.unwrap_or(20);
+    
+    let output = platform::git()?
+        .args(["log", &format!("-{}", limit), "--format=%H|%s|%ai|%an"])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to get git history: {}", e))?;
+    
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    
+    let history: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let parts: Vec<&str> = line.split('|').collect();
+            if parts.len() >= 4 {
+                serde_json::json!({
+                    "hash": parts[0],
+                    "message": parts[1],
+                    "date": parts[2],
+                    "author": parts[3],
+                })
+            } else {
+                serde_json::json!({
+                    "raw": line,
+                })
+            }
+        })
+        .collect();
+    
+    Ok(history)
+}
+
+/// v1.4: A git remote and whether syncs push to it.
+#[derive(serde::Serialize)]
+struct SyncRemote {
+    name: String,
+    url: String,
+    enabled: bool,
+}
+
+/// Git config key (under `remote.<name>.`) that leaves a remote out of syncs when false.
+const REMOTE_SYNC_KEY: &str = "flowstateSync";
+
+/// v1.4: The data folder's remotes, origin first. The first enabled one is the primary: syncs
+/// pull from it, and push to every enabled remote.
+fn list_sync_remotes(path: &str) -> Result<Vec<SyncRemote>, String> {
+    let git = |args: &[&str]| -> Result<std::process::Output, String> {
+        platform::git()?
+            .args(args)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))
+    };
+    let names_output = git(&["remote"])?;
+    if !names_output.status.success() {
+        return Err(format!("git remote failed: {}", String::from_utf8_lossy(&names_output.stderr).trim()));
+    }
+    let mut remotes = Vec::new();
+    for name in String::from_utf8_lossy(&names_output.stdout).lines().map(str::trim).filter(|n| !n.is_empty()) {
+        let url = git(&["remote", "get-url", name])?;
+        let enabled = git(&["config", "--type=bool", "--get", &format!("remote.{}.{}", name, REMOTE_SYNC_KEY)])?;
+        remotes.push(SyncRemote {
+            name: name.to_string(),
+            url: String::from_utf8_lossy(&url.stdout).trim().to_string(),
+            enabled: String::from_utf8_lossy(&enabled.stdout).trim() != "false",
+        });
+    }
+    remotes.sort_by_key(|remote| remote.name != "origin");
+    Ok(remotes)
+}
+
+fn primary_remote(path: &str) -> Result<String, String> {
+    list_sync_remotes(path)?
+        .into_iter()
+        .find(|remote| remote.enabled)
+        .map(|remote| remote.name)
+        .ok_or_else(|| "No remote is enabled for sync".to_string())
+}
+
+/// Git config key (under `remote.<name>.`) naming the hosting provider whose stored token
+/// authenticates that remote; set by `provision_remote`.
+const REMOTE_PROVIDER_KEY: &str = "flowstateProvider";
+
+/// v1.4: A git command in `path` that can reach `remote`. For a provisioned remote the stored
+/// token goes along as an Authorization header on this one invocation only, so it is never
+/// written to .git/config.
+fn remote_git(path: &str, remote: &str) -> Result<std::process::Command, String> {
+    let mut command = platform::git()?;
+    command.current_dir(path);
+    let config = |key: String| {
+        platform::git().ok()?
+            .args(["config", "--get", &key])
+            .current_dir(path)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let provider = config(format!("remote.{}.{}", remote, REMOTE_PROVIDER_KEY))
+        .filter(|provider| hosting::PROVIDERS.contains(&provider.as_str()));
+    if let Some(provider) = provider {
+        let token = secrets::get(&format!("{}_token", provider));
+        if let (Some(url), Some(token)) = (config(format!("remote.{}.url", remote)), token) {
+            command.args(["-c", &format!("http.{}.extraHeader={}", url, hosting::git_auth_header(&provider, &token))]);
+        }
+    }
+    Ok(command)
+}
+
+/// v1.4: Earlier versions saved the hosting token in .git/config as an http.<url>.extraHeader.
+/// Remove those, marking the remotes they were for so `remote_git` sends the token instead.
+fn forget_saved_auth_headers(path: &str) {
+    let git = |args: &[&str]| {
+        platform::git().ok()?
+            .args(args)
+            .current_dir(path)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+    };
+    let Some(saved) = git(&["config", "--get-regexp", r"^http\..*\.extraheader$"]) else { return };
+    let remotes = list_sync_remotes(path).unwrap_or_default();
+    for line in saved.lines() {
+        let Some((key, value)) = line.split_once(' ') else { continue };
+        if !value.starts_with("Authorization: Basic ") {
+            continue;
+        }
+        let url = &key["http.".len()..key.len() - ".extraheader".len()];
+        let provider = if url.starts_with("https://github.com/") { "github" } else { "gitlab" };
+        for remote in remotes.iter().filter(|remote| remote.url == url) {
+            let _ = git(&["config", &format!("remote.{}.{}", remote.name, REMOTE_PROVIDER_KEY), provider]);
+        }
+        let _ = git(&["config", "--unset-all", key]);
+    }
+}
+
+/// v1.4: Remotes of the data folder with their sync flags.
+#[tauri::command]
+fn git_list_remotes(data_path: Option<String>) -> Result<Vec<SyncRemote>, String> {
+    list_sync_remotes(&data_path.unwrap_or_else(get_flowstate_data_path))
+}
+
+/// v1.4: Add a remote to sync with, or point an existing one at `url`. New remotes are enabled.
+#[tauri::command]
+fn git_add_remote(data_path: Option<String>, name: String, url: String) -> Result<Vec<SyncRemote>, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let name = name.trim();
+    if name.is_empty() || url.trim().is_empty() {
+        return Err("A remote needs a name and a URL".to_string());
+    }
+    let exists = list_sync_remotes(&path)?.iter().any(|remote| remote.name == name);
+    let action = if exists { "set-url" } else { "add" };
+    let output = platform::git()?
+        .args(["remote", action, name, url.trim()])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to set remote: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    list_sync_remotes(&path)
+}
+
+/// v1.4: Remove a remote; its remote-tracking branches go with it.
+#[tauri::command]
+fn git_remove_remote(data_path: Option<String>, name: String) -> Result<Vec<SyncRemote>, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let output = platform::git()?
+        .args(["remote", "remove", &name])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to remove remote: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    list_sync_remotes(&path)
+}
+
+/// v1.4: Include a remote in syncs or leave it out. The flag lives in the repository's git
+/// config, so it stays with the remote rather than syncing to other machines.
+#[tauri::command]
+fn git_set_remote_enabled(data_path: Option<String>, name: String, enabled: bool) -> Result<Vec<SyncRemote>, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    if !list_sync_remotes(&path)?.iter().any(|remote| remote.name == name) {
+        return Err(format!("No remote named {}", name));
+    }
+    let output = platform::git()?
+        .args(["config", "--type=bool", &format!("remote.{}.{}", name, REMOTE_SYNC_KEY), if enabled { "true" } else { "false" }])
+        .current_dir(&path)
+        .output()
+        .map_err(|e| format!("Failed to update git config: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    list_sync_remotes(&path)
+}
+
+/// v1.4: Set up sync in one step: create a repository (private unless `private` is false) on
+/// GitHub or GitLab with the stored token (github_token or gitlab_token, and gitlab_url for a
+/// self-hosted GitLab), make it origin and push to it. Without `confirmation` nothing is created
+/// and the result is the preview of that first push; called again with the preview's
+/// confirmation, it creates the repository and starts the push the way `git_sync` does. Git is
+/// handed the token on each pull and push and never stores it.
+#[tauri::command]
+fn provision_remote(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    data_path: Option<String>,
+    provider: String,
+    name: String,
+    private: Option<bool>,
+    confirmation: Option<String>
+) -> Result<serde_json::Value, String> {
+    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("The repository needs a name".to_string());
+    }
+    let (token, gitlab_url) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let token = match provider.as_str() {
+            "github" => setting_string(&db, "github_token").ok_or("GitHub token not configured (setting: github_token)")?,
+            "gitlab" => setting_string(&db, "gitlab_token").ok_or("GitLab token not configured (setting: gitlab_token)")?,
+            _ => return Err(format!("Unknown provider '{}'. Expected one of: {}", provider, hosting::PROVIDERS.join(", "))),
+        };
+        (token, setting_string(&db, "gitlab_url"))
+    };
+    
+    git_init(state.clone(), Some(path.clone()))?;
+    if list_sync_remotes(&path)?.iter().any(|remote| remote.name == "origin") {
+        return Err("This data folder already has an origin remote; remove it or add the new repository under another name".to_string());
+    }
+    let preview = sync_preview_for(&state, &path, true)?;
+    match confirmation {
+        None => return Ok(serde_json::json!({ "status": "needs_confirmation", "preview": preview })),
+        Some(confirmation) if preview["confirmation"].as_str() != Some(confirmation.as_str()) => {
+            return Err("Files changed since the preview; preview the setup again".to_string());
+        }
+        Some(_) => {}
+    }
+    if let Some(blocked) = preview["blocked"].as_str() {
+        return Err(blocked.to_string());
+    }
+    
+    // Claimed before the repository is created, so a sync started meanwhile can't get in first
+    let control = begin_sync(&app)?;
+    let provisioned = hosting::create_repository(&provider, &token, name, private.unwrap_or(true), gitlab_url.as_deref())
+        .and_then(|repository| {
+            let git = |args: &[&str]| -> Result<(), String> {
+                let output = platform::git()?
+                    .args(args)
+                    .current_dir(&path)
+                    .output()
+                    .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "git {} failed: {} (the repository was created at {})",
+                        args[0],
+                        String::from_utf8_lossy(&output.stderr).trim(),
+                        repository.web_url,
+                    ));
+                }
+                Ok(())
+            };
+            // Syncs push main, whatever git init called the first branch
+            git(&["branch", "-M", "main"])?;
+            git(&["remote", "add", "origin", &repository.clone_url])?;
+            git(&["config", &format!("remote.origin.{}", REMOTE_PROVIDER_KEY), &provider])?;
+            Ok(repository)
+        });
+    let repository = match provisioned {
+        Ok(repository) => repository,
+        Err(e) => {
+            release_sync(&state);
+            return Err(e);
+        }
+    };
+    
+    let sync_path = path.clone();
+    std::thread::spawn(move || {
+        let _ = run_sync(&app, &sync_path, None, &control);
+    });
+    Ok(serde_json::json!({
+        "status": "started",
+        "provider": provider,
+        "repository": repository.full_name,
+        "remote_url": repository.clone_url,
+        "web_url": repository.web_url,
+    }))
+}
+
+/// v1.4: Override the project's sync policy for one attachment; `None` goes back to it.
+#[tauri::command]
+fn set_attachment_sync_policy(state: State<AppState>, id: i64, policy: Option<String>) -> Result<database::Attachment, String> {
+    if let Some(policy) = &policy {
+        check_attachment_sync_policy(policy)?;
+    }
+    let attachment = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_attachment_sync_policy(id, policy.as_deref()).map_err(|e| e.to_string())?
+    };
+    refresh_sync_ignore(&state)?;
+    Ok(attachment)
+}
+
+/// v1.4: Download a metadata-only attachment from the remote's attachments branch into this
+/// machine's bundle folder.
+#[tauri::command]
+fn fetch_attachment_from_remote(state: State<AppState>, id: i64) -> Result<database::Attachment, String> {
+    let (attachment, policy) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let attachment = db.get_attachment(id).map_err(|e| e.to_string())?;
+        let project = db.get_project(attachment.project_id).map_err(|e| e.to_string())?;
+        let policy = attachment.sync_policy.clone().unwrap_or(project.attachment_sync);
+        (attachment, policy)
+    };
+    if attachment.is_external {
+        return Err(format!("{} is an external file; only its record syncs", attachment.file_name));
+    }
+    match policy.as_str() {
+        "never" => return Err(format!("{} never syncs; it's only on the machine that added it", attachment.file_name)),
+        "full" => return Err(format!("{} syncs in full; run a sync to get it", attachment.file_name)),
+        _ => {}
+    }
+    
+    let data_path = get_flowstate_data_path();
+    let remote = primary_remote(&data_path)?;
+    let fetch_output = remote_git(&data_path, &remote)?
+        .args(["fetch", "-q", &remote, ATTACHMENTS_BRANCH])
+        .output()
+        .map_err(|e| format!("Failed to run git fetch: {}", e))?;
+    if !fetch_output.status.success() {
+        return Err(format!("Couldn't fetch attachments from the remote: {}", String::from_utf8_lossy(&fetch_output.stderr).trim()));
+    }
+    let file = attachment_repo_path(&attachment);
+    let show_output = platform::git()?
+        .args(["show", &format!("FETCH_HEAD:{}", file)])
+        .current_dir(&data_path)
+        .output()
+        .map_err(|e| format!("Failed to run git show: {}", e))?;
+    if !show_output.status.success() {
+        return Err(format!("{} isn't on the remote yet; it goes up with the next sync on the machine that added it", attachment.file_name));
+    }
+    
+    let local_path = Path::new(&data_path).join(&file);
+    if let Some(parent) = local_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    std::fs::write(&local_path, &show_output.stdout).map_err(|e| format!("Failed to write {}: {}", file, e))?;
+    
+    let local_path = local_path.to_string_lossy().to_string();
+    if local_path == attachment.file_path {
+        return Ok(attachment);
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_attachment_file_path(id, &local_path).map_err(|e| e.to_string())
+}
+
+/// v1.4: What a sync leaves out of the main branch, as paths within the data folder.
+struct SyncScope {
+    excluded_projects: Vec<String>, // Folders of projects kept out of sync
+    unsynced_files: Vec<String>,    // Attachments synced as metadata only, or never
+    on_demand_files: Vec<String>,   // The metadata-only ones present here, for the attachments branch
+}
+
+impl SyncScope {
+    fn ignored(&self) -> Vec<String> {
+        self.excluded_projects.iter().chain(&self.unsynced_files).cloned().collect()
+    }
+}
+
+fn excluded_projects_error(count: usize) -> String {
+    let (projects, their, them) = match count {
+        1 => ("A project is".to_string(), "its", "it"),
+        n => (format!("{} projects are", n), "their", "them"),
+    };
+    format!("{} kept out of sync, and git sync would push {} records with flowstate.db. \
+             Let {} back in with set_project_sync to sync.", projects, their, them)
+}
+
+fn sync_scope(state: &AppState) -> Result<SyncScope, String> {
+    let (project_ids, attachments) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (
+            db.get_sync_excluded_project_ids().map_err(|e| e.to_string())?,
+            db.get_limited_sync_attachments().map_err(|e| e.to_string())?,
+        )
+    };
+    let data_dir = platform::data_dir();
+    let mut scope = SyncScope {
+        excluded_projects: project_ids.iter().map(|id| format!("projects/project_{}/", id)).collect(),
+        unsynced_files: Vec::new(),
+        on_demand_files: Vec::new(),
+    };
+    for (attachment, policy) in attachments {
+        let file = attachment_repo_path(&attachment);
+        if policy == "metadata" && data_dir.join(&file).is_file() {
+            scope.on_demand_files.push(file.clone());
+        }
+        scope.unsynced_files.push(file);
+    }
+    Ok(scope)
+}
+
+/// v1.4: Where a bundled attachment sits in the data folder, '/'-separated. Paths recorded
+/// on another machine are mapped onto this one's bundle folder.
+fn attachment_repo_path(attachment: &database::Attachment) -> String {
+    let path = Path::new(&attachment.file_path);
+    match path.strip_prefix(platform::data_dir()) {
+        Ok(relative) => relative.iter().map(|part| part.to_string_lossy()).collect::<Vec<_>>().join("/"),
+        Err(_) => {
+            let file_name = attachment.file_path.rsplit(['/', '\\']).next().unwrap_or(&attachment.file_name);
+            format!("projects/project_{}/attachments/{}", attachment.project_id, file_name)
+        }
+    }
+}
+
+fn check_attachment_sync_policy(policy: &str) -> Result<(), String> {
+    if ATTACHMENT_SYNC_POLICIES.contains(&policy) {
+        Ok(())
+    } else {
+        Err(format!("Unknown sync policy '{}' ({})", policy, ATTACHMENT_SYNC_POLICIES.join(", ")))
+    }
+}
+
+/// v1.4: Bring the data folder's .gitignore in line with the sync settings, once it's a repository.
+fn refresh_sync_ignore(state: &AppState) -> Result<(), String> {
+    let path = get_flowstate_data_path();
+    if Path::new(&path).join(".git").exists() {
+        write_sync_ignore(&path, &sync_scope(state)?.ignored())?;
+    }
+    Ok(())
+}
+
+/// Rewrite the block of .gitignore that FlowState manages; the rest of the file is left alone.
+fn write_sync_ignore(path: &str, excluded: &[String]) -> Result<(), String> {
+    let gitignore_path = Path::new(path).join(".gitignore");
+    let current = std::fs::read_to_string(&gitignore_path).unwrap_or_default();
+    if excluded.is_empty() && !current.contains(SYNC_IGNORE_START) {
+        return Ok(());
+    }
+    let mut kept = Vec::new();
+    let mut in_block = false;
+    for line in current.lines() {
+        match line {
+            SYNC_IGNORE_START => in_block = true,
+            SYNC_IGNORE_END => in_block = false,
+            _ if !in_block => kept.push(line),
+            _ => {}
+        }
+    }
+    let mut updated = kept.join("\n").trim_end().to_string();
+    if !excluded.is_empty() {
+        let patterns: Vec<String> = excluded.iter().map(|path| gitignore_pattern(path)).collect();
+        updated.push_str(&format!("\n\n{}\n{}\n{}", SYNC_IGNORE_START, patterns.join("\n"), SYNC_IGNORE_END));
+    }
+    updated.push('\n');
+    if updated != current {
+        std::fs::write(&gitignore_path, updated).map_err(|e| format!("Failed to update .gitignore: {}", e))?;
+    }
+    Ok(())
+}
+
+/// An anchored .gitignore line matching exactly `path`.
+fn gitignore_pattern(path: &str) -> String {
+    let mut pattern = String::from("/");
+    for c in path.chars() {
+        if matches!(c, '\\' | '*' | '?' | '[') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    if pattern.ends_with(' ') {
+        pattern.insert(pattern.len() - 1, '\\');
+    }
+    pattern
+}
+
+/// v1.4: Add metadata-only attachments to the attachments branch and push it, so another
+/// machine can fetch one file when it's needed rather than every file with each pull. The
+/// branch starts from the remote's, so files pushed from other machines stay on it.
+fn push_on_demand_files(path: &str, files: &[String], remotes: &[&str]) -> Result<(), String> {
+    let index = Path::new(path).join(".git").join("flowstate-attachments.index");
+    let run = |mut command: std::process::Command, args: &[&str]| -> Result<String, String> {
+        let output = command
+            .args(args)
+            .env("GIT_INDEX_FILE", &index)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+        if !output.status.success() {
+            return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let git = |args: &[&str]| run(platform::git()?, args);
+    let with_remote = |remote: &str, args: &[&str]| run(remote_git(path, remote)?, args);
+    
+    let primary = remotes.first().ok_or("No remote to push attachments to")?;
+    let parent = with_remote(primary, &["fetch", "-q", primary, ATTACHMENTS_BRANCH])
+        .and_then(|_| git(&["rev-parse", "FETCH_HEAD"]))
+        .or_else(|_| git(&["rev-parse", "--verify", "-q", ATTACHMENTS_BRANCH]))
+        .ok();
+    match &parent {
+        Some(commit) => git(&["read-tree", commit])?,
+        None => git(&["read-tree", "--empty"])?,
+    };
+    let mut add = vec!["--literal-pathspecs", "update-index", "--add", "--"];
+    add.extend(files.iter().map(String::as_str));
+    git(&add)?;
+    let tree = git(&["write-tree"])?;
+    let _ = std::fs::remove_file(&index);
+    
+    let parent_tree = match &parent {
+        Some(commit) => Some(git(&["rev-parse", &format!("{}^{{tree}}", commit)])?),
+        None => None,
+    };
+    if parent_tree.as_deref() == Some(tree.as_str()) {
+        // Nothing new, though mirrors may not have everything the primary does
+        if let Some(commit) = &parent {
+            for remote in &remotes[1..] {
+                with_remote(remote, &["push", "-q", remote, &format!("{}:refs/heads/{}", commit, ATTACHMENTS_BRANCH)])?;
+            }
+        }
+        return Ok(());
+    }
+    let mut commit_args = vec!["commit-tree", tree.as_str(), "-m", "FlowState attachments"];
+    if let Some(commit) = &parent {
+        commit_args.extend(["-p", commit.as_str()]);
+    }
+    let commit = git(&commit_args)?;
+    git(&["update-ref", &format!("refs/heads/{}", ATTACHMENTS_BRANCH), &commit])?;
+    for remote in remotes {
+        with_remote(remote, &["push", "-q", remote, ATTACHMENTS_BRANCH])?;
+    }
+    Ok(())
+}
+
+// ============================================================
+// v1.1: SETTINGS COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_settings(state: State<AppState>) -> Result<Vec<database::Setting>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_all_settings().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_setting(state: State<AppState>, key: String) -> Result<Option<String>, String> {
+    // v1.4: Credentials live outside the synced database
+    if secrets::is_secret(&key) {
+        return Ok(secrets::get(&key).map(|value| serde_json::json!(value).to_string()));
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_setting(&key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_setting(state: State<AppState>, key: String, value: String, category: Option<String>) -> Result<(), String> {
+    if secrets::is_secret(&key) {
+        return secrets::set(&key, Some(&value));
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_setting(&key, &value, category.as_deref()).map_err(|e| e.to_string())?;
+    if key == "local_api_rate_limit" {
+        state.api_limiter.set_limit(setting_string(&db, &key).and_then(|l| l.parse().ok()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_setting(state: State<AppState>, key: String) -> Result<(), String> {
+    if secrets::is_secret(&key) {
+        return secrets::set(&key, None);
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_setting(&key).map_err(|e| e.to_string())?;
+    if key == "local_api_rate_limit" {
+        state.api_limiter.set_limit(None);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_settings_by_category(state: State<AppState>, category: String) -> Result<Vec<database::Setting>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_settings_by_category(&category).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: TIMEZONE COMMANDS
+// ============================================================
+
+/// v1.4: The timezone "today" and due dates are worked out in, with the current time there.
+#[tauri::command]
+fn get_timezone(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let zone = db.timezone();
+    let now = zone.now();
+    Ok(serde_json::json!({
+        "timezone": zone.name(),
+        "offset": now.format("%:z").to_string(),
+        "now": now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        "today": now.format("%Y-%m-%d").to_string(),
+    }))
+}
+
+/// v1.4: The window a named range ("today", "this week", "this sprint"...) covers right now,
+/// as RFC 3339 times on the user's clock, so views can label what they're showing.
+#[tauri::command]
+fn resolve_time_range(state: State<AppState>, range: String) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let zone = db.timezone();
+    let (since, until) = timezone::named_range(&range, zone, sprint_start(&db)?)?;
+    let local = |t: chrono::DateTime<chrono::Utc>| zone.localize(t).to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    Ok(serde_json::json!({ "range": range, "since": local(since), "until": local(until) }))
+}
+
+/// v1.4: Set the timezone by IANA name ("Europe/Berlin"); empty or "system" follows the system.
+#[tauri::command]
+fn set_timezone(state: State<AppState>, name: String) -> Result<String, String> {
+    let zone = timezone::Zone::parse(&name)?;
+    let value = match zone {
+        timezone::Zone::System => String::new(),
+        timezone::Zone::Named(_) => zone.name(),
+    };
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let value = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+    db.set_setting(timezone::SETTING, &value, Some("general")).map_err(|e| e.to_string())?;
+    Ok(zone.name())
+}
+
+// ============================================================
+// v1.1: SYNC STATUS COMMANDS (Database-tracked sync state)
+// ============================================================
+
+#[tauri::command]
+fn get_sync_status(state: State<AppState>) -> Result<Option<database::SyncStatus>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_sync_status().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn init_sync_status(state: State<AppState>, device_name: String) -> Result<database::SyncStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let device_id = uuid::Uuid::new_v4().to_string();
+    db.create_sync_status(&device_name, &device_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_sync_status(
+    state: State<AppState>,
+    remote_url: Option<String>,
+    last_sync_at: Option<String>,
+    last_sync_commit: Option<String>,
+    pending_changes: Option<i64>,
+    has_conflicts: Option<bool>
+) -> Result<database::SyncStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.update_sync_status(
+        remote_url.as_deref(),
+        last_sync_at.as_deref(),
+        last_sync_commit.as_deref(),
+        pending_changes,
+        has_conflicts,
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_sync_history(state: State<AppState>, limit: Option<i32>) -> Result<Vec<database::SyncHistory>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(20);
+    db.get_sync_history(limit).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn log_sync_operation(
+    state: State<AppState>,
+    device_id: String,
+    operation: String,
+    commit_hash: Option<String>,
+    files_changed: Option<i64>,
+    status: String,
+    error_message: Option<String>
+) -> Result<database::SyncHistory, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.log_sync_operation(
+        &device_id,
+        &operation,
+        commit_hash.as_deref(),
+        files_changed,
+        &status,
+        error_message.as_deref(),
+    ).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.2: PROJECT VARIABLES COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn create_project_variable(
+    state: State<AppState>,
+    project_id: i64,
+    category: String,
+    name: String,
+    value: Option<String>,
+    is_secret: Option<bool>,
+    description: Option<String>,
+) -> Result<database::ProjectVariable, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_project_variable(
+        project_id,
+        &category,
+        &name,
+        value.as_deref(),
+        is_secret.unwrap_or(false),
+        description.as_deref(),
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_project_variables(
+    state: State<AppState>,
+    project_id: i64,
+    category: Option<String>,
+) -> Result<Vec<database::ProjectVariable>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_project_variables(project_id, category.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_project_variable(
+    state: State<AppState>,
+    id: RecordId,
+    category: Option<String>,
+    name: Option<String>,
+    value: Option<String>,
+    is_secret: Option<bool>,
+    description: Option<String>,
+) -> Result<database::ProjectVariable, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "project_variables")?;
+    db.update_project_variable(
+        id,
+        category.as_deref(),
+        name.as_deref(),
+        value.as_deref(),
+        is_secret,
+        description.as_deref(),
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_project_variable(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "project_variables")?;
+    db.delete_project_variable(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.2: PROJECT METHODS COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn create_project_method(
+    state: State<AppState>,
+    project_id: i64,
+    name: String,
+    description: String,
+    category: Option<String>,
+    steps: Option<String>,
+    code_example: Option<String>,
+    related_component_id: Option<i64>,
+) -> Result<database::ProjectMethod, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_project_method(
+        project_id,
+        &name,
+        &description,
+        category.as_deref(),
+        steps.as_deref(),
+        code_example.as_deref(),
+        related_component_id,
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_project_methods(
+    state: State<AppState>,
+    project_id: i64,
+    category: Option<String>,
+) -> Result<Vec<database::ProjectMethod>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_project_methods(project_id, category.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_project_method(
+    state: State<AppState>,
+    id: RecordId,
+    name: Option<String>,
+    description: Option<String>,
+    category: Option<String>,
+    steps: Option<String>,
+    code_example: Option<String>,
+    related_component_id: Option<i64>,
+) -> Result<database::ProjectMethod, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "project_methods")?;
+    db.update_project_method(
+        id,
+        name.as_deref(),
+        description.as_deref(),
+        category.as_deref(),
+        steps.as_deref(),
+        code_example.as_deref(),
+        related_component_id,
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_project_method(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "project_methods")?;
+    db.delete_project_method(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.2: CONVERSATIONS COMMANDS (read-only)
+// ============================================================
+
+#[tauri::command]
+fn get_conversations(
+    state: State<AppState>,
+    project_id: i64,
+    limit: Option<i32>,
+) -> Result<Vec<database::Conversation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_conversations(project_id, limit).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.2: SESSIONS COMMANDS (read-only)
+// ============================================================
+
+#[tauri::command]
+fn get_sessions_list(
+    state: State<AppState>,
+    project_id: i64,
+    limit: Option<i32>,
+) -> Result<Vec<database::Session>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_sessions_list(project_id, limit).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.2: CROSS REFERENCES COMMANDS (read-only)
+// ============================================================
+
+#[tauri::command]
+fn get_cross_references(
+    state: State<AppState>,
+    project_id: i64,
+) -> Result<Vec<database::CrossReference>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_cross_references(project_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: SEVERITY SLA COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_sla_thresholds(state: State<AppState>) -> Result<database::SlaThresholds, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_sla_thresholds().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_sla_thresholds(state: State<AppState>, thresholds: database::SlaThresholds) -> Result<database::SlaThresholds, String> {
+    let all = [thresholds.critical, thresholds.high, thresholds.medium, thresholds.low];
+    if all.iter().flatten().any(|hours| !hours.is_finite() || *hours <= 0.0) {
+        return Err("SLA thresholds must be positive numbers of hours".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_sla_thresholds(&thresholds).map_err(|e| e.to_string())?;
+    Ok(thresholds)
+}
+
+#[tauri::command]
+fn get_stale_problems(state: State<AppState>, project_id: Option<i64>) -> Result<Vec<database::StaleProblem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_stale_problems(project_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: PROBLEM BOARD COMMANDS
+// ============================================================
+
+/// Open problems in swimlanes for triage. `group_by` is "severity" (the default), "component"
+/// or "age".
+#[tauri::command]
+fn get_problem_board(state: State<AppState>, project_id: i64, group_by: Option<String>) -> Result<database::ProblemBoard, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_problem_board(project_id, group_by.as_deref().unwrap_or("severity")).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_wip_limits(state: State<AppState>) -> Result<database::WipLimits, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_wip_limits().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_wip_limits(state: State<AppState>, limits: database::WipLimits) -> Result<database::WipLimits, String> {
+    if let Some(group_by) = limits.keys().find(|g| !database::BOARD_GROUPINGS.contains(&g.as_str())) {
+        return Err(format!("Unknown grouping '{}'", group_by));
+    }
+    if limits.values().flat_map(|lanes| lanes.values()).any(|limit| *limit < 0) {
+        return Err("WIP limits can't be negative".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_wip_limits(&limits).map_err(|e| e.to_string())?;
+    Ok(limits)
+}
+
+// ============================================================
+// v1.4: DASHBOARD COMMANDS
+// ============================================================
+
+fn validate_widgets(widgets: &[database::DashboardWidget]) -> Result<(), String> {
+    widgets.iter().try_for_each(|widget| widget.validate())
+}
+
+#[tauri::command]
+fn get_dashboard_layouts(state: State<AppState>, project_id: i64) -> Result<Vec<database::DashboardLayout>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_dashboard_layouts(project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_dashboard_layout(
+    state: State<AppState>,
+    project_id: i64,
+    name: String,
+    widgets: Vec<database::DashboardWidget>
+) -> Result<database::DashboardLayout, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("A dashboard needs a name".to_string());
+    }
+    validate_widgets(&widgets)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_dashboard_layout(project_id, name, &widgets).map_err(|e| e.to_string())
+}
+
+/// Rename a layout and/or replace its widgets, given in display order.
+#[tauri::command]
+fn update_dashboard_layout(
+    state: State<AppState>,
+    id: RecordId,
+    name: Option<String>,
+    widgets: Option<Vec<database::DashboardWidget>>
+) -> Result<database::DashboardLayout, String> {
+    let name = name.map(|n| n.trim().to_string());
+    if name.as_deref() == Some("") {
+        return Err("A dashboard needs a name".to_string());
+    }
+    if let Some(widgets) = &widgets {
+        validate_widgets(widgets)?;
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "dashboard_layouts")?;
+    db.update_dashboard_layout(id, name.as_deref(), widgets.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_dashboard_layout(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "dashboard_layouts")?;
+    db.delete_dashboard_layout(id).map_err(|e| e.to_string())
+}
+
+/// Everything a layout's widgets show, evaluated in one call.
+#[tauri::command]
+fn get_dashboard_data(state: State<AppState>, layout_id: RecordId) -> Result<database::DashboardData, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let layout_id = layout_id.resolve(&db, "dashboard_layouts")?;
+    db.get_dashboard_data(layout_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: GOAL COMMANDS
+// ============================================================
+
+/// Goal dates are calendar days; progress is measured between them.
+fn check_goal_date(field: &str, date: Option<&str>) -> Result<(), String> {
+    match date {
+        Some(d) if chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_err() => {
+            Err(format!("{} must be a date like 2025-06-30", field))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn create_goal(
+    state: State<AppState>,
+    project_id: i64,
+    title: String,
+    description: Option<String>,
+    start_date: Option<String>,
+    target_date: Option<String>
+) -> Result<database::Goal, String> {
+    check_goal_date("start_date", start_date.as_deref())?;
+    check_goal_date("target_date", target_date.as_deref())?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_goal(project_id, &title, description.as_deref(), start_date.as_deref(), target_date.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// All of a project's goals unless a status (active, achieved, abandoned) is given.
+#[tauri::command]
+fn get_goals(state: State<AppState>, project_id: i64, status: Option<String>) -> Result<Vec<database::Goal>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_goals(project_id, status.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_goal(
+    state: State<AppState>,
+    id: RecordId,
+    title: Option<String>,
+    description: Option<String>,
+    target_date: Option<String>,
+    status: Option<String>
+) -> Result<database::Goal, String> {
+    check_goal_date("target_date", target_date.as_deref())?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "goals")?;
+    db.update_goal(id, title.as_deref(), description.as_deref(), target_date.as_deref(), status.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_goal(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "goals")?;
+    db.delete_goal(id).map_err(|e| e.to_string())
+}
+
+/// `metric` is "manual" (the default), "todos_completed" or "problems_solved"; linked ones
+/// count the goal's project, or only `component_id` when given.
+#[tauri::command]
+fn add_key_result(
+    state: State<AppState>,
+    goal_id: RecordId,
+    title: String,
+    target_value: f64,
+    metric: Option<String>,
+    component_id: Option<i64>
+) -> Result<database::KeyResult, String> {
+    let metric = metric.unwrap_or_else(|| "manual".to_string());
+    if !database::KEY_RESULT_METRICS.contains(&metric.as_str()) {
+        return Err(format!("Unknown metric '{}'. Expected one of: {}", metric, database::KEY_RESULT_METRICS.join(", ")));
+    }
+    if target_value <= 0.0 {
+        return Err("A key result's target must be above zero".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let goal_id = goal_id.resolve(&db, "goals")?;
+    db.add_key_result(goal_id, &title, &metric, target_value, component_id).map_err(|e| e.to_string())
+}
+
+/// `current_value` records progress on a manual key result.
+#[tauri::command]
+fn update_key_result(
+    state: State<AppState>,
+    id: RecordId,
+    title: Option<String>,
+    target_value: Option<f64>,
+    current_value: Option<f64>
+) -> Result<database::KeyResult, String> {
+    if target_value.is_some_and(|target| target <= 0.0) {
+        return Err("A key result's target must be above zero".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "key_results")?;
+    db.update_key_result(id, title.as_deref(), target_value, current_value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_key_result(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "key_results")?;
+    db.delete_key_result(id).map_err(|e| e.to_string())
+}
+
+/// A goal's key results with their current values and whether it's on pace for its target date.
+#[tauri::command]
+fn get_goal_progress(state: State<AppState>, goal_id: RecordId) -> Result<database::GoalProgress, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let goal_id = goal_id.resolve(&db, "goals")?;
+    db.get_goal_progress(goal_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: CHANGELOG COMMANDS
+// ============================================================
+
+/// Database time for `since`: a date, an RFC 3339 time, or a git tag in `repo_path` (when
+/// its commit was made).
+fn changelog_since(since: &str, repo_path: Option<&str>) -> Result<String, String> {
+    let since = since.trim();
+    if chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d").is_ok() {
+        return Ok(since.to_string());
+    }
+    let time = match chrono::DateTime::parse_from_rfc3339(since) {
+        Ok(time) => time,
+        Err(_) => {
+            let repo = repo_path.ok_or_else(|| format!("'{}' isn't a date; give repo_path to look it up as a tag", since))?;
+            let output = platform::git()?
+                .args(["log", "-1", "--format=%cI", &format!("{}^{{commit}}", since), "--"])
+                .current_dir(repo)
+                .output()
+                .map_err(|e| e.to_string())?;
+            if !output.status.success() {
+                return Err(format!("No tag or commit '{}' in {}", since, repo));
+            }
+            let time = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            chrono::DateTime::parse_from_rfc3339(&time).map_err(|e| e.to_string())?
+        }
+    };
+    Ok(time.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Keep a Changelog entries for a project's changes and solved problems since a tag or date.
+/// `format` is "markdown" (the default) or "json". With `write`, the Markdown becomes the
+/// Unreleased section of CHANGELOG.md in `repo_path`.
+#[tauri::command]
+fn generate_changelog(
+    state: State<AppState>,
+    project_id: i64,
+    since: String,
+    format: Option<String>,
+    repo_path: Option<String>,
+    write: Option<bool>
+) -> Result<serde_json::Value, String> {
+    let format = format.unwrap_or_else(|| "markdown".to_string());
+    if !changelog::FORMATS.contains(&format.as_str()) {
+        return Err(format!("Unknown format '{}'. Expected one of: {}", format, changelog::FORMATS.join(", ")));
+    }
+    let write = write.unwrap_or(false);
+    if write && format != "markdown" {
+        return Err("Only a Markdown changelog can be written to the repository".to_string());
+    }
+    let since = changelog_since(&since, repo_path.as_deref())?;
+    let items = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_changelog_items(project_id, &since, None).map_err(|e| e.to_string())?
+    };
+
+    let content = match format.as_str() {
+        "json" => changelog::json(&items),
+        _ => serde_json::Value::String(changelog::markdown(&items)),
+    };
+    let mut written_to = None;
+    if write {
+        let repo = repo_path.as_deref().ok_or("Give repo_path to write CHANGELOG.md")?;
+        let path = Path::new(repo).join(changelog::FILE_NAME);
+        let existing = std::fs::read_to_string(&path).ok();
+        let merged = changelog::merge(existing.as_deref(), content.as_str().unwrap_or_default());
+        std::fs::write(&path, merged).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))?;
+        written_to = Some(path.to_string_lossy().to_string());
+    }
+    Ok(serde_json::json!({
+        "since": since,
+        "format": format,
+        "items": items.len(),
+        "content": content,
+        "written_to": written_to,
+    }))
+}
+
+/// Draft release notes from the problems solved over a goal (its start to target date, standing
+/// in for a milestone) or between `since` and `until`. Problems tagged "internal" are left out.
+/// `polish` has the configured language model reword the draft for users.
+#[tauri::command]
+fn generate_release_notes(
+    state: State<AppState>,
+    project_id: i64,
+    goal_id: Option<RecordId>,
+    since: Option<String>,
+    until: Option<String>,
+    polish: Option<bool>
+) -> Result<serde_json::Value, String> {
+    check_goal_date("since", since.as_deref())?;
+    check_goal_date("until", until.as_deref())?;
+    let (heading, since, until, items, client) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let (heading, since, until) = match goal_id {
+            Some(goal_id) => {
+                let goal = db.get_goal(goal_id.resolve(&db, "goals")?).map_err(|e| e.to_string())?;
+                if goal.project_id != project_id {
+                    return Err(format!("Goal '{}' belongs to another project", goal.title));
+                }
+                (goal.title, goal.start_date, goal.target_date)
+            }
+            None => {
+                let since = since.ok_or("Give a goal or a since date")?;
+                let today = db.timezone().today().format("%Y-%m-%d").to_string();
+                (format!("{} to {}", since, until.as_deref().unwrap_or(&today)), since, until)
+            }
+        };
+        let items = db.get_release_note_items(project_id, &since, until.as_deref()).map_err(|e| e.to_string())?;
+        let client = if polish.unwrap_or(false) { Some(llm_client(&state, &db, "generate_release_notes")?) } else { None };
+        (heading, since, until, items, client)
+    };
+
+    let draft = changelog::release_notes(&heading, &items);
+    let polished = match client {
+        Some(client) if !items.is_empty() => {
+            let bullets = client.complete(changelog::RELEASE_NOTES_PROMPT, &draft, 1500)?;
+            Some(format!("## {}\n\n{}\n", heading, bullets))
+        }
+        _ => None,
+    };
+    Ok(serde_json::json!({
+        "heading": heading,
+        "since": since,
+        "until": until,
+        "items": items,
+        "notes": polished.as_ref().unwrap_or(&draft),
+        "draft": draft,
+        "polished": polished.is_some(),
+    }))
+}
+
+// ============================================================
+// v1.4: RETROSPECTIVE COMMANDS
+// ============================================================
+
+/// What went well, what didn't and action items for the `period` (week, sprint or month,
+/// default sprint) ending at `until` (a date, default now), compared with the period before.
+#[tauri::command]
+fn generate_retrospective(
+    state: State<AppState>,
+    project_id: i64,
+    period: Option<String>,
+    until: Option<String>
+) -> Result<serde_json::Value, String> {
+    let period = period.unwrap_or_else(|| "sprint".to_string());
+    if !reports::RETRO_PERIODS.contains(&period.as_str()) {
+        return Err(format!("Unknown period '{}'. Expected one of: {}", period, reports::RETRO_PERIODS.join(", ")));
+    }
+    check_goal_date("until", until.as_deref())?;
+    let end = match until.as_deref() {
+        Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string())?
+            .succ_opt().ok_or("until is out of range")?
+            .and_hms_opt(0, 0, 0).ok_or("until is out of range")?,
+        None => chrono::Utc::now().naive_utc(),
+    };
+    let days = chrono::Duration::days(reports::retro_days(&period));
+    let time = |t: chrono::NaiveDateTime| t.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let project = db.get_project(project_id).map_err(|e| e.to_string())?;
+    let activity = db.get_retro_activity(
+        project_id,
+        &time(end - days - days),
+        &time(end - days),
+        &time(end),
+        reports::RETRO_MIN_FAILURES,
+    ).map_err(|e| e.to_string())?;
+    let retro = reports::classify_retro(&activity);
+    Ok(serde_json::json!({
+        "period": period,
+        "since": activity.since,
+        "until": activity.until,
+        "went_well": retro.went_well,
+        "went_poorly": retro.went_poorly,
+        "action_items": retro.action_items,
+        "markdown": reports::render_retrospective(&project.name, &period, &activity, &retro),
+        "activity": activity,
+    }))
+}
+
+// ============================================================
+// v1.4: ANOMALY ALERT COMMANDS
+// ============================================================
+
+/// Run the anomaly analysis now. New alerts go out as `anomaly-alerts` for dashboards, and as
+/// the `anomalies-detected` notification when the `anomaly_notifications` setting is on and
+/// no focus session is running.
+fn check_anomalies(app: &tauri::AppHandle) -> Result<Vec<database::AnomalyAlert>, String> {
+    let state = app.state::<AppState>();
+    let (alerts, notify) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let alerts = db.detect_anomalies().map_err(|e| e.to_string())?;
+        let notify = setting_string(&db, "anomaly_notifications").as_deref() == Some("true")
+            && !matches!(db.get_focus_state(), Ok(Some(_)));
+        (alerts, notify)
+    };
+    if !alerts.is_empty() {
+        let _ = app.emit("anomaly-alerts", &alerts);
+        if notify {
+            let _ = app.emit("anomalies-detected", &alerts);
+        }
+    }
+    Ok(alerts)
+}
+
+/// Run the hourly anomaly analysis right away. Returns the alerts it raised.
+#[tauri::command]
+fn detect_anomalies(app: tauri::AppHandle) -> Result<Vec<database::AnomalyAlert>, String> {
+    check_anomalies(&app)
+}
+
+/// Unacknowledged alerts, newest first, for one project or all of them.
+#[tauri::command]
+fn get_anomaly_alerts(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    include_acknowledged: Option<bool>
+) -> Result<Vec<database::AnomalyAlert>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_anomaly_alerts(project_id, include_acknowledged.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn acknowledge_anomaly_alert(state: State<AppState>, id: RecordId) -> Result<database::AnomalyAlert, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "anomaly_alerts")?;
+    db.acknowledge_anomaly_alert(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: PEOPLE COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn list_people(state: State<AppState>, include_inactive: Option<bool>) -> Result<Vec<database::Person>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_people(include_inactive.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_person(
+    state: State<AppState>,
+    name: String,
+    email: Option<String>,
+    role: Option<String>,
+    notes: Option<String>,
+) -> Result<database::Person, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_person(&name, email.as_deref(), role.as_deref(), notes.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_person(
+    state: State<AppState>,
+    id: RecordId,
+    name: Option<String>,
+    email: Option<String>,
+    role: Option<String>,
+    notes: Option<String>,
+    active: Option<bool>,
+) -> Result<database::Person, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "people")?;
+    db.update_person(id, name.as_deref(), email.as_deref(), role.as_deref(), notes.as_deref(), active)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_person(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "people")?;
+    db.delete_person(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn assign_todo(app: tauri::AppHandle, state: State<AppState>, todo_id: i64, person_id: Option<i64>) -> Result<database::Todo, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let todo = db.assign_todo(todo_id, person_id).map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "todo", todo_id, "assigned", serde_json::json!(todo));
+    Ok(todo)
+}
+
+#[tauri::command]
+fn assign_problem(app: tauri::AppHandle, state: State<AppState>, problem_id: i64, person_id: Option<i64>) -> Result<database::Problem, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let problem = db.assign_problem(problem_id, person_id).map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, "problem", problem_id, "assigned", serde_json::json!(problem));
+    Ok(problem)
+}
+
+#[tauri::command]
+fn get_work_for_person(state: State<AppState>, person_id: i64, include_closed: Option<bool>) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_work_for_person(person_id, include_closed.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: COMMENT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_comments(state: State<AppState>, entity_type: String, entity_id: i64) -> Result<Vec<database::Comment>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_comments(&entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn add_comment(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    entity_type: String,
+    entity_id: i64,
+    author: Option<String>,
+    body: String,
+) -> Result<database::Comment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let comment = db.add_comment(&entity_type, entity_id, author.as_deref(), &body)
+        .map_err(|e| e.to_string())?;
+    
+    // Comments on an attempt count as activity on its problem
+    let watched = match entity_type.as_str() {
+        "attempt" => db.get_attempt(entity_id).ok().map(|a| ("problem", a.problem_id)),
+        other => Some((other, entity_id)),
+    };
+    if let Some((watched_type, watched_id)) = watched {
+        notify_watchers(&app, &db, watched_type, watched_id, "commented", serde_json::json!(comment));
+    }
+    Ok(comment)
+}
+
+#[tauri::command]
+fn update_comment(state: State<AppState>, id: RecordId, body: String) -> Result<database::Comment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "comments")?;
+    db.update_comment(id, &body).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_comment(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "comments")?;
+    db.delete_comment(id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: PROBLEM CHECKLIST COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_checklist_items(state: State<AppState>, problem_id: i64) -> Result<Vec<database::ChecklistItem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_checklist_items(problem_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn add_checklist_item(state: State<AppState>, problem_id: i64, text: String) -> Result<database::ChecklistItem, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.add_checklist_item(problem_id, &text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_checklist_item(
+    state: State<AppState>,
+    id: RecordId,
+    text: Option<String>,
+    checked: Option<bool>,
+) -> Result<database::ChecklistItem, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "problem_checklist_items")?;
+    db.update_checklist_item(id, text.as_deref(), checked).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_checklist_item(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "problem_checklist_items")?;
+    db.delete_checklist_item(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn reorder_checklist_items(
+    state: State<AppState>,
+    problem_id: i64,
+    ordered_ids: Vec<i64>,
+) -> Result<Vec<database::ChecklistItem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.reorder_checklist_items(problem_id, &ordered_ids).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: PROBLEM TEMPLATE COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_problem_templates() -> Vec<templates::ProblemTemplate> {
+    templates::PROBLEM_TEMPLATES.to_vec()
+}
+
+/// v1.4: Log a problem from a template: `fields` fill its blanks, and the template's checklist
+/// comes along. `severity` overrides the template's default.
+#[tauri::command]
+fn create_problem_from_template(
+    state: State<AppState>,
+    template_id: String,
+    component_id: i64,
+    fields: HashMap<String, String>,
+    severity: Option<String>
+) -> Result<database::Problem, String> {
+    let template = templates::problem_template(&template_id)?;
+    let (title, description) = template.fill(&fields)?;
+    let severity = severity.as_deref().unwrap_or(template.severity);
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.log_problem_with_checklist(component_id, &title, &description, severity, template.checklist)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: TODO TEMPLATE COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_todo_templates(state: State<AppState>) -> Result<Vec<database::TodoTemplate>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_todo_templates().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_todo_template(
+    state: State<AppState>,
+    name: String,
+    description: Option<String>,
+    items: Vec<String>
+) -> Result<database::TodoTemplate, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_todo_template(&name, description.as_deref(), &items).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_todo_template(
+    state: State<AppState>,
+    id: RecordId,
+    name: Option<String>,
+    description: Option<String>,
+    items: Option<Vec<String>>
+) -> Result<database::TodoTemplate, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todo_templates")?;
+    db.update_todo_template(id, name.as_deref(), description.as_deref(), items.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_todo_template(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todo_templates")?;
+    db.delete_todo_template(id).map_err(|e| e.to_string())
+}
+
+/// v1.4: Turn a todo template into a checklist of todos in a project, e.g. the release
+/// checklist for 1.5. Each checklist tracks its own todos.
+#[tauri::command]
+fn instantiate_todo_template(
+    state: State<AppState>,
+    template_id: RecordId,
+    project_id: i64,
+    name: Option<String>,
+    component_id: Option<i64>,
+    due_date: Option<String>
+) -> Result<database::TodoChecklistDetail, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let template_id = template_id.resolve(&db, "todo_templates")?;
+    db.instantiate_todo_template(template_id, project_id, name.as_deref(), component_id, due_date.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_todo_checklists(state: State<AppState>, project_id: i64) -> Result<Vec<database::TodoChecklist>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_todo_checklists(project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_todo_checklist(state: State<AppState>, id: RecordId) -> Result<database::TodoChecklistDetail, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todo_checklists")?;
+    db.get_todo_checklist(id).map_err(|e| e.to_string())
+}
+
+/// Deletes the checklist's todos too, unless `keep_todos`.
+#[tauri::command]
+fn delete_todo_checklist(state: State<AppState>, id: RecordId, keep_todos: Option<bool>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "todo_checklists")?;
+    db.delete_todo_checklist(id, keep_todos.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: PROBLEM ENVIRONMENT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_problem_environment(state: State<AppState>, problem_id: i64) -> Result<Option<database::ProblemEnvironment>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_problem_environment(problem_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_problem_environment(
+    state: State<AppState>,
+    problem_id: i64,
+    environment: database::EnvironmentInput,
+) -> Result<database::ProblemEnvironment, String> {
+    let environment = environment.validated()?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_problem_environment(problem_id, &environment).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn clear_problem_environment(state: State<AppState>, problem_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.clear_problem_environment(problem_id).map_err(|e| e.to_string())
+}
+
+/// Pre-fill an environment from this machine. Nothing is saved; the UI shows it for editing.
+#[tauri::command]
+fn capture_environment(repo_path: Option<String>) -> Result<database::EnvironmentInput, String> {
+    let device = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok();
+    
+    // Branch only when a repository was given and git can answer
+    let branch = repo_path.and_then(|path| {
+        platform::git().ok()?
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(&path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    
+    database::EnvironmentInput {
+        os: Some(format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH)),
+        app_version: Some(format!("FlowState {}", updater::CURRENT_VERSION)),
+        device,
+        branch,
+        repro_steps: Vec::new(),
+    }
+    .validated()
+}
+
+// ============================================================
+// v1.4: NOTES COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn create_note(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    note_date: Option<String>,
+    title: Option<String>,
+    body: String,
+    pinned: Option<bool>,
+) -> Result<database::Note, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_note(project_id, note_date.as_deref(), title.as_deref(), &body, pinned.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_note(state: State<AppState>, id: RecordId) -> Result<database::Note, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "notes")?;
+    db.get_note(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_notes(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    pinned_only: Option<bool>,
+) -> Result<Vec<database::Note>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_notes(project_id, date_from.as_deref(), date_to.as_deref(), pinned_only.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_note(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: RecordId,
+    note_date: Option<String>,
+    title: Option<String>,
+    body: Option<String>,
+    pinned: Option<bool>,
+) -> Result<database::Note, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "notes")?;
+    check_lock(&app, &db, "note", id)?;
+    db.update_note(id, note_date.as_deref(), title.as_deref(), body.as_deref(), pinned)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_note(app: tauri::AppHandle, state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "notes")?;
+    check_lock(&app, &db, "note", id)?;
+    db.delete_note(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_note_links(state: State<AppState>, note_id: i64) -> Result<Vec<database::NoteLink>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_note_links(note_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_backlinks(state: State<AppState>, target_type: String, target_id: i64) -> Result<Vec<database::Note>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_backlinks(&target_type, target_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: QUICK ADD COMMANDS
+// ============================================================
+
+fn interpret_quick_add(db: &Database, project_id: i64, text: &str) -> Result<quickadd::QuickAdd, String> {
+    let components = db.list_components(project_id).map_err(|e| e.to_string())?;
+    let parsed = quickadd::interpret(text, db.timezone().now(), &components);
+    if parsed.title.is_empty() {
+        return Err("Quick add needs a title".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Show how a capture string would be read without creating anything.
+#[tauri::command]
+fn parse_quick_add(state: State<AppState>, project_id: i64, text: String) -> Result<quickadd::QuickAdd, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    interpret_quick_add(&db, project_id, &text)
+}
+
+/// Create the todo, problem, or learning a capture string describes.
+/// Returns the interpretation alongside the new record so the UI can confirm or undo it,
+/// plus the todo holding the due date when a problem was given one.
+#[tauri::command]
+fn quick_add(state: State<AppState>, project_id: i64, text: String) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let parsed = interpret_quick_add(&db, project_id, &text)?;
+    db.quick_add(project_id, &parsed).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_tags(state: State<AppState>, entity_type: String, entity_id: i64) -> Result<Vec<String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_tags(&entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn add_tags(state: State<AppState>, entity_type: String, entity_id: i64, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.add_tags(&entity_type, entity_id, &tags).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn remove_tag(state: State<AppState>, entity_type: String, entity_id: i64, tag: String) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.remove_tag(&entity_type, entity_id, &tag).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: STAR COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn star_record(state: State<AppState>, entity_type: String, entity_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.star_record(&entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn unstar_record(state: State<AppState>, entity_type: String, entity_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.unstar_record(&entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_starred(state: State<AppState>, project_id: Option<i64>) -> Result<Vec<database::StarredRecord>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_starred(project_id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: WATCH COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn watch_record(
+    state: State<AppState>,
+    entity_type: String,
+    entity_id: i64,
+    notify: Option<bool>,
+) -> Result<database::Watch, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.watch_record(&entity_type, entity_id, notify.unwrap_or(true)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn unwatch_record(state: State<AppState>, entity_type: String, entity_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.unwatch_record(&entity_type, entity_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_watches(state: State<AppState>) -> Result<Vec<database::Watch>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_watches().map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: STREAMING RESULT COMMANDS
+// ============================================================
+
+/// Run a command whose result can be large and keep the result under a handle instead of
+/// returning it. With `emit`, chunks are also sent as `result-chunk` events, followed by
+/// `result-complete`; listen before invoking and match events on `result_handle`.
+#[tauri::command]
+fn stream_result(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    command: String,
+    args: Option<serde_json::Value>,
+    emit: Option<bool>
+) -> Result<streaming::ResultHandle, String> {
+    let args = args.unwrap_or_default();
+    let value = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        match command.as_str() {
+            "generate_project_story" => {
+                let project_id = args["project_id"].as_i64().ok_or("generate_project_story needs a project_id")?;
+                build_project_story(&db, project_id)?
+            }
+            "get_project_context" => {
+                let project_name = args["project_name"].as_str().ok_or("get_project_context needs a project_name")?;
+                let fields = serde_json::from_value(args["fields"].clone()).map_err(|e| e.to_string())?;
+                let limits = serde_json::from_value(args["limits"].clone()).map_err(|e| e.to_string())?;
+                let selection = context::Selection::new(fields, limits, args["include_files"].as_bool().unwrap_or(true))?;
+                let hours = args["hours"].as_i64().map(|h| h as i32).unwrap_or(48);
+                build_project_context(&db, project_name, hours, &selection)?
+            }
+            other => return Err(format!("'{}' can't be streamed", other)),
+        }
+    };
+    
+    let handle = state.results.lock().map_err(|e| e.to_string())?.insert(&value);
+    if emit.unwrap_or(true) {
+        let app = app.clone();
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            let state = app.state::<AppState>();
+            for index in 0..handle.total_chunks {
+                let chunk = match state.results.lock() {
+                    Ok(results) => results.chunk(&handle.result_handle, index),
+                    Err(e) => Err(e.to_string()),
+                };
+                match chunk {
+                    Ok(chunk) => { let _ = app.emit("result-chunk", chunk); }
+                    // Released or expired mid-stream; the listener has what it asked for
+                    Err(_) => return,
+                }
+            }
+            let _ = app.emit("result-complete", &handle);
+        });
+    }
+    Ok(handle)
+}
+
+#[tauri::command]
+fn get_result_chunk(state: State<AppState>, result_handle: String, index: usize) -> Result<streaming::ResultChunk, String> {
+    let results = state.results.lock().map_err(|e| e.to_string())?;
+    results.chunk(&result_handle, index)
+}
+
+/// Drop a result once the frontend has it all. Returns false if it was already gone.
+#[tauri::command]
+fn release_result(state: State<AppState>, result_handle: String) -> Result<bool, String> {
+    let mut results = state.results.lock().map_err(|e| e.to_string())?;
+    Ok(results.release(&result_handle))
+}
+
+// ============================================================
+// v1.4: CAPTURE COMMANDS
+// ============================================================
+
+/// Start recording copied text for a project. Only text of at least `min_chars` characters
+/// is kept, and whatever is on the clipboard right now is ignored.
+#[tauri::command]
+fn start_clipboard_capture(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    project_id: i64,
+    min_chars: Option<usize>
+) -> Result<serde_json::Value, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_project(project_id).map_err(|e| e.to_string())?;
+    }
+    let mut capture = state.clipboard_capture.lock().map_err(|e| e.to_string())?;
+    *capture = Some(ClipboardCapture {
+        project_id,
+        min_chars: min_chars.unwrap_or(DEFAULT_CAPTURE_MIN_CHARS).max(1),
+        last_text: app.clipboard().read_text().ok(),
+    });
+    Ok(clipboard_capture_status(capture.as_ref()))
+}
+
+#[tauri::command]
+fn stop_clipboard_capture(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let mut capture = state.clipboard_capture.lock().map_err(|e| e.to_string())?;
+    *capture = None;
+    Ok(clipboard_capture_status(None))
+}
+
+#[tauri::command]
+fn get_clipboard_capture_status(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let capture = state.clipboard_capture.lock().map_err(|e| e.to_string())?;
+    Ok(clipboard_capture_status(capture.as_ref()))
+}
+
+/// Pending captures unless a status (converted, discarded) is asked for.
+#[tauri::command]
+fn get_captures(
+    state: State<AppState>,
+    project_id: i64,
+    status: Option<String>,
+    limit: Option<i64>
+) -> Result<Vec<database::Capture>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_captures(project_id, status.as_deref(), limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn discard_capture(state: State<AppState>, id: i64) -> Result<database::Capture, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let capture = db.set_capture_status(id, "discarded").map_err(|e| e.to_string())?;
+    db.settle_capture_inbox_item(&capture).map_err(|e| e.to_string())?;
+    Ok(capture)
+}
+
+/// Turn a capture into a learning (the capture becomes its context) or a note. `title` is
+/// the learning's insight or the note's title; it defaults to the capture's first line.
+#[tauri::command]
+fn convert_capture(
+    state: State<AppState>,
+    id: i64,
+    target: String,
+    title: Option<String>,
+    category: Option<String>
+) -> Result<database::Capture, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let capture = db.get_capture(id).map_err(|e| e.to_string())?;
+    let title = title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| first_line_title(&capture.content));
+    let body = format!("```\n{}\n```", capture.content.trim());
+    
+    let converted_id = match target.as_str() {
+        "learning" => db.log_learning(
+            capture.project_id,
+            &title,
+            Some(category.as_deref().unwrap_or("tool_tip")),
+            Some(&body),
+            None,
+            "experience",
+        ).map_err(|e| e.to_string())?.id,
+        "note" => db.create_note(Some(capture.project_id), None, Some(&title), &body, false)
+            .map_err(|e| e.to_string())?.id,
+        other => return Err(format!("Captures convert to a learning or a note, not '{}'", other)),
+    };
+    let capture = db.mark_capture_converted(id, &target, converted_id).map_err(|e| e.to_string())?;
+    db.settle_capture_inbox_item(&capture).map_err(|e| e.to_string())?;
+    Ok(capture)
+}
+
+/// Delete discarded captures, or all of a project's captures with `all`.
+#[tauri::command]
+fn clear_captures(state: State<AppState>, project_id: i64, all: Option<bool>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.clear_captures(project_id, all.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+fn clipboard_capture_status(capture: Option<&ClipboardCapture>) -> serde_json::Value {
+    serde_json::json!({
+        "active": capture.is_some(),
+        "project_id": capture.map(|c| c.project_id),
+        "min_chars": capture.map(|c| c.min_chars),
+    })
+}
+
+/// One poll of the clipboard while capture mode is on; new text over the threshold is stored
+/// and announced with `clipboard-captured`. Text that looks like it holds a credential is left
+/// alone, since captures go into the synced database.
+fn capture_clipboard(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let (project_id, text) = {
+        let mut capture = state.clipboard_capture.lock().map_err(|e| e.to_string())?;
+        let Some(capture) = capture.as_mut() else { return Ok(()) };
+        // Images and files on the clipboard aren't text; skip them
+        let Ok(text) = app.clipboard().read_text() else { return Ok(()) };
+        if capture.last_text.as_deref() == Some(text.as_str()) {
+            return Ok(());
+        }
+        capture.last_text = Some(text.clone());
+        let length = text.trim().chars().count();
+        if length < capture.min_chars || length > MAX_CAPTURE_CHARS {
+            return Ok(());
+        }
+        (capture.project_id, text.trim().to_string())
+    };
+    if !secrets::scan_file("clipboard", text.as_bytes()).is_empty() {
+        return Ok(());
+    }
+    
+    let captured = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let captured = db.create_capture(project_id, "clipboard", &text).map_err(|e| e.to_string())?;
+        if captured.status == "pending" {
+            db.add_inbox_item(project_id, "capture", Some(captured.id), &first_line_title(&text), Some(&text))
+                .map_err(|e| e.to_string())?;
+        }
+        captured
+    };
+    let _ = app.emit("clipboard-captured", &captured);
+    Ok(())
+}
+
+/// The first line of some text, cut to a title's length.
+fn first_line_title(text: &str) -> String {
+    let first_line = text.trim().lines().next().unwrap_or_default();
+    first_line.chars().take(120).collect()
+}
+
+// ============================================================
+// v1.4: INBOX COMMANDS
+// ============================================================
+
+/// Pending items, oldest first, unless a status (converted, discarded) is asked for.
+/// `source` narrows it to captures, emails, extractions or manual entries.
+#[tauri::command]
+fn list_inbox(
+    state: State<AppState>,
+    project_id: i64,
+    status: Option<String>,
+    source: Option<String>,
+    limit: Option<i64>
+) -> Result<Vec<database::InboxItem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_inbox(project_id, status.as_deref(), source.as_deref(), limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// Put something in the inbox to triage later: an imported email, an AI extraction, or a
+/// thought typed in by hand (the default). `title` defaults to the content's first line.
+#[tauri::command]
+fn add_inbox_item(
+    state: State<AppState>,
+    project_id: i64,
+    source: Option<String>,
+    source_id: Option<i64>,
+    title: Option<String>,
+    content: Option<String>
+) -> Result<database::InboxItem, String> {
+    let content = content.filter(|c| !c.trim().is_empty());
+    let title = title.filter(|t| !t.trim().is_empty())
+        .or_else(|| content.as_deref().map(first_line_title))
+        .ok_or("An inbox item needs a title or some content")?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.add_inbox_item(project_id, source.as_deref().unwrap_or("manual"), source_id, &title, content.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Put a saved email (.eml) in the inbox, titled with its subject.
+#[tauri::command]
+fn import_email(state: State<AppState>, project_id: i64, path: String) -> Result<database::InboxItem, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+    let email = eml::parse(&bytes)?;
+    let content = email.content();
+    let title = email.subject.clone()
+        .map(|subject| first_line_title(&subject))
+        .or_else(|| Some(first_line_title(&email.body)).filter(|t| !t.is_empty()))
+        .unwrap_or_else(|| "(no subject)".to_string());
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.add_inbox_item(project_id, "email", None, &title, Some(&content).filter(|c| !c.trim().is_empty()).map(String::as_str))
+        .map_err(|e| e.to_string())
+}
+
+/// Triage an inbox item: `convert_to` a todo, problem, learning or note, or "discard" it.
+/// The new record goes in `project_id`, the item's own by default; a problem needs
+/// `component_id`, a component of that project. The item keeps what it became. An
+/// extraction converted to the kind of record it already made keeps that record.
+#[tauri::command]
+fn triage_item(
+    state: State<AppState>,
+    id: RecordId,
+    convert_to: String,
+    project_id: Option<i64>,
+    component_id: Option<i64>
+) -> Result<database::InboxItem, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "inbox_items")?;
+    let item = db.get_inbox_item(id).map_err(|e| e.to_string())?;
+    if item.status != "pending" {
+        return Err(format!("This item was already triaged ({})", item.status));
+    }
+    let body = item.content.as_deref().filter(|c| !c.trim().is_empty());
+    let project_id = project_id.unwrap_or(item.project_id);
+    if let Some(component_id) = component_id {
+        let component = db.get_component(component_id).map_err(|e| e.to_string())?;
+        if component.project_id != project_id {
+            return Err(format!("Component '{}' belongs to another project", component.name));
+        }
+    }
+    let extraction = match (item.source.as_str(), item.source_id) {
+        ("extraction", Some(extraction_id)) => db.get_extraction(extraction_id).ok(),
+        _ => None,
+    };
+    
+    let converted_id = match convert_to.as_str() {
+        _ if extraction.as_ref().is_some_and(|e| e.record_type == convert_to) => extraction.as_ref().map(|e| e.record_id),
+        "todo" => Some(db.add_todo(project_id, &item.title, body, "medium", component_id, None)
+            .map_err(|e| e.to_string())?.id),
+        "problem" => {
+            let component_id = component_id.ok_or("Problems need a component; pass component_id")?;
+            Some(db.log_problem(component_id, &item.title, body, "medium").map_err(|e| e.to_string())?.id)
+        }
+        "learning" => Some(db.log_learning(project_id, &item.title, None, body, component_id, "experience")
+            .map_err(|e| e.to_string())?.id),
+        "note" => Some(db.create_note(Some(project_id), None, Some(&item.title), body.unwrap_or_default(), false)
+            .map_err(|e| e.to_string())?.id),
+        "discard" => None,
+        other => return Err(format!("Inbox items become a todo, problem, learning or note, not '{}'", other)),
+    };
+    
+    // Keep the capture list and extraction reviews in step; the source may be gone already
+    match (item.source.as_str(), item.source_id) {
+        ("capture", Some(capture_id)) => {
+            let _ = match converted_id {
+                Some(converted_id) if convert_to == "learning" || convert_to == "note" => {
+                    db.mark_capture_converted(capture_id, &convert_to, converted_id)
+                }
+                Some(_) => db.set_capture_status(capture_id, "converted"),
+                None => db.set_capture_status(capture_id, "discarded"),
+            };
+        }
+        ("extraction", Some(extraction_id)) => {
+            let _ = db.update_extraction_review(extraction_id, true, Some(converted_id.is_some()));
+        }
+        _ => {}
+    }
+    db.record_triage(id, converted_id.map(|_| convert_to.as_str()), converted_id)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: REFERENCE COMMANDS
+// ============================================================
+
+/// Keep a link with the project. Unless `fetch_metadata` is false, the page's title,
+/// description and favicon fill in whatever wasn't given; offline, the reference is kept
+/// as entered. With `snapshot`, the page is also saved to the bundle so it survives the
+/// site going away; a failed snapshot doesn't lose the reference either.
+#[tauri::command]
+fn add_reference(
+    state: State<AppState>,
+    project_id: i64,
+    url: String,
+    title: Option<String>,
+    description: Option<String>,
+    component_id: Option<i64>,
+    problem_id: Option<i64>,
+    snapshot: Option<bool>,
+    fetch_metadata: Option<bool>
+) -> Result<database::Reference, String> {
+    let url = webref::validate_url(&url)?;
+    let mut reference = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.create_reference(project_id, &url, title.as_deref(), description.as_deref(), component_id, problem_id)
+            .map_err(|e| e.to_string())?
+    };
+    let payload = serde_json::json!({ "reference_id": reference.id });
+    if fetch_metadata.unwrap_or(true) {
+        match webref::fetch_metadata(&url) {
+            Ok(metadata) => {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                reference = apply_page_metadata(&db, reference.id, &metadata, false)?;
+            }
+            Err(e) if network::looks_offline(&e) => {
+                queue_network_operation(&state, "reference_metadata", payload.clone(), &format!("Fetch details of {}", url))?;
+            }
+            Err(_) => {}
+        }
+    }
+    if snapshot.unwrap_or(false) {
+        match save_reference_snapshot(&state, &reference) {
+            Ok(updated) => return Ok(updated),
+            Err(e) if network::looks_offline(&e) => {
+                queue_network_operation(&state, "reference_snapshot", payload, &format!("Snapshot {}", url))?;
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(reference)
+}
+
+#[tauri::command]
+fn get_references(
+    state: State<AppState>,
+    project_id: i64,
+    component_id: Option<i64>,
+    problem_id: Option<i64>
+) -> Result<Vec<database::Reference>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_references(project_id, component_id, problem_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_reference(
+    state: State<AppState>,
+    id: RecordId,
+    url: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    component_id: Option<i64>,
+    problem_id: Option<i64>
+) -> Result<database::Reference, String> {
+    let url = url.map(|u| webref::validate_url(&u)).transpose()?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "web_references")?;
+    db.update_reference(id, url.as_deref(), title.as_deref(), description.as_deref(), component_id, problem_id)
+        .map_err(|e| e.to_string())
+}
+
+/// The snapshot is deleted along with the reference.
+#[tauri::command]
+fn delete_reference(state: State<AppState>, id: RecordId) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, "web_references")?;
+    let reference = db.get_reference(id).map_err(|e| e.to_string())?;
+    db.delete_reference(id).map_err(|e| e.to_string())?;
+    if let Some(path) = reference.archived_html_path {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Fetch a reference's title, description and favicon again, e.g. one added while offline.
+/// Without `overwrite`, only empty fields are filled.
+#[tauri::command]
+fn refresh_reference_metadata(state: State<AppState>, id: i64, overwrite: Option<bool>) -> Result<database::Reference, String> {
+    let url = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_reference(id).map_err(|e| e.to_string())?.url
+    };
+    let metadata = webref::fetch_metadata(&url)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    apply_page_metadata(&db, id, &metadata, overwrite.unwrap_or(false))
+}
+
+fn apply_page_metadata(db: &Database, id: i64, metadata: &webref::PageMetadata, overwrite: bool) -> Result<database::Reference, String> {
+    db.apply_reference_metadata(
+        id,
+        metadata.title.as_deref(),
+        metadata.description.as_deref(),
+        metadata.favicon_url.as_deref(),
+        overwrite,
+    ).map_err(|e| e.to_string())
+}
+
+/// Save (or refresh) the page behind a reference into the project bundle. Offline, the
+/// snapshot is queued and the error says so.
+#[tauri::command]
+fn snapshot_reference(state: State<AppState>, id: i64) -> Result<database::Reference, String> {
+    let reference = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_reference(id).map_err(|e| e.to_string())?
+    };
+    save_reference_snapshot(&state, &reference).map_err(|e| {
+        if !network::looks_offline(&e) {
+            return e;
+        }
+        let payload = serde_json::json!({ "reference_id": id });
+        match queue_network_operation(&state, "reference_snapshot", payload, &format!("Snapshot {}", reference.url)) {
+            Ok(_) => format!("{} (queued until the network is back)", e),
+            Err(_) => e,
+        }
+    })
+}
+
+/// Check a project's links (or just `ids`) for rot. Each result is stored on the reference;
+/// the ones that are broken are returned.
+#[tauri::command]
+fn check_reference_links(
+    state: State<AppState>,
+    project_id: i64,
+    ids: Option<Vec<i64>>
+) -> Result<Vec<database::Reference>, String> {
+    let references: Vec<database::Reference> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_references(project_id, None, None).map_err(|e| e.to_string())?
+    };
+    let mut broken = Vec::new();
+    for reference in references.iter().filter(|r| ids.as_ref().is_none_or(|ids| ids.contains(&r.id))) {
+        // The network is slow; only hold the lock to store each result
+        let check = webref::check(&reference.url);
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let updated = db.set_reference_check(
+            reference.id,
+            if check.broken { "broken" } else { "ok" },
+            check.http_status.map(i64::from),
+        ).map_err(|e| e.to_string())?;
+        if check.broken {
+            broken.push(updated);
+        }
+    }
+    Ok(broken)
+}
+
+fn save_reference_snapshot(state: &AppState, reference: &database::Reference) -> Result<database::Reference, String> {
+    let page = webref::fetch(&reference.url, webref::MAX_SNAPSHOT_BYTES)?;
+    if page.status >= 400 {
+        return Err(format!("{} returned HTTP {}", reference.url, page.status));
+    }
+    let dir = project_bundle_dir(reference.project_id).join("references");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create references directory: {}", e))?;
+    let path = dir.join(format!("reference_{}.html", reference.id));
+    std::fs::write(&path, &page.body).map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_reference_snapshot(reference.id, &path.to_string_lossy()).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: NETWORK QUEUE COMMANDS
+// ============================================================
+
+/// Operations waiting for the network, oldest first. `include_failed` adds the ones that
+/// ran out of retries.
+#[tauri::command]
+fn get_pending_network_operations(state: State<AppState>, include_failed: Option<bool>) -> Result<Vec<database::NetworkOperation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_network_operations(include_failed.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Retry an operation at the next network check, even one that had given up.
+#[tauri::command]
+fn retry_network_operation(state: State<AppState>, id: i64) -> Result<database::NetworkOperation, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.retry_network_operation(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn cancel_network_operation(state: State<AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_network_operation(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_network_status(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let online = network::is_online();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let pending = db.get_network_operations(false).map_err(|e| e.to_string())?.len();
+    Ok(serde_json::json!({
+        "online": online,
+        "pending": pending,
+    }))
+}
+
+fn queue_network_operation(
+    state: &AppState,
+    operation: &str,
+    payload: serde_json::Value,
+    description: &str
+) -> Result<database::NetworkOperation, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.queue_network_operation(operation, &payload, Some(description)).map_err(|e| e.to_string())
+}
+
+/// Check the network and, when it's up, run the operations that are due. Each success is
+/// removed from the queue; each failure waits out its backoff, until it runs out of attempts.
+fn process_network_queue(app: &tauri::AppHandle, was_online: &mut Option<bool>) -> Result<(), String> {
+    let online = network::check_connectivity();
+    if was_online.is_some_and(|was| was != online) {
+        let _ = app.emit("network-status-changed", serde_json::json!({ "online": online }));
+    }
+    *was_online = Some(online);
+    if !online {
+        return Ok(());
+    }
+    
+    let state = app.state::<AppState>();
+    let due = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_due_network_operations().map_err(|e| e.to_string())?
+    };
+    for operation in due {
+        let result = run_network_operation(&state, &operation);
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        match result {
+            Ok(result) => {
+                db.delete_network_operation(operation.id).map_err(|e| e.to_string())?;
+                let _ = app.emit("network-operation-completed", serde_json::json!({
+                    "operation": operation,
+                    "result": result,
+                }));
+            }
+            Err(e) => {
+                let retry_in = network::backoff(operation.attempts + 1).as_secs() as i64;
+                let failed = db.record_network_failure(operation.id, &e, retry_in, network::MAX_ATTEMPTS)
+                    .map_err(|e| e.to_string())?;
+                let _ = app.emit("network-operation-failed", &failed);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_network_operation(state: &AppState, operation: &database::NetworkOperation) -> Result<serde_json::Value, String> {
+    let payload = &operation.payload;
+    match operation.operation.as_str() {
+        "git_sync" => {
+            let path = payload["data_path"].as_str().ok_or("Queued sync has no data path")?;
+            // Changes made since the sync was queued go through the preview like any others
+            let preview = sync_preview(state, path)?;
+            if preview["file_count"] != 0 && preview["confirmation"] != payload["confirmation"] {
+                return Ok(serde_json::json!({
+                    "status": "needs_confirmation",
+                    "message": "There are changes the queued sync wasn't confirmed for; preview the sync and confirm it",
+                    "preview": preview,
+                }));
+            }
+            // A retry is a sync like any other: one at a time, and cancel_sync stops it
+            let control = claim_sync(state, syncjob::Control::silent())?;
+            let result = sync_scope(state)
+                .and_then(|scope| git_sync_at(path, &scope, default_commit_message(state, path), &control));
+            release_sync(state);
+            let result = result?;
+            // Only a failed push is retried; a conflict needs the user, not another try
+            if result["pushed"] == false {
+                return Err("git push failed".to_string());
+            }
+            Ok(result)
+        }
+        "notion_export" => {
+            let project_id = payload["project_id"].as_i64().ok_or("Queued export has no project")?;
+            let entities: Vec<String> = serde_json::from_value(payload["entities"].clone()).unwrap_or_default();
+            export_project_to_notion(state, project_id, &entities)
+        }
+        "confluence_publish" => {
+            let project_id = payload["project_id"].as_i64().ok_or("Queued publish has no project")?;
+            let documents: Vec<String> = serde_json::from_value(payload["documents"].clone()).unwrap_or_default();
+            publish_project_to_confluence(state, project_id, &documents)
+        }
+        "reference_metadata" | "reference_snapshot" => {
+            let id = payload["reference_id"].as_i64().ok_or("Queued operation has no reference")?;
+            let reference = {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                match db.get_reference(id) {
+                    Ok(reference) => reference,
+                    // Deleted while queued; nothing left to do
+                    Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(serde_json::Value::Null),
+                    Err(e) => return Err(e.to_string()),
+                }
+            };
+            let updated = if operation.operation == "reference_snapshot" {
+                save_reference_snapshot(state, &reference)?
+            } else {
+                let metadata = webref::fetch_metadata(&reference.url)?;
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                apply_page_metadata(&db, id, &metadata, false)?
+            };
+            Ok(serde_json::json!(updated))
+        }
+        other => Err(format!("Unknown network operation: {}", other)),
+    }
+}
+
+// ============================================================
+// v1.4: SHELL HISTORY COMMANDS
+// ============================================================
+
+/// Read the commands run between `since` and `until` (default now) from a shell's history.
+/// `mode` decides what happens with them:
+/// - "preview" (default) only lists them, so the user can pick
+/// - "changes" logs each command (or just the picked `commands`) as a change on `component_id`
+/// - "transcript" saves them as a session transcript attached to the project
+#[tauri::command]
+fn import_shell_history(
+    state: State<AppState>,
+    project_id: i64,
+    since: String,
+    shell: Option<String>,
+    until: Option<String>,
+    mode: Option<String>,
+    commands: Option<Vec<String>>,
+    component_id: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let shell = shell.unwrap_or_else(shellhist::default_shell);
+    if !shellhist::SHELLS.contains(&shell.as_str()) {
+        return Err(format!("Unsupported shell '{}' (expected one of {})", shell, shellhist::SHELLS.join(", ")));
+    }
+    let from = shellhist::parse_time(&since).ok_or_else(|| format!("Invalid time '{}'", since))?;
+    let to = match until.as_deref() {
+        Some(t) => shellhist::parse_time(t).ok_or_else(|| format!("Invalid time '{}'", t))?,
+        None => chrono::Utc::now().timestamp(),
+    };
     
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    let path = shellhist::history_path(&shell).ok_or("Cannot locate the home directory")?;
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let entries: Vec<shellhist::HistoryEntry> = shellhist::parse(&shell, &bytes)
+        .into_iter()
+        .filter(|e| e.timestamp >= from && e.timestamp <= to)
+        .filter(|e| commands.as_ref().is_none_or(|picked| picked.contains(&e.command)))
+        .collect();
+    
+    match mode.as_deref().unwrap_or("preview") {
+        "preview" => Ok(serde_json::json!({
+            "shell": shell,
+            "history_file": path.to_string_lossy(),
+            "commands": entries,
+        })),
+        "changes" => {
+            let component_id = component_id.ok_or("A component is required to log commands as changes")?;
+            let changes: Vec<database::NewChange> = entries.iter().map(|e| database::NewChange {
+                component_id,
+                field_name: "shell".to_string(),
+                old_value: None,
+                new_value: Some(e.command.clone()),
+                change_type: Some("other".to_string()),
+                reason: Some(format!("Ran in {} at {} UTC", shell, e.executed_at)),
+            }).collect();
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let logged = db.log_changes_batch(&changes).map_err(|e| e.to_string())?;
+            Ok(serde_json::json!({ "changes": logged }))
+        }
+        "transcript" => {
+            if entries.is_empty() {
+                return Err("No commands in that time window".to_string());
+            }
+            let mut transcript = format!(
+                "# {} session, {} to {} UTC\n\n",
+                shell, entries[0].executed_at, entries[entries.len() - 1].executed_at
+            );
+            for e in &entries {
+                transcript.push_str(&format!("[{}] $ {}\n", e.executed_at, e.command));
+            }
+            
+            let bundle = project_bundle_dir(project_id);
+            std::fs::create_dir_all(&bundle).map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+            let file_name = format!("{}-session-{}.txt", shell, chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+            let file_path = bundle.join(&file_name);
+            std::fs::write(&file_path, &transcript).map_err(|e| format!("Failed to write transcript: {}", e))?;
+            let file_path = file_path.to_string_lossy().to_string();
+            
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let attachment = db.create_attachment(
+                project_id,
+                &file_name,
+                &file_path,
+                "txt",
+                Some(transcript.len() as i64),
+                calculate_file_hash(&file_path).ok().as_deref(),
+                false,
+                component_id,
+                None,
+                Some(&format!("{} commands from {} history", entries.len(), shell)),
+                None,
+            ).map_err(|e| e.to_string())?;
+            let attachment = index_attachment(&db, attachment)?;
+            Ok(serde_json::json!({ "attachment": attachment }))
+        }
+        other => Err(format!("Unknown import mode '{}' (expected preview, changes or transcript)", other)),
+    }
+}
+
+// ============================================================
+// v1.4: DIAGNOSTICS COMMANDS
+// ============================================================
+
+/// Statements slower than 50ms, slowest first.
+#[tauri::command]
+fn get_slow_queries(state: State<AppState>, limit: Option<i64>) -> Result<Vec<database::SlowQuery>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_slow_queries(limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn clear_slow_queries(state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.clear_slow_queries().map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: SHORTCUT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn get_shortcut_map(state: State<AppState>) -> Result<BTreeMap<String, String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    shortcut_map(&db)
+}
+
+/// Bind `action` to `accelerator`. An empty accelerator disables the shortcut; none restores the default.
+#[tauri::command]
+fn set_shortcut(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    action: String,
+    accelerator: Option<String>,
+) -> Result<BTreeMap<String, String>, String> {
+    if !shortcuts::is_known_action(&action) {
+        return Err(format!("Unknown menu action '{}'", action));
     }
+    let key = format!("{}{}", shortcuts::KEY_PREFIX, action);
     
-    // Create .gitignore
-    let gitignore_path = Path::new(&path).join(".gitignore");
-    let gitignore_content = r#"# OS files
-.DS_Store
-Thumbs.db
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        match accelerator.as_deref().map(str::trim) {
+            None => db.delete_setting(&key).map_err(|e| e.to_string())?,
+            Some("") => db.set_setting(&key, "\"\"", Some(shortcuts::SETTINGS_CATEGORY)).map_err(|e| e.to_string())?,
+            Some(raw) => {
+                let accelerator = shortcuts::normalize(raw)?;
+                if let Some(other) = shortcuts::find_conflict(&shortcut_map(&db)?, &action, &accelerator) {
+                    return Err(format!("{} is already used by {}", accelerator, other));
+                }
+                let value = serde_json::to_string(&accelerator).map_err(|e| e.to_string())?;
+                db.set_setting(&key, &value, Some(shortcuts::SETTINGS_CATEGORY)).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    
+    rebuild_menu(&app)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let map = shortcut_map(&db)?;
+    let _ = app.emit("shortcuts-changed", &map);
+    Ok(map)
+}
+
+// ============================================================
+// v1.4: FOCUS MODE COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn start_focus(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    todo_id: i64,
+    minutes: Option<i64>,
+) -> Result<database::FocusState, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    if let Some(active) = db.get_focus_state().map_err(|e| e.to_string())? {
+        return Err(format!("A focus session is already running for \"{}\"", active.todo_title));
+    }
+    
+    let minutes = minutes.unwrap_or(25).max(1);
+    let todo = db.get_todo(todo_id).map_err(|e| e.to_string())?;
+    let session = db.start_session(todo.project_id, todo.component_id, Some(&format!("Focus: {}", todo.title)))
+        .map_err(|e| e.to_string())?;
+    
+    if todo.status == "pending" {
+        db.update_todo(todo.id, None, None, Some("in_progress"), None, None)
+            .map_err(|e| e.to_string())?;
+    }
+    
+    let started_at = chrono::Utc::now();
+    let ends_at = started_at + chrono::Duration::minutes(minutes);
+    let focus = database::FocusState {
+        session_id: session.id,
+        project_id: todo.project_id,
+        todo_id: todo.id,
+        todo_title: todo.title.clone(),
+        planned_minutes: minutes,
+        started_at: started_at.to_rfc3339(),
+        ends_at: ends_at.to_rfc3339(),
+        remaining_seconds: minutes * 60,
+    };
+    db.set_focus_state(Some(&focus)).map_err(|e| e.to_string())?;
+    
+    let _ = app.emit("focus-changed", &focus);
+    Ok(focus)
+}
+
+#[tauri::command]
+fn get_focus_state(state: State<AppState>) -> Result<Option<database::FocusState>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_focus_state().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn end_focus(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    notes: Option<String>,
+) -> Result<database::Session, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let focus = db.get_focus_state().map_err(|e| e.to_string())?
+        .ok_or("No focus session is running")?;
+    
+    let outcomes = serde_json::json!([{
+        "todo_id": focus.todo_id,
+        "planned_minutes": focus.planned_minutes,
+        "notes": notes,
+    }]).to_string();
+    let session = db.end_session(focus.session_id, None, Some(&outcomes))
+        .map_err(|e| e.to_string())?;
+    let minutes = session.duration_minutes.unwrap_or(0);
+    if minutes > 0 {
+        // The todo may have been deleted while the timer ran
+        let _ = db.log_todo_time(focus.todo_id, minutes);
+    }
+    
+    // Changes hang off components, so the focus log lands on the todo's component when it has
+    // one and in a comment on the todo when it doesn't
+    if let Ok(todo) = db.get_todo(focus.todo_id) {
+        let summary = format!("Focused {} min on todo #{} \"{}\"", minutes, todo.id, todo.title);
+        match todo.component_id {
+            Some(component_id) => {
+                db.log_change(component_id, "focus", None, Some(&summary), "other", notes.as_deref())
+                    .map_err(|e| e.to_string())?;
+            }
+            None => {
+                let body = match notes.as_deref() {
+                    Some(notes) => format!("{}\n\n{}", summary, notes),
+                    None => summary,
+                };
+                db.add_comment("todo", todo.id, None, &body).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    
+    db.set_focus_state(None).map_err(|e| e.to_string())?;
+    let _ = app.emit("focus-changed", serde_json::Value::Null);
+    Ok(session)
+}
+
+// ============================================================
+// v1.1: LEGACY IMPORT COMMANDS
+// ============================================================
+
+#[tauri::command]
+fn import_legacy_database(state: State<AppState>, path: String) -> Result<database::LegacyImportReport, String> {
+    let source = Path::new(&path);
+    if !source.exists() {
+        return Err("File not found".to_string());
+    }
+    
+    let active = get_default_db_path();
+    if source.canonicalize().ok() == active.canonicalize().ok() {
+        return Err("Cannot import the active database into itself".to_string());
+    }
+    
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.import_legacy_database(source).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: RECORD HISTORY COMMANDS
+// ============================================================
+
+/// Versions of a problem or solution (`entity` "problem" or "solution"), newest first.
+#[tauri::command]
+fn get_record_history(state: State<AppState>, entity: String, id: RecordId) -> Result<Vec<database::RecordVersion>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, Database::versioned_table(&entity).map_err(|e| e.to_string())?)?;
+    db.get_record_history(&entity, id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn revert_to_version(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    entity: String,
+    id: RecordId,
+    version: i64
+) -> Result<database::RecordVersion, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = id.resolve(&db, Database::versioned_table(&entity).map_err(|e| e.to_string())?)?;
+    check_lock(&app, &db, &entity, id)?;
+    let reverted = db.revert_to_version(&entity, id, version).map_err(|e| e.to_string())?;
+    notify_watchers(&app, &db, &entity, id, "updated", serde_json::json!(reverted));
+    Ok(reverted)
+}
+
+// ============================================================
+// v1.4: REPORT EMAIL COMMANDS
+// ============================================================
+
+/// Email a weekly review or standup right away, to try the SMTP settings. `kind` and
+/// `project_id` default to the scheduled report's. The attempt is logged either way.
+#[tauri::command]
+fn send_report_now(state: State<AppState>, kind: Option<String>, project_id: Option<i64>) -> Result<database::ReportEmail, String> {
+    let email = send_report(&state, kind, project_id, false)?;
+    match email.error {
+        Some(e) => Err(e),
+        None => Ok(email),
+    }
+}
+
+/// The subject and Markdown body a report would be sent with.
+#[tauri::command]
+fn preview_report(state: State<AppState>, kind: Option<String>, project_id: Option<i64>) -> Result<serde_json::Value, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (kind, project_id) = report_target(&db, kind, project_id)?;
+    let (subject, body) = build_report(&db, &kind, project_id)?;
+    Ok(serde_json::json!({ "kind": kind, "subject": subject, "body": body }))
+}
+
+#[tauri::command]
+fn get_report_emails(state: State<AppState>, limit: Option<i64>) -> Result<Vec<database::ReportEmail>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_report_emails(limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// Which report to build: the arguments, else the report_email_kind and
+/// report_email_project_id settings (no project means every project).
+fn report_target(db: &Database, kind: Option<String>, project_id: Option<i64>) -> Result<(String, Option<i64>), String> {
+    let kind = kind
+        .or_else(|| setting_string(db, "report_email_kind"))
+        .unwrap_or_else(|| "weekly_review".to_string());
+    if !reports::KINDS.contains(&kind.as_str()) {
+        return Err(format!("Unknown report '{}' (weekly_review or standup)", kind));
+    }
+    let project_id = project_id.or_else(|| setting_string(db, "report_email_project_id").and_then(|id| id.parse().ok()));
+    Ok((kind, project_id))
+}
+
+fn build_report(db: &Database, kind: &str, project_id: Option<i64>) -> Result<(String, String), String> {
+    let now = db.timezone().now();
+    let since = reports::period_start(kind, now)
+        .with_timezone(&chrono::Utc)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let scope = match project_id {
+        Some(id) => Some(db.get_project(id).map_err(|e| e.to_string())?.name),
+        None => None,
+    };
+    let activity = db.get_report_activity(project_id, &since).map_err(|e| e.to_string())?;
+    Ok((
+        reports::subject(kind, scope.as_deref(), now),
+        reports::render(kind, scope.as_deref(), &activity, now),
+    ))
+}
+
+/// The smtp_* settings and the report_email_to recipients (comma-separated).
+fn smtp_settings(db: &Database) -> Result<(mailer::SmtpConfig, Vec<String>), String> {
+    let host = setting_string(db, "smtp_host").ok_or("SMTP server not configured (setting: smtp_host)")?;
+    let security = setting_string(db, "smtp_security").unwrap_or_else(|| "starttls".to_string());
+    let port = match setting_string(db, "smtp_port") {
+        Some(port) => port.trim().parse().map_err(|_| format!("Invalid SMTP port '{}'", port))?,
+        None if security == "tls" => 465,
+        None => 587,
+    };
+    let username = setting_string(db, "smtp_username");
+    let from = setting_string(db, "report_email_from")
+        .or_else(|| username.clone())
+        .ok_or("Sender not configured (setting: report_email_from)")?;
+    let to: Vec<String> = setting_string(db, "report_email_to")
+        .unwrap_or_default()
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect();
+    if to.is_empty() {
+        return Err("No recipient configured (setting: report_email_to)".to_string());
+    }
+    let password = setting_string(db, "smtp_password");
+    Ok((mailer::SmtpConfig { host, port, security, username, password, from }, to))
+}
+
+/// Build a report and email it, logging the attempt. Everything is read under the lock;
+/// the SMTP conversation happens without it.
+fn send_report(state: &AppState, kind: Option<String>, project_id: Option<i64>, scheduled: bool) -> Result<database::ReportEmail, String> {
+    let (kind, project_id, config, to, subject, body) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let (kind, project_id) = report_target(&db, kind, project_id)?;
+        let (config, to) = smtp_settings(&db)?;
+        let (subject, body) = build_report(&db, &kind, project_id)?;
+        (kind, project_id, config, to, subject, body)
+    };
+    let error = mailer::send(&config, &to, &subject, &body).err();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.log_report_email(&kind, project_id, &to.join(", "), &subject, error.as_deref(), scheduled)
+        .map_err(|e| e.to_string())
+}
+
+/// Send Friday's report once the report_email_hour (in the user's timezone) has passed. A send
+/// that failed because the network was down is tried again on the next check; any other
+/// failure waits for next Friday, so a wrong password isn't retried all afternoon.
+fn send_scheduled_report(app: &tauri::AppHandle) -> Result<(), String> {
+    use chrono::{Datelike, Timelike};
+    let state = app.state::<AppState>();
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let zone = db.timezone();
+        let now = zone.now();
+        if now.weekday() != chrono::Weekday::Fri {
+            return Ok(());
+        }
+        let enabled = setting_string(&db, "report_email_enabled").is_some_and(|v| v == "true");
+        let hour = setting_string(&db, "report_email_hour").and_then(|h| h.parse::<u32>().ok()).unwrap_or(16);
+        if !enabled || now.hour() < hour {
+            return Ok(());
+        }
+        let (day_start, day_end) = zone.day_bounds(now.date_naive());
+        let earlier = db.get_scheduled_report_emails(&timezone::stored(day_start), &timezone::stored(day_end))
+            .map_err(|e| e.to_string())?;
+        if earlier.iter().any(|email| !email.error.as_deref().is_some_and(network::looks_offline)) {
+            return Ok(());
+        }
+    }
+    if !network::is_online() {
+        return Ok(());
+    }
+    let email = send_report(&state, None, None, true)?;
+    let event = if email.error.is_some() { "report-email-failed" } else { "report-email-sent" };
+    let _ = app.emit(event, &email);
+    Ok(())
+}
+
+// ============================================================
+// v1.4: RECORD UUID COMMANDS
+// ============================================================
+
+/// The uuid of a record, for links that have to survive export, merge and sync.
+/// `table` is the table name ("problems", "notes", ...).
+#[tauri::command]
+fn get_record_uuid(state: State<AppState>, table: String, id: i64) -> Result<Option<String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.uuid_for_id(&table, id).map_err(|e| e.to_string())
+}
+
+// ============================================================
+// v1.4: AUTHOR IDENTITY COMMANDS
+// ============================================================
+
+// Who new records are attributed to, and (v1.4 locks) which device this is. It's kept per
+// machine, outside the synced database, so two people sharing a sync repository each keep
+// their own.
+const IDENTITY_FILE: &str = "identity.json";
+/// v1.4: The machine's own tables, such as the local API request log (see `Database::attach_local`).
+const LOCAL_DB_FILE: &str = "local.db";
+
+fn read_identity() -> serde_json::Value {
+    std::fs::read_to_string(platform::local_dir().join(IDENTITY_FILE)).ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+fn write_identity(identity: &serde_json::Value) -> Result<(), String> {
+    let dir = platform::local_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(IDENTITY_FILE), identity.to_string()).map_err(|e| e.to_string())
+}
+
+/// This machine's author: the saved name, or the login name until one is saved. An empty
+/// saved name turns attribution off.
+fn author_identity() -> Option<String> {
+    let saved = read_identity()["name"].as_str().map(|name| name.trim().to_string());
+    match saved {
+        Some(name) => Some(name).filter(|name| !name.is_empty()),
+        None => std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok()
+            .filter(|name| !name.trim().is_empty()),
+    }
+}
+
+#[tauri::command]
+fn get_author_identity() -> Option<String> {
+    author_identity()
+}
+
+/// Save the name new records on this machine are attributed to. Records already created keep
+/// their author.
+#[tauri::command]
+fn set_author_identity(state: State<AppState>, name: String) -> Result<Option<String>, String> {
+    let mut identity = read_identity();
+    identity["name"] = serde_json::json!(name.trim());
+    write_identity(&identity)?;
+    
+    let author = author_identity();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_author(author.as_deref()).map_err(|e| e.to_string())?;
+    Ok(author)
+}
+
+/// A random id for this device, made the first time it's needed.
+fn device_id() -> Result<String, String> {
+    let mut identity = read_identity();
+    if let Some(device) = identity["device"].as_str() {
+        return Ok(device.to_string());
+    }
+    let device = uuid::Uuid::new_v4().to_string();
+    identity["device"] = serde_json::json!(device);
+    write_identity(&identity)?;
+    Ok(device)
+}
+
+// ============================================================
+// v1.4: RECORD LOCK COMMANDS
+// ============================================================
+
+// Lockable records, by the entity names the UI uses
+const LOCKABLE: &[(&str, &str)] = &[
+    ("project", "projects"),
+    ("component", "components"),
+    ("problem", "problems"),
+    ("solution", "solutions"),
+    ("todo", "todos"),
+    ("learning", "learnings"),
+    ("note", "notes"),
+];
+
+fn lock_table(entity: &str) -> Result<&'static str, String> {
+    LOCKABLE.iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, table)| *table)
+        .ok_or_else(|| format!("A {} can't be locked", entity))
+}
+
+/// The table and uuid a lock on the record is filed under.
+fn lock_target(db: &Database, entity: &str, id: RecordId) -> Result<(&'static str, String), String> {
+    let table = lock_table(entity)?;
+    let id = id.resolve(db, table)?;
+    let uuid = db.uuid_for_id(table, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No {} with id {}", entity, id))?;
+    Ok((table, uuid))
+}
+
+fn locks_dir() -> PathBuf {
+    platform::data_dir().join(locks::DIR)
+}
+
+/// Why check_lock stopped an edit: another device holds the lock, or looking for one failed.
+/// Commands pass either on as its message; the local API answers a held lock with 409.
+enum LockError {
+    Held(String),
+    Other(String),
+}
+
+impl From<String> for LockError {
+    fn from(e: String) -> Self {
+        LockError::Other(e)
+    }
+}
+
+impl From<LockError> for String {
+    fn from(e: LockError) -> Self {
+        match e {
+            LockError::Held(message) | LockError::Other(message) => message,
+        }
+    }
+}
+
+/// Before an edit in shared mode (setting: shared_mode), look for another device's lock on the
+/// record. The edit goes ahead with a `record-locked` warning to the UI, or fails when the
+/// setting lock_mode is "strict".
+fn check_lock(app: &tauri::AppHandle, db: &Database, entity: &str, id: i64) -> Result<(), LockError> {
+    if setting_string(db, "shared_mode").as_deref() != Some("true") {
+        return Ok(());
+    }
+    let (table, uuid) = lock_target(db, entity, RecordId::Id(id))?;
+    let device = device_id()?;
+    if let Some(lock) = locks::get(&locks_dir(), table, &uuid).filter(|lock| lock.device != device) {
+        if setting_string(db, "lock_mode").as_deref() == Some("strict") {
+            return Err(LockError::Held(format!("This {} is locked by {}", entity, lock.describe())));
+        }
+        let _ = app.emit("record-locked", serde_json::json!({ "entity": entity, "id": id, "lock": lock }));
+    }
+    Ok(())
+}
+
+/// Lock a record for `minutes` (default 60) so other devices sharing the sync repository are
+/// warned off editing it. Acquiring again renews the lock. The lock reaches them on the next sync.
+#[tauri::command]
+fn acquire_lock(state: State<AppState>, entity: String, id: RecordId, minutes: Option<i64>) -> Result<locks::Lock, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (table, uuid) = lock_target(&db, &entity, id)?;
+    let holder = db.author().map_err(|e| e.to_string())?;
+    locks::acquire(
+        &locks_dir(),
+        table,
+        &uuid,
+        &device_id()?,
+        holder.as_deref(),
+        minutes.unwrap_or(locks::DEFAULT_MINUTES),
+    )
+}
 
-# Temporary files
-*.sqlite-journal
-*.sqlite-wal
-*.sqlite-shm
-*.tmp
-*.bak
+/// Release a lock this device holds. `force` also removes another device's lock, for when
+/// it's gone away without releasing it. Returns whether there was a lock.
+#[tauri::command]
+fn release_lock(state: State<AppState>, entity: String, id: RecordId, force: Option<bool>) -> Result<bool, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (table, uuid) = lock_target(&db, &entity, id)?;
+    locks::release(&locks_dir(), table, &uuid, &device_id()?, force.unwrap_or(false))
+}
 
-# Local backups
-*.local-backup-*
-"#;
-    std::fs::write(gitignore_path, gitignore_content)
-        .map_err(|e| format!("Failed to create .gitignore: {}", e))?;
-    
-    // Initial commit
-    let _ = Command::new("git")
-        .args(["add", "."])
-        .current_dir(&path)
-        .output();
-    
-    let _ = Command::new("git")
-        .args(["commit", "-m", "FlowState initialized"])
-        .current_dir(&path)
-        .output();
+/// The lock on a record, unless there's none or it has expired.
+#[tauri::command]
+fn get_lock(state: State<AppState>, entity: String, id: RecordId) -> Result<Option<locks::Lock>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (table, uuid) = lock_target(&db, &entity, id)?;
+    Ok(locks::get(&locks_dir(), table, &uuid))
+}
+
+/// Every unexpired lock, oldest first.
+#[tauri::command]
+fn list_locks() -> Vec<locks::Lock> {
+    locks::list(&locks_dir()).into_iter().filter(|lock| !lock.is_expired()).collect()
+}
+
+/// Delete expired locks now rather than at the next sync. Returns how many went.
+#[tauri::command]
+fn expire_stale_locks() -> usize {
+    locks::expire_stale(&locks_dir())
+}
+
+// ============================================================
+// v1.4: PROJECT EXPORT & MERGE COMMANDS
+// ============================================================
+
+/// Write a project and everything under it to a portable JSON file for another database
+/// (or this one, later) to merge.
+#[tauri::command]
+fn export_project(state: State<AppState>, project_id: i64, path: String) -> Result<serde_json::Value, String> {
+    let export = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.export_project(project_id).map_err(|e| e.to_string())?
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write export: {}", e))?;
     
+    let records: usize = export.records.values().map(|r| r.len()).sum();
     Ok(serde_json::json!({
-        "status": "initialized",
         "path": path,
+        "records": records,
     }))
 }
 
+/// v1.4: Write a project's data to a standalone SQLite file collaborators can open in any
+/// SQLite browser; settings, secrets and local file paths stay behind. The file is left
+/// read-only. An existing file is never replaced: the report is written next to it under a
+/// temporary name and only moved into place if `path` is still free.
 #[tauri::command]
-fn git_status(data_path: Option<String>) -> Result<serde_json::Value, String> {
-    let path = data_path.unwrap_or_else(get_flowstate_data_path);
-    
-    // Check if git is initialized
-    let git_dir = Path::new(&path).join(".git");
-    if !git_dir.exists() {
-        return Ok(serde_json::json!({
-            "initialized": false,
-            "status": "not_initialized",
-        }));
+fn export_report_db(state: State<AppState>, project_id: i64, path: String) -> Result<serde_json::Value, String> {
+    let file = std::path::Path::new(&path);
+    if file.exists() {
+        return Err(format!("{} already exists; pick a new file for the report", path));
     }
-    
-    // Get status
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to run git status: {}", e))?;
-    
-    let changes = String::from_utf8_lossy(&output.stdout);
-    let pending_changes = changes.lines().count();
-    
-    // Check for remote
-    let remote_output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .current_dir(&path)
-        .output()
-        .ok();
-    
-    let remote_url = remote_output
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
-    
-    // Get last commit
-    let log_output = Command::new("git")
-        .args(["log", "-1", "--format=%H|%s|%ai"])
-        .current_dir(&path)
-        .output()
-        .ok();
-    
-    let last_commit = log_output
-        .filter(|o| o.status.success())
-        .map(|o| {
-            let s = String::from_utf8_lossy(&o.stdout);
-            let parts: Vec<&str> = s.trim().split('|').collect();
-            if parts.len() >= 3 {
-                serde_json::json!({
-                    "hash": parts[0],
-                    "message": parts[1],
-                    "date": parts[2],
-                })
-            } else {
-                serde_json::json!(null)
-            }
-        });
-    
+    let file_name = file.file_name().ok_or_else(|| format!("{} isn't a file path", path))?;
+    let partial = file.with_file_name(format!(".{}.{}.part", file_name.to_string_lossy(), std::process::id()));
+    let written = (|| -> Result<BTreeMap<String, i64>, String> {
+        let tables = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.export_report_db(project_id, &partial).map_err(|e| e.to_string())?
+        };
+        let mut permissions = std::fs::metadata(&partial).map_err(|e| e.to_string())?.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&partial, permissions).map_err(|e| format!("Failed to make {} read-only: {}", path, e))?;
+        if file.exists() {
+            return Err(format!("{} appeared while the report was written; pick a new file", path));
+        }
+        std::fs::rename(&partial, file).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        Ok(tables)
+    })();
+    let tables = match written {
+        Ok(tables) => tables,
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+
+    let records: i64 = tables.values().sum();
     Ok(serde_json::json!({
-        "initialized": true,
-        "pending_changes": pending_changes,
-        "has_changes": pending_changes > 0,
-        "remote_url": remote_url,
-        "has_remote": remote_url.is_some(),
-        "last_commit": last_commit,
+        "path": path,
+        "tables": tables,
+        "records": records,
     }))
 }
 
+/// Merge a project export into this database instead of duplicating it. `strategy` is
+/// "last_writer_wins" (default) or "field"; with `dry_run` nothing is written and the
+/// report shows what would happen.
 #[tauri::command]
-fn git_sync(data_path: Option<String>, commit_message: Option<String>) -> Result<serde_json::Value, String> {
-    let path = data_path.unwrap_or_else(get_flowstate_data_path);
+fn import_project(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    path: String,
+    strategy: Option<String>,
+    dry_run: Option<bool>
+) -> Result<database::MergeReport, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: database::ProjectExport = serde_json::from_str(&content)
+        .map_err(|e| format!("Not a FlowState project export: {}", e))?;
+    let dry_run = dry_run.unwrap_or(false);
     
-    // Check if git is initialized
-    let git_dir = Path::new(&path).join(".git");
-    if !git_dir.exists() {
-        return Err("Git not initialized. Run git_init first.".to_string());
+    let report = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.merge_project_export(&export, strategy.as_deref().unwrap_or("last_writer_wins"), dry_run)
+            .map_err(|e| e.to_string())?
+    };
+    if !dry_run {
+        let _ = rebuild_menu(&app);
     }
+    Ok(report)
+}
+
+// ============================================================
+// v1.4: NOTION EXPORT COMMANDS
+// ============================================================
+
+/// Export a project's records to Notion. Offline, the export is queued and runs once the
+/// network is back (status "queued").
+#[tauri::command]
+fn export_to_notion(state: State<AppState>, project_id: i64, entities: Option<Vec<String>>) -> Result<serde_json::Value, String> {
+    let entities = entities.unwrap_or_else(|| {
+        notion::EXPORTABLE_ENTITIES.iter().map(|e| e.to_string()).collect()
+    });
     
-    // Add all changes
-    let add_output = Command::new("git")
-        .args(["add", "."])
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to git add: {}", e))?;
-    
-    if !add_output.status.success() {
-        return Err(format!("git add failed: {}", String::from_utf8_lossy(&add_output.stderr)));
+    let result = if network::is_online() {
+        export_project_to_notion(&state, project_id, &entities)
+    } else {
+        Err("Failed to reach Notion: offline".to_string())
+    };
+    match result {
+        Err(e) if network::looks_offline(&e) => {
+            let payload = serde_json::json!({ "project_id": project_id, "entities": entities });
+            let operation = queue_network_operation(&state, "notion_export", payload, "Export to Notion")?;
+            Ok(serde_json::json!({
+                "status": "queued",
+                "queued_operation_id": operation.id,
+                "message": e,
+            }))
+        }
+        other => other,
     }
-    
-    // Check if there are changes to commit
-    let status_output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to check status: {}", e))?;
-    
-    let has_changes = !String::from_utf8_lossy(&status_output.stdout).is_empty();
-    
-    if has_changes {
-        // Commit
-        let message = commit_message.unwrap_or_else(|| {
-            format!("FlowState sync - {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"))
-        });
-        
-        let commit_output = Command::new("git")
-            .args(["commit", "-m", &message])
-            .current_dir(&path)
-            .output()
-            .map_err(|e| format!("Failed to git commit: {}", e))?;
+}
+
+fn export_project_to_notion(state: &AppState, project_id: i64, entities: &[String]) -> Result<serde_json::Value, String> {
+    // Gather configuration and records under the lock, then release it for the network calls
+    let (token, parent_page_id, overrides, project_name, batches) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let token = setting_string(&db, "notion_token")
+            .ok_or("Notion token not configured (setting: notion_token)")?;
+        let parent_page_id = setting_string(&db, "notion_parent_page_id");
+        let overrides = db.get_setting_value("notion_mapping").map_err(|e| e.to_string())?;
+        let project = db.get_project(project_id).map_err(|e| e.to_string())?;
         
-        if !commit_output.status.success() {
-            return Err(format!("git commit failed: {}", String::from_utf8_lossy(&commit_output.stderr)));
+        let mut batches = Vec::new();
+        for entity in entities {
+            let records: Vec<(i64, serde_json::Value)> = match entity.as_str() {
+                "component" => db.list_components(project_id).map_err(|e| e.to_string())?
+                    .into_iter().map(|c| (c.id, serde_json::json!(c))).collect(),
+                "problem" => db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())?
+                    .into_iter().map(|p| (p.id, serde_json::json!(p))).collect(),
+                "learning" => db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?
+                    .into_iter().map(|l| (l.id, serde_json::json!(l))).collect(),
+                other => return Err(format!("Unsupported Notion entity: {}", other)),
+            };
+            let database_id = db.get_integration_link("notion", &format!("{}_database", entity), project_id)
+                .map_err(|e| e.to_string())?
+                .map(|link| link.external_id);
+            let page_ids: HashMap<i64, String> = db.get_integration_links("notion", Some(entity))
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|link| (link.entity_id, link.external_id))
+                .collect();
+            batches.push((entity.clone(), records, database_id, page_ids));
         }
-    }
-    
-    // Check if remote exists
-    let remote_output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .current_dir(&path)
-        .output();
+        (token, parent_page_id, overrides, project.name, batches)
+    };
     
-    let has_remote = remote_output
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    let client = notion::NotionClient::new(&token);
+    let mut summary = Vec::new();
     
-    if has_remote {
-        // Pull with rebase
-        let pull_output = Command::new("git")
-            .args(["pull", "--rebase", "origin", "main"])
-            .current_dir(&path)
-            .output();
+    for (entity, records, known_database_id, page_ids) in batches {
+        let mapping = notion::resolve_mapping(&entity, overrides.as_ref())
+            .ok_or_else(|| format!("No Notion mapping for {}", entity))?;
         
-        if let Ok(output) = pull_output {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                if stderr.contains("conflict") {
-                    return Ok(serde_json::json!({
-                        "status": "conflict",
-                        "message": "Sync conflict detected. Your local changes have been preserved.",
-                        "committed": has_changes,
-                    }));
-                }
-                // Ignore other pull errors (e.g., no remote tracking)
+        let database_id = match mapping.database_id.clone().or(known_database_id) {
+            Some(id) => id,
+            None => {
+                let parent = parent_page_id.as_deref()
+                    .ok_or("Notion parent page not configured (setting: notion_parent_page_id)")?;
+                let title = format!("{} – {}", project_name, mapping.database_title);
+                let id = client.create_database(parent, &title, notion::database_schema(&mapping))?;
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                db.upsert_integration_link("notion", &format!("{}_database", entity), project_id, &id, Some(parent))
+                    .map_err(|e| e.to_string())?;
+                id
             }
-        }
+        };
         
-        // Push
-        let push_output = Command::new("git")
-            .args(["push", "origin", "main"])
-            .current_dir(&path)
-            .output();
+        let mut created = 0;
+        let mut updated = 0;
+        let mut failed = Vec::new();
         
-        let pushed = push_output
-            .map(|o| o.status.success())
-            .unwrap_or(false);
+        for (record_id, record) in records {
+            let properties = notion::page_properties(&mapping, &record);
+            
+            // Existing pages are updated in place; pages deleted on the Notion side are recreated
+            let existing = match page_ids.get(&record_id) {
+                Some(page_id) => match client.update_page(page_id, properties.clone()) {
+                    Ok(notion::PageUpdate::Updated) => Ok(Some(page_id.clone())),
+                    Ok(notion::PageUpdate::Missing) => Ok(None),
+                    Err(e) => Err(e),
+                },
+                None => Ok(None),
+            };
+            let result = match existing {
+                Ok(Some(page_id)) => Ok((page_id, false)),
+                Ok(None) => client
+                    .create_page(&database_id, properties, notion::page_body(&entity, &record))
+                    .map(|page_id| (page_id, true)),
+                Err(e) => Err(e),
+            };
+            
+            match result {
+                Ok((page_id, was_created)) => {
+                    let db = state.db.lock().map_err(|e| e.to_string())?;
+                    db.upsert_integration_link("notion", &entity, record_id, &page_id, Some(&database_id))
+                        .map_err(|e| e.to_string())?;
+                    if was_created { created += 1 } else { updated += 1 }
+                }
+                Err(e) => failed.push(serde_json::json!({ "id": record_id, "error": e })),
+            }
+        }
         
-        return Ok(serde_json::json!({
-            "status": "synced",
-            "committed": has_changes,
-            "pushed": pushed,
+        summary.push(serde_json::json!({
+            "entity": entity,
+            "database_id": database_id,
+            "created": created,
+            "updated": updated,
+            "failed": failed,
         }));
     }
     
     Ok(serde_json::json!({
-        "status": "committed_local",
-        "committed": has_changes,
-        "message": "Changes committed locally. No remote configured.",
+        "project": project_name,
+        "exports": summary,
     }))
 }
 
 #[tauri::command]
-fn git_set_remote(data_path: Option<String>, remote_url: String) -> Result<serde_json::Value, String> {
-    let path = data_path.unwrap_or_else(get_flowstate_data_path);
-    
-    // Check if remote exists
-    let check_output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .current_dir(&path)
-        .output();
-    
-    let has_existing_remote = check_output
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-    
-    // Set or update remote
-    let args = if has_existing_remote {
-        vec!["remote", "set-url", "origin", &remote_url]
-    } else {
-        vec!["remote", "add", "origin", &remote_url]
-    };
-    
-    let output = Command::new("git")
-        .args(&args)
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to set remote: {}", e))?;
-    
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-    
-    Ok(serde_json::json!({
-        "status": "success",
-        "remote_url": remote_url,
-        "action": if has_existing_remote { "updated" } else { "added" },
-    }))
+fn get_notion_links(state: State<AppState>, entity_type: Option<String>) -> Result<Vec<database::IntegrationLink>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_integration_links("notion", entity_type.as_deref()).map_err(|e| e.to_string())
 }
 
+// ============================================================
+// v1.4: CONFLUENCE PUBLISHING COMMANDS
+// ============================================================
+
+/// Publish project documents ("story", "decisions", "learnings"; default all) as pages in
+/// the configured Confluence space. Offline, publishing is queued like a Notion export.
 #[tauri::command]
-fn git_clone(remote_url: String, local_path: Option<String>) -> Result<serde_json::Value, String> {
-    let path = local_path.unwrap_or_else(get_flowstate_data_path);
-    
-    // Check if path already exists and has content
-    let path_obj = Path::new(&path);
-    if path_obj.exists() && path_obj.read_dir().map(|mut d| d.next().is_some()).unwrap_or(false) {
-        return Err("Target directory is not empty".to_string());
+fn publish_to_confluence(state: State<AppState>, project_id: i64, documents: Option<Vec<String>>) -> Result<serde_json::Value, String> {
+    let documents = documents.unwrap_or_else(|| {
+        confluence::DOCUMENTS.iter().map(|d| d.to_string()).collect()
+    });
+    if let Some(unknown) = documents.iter().find(|d| !confluence::DOCUMENTS.contains(&d.as_str())) {
+        return Err(format!("Unknown Confluence document '{}' (story, decisions or learnings)", unknown));
     }
     
-    // Create parent directory if needed
-    if let Some(parent) = path_obj.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    let result = if network::is_online() {
+        publish_project_to_confluence(&state, project_id, &documents)
+    } else {
+        Err("Failed to reach Confluence: offline".to_string())
+    };
+    match result {
+        Err(e) if network::looks_offline(&e) => {
+            let payload = serde_json::json!({ "project_id": project_id, "documents": documents });
+            let operation = queue_network_operation(&state, "confluence_publish", payload, "Publish to Confluence")?;
+            Ok(serde_json::json!({
+                "status": "queued",
+                "queued_operation_id": operation.id,
+                "message": e,
+            }))
+        }
+        other => other,
     }
+}
+
+fn publish_project_to_confluence(state: &AppState, project_id: i64, documents: &[String]) -> Result<serde_json::Value, String> {
+    let (base_url, client, space_key, parent_id, project_name) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let base_url = setting_string(&db, "confluence_base_url")
+            .ok_or("Confluence not configured (setting: confluence_base_url)")?;
+        let token = setting_string(&db, "confluence_token")
+            .ok_or("Confluence token not configured (setting: confluence_token)")?;
+        let space_key = setting_string(&db, "confluence_space_key")
+            .ok_or("Confluence space not configured (setting: confluence_space_key)")?;
+        let client = confluence::ConfluenceClient::new(&base_url, setting_string(&db, "confluence_email").as_deref(), &token);
+        let project = db.get_project(project_id).map_err(|e| e.to_string())?;
+        (base_url, client, space_key, setting_string(&db, "confluence_parent_page_id"), project.name)
+    };
     
-    // Clone
-    let output = Command::new("git")
-        .args(["clone", &remote_url, &path])
-        .output()
-        .map_err(|e| format!("Failed to git clone: {}", e))?;
-    
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    let mut published = Vec::new();
+    let mut failed = Vec::new();
+    for document in documents {
+        let blocks = match document.as_str() {
+            "story" => story::blocks("narrative", &gather_story_source(state, project_id)?.0)?,
+            "decisions" => {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                let conversations = db.get_conversations(project_id, Some(i32::MAX)).map_err(|e| e.to_string())?;
+                let mut solved = Vec::new();
+                for problem in db.get_all_problems(Some(project_id), None, None, None).map_err(|e| e.to_string())? {
+                    if let Some(solution) = db.get_solution_for_problem(problem.id).map_err(|e| e.to_string())? {
+                        solved.push((problem, solution));
+                    }
+                }
+                confluence::decision_blocks(&conversations, &solved)
+            }
+            "learnings" => {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                confluence::learning_blocks(&db.get_learnings(Some(project_id), None, false, None).map_err(|e| e.to_string())?)
+            }
+            other => return Err(format!("Unknown Confluence document '{}'", other)),
+        };
+        let title = confluence::page_title(&project_name, document);
+        let content = confluence::page_content(&blocks);
+        let entity_type = format!("{}_page", document);
+        let known_page_id = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.get_integration_link("confluence", &entity_type, project_id)
+                .map_err(|e| e.to_string())?
+                .map(|link| link.external_id)
+        };
+        
+        let result = client.publish_page(&space_key, parent_id.as_deref(), known_page_id.as_deref(), &title, &content.body);
+        let (page_id, created) = match result {
+            Ok(page) => page,
+            // Offline is the whole publish failing, so it can be queued and retried
+            Err(e) if network::looks_offline(&e) => return Err(e),
+            Err(e) => {
+                failed.push(serde_json::json!({ "document": document, "error": e }));
+                continue;
+            }
+        };
+        {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.upsert_integration_link("confluence", &entity_type, project_id, &page_id, Some(&space_key))
+                .map_err(|e| e.to_string())?;
+        }
+        
+        let mut image_errors = Vec::new();
+        for image in &content.images {
+            if let Err(e) = client.upload_attachment(&page_id, image) {
+                image_errors.push(format!("{}: {}", image.filename, e));
+            }
+        }
+        published.push(serde_json::json!({
+            "document": document,
+            "title": title,
+            "page_id": page_id,
+            "url": format!("{}/pages/viewpage.action?pageId={}", base_url.trim_end_matches('/'), page_id),
+            "created": created,
+            "images": content.images.len() - image_errors.len(),
+            "image_errors": image_errors,
+        }));
     }
     
     Ok(serde_json::json!({
-        "status": "cloned",
-        "path": path,
-        "remote_url": remote_url,
+        "project": project_name,
+        "space": space_key,
+        "published": published,
+        "failed": failed,
     }))
 }
 
 #[tauri::command]
-fn git_history(data_path: Option<String>, limit: Option<i32>) -> Result<Vec<serde_json::Value>, String> {
-    let path = data_path.unwrap_or_else(get_flowstate_data_path);
-    let limit = limit.unwrap_or(20);
-    
-    let output = Command::new("git")
-        .args(["log", &format!("-{}", limit), "--format=%H|%s|%ai|%an"])
-        .current_dir(&path)
-        .output()
-        .map_err(|e| format!("Failed to get git history: {}", e))?;
-    
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-    
-    let history: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(|line| {
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() >= 4 {
-                serde_json::json!({
-                    "hash": parts[0],
-                    "message": parts[1],
-                    "date": parts[2],
-                    "author": parts[3],
-                })
-            } else {
-                serde_json::json!({
-                    "raw": line,
-                })
-            }
-        })
-        .collect();
-    
-    Ok(history)
+fn get_confluence_links(state: State<AppState>) -> Result<Vec<database::IntegrationLink>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_integration_links("confluence", None).map_err(|e| e.to_string())
 }
 
 // ============================================================
-// v1.1: SETTINGS COMMANDS
+// v1.4: AUTOMATION COMMANDS
 // ============================================================
 
+/// The token flowstate:// links must carry; created the first time it's asked for.
 #[tauri::command]
-fn get_settings(state: State<AppState>) -> Result<Vec<database::Setting>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_settings().map_err(|e| e.to_string())
+fn get_automation_token() -> Result<String, String> {
+    match secrets::get("automation_token") {
+        Some(token) => Ok(token),
+        None => store_automation_token(),
+    }
 }
 
+/// Replace the token; shortcuts built with the old one stop working.
 #[tauri::command]
-fn get_setting(state: State<AppState>, key: String) -> Result<Option<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_setting(&key).map_err(|e| e.to_string())
+fn regenerate_automation_token() -> Result<String, String> {
+    store_automation_token()
 }
 
-#[tauri::command]
-fn set_setting(state: State<AppState>, key: String, value: String, category: Option<String>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.set_setting(&key, &value, category.as_deref()).map_err(|e| e.to_string())
+fn store_automation_token() -> Result<String, String> {
+    let token = automation::generate_token();
+    secrets::set("automation_token", Some(&token))?;
+    Ok(token)
 }
 
-#[tauri::command]
-fn delete_setting(state: State<AppState>, key: String) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_setting(&key).map_err(|e| e.to_string())
+/// Run one flowstate:// link and report the outcome to the frontend and, through
+/// x-success / x-error, to the app that opened it.
+fn handle_automation_link(app: &tauri::AppHandle, url: &str) {
+    let invocation = match automation::parse(url) {
+        Ok(invocation) => invocation,
+        Err(e) => {
+            let _ = app.emit("automation-failed", serde_json::json!({ "error": e }));
+            return;
+        }
+    };
+    let authorized = {
+        let state = app.state::<AppState>();
+        let token = state.db.lock().ok().and_then(|db| setting_string(&db, "automation_token"));
+        match token {
+            Some(token) => automation::check_token(invocation.param("token"), &token),
+            None => Err("Automation links are off until a token is created in Settings".to_string()),
+        }
+    };
+    if let Err(e) = authorized {
+        // No callback either: an unauthorized link mustn't get FlowState to open URLs for it
+        let _ = app.emit("automation-failed", serde_json::json!({ "command": invocation.command, "error": e }));
+        return;
+    }
+    
+    let result = run_automation(app, &invocation);
+    let callback = match &result {
+        Ok((_, message)) => invocation.param("x-success").map(|base| automation::callback_url(base, "result", message)),
+        Err(e) => invocation.param("x-error").map(|base| automation::callback_url(base, "errorMessage", e)),
+    };
+    if let Some(callback) = callback {
+        let _ = app.opener().open_url(callback, None::<&str>);
+    }
+    let _ = match result {
+        Ok((value, message)) => app.emit("automation-completed", serde_json::json!({
+            "command": invocation.command,
+            "result": value,
+            "message": message,
+        })),
+        Err(e) => app.emit("automation-failed", serde_json::json!({ "command": invocation.command, "error": e })),
+    };
 }
 
-#[tauri::command]
-fn get_settings_by_category(state: State<AppState>, category: String) -> Result<Vec<database::Setting>, String> {
+/// Returns the command's result and a one-line message for the caller.
+fn run_automation(app: &tauri::AppHandle, invocation: &automation::Invocation) -> Result<(serde_json::Value, String), String> {
+    match invocation.command.as_str() {
+        "capture" => {
+            let project_id = automation_project(app, invocation.param("project"))?;
+            let result = quick_add(app.state(), project_id, invocation.require("text")?.to_string())?;
+            let message = format!(
+                "Added {}: {}",
+                result["interpretation"]["kind"].as_str().unwrap_or("todo"),
+                result["interpretation"]["title"].as_str().unwrap_or_default(),
+            );
+            Ok((result, message))
+        }
+        "add_todo" => {
+            let project_id = automation_project(app, invocation.param("project"))?;
+            let todo = add_todo(
+                app.state(),
+                project_id,
+                invocation.require("title")?.to_string(),
+                invocation.param("description").map(String::from),
+                invocation.param("priority").map(String::from),
+                None,
+                invocation.param("due").map(String::from),
+            )?;
+            let message = format!("Added todo: {}", todo.title);
+            Ok((serde_json::json!(todo), message))
+        }
+        "sync" => {
+            // Links can't show a preview, so they only sync changes with nothing flagged
+            let preview = sync_preview(&app.state::<AppState>(), &get_flowstate_data_path())?;
+            let flagged = preview["findings"].as_array().map_or(0, |f| f.len());
+            if flagged > 0 {
+                return Err(format!("The sync would push {} possible secret(s); review it in FlowState first", flagged));
+            }
+            // The automation thread can wait for the sync, so run it here rather than in the background
+            let control = begin_sync(app)?;
+            let result = run_sync(app, &get_flowstate_data_path(), invocation.param("message").map(String::from), &control)?;
+            let message = if result["status"] == "queued" { "Committed; the push is queued until the network is back" } else { "Synced" };
+            Ok((result, message.to_string()))
+        }
+        "agenda" => {
+            let project_id = match invocation.param("project") {
+                Some(project) => Some(automation_project(app, Some(project))?),
+                None => None,
+            };
+            let (today, items) = {
+                let state = app.state::<AppState>();
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                let zone = db.timezone();
+                let date = zone.today();
+                let today = date.format("%Y-%m-%d").to_string();
+                let (_, day_end) = zone.day_bounds(date);
+                let mut items = db.get_agenda(project_id, &today, &timezone::rfc3339(day_end)).map_err(|e| e.to_string())?;
+                // Timed due dates are stored in UTC; show them on the user's clock
+                for item in &mut items {
+                    item.due_date = item.due_date.take().map(|due| timezone::display(&due, zone));
+                }
+                (today, items)
+            };
+            let text = automation::agenda_text(&today, &items);
+            // Shortcuts reads the clipboard more easily than a callback
+            if invocation.param("copy").is_some_and(|copy| copy != "0" && copy != "false") {
+                app.clipboard().write_text(text.clone()).map_err(|e| e.to_string())?;
+            }
+            Ok((serde_json::json!({ "date": today, "items": items, "text": text }), text))
+        }
+        other => Err(format!("Unknown automation command '{}'", other)),
+    }
+}
+
+/// The project a link names by id, uuid or name; without one, the most recently opened.
+fn automation_project(app: &tauri::AppHandle, project: Option<&str>) -> Result<i64, String> {
+    let state = app.state::<AppState>();
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_settings_by_category(&category).map_err(|e| e.to_string())
+    let Some(project) = project else {
+        let recent = db.get_recent_projects(1).map_err(|e| e.to_string())?.into_iter().next();
+        let project = match recent {
+            Some(project) => Some(project),
+            None => db.list_projects(Some("active"), None).map_err(|e| e.to_string())?.into_iter().next(),
+        };
+        return project.map(|p| p.id).ok_or_else(|| "No project to add to; pass project=<name or id>".to_string());
+    };
+    if let Ok(id) = RecordId::Uuid(project.to_string()).resolve(&db, "projects") {
+        return Ok(id);
+    }
+    db.list_projects(None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(project))
+        .map(|p| p.id)
+        .ok_or_else(|| format!("No project named '{}'", project))
 }
 
 // ============================================================
-// v1.1: SYNC STATUS COMMANDS (Database-tracked sync state)
+// v1.4: LAUNCHER COMMANDS
 // ============================================================
 
+/// Search problems and todos for a launcher. Items carry `title`, `subtitle` and `arg` as
+/// Alfred's script filters expect, plus the actions `quick_actions` accepts for them.
 #[tauri::command]
-fn get_sync_status(state: State<AppState>) -> Result<Option<database::SyncStatus>, String> {
+fn query_quick(state: State<AppState>, query: String, limit: Option<i64>) -> Result<serde_json::Value, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_sync_status().map_err(|e| e.to_string())
+    let items = db.quick_search(&query, limit.unwrap_or(20).clamp(1, 100)).map_err(|e| e.to_string())?;
+    let zone = db.timezone();
+    Ok(serde_json::json!({ "items": items.iter().map(|item| launcher_item(item, zone)).collect::<Vec<_>>() }))
+}
+
+fn launcher_item(item: &database::QuickItem, zone: timezone::Zone) -> serde_json::Value {
+    let action = |title: &str, action: &str| serde_json::json!({
+        "title": title,
+        "action": action,
+        "payload": { "action": action, "id": item.id },
+    });
+    let actions = match (item.entity_type.as_str(), item.status.as_str()) {
+        ("todo", "done" | "cancelled") => vec![],
+        ("todo", "in_progress") => vec![action("Mark done", "complete_todo")],
+        ("todo", _) => vec![action("Mark done", "complete_todo"), action("Start", "start_todo")],
+        ("problem", "solved" | "wont_fix") => vec![],
+        ("problem", "investigating") => vec![action("Mark solved", "solve_problem")],
+        _ => vec![action("Mark solved", "solve_problem"), action("Investigate", "investigate_problem")],
+    };
+    let mut subtitle = vec![item.project_name.clone()];
+    subtitle.extend(item.component_name.clone());
+    subtitle.push(item.status.replace('_', " "));
+    subtitle.push(item.priority.clone());
+    // Timed due dates are stored in UTC; the day shown is the user's
+    subtitle.extend(item.due_date.as_ref().map(|due| {
+        let due = timezone::display(due, zone);
+        format!("due {}", due.get(..10).unwrap_or(&due))
+    }));
+    serde_json::json!({
+        "uid": format!("{}:{}", item.entity_type, item.id),
+        "type": item.entity_type,
+        "id": item.id,
+        "project_id": item.project_id,
+        "title": item.title,
+        "subtitle": subtitle.join(" · "),
+        // The default action's payload, ready to hand back to quick_actions
+        "arg": actions.first().map(|a| a["payload"].to_string()).unwrap_or_default(),
+        "valid": !actions.is_empty(),
+        "actions": actions,
+    })
 }
 
+/// Run a launcher action: {"action": "complete_todo" | "start_todo" | "solve_problem" |
+/// "investigate_problem", "id": …}. solve_problem takes an optional "summary".
 #[tauri::command]
-fn init_sync_status(state: State<AppState>, device_name: String) -> Result<database::SyncStatus, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let device_id = uuid::Uuid::new_v4().to_string();
-    db.create_sync_status(&device_name, &device_id).map_err(|e| e.to_string())
+fn quick_actions(app: tauri::AppHandle, state: State<AppState>, payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let action = payload["action"].as_str().ok_or("Quick action needs an 'action'")?.to_string();
+    let id: RecordId = serde_json::from_value(payload["id"].clone())
+        .map_err(|_| "Quick action needs the record 'id'".to_string())?;
+    
+    let (record, message) = match action.as_str() {
+        "complete_todo" | "start_todo" => {
+            let status = if action == "complete_todo" { "done" } else { "in_progress" };
+            let todo = update_todo(app, state, id, None, None, Some(status.to_string()), None, None)?;
+            let message = format!("{}: {}", if status == "done" { "Done" } else { "Started" }, todo.title);
+            (serde_json::json!(todo), message)
+        }
+        "investigate_problem" => {
+            let problem = update_problem(app, state, id, None, None, Some("investigating".to_string()), None, None)?;
+            let message = format!("Investigating: {}", problem.title);
+            (serde_json::json!(problem), message)
+        }
+        "solve_problem" => {
+            let (problem_id, title) = {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                let problem_id = id.resolve(&db, "problems")?;
+                (problem_id, db.get_problem(problem_id).map_err(|e| e.to_string())?.title)
+            };
+            let summary = payload["summary"].as_str()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or("Marked solved from a launcher")
+                .to_string();
+            let solution = mark_problem_solved(app, state, problem_id, None, summary, None, None)?;
+            (serde_json::json!(solution), format!("Solved: {}", title))
+        }
+        other => return Err(format!("Unknown quick action '{}'", other)),
+    };
+    Ok(serde_json::json!({ "action": action, "message": message, "record": record }))
 }
 
+// ============================================================
+// v1.4: LOCAL API COMMANDS
+// ============================================================
+
+/// Turn the local HTTP API on or off. It starts listening right away; turning it off takes
+/// effect immediately too (requests are refused), and the port is released on restart.
 #[tauri::command]
-fn update_sync_status(
-    state: State<AppState>,
-    remote_url: Option<String>,
-    last_sync_at: Option<String>,
-    last_sync_commit: Option<String>,
-    pending_changes: Option<i64>,
-    has_conflicts: Option<bool>
-) -> Result<database::SyncStatus, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_sync_status(
-        remote_url.as_deref(),
-        last_sync_at.as_deref(),
-        last_sync_commit.as_deref(),
-        pending_changes,
-        has_conflicts,
-    ).map_err(|e| e.to_string())
+fn set_local_api_enabled(app: tauri::AppHandle, state: State<AppState>, enabled: bool) -> Result<serde_json::Value, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_setting("local_api_enabled", &enabled.to_string(), Some("general")).map_err(|e| e.to_string())?;
+    }
+    if enabled {
+        start_local_api(&app)?;
+    }
+    get_local_api_status(state)
 }
 
 #[tauri::command]
-fn get_sync_history(state: State<AppState>, limit: Option<i32>) -> Result<Vec<database::SyncHistory>, String> {
+fn get_local_api_status(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let port = *state.local_api_port.lock().map_err(|e| e.to_string())?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let limit = limit.unwrap_or(20);
-    db.get_sync_history(limit).map_err(|e| e.to_string())
+    Ok(serde_json::json!({
+        "enabled": local_api_enabled(&db),
+        "listening": port.is_some(),
+        "url": port.map(|port| format!("http://127.0.0.1:{}", port)),
+        "has_token": setting_string(&db, "automation_token").is_some(),
+    }))
 }
 
+/// v1.4: Create a local API token. `permission` is "read" (the default) or "write"; with a
+/// `project_id` the token only reaches that project's records. The token is returned this
+/// once; only its hash is kept.
 #[tauri::command]
-fn log_sync_operation(
+fn create_api_token(
     state: State<AppState>,
-    device_id: String,
-    operation: String,
-    commit_hash: Option<String>,
-    files_changed: Option<i64>,
-    status: String,
-    error_message: Option<String>
-) -> Result<database::SyncHistory, String> {
+    name: String,
+    permission: Option<String>,
+    project_id: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("An API token needs a name".to_string());
+    }
+    let permission = permission.unwrap_or_else(|| "read".to_string());
+    if !database::API_TOKEN_PERMISSIONS.contains(&permission.as_str()) {
+        return Err(format!("Unknown permission '{}'. Expected one of: {}", permission, database::API_TOKEN_PERMISSIONS.join(", ")));
+    }
+    let token = automation::generate_token();
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.log_sync_operation(
-        &device_id,
-        &operation,
-        commit_hash.as_deref(),
-        files_changed,
-        &status,
-        error_message.as_deref(),
-    ).map_err(|e| e.to_string())
+    if let Some(project_id) = project_id {
+        db.get_project(project_id).map_err(|e| e.to_string())?;
+    }
+    let record = db.create_api_token(name, &automation::hash_token(&token), &token[..8], &permission, project_id)
+        .map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({ "token": token, "api_token": record }))
 }
 
-// ============================================================
-// v1.2: PROJECT VARIABLES COMMANDS
-// ============================================================
-
 #[tauri::command]
-fn create_project_variable(
-    state: State<AppState>,
-    project_id: i64,
-    category: String,
-    name: String,
-    value: Option<String>,
-    is_secret: Option<bool>,
-    description: Option<String>,
-) -> Result<database::ProjectVariable, String> {
+fn list_api_tokens(state: State<AppState>, include_revoked: Option<bool>) -> Result<Vec<database::ApiToken>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_project_variable(
-        project_id,
-        &category,
-        &name,
-        value.as_deref(),
-        is_secret.unwrap_or(false),
-        description.as_deref(),
-    ).map_err(|e| e.to_string())
+    db.list_api_tokens(include_revoked.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
+/// Revoked tokens stop working at once and stay listed (with include_revoked) for reference.
 #[tauri::command]
-fn get_project_variables(
-    state: State<AppState>,
-    project_id: i64,
-    category: Option<String>,
-) -> Result<Vec<database::ProjectVariable>, String> {
+fn revoke_api_token(state: State<AppState>, id: RecordId) -> Result<database::ApiToken, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_project_variables(project_id, category.as_deref()).map_err(|e| e.to_string())
+    let id = id.resolve(&db, "api_tokens")?;
+    db.revoke_api_token(id).map_err(|e| e.to_string())
 }
 
+/// v1.4: Local API traffic over the last `hours` (default 24), optionally one token's only.
 #[tauri::command]
-fn update_project_variable(
+fn get_api_usage_stats(
     state: State<AppState>,
-    id: i64,
-    category: Option<String>,
-    name: Option<String>,
-    value: Option<String>,
-    is_secret: Option<bool>,
-    description: Option<String>,
-) -> Result<database::ProjectVariable, String> {
+    hours: Option<i64>,
+    token_id: Option<i64>
+) -> Result<database::ApiUsageStats, String> {
+    let hours = hours.unwrap_or(24).max(1);
+    let since = (chrono::Utc::now() - chrono::Duration::hours(hours)).format("%Y-%m-%d %H:%M:%S").to_string();
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_project_variable(
-        id,
-        category.as_deref(),
-        name.as_deref(),
-        value.as_deref(),
-        is_secret,
-        description.as_deref(),
-    ).map_err(|e| e.to_string())
+    db.get_api_usage_stats(&since, token_id).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn delete_project_variable(state: State<AppState>, id: i64) -> Result<(), String> {
+fn local_api_enabled(db: &Database) -> bool {
+    setting_string(db, "local_api_enabled").is_some_and(|v| v == "true")
+}
+
+/// Start listening unless already running. The port comes from the local_api_port setting.
+fn start_local_api(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut running = state.local_api_port.lock().map_err(|e| e.to_string())?;
+    if running.is_some() {
+        return Ok(());
+    }
+    let port = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        setting_string(&db, "local_api_port")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(localapi::DEFAULT_PORT)
+    };
+    let handle = app.clone();
+    localapi::serve(port, move |request| handle_local_api_request(&handle, request))?;
+    *running = Some(port);
+    Ok(())
+}
+
+/// v1.4: Local API routes, and whether each changes data (which takes a write token).
+const LOCAL_API_ROUTES: &[(&str, bool)] = &[("/quick/query", false), ("/quick/actions", true)];
+
+/// v1.4: What a local API caller may do. The automation token may do anything; an API token
+/// what it was created with.
+struct ApiAccess {
+    write: bool,
+    project_id: Option<i64>,
+    caller: &'static str, // automation or api_token, as logged
+    token_id: Option<i64>,
+}
+
+fn authorize_local_api(db: &Database, bearer: Option<&str>) -> Result<ApiAccess, localapi::Response> {
+    let bearer = bearer.filter(|t| !t.is_empty()).ok_or_else(|| localapi::Response::error(401, "Missing bearer token"))?;
+    if let Some(token) = setting_string(db, "automation_token") {
+        if automation::check_token(Some(bearer), &token).is_ok() {
+            return Ok(ApiAccess { write: true, project_id: None, caller: "automation", token_id: None });
+        }
+    }
+    match db.use_api_token(&automation::hash_token(bearer)) {
+        Ok(Some(token)) => Ok(ApiAccess {
+            write: token.permission == "write",
+            project_id: token.project_id,
+            caller: "api_token",
+            token_id: Some(token.id),
+        }),
+        Ok(None) => Err(localapi::Response::error(401, "Wrong or revoked bearer token")),
+        Err(e) => Err(localapi::Response::error(503, &e.to_string())),
+    }
+}
+
+/// Refuse a quick action on a record outside the token's project.
+fn check_action_project(state: &AppState, body: &serde_json::Value, project_id: i64) -> Result<(), String> {
+    let entity_type = match body["action"].as_str() {
+        Some("complete_todo" | "start_todo") => "todo",
+        _ => "problem",
+    };
+    let id: RecordId = serde_json::from_value(body["id"].clone())
+        .map_err(|_| "Quick action needs the record 'id'".to_string())?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_project_variable(id).map_err(|e| e.to_string())
+    let id = id.resolve(&db, if entity_type == "todo" { "todos" } else { "problems" })?;
+    match db.record_project_id(entity_type, id) {
+        Ok(owner) if owner == project_id => Ok(()),
+        _ => Err(format!("This token can't reach {} {}", entity_type, id)),
+    }
+}
+
+/// Routes of the local API. Everything except /health and the (signed) Sentry webhooks needs a
+/// bearer token: the automation token, or a v1.4 API token whose permission and project allow
+/// the request. v1.4: Each bearer token presented gets local_api_rate_limit requests a minute,
+/// checked before the database is touched, and failed authentications share one more budget.
+/// Requests over either are refused without being logged; every other request but /health and
+/// webhooks is.
+fn handle_local_api_request(app: &tauri::AppHandle, request: &localapi::Request) -> localapi::Response {
+    use sha2::{Digest, Sha256};
+    
+    if request.path == "/health" {
+        return localapi::Response::ok(serde_json::json!({ "app": "FlowState", "version": env!("CARGO_PKG_VERSION") }));
+    }
+    if request.path == sentry::WEBHOOK_PATH {
+        return handle_sentry_webhook(app, request);
+    }
+    let started = std::time::Instant::now();
+    let state = app.state::<AppState>();
+    let budget = match request.bearer_token().filter(|t| !t.is_empty()) {
+        Some(token) => format!("bearer:{:x}", Sha256::digest(token.as_bytes())),
+        None => "unauthenticated".to_string(),
+    };
+    if let Err(retry_after) = state.api_limiter.allow(&budget) {
+        return localapi::Response::too_many_requests(retry_after);
+    }
+    let access = match state.db.lock() {
+        Ok(db) if !local_api_enabled(&db) => return localapi::Response::error(503, "The local API is turned off"),
+        Ok(db) => authorize_local_api(&db, request.bearer_token()),
+        Err(e) => return localapi::Response::error(503, &e.to_string()),
+    };
+    let (caller, token_id) = access.as_ref().map(|a| (a.caller, a.token_id)).unwrap_or(("none", None));
+    let response = match access {
+        Ok(access) => route_local_api_request(app, request, &access),
+        // Someone trying tokens gets a new budget with each one, so failures share another
+        Err(_) if budget != "unauthenticated" && state.api_limiter.allow("unauthenticated").is_err() => {
+            return localapi::Response::error(401, "Wrong or revoked bearer token");
+        }
+        Err(response) => response,
+    };
+
+    let latency_ms = started.elapsed().as_millis() as i64;
+    if let Ok(db) = state.db.lock() {
+        if let Err(e) = db.log_api_request(caller, token_id, &request.method, &request.path, response.status, latency_ms) {
+            eprintln!("Failed to log local API request: {}", e);
+        }
+    }
+    response
+}
+
+fn route_local_api_request(app: &tauri::AppHandle, request: &localapi::Request, access: &ApiAccess) -> localapi::Response {
+    let state = app.state::<AppState>();
+    let Some(&(_, writes)) = LOCAL_API_ROUTES.iter().find(|(path, _)| *path == request.path) else {
+        return localapi::Response::error(404, "No such endpoint");
+    };
+    if request.method != "POST" {
+        return localapi::Response::error(405, "Use POST");
+    }
+    if writes && !access.write {
+        return localapi::Response::error(403, "This token is read-only");
+    }
+    
+    let body = match request.json() {
+        Ok(body) => body,
+        Err(e) => return localapi::Response::error(400, &e),
+    };
+    if let (Some(project_id), true) = (access.project_id, request.path == "/quick/actions") {
+        if let Err(e) = check_action_project(&state, &body, project_id) {
+            return localapi::Response::error(403, &e);
+        }
+    }
+    let result = match request.path.as_str() {
+        "/quick/query" => query_quick(
+            app.state(),
+            body["query"].as_str().unwrap_or_default().to_string(),
+            body["limit"].as_i64(),
+        ).map(|mut found| {
+            // Project-scoped tokens only see their project's records
+            if let (Some(project_id), Some(items)) = (access.project_id, found["items"].as_array_mut()) {
+                items.retain(|item| item["project_id"].as_i64() == Some(project_id));
+            }
+            found
+        }),
+        _ => quick_actions(app.clone(), app.state(), body),
+    };
+    match result {
+        Ok(value) => localapi::Response::ok(value),
+        Err(e) if e.starts_with("Conflict:") => localapi::Response::error(409, &e),
+        Err(e) => localapi::Response::error(422, &e),
+    }
 }
 
 // ============================================================
-// v1.2: PROJECT METHODS COMMANDS
+// v1.4: LAN SYNC COMMANDS
 // ============================================================
-
-#[tauri::command]
-fn create_project_method(
-    state: State<AppState>,
-    project_id: i64,
-    name: String,
-    description: String,
-    category: Option<String>,
-    steps: Option<String>,
-    code_example: Option<String>,
-    related_component_id: Option<i64>,
-) -> Result<database::ProjectMethod, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_project_method(
-        project_id,
-        &name,
-        &description,
-        category.as_deref(),
-        steps.as_deref(),
-        code_example.as_deref(),
-        related_component_id,
-    ).map_err(|e| e.to_string())
+
+/// Turn LAN sync on or off. Turning it on creates the shared key if there isn't one yet. The
+/// key is kept in this machine's credentials file and never syncs, so each other device is
+/// paired by entering it there with set_lan_sync_key; the on/off setting reaches them with
+/// the next git sync.
+#[tauri::command]
+fn set_lan_sync_enabled(app: tauri::AppHandle, state: State<AppState>, enabled: bool) -> Result<serde_json::Value, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if enabled && secrets::get("lan_sync_key").is_none() {
+            secrets::set("lan_sync_key", Some(&automation::generate_token()))?;
+        }
+        db.set_setting("lan_sync_enabled", &enabled.to_string(), Some("sync")).map_err(|e| e.to_string())?;
+    }
+    if enabled {
+        start_lan_sync(&app)?;
+    } else if let Some(lan) = state.lan_sync.lock().map_err(|e| e.to_string())?.take() {
+        lan.stop();
+    }
+    get_lan_sync_status(state)
 }
 
+/// The shared key, to enter on the device being paired. Only asked for when pairing, so
+/// it stays out of the status the app polls.
 #[tauri::command]
-fn get_project_methods(
-    state: State<AppState>,
-    project_id: i64,
-    category: Option<String>,
-) -> Result<Vec<database::ProjectMethod>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_project_methods(project_id, category.as_deref()).map_err(|e| e.to_string())
+fn get_lan_sync_key() -> Option<String> {
+    secrets::get("lan_sync_key")
 }
 
+/// Pair with another device by taking the key it shows. A running LAN sync restarts with it.
 #[tauri::command]
-fn update_project_method(
-    state: State<AppState>,
-    id: i64,
-    name: Option<String>,
-    description: Option<String>,
-    category: Option<String>,
-    steps: Option<String>,
-    code_example: Option<String>,
-    related_component_id: Option<i64>,
-) -> Result<database::ProjectMethod, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_project_method(
-        id,
-        name.as_deref(),
-        description.as_deref(),
-        category.as_deref(),
-        steps.as_deref(),
-        code_example.as_deref(),
-        related_component_id,
-    ).map_err(|e| e.to_string())
+fn set_lan_sync_key(app: tauri::AppHandle, state: State<AppState>, key: String) -> Result<serde_json::Value, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("The LAN sync key can't be empty".to_string());
+    }
+    secrets::set("lan_sync_key", Some(key))?;
+    let running = state.lan_sync.lock().map_err(|e| e.to_string())?.take();
+    if let Some(lan) = running {
+        lan.stop();
+        start_lan_sync(&app)?;
+    }
+    get_lan_sync_status(state)
 }
 
+/// Whether LAN sync is running, and the devices it has found.
 #[tauri::command]
-fn delete_project_method(state: State<AppState>, id: i64) -> Result<(), String> {
+fn get_lan_sync_status(state: State<AppState>) -> Result<serde_json::Value, String> {
+    let lan = state.lan_sync.lock().map_err(|e| e.to_string())?.clone();
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_project_method(id).map_err(|e| e.to_string())
+    Ok(serde_json::json!({
+        "enabled": lan_sync_enabled(&db),
+        "running": lan.is_some(),
+        "port": lan.as_ref().map(|lan| lan.port),
+        "peers": lan.map(|lan| lan.peers()).unwrap_or_default(),
+        "has_key": secrets::get("lan_sync_key").is_some(),
+    }))
 }
 
-// ============================================================
-// v1.2: CONVERSATIONS COMMANDS (read-only)
-// ============================================================
+fn lan_sync_enabled(db: &Database) -> bool {
+    setting_string(db, "lan_sync_enabled").is_some_and(|v| v == "true")
+}
 
-#[tauri::command]
-fn get_conversations(
-    state: State<AppState>,
-    project_id: i64,
-    limit: Option<i32>,
-) -> Result<Vec<database::Conversation>, String> {
+/// Start listening, announcing and pushing unless already running. The port comes from the
+/// lan_sync_port setting.
+fn start_lan_sync(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut running = state.lan_sync.lock().map_err(|e| e.to_string())?;
+    if running.is_some() {
+        return Ok(());
+    }
+    let (key, port, name) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let key = setting_string(&db, "lan_sync_key").ok_or("Turn LAN sync on to create its key first")?;
+        let port = setting_string(&db, "lan_sync_port")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(lansync::DEFAULT_PORT);
+        (key, port, db.author().map_err(|e| e.to_string())?)
+    };
+    let handle = app.clone();
+    let lan = std::sync::Arc::new(lansync::LanSync::start(&device_id()?, name.as_deref(), port, &key, move |batch| {
+        receive_lan_batch(&handle, batch)
+    })?);
+    *running = Some(lan.clone());
+    
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        while !lan.is_stopped() {
+            std::thread::sleep(lansync::PUSH_INTERVAL);
+            for peer in lan.peers() {
+                push_to_lan_peer(&handle, &lan, &peer, &key);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Send a peer the records changed since the last push to it. The first push covers everything
+/// since the last git sync, which the peer should already have.
+fn push_to_lan_peer(app: &tauri::AppHandle, lan: &lansync::LanSync, peer: &lansync::Peer, key: &str) {
+    let state = app.state::<AppState>();
+    let since = peer.last_sent.clone().unwrap_or_else(|| {
+        last_commit_time(&get_flowstate_data_path())
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(&time).ok())
+            .map(|time| time.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "1970-01-01 00:00:00".to_string())
+    });
+    let prepared = state.db.lock().map_err(|e| e.to_string()).and_then(|db| {
+        let until = db.current_timestamp().map_err(|e| e.to_string())?;
+        let exports = db.export_changes_since(&since).map_err(|e| e.to_string())?;
+        Ok((until, exports, db.author().map_err(|e| e.to_string())?))
+    });
+    let (until, exports, name) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => return lan.update_peer(&peer.device, |peer| peer.last_error = Some(e)),
+    };
+    if exports.is_empty() {
+        return lan.update_peer(&peer.device, |peer| peer.last_sent = Some(until));
+    }
+    
+    let device = match device_id() {
+        Ok(device) => device,
+        Err(e) => return lan.update_peer(&peer.device, |peer| peer.last_error = Some(e)),
+    };
+    let batch = lansync::Batch { device, name, sent_at: chrono::Utc::now().to_rfc3339(), exports };
+    let records = batch.record_count() as i64;
+    let result = lansync::send(peer.address, key, &batch);
+    if let Ok(db) = state.db.lock() {
+        let (status, error) = match &result {
+            Ok(_) => ("success", None),
+            Err(e) => ("failed", Some(e.as_str())),
+        };
+        let _ = db.log_lan_sync(&peer.device, "push", records, status, error);
+    }
+    lan.update_peer(&peer.device, |peer| match result {
+        Ok(_) => {
+            peer.last_sent = Some(until);
+            peer.last_error = None;
+        }
+        Err(e) => peer.last_error = Some(e),
+    });
+}
+
+/// Merge the records a peer pushed, field by field. Returns how many were created or changed.
+fn receive_lan_batch(app: &tauri::AppHandle, batch: lansync::Batch) -> Result<usize, String> {
+    let state = app.state::<AppState>();
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_conversations(project_id, limit).map_err(|e| e.to_string())
+    let mut merged = 0;
+    let mut failure = None;
+    for export in &batch.exports {
+        match db.merge_project_export(export, "field", false) {
+            Ok(report) => merged += report.created.values().chain(report.updated.values()).sum::<i64>(),
+            Err(e) => {
+                failure = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    let status = if failure.is_some() { "failed" } else { "success" };
+    db.log_lan_sync(&batch.device, "pull", merged, status, failure.as_deref()).map_err(|e| e.to_string())?;
+    if merged > 0 {
+        let _ = app.emit("lan-sync-received", serde_json::json!({
+            "device": batch.device,
+            "name": batch.name,
+            "records": merged,
+        }));
+    }
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(merged as usize),
+    }
 }
 
 // ============================================================
-// v1.2: SESSIONS COMMANDS (read-only)
+// v1.1: UPDATE COMMANDS
 // ============================================================
 
 #[tauri::command]
-fn get_sessions_list(
-    state: State<AppState>,
-    project_id: i64,
-    limit: Option<i32>,
-) -> Result<Vec<database::Session>, String> {
+fn check_for_updates(state: State<AppState>, include_prereleases: Option<bool>) -> Result<updater::UpdateInfo, String> {
+    let info = updater::check_for_updates(include_prereleases.unwrap_or(false))?;
+    
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_sessions_list(project_id, limit).map_err(|e| e.to_string())
+    let checked_at = serde_json::json!(chrono::Utc::now().to_rfc3339()).to_string();
+    db.set_setting("last_update_check", &checked_at, Some("general")).map_err(|e| e.to_string())?;
+    
+    Ok(info)
 }
 
-// ============================================================
-// v1.2: CROSS REFERENCES COMMANDS (read-only)
-// ============================================================
+/// Download the installer from the latest release, or `download_url` when it is one of this
+/// repository's release assets, check its signature and hand it to the OS.
+#[cfg(desktop)] // Mobile apps update through their store
+#[tauri::command]
+fn install_update(download_url: Option<String>) -> Result<serde_json::Value, String> {
+    let download_url = match download_url {
+        Some(url) => url,
+        None => {
+            let info = updater::check_for_updates(false)?;
+            if !info.update_available {
+                return Ok(serde_json::json!({
+                    "status": "up_to_date",
+                    "version": info.current_version,
+                }));
+            }
+            info.download_url
+                .ok_or_else(|| format!("Release {} has no installer for this platform", info.latest_version))?
+        }
+    };
+    
+    let download_dir = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+    let installer_path = updater::download_update(&download_url, &download_dir)?;
+    
+    // Hand the installer to the OS; the user completes the install and restarts
+    let opened = tauri_plugin_opener::open_path(&installer_path, None::<&str>).is_ok();
+    
+    Ok(serde_json::json!({
+        "status": "downloaded",
+        "path": installer_path.to_string_lossy(),
+        "opened": opened,
+    }))
+}
 
 #[tauri::command]
-fn get_cross_references(
-    state: State<AppState>,
-    project_id: i64,
-) -> Result<Vec<database::CrossReference>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_cross_references(project_id).map_err(|e| e.to_string())
+fn get_release_notes(version: Option<String>) -> Result<updater::ReleaseNotes, String> {
+    updater::get_release_notes(version.as_deref())
 }
 
 // ============================================================
@@ -1438,15 +8506,212 @@ fn write_text_file(path: String, content: String) -> Result<(), String> {
     std::fs::write(&path, content).map_err(|e| e.to_string())
 }
 
+/// Build an .ics calendar of due todos and milestones (solved problems) for one
+/// project, or every project when `project_id` is None, optionally limited to one
+/// assignee. Writes it to
+/// `output_path` when given and returns the calendar text either way.
+#[tauri::command]
+fn export_ics(
+    state: State<AppState>,
+    project_id: Option<i64>,
+    assignee_id: Option<i64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    
+    let projects = match project_id {
+        Some(id) => vec![db.get_project(id).map_err(|e| e.to_string())?],
+        None => db.list_projects(None, None).map_err(|e| e.to_string())?,
+    };
+    
+    let mut feed = match project_id {
+        Some(_) => ics::CalendarFeed::new(&format!("FlowState – {}", projects[0].name)),
+        None => ics::CalendarFeed::new("FlowState"),
+    };
+    
+    for project in &projects {
+        // Prefix entries with the project name only when several projects share the feed
+        let label = if project_id.is_none() { Some(project.name.as_str()) } else { None };
+        
+        for todo in db.get_todos(project.id, None, None, None, None).map_err(|e| e.to_string())? {
+            if assignee_id.is_none() || todo.assignee_id == assignee_id {
+                feed.add_todo(&todo, label);
+            }
+        }
+        for problem in db.get_all_problems(Some(project.id), None, None, None).map_err(|e| e.to_string())? {
+            if assignee_id.is_none() || problem.assignee_id == assignee_id {
+                feed.add_milestone(&problem, label);
+            }
+        }
+    }
+    
+    let content = feed.finish();
+    if let Some(path) = output_path {
+        std::fs::write(&path, &content).map_err(|e| format!("Failed to write calendar: {}", e))?;
+    }
+    Ok(content)
+}
+
 // ============================================================
 // HELPER FUNCTIONS
 // ============================================================
 
 fn get_flowstate_data_path() -> String {
-    let data_dir = dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("flowstate");
-    data_dir.to_string_lossy().to_string()
+    platform::data_dir().to_string_lossy().to_string()
+}
+
+/// Read a setting as a plain string (JSON-encoded or raw), treating blanks as unset.
+fn setting_string(db: &Database, key: &str) -> Option<String> {
+    if secrets::is_secret(key) {
+        return secrets::get(key);
+    }
+    let value = db.get_setting_value(key).ok().flatten()?;
+    let text = match value {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => return None,
+        other => other.to_string(),
+    };
+    if text.trim().is_empty() { None } else { Some(text) }
+}
+
+/// v1.4: The language model set up in settings (llm_model, with llm_base_url and llm_api_key
+/// when the endpoint needs them). Its requests are logged in ai_usage against `command`.
+fn llm_client<'a>(state: &'a AppState, db: &Database, command: &'static str) -> Result<llm::LlmClient<'a>, String> {
+    let model = setting_string(db, "llm_model")
+        .ok_or("No language model configured (setting: llm_model)")?;
+    let client = llm::LlmClient::new(
+        setting_string(db, "llm_base_url").as_deref(),
+        setting_string(db, "llm_api_key").as_deref(),
+        &model,
+    );
+    Ok(client.metered(UsageMeter { state, command, prices: llm_prices(db) }))
+}
+
+/// v1.4: Prices per million tokens from llm_cost_per_million_input/output; unset is free.
+fn llm_prices(db: &Database) -> llm::Prices {
+    llm::Prices {
+        input: setting_number(db, "llm_cost_per_million_input").unwrap_or(0.0),
+        output: setting_number(db, "llm_cost_per_million_output").unwrap_or(0.0),
+    }
+}
+
+fn setting_number(db: &Database, key: &str) -> Option<f64> {
+    setting_string(db, key).and_then(|v| v.trim().parse::<f64>().ok())
+}
+
+/// v1.4: Logs each provider request in ai_usage, and refuses new ones once this month's
+/// spend reaches the ai_monthly_budget setting.
+struct UsageMeter<'a> {
+    state: &'a AppState,
+    command: &'static str,
+    prices: llm::Prices,
+}
+
+impl llm::Meter for UsageMeter<'_> {
+    fn check(&self) -> Result<(), String> {
+        let db = self.state.db.lock().map_err(|e| e.to_string())?;
+        let (budget, spent) = ai_month_spend(&db)?;
+        match budget {
+            Some(budget) if spent >= budget => Err(format!(
+                "This month's AI budget of {:.2} is spent ({:.2} so far; setting: ai_monthly_budget)", budget, spent
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn record(&self, call: &llm::Call) {
+        // A request that went through is never failed for want of a log entry
+        if let Ok(db) = self.state.db.lock() {
+            let _ = db.record_ai_usage(call, self.prices.cost(&call.usage), self.command);
+        }
+    }
+}
+
+/// The monthly budget, if one is set, and what's been spent since the month began.
+fn ai_month_spend(db: &Database) -> Result<(Option<f64>, f64), String> {
+    let (month_start, _) = timezone::named_range("this_month", db.timezone(), sprint_start(db)?)?;
+    let spent = db.get_ai_cost_since(&timezone::stored(month_start)).map_err(|e| e.to_string())?;
+    Ok((setting_number(db, "ai_monthly_budget").filter(|b| *b > 0.0), spent))
+}
+
+/// Default shortcuts with the user's overrides from the `shortcuts` settings category.
+fn shortcut_map(db: &Database) -> Result<BTreeMap<String, String>, String> {
+    let overrides: Vec<(String, String)> = db.get_settings_by_category(shortcuts::SETTINGS_CATEGORY)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|setting| {
+            let action = setting.key.strip_prefix(shortcuts::KEY_PREFIX)?.to_string();
+            let value = serde_json::from_str::<String>(&setting.value).unwrap_or(setting.value);
+            Some((action, value))
+        })
+        .collect();
+    Ok(shortcuts::resolve(&overrides))
+}
+
+/// v1.4: Tell the UI a watched record changed. The targeted `watch:{entity}:{id}` event
+/// always fires; `watched-record-changed` is the notification, and honours focus mode.
+/// Takes the already-locked database, so it can run inside a command.
+fn notify_watchers(
+    app: &tauri::AppHandle,
+    db: &Database,
+    entity_type: &str,
+    entity_id: i64,
+    change: &str,
+    record: serde_json::Value,
+) {
+    let Ok(Some(watch)) = db.get_watch(entity_type, entity_id) else { return };
+    let payload = serde_json::json!({
+        "entity_type": entity_type,
+        "entity_id": entity_id,
+        "change": change,
+        "record": record,
+    });
+    let _ = app.emit(&format!("watch:{}:{}", entity_type, entity_id), &payload);
+    if watch.notify && !matches!(db.get_focus_state(), Ok(Some(_))) {
+        let _ = app.emit("watched-record-changed", &payload);
+    }
+}
+
+/// v1.4: One aggregated event for a batch of changes, plus one per watched component.
+fn announce_changes(app: &tauri::AppHandle, db: &Database, changes: &[database::Change]) {
+    let mut component_ids: Vec<i64> = changes.iter().map(|c| c.component_id).collect();
+    component_ids.sort_unstable();
+    component_ids.dedup();
+    let _ = app.emit("changes-logged", serde_json::json!({
+        "count": changes.len(),
+        "component_ids": component_ids,
+    }));
+    for component_id in component_ids {
+        let batch: Vec<&database::Change> = changes.iter().filter(|c| c.component_id == component_id).collect();
+        notify_watchers(app, db, "component", component_id, "change_logged", serde_json::json!(batch));
+    }
+}
+
+/// Drain the change queue into a single transaction. Returns how many changes were written.
+fn flush_queued_changes(app: &tauri::AppHandle) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let pending: Vec<database::NewChange> = {
+        let mut queue = state.change_queue.lock().map_err(|e| e.to_string())?;
+        std::mem::take(&mut *queue)
+    };
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    // Not requeued on failure: a bad component id would otherwise fail every later flush too
+    let logged = db.log_changes_batch(&pending).map_err(|e| e.to_string())?;
+    announce_changes(app, &db, &logged);
+    Ok(logged.len())
+}
+
+/// Notifications stay quiet while a focus session is running.
+fn notifications_suppressed(app: &tauri::AppHandle) -> bool {
+    let state = app.state::<AppState>();
+    let db = match state.db.lock() {
+        Ok(db) => db,
+        Err(_) => return false,
+    };
+    matches!(db.get_focus_state(), Ok(Some(_)))
 }
 
 fn calculate_file_hash(file_path: &str) -> Result<String, String> {
@@ -1464,11 +8729,7 @@ fn calculate_file_hash(file_path: &str) -> Result<String, String> {
 }
 
 fn copy_file_to_project_bundle(source_path: &str, project_id: i64) -> Result<String, String> {
-    let data_path = get_flowstate_data_path();
-    let bundle_path = Path::new(&data_path)
-        .join("projects")
-        .join(format!("project_{}", project_id))
-        .join("attachments");
+    let bundle_path = project_bundle_dir(project_id);
     
     // Create directory if needed
     std::fs::create_dir_all(&bundle_path)
@@ -1500,26 +8761,145 @@ fn copy_file_to_project_bundle(source_path: &str, project_id: i64) -> Result<Str
     Ok(dest_path.to_string_lossy().to_string())
 }
 
+fn project_bundle_dir(project_id: i64) -> PathBuf {
+    Path::new(&get_flowstate_data_path())
+        .join("projects")
+        .join(format!("project_{}", project_id))
+        .join("attachments")
+}
+
+/// v1.4: Ids of every project that has a bundle folder, deleted projects included.
+fn bundle_project_ids() -> Vec<i64> {
+    let root = Path::new(&get_flowstate_data_path()).join("projects");
+    let Ok(entries) = std::fs::read_dir(root) else { return Vec::new() };
+    let mut ids: Vec<i64> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.strip_prefix("project_")?.parse().ok())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// v1.4: Every file under a bundle folder with its size.
+fn bundle_files(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else { return files };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(meta) if meta.is_dir() => files.extend(bundle_files(&path)),
+            Ok(meta) => files.push((path, meta.len())),
+            Err(_) => {}
+        }
+    }
+    files
+}
+
+fn bundled_file_paths(db: &Database) -> Result<std::collections::HashSet<PathBuf>, String> {
+    Ok(db.get_bundled_file_paths().map_err(|e| e.to_string())?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// v1.4: Storage accounting behind get_storage_usage and the quota warning.
+fn storage_usage(db: &Database, project_id: Option<i64>) -> Result<serde_json::Value, String> {
+    let referenced = bundled_file_paths(db)?;
+    let mut projects = db.get_attachment_storage(project_id).map_err(|e| e.to_string())?;
+    for project in projects.iter_mut() {
+        for (path, size) in bundle_files(&project_bundle_dir(project.project_id)) {
+            project.bundled_bytes += size as i64;
+            if !referenced.contains(&path) {
+                project.orphaned_files += 1;
+                project.orphaned_bytes += size as i64;
+            }
+        }
+    }
+    
+    // Folders of projects that were deleted without cleaning up their files
+    let deleted_project_bytes: i64 = if project_id.is_none() {
+        bundle_project_ids()
+            .into_iter()
+            .filter(|id| !projects.iter().any(|p| p.project_id == *id))
+            .flat_map(|id| bundle_files(&project_bundle_dir(id)))
+            .map(|(_, size)| size as i64)
+            .sum()
+    } else {
+        0
+    };
+    
+    // The quota is global, so it's measured against every bundle even for a single-project view
+    let total_bytes: i64 = if project_id.is_none() {
+        projects.iter().map(|p| p.bundled_bytes).sum::<i64>() + deleted_project_bytes
+    } else {
+        bundle_project_ids()
+            .into_iter()
+            .flat_map(|id| bundle_files(&project_bundle_dir(id)))
+            .map(|(_, size)| size as i64)
+            .sum()
+    };
+    
+    // 0 (or a blank setting) turns the quota off
+    let quota_bytes = db.get_setting_value("storage_quota_mb").ok().flatten()
+        .and_then(|v| v.as_f64().or_else(|| v.as_str()?.trim().parse().ok()))
+        .filter(|mb| *mb > 0.0)
+        .map(|mb| (mb * 1024.0 * 1024.0) as i64);
+    
+    Ok(serde_json::json!({
+        "projects": projects,
+        "deleted_project_bytes": deleted_project_bytes,
+        "total_bytes": total_bytes,
+        "quota_bytes": quota_bytes,
+        "quota_used_percent": quota_bytes.map(|q| total_bytes as f64 * 100.0 / q as f64),
+        "over_quota": quota_bytes.is_some_and(|q| total_bytes > q),
+    }))
+}
+
 // ============================================================
 // NATIVE MENU SETUP (v1.1 Updated)
 // ============================================================
 
-fn create_menu(app: &tauri::App) -> Result<Menu<tauri::Wry>, tauri::Error> {
+/// Everything in the native menu that comes from the database.
+#[cfg(desktop)]
+struct MenuData {
+    shortcuts: BTreeMap<String, String>,
+    recent_projects: Vec<database::Project>,
+    active_projects: Vec<database::Project>,
+}
+
+#[cfg(desktop)]
+fn create_menu<M: Manager<tauri::Wry>>(app: &M, data: &MenuData) -> Result<Menu<tauri::Wry>, tauri::Error> {
     use tauri::menu::AboutMetadataBuilder;
+    let shortcuts = &data.shortcuts;
+    
+    // v1.4: Open Recent submenu; items emit open_project:{id}
+    let mut open_recent = SubmenuBuilder::new(app, "Open Recent");
+    for project in &data.recent_projects {
+        open_recent = open_recent.item(
+            &MenuItemBuilder::with_id(format!("open_project:{}", project.id), &project.name).build(app)?
+        );
+    }
+    open_recent = if data.recent_projects.is_empty() {
+        open_recent.item(&MenuItemBuilder::with_id("no_recent_projects", "No Recent Projects").enabled(false).build(app)?)
+    } else {
+        open_recent.separator().item(&MenuItemBuilder::with_id("clear_recent_projects", "Clear Recent").build(app)?)
+    };
+    let open_recent = open_recent.build()?;
     
     // Build File menu (v1.1 updated)
     let file_menu = SubmenuBuilder::new(app, "File")
-        .item(&MenuItemBuilder::with_id("new_project", "New Project").accelerator("CmdOrCtrl+N").build(app)?)
-        .item(&MenuItemBuilder::with_id("open_project", "Open Project…").accelerator("CmdOrCtrl+O").build(app)?)
+        .item(&menu_item(app, shortcuts, "new_project", "New Project")?)
+        .item(&menu_item(app, shortcuts, "open_project", "Open Project…")?)
+        .item(&open_recent)
         .separator()
-        .item(&MenuItemBuilder::with_id("import_file", "Import File…").accelerator("CmdOrCtrl+I").build(app)?)
-        .item(&MenuItemBuilder::with_id("import_extract", "Import & Extract…").accelerator("CmdOrCtrl+Shift+I").build(app)?)
+        .item(&menu_item(app, shortcuts, "import_file", "Import File…")?)
+        .item(&menu_item(app, shortcuts, "import_extract", "Import & Extract…")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("export_project", "Export Project…").accelerator("CmdOrCtrl+E").build(app)?)
-        .item(&MenuItemBuilder::with_id("export_markdown", "Export as Markdown…").accelerator("CmdOrCtrl+Shift+E").build(app)?)
+        .item(&menu_item(app, shortcuts, "export_project", "Export Project…")?)
+        .item(&menu_item(app, shortcuts, "export_markdown", "Export as Markdown…")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("sync_now", "Sync Now").accelerator("CmdOrCtrl+S").build(app)?)
-        .item(&MenuItemBuilder::with_id("sync_settings", "Sync Settings…").build(app)?)
+        .item(&menu_item(app, shortcuts, "sync_now", "Sync Now")?)
+        .item(&menu_item(app, shortcuts, "sync_settings", "Sync Settings…")?)
         .separator()
         .item(&PredefinedMenuItem::close_window(app, Some("Close Window"))?)
         .build()?;
@@ -1534,81 +8914,90 @@ fn create_menu(app: &tauri::App) -> Result<Menu<tauri::Wry>, tauri::Error> {
         .item(&PredefinedMenuItem::paste(app, Some("Paste"))?)
         .item(&PredefinedMenuItem::select_all(app, Some("Select All"))?)
         .separator()
-        .item(&MenuItemBuilder::with_id("find", "Find…").accelerator("CmdOrCtrl+F").build(app)?)
-        .item(&MenuItemBuilder::with_id("find_in_files", "Find in Files…").accelerator("CmdOrCtrl+Shift+F").build(app)?)
+        .item(&menu_item(app, shortcuts, "find", "Find…")?)
+        .item(&menu_item(app, shortcuts, "find_in_files", "Find in Files…")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("quick_capture", "Quick Capture").accelerator("CmdOrCtrl+Shift+M").build(app)?)
+        .item(&menu_item(app, shortcuts, "quick_capture", "Quick Capture")?)
         .build()?;
     
     // Build View menu (v1.2 updated with new views)
     let view_menu = SubmenuBuilder::new(app, "View")
-        .item(&MenuItemBuilder::with_id("view_dashboard", "Dashboard").accelerator("CmdOrCtrl+1").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_timeline", "Timeline").accelerator("CmdOrCtrl+2").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_kanban", "Kanban Board").accelerator("CmdOrCtrl+3").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_decision", "Decision Trees").accelerator("CmdOrCtrl+4").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_files", "Files & Attachments").accelerator("CmdOrCtrl+5").build(app)?)
+        .item(&menu_item(app, shortcuts, "view_dashboard", "Dashboard")?)
+        .item(&menu_item(app, shortcuts, "view_timeline", "Timeline")?)
+        .item(&menu_item(app, shortcuts, "view_kanban", "Kanban Board")?)
+        .item(&menu_item(app, shortcuts, "view_decision", "Decision Trees")?)
+        .item(&menu_item(app, shortcuts, "view_files", "Files & Attachments")?)
         .separator()
         // v1.2: Additional views
-        .item(&MenuItemBuilder::with_id("view_tree", "Tree View").accelerator("CmdOrCtrl+6").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_story", "Story Mode").accelerator("CmdOrCtrl+7").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_architecture", "Architecture Diagram").accelerator("CmdOrCtrl+8").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_search", "Search").build(app)?)
+        .item(&menu_item(app, shortcuts, "view_tree", "Tree View")?)
+        .item(&menu_item(app, shortcuts, "view_story", "Story Mode")?)
+        .item(&menu_item(app, shortcuts, "view_architecture", "Architecture Diagram")?)
+        .item(&menu_item(app, shortcuts, "view_search", "Search")?)
         .separator()
         // v1.2: New data views
-        .item(&MenuItemBuilder::with_id("view_todos", "Todo Board").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_conversations", "Conversations").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_sessions", "Sessions").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_knowledge", "Knowledge").build(app)?)
-        .item(&MenuItemBuilder::with_id("view_data", "Data Browser").build(app)?)
+        .item(&menu_item(app, shortcuts, "view_todos", "Todo Board")?)
+        .item(&menu_item(app, shortcuts, "view_conversations", "Conversations")?)
+        .item(&menu_item(app, shortcuts, "view_sessions", "Sessions")?)
+        .item(&menu_item(app, shortcuts, "view_knowledge", "Knowledge")?)
+        .item(&menu_item(app, shortcuts, "view_data", "Data Browser")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("toggle_sidebar", "Toggle Sidebar").accelerator("CmdOrCtrl+\\").build(app)?)
-        .item(&MenuItemBuilder::with_id("toggle_ai_panel", "Toggle AI Panel").accelerator("CmdOrCtrl+Shift+A").build(app)?)
+        .item(&menu_item(app, shortcuts, "toggle_sidebar", "Toggle Sidebar")?)
+        .item(&menu_item(app, shortcuts, "toggle_ai_panel", "Toggle AI Panel")?)
         .separator()
         .item(&PredefinedMenuItem::fullscreen(app, Some("Enter Full Screen"))?)
         .build()?;
     
     // Build Tools menu (v1.1 new)
     let tools_menu = SubmenuBuilder::new(app, "Tools")
-        .item(&MenuItemBuilder::with_id("ai_describe_file", "AI Describe File…").build(app)?)
-        .item(&MenuItemBuilder::with_id("ai_extract_file", "AI Extract from File…").build(app)?)
-        .item(&MenuItemBuilder::with_id("ai_summarize", "AI Summarize Project…").build(app)?)
+        .item(&menu_item(app, shortcuts, "ai_describe_file", "AI Describe File…")?)
+        .item(&menu_item(app, shortcuts, "ai_extract_file", "AI Extract from File…")?)
+        .item(&menu_item(app, shortcuts, "ai_summarize", "AI Summarize Project…")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("reindex_files", "Reindex All Files").build(app)?)
-        .item(&MenuItemBuilder::with_id("verify_integrity", "Verify File Integrity").build(app)?)
+        .item(&menu_item(app, shortcuts, "reindex_files", "Reindex All Files")?)
+        .item(&menu_item(app, shortcuts, "verify_integrity", "Verify File Integrity")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("git_history", "Git History…").build(app)?)
-        .item(&MenuItemBuilder::with_id("resolve_conflicts", "Resolve Sync Conflicts…").build(app)?)
+        .item(&menu_item(app, shortcuts, "git_history", "Git History…")?)
+        .item(&menu_item(app, shortcuts, "resolve_conflicts", "Resolve Sync Conflicts…")?)
         .build()?;
     
-    // Build Window menu
-    let window_menu = SubmenuBuilder::new(app, "Window")
+    // Build Window menu (v1.4: lists active projects)
+    let mut window_menu = SubmenuBuilder::new(app, "Window")
         .item(&PredefinedMenuItem::minimize(app, Some("Minimize"))?)
         .item(&PredefinedMenuItem::maximize(app, Some("Zoom"))?)
         .separator()
-        .item(&MenuItemBuilder::with_id("show_all_projects", "Show All Projects").accelerator("CmdOrCtrl+0").build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::with_id("bring_to_front", "FlowState").build(app)?)
+        .item(&menu_item(app, shortcuts, "show_all_projects", "Show All Projects")?)
+        .separator();
+    for project in data.active_projects.iter().take(MENU_PROJECT_LIMIT) {
+        window_menu = window_menu.item(
+            &MenuItemBuilder::with_id(format!("open_project:{}", project.id), &project.name).build(app)?
+        );
+    }
+    if !data.active_projects.is_empty() {
+        window_menu = window_menu.separator();
+    }
+    let window_menu = window_menu
+        .item(&menu_item(app, shortcuts, "bring_to_front", "FlowState")?)
         .build()?;
     
     // Build Help menu (v1.1 updated)
     let help_menu = SubmenuBuilder::new(app, "Help")
-        .item(&MenuItemBuilder::with_id("help_guide", "FlowState Help").accelerator("CmdOrCtrl+?").build(app)?)
-        .item(&MenuItemBuilder::with_id("help_shortcuts", "Keyboard Shortcuts").build(app)?)
+        .item(&menu_item(app, shortcuts, "help_guide", "FlowState Help")?)
+        .item(&menu_item(app, shortcuts, "help_shortcuts", "Keyboard Shortcuts")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("help_getting_started", "Getting Started Guide").build(app)?)
-        .item(&MenuItemBuilder::with_id("help_working_files", "Working with Files").build(app)?)
-        .item(&MenuItemBuilder::with_id("help_sync", "Setting Up Sync").build(app)?)
-        .item(&MenuItemBuilder::with_id("help_ai", "AI Features Guide").build(app)?)
+        .item(&menu_item(app, shortcuts, "help_getting_started", "Getting Started Guide")?)
+        .item(&menu_item(app, shortcuts, "help_working_files", "Working with Files")?)
+        .item(&menu_item(app, shortcuts, "help_sync", "Setting Up Sync")?)
+        .item(&menu_item(app, shortcuts, "help_ai", "AI Features Guide")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("help_mcp", "MCP Setup Guide").build(app)?)
+        .item(&menu_item(app, shortcuts, "help_mcp", "MCP Setup Guide")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("check_updates", "Check for Updates…").build(app)?)
-        .item(&MenuItemBuilder::with_id("release_notes", "Release Notes").build(app)?)
+        .item(&menu_item(app, shortcuts, "check_updates", "Check for Updates…")?)
+        .item(&menu_item(app, shortcuts, "release_notes", "Release Notes")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("report_bug", "Report a Bug…").build(app)?)
-        .item(&MenuItemBuilder::with_id("send_feedback", "Send Feedback…").build(app)?)
+        .item(&menu_item(app, shortcuts, "report_bug", "Report a Bug…")?)
+        .item(&menu_item(app, shortcuts, "send_feedback", "Send Feedback…")?)
         .separator()
-        .item(&MenuItemBuilder::with_id("help_about", "About FlowState").build(app)?)
+        .item(&menu_item(app, shortcuts, "help_about", "About FlowState")?)
         .build()?;
     
     // Build the complete menu bar
@@ -1623,7 +9012,7 @@ fn create_menu(app: &tauri::App) -> Result<Menu<tauri::Wry>, tauri::Error> {
                     .build()
             ))?)
             .separator()
-            .item(&MenuItemBuilder::with_id("settings", "Settings…").accelerator("CmdOrCtrl+,").build(app)?)
+            .item(&menu_item(app, shortcuts, "settings", "Settings…")?)
             .separator()
             .item(&PredefinedMenuItem::services(app, Some("Services"))?)
             .separator()
@@ -1644,33 +9033,312 @@ fn create_menu(app: &tauri::App) -> Result<Menu<tauri::Wry>, tauri::Error> {
     Ok(menu)
 }
 
+/// A menu item carrying its configured accelerator, if any.
+#[cfg(desktop)]
+fn menu_item<M: Manager<tauri::Wry>>(
+    app: &M,
+    shortcuts: &BTreeMap<String, String>,
+    id: &str,
+    label: &str,
+) -> Result<MenuItem<tauri::Wry>, tauri::Error> {
+    let mut builder = MenuItemBuilder::with_id(id, label);
+    if let Some(accelerator) = shortcuts.get(id).filter(|a| !a.is_empty()) {
+        builder = builder.accelerator(accelerator);
+    }
+    builder.build(app)
+}
+
+/// v1.4: Rebuild the native menu from current settings and projects.
+#[cfg(desktop)]
+fn rebuild_menu(app: &tauri::AppHandle) -> Result<(), String> {
+    let data = {
+        let state = app.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        MenuData {
+            shortcuts: shortcut_map(&db)?,
+            recent_projects: db.get_recent_projects(MENU_RECENT_LIMIT).map_err(|e| e.to_string())?,
+            active_projects: db.list_projects(Some("active"), None).map_err(|e| e.to_string())?,
+        }
+    };
+    let menu = create_menu(app, &data).map_err(|e| e.to_string())?;
+    app.set_menu(menu).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Mobile apps have no menu bar.
+#[cfg(mobile)]
+fn rebuild_menu(_app: &tauri::AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
 // ============================================================
 // APP ENTRY POINT
 // ============================================================
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize database
-    let db_path = get_default_db_path();
-    println!("FlowState v1.1: Using database at {:?}", db_path);
+    let builder = tauri::Builder::default();
     
-    let db = Database::new(db_path).expect("Failed to initialize database");
+    // v1.4: Must come first. On Windows and Linux a flowstate:// link starts a second
+    // process; this hands the link to the running app instead.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+        if args.iter().any(|arg| arg.starts_with("flowstate://")) {
+            return; // Automation runs in the background
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.set_focus();
+        }
+    }));
     
-    tauri::Builder::default()
+    let builder = builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
-            // Set up the native menu
-            let menu = create_menu(app)?;
-            app.set_menu(menu)?;
+            // v1.4: Mobile apps can only write inside the sandbox directory Tauri gives them
+            #[cfg(mobile)]
+            platform::set_data_dir(app.path().app_data_dir()?);
+            
+            // Initialize database
+            let db_path = get_default_db_path();
+            println!("FlowState v1.1: Using database at {:?}", db_path);
+            let db = Database::new(db_path)?;
+            db.set_author(author_identity().as_deref())?;
+            secrets::migrate(&db)?;
+            db.attach_local(&platform::local_dir().join(LOCAL_DB_FILE))?;
+            let api_limiter = localapi::RateLimiter::default();
+            api_limiter.set_limit(setting_string(&db, "local_api_rate_limit").and_then(|l| l.parse().ok()));
+            app.manage(AppState {
+                db: Mutex::new(db),
+                change_queue: Mutex::new(Vec::new()),
+                results: Mutex::new(streaming::ResultStore::default()),
+                scanned_files: Mutex::new(HashMap::new()),
+                clipboard_capture: Mutex::new(None),
+                local_api_port: Mutex::new(None),
+                sync_job: Mutex::new(None),
+                lan_sync: Mutex::new(None),
+                api_limiter,
+            });
+            
+            // Set up the native menu (v1.4: shortcuts and project lists come from the database)
+            rebuild_menu(app.handle())?;
+            
+            // v1.4: flowstate:// automation links; commands like sync can take a while, so
+            // each runs off the event loop
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    let handle = handle.clone();
+                    std::thread::spawn(move || handle_automation_link(&handle, url.as_str()));
+                }
+            });
+            // macOS and iOS deliver the link that launched the app as an event; elsewhere it's only here
+            #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    let handle = app.handle().clone();
+                    std::thread::spawn(move || handle_automation_link(&handle, url.as_str()));
+                }
+            }
+            
+            // v1.4: Local API for launchers, when turned on
+            #[cfg(desktop)]
+            let enabled = app.state::<AppState>().db.lock().map(|db| local_api_enabled(&db)).unwrap_or(false);
+            #[cfg(desktop)]
+            if enabled {
+                if let Err(e) = start_local_api(app.handle()) {
+                    eprintln!("{}", e);
+                }
+            }
+            
+            // v1.4: LAN sync, when turned on
+            #[cfg(desktop)]
+            let enabled = app.state::<AppState>().db.lock().map(|db| lan_sync_enabled(&db)).unwrap_or(false);
+            #[cfg(desktop)]
+            if enabled {
+                if let Err(e) = start_lan_sync(app.handle()) {
+                    eprintln!("{}", e);
+                }
+            }
+            
+            // v1.1: Background update check (respects the auto_check_updates setting)
+            #[cfg(desktop)]
+            let handle = app.handle().clone();
+            #[cfg(desktop)]
+            std::thread::spawn(move || {
+                let enabled = {
+                    let state = handle.state::<AppState>();
+                    let db = match state.db.lock() {
+                        Ok(db) => db,
+                        Err(_) => return,
+                    };
+                    db.get_setting("auto_check_updates").ok().flatten().map(|v| v != "false").unwrap_or(true)
+                };
+                if !enabled {
+                    return;
+                }
+                if let Ok(info) = updater::check_for_updates(false) {
+                    if info.update_available && !notifications_suppressed(&handle) {
+                        let _ = handle.emit("update-available", &info);
+                    }
+                }
+            });
+            
+            // v1.4: Change coalescer; queued changes are written together
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(CHANGE_FLUSH_INTERVAL);
+                if let Err(e) = flush_queued_changes(&handle) {
+                    eprintln!("Failed to flush queued changes: {}", e);
+                }
+            });
+            
+            // v1.4: Directory watches are polled; files are only hashed when their mtime moves
+            #[cfg(desktop)]
+            let handle = app.handle().clone();
+            #[cfg(desktop)]
+            std::thread::spawn(move || loop {
+                std::thread::sleep(DIRECTORY_SCAN_INTERVAL);
+                let watches = {
+                    let state = handle.state::<AppState>();
+                    let db = match state.db.lock() {
+                        Ok(db) => db,
+                        Err(_) => return,
+                    };
+                    db.get_directory_watches(None).unwrap_or_default()
+                };
+                for watch in watches.iter().filter(|w| w.enabled) {
+                    if let Err(e) = sync_directory_watch(&handle, watch) {
+                        eprintln!("Failed to scan {}: {}", watch.path, e);
+                    }
+                }
+            });
+            
+            // v1.4: Clipboard capture; polls only do work while capture mode is on
+            #[cfg(desktop)]
+            let handle = app.handle().clone();
+            #[cfg(desktop)]
+            std::thread::spawn(move || loop {
+                std::thread::sleep(CLIPBOARD_POLL_INTERVAL);
+                if let Err(e) = capture_clipboard(&handle) {
+                    eprintln!("Failed to capture clipboard: {}", e);
+                }
+            });
+            
+            // v1.4: Network queue; operations that failed offline are retried once it's back
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                let mut was_online = None;
+                loop {
+                    std::thread::sleep(NETWORK_CHECK_INTERVAL);
+                    if let Err(e) = process_network_queue(&handle, &mut was_online) {
+                        eprintln!("Failed to process network queue: {}", e);
+                    }
+                }
+            });
+            
+            // v1.4: Report emails; the weekly review or standup goes out on Friday afternoon
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(REPORT_CHECK_INTERVAL);
+                if let Err(e) = send_scheduled_report(&handle) {
+                    eprintln!("Failed to send scheduled report: {}", e);
+                }
+            });
+            
+            // v1.4: Hourly anomaly analysis; findings become dashboard alerts
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                if let Err(e) = check_anomalies(&handle) {
+                    eprintln!("Failed to check for anomalies: {}", e);
+                }
+                std::thread::sleep(ANOMALY_CHECK_INTERVAL);
+            });
+            
+            // v1.4: CI status; sources are polled every few minutes
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                if let Err(e) = poll_ci_sources(&handle, None) {
+                    eprintln!("Failed to poll CI: {}", e);
+                }
+                std::thread::sleep(CI_POLL_INTERVAL);
+            });
+            
+            // v1.4: Stats history; checked hourly, snapshotted once a day
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                if let Err(e) = snapshot_stats_daily(&handle) {
+                    eprintln!("Failed to snapshot stats: {}", e);
+                }
+                std::thread::sleep(STATS_SNAPSHOT_INTERVAL);
+            });
+            
+            // v1.4: Job queue; one job at a time, picked up from where a quit left it
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                if let Ok(db) = handle.state::<AppState>().db.lock() {
+                    let _ = db.requeue_interrupted_jobs();
+                }
+                loop {
+                    match run_next_job(&handle) {
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        Err(e) => eprintln!("Failed to run job: {}", e),
+                    }
+                    std::thread::sleep(JOB_POLL_INTERVAL);
+                }
+            });
+            
+            // v1.4: Hourly SLA check; each stale problem is escalated (and announced) once.
+            // Skipped during focus sessions so the announcement isn't lost.
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                if notifications_suppressed(&handle) {
+                    std::thread::sleep(std::time::Duration::from_secs(5 * 60));
+                    continue;
+                }
+                let escalated = {
+                    let state = handle.state::<AppState>();
+                    let db = match state.db.lock() {
+                        Ok(db) => db,
+                        Err(_) => return,
+                    };
+                    db.escalate_stale_problems().unwrap_or_default()
+                };
+                if !escalated.is_empty() {
+                    let _ = handle.emit("problems-escalated", &escalated);
+                }
+                std::thread::sleep(std::time::Duration::from_secs(60 * 60));
+            });
             Ok(())
-        })
+        });
+    
+    #[cfg(desktop)]
+    let builder = builder
         .on_menu_event(|app, event| {
             let id = event.id().as_ref();
             
+            // v1.4: Clearing recents is handled here so the menu updates even without a listener
+            if id == "clear_recent_projects" {
+                let state = app.state::<AppState>();
+                let cleared = state.db.lock().map(|db| db.clear_recent_projects().is_ok()).unwrap_or(false);
+                if cleared {
+                    let _ = rebuild_menu(app);
+                }
+                return;
+            }
+            
             // Get the main window
             if let Some(window) = app.get_webview_window("main") {
+                // v1.4: Project entries from Open Recent and the Window menu
+                if id.starts_with("open_project:") {
+                    let _ = window.emit("menu-event", id);
+                    return;
+                }
+                
                 // Emit events to the frontend based on menu selection
                 match id {
                     // File menu
@@ -1739,24 +9407,81 @@ pub fn run() {
                 }
             }
         })
-        .manage(AppState { db: Mutex::new(db) })
         .invoke_handler(tauri::generate_handler![
             // v1.0: Project commands
             list_projects,
             create_project,
             get_project,
             update_project,
+            rename_project,
             delete_project,
+            record_project_opened,
+            get_recent_projects,
+            record_view,
+            get_recent_items,
+            set_project_appearance,
+            pin_project,
+            set_project_sync,
+            set_project_attachment_sync,
+            reorder_projects,
             get_project_context,
+            get_multi_project_context,
+            get_context_delta,
             get_project_stats,
+            recompute_stats,
+            get_stats_history,
+            snapshot_stats,
+            check_referential_integrity,
             // v1.0: Component commands
             list_components,
             create_component,
             get_component,
             update_component,
             delete_component,
+            suggest_components_from_repo,
+            create_components_from_repo,
+            set_project_repo_path,
+            get_component_paths,
+            add_component_path,
+            delete_component_path,
+            get_component_for_path,
+            import_commits,
+            snapshot_dependencies,
+            get_dependency_snapshots,
+            get_snapshot_dependencies,
+            delete_dependency_snapshot,
+            ingest_test_results,
+            get_test_runs,
+            get_test_run,
+            delete_test_run,
+            get_flaky_tests,
+            add_ci_source,
+            get_ci_sources,
+            set_ci_source_enabled,
+            delete_ci_source,
+            get_ci_runs,
+            get_ci_status,
+            poll_ci,
+            link_sentry_project,
+            unlink_sentry_project,
+            get_sentry_project_links,
+            record_benchmark,
+            get_benchmark_series,
+            get_latest_benchmarks,
+            delete_benchmark,
+            get_jobs,
+            get_job,
+            cancel_job,
+            reindex_embeddings,
+            get_embedding_status,
+            get_ai_usage,
+            cluster_records,
+            describe_attachments,
             // v1.0: Change commands
             log_change,
+            log_changes_batch,
+            queue_change,
+            flush_change_queue,
             get_recent_changes,
             get_all_changes,
             // v1.0: Problem commands
@@ -1770,6 +9495,8 @@ pub fn run() {
             // v1.0: Attempt commands
             log_attempt,
             mark_attempt_outcome,
+            start_attempt,
+            finish_attempt,
             get_attempts_for_problem,
             // v1.0: Solution commands
             mark_problem_solved,
@@ -1780,17 +9507,32 @@ pub fn run() {
             get_todos,
             update_todo,
             delete_todo,
+            set_todo_estimate,
+            log_todo_time,
+            get_estimation_accuracy,
             // v1.0: Learning commands
             log_learning,
             get_learning,
             get_learnings,
             update_learning,
             delete_learning,
+            get_due_reviews,
+            record_review,
+            find_conflicting_learnings,
+            resolve_learning_conflict,
+            suggest_category,
+            auto_categorize_learnings,
+            apply_learning_categories,
             // v1.0: Search
             search,
+            get_facet_counts,
+            quick_open,
             // v1.0: Story generation
             generate_project_story,
             generate_problem_journey,
+            export_story_pdf,
+            export_static_site,
+            export_ai_corpus,
             // v1.1: File attachment commands
             attach_file,
             get_attachments,
@@ -1798,10 +9540,35 @@ pub fn run() {
             update_attachment,
             remove_attachment,
             read_file_content,
+            refresh_attachment_preview,
+            index_attachment_sections,
+            index_attachment_symbols,
+            analyze_log_attachment,
+            // v1.4: Annotations
+            get_annotations,
+            create_annotation,
+            update_annotation,
+            delete_annotation,
+            // v1.4: Attachment links
+            link_attachment,
+            unlink_attachment,
+            get_attachment_links,
+            // v1.4: Attachment storage
+            get_storage_usage,
+            cleanup_orphaned_files,
+            // v1.4: Directory watches
+            watch_project_directory,
+            get_directory_watches,
+            set_directory_watch_enabled,
+            unwatch_project_directory,
+            sync_directory_watch_now,
             // v1.1: Content location commands
             get_content_locations,
             create_content_location,
             delete_content_location,
+            // v1.4: Symbol search
+            search_symbols,
+            link_content_location,
             // v1.1: Extraction commands
             get_extractions,
             create_extraction,
@@ -1810,16 +9577,33 @@ pub fn run() {
             // v1.1: Git sync commands
             git_init,
             git_status,
+            git_sync_preview,
             git_sync,
+            cancel_sync,
+            generate_sync_commit_message,
             git_set_remote,
+            git_list_remotes,
+            git_add_remote,
+            git_remove_remote,
+            git_set_remote_enabled,
+            provision_remote,
             git_clone,
+            git_unshallow,
+            git_repo_size,
+            git_prune_history,
             git_history,
+            // v1.4: Attachment sync policies
+            set_attachment_sync_policy,
+            fetch_attachment_from_remote,
             // v1.1: Settings commands
             get_settings,
             get_setting,
             set_setting,
             delete_setting,
             get_settings_by_category,
+            get_timezone,
+            set_timezone,
+            resolve_time_range,
             // v1.1: Sync status commands
             get_sync_status,
             init_sync_status,
@@ -1828,6 +9612,188 @@ pub fn run() {
             log_sync_operation,
             // v1.1: File export
             write_text_file,
+            export_ics,
+            // v1.4: Severity SLAs
+            get_sla_thresholds,
+            set_sla_thresholds,
+            get_stale_problems,
+            // v1.4: Problem board
+            get_problem_board,
+            get_wip_limits,
+            set_wip_limits,
+            // v1.4: Dashboards
+            get_dashboard_layouts,
+            create_dashboard_layout,
+            update_dashboard_layout,
+            delete_dashboard_layout,
+            get_dashboard_data,
+            // v1.4: Goals
+            create_goal,
+            get_goals,
+            update_goal,
+            delete_goal,
+            add_key_result,
+            update_key_result,
+            delete_key_result,
+            get_goal_progress,
+            // v1.4: Changelog
+            generate_changelog,
+            generate_release_notes,
+            // v1.4: Retrospectives
+            generate_retrospective,
+            // v1.4: Anomaly alerts
+            detect_anomalies,
+            get_anomaly_alerts,
+            acknowledge_anomaly_alert,
+            // v1.4: People
+            list_people,
+            create_person,
+            update_person,
+            delete_person,
+            assign_todo,
+            assign_problem,
+            get_work_for_person,
+            // v1.4: Comments
+            get_comments,
+            add_comment,
+            update_comment,
+            delete_comment,
+            // v1.4: Problem checklists
+            get_checklist_items,
+            add_checklist_item,
+            update_checklist_item,
+            delete_checklist_item,
+            reorder_checklist_items,
+            get_problem_templates,
+            create_problem_from_template,
+            get_todo_templates,
+            create_todo_template,
+            update_todo_template,
+            delete_todo_template,
+            instantiate_todo_template,
+            get_todo_checklists,
+            get_todo_checklist,
+            delete_todo_checklist,
+            // v1.4: Problem environments
+            get_problem_environment,
+            set_problem_environment,
+            clear_problem_environment,
+            capture_environment,
+            // v1.4: Notes
+            create_note,
+            get_note,
+            get_notes,
+            update_note,
+            delete_note,
+            get_note_links,
+            get_backlinks,
+            // v1.4: Quick add
+            parse_quick_add,
+            quick_add,
+            get_tags,
+            add_tags,
+            remove_tag,
+            // v1.4: Stars
+            star_record,
+            unstar_record,
+            list_starred,
+            // v1.4: Watches
+            watch_record,
+            unwatch_record,
+            list_watches,
+            // v1.4: Streaming results
+            stream_result,
+            get_result_chunk,
+            release_result,
+            // v1.4: Captures
+            start_clipboard_capture,
+            stop_clipboard_capture,
+            get_clipboard_capture_status,
+            get_captures,
+            discard_capture,
+            convert_capture,
+            clear_captures,
+            // v1.4: Inbox
+            list_inbox,
+            add_inbox_item,
+            import_email,
+            triage_item,
+            // v1.4: Web references
+            add_reference,
+            get_references,
+            update_reference,
+            delete_reference,
+            refresh_reference_metadata,
+            snapshot_reference,
+            check_reference_links,
+            // v1.4: Network queue
+            get_pending_network_operations,
+            retry_network_operation,
+            cancel_network_operation,
+            get_network_status,
+            // v1.4: Shell history
+            import_shell_history,
+            // v1.4: Diagnostics
+            get_slow_queries,
+            clear_slow_queries,
+            // v1.4: Shortcuts
+            get_shortcut_map,
+            set_shortcut,
+            // v1.4: Focus mode
+            start_focus,
+            get_focus_state,
+            end_focus,
+            // v1.1: Legacy import
+            import_legacy_database,
+            // v1.4: Record history
+            get_record_history,
+            revert_to_version,
+            // v1.4: Report emails
+            send_report_now,
+            preview_report,
+            get_report_emails,
+            // v1.4: Record uuids
+            get_record_uuid,
+            // v1.4: Author identity
+            get_author_identity,
+            set_author_identity,
+            // v1.4: Record locks
+            acquire_lock,
+            release_lock,
+            get_lock,
+            list_locks,
+            expire_stale_locks,
+            // v1.4: Project export & merge
+            export_project,
+            export_report_db,
+            import_project,
+            // v1.4: Notion export
+            export_to_notion,
+            get_notion_links,
+            // v1.4: Confluence publishing
+            publish_to_confluence,
+            get_confluence_links,
+            // v1.4: Automation links
+            get_automation_token,
+            regenerate_automation_token,
+            // v1.4: Launcher endpoints and local API
+            query_quick,
+            quick_actions,
+            set_local_api_enabled,
+            get_local_api_status,
+            create_api_token,
+            list_api_tokens,
+            revoke_api_token,
+            get_api_usage_stats,
+            // v1.4: LAN sync
+            set_lan_sync_enabled,
+            get_lan_sync_key,
+            set_lan_sync_key,
+            get_lan_sync_status,
+            // v1.1: Update commands
+            check_for_updates,
+            install_update,
+            get_release_notes,
             // v1.2: Project Variables commands
             create_project_variable,
             get_project_variables,
@@ -1842,7 +9808,290 @@ pub fn run() {
             get_conversations,
             get_sessions_list,
             get_cross_references,
-        ])
+        ]);
+    
+    // v1.4: Mobile companion. Everything that needs git, host file paths, a menu bar or a
+    // long-running desktop process (watches, local API, updater) stays on the desktop.
+    #[cfg(mobile)]
+    let builder = builder.invoke_handler(tauri::generate_handler![
+        // Projects
+        list_projects,
+        create_project,
+        get_project,
+        update_project,
+        rename_project,
+        delete_project,
+        record_project_opened,
+        get_recent_projects,
+        record_view,
+        get_recent_items,
+        set_project_appearance,
+        pin_project,
+        set_project_sync,
+        set_project_attachment_sync,
+        reorder_projects,
+        get_project_context,
+        get_multi_project_context,
+        get_context_delta,
+        get_project_stats,
+        recompute_stats,
+        get_stats_history,
+        snapshot_stats,
+        check_referential_integrity,
+        // Components
+        list_components,
+        create_component,
+        get_component,
+        update_component,
+        delete_component,
+        suggest_components_from_repo,
+        create_components_from_repo,
+        set_project_repo_path,
+        get_component_paths,
+        add_component_path,
+        delete_component_path,
+        get_component_for_path,
+        import_commits,
+        snapshot_dependencies,
+        get_dependency_snapshots,
+        get_snapshot_dependencies,
+        delete_dependency_snapshot,
+        ingest_test_results,
+        get_test_runs,
+        get_test_run,
+        delete_test_run,
+        get_flaky_tests,
+        add_ci_source,
+        get_ci_sources,
+        set_ci_source_enabled,
+        delete_ci_source,
+        get_ci_runs,
+        get_ci_status,
+        poll_ci,
+        link_sentry_project,
+        unlink_sentry_project,
+        get_sentry_project_links,
+        record_benchmark,
+        get_benchmark_series,
+        get_latest_benchmarks,
+        delete_benchmark,
+        get_jobs,
+        get_job,
+        cancel_job,
+        reindex_embeddings,
+        get_embedding_status,
+        get_ai_usage,
+        cluster_records,
+        describe_attachments,
+        // Changes
+        log_change,
+        log_changes_batch,
+        queue_change,
+        flush_change_queue,
+        get_recent_changes,
+        get_all_changes,
+        // Problems, attempts and solutions
+        log_problem,
+        get_problem,
+        get_open_problems,
+        get_all_problems,
+        update_problem,
+        delete_problem,
+        get_problem_tree,
+        log_attempt,
+        mark_attempt_outcome,
+        start_attempt,
+        finish_attempt,
+        get_attempts_for_problem,
+        mark_problem_solved,
+        get_solution_for_problem,
+        // Todos
+        add_todo,
+        get_todo,
+        get_todos,
+        update_todo,
+        delete_todo,
+        set_todo_estimate,
+        log_todo_time,
+        get_estimation_accuracy,
+        // Learnings
+        log_learning,
+        get_learning,
+        get_learnings,
+        update_learning,
+        delete_learning,
+        get_due_reviews,
+        record_review,
+        find_conflicting_learnings,
+        resolve_learning_conflict,
+        suggest_category,
+        auto_categorize_learnings,
+        apply_learning_categories,
+        // Search and story
+        search,
+        get_facet_counts,
+        quick_open,
+        generate_project_story,
+        generate_problem_journey,
+        // Attachments already in the data folder
+        get_attachments,
+        get_attachment,
+        update_attachment,
+        remove_attachment,
+        set_attachment_sync_policy,
+        get_annotations,
+        create_annotation,
+        update_annotation,
+        delete_annotation,
+        link_attachment,
+        unlink_attachment,
+        get_attachment_links,
+        get_storage_usage,
+        get_content_locations,
+        create_content_location,
+        delete_content_location,
+        search_symbols,
+        link_content_location,
+        get_extractions,
+        create_extraction,
+        update_extraction_review,
+        delete_extraction,
+        // Settings and sync status
+        get_settings,
+        get_setting,
+        set_setting,
+        delete_setting,
+        get_settings_by_category,
+        get_timezone,
+        set_timezone,
+        resolve_time_range,
+        get_sync_status,
+        get_sync_history,
+        // SLAs, problem board, people, comments, checklists, environments
+        get_sla_thresholds,
+        set_sla_thresholds,
+        get_stale_problems,
+        get_problem_board,
+        get_wip_limits,
+        set_wip_limits,
+        get_dashboard_layouts,
+        create_dashboard_layout,
+        update_dashboard_layout,
+        delete_dashboard_layout,
+        get_dashboard_data,
+        create_goal,
+        get_goals,
+        update_goal,
+        delete_goal,
+        add_key_result,
+        update_key_result,
+        delete_key_result,
+        get_goal_progress,
+        generate_release_notes,
+        generate_retrospective,
+        detect_anomalies,
+        get_anomaly_alerts,
+        acknowledge_anomaly_alert,
+        list_people,
+        create_person,
+        update_person,
+        delete_person,
+        assign_todo,
+        assign_problem,
+        get_work_for_person,
+        get_comments,
+        add_comment,
+        update_comment,
+        delete_comment,
+        get_checklist_items,
+        add_checklist_item,
+        update_checklist_item,
+        delete_checklist_item,
+        reorder_checklist_items,
+        get_problem_templates,
+        create_problem_from_template,
+        get_todo_templates,
+        create_todo_template,
+        update_todo_template,
+        delete_todo_template,
+        instantiate_todo_template,
+        get_todo_checklists,
+        get_todo_checklist,
+        delete_todo_checklist,
+        get_problem_environment,
+        set_problem_environment,
+        clear_problem_environment,
+        // Notes, quick add, tags, stars, watches
+        create_note,
+        get_note,
+        get_notes,
+        update_note,
+        delete_note,
+        get_note_links,
+        get_backlinks,
+        parse_quick_add,
+        quick_add,
+        get_tags,
+        add_tags,
+        remove_tag,
+        star_record,
+        unstar_record,
+        list_starred,
+        watch_record,
+        unwatch_record,
+        list_watches,
+        // Streaming results
+        stream_result,
+        get_result_chunk,
+        release_result,
+        // Captures made on the desktop
+        get_captures,
+        discard_capture,
+        convert_capture,
+        clear_captures,
+        list_inbox,
+        add_inbox_item,
+        import_email,
+        triage_item,
+        // Web references
+        add_reference,
+        get_references,
+        update_reference,
+        delete_reference,
+        refresh_reference_metadata,
+        check_reference_links,
+        // Network queue
+        get_pending_network_operations,
+        retry_network_operation,
+        cancel_network_operation,
+        get_network_status,
+        // Focus, history, uuids
+        start_focus,
+        get_focus_state,
+        end_focus,
+        get_record_history,
+        revert_to_version,
+        get_record_uuid,
+        get_author_identity,
+        set_author_identity,
+        // Launcher search (in-app)
+        query_quick,
+        quick_actions,
+        // Variables, methods, conversations
+        create_project_variable,
+        get_project_variables,
+        update_project_variable,
+        delete_project_variable,
+        create_project_method,
+        get_project_methods,
+        update_project_method,
+        delete_project_method,
+        get_conversations,
+        get_sessions_list,
+        get_cross_references,
+    ]);
+    
+    builder
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
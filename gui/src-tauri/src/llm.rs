@@ -0,0 +1,203 @@
+// FlowState LLM Module - Optional text generation through a chat completions API
+// Any OpenAI-compatible endpoint works: OpenAI itself, or a local server such as Ollama
+// (http://localhost:11434/v1) or LM Studio, which need no key. Nothing is sent anywhere
+// unless a model is configured, and callers always have a plain fallback.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+// Host fragments of well-known endpoints and the provider each belongs to
+const PROVIDERS: &[(&str, &str)] = &[
+    ("openai.com", "openai"),
+    ("anthropic.com", "anthropic"),
+    ("openrouter.ai", "openrouter"),
+    ("groq.com", "groq"),
+    ("mistral.ai", "mistral"),
+    ("together", "together"),
+    ("googleapis.com", "google"),
+    (":11434", "ollama"),
+    (":1234", "lmstudio"),
+];
+
+/// v1.4: Tokens one request used, as the endpoint reports them (0 when it doesn't).
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct Usage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// v1.4: Part of a prompt: text, or an image for models that can see.
+pub enum Part {
+    Text(String),
+    Image { mime: String, data: Vec<u8> },
+}
+
+/// v1.4: Prices per million tokens, for estimating what a request cost.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prices {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl Prices {
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.input_tokens as f64 * self.input + usage.output_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// v1.4: One request to the provider, as a Meter hears of it.
+pub struct Call<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub operation: &'a str, // chat or embeddings
+    pub usage: Usage,
+}
+
+/// v1.4: Sees every request a client makes: `check` can refuse one before it's sent (a spent
+/// budget), and `record` hears what each that succeeded used.
+pub trait Meter {
+    fn check(&self) -> Result<(), String>;
+    fn record(&self, call: &Call);
+}
+
+pub struct LlmClient<'m> {
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    meter: Option<Box<dyn Meter + 'm>>,
+}
+
+impl<'m> LlmClient<'m> {
+    pub fn new(base_url: Option<&str>, api_key: Option<&str>, model: &str) -> Self {
+        LlmClient {
+            base_url: base_url.unwrap_or(DEFAULT_BASE_URL).trim_end_matches('/').to_string(),
+            api_key: api_key.map(String::from),
+            model: model.to_string(),
+            meter: None,
+        }
+    }
+
+    /// v1.4: The same client with every request checked and recorded by `meter`.
+    pub fn metered(mut self, meter: impl Meter + 'm) -> Self {
+        self.meter = Some(Box::new(meter));
+        self
+    }
+
+    /// v1.4: Who serves the requests, from the endpoint's host: a known provider's name, or
+    /// the host itself.
+    pub fn provider(&self) -> String {
+        let host = self.base_url.split("://").nth(1).unwrap_or(&self.base_url).split('/').next().unwrap_or_default();
+        let known = PROVIDERS.iter().find(|(pattern, _)| host.contains(pattern));
+        known.map_or_else(|| host.to_string(), |(_, name)| name.to_string())
+    }
+
+    /// The model's reply to `prompt` under the `system` instructions, trimmed.
+    pub fn complete(&self, system: &str, prompt: &str, max_tokens: u32) -> Result<String, String> {
+        self.chat(system, json!(prompt), max_tokens).map(|(text, _)| text)
+    }
+
+    /// v1.4: The reply to a prompt of text and images, with the tokens it took.
+    pub fn complete_parts(&self, system: &str, parts: &[Part], max_tokens: u32) -> Result<(String, Usage), String> {
+        let content: Vec<Value> = parts.iter()
+            .map(|part| match part {
+                Part::Text(text) => json!({ "type": "text", "text": text }),
+                Part::Image { mime, data } => json!({
+                    "type": "image_url",
+                    "image_url": { "url": format!("data:{};base64,{}", mime, STANDARD.encode(data)) },
+                }),
+            })
+            .collect();
+        self.chat(system, Value::Array(content), max_tokens)
+    }
+
+    fn chat(&self, system: &str, content: Value, max_tokens: u32) -> Result<(String, Usage), String> {
+        self.check()?;
+        let mut request = ureq::post(&format!("{}/chat/completions", self.base_url))
+            .timeout(Duration::from_secs(60));
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Value = request
+            .send_json(json!({
+                "model": self.model,
+                "max_tokens": max_tokens,
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": content },
+                ],
+            }))
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        let usage = Usage {
+            input_tokens: response["usage"]["prompt_tokens"].as_i64().unwrap_or_default(),
+            output_tokens: response["usage"]["completion_tokens"].as_i64().unwrap_or_default(),
+        };
+        self.record("chat", usage);
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|text| (text.trim().to_string(), usage))
+            .filter(|(text, _)| !text.is_empty())
+            .ok_or_else(|| "The language model returned no text".to_string())
+    }
+
+    /// v1.4: One embedding vector per text, in order, from the endpoint's /embeddings.
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        self.check()?;
+        let mut request = ureq::post(&format!("{}/embeddings", self.base_url))
+            .timeout(Duration::from_secs(120));
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Value = request
+            .send_json(json!({ "model": self.model, "input": texts }))
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        self.record("embeddings", Usage {
+            input_tokens: response["usage"]["prompt_tokens"].as_i64().unwrap_or_default(),
+            output_tokens: 0,
+        });
+        let mut data: Vec<&Value> = response["data"].as_array().map(|d| d.iter().collect()).unwrap_or_default();
+        // Responses carry each vector's index; most keep the order but nothing promises it
+        data.sort_by_key(|item| item["index"].as_u64().unwrap_or_default());
+        let vectors: Vec<Vec<f32>> = data.iter()
+            .map(|item| {
+                item["embedding"].as_array()
+                    .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+        if vectors.len() != texts.len() || vectors.iter().any(|v| v.is_empty()) {
+            return Err(format!("The embedding model returned {} vectors for {} texts", vectors.len(), texts.len()));
+        }
+        Ok(vectors)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.meter.as_ref().map_or(Ok(()), |meter| meter.check())
+    }
+
+    fn record(&self, operation: &str, usage: Usage) {
+        if let Some(meter) = &self.meter {
+            let provider = self.provider();
+            meter.record(&Call { provider: &provider, model: &self.model, operation, usage });
+        }
+    }
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let message = response.into_json::<Value>().ok()
+                .and_then(|v| v["error"]["message"].as_str().map(|m| m.to_string()))
+                .unwrap_or_default();
+            format!("Language model API error {}: {}", code, message)
+        }
+        ureq::Error::Transport(t) => format!("Failed to reach the language model: {}", t),
+    }
+}
@@ -0,0 +1,211 @@
+// FlowState Local API Module - JSON over HTTP on 127.0.0.1
+// For launchers and scripts on the same machine (Raycast, Alfred, curl). It only listens on
+// loopback, rejects requests addressed to any other host name (so a web page can't reach it
+// through DNS rebinding), and leaves authentication to the handler. Requests are small, so
+// a thread per connection with blocking I/O is plenty.
+// v1.4: Callers get a per-minute request budget (RateLimiter) so a runaway script can't keep
+// the database busy; the handler decides whose budget a request counts against. The limit is
+// kept here rather than read from settings, so a request over it never reaches the database.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 47821;
+pub const DEFAULT_RATE_LIMIT: usize = 120; // Requests per caller per minute
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const MAX_HEADER_LINES: usize = 100;
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: BTreeMap<String, String>, // Lowercase names
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.headers.get("authorization")?.strip_prefix("Bearer ").map(|t| t.trim())
+    }
+
+    /// The JSON body; an empty body reads as `{}`.
+    pub fn json(&self) -> Result<Value, String> {
+        if self.body.iter().all(|b| b.is_ascii_whitespace()) {
+            return Ok(json!({}));
+        }
+        serde_json::from_slice(&self.body).map_err(|e| format!("Invalid JSON body: {}", e))
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+    pub retry_after: Option<u64>, // Seconds, sent as Retry-After
+}
+
+impl Response {
+    pub fn ok(body: Value) -> Self {
+        Response { status: 200, body, retry_after: None }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Response { status, body: json!({ "error": message }), retry_after: None }
+    }
+
+    pub fn too_many_requests(retry_after: Duration) -> Self {
+        let seconds = retry_after.as_secs() + 1;
+        Response {
+            status: 429,
+            body: json!({ "error": "Too many requests", "retry_after_seconds": seconds }),
+            retry_after: Some(seconds),
+        }
+    }
+}
+
+/// Sliding one-minute window of request times per caller.
+pub struct RateLimiter {
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+    limit: AtomicUsize, // Requests per caller per minute
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter { hits: Mutex::default(), limit: AtomicUsize::new(DEFAULT_RATE_LIMIT) }
+    }
+}
+
+impl RateLimiter {
+    /// Use `limit` requests a minute from now on; `None` or 0 goes back to the default.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        let limit = limit.filter(|l| *l > 0).unwrap_or(DEFAULT_RATE_LIMIT);
+        self.limit.store(limit, Ordering::SeqCst);
+    }
+
+    /// Count a request from `caller` if it's within the limit for the last minute; otherwise
+    /// how long until it would be.
+    pub fn allow(&self, caller: &str) -> Result<(), Duration> {
+        let limit = self.limit.load(Ordering::SeqCst);
+        let now = Instant::now();
+        let Ok(mut hits) = self.hits.lock() else { return Ok(()) };
+        hits.retain(|_, times| times.back().is_some_and(|last| now.duration_since(*last) < RATE_WINDOW));
+        let times = hits.entry(caller.to_string()).or_default();
+        while times.front().is_some_and(|first| now.duration_since(*first) >= RATE_WINDOW) {
+            times.pop_front();
+        }
+        if times.len() >= limit {
+            let oldest = times.front().copied().unwrap_or(now);
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+/// Listen on 127.0.0.1:`port` and answer every request with `handler` on its own thread.
+/// Fails right away if the port can't be bound.
+pub fn serve<F>(port: u16, handler: F) -> Result<(), String>
+where
+    F: Fn(&Request) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Local API couldn't listen on port {}: {}", port, e))?;
+    let handler = Arc::new(handler);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let handler = handler.clone();
+            std::thread::spawn(move || {
+                let _ = respond(stream, port, handler.as_ref());
+            });
+        }
+    });
+    Ok(())
+}
+
+fn respond<F: Fn(&Request) -> Response>(mut stream: TcpStream, port: u16, handler: &F) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let response = match read_request(&stream) {
+        Ok(request) if !local_host(&request, port) => Response::error(403, "Requests must be addressed to localhost"),
+        Ok(request) => handler(&request),
+        Err(response) => response,
+    };
+
+    let body = response.body.to_string();
+    let retry_after = response.retry_after.map(|s| format!("Retry-After: {}\r\n", s)).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\n{}Connection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        body.len(),
+        retry_after,
+        body,
+    )?;
+    stream.flush()
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let bad_request = |message: &str| Response::error(400, message);
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|_| bad_request("Unreadable request"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line"));
+    };
+    // Query strings aren't used; parameters go in the JSON body
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    let method = method.to_string();
+
+    let mut headers = BTreeMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(|_| bad_request("Unreadable headers"))?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() >= MAX_HEADER_LINES {
+            return Err(bad_request("Too many headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Request body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|_| bad_request("Request body shorter than Content-Length"))?;
+    Ok(Request { method, path, headers, body })
+}
+
+fn local_host(request: &Request, port: u16) -> bool {
+    let host = request.headers.get("host").map(|h| h.as_str()).unwrap_or_default();
+    ["127.0.0.1", "localhost", "[::1]"].iter().any(|name| host == *name || host == format!("{}:{}", name, port))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
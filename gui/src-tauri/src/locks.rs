@@ -0,0 +1,168 @@
+// FlowState Locks Module - Advisory record locks for a shared sync repository
+// A lock is a small JSON file under `locks/` in the synced folder, one per record, named by the
+// record's uuid so it means the same thing in every copy of the database. Git merges separate
+// files from different devices cleanly, which the database file can't do. Locks only warn or
+// refuse edits in the app; nothing stops git or another tool from writing.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const DIR: &str = "locks";
+pub const DEFAULT_MINUTES: i64 = 60;
+const STALE_CLAIM: std::time::Duration = std::time::Duration::from_secs(30); // Left by a writer that died
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lock {
+    pub table: String,
+    pub uuid: String,
+    pub holder: Option<String>, // Author name of the device holding it
+    pub device: String,         // Device id, which decides who the lock belongs to
+    pub acquired_at: String,    // RFC 3339
+    pub expires_at: String,
+}
+
+impl Lock {
+    pub fn is_expired(&self) -> bool {
+        DateTime::parse_from_rfc3339(&self.expires_at)
+            .map(|expires| expires <= Utc::now())
+            .unwrap_or(true)
+    }
+
+    /// "Sam (until 14:05 UTC)", for messages.
+    pub fn describe(&self) -> String {
+        let until = DateTime::parse_from_rfc3339(&self.expires_at)
+            .map(|expires| expires.with_timezone(&Utc).format("%H:%M UTC").to_string())
+            .unwrap_or_default();
+        format!("{} (until {})", self.holder.as_deref().unwrap_or("another device"), until)
+    }
+}
+
+fn lock_path(dir: &Path, table: &str, uuid: &str) -> PathBuf {
+    dir.join(table).join(format!("{}.json", uuid))
+}
+
+fn read_file(path: &Path) -> Option<Lock> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// The unexpired lock on a record, if any.
+pub fn get(dir: &Path, table: &str, uuid: &str) -> Option<Lock> {
+    read_file(&lock_path(dir, table, uuid)).filter(|lock| !lock.is_expired())
+}
+
+/// Claim on a record's lock file for one acquire: created exclusively, so two writers on this
+/// machine can't both find the record free and both take it. Removed when dropped.
+struct Claim(PathBuf);
+
+impl Claim {
+    fn take(lock_path: &Path) -> Result<Claim, String> {
+        let path = lock_path.with_extension("json.claim");
+        let create = || std::fs::OpenOptions::new().write(true).create_new(true).open(&path);
+        match create() {
+            Ok(_) => Ok(Claim(path)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let abandoned = std::fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|taken| taken.elapsed().is_ok_and(|age| age > STALE_CLAIM));
+                if !abandoned {
+                    return Err("Someone else is locking this record right now".to_string());
+                }
+                let _ = std::fs::remove_file(&path);
+                create().map(|_| Claim(path)).map_err(|_| "Someone else is locking this record right now".to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Lock a record for `minutes`, or renew this device's lock on it. Fails while another
+/// device holds an unexpired lock. The check and the write happen under a `Claim`, and the
+/// lock file is replaced by a rename, so readers never see half of one.
+pub fn acquire(
+    dir: &Path,
+    table: &str,
+    uuid: &str,
+    device: &str,
+    holder: Option<&str>,
+    minutes: i64,
+) -> Result<Lock, String> {
+    if minutes <= 0 {
+        return Err("Lock duration must be a positive number of minutes".to_string());
+    }
+    let path = lock_path(dir, table, uuid);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let _claim = Claim::take(&path)?;
+    if let Some(lock) = get(dir, table, uuid).filter(|lock| lock.device != device) {
+        return Err(format!("Locked by {}", lock.describe()));
+    }
+    let now = Utc::now();
+    let lock = Lock {
+        table: table.to_string(),
+        uuid: uuid.to_string(),
+        holder: holder.map(String::from),
+        device: device.to_string(),
+        acquired_at: now.to_rfc3339(),
+        expires_at: (now + Duration::minutes(minutes)).to_rfc3339(),
+    };
+    let json = serde_json::to_string_pretty(&lock).map_err(|e| e.to_string())?;
+    let written = path.with_extension("json.tmp");
+    std::fs::write(&written, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&written, &path).map_err(|e| e.to_string())?;
+    Ok(lock)
+}
+
+/// Remove a record's lock. Only this device's own or an expired lock goes unless `force` is
+/// set. Returns whether there was a lock to remove.
+pub fn release(dir: &Path, table: &str, uuid: &str, device: &str, force: bool) -> Result<bool, String> {
+    let path = lock_path(dir, table, uuid);
+    let Some(lock) = read_file(&path) else {
+        return Ok(false);
+    };
+    if lock.device != device && !lock.is_expired() && !force {
+        return Err(format!("Locked by {}; only its holder can release it", lock.describe()));
+    }
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Every lock file, expired ones included, oldest first.
+pub fn list(dir: &Path) -> Vec<Lock> {
+    let mut locks: Vec<Lock> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .flat_map(|table| std::fs::read_dir(table.path()).into_iter().flatten().flatten())
+        .filter_map(|entry| read_file(&entry.path()))
+        .collect();
+    locks.sort_by(|a, b| a.acquired_at.cmp(&b.acquired_at));
+    locks
+}
+
+/// Delete expired lock files (and unreadable ones), so the next sync removes them everywhere.
+/// Returns how many went.
+pub fn expire_stale(dir: &Path) -> usize {
+    let mut removed = 0;
+    for table in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        for entry in std::fs::read_dir(table.path()).into_iter().flatten().flatten() {
+            // Claims and half-written files belong to an acquire in progress
+            if entry.path().extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let stale = read_file(&entry.path()).map(|lock| lock.is_expired()).unwrap_or(true);
+            if stale && std::fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
@@ -0,0 +1,210 @@
+// FlowState Log Parse Module - Turn log files into proposed problems
+// Recognises Rust panics, Python tracebacks and JS stack traces (plus any user rules),
+// groups repeats of the same error, and keeps the line ranges of every occurrence so
+// each proposal can point content_locations back into the log.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+// Enough occurrences to find the pattern; the count still covers every one
+const MAX_LOCATIONS_PER_FINDING: usize = 20;
+const MAX_SNIPPET_CHARS: usize = 2000;
+const MAX_TITLE_CHARS: usize = 120;
+
+/// A user rule: any line containing `contains` is reported under `name`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogRule {
+    pub name: String,
+    pub contains: String,
+    pub severity: Option<String>, // Defaults to medium
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LineRange {
+    pub start_line: usize, // 1-based, inclusive
+    pub end_line: usize,
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogFinding {
+    pub kind: String, // rust_panic, python_traceback, js_error, or the rule name
+    pub title: String,
+    pub severity: String,
+    pub occurrences: usize,
+    pub locations: Vec<LineRange>,
+}
+
+/// Findings with the most occurrences first.
+pub fn analyze(text: &str, rules: &[LogRule]) -> Vec<LogFinding> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut findings: Vec<(String, LogFinding)> = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let block = rust_panic(&lines, i)
+            .or_else(|| python_traceback(&lines, i))
+            .or_else(|| js_error(&lines, i));
+        let (kind, title, severity, end) = match block {
+            Some((kind, title, end)) => (kind.to_string(), title, "high".to_string(), end),
+            None => match rules.iter().find(|r| !r.contains.is_empty() && lines[i].contains(&r.contains)) {
+                Some(rule) => (
+                    rule.name.clone(),
+                    lines[i].trim().to_string(),
+                    rule.severity.clone().unwrap_or_else(|| "medium".to_string()),
+                    i,
+                ),
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+        };
+
+        let location = LineRange {
+            start_line: i + 1,
+            end_line: end + 1,
+            snippet: truncate(&lines[i..=end].join("\n"), MAX_SNIPPET_CHARS),
+        };
+        let key = format!("{}|{}", kind, signature(&title));
+        match findings.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, finding)) => {
+                finding.occurrences += 1;
+                if finding.locations.len() < MAX_LOCATIONS_PER_FINDING {
+                    finding.locations.push(location);
+                }
+            }
+            None => findings.push((key, LogFinding {
+                kind,
+                title: truncate(&title, MAX_TITLE_CHARS),
+                severity,
+                occurrences: 1,
+                locations: vec![location],
+            })),
+        }
+        i = end + 1;
+    }
+
+    let mut findings: Vec<LogFinding> = findings.into_iter().map(|(_, f)| f).collect();
+    findings.sort_by_key(|f| Reverse(f.occurrences));
+    findings
+}
+
+// ============================================================
+// FORMATS
+// ============================================================
+
+/// `thread 'main' panicked at src/main.rs:2:5:` with the message on the next line (Rust 1.73+),
+/// or `thread 'main' panicked at 'message', src/main.rs:2:5` before that. Backtraces are included.
+fn rust_panic(lines: &[&str], at: usize) -> Option<(&'static str, String, usize)> {
+    let line = lines[at];
+    let start = line.find("thread '")?;
+    let rest = &line[start..];
+    let panicked = rest.find("' panicked at ")?;
+    let after = &rest[panicked + "' panicked at ".len()..];
+
+    let mut end = at;
+    let message = if let Some(quoted) = after.strip_prefix('\'') {
+        quoted.rsplit_once("', ").map(|(m, _)| m.to_string()).unwrap_or_else(|| quoted.to_string())
+    } else {
+        // A header on the log's last line has no message to take in
+        let message = lines.get(at + 1).map(|m| m.trim().to_string());
+        if message.is_some() {
+            end += 1;
+        }
+        message.unwrap_or_default()
+    };
+
+    // note: run with `RUST_BACKTRACE=1` ..., stack backtrace:, and indented frames
+    while let Some(next) = lines.get(end + 1) {
+        let trimmed = next.trim_start();
+        if trimmed.starts_with("note:") || trimmed.starts_with("stack backtrace:") || (next.starts_with(' ') && !trimmed.is_empty()) {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    Some(("rust_panic", format!("Panic: {}", message), end))
+}
+
+/// `Traceback (most recent call last):`, indented frames, then `ValueError: message`.
+fn python_traceback(lines: &[&str], at: usize) -> Option<(&'static str, String, usize)> {
+    if !lines[at].trim_start().starts_with("Traceback (most recent call last):") {
+        return None;
+    }
+    let mut end = at;
+    while let Some(next) = lines.get(end + 1) {
+        if next.starts_with(' ') || next.starts_with('\t') {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    let exception = lines.get(end + 1).map(|l| l.trim()).filter(|l| !l.is_empty())?;
+    Some(("python_traceback", exception.to_string(), end + 1))
+}
+
+/// `TypeError: x is undefined` (optionally `Uncaught ...`) followed by `    at ...` frames.
+fn js_error(lines: &[&str], at: usize) -> Option<(&'static str, String, usize)> {
+    let line = lines[at].trim();
+    let is_frame = |l: &str| l.trim_start().starts_with("at ") && l.starts_with(char::is_whitespace);
+    if !(line.contains("Error") || line.contains("Exception")) || !lines.get(at + 1).is_some_and(|l| is_frame(l)) {
+        return None;
+    }
+    let mut end = at + 1;
+    while lines.get(end + 1).is_some_and(|l| is_frame(l)) {
+        end += 1;
+    }
+    let title = line.trim_start_matches("Uncaught ").to_string();
+    Some(("js_error", title, end))
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// Repeats of one error differ in ids, counts, addresses and timestamps; numbers are masked
+/// so they group together.
+fn signature(title: &str) -> String {
+    let mut signature = String::new();
+    let mut in_number = false;
+    for c in title.to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                signature.push('#');
+            }
+            in_number = true;
+        } else {
+            in_number = false;
+            signature.push(c);
+        }
+    }
+    signature
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_header_on_the_last_line() {
+        let findings = analyze("starting\nthread 'main' panicked at src/main.rs:2:5:", &[]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].title, "Panic: ");
+        assert_eq!((findings[0].locations[0].start_line, findings[0].locations[0].end_line), (2, 2));
+    }
+
+    #[test]
+    fn panic_message_on_the_next_line() {
+        let findings = analyze("thread 'main' panicked at src/main.rs:2:5:\nindex out of bounds\ndone", &[]);
+        assert_eq!(findings[0].title, "Panic: index out of bounds");
+        assert_eq!(findings[0].locations[0].end_line, 2);
+    }
+}
@@ -0,0 +1,239 @@
+// FlowState Mailer Module - Minimal SMTP client for report emails
+// Speaks just enough SMTP to hand one plain-text message to the user's mail server:
+// implicit TLS (port 465), STARTTLS (587) or, for local relays, no encryption; AUTH PLAIN
+// or LOGIN. Passwords are never sent over an unencrypted connection.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+const CLIENT_NAME: &str = "flowstate.localhost";
+
+pub const SECURITY_MODES: &[&str] = &["starttls", "tls", "none"];
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: String, // starttls, tls, none
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+/// Send a plain-text message (UTF-8, so Markdown reads fine) to every recipient.
+pub fn send(config: &SmtpConfig, to: &[String], subject: &str, body: &str) -> Result<(), String> {
+    if to.is_empty() {
+        return Err("No recipients".to_string());
+    }
+    for address in std::iter::once(&config.from).chain(to) {
+        validate_address(address)?;
+    }
+    if !SECURITY_MODES.contains(&config.security.as_str()) {
+        return Err(format!("Unknown SMTP security '{}' (starttls, tls or none)", config.security));
+    }
+    if config.security == "none" && config.username.is_some() {
+        return Err("Refusing to send the SMTP password without encryption; use starttls or tls".to_string());
+    }
+
+    let addr = (config.host.as_str(), config.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", config.host, e))?
+        .next()
+        .ok_or_else(|| format!("Could not resolve {}", config.host))?;
+    let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        .map_err(|e| format!("Failed to reach {}:{}: {}", config.host, config.port, e))?;
+    tcp.set_read_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    tcp.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+
+    let stream = if config.security == "tls" { Stream::Tls(Box::new(tls(&config.host, tcp)?)) } else { Stream::Plain(tcp) };
+    let mut session = Session { stream, buffer: Vec::new() };
+    session.expect(&[220])?;
+    let mut capabilities = session.command(&format!("EHLO {}", CLIENT_NAME), &[250])?;
+
+    if config.security == "starttls" {
+        if !capabilities.iter().any(|c| c.eq_ignore_ascii_case("STARTTLS")) {
+            return Err(format!("{} doesn't offer STARTTLS; use security 'tls' or 'none'", config.host));
+        }
+        session.command("STARTTLS", &[220])?;
+        session.stream = match session.stream {
+            Stream::Plain(tcp) => Stream::Tls(Box::new(tls(&config.host, tcp)?)),
+            tls_stream => tls_stream,
+        };
+        capabilities = session.command(&format!("EHLO {}", CLIENT_NAME), &[250])?;
+    }
+
+    if let Some(username) = &config.username {
+        let password = config.password.as_deref().unwrap_or_default();
+        let auth = capabilities.iter()
+            .find_map(|c| c.to_ascii_uppercase().strip_prefix("AUTH").map(|m| m.to_string()))
+            .unwrap_or_default();
+        if auth.split_whitespace().any(|m| m == "PLAIN") || !auth.split_whitespace().any(|m| m == "LOGIN") {
+            let credentials = STANDARD.encode(format!("\0{}\0{}", username, password));
+            session.command(&format!("AUTH PLAIN {}", credentials), &[235])?;
+        } else {
+            session.command("AUTH LOGIN", &[334])?;
+            session.command(&STANDARD.encode(username), &[334])?;
+            session.command(&STANDARD.encode(password), &[235])?;
+        }
+    }
+
+    session.command(&format!("MAIL FROM:<{}>", config.from), &[250])?;
+    for address in to {
+        session.command(&format!("RCPT TO:<{}>", address), &[250, 251])?;
+    }
+    session.command("DATA", &[354])?;
+    session.write(&message(&config.from, to, subject, body))?;
+    session.command(".", &[250])?;
+    // The message is accepted at this point; a server that drops the connection on QUIT is fine
+    let _ = session.command("QUIT", &[221]);
+    Ok(())
+}
+
+/// Headers plus a base64 body, which keeps lines short and any character safe in transit.
+fn message(from: &str, to: &[String], subject: &str, body: &str) -> String {
+    let normalized = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    let encoded = STANDARD.encode(normalized);
+    let mut lines = vec![
+        format!("From: {}", from),
+        format!("To: {}", to.join(", ")),
+        format!("Subject: {}", encode_header(subject)),
+        format!("Date: {}", chrono::Local::now().to_rfc2822()),
+        format!("Message-ID: <{}@{}>", uuid::Uuid::new_v4(), CLIENT_NAME),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+        "Content-Transfer-Encoding: base64".to_string(),
+        String::new(),
+    ];
+    // Base64 has no '.', so no line needs dot-stuffing
+    lines.extend(encoded.as_bytes().chunks(76).map(|chunk| String::from_utf8_lossy(chunk).to_string()));
+    lines.join("\r\n") + "\r\n"
+}
+
+/// RFC 2047 encoded-word for subjects that aren't plain ASCII.
+fn encode_header(value: &str) -> String {
+    let value: String = value.chars().filter(|c| !c.is_control()).collect();
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?utf-8?B?{}?=", STANDARD.encode(value))
+    }
+}
+
+/// Enough to catch typos and keep anything that could break out of a header or command.
+pub fn validate_address(address: &str) -> Result<(), String> {
+    let valid = address.split_once('@').is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        && !address.chars().any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | ',' | ';'));
+    if valid { Ok(()) } else { Err(format!("'{}' is not an email address", address)) }
+}
+
+fn tls(host: &str, tcp: TcpStream) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, String> {
+    let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|e| format!("Invalid SMTP host {}: {}", host, e))?;
+    let connection = rustls::ClientConnection::new(Arc::new(config), name)
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    Ok(rustls::StreamOwned::new(connection, tcp))
+}
+
+// ============================================================
+// CONNECTION
+// ============================================================
+
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Plain(s) => s.read(buf),
+            Stream::Tls(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Plain(s) => s.write(buf),
+            Stream::Tls(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Stream::Plain(s) => s.flush(),
+            Stream::Tls(s) => s.flush(),
+        }
+    }
+}
+
+struct Session {
+    stream: Stream,
+    buffer: Vec<u8>, // Read but not yet consumed
+}
+
+impl Session {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        self.stream.write_all(text.as_bytes())
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| format!("Failed to reach the SMTP server: {}", e))
+    }
+
+    /// Send one command line and check the reply code. The password lines of AUTH LOGIN
+    /// are sent through here too, so commands never appear in errors.
+    fn command(&mut self, line: &str, accepted: &[u16]) -> Result<Vec<String>, String> {
+        self.write(&format!("{}\r\n", line))?;
+        self.expect(accepted)
+    }
+
+    /// Read a reply and return its text lines (for EHLO, the capabilities).
+    fn expect(&mut self, accepted: &[u16]) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            let code = line.get(..3).and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| format!("Unexpected SMTP reply: {}", line))?;
+            let last = line.as_bytes().get(3) != Some(&b'-');
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+            if last {
+                if !accepted.contains(&code) {
+                    return Err(format!("SMTP server refused: {} {}", code, lines.join(" ")));
+                }
+                // The first line of an EHLO reply is the server greeting, not a capability
+                if lines.len() > 1 {
+                    lines.remove(0);
+                }
+                return Ok(lines);
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String, String> {
+        loop {
+            if let Some(end) = self.buffer.windows(2).position(|w| w == b"\r\n") {
+                let line = String::from_utf8_lossy(&self.buffer[..end]).to_string();
+                self.buffer.drain(..end + 2);
+                return Ok(line);
+            }
+            let mut chunk = [0u8; 1024];
+            let read = self.stream.read(&mut chunk)
+                .map_err(|e| format!("Failed to reach the SMTP server: {}", e))?;
+            if read == 0 {
+                return Err("SMTP server closed the connection".to_string());
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
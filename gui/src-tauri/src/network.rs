@@ -0,0 +1,69 @@
+// FlowState Network Module - Connectivity checks and retry pacing for queued operations
+// Being online is judged by reaching well-known public resolvers over TCP, so a captive
+// portal or a dead DNS server doesn't pass for a working connection. The answer is cached
+// briefly because commands ask before every outbound call.
+
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const PROBES: &[&str] = &["1.1.1.1:443", "8.8.8.8:443", "9.9.9.9:443"];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const CACHE_TTL: Duration = Duration::from_secs(15);
+
+// Retries start quickly and back off to at most an hour; after that many failures the
+// operation is parked as failed until the user retries it
+const FIRST_RETRY_SECS: u64 = 30;
+const MAX_RETRY_SECS: u64 = 60 * 60;
+pub const MAX_ATTEMPTS: i64 = 8;
+
+static LAST_CHECK: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+
+/// Whether the network is reachable, from the cache when it's fresh.
+pub fn is_online() -> bool {
+    if let Ok(last) = LAST_CHECK.lock() {
+        if let Some((at, online)) = *last {
+            if at.elapsed() < CACHE_TTL {
+                return online;
+            }
+        }
+    }
+    check_connectivity()
+}
+
+/// Probe the network now and refresh the cache.
+pub fn check_connectivity() -> bool {
+    let online = PROBES
+        .iter()
+        .filter_map(|p| p.parse::<SocketAddr>().ok())
+        .any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok());
+    if let Ok(mut last) = LAST_CHECK.lock() {
+        *last = Some((Instant::now(), online));
+    }
+    online
+}
+
+/// Whether an error message is what an operation reports when it can't reach the network,
+/// as opposed to a real failure (bad token, conflict) that retrying won't fix.
+pub fn looks_offline(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "failed to reach",
+        "could not resolve",
+        "dns",
+        "network is unreachable",
+        "no route to host",
+        "timed out",
+        "connection refused",
+        "connection reset",
+        "unable to access",
+    ]
+    .iter()
+    .any(|marker| error.contains(marker))
+}
+
+/// Delay before the next try after `attempts` failures.
+pub fn backoff(attempts: i64) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    Duration::from_secs(FIRST_RETRY_SECS.saturating_mul(1 << exponent).min(MAX_RETRY_SECS))
+}
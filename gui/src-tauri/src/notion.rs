@@ -0,0 +1,255 @@
+// FlowState Notion Module - Export components, problems, and learnings to Notion
+// Records map onto one Notion database per entity type; page ids are kept in
+// integration_links so re-exports update pages instead of duplicating them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const NOTION_API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+const RICH_TEXT_LIMIT: usize = 2000;
+
+pub const EXPORTABLE_ENTITIES: &[&str] = &["component", "problem", "learning"];
+
+// ============================================================
+// MAPPING
+// ============================================================
+
+/// How one FlowState field lands in a Notion database property.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PropertyMapping {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String, // title, rich_text, select, number, checkbox, date
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntityMapping {
+    pub database_title: String,
+    pub database_id: Option<String>, // Use an existing database instead of creating one
+    pub properties: BTreeMap<String, PropertyMapping>, // FlowState field -> Notion property
+}
+
+fn property(name: &str, kind: &str) -> PropertyMapping {
+    PropertyMapping { name: name.to_string(), kind: kind.to_string() }
+}
+
+pub fn default_mapping(entity: &str) -> Option<EntityMapping> {
+    let (title, fields): (&str, Vec<(&str, PropertyMapping)>) = match entity {
+        "component" => ("Components", vec![
+            ("name", property("Name", "title")),
+            ("status", property("Status", "select")),
+            ("description", property("Description", "rich_text")),
+            ("created_at", property("Created", "date")),
+        ]),
+        "problem" => ("Problems", vec![
+            ("title", property("Name", "title")),
+            ("status", property("Status", "select")),
+            ("severity", property("Severity", "select")),
+            ("description", property("Description", "rich_text")),
+            ("root_cause", property("Root Cause", "rich_text")),
+            ("created_at", property("Created", "date")),
+            ("solved_at", property("Solved", "date")),
+        ]),
+        "learning" => ("Learnings", vec![
+            ("insight", property("Insight", "title")),
+            ("category", property("Category", "select")),
+            ("context", property("Context", "rich_text")),
+            ("verified", property("Verified", "checkbox")),
+            ("created_at", property("Created", "date")),
+        ]),
+        _ => return None,
+    };
+
+    Some(EntityMapping {
+        database_title: title.to_string(),
+        database_id: None,
+        properties: fields.into_iter().map(|(f, p)| (f.to_string(), p)).collect(),
+    })
+}
+
+/// Merge a user mapping (the `notion_mapping` setting) over the defaults.
+pub fn resolve_mapping(entity: &str, overrides: Option<&Value>) -> Option<EntityMapping> {
+    let mut mapping = default_mapping(entity)?;
+    if let Some(custom) = overrides.and_then(|o| o.get(entity)) {
+        if let Some(title) = custom.get("database_title").and_then(|v| v.as_str()) {
+            mapping.database_title = title.to_string();
+        }
+        if let Some(id) = custom.get("database_id").and_then(|v| v.as_str()) {
+            mapping.database_id = Some(id.to_string());
+        }
+        if let Some(props) = custom.get("properties").and_then(|v| v.as_object()) {
+            for (field, prop) in props {
+                match serde_json::from_value::<PropertyMapping>(prop.clone()) {
+                    Ok(p) => { mapping.properties.insert(field.clone(), p); }
+                    // `"field": null` drops a default property from the export
+                    Err(_) if prop.is_null() => { mapping.properties.remove(field); }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+    Some(mapping)
+}
+
+/// Notion database property schema for a mapping.
+pub fn database_schema(mapping: &EntityMapping) -> Value {
+    let properties: serde_json::Map<String, Value> = mapping.properties.values()
+        .map(|p| (p.name.clone(), json!({ p.kind.clone(): {} })))
+        .collect();
+    Value::Object(properties)
+}
+
+/// Notion page property values for a serialized FlowState record.
+pub fn page_properties(mapping: &EntityMapping, record: &Value) -> Value {
+    let mut properties = serde_json::Map::new();
+    for (field, prop) in &mapping.properties {
+        let value = record.get(field).unwrap_or(&Value::Null);
+        let text = match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Null => None,
+            other => Some(other.to_string()),
+        };
+
+        let notion_value = match prop.kind.as_str() {
+            "title" => json!({ "title": rich_text(text.as_deref().unwrap_or("Untitled")) }),
+            "rich_text" => json!({ "rich_text": rich_text(text.as_deref().unwrap_or("")) }),
+            "select" => json!({ "select": text.map(|t| json!({ "name": t.replace(',', " ") })) }),
+            "number" => json!({ "number": value.as_f64() }),
+            "checkbox" => json!({ "checkbox": value.as_bool().unwrap_or(false) }),
+            "date" => json!({ "date": text.map(|t| json!({ "start": to_iso8601(&t) })) }),
+            _ => continue,
+        };
+        properties.insert(prop.name.clone(), notion_value);
+    }
+    Value::Object(properties)
+}
+
+fn rich_text(content: &str) -> Value {
+    let truncated: String = content.chars().take(RICH_TEXT_LIMIT).collect();
+    if truncated.is_empty() {
+        return json!([]);
+    }
+    json!([{ "type": "text", "text": { "content": truncated } }])
+}
+
+/// SQLite CURRENT_TIMESTAMP ("2026-01-28 14:03:00") is UTC without a zone marker.
+fn to_iso8601(timestamp: &str) -> String {
+    match chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S") {
+        Ok(dt) => dt.and_utc().to_rfc3339(),
+        Err(_) => timestamp.to_string(),
+    }
+}
+
+// ============================================================
+// API CLIENT
+// ============================================================
+
+pub enum PageUpdate {
+    Updated,
+    Missing, // Page was deleted or archived on the Notion side
+}
+
+pub struct NotionClient {
+    token: String,
+}
+
+impl NotionClient {
+    pub fn new(token: &str) -> Self {
+        NotionClient { token: token.to_string() }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        ureq::request(method, &format!("{}{}", NOTION_API, path))
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .set("Notion-Version", NOTION_VERSION)
+            .timeout(Duration::from_secs(30))
+    }
+
+    pub fn create_database(&self, parent_page_id: &str, title: &str, properties: Value) -> Result<String, String> {
+        let response: Value = self.request("POST", "/databases")
+            .send_json(json!({
+                "parent": { "type": "page_id", "page_id": parent_page_id },
+                "title": rich_text(title),
+                "properties": properties,
+            }))
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        extract_id(&response)
+    }
+
+    pub fn create_page(&self, database_id: &str, properties: Value, children: Vec<Value>) -> Result<String, String> {
+        let response: Value = self.request("POST", "/pages")
+            .send_json(json!({
+                "parent": { "database_id": database_id },
+                "properties": properties,
+                "children": children,
+            }))
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        extract_id(&response)
+    }
+
+    pub fn update_page(&self, page_id: &str, properties: Value) -> Result<PageUpdate, String> {
+        match self.request("PATCH", &format!("/pages/{}", page_id)).send_json(json!({ "properties": properties })) {
+            Ok(_) => Ok(PageUpdate::Updated),
+            Err(ureq::Error::Status(404, _)) => Ok(PageUpdate::Missing),
+            Err(e) => Err(describe_error(e)),
+        }
+    }
+}
+
+/// Page body for a newly created page: long-form fields as headed paragraphs.
+pub fn page_body(entity: &str, record: &Value) -> Vec<Value> {
+    let field = |name: &str| record.get(name).and_then(|v| v.as_str());
+    match entity {
+        "component" => paragraph_blocks(&[("Description", field("description"))]),
+        "problem" => paragraph_blocks(&[
+            ("Description", field("description")),
+            ("Root Cause", field("root_cause")),
+        ]),
+        "learning" => paragraph_blocks(&[("Context", field("context"))]),
+        _ => Vec::new(),
+    }
+}
+
+fn paragraph_blocks(sections: &[(&str, Option<&str>)]) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    for (heading, body) in sections {
+        let Some(body) = body.filter(|b| !b.trim().is_empty()) else { continue };
+        blocks.push(json!({
+            "object": "block",
+            "type": "heading_3",
+            "heading_3": { "rich_text": rich_text(heading) },
+        }));
+        blocks.push(json!({
+            "object": "block",
+            "type": "paragraph",
+            "paragraph": { "rich_text": rich_text(body) },
+        }));
+    }
+    blocks
+}
+
+fn extract_id(response: &Value) -> Result<String, String> {
+    response.get("id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Notion response did not include an id".to_string())
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let message = response.into_json::<Value>().ok()
+                .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(|m| m.to_string()))
+                .unwrap_or_default();
+            format!("Notion API error {}: {}", code, message)
+        }
+        ureq::Error::Transport(t) => format!("Failed to reach Notion: {}", t),
+    }
+}
@@ -0,0 +1,564 @@
+// FlowState PDF Module - Small PDF writer for shareable exports
+// Lays out headings, paragraphs, quotes, code, stats and images on A4 pages using the
+// standard PDF fonts, so no font has to be embedded and no browser engine is needed.
+// Those fonts only cover Windows-1252; other letters print as '?' and emoji are dropped.
+// Images can be JPEG (embedded as is) or PNG (decoded and recompressed).
+
+use flate2::{write::ZlibEncoder, Compression};
+use std::io::Write;
+
+const PAGE_WIDTH: f32 = 595.28; // A4
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 56.0;
+const FOOTER_HEIGHT: f32 = 24.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const MAX_IMAGE_HEIGHT: f32 = 340.0;
+// Decoded PNGs are held in memory while the document is written
+const MAX_IMAGE_BYTES: usize = 64 * 1024 * 1024;
+
+const INK: (f32, f32, f32) = (0.12, 0.13, 0.16);
+const MUTED: (f32, f32, f32) = (0.42, 0.45, 0.50);
+const ACCENT: (f32, f32, f32) = (0.43, 0.16, 0.85); // StoryMode purple, darkened for paper
+const CODE_BACKGROUND: (f32, f32, f32) = (0.95, 0.95, 0.96);
+
+pub enum Block {
+    Title(String),
+    Subtitle(String), // Muted line under a title or heading
+    Heading(String),
+    Subheading(String),
+    Paragraph(String),
+    Quote { text: String, cite: Option<String> },
+    Code(String),
+    Bullets(Vec<String>),
+    Stats(Vec<(String, String)>), // (value, label)
+    Image { data: Vec<u8>, caption: Option<String> },
+}
+
+/// Lay out `blocks` and return the PDF file. `title` goes in the document info and the
+/// page footers. Images that can't be read are left out rather than failing the export.
+pub fn render(title: &str, blocks: &[Block]) -> Result<Vec<u8>, String> {
+    let mut layout = Layout::new();
+    for block in blocks {
+        layout.block(block);
+    }
+    layout.finish_page();
+    write_document(title, &layout)
+}
+
+// ============================================================
+// FONTS AND TEXT
+// ============================================================
+
+#[derive(Clone, Copy, PartialEq)]
+enum Font {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+}
+
+const FONTS: &[(Font, &str, &str)] = &[
+    (Font::Regular, "F1", "Helvetica"),
+    (Font::Bold, "F2", "Helvetica-Bold"),
+    (Font::Italic, "F3", "Helvetica-Oblique"),
+    (Font::Mono, "F4", "Courier"),
+];
+
+// Advance widths (per 1000 em) of ' ' through '~', from the standard Helvetica metrics.
+// The oblique face shares the regular widths.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+// Windows-1252 bytes 0x80-0x9f that have glyphs in the standard fonts
+const WIN_ANSI_EXTRAS: &[(char, u8, u16)] = &[
+    ('€', 0x80, 556), ('‚', 0x82, 222), ('ƒ', 0x83, 556), ('„', 0x84, 333), ('…', 0x85, 1000),
+    ('†', 0x86, 556), ('‡', 0x87, 556), ('ˆ', 0x88, 333), ('‰', 0x89, 1000), ('Š', 0x8A, 667),
+    ('‹', 0x8B, 333), ('Œ', 0x8C, 1000), ('Ž', 0x8E, 611), ('‘', 0x91, 222), ('’', 0x92, 222),
+    ('“', 0x93, 333), ('”', 0x94, 333), ('•', 0x95, 350), ('–', 0x96, 556), ('—', 0x97, 1000),
+    ('˜', 0x98, 333), ('™', 0x99, 1000), ('š', 0x9A, 500), ('›', 0x9B, 333), ('œ', 0x9C, 944),
+    ('ž', 0x9E, 500), ('Ÿ', 0x9F, 667),
+];
+
+impl Font {
+    fn resource(self) -> &'static str {
+        FONTS.iter().find(|(f, _, _)| *f == self).map(|(_, name, _)| *name).unwrap_or("F1")
+    }
+
+    fn width(self, byte: u8, size: f32) -> f32 {
+        let units = match (self, byte) {
+            (Font::Mono, _) => 600,
+            (_, 32..=126) if self == Font::Bold => HELVETICA_BOLD_WIDTHS[(byte - 32) as usize],
+            (_, 32..=126) => HELVETICA_WIDTHS[(byte - 32) as usize],
+            _ => WIN_ANSI_EXTRAS.iter().find(|(_, b, _)| *b == byte).map(|(_, _, w)| *w).unwrap_or(556),
+        };
+        units as f32 * size / 1000.0
+    }
+
+    fn text_width(self, text: &[u8], size: f32) -> f32 {
+        text.iter().map(|b| self.width(*b, size)).sum()
+    }
+}
+
+/// Text as Windows-1252 bytes, the encoding of the standard fonts.
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => bytes.extend_from_slice(b"    "),
+            ' '..='~' | '\u{a0}'..='\u{ff}' => bytes.push(c as u8),
+            _ => match WIN_ANSI_EXTRAS.iter().find(|(extra, _, _)| *extra == c) {
+                Some((_, byte, _)) => bytes.push(*byte),
+                // A letter the fonts lack still takes its place; symbols and emoji just go
+                None if c.is_alphanumeric() => bytes.push(b'?'),
+                None => {}
+            },
+        }
+    }
+    bytes
+}
+
+/// Text on one line, with the gaps left by dropped characters closed up.
+fn encode_line(text: &str) -> Vec<u8> {
+    text.split_whitespace().map(encode).filter(|w| !w.is_empty()).collect::<Vec<_>>().join(&b' ')
+}
+
+/// Break text into lines no wider than `width`, keeping explicit line breaks.
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<Vec<u8>> {
+    let space = font.width(b' ', size);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line: Vec<u8> = Vec::new();
+        let mut line_width = 0.0;
+        for word in paragraph.split_whitespace().map(encode).filter(|w| !w.is_empty()) {
+            let word_width = font.text_width(&word, size);
+            if !line.is_empty() && line_width + space + word_width <= width {
+                line.push(b' ');
+                line.extend_from_slice(&word);
+                line_width += space + word_width;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Words wider than the line (URLs, hashes) are split wherever they overflow
+            line_width = 0.0;
+            for byte in word {
+                let w = font.width(byte, size);
+                if line_width + w > width && !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                line.push(byte);
+                line_width += w;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// A PDF literal string.
+fn literal(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('(');
+    for &b in bytes {
+        match b {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            32..=126 => out.push(b as char),
+            _ => out.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    out.push(')');
+    out
+}
+
+// ============================================================
+// LAYOUT
+// ============================================================
+
+struct Layout {
+    pages: Vec<String>, // Finished content streams
+    current: String,
+    y: f32, // Top of the free space on the current page
+    images: Vec<Image>,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Layout { pages: Vec::new(), current: String::new(), y: PAGE_HEIGHT - MARGIN, images: Vec::new() }
+    }
+
+    fn finish_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.current));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Start a new page unless `height` still fits on this one.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN + FOOTER_HEIGHT && !self.current.is_empty() {
+            self.finish_page();
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        // Space at the top of a page would only push content down
+        if !self.current.is_empty() {
+            self.y -= height;
+        }
+    }
+
+    fn text_line(&mut self, bytes: &[u8], font: Font, size: f32, leading: f32, x: f32, color: (f32, f32, f32)) {
+        self.ensure_space(leading);
+        let baseline = self.y - size;
+        self.current.push_str(&format!(
+            "BT /{} {} Tf {} {} {} rg {:.2} {:.2} Td {} Tj ET\n",
+            font.resource(), size, color.0, color.1, color.2, x, baseline, literal(bytes),
+        ));
+        self.y -= leading;
+    }
+
+    fn paragraph(&mut self, text: &str, font: Font, size: f32, leading: f32, indent: f32, color: (f32, f32, f32)) {
+        for line in wrap(text, font, size, CONTENT_WIDTH - indent) {
+            self.text_line(&line, font, size, leading, MARGIN + indent, color);
+        }
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: (f32, f32, f32)) {
+        self.current.push_str(&format!(
+            "{} {} {} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            color.0, color.1, color.2, x, y, width, height,
+        ));
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Title(text) => {
+                self.paragraph(text, Font::Bold, 24.0, 30.0, 0.0, INK);
+                self.gap(4.0);
+            }
+            Block::Subtitle(text) => {
+                self.paragraph(text, Font::Italic, 11.0, 15.0, 0.0, MUTED);
+                self.gap(10.0);
+            }
+            Block::Heading(text) => {
+                self.gap(18.0);
+                // Keep a heading with at least a few lines of what follows
+                self.ensure_space(90.0);
+                self.paragraph(text, Font::Bold, 17.0, 22.0, 0.0, ACCENT);
+                self.gap(4.0);
+            }
+            Block::Subheading(text) => {
+                self.gap(8.0);
+                self.ensure_space(50.0);
+                self.paragraph(text, Font::Bold, 12.5, 17.0, 0.0, INK);
+                self.gap(2.0);
+            }
+            Block::Paragraph(text) => {
+                self.paragraph(text, Font::Regular, 10.5, 15.0, 0.0, INK);
+                self.gap(8.0);
+            }
+            Block::Quote { text, cite } => {
+                let lines = wrap(text, Font::Italic, 11.0, CONTENT_WIDTH - 16.0);
+                for line in lines {
+                    self.ensure_space(16.0);
+                    let y = self.y;
+                    self.rect(MARGIN, y - 16.0, 3.0, 16.0, ACCENT);
+                    self.text_line(&line, Font::Italic, 11.0, 16.0, MARGIN + 14.0, INK);
+                }
+                if let Some(cite) = cite {
+                    self.paragraph(&format!("— {}", cite), Font::Regular, 9.0, 13.0, 14.0, MUTED);
+                }
+                self.gap(10.0);
+            }
+            Block::Code(text) => {
+                let size = 8.5;
+                let columns = ((CONTENT_WIDTH - 16.0) / Font::Mono.width(b' ', size)) as usize;
+                self.gap(2.0);
+                for source_line in text.lines() {
+                    let bytes = encode(source_line);
+                    let chunks: Vec<&[u8]> = if bytes.is_empty() { vec![&[]] } else { bytes.chunks(columns.max(1)).collect() };
+                    for chunk in chunks {
+                        self.ensure_space(11.0);
+                        let y = self.y;
+                        self.rect(MARGIN, y - 11.0, CONTENT_WIDTH, 11.0, CODE_BACKGROUND);
+                        self.text_line(chunk, Font::Mono, size, 11.0, MARGIN + 8.0, INK);
+                    }
+                }
+                self.gap(10.0);
+            }
+            Block::Bullets(items) => {
+                for item in items {
+                    let lines = wrap(item, Font::Regular, 10.5, CONTENT_WIDTH - 14.0);
+                    for (i, line) in lines.iter().enumerate() {
+                        self.ensure_space(15.0);
+                        if i == 0 {
+                            let y = self.y;
+                            self.text_line(&[0x95], Font::Regular, 10.5, 0.0, MARGIN + 2.0, ACCENT);
+                            self.y = y;
+                        }
+                        self.text_line(line, Font::Regular, 10.5, 15.0, MARGIN + 14.0, INK);
+                    }
+                    self.gap(2.0);
+                }
+                self.gap(6.0);
+            }
+            Block::Stats(stats) => {
+                for row in stats.chunks(4) {
+                    self.ensure_space(44.0);
+                    let column = CONTENT_WIDTH / row.len() as f32;
+                    let top = self.y;
+                    for (i, (value, label)) in row.iter().enumerate() {
+                        let x = MARGIN + column * i as f32;
+                        self.y = top;
+                        self.text_line(&encode(value), Font::Bold, 18.0, 24.0, x, ACCENT);
+                        self.text_line(&encode(label), Font::Regular, 8.5, 14.0, x, MUTED);
+                    }
+                    self.y = top - 44.0;
+                }
+                self.gap(8.0);
+            }
+            Block::Image { data, caption } => {
+                let Ok(image) = Image::load(data) else { return };
+                let scale = (CONTENT_WIDTH / image.width as f32).min(MAX_IMAGE_HEIGHT / image.height as f32).min(1.0);
+                let (width, height) = (image.width as f32 * scale, image.height as f32 * scale);
+                self.gap(4.0);
+                self.ensure_space(height + if caption.is_some() { 16.0 } else { 0.0 });
+                self.images.push(image);
+                self.current.push_str(&format!(
+                    "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q\n",
+                    width, height, MARGIN, self.y - height, self.images.len(),
+                ));
+                self.y -= height + 4.0;
+                if let Some(caption) = caption {
+                    self.paragraph(caption, Font::Italic, 9.0, 13.0, 0.0, MUTED);
+                }
+                self.gap(8.0);
+            }
+        }
+    }
+}
+
+// ============================================================
+// IMAGES
+// ============================================================
+
+struct Image {
+    width: u32,
+    height: u32,
+    color_space: &'static str,
+    filter: &'static str,
+    data: Vec<u8>,
+    decode: Option<&'static str>,
+    alpha: Option<Vec<u8>>, // Compressed soft mask
+}
+
+impl Image {
+    fn load(data: &[u8]) -> Result<Image, String> {
+        if data.starts_with(&[0xFF, 0xD8]) {
+            Self::jpeg(data)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Self::png(data)
+        } else {
+            Err("Only JPEG and PNG images can go in a PDF".to_string())
+        }
+    }
+
+    /// JPEGs go in untouched; only the frame header is read for the size and colours.
+    fn jpeg(data: &[u8]) -> Result<Image, String> {
+        let mut at = 2;
+        while at + 4 <= data.len() {
+            if data[at] != 0xFF {
+                return Err("Malformed JPEG".to_string());
+            }
+            let marker = data[at + 1];
+            let length = u16::from_be_bytes([data[at + 2], data[at + 3]]) as usize;
+            let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame && at + 10 <= data.len() {
+                let height = u16::from_be_bytes([data[at + 5], data[at + 6]]) as u32;
+                let width = u16::from_be_bytes([data[at + 7], data[at + 8]]) as u32;
+                let (color_space, decode) = match data[at + 9] {
+                    1 => ("DeviceGray", None),
+                    // Adobe writes CMYK JPEGs inverted
+                    4 => ("DeviceCMYK", Some("[1 0 1 0 1 0 1 0]")),
+                    _ => ("DeviceRGB", None),
+                };
+                if width == 0 || height == 0 {
+                    return Err("JPEG has no size".to_string());
+                }
+                return Ok(Image { width, height, color_space, filter: "DCTDecode", data: data.to_vec(), decode, alpha: None });
+            }
+            at += 2 + length;
+        }
+        Err("JPEG has no frame header".to_string())
+    }
+
+    /// PNGs are decoded to 8-bit samples; any alpha channel becomes a soft mask.
+    fn png(data: &[u8]) -> Result<Image, String> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        decoder.set_limits(png::Limits { bytes: MAX_IMAGE_BYTES });
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+        pixels.truncate(frame.buffer_size());
+
+        let (channels, color_space) = match frame.color_type {
+            png::ColorType::Grayscale => (1, "DeviceGray"),
+            png::ColorType::GrayscaleAlpha => (2, "DeviceGray"),
+            png::ColorType::Rgba => (4, "DeviceRGB"),
+            _ => (3, "DeviceRGB"),
+        };
+        let (color, alpha) = if channels % 2 == 0 {
+            let mut color = Vec::with_capacity(pixels.len());
+            let mut alpha = Vec::with_capacity(pixels.len() / channels);
+            for pixel in pixels.chunks_exact(channels) {
+                color.extend_from_slice(&pixel[..channels - 1]);
+                alpha.push(pixel[channels - 1]);
+            }
+            let opaque = alpha.iter().all(|a| *a == 255);
+            (color, if opaque { None } else { Some(deflate(&alpha)?) })
+        } else {
+            (pixels, None)
+        };
+        Ok(Image {
+            width: frame.width,
+            height: frame.height,
+            color_space,
+            filter: "FlateDecode",
+            data: deflate(&color)?,
+            decode: None,
+            alpha,
+        })
+    }
+}
+
+fn deflate(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())
+}
+
+// ============================================================
+// FILE STRUCTURE
+// ============================================================
+
+struct Objects {
+    out: Vec<u8>,
+    offsets: Vec<usize>, // Byte offset of each object, by id - 1
+}
+
+impl Objects {
+    fn add(&mut self, id: usize, dictionary: &str, stream: Option<&[u8]>) {
+        if self.offsets.len() < id {
+            self.offsets.resize(id, 0);
+        }
+        self.offsets[id - 1] = self.out.len();
+        self.out.extend_from_slice(format!("{} 0 obj\n{}", id, dictionary).as_bytes());
+        if let Some(stream) = stream {
+            self.out.extend_from_slice(b"\nstream\n");
+            self.out.extend_from_slice(stream);
+            self.out.extend_from_slice(b"\nendstream");
+        }
+        self.out.extend_from_slice(b"\nendobj\n");
+    }
+}
+
+/// Objects: 1 catalog, 2 page tree, 3 shared resources, 4 info, then fonts, images (each
+/// followed by its soft mask, if any) and each page with its content stream.
+fn write_document(title: &str, layout: &Layout) -> Result<Vec<u8>, String> {
+    let mut objects = Objects { out: b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec(), offsets: Vec::new() };
+    let font_ids: Vec<usize> = (0..FONTS.len()).map(|i| 5 + i).collect();
+    let mut next_id = 5 + FONTS.len();
+
+    let mut image_ids = Vec::new();
+    for image in &layout.images {
+        let id = next_id;
+        let mask_id = image.alpha.as_ref().map(|_| id + 1);
+        next_id += if mask_id.is_some() { 2 } else { 1 };
+        image_ids.push(id);
+
+        let mut dictionary = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8 /Filter /{} /Length {}",
+            image.width, image.height, image.color_space, image.filter, image.data.len(),
+        );
+        if let Some(decode) = image.decode {
+            dictionary.push_str(&format!(" /Decode {}", decode));
+        }
+        if let Some(mask_id) = mask_id {
+            dictionary.push_str(&format!(" /SMask {} 0 R", mask_id));
+        }
+        dictionary.push_str(" >>");
+        objects.add(id, &dictionary, Some(&image.data));
+        if let (Some(mask_id), Some(alpha)) = (mask_id, &image.alpha) {
+            objects.add(mask_id, &format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>",
+                image.width, image.height, alpha.len(),
+            ), Some(alpha));
+        }
+    }
+
+    for ((_, _, base), id) in FONTS.iter().zip(&font_ids) {
+        objects.add(*id, &format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", base), None);
+    }
+    let fonts: Vec<String> = FONTS.iter().zip(&font_ids).map(|((_, name, _), id)| format!("/{} {} 0 R", name, id)).collect();
+    let xobjects: Vec<String> = image_ids.iter().enumerate().map(|(i, id)| format!("/Im{} {} 0 R", i + 1, id)).collect();
+    objects.add(3, &format!("<< /Font << {} >> /XObject << {} >> >>", fonts.join(" "), xobjects.join(" ")), None);
+
+    let total = layout.pages.len();
+    let mut page_ids = Vec::new();
+    for (number, content) in layout.pages.iter().enumerate() {
+        let footer = encode_line(&format!("{} — {} of {}", title, number + 1, total));
+        let footer_width = Font::Regular.text_width(&footer, 8.0);
+        let stream = format!(
+            "{}BT /F1 8 Tf {} {} {} rg {:.2} {:.2} Td {} Tj ET\n",
+            content, MUTED.0, MUTED.1, MUTED.2, (PAGE_WIDTH - footer_width) / 2.0, MARGIN / 2.0, literal(&footer),
+        );
+        let compressed = deflate(stream.as_bytes())?;
+        let (page_id, content_id) = (next_id, next_id + 1);
+        next_id += 2;
+        objects.add(page_id, &format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources 3 0 R /Contents {} 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT, content_id,
+        ), None);
+        objects.add(content_id, &format!("<< /Length {} /Filter /FlateDecode >>", compressed.len()), Some(&compressed));
+        page_ids.push(page_id);
+    }
+
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    objects.add(2, &format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), total), None);
+    objects.add(1, "<< /Type /Catalog /Pages 2 0 R >>", None);
+    objects.add(4, &format!(
+        "<< /Title {} /Producer (FlowState) /CreationDate (D:{}Z) >>",
+        literal(&encode_line(title)),
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+    ), None);
+
+    let xref_at = objects.out.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.offsets.len() + 1);
+    for offset in &objects.offsets {
+        xref.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    xref.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.offsets.len() + 1, xref_at,
+    ));
+    objects.out.extend_from_slice(xref.as_bytes());
+    Ok(objects.out)
+}
@@ -0,0 +1,50 @@
+// FlowState Platform Module - What differs between desktop and mobile builds
+// Mobile apps are sandboxed: there's no git binary to run, and `dirs` has no answer on
+// Android, so the data directory comes from Tauri instead. Desktop behaviour is unchanged.
+// Git can also be left out of a desktop build with `--no-default-features`.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// True when this build can run the git CLI (desktop with the `git-cli` feature).
+pub const GIT_AVAILABLE: bool = cfg!(all(desktop, feature = "git-cli"));
+
+/// Use `path` as the data directory. Called once at startup on mobile with the app's
+/// sandboxed data directory; later calls are ignored.
+#[cfg(mobile)]
+pub fn set_data_dir(path: PathBuf) {
+    let _ = DATA_DIR.set(path);
+}
+
+/// The folder holding flowstate.db and everything that gets synced.
+pub fn data_dir() -> PathBuf {
+    match DATA_DIR.get() {
+        Some(path) => path.clone(),
+        None => dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("flowstate"),
+    }
+}
+
+/// The folder for settings that belong to this machine rather than the synced database,
+/// such as who is using it.
+pub fn local_dir() -> PathBuf {
+    match DATA_DIR.get() {
+        Some(path) => path.join("local"),
+        None => dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("flowstate"),
+    }
+}
+
+/// A `git` command, or an error on builds that can't run one.
+pub fn git() -> Result<Command, String> {
+    if GIT_AVAILABLE {
+        Ok(Command::new("git"))
+    } else {
+        Err("Git sync isn't available on this device".to_string())
+    }
+}
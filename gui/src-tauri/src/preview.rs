@@ -0,0 +1,283 @@
+// FlowState Preview Module - Cheap metadata for attachment cards
+// Read once when a file is attached so the Files view can show dimensions, page counts,
+// line counts or column headers without opening the file. Only headers are parsed;
+// anything unrecognised simply gets no metadata.
+
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::Path;
+
+// Image headers (and EXIF) sit at the front; PDFs need the whole file for their page tree
+const IMAGE_READ_LIMIT: u64 = 1024 * 1024;
+const TEXT_READ_LIMIT: u64 = 20 * 1024 * 1024;
+const PDF_READ_LIMIT: u64 = 64 * 1024 * 1024;
+
+const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("swift", "Swift"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cpp", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("sh", "Shell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+    ("json", "JSON"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("toml", "TOML"),
+    ("xml", "XML"),
+];
+
+/// Metadata for a file, keyed by its extension (`file_type` as stored on the attachment).
+pub fn extract(path: &Path, file_type: &str) -> Option<Value> {
+    let ext = file_type.to_lowercase();
+    match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" => image_metadata(&read_prefix(path, IMAGE_READ_LIMIT)?),
+        "pdf" => pdf_metadata(&read_prefix(path, PDF_READ_LIMIT)?),
+        "csv" | "tsv" => table_metadata(&read_prefix(path, TEXT_READ_LIMIT)?, if ext == "tsv" { '\t' } else { ',' }),
+        "md" | "txt" | "log" => Some(json!({ "kind": "text", "line_count": line_count(&read_prefix(path, TEXT_READ_LIMIT)?) })),
+        _ => {
+            let (_, language) = LANGUAGES.iter().find(|(e, _)| *e == ext)?;
+            Some(json!({
+                "kind": "code",
+                "language": language,
+                "line_count": line_count(&read_prefix(path, TEXT_READ_LIMIT)?),
+            }))
+        }
+    }
+}
+
+// ============================================================
+// IMAGES
+// ============================================================
+
+fn image_metadata(bytes: &[u8]) -> Option<Value> {
+    let (format, width, height, taken_at) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("png", be32(bytes, 16)?, be32(bytes, 20)?, None)
+    } else if bytes.starts_with(b"GIF8") {
+        ("gif", le16(bytes, 6)? as u32, le16(bytes, 8)? as u32, None)
+    } else if bytes.starts_with(b"BM") {
+        ("bmp", le32(bytes, 18)?, (le32(bytes, 22)? as i32).unsigned_abs(), None)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        let (w, h) = webp_size(bytes)?;
+        ("webp", w, h, None)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        let (w, h, taken_at) = jpeg_info(bytes)?;
+        ("jpeg", w, h, taken_at)
+    } else {
+        return None;
+    };
+    Some(json!({
+        "kind": "image",
+        "format": format,
+        "width": width,
+        "height": height,
+        "taken_at": taken_at,
+    }))
+}
+
+fn webp_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some(((le16(bytes, 26)? & 0x3FFF) as u32, (le16(bytes, 28)? & 0x3FFF) as u32)),
+        b"VP8L" => {
+            let b = bytes.get(21..25)?;
+            let width = 1 + (((b[1] as u32 & 0x3F) << 8) | b[0] as u32);
+            let height = 1 + (((b[3] as u32 & 0x0F) << 10) | ((b[2] as u32) << 2) | ((b[1] as u32 & 0xC0) >> 6));
+            Some((width, height))
+        }
+        b"VP8X" => {
+            let le24 = |at: usize| -> Option<u32> {
+                let b = bytes.get(at..at + 3)?;
+                Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+            };
+            Some((1 + le24(24)?, 1 + le24(27)?))
+        }
+        _ => None,
+    }
+}
+
+/// Walk JPEG segments up to the frame header, picking up the EXIF timestamp on the way.
+fn jpeg_info(bytes: &[u8]) -> Option<(u32, u32, Option<String>)> {
+    let mut taken_at = None;
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            return None;
+        }
+        let marker = bytes[pos + 1];
+        // Fill bytes and markers without a length
+        if marker == 0xFF || (0xD0..=0xD9).contains(&marker) || marker == 0x01 {
+            pos += if marker == 0xFF { 1 } else { 2 };
+            continue;
+        }
+        let length = be16(bytes, pos + 2)? as usize;
+        let segment = bytes.get(pos + 4..pos + 2 + length)?;
+        match marker {
+            0xE1 if segment.starts_with(b"Exif\0\0") => taken_at = exif_timestamp(&segment[6..]),
+            // Start-of-frame markers (C4, C8 and CC are tables, not frames)
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((be16(segment, 3)? as u32, be16(segment, 1)? as u32, taken_at));
+            }
+            _ => {}
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+/// DateTimeOriginal from the EXIF sub-IFD, falling back to IFD0's DateTime.
+fn exif_timestamp(tiff: &[u8]) -> Option<String> {
+    let big_endian = match tiff.get(0..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| if big_endian { be16(tiff, at) } else { le16(tiff, at) };
+    let u32_at = |at: usize| if big_endian { be32(tiff, at) } else { le32(tiff, at) };
+
+    // (tag, value-or-offset) for every entry of the IFD at `offset`
+    let entries = |offset: usize| -> Vec<(u16, u32, u32)> {
+        let count = u16_at(offset).unwrap_or(0) as usize;
+        (0..count)
+            .filter_map(|i| {
+                let at = offset + 2 + i * 12;
+                Some((u16_at(at)?, u32_at(at + 4)?, u32_at(at + 8)?))
+            })
+            .collect()
+    };
+    let ascii = |count: u32, offset: u32| -> Option<String> {
+        let raw = tiff.get(offset as usize..offset as usize + count as usize)?;
+        let text = String::from_utf8_lossy(raw).trim_end_matches('\0').trim().to_string();
+        // "2023:05:01 12:34:56" -> "2023-05-01 12:34:56"
+        let (date, time) = text.split_once(' ')?;
+        Some(format!("{} {}", date.replace(':', "-"), time))
+    };
+
+    let ifd0 = entries(u32_at(4)? as usize);
+    let original = ifd0
+        .iter()
+        .find(|(tag, _, _)| *tag == 0x8769)
+        .and_then(|(_, _, exif_offset)| {
+            entries(*exif_offset as usize)
+                .into_iter()
+                .find(|(tag, _, _)| *tag == 0x9003)
+                .and_then(|(_, count, offset)| ascii(count, offset))
+        });
+    original.or_else(|| {
+        ifd0.iter()
+            .find(|(tag, _, _)| *tag == 0x0132)
+            .and_then(|(_, count, offset)| ascii(*count, *offset))
+    })
+}
+
+// ============================================================
+// DOCUMENTS
+// ============================================================
+
+/// Page count from the page tree's /Count, or by counting page objects when the
+/// tree is hidden in compressed object streams.
+fn pdf_metadata(bytes: &[u8]) -> Option<Value> {
+    if !bytes.starts_with(b"%PDF-") {
+        return None;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let from_tree = text
+        .match_indices("/Count")
+        .filter_map(|(at, _)| {
+            let digits: String = text[at + 6..].trim_start().chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u64>().ok()
+        })
+        .max();
+    let page_objects = text
+        .match_indices("/Type")
+        .filter(|(at, _)| {
+            let rest = text[at + 5..].trim_start();
+            rest.starts_with("/Page") && !rest.starts_with("/Pages")
+        })
+        .count() as u64;
+    let page_count = from_tree.unwrap_or(0).max(page_objects);
+    let version = text.get(5..8).map(|v| v.to_string());
+    Some(json!({
+        "kind": "pdf",
+        "page_count": if page_count > 0 { Some(page_count) } else { None },
+        "pdf_version": version,
+    }))
+}
+
+fn table_metadata(bytes: &[u8], delimiter: char) -> Option<Value> {
+    let text = String::from_utf8_lossy(bytes);
+    let header = text.lines().next()?.trim_start_matches('\u{feff}');
+    let headers = split_row(header, delimiter);
+    let rows = text.lines().skip(1).filter(|l| !l.trim().is_empty()).count();
+    Some(json!({
+        "kind": "table",
+        "headers": headers,
+        "column_count": headers.len(),
+        "row_count": rows,
+    }))
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn read_prefix(path: &Path, limit: u64) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path).ok()?.take(limit).read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn line_count(bytes: &[u8]) -> usize {
+    let newlines = bytes.iter().filter(|b| **b == b'\n').count();
+    if bytes.last().is_some_and(|b| *b != b'\n') { newlines + 1 } else { newlines }
+}
+
+/// One CSV row, honouring double quotes (a quoted field may contain the delimiter or "").
+fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+fn be16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
@@ -0,0 +1,183 @@
+// FlowState Query Module - SELECTs assembled from fixed SQL with every value bound
+// The SQL text (columns, joins, ORDER BY) comes from the code; anything from outside, like a
+// search term, a project id or a limit, goes in as a parameter, so a title full of quotes or a
+// project named `x'); DROP TABLE problems; --` is only ever data. Text searches escape LIKE's
+// wildcards, so "100%" looks for a percent sign rather than matching everything.
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, Result, Row};
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    select: String,
+    conditions: Vec<String>,
+    params: Vec<Value>,
+    order_by: Option<String>,
+    limit: Option<i64>,
+}
+
+impl Query {
+    /// Start from "SELECT ... FROM ... JOIN ...", with no WHERE.
+    pub fn select(select: &str) -> Query {
+        Query { select: select.to_string(), conditions: Vec::new(), params: Vec::new(), order_by: None, limit: None }
+    }
+
+    /// AND a condition whose `?` placeholders take `values`, in order.
+    pub fn filter<I: IntoIterator<Item = Value>>(mut self, condition: &str, values: I) -> Query {
+        let before = self.params.len();
+        self.params.extend(values);
+        debug_assert_eq!(condition.matches('?').count(), self.params.len() - before, "placeholders in {}", condition);
+        self.conditions.push(format!("({})", condition));
+        self
+    }
+
+    /// AND `condition` with its one placeholder bound to `value`, when there is one.
+    pub fn filter_opt<T: Into<Value>>(self, condition: &str, value: Option<T>) -> Query {
+        match value {
+            Some(value) => self.filter(condition, [value.into()]),
+            None => self,
+        }
+    }
+
+    /// Keep rows where any of `columns` contains `text`, case-insensitively.
+    pub fn contains(self, columns: &[&str], text: &str) -> Query {
+        let pattern = contains_pattern(text);
+        let condition = columns.iter()
+            .map(|column| format!("LOWER({}) LIKE ? ESCAPE '\\'", column))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        self.filter(&condition, columns.iter().map(|_| Value::Text(pattern.clone())))
+    }
+
+    pub fn order_by(mut self, order_by: &str) -> Query {
+        self.order_by = Some(order_by.to_string());
+        self
+    }
+
+    pub fn limit(mut self, limit: i64) -> Query {
+        self.limit = Some(limit.max(0));
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let mut sql = self.select.clone();
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        if let Some(order_by) = &self.order_by {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(order_by);
+        }
+        if self.limit.is_some() {
+            sql.push_str(" LIMIT ?");
+        }
+        sql
+    }
+
+    /// The bound values, in placeholder order.
+    pub fn params(&self) -> Vec<Value> {
+        let mut params = self.params.clone();
+        params.extend(self.limit.map(Value::Integer));
+        params
+    }
+
+    /// Run the query and map each row. The SQL only varies with which filters were added, so
+    /// it goes through the statement cache.
+    pub fn query_map<T, F>(&self, conn: &Connection, f: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&Row<'_>) -> Result<T>,
+    {
+        let mut stmt = conn.prepare_cached(&self.sql())?;
+        let rows = stmt.query_map(params_from_iter(self.params()), f)?;
+        rows.collect()
+    }
+}
+
+/// `text` with LIKE's wildcards and the escape character escaped, for use with ESCAPE '\'.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The lowercase LIKE pattern matching text that contains `text`. NUL characters are dropped,
+/// since SQLite would end the pattern there and match everything.
+pub fn contains_pattern(text: &str) -> String {
+    format!("%{}%", escape_like(&text.replace('\0', "").to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, project_id INTEGER, title TEXT);
+             INSERT INTO items (project_id, title) VALUES
+                 (1, 'Plain title'), (1, '100% CPU'), (1, 'snake_case name'), (2, 'O''Brien''s \"quoted\" bug'),
+                 (2, 'back\\slash'), (2, 'x''); DROP TABLE items; --');",
+        ).unwrap();
+        conn
+    }
+
+    fn titles(conn: &Connection, query: &Query) -> Vec<String> {
+        query.query_map(conn, |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn builds_sql_with_placeholders_only() {
+        let query = Query::select("SELECT title FROM items")
+            .filter_opt("project_id = ?", Some(1))
+            .filter_opt::<i64>("id = ?", None)
+            .contains(&["title", "title"], "'; --")
+            .order_by("id")
+            .limit(5);
+        assert_eq!(
+            query.sql(),
+            "SELECT title FROM items WHERE (project_id = ?) AND (LOWER(title) LIKE ? ESCAPE '\\' OR LOWER(title) LIKE ? ESCAPE '\\') ORDER BY id LIMIT ?"
+        );
+        assert_eq!(query.params().len(), 4);
+        assert!(!query.sql().contains("--"));
+    }
+
+    #[test]
+    fn wildcards_match_themselves() {
+        let conn = table();
+        let find = |text: &str| titles(&conn, &Query::select("SELECT title FROM items").contains(&["title"], text).order_by("id"));
+        assert_eq!(find("%"), vec!["100% CPU"]);
+        assert_eq!(find("_"), vec!["snake_case name"]);
+        assert_eq!(find("\\"), vec!["back\\slash"]);
+        assert_eq!(find("o'brien's \"QUOTED\""), vec!["O'Brien's \"quoted\" bug"]);
+        assert_eq!(find("'); drop table items; --"), vec!["x'); DROP TABLE items; --"]);
+        assert!(find("%%").is_empty());
+    }
+
+    #[test]
+    fn fuzzed_input_never_breaks_the_query() {
+        let conn = table();
+        let pieces = ["'", "\"", "%", "_", "\\", ";", "--", "/*", "*/", " OR 1=1", "?", "?1", ":name", "')", "é", "\0", " ", "a"];
+        // A small linear congruential generator keeps the cases the same on every run
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            let mut input = String::new();
+            for _ in 0..(seed % 8) {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                input.push_str(pieces[(seed >> 33) as usize % pieces.len()]);
+            }
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let query = Query::select("SELECT title FROM items").filter_opt("project_id = ?", Some(1)).contains(&["title"], &input).limit(10);
+            let found = titles(&conn, &query);
+            let needle = input.replace('\0', "").to_lowercase();
+            assert!(found.iter().all(|title| title.to_lowercase().contains(&needle)), "{:?} matched {:?}", input, found);
+        }
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 6);
+    }
+}
@@ -0,0 +1,169 @@
+// FlowState Quick Add Module - Natural-language capture strings
+// "fix login crash !high @auth due friday #bug" -> a high-severity problem on the
+// auth component, tagged bug, with a todo for it due this Friday. Parsing never
+// writes; Database::quick_add creates the records and hands the interpretation back
+// for confirmation.
+
+use crate::database::Component;
+use crate::timezone;
+use chrono::{DateTime, FixedOffset};
+use chrono_english::{parse_date_string, Dialect};
+use serde::{Deserialize, Serialize};
+
+const PROBLEM_TAGS: &[&str] = &["bug", "problem", "issue"];
+const LEARNING_TAGS: &[&str] = &["learning", "til", "insight"];
+const LEARNING_CATEGORIES: &[&str] = &[
+    "pattern", "gotcha", "best_practice", "tool_tip", "architecture", "performance", "security", "other",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickAdd {
+    pub input: String,
+    pub kind: String, // todo, problem, learning
+    pub title: String,
+    pub priority: Option<String>, // Severity when kind is problem
+    pub component: Option<String>,
+    pub component_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub category: Option<String>, // Learnings only
+    pub due_phrase: Option<String>,
+    pub due_date: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Parse a capture string against the project's components.
+pub fn interpret(input: &str, now: DateTime<FixedOffset>, components: &[Component]) -> QuickAdd {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let mut title_words: Vec<&str> = Vec::new();
+    let mut priority = None;
+    let mut component: Option<String> = None;
+    let mut tags: Vec<String> = Vec::new();
+    let mut due_phrase = None;
+    let mut due_date = None;
+    let mut warnings = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+
+        if let Some(level) = token.strip_prefix('!') {
+            match parse_priority(level) {
+                Some(p) => priority = Some(p.to_string()),
+                None => {
+                    warnings.push(format!("Unknown priority '{}' kept in the title", token));
+                    title_words.push(token);
+                }
+            }
+        } else if let Some(name) = token.strip_prefix('@').filter(|n| !n.is_empty()) {
+            if let Some(previous) = component.replace(name.to_string()) {
+                warnings.push(format!("Component @{} replaced by @{}", previous, name));
+            }
+        } else if let Some(tag) = token.strip_prefix('#').filter(|t| !t.is_empty()) {
+            let tag = tag.to_lowercase();
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        } else if token.eq_ignore_ascii_case("due") && i + 1 < tokens.len() {
+            // The date phrase runs until the next marker; take the longest prefix that parses
+            let end = tokens[i + 1..]
+                .iter()
+                .position(|t| is_marker(t))
+                .map(|p| i + 1 + p)
+                .unwrap_or(tokens.len());
+            let parsed = (i + 1..end).rev().find_map(|stop| {
+                let phrase = tokens[i + 1..=stop].join(" ");
+                parse_date_string(&phrase, now, Dialect::Us).ok().map(|dt| (stop, phrase, dt))
+            });
+            match parsed {
+                Some((stop, phrase, dt)) => {
+                    due_date = Some(timezone::due_value(dt, timezone::mentions_time(&phrase)));
+                    due_phrase = Some(phrase);
+                    i = stop;
+                }
+                None => {
+                    warnings.push("Couldn't read a date after 'due'; kept it in the title".to_string());
+                    title_words.push(token);
+                }
+            }
+        } else {
+            title_words.push(token);
+        }
+        i += 1;
+    }
+
+    let kind = if tags.iter().any(|t| PROBLEM_TAGS.contains(&t.as_str())) {
+        "problem"
+    } else if tags.iter().any(|t| LEARNING_TAGS.contains(&t.as_str())) {
+        "learning"
+    } else {
+        "todo"
+    };
+
+    let component_id = component.as_deref().and_then(|name| {
+        let found = resolve_component(name, components).map(|c| c.id);
+        if found.is_none() {
+            warnings.push(format!("No component named '{}' in this project", name));
+        }
+        found
+    });
+
+    let category = match kind {
+        "learning" => tags.iter().find(|t| LEARNING_CATEGORIES.contains(&t.as_str())).cloned(),
+        _ => None,
+    };
+
+    match kind {
+        "problem" if component_id.is_none() => {
+            warnings.push("Problems need a component; add @component".to_string())
+        }
+        "learning" if priority.is_some() => {
+            warnings.push("Learnings have no priority; ignoring it".to_string())
+        }
+        "learning" if due_date.is_some() => {
+            warnings.push("Learnings have no due date; ignoring it".to_string())
+        }
+        "problem" if due_date.is_some() => {
+            warnings.push("Problems have no due date; it goes on a todo for the problem".to_string())
+        }
+        _ => {}
+    }
+
+    QuickAdd {
+        input: input.to_string(),
+        kind: kind.to_string(),
+        title: title_words.join(" "),
+        priority,
+        component,
+        component_id,
+        tags,
+        category,
+        due_phrase,
+        due_date,
+        warnings,
+    }
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn is_marker(token: &str) -> bool {
+    token.starts_with(['!', '@', '#'])
+}
+
+fn parse_priority(level: &str) -> Option<&'static str> {
+    match level.to_lowercase().as_str() {
+        "critical" | "crit" | "urgent" | "!!" => Some("critical"),
+        "high" | "hi" | "!" => Some("high"),
+        "medium" | "med" | "normal" => Some("medium"),
+        "low" | "lo" => Some("low"),
+        _ => None,
+    }
+}
+
+/// Match `@name` case-insensitively, treating '-' and '_' as spaces so `@login-flow` finds "Login Flow".
+fn resolve_component<'a>(name: &str, components: &'a [Component]) -> Option<&'a Component> {
+    let normalize = |s: &str| s.to_lowercase().replace(['-', '_'], " ");
+    let wanted = normalize(name);
+    components.iter().find(|c| normalize(&c.name) == wanted)
+}
@@ -0,0 +1,120 @@
+// FlowState Quick Open Module - Fuzzy ranking for the command palette
+// "authlog" finds "Auth: login loop" because its letters appear in order. Letters that start
+// words or follow each other count for more, a plain substring beats a scattered match, and on
+// top of how well the text matches: projects and components outrank problems and todos, which
+// outrank files; things opened lately get a frecency boost; finished things sink.
+
+use crate::database::QuickOpenCandidate;
+use serde::{Deserialize, Serialize};
+
+const TYPE_BONUS: &[(&str, f64)] = &[("project", 30.0), ("component", 20.0), ("problem", 10.0), ("todo", 10.0), ("attachment", 0.0)];
+const CLOSED_STATUSES: &[&str] = &["completed", "archived", "deprecated", "solved", "wont_fix", "done", "cancelled"];
+const CLOSED_PENALTY: f64 = 25.0;
+const MAX_FRECENCY_BONUS: f64 = 40.0;
+const FRECENCY_WEIGHT: f64 = 10.0; // Bonus per point of frecency, up to the maximum
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickOpenItem {
+    #[serde(flatten)]
+    pub target: QuickOpenCandidate,
+    pub score: f64,
+    pub matches: Vec<usize>, // Character positions in the title that matched, for highlighting
+}
+
+/// The SQL LIKE pattern that keeps titles holding the query's letters in order.
+pub fn like_pattern(query: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in query.chars().filter(|c| !c.is_whitespace()) {
+        pattern.push_str(&crate::query::escape_like(&c.to_string()));
+        pattern.push('%');
+    }
+    pattern
+}
+
+/// Score candidates against `query`, best first. An empty query ranks by type and frecency alone.
+pub fn rank(query: &str, candidates: Vec<QuickOpenCandidate>, limit: usize) -> Vec<QuickOpenItem> {
+    let mut items: Vec<QuickOpenItem> = candidates.into_iter()
+        .filter_map(|target| {
+            let (text_score, matches) = fuzzy_match(query, &target.title)?;
+            let type_bonus = TYPE_BONUS.iter().find(|(t, _)| *t == target.entity_type).map(|(_, b)| *b).unwrap_or_default();
+            let closed = target.status.as_deref().is_some_and(|s| CLOSED_STATUSES.contains(&s));
+            let score = text_score
+                + type_bonus
+                + (target.frecency * FRECENCY_WEIGHT).min(MAX_FRECENCY_BONUS)
+                - if closed { CLOSED_PENALTY } else { 0.0 };
+            Some(QuickOpenItem { target, score, matches })
+        })
+        .collect();
+    items.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.target.title.len().cmp(&b.target.title.len())));
+    items.truncate(limit);
+    items
+}
+
+/// How well `text` matches `query`, and which of its characters did, or None when the
+/// query's letters don't all appear in order. Case and spaces in the query are ignored.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(f64, Vec<usize>)> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let original: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = original.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    if query.is_empty() {
+        return Some((0.0, Vec::new()));
+    }
+    // Long titles that only just match shouldn't beat short ones
+    let length_penalty = (lower.len().saturating_sub(query.len()) as f64 * 0.5).min(20.0);
+
+    let substring = (0..=lower.len().saturating_sub(query.len())).find(|&start| lower[start..].starts_with(&query));
+    if let Some(start) = substring {
+        let mut score = 100.0;
+        if start == 0 {
+            score += if lower.len() == query.len() { 100.0 } else { 50.0 };
+        } else if word_start(&original, start) {
+            score += 25.0;
+        }
+        return Some((score - length_penalty, (start..start + query.len()).collect()));
+    }
+
+    let mut matches = Vec::with_capacity(query.len());
+    let mut from = 0;
+    for (n, q) in query.iter().enumerate() {
+        // Keep a run going; otherwise jump to a word start holding the letter, as long as the
+        // rest of the query still fits after it
+        let first = (from..lower.len()).find(|&i| lower[i] == *q)?;
+        let at = if matches.last().is_some_and(|last| first == last + 1) {
+            first
+        } else {
+            (first..lower.len())
+                .find(|&i| lower[i] == *q && word_start(&original, i) && in_order(&query[n + 1..], &lower[i + 1..]))
+                .unwrap_or(first)
+        };
+        matches.push(at);
+        from = at + 1;
+    }
+    let score: f64 = matches.iter().enumerate()
+        .map(|(n, &i)| {
+            let mut points = 1.0;
+            if n > 0 && matches[n - 1] + 1 == i {
+                points += 5.0;
+            }
+            if word_start(&original, i) {
+                points += 8.0;
+            }
+            points
+        })
+        .sum();
+    Some((score - length_penalty, matches))
+}
+
+/// Whether a word starts at `i`: the first character, one after a separator, or a capital
+/// after a lowercase letter (camelCase).
+fn word_start(text: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let (previous, current) = (text[i - 1], text[i]);
+    !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+}
+
+fn in_order(letters: &[char], text: &[char]) -> bool {
+    let mut text = text.iter();
+    letters.iter().all(|l| text.any(|c| c == l))
+}
@@ -0,0 +1,243 @@
+// FlowState Reports Module - Weekly review and standup Markdown
+// Both are written from the same activity summary (database::ReportActivity). The weekly
+// review looks back seven days; the standup covers the previous working day, so Monday's
+// reaches back to Friday.
+// v1.4: The retrospective sorts a sprint's signals into what went well, what didn't and what to
+// do about it, comparing its throughput with the period before.
+
+use crate::database::{ReportActivity, ReportItem, RetroActivity};
+use serde::Serialize;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Weekday};
+
+pub const KINDS: &[&str] = &["weekly_review", "standup"];
+pub const RETRO_PERIODS: &[&str] = &["week", "sprint", "month"];
+pub const RETRO_MIN_FAILURES: i64 = 3; // Failed attempts on one problem before it counts as a struggle
+const VELOCITY_SHIFT: f64 = 0.2;        // Throughput change, either way, worth mentioning
+
+/// Start of the period a report covers.
+pub fn period_start(kind: &str, now: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    let days = match (kind, now.weekday()) {
+        ("standup", Weekday::Mon) => 3,
+        ("standup", Weekday::Sun) => 2,
+        ("standup", _) => 1,
+        _ => 7,
+    };
+    now - Duration::days(days)
+}
+
+/// `scope` is the project name, or None for a report across every project.
+pub fn subject(kind: &str, scope: Option<&str>, now: DateTime<FixedOffset>) -> String {
+    let title = if kind == "standup" { "standup" } else { "weekly review" };
+    match scope {
+        Some(project) => format!("FlowState {} – {} – {}", title, project, now.format("%-d %b %Y")),
+        None => format!("FlowState {} – {}", title, now.format("%-d %b %Y")),
+    }
+}
+
+pub fn render(kind: &str, scope: Option<&str>, activity: &ReportActivity, now: DateTime<FixedOffset>) -> String {
+    if kind == "standup" {
+        standup(scope, activity, now)
+    } else {
+        weekly_review(scope, activity, now)
+    }
+}
+
+fn weekly_review(scope: Option<&str>, activity: &ReportActivity, now: DateTime<FixedOffset>) -> String {
+    let show_project = scope.is_none();
+    let mut out = format!("# Weekly review – {}\n\n", scope.unwrap_or("All projects"));
+    out.push_str(&format!(
+        "_{} to {}_\n\n",
+        period_start("weekly_review", now).format("%a %-d %b"),
+        now.format("%a %-d %b %Y"),
+    ));
+    out.push_str(&format!(
+        "**{} solved · {} new problems · {} todos done · {} learnings · {}**\n",
+        activity.solved.len(),
+        activity.opened.len(),
+        activity.completed_todos.len(),
+        activity.learnings.len(),
+        sessions(activity),
+    ));
+
+    section(&mut out, "Solved", &activity.solved, show_project, |item| {
+        item.detail.as_deref().and_then(|summary| summary.lines().next()).map(|summary| format!(" — {}", summary.trim()))
+    });
+    section(&mut out, "New problems", &activity.opened, show_project, parenthesized);
+    section(&mut out, "Completed todos", &activity.completed_todos, show_project, |_| None);
+    section(&mut out, "Learnings", &activity.learnings, show_project, parenthesized);
+    section(&mut out, "Still in progress", &activity.in_progress, show_project, parenthesized);
+    section(&mut out, "Blocked", &activity.blocked, show_project, parenthesized);
+    out
+}
+
+fn standup(scope: Option<&str>, activity: &ReportActivity, now: DateTime<FixedOffset>) -> String {
+    let show_project = scope.is_none();
+    let mut out = format!("# Standup – {}\n\n_{}_\n\n", scope.unwrap_or("All projects"), now.format("%A %-d %B %Y"));
+
+    let since = period_start("standup", now);
+    out.push_str(if now.weekday() == Weekday::Mon { "## Since Friday\n\n" } else { "## Yesterday\n\n" });
+    let done: Vec<String> = activity.solved.iter().map(|i| format!("- Solved: {}", line(i, show_project)))
+        .chain(activity.completed_todos.iter().map(|i| format!("- Done: {}", line(i, show_project))))
+        .chain(activity.learnings.iter().map(|i| format!("- Learned: {}", line(i, show_project))))
+        .collect();
+    if done.is_empty() {
+        out.push_str(&format!("- Nothing recorded since {}\n", since.format("%a %H:%M")));
+    } else {
+        out.push_str(&(done.join("\n") + "\n"));
+    }
+
+    out.push_str("\n## Today\n\n");
+    if activity.in_progress.is_empty() {
+        out.push_str("- No todos in progress\n");
+    }
+    for item in &activity.in_progress {
+        out.push_str(&format!("- {}{}\n", line(item, show_project), parenthesized(item).unwrap_or_default()));
+    }
+
+    out.push_str("\n## Blockers\n\n");
+    if activity.blocked.is_empty() {
+        out.push_str("- None\n");
+    }
+    for item in &activity.blocked {
+        out.push_str(&format!("- {}{}\n", line(item, show_project), parenthesized(item).unwrap_or_default()));
+    }
+    out
+}
+
+// ============================================================
+// RETROSPECTIVE
+// ============================================================
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct Retrospective {
+    pub went_well: Vec<String>,
+    pub went_poorly: Vec<String>,
+    pub action_items: Vec<String>,
+}
+
+/// Days a retro period covers; a sprint is two weeks.
+pub fn retro_days(period: &str) -> i64 {
+    match period {
+        "week" => 7,
+        "month" => 30,
+        _ => 14,
+    }
+}
+
+pub fn classify_retro(activity: &RetroActivity) -> Retrospective {
+    let mut retro = Retrospective::default();
+    let plural = |count: usize, noun: &str| format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" });
+
+    let (now, before) = (activity.solved + activity.todos_done, activity.previous_solved + activity.previous_todos_done);
+    let velocity = format!(
+        "{} solved and {} done, against {} and {} the period before",
+        plural(activity.solved as usize, "problem"),
+        plural(activity.todos_done as usize, "todo"),
+        activity.previous_solved,
+        activity.previous_todos_done,
+    );
+    if before > 0 && (now as f64) < before as f64 * (1.0 - VELOCITY_SHIFT) {
+        retro.went_poorly.push(format!("Throughput fell: {}", velocity));
+        retro.action_items.push("Look at what took time away from planned work this period".to_string());
+    } else if now > 0 && (before == 0 || now as f64 > before as f64 * (1.0 + VELOCITY_SHIFT)) {
+        retro.went_well.push(format!("Throughput rose: {}", velocity));
+    } else if now > 0 {
+        retro.went_well.push(format!("Steady throughput: {}", velocity));
+    }
+
+    for item in &activity.quick_solves {
+        retro.went_well.push(format!("Solved without a failed attempt: {}{}", line(item, false), parenthesized(item).unwrap_or_default()));
+    }
+    for item in &activity.verified_learnings {
+        retro.went_well.push(format!("Verified learning: {}", line(item, false)));
+    }
+
+    for item in &activity.struggles {
+        let attempts = item.detail.as_deref().unwrap_or("several");
+        retro.went_poorly.push(format!("{} took {} failed attempts", line(item, false), attempts));
+        retro.action_items.push(format!("Write up what finally worked (or didn't) on {}", line(item, false)));
+    }
+    if !activity.abandoned_todos.is_empty() {
+        for item in &activity.abandoned_todos {
+            retro.went_poorly.push(format!("Abandoned: {}{}", line(item, false), parenthesized(item).unwrap_or_default()));
+        }
+        retro.action_items.push(format!(
+            "Check why {} dropped and scope the next ones smaller",
+            if activity.abandoned_todos.len() == 1 { "a todo was".to_string() } else { format!("{} were", plural(activity.abandoned_todos.len(), "todo")) },
+        ));
+    }
+    for item in &activity.blocked {
+        retro.action_items.push(format!("Unblock {}{}", line(item, false), parenthesized(item).unwrap_or_default()));
+    }
+    retro
+}
+
+pub fn render_retrospective(project: &str, period: &str, activity: &RetroActivity, retro: &Retrospective) -> String {
+    let mut out = format!("# Retrospective – {}\n\n", project);
+    // The period ends just before `until`, so the last day shown is the one before a midnight end
+    let last_day = |time: &str| chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+        .map(|t| (t - Duration::seconds(1)).format("%-d %b %Y").to_string())
+        .unwrap_or_else(|_| time.to_string());
+    let first_day = |time: &str| chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+        .map(|t| t.format("%-d %b").to_string())
+        .unwrap_or_else(|_| time.to_string());
+    let label = match period {
+        "week" => "Week",
+        "month" => "Month",
+        _ => "Sprint",
+    };
+    out.push_str(&format!("_{}, {} to {}_\n", label, first_day(&activity.since), last_day(&activity.until)));
+    for (heading, lines, empty) in [
+        ("What went well", &retro.went_well, "Nothing stood out"),
+        ("What didn't go well", &retro.went_poorly, "Nothing stood out"),
+        ("Action items", &retro.action_items, "None"),
+    ] {
+        out.push_str(&format!("\n## {}\n\n", heading));
+        if lines.is_empty() {
+            out.push_str(&format!("- {}\n", empty));
+        }
+        for line in lines {
+            out.push_str(&format!("- {}\n", line));
+        }
+    }
+    out
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// A `## title` list; empty sections are left out.
+fn section(out: &mut String, title: &str, items: &[ReportItem], show_project: bool, suffix: impl Fn(&ReportItem) -> Option<String>) {
+    if items.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n## {}\n\n", title));
+    for item in items {
+        out.push_str(&format!("- {}{}\n", line(item, show_project), suffix(item).unwrap_or_default()));
+    }
+}
+
+fn line(item: &ReportItem, show_project: bool) -> String {
+    // Titles are single-line in a list; the rest of a long insight stays in FlowState
+    let title = item.title.lines().next().unwrap_or_default().trim();
+    if show_project {
+        format!("**{}** {}", item.project_name, title)
+    } else {
+        title.to_string()
+    }
+}
+
+fn parenthesized(item: &ReportItem) -> Option<String> {
+    item.detail.as_deref().map(|detail| format!(" ({})", detail.replace('_', " ")))
+}
+
+fn sessions(activity: &ReportActivity) -> String {
+    let (hours, minutes) = (activity.session_minutes / 60, activity.session_minutes % 60);
+    let noun = if activity.session_count == 1 { "session" } else { "sessions" };
+    match hours {
+        _ if activity.session_count == 0 => "no sessions".to_string(),
+        0 => format!("{}m in {} {}", minutes, activity.session_count, noun),
+        _ => format!("{}h {}m in {} {}", hours, minutes, activity.session_count, noun),
+    }
+}
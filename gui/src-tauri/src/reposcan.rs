@@ -0,0 +1,185 @@
+// FlowState Repo Scan Module - Propose components from how a codebase is laid out
+// Cargo and npm workspace members become components, with the folders under their src/ as
+// children, and so do the folders under the repository's own src/. Each suggestion keeps the
+// path it came from, which becomes its component path once the user accepts it.
+
+use crate::dirwatch;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const MAX_MEMBER_DEPTH: usize = 4; // How deep workspace member globs are followed
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SuggestedComponent {
+    pub name: String,
+    pub path: String,   // Relative to the repository root, `/`-separated
+    pub source: String, // cargo, npm or directory
+    pub description: Option<String>,
+    #[serde(default)]
+    pub existing_component_id: Option<i64>, // The project already has a component by this name
+    #[serde(default)]
+    pub children: Vec<SuggestedComponent>,
+}
+
+/// The component hierarchy suggested for the repository at `root`: Cargo workspace members,
+/// then npm workspace members, then the folders under src/.
+pub fn suggest(root: &Path) -> Result<Vec<SuggestedComponent>, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let mut suggestions: Vec<SuggestedComponent> = Vec::new();
+    let packages = cargo_members(root).into_iter()
+        .map(|path| cargo_package(root, &path))
+        .chain(npm_members(root).into_iter().map(|path| npm_package(root, &path)))
+        .chain(source_dirs(root, "", None));
+    for suggestion in packages {
+        if !suggestions.iter().any(|s| s.path == suggestion.path) {
+            suggestions.push(suggestion);
+        }
+    }
+    Ok(suggestions)
+}
+
+fn cargo_members(root: &Path) -> Vec<String> {
+    let Some(manifest) = read_toml(&root.join("Cargo.toml")) else { return Vec::new() };
+    let Some(workspace) = manifest.get("workspace") else { return Vec::new() };
+    let strings = |key: &str| -> Vec<String> {
+        workspace.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+    members(root, &strings("members"), &strings("exclude"), "Cargo.toml")
+}
+
+/// `workspaces` is a list of globs, or an object holding one under `packages` (Yarn); a
+/// leading `!` excludes.
+fn npm_members(root: &Path) -> Vec<String> {
+    let Some(manifest) = read_json(&root.join("package.json")) else { return Vec::new() };
+    let patterns = match manifest.get("workspaces") {
+        Some(serde_json::Value::Object(object)) => object.get("packages").and_then(|p| p.as_array()).cloned(),
+        Some(value) => value.as_array().cloned(),
+        None => None,
+    };
+    let patterns: Vec<String> = patterns.unwrap_or_default().iter().filter_map(|v| v.as_str().map(str::to_string)).collect();
+    let (excluded, included): (Vec<String>, Vec<String>) = patterns.into_iter().partition(|p| p.starts_with('!'));
+    let excluded: Vec<String> = excluded.iter().map(|p| p.trim_start_matches('!').to_string()).collect();
+    members(root, &included, &excluded, "package.json")
+}
+
+/// Directories under `root` matching one of `include` and none of `exclude` that hold a
+/// `manifest`, sorted.
+fn members(root: &Path, include: &[String], exclude: &[String], manifest: &str) -> Vec<String> {
+    let clean = |patterns: &[String]| -> Vec<String> {
+        patterns.iter().map(|p| p.trim().trim_start_matches("./").trim_end_matches('/').to_string()).collect()
+    };
+    let (include, exclude) = (clean(include), clean(exclude));
+    if include.is_empty() {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    let mut pending = vec![(String::new(), 0)];
+    while let Some((relative, depth)) = pending.pop() {
+        for name in subdirectories(&root.join(&relative)) {
+            let path = join(&relative, &name);
+            if include.iter().any(|p| dirwatch::glob_matches(p, &path))
+                && !exclude.iter().any(|p| dirwatch::glob_matches(p, &path))
+                && root.join(&path).join(manifest).is_file()
+            {
+                found.push(path.clone());
+            }
+            if depth + 1 < MAX_MEMBER_DEPTH {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+fn cargo_package(root: &Path, path: &str) -> SuggestedComponent {
+    let package = read_toml(&root.join(path).join("Cargo.toml")).and_then(|m| m.get("package").cloned());
+    let field = |key: &str| package.as_ref().and_then(|p| p.get(key)).and_then(|v| v.as_str()).map(str::to_string);
+    let name = field("name").unwrap_or_else(|| last_segment(path));
+    SuggestedComponent {
+        children: source_dirs(root, path, Some(&name)),
+        description: field("description"),
+        name,
+        path: path.to_string(),
+        source: "cargo".to_string(),
+        existing_component_id: None,
+    }
+}
+
+fn npm_package(root: &Path, path: &str) -> SuggestedComponent {
+    let manifest = read_json(&root.join(path).join("package.json"));
+    let field = |key: &str| manifest.as_ref().and_then(|m| m.get(key)).and_then(|v| v.as_str()).map(str::to_string);
+    // "@acme/ui" is the ui package to anyone working in the acme repository
+    let name = field("name")
+        .map(|n| n.rsplit('/').next().unwrap_or_default().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| last_segment(path));
+    SuggestedComponent {
+        children: source_dirs(root, path, Some(&name)),
+        description: field("description"),
+        name,
+        path: path.to_string(),
+        source: "npm".to_string(),
+        existing_component_id: None,
+    }
+}
+
+/// The folders under `path`/src. Under a package they're named "package/folder", since
+/// component names are unique within a project.
+fn source_dirs(root: &Path, path: &str, package: Option<&str>) -> Vec<SuggestedComponent> {
+    let src = join(path, "src");
+    subdirectories(&root.join(&src)).into_iter()
+        .map(|name| SuggestedComponent {
+            name: match package {
+                Some(package) => format!("{}/{}", package, name),
+                None => name.clone(),
+            },
+            path: join(&src, &name),
+            source: "directory".to_string(),
+            description: None,
+            existing_component_id: None,
+            children: Vec::new(),
+        })
+        .collect()
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// Names of the directories in `dir`, sorted, leaving out hidden, VCS and build folders.
+fn subdirectories(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut names: Vec<String> = entries.flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') && !dirwatch::SKIPPED_DIRS.contains(&name.as_str()))
+        .collect();
+    names.sort();
+    names
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+fn last_segment(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
@@ -0,0 +1,197 @@
+// FlowState Secrets Module - Keeping credentials out of what a sync pushes
+// The data folder goes to a remote that may be shared. Credential settings are kept in a file
+// of their own in the machine's local folder, never in flowstate.db, and the sync preview flags
+// anything else that looks like a credential. The checks are plain string tests rather than
+// regexes: known token prefixes, private key blocks, .env files, and `name = value` lines whose
+// name says key, token, secret or password.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Settings holding credentials. They're read and written through `get` and `set`, which keep
+/// them in STORE_FILE; the settings table never holds them.
+pub const SECRET_SETTINGS: &[&str] = &["notion_token", "confluence_token", "smtp_password", "automation_token", "llm_api_key", "embedding_api_key", "github_token", "gitlab_token", "lan_sync_key", "sentry_client_secret", "sentry_token"];
+
+const STORE_FILE: &str = "secrets.json";
+
+/// Larger files are listed but not read.
+pub const MAX_SCAN_BYTES: u64 = 1024 * 1024;
+
+const SECRET_NAMES: &[&str] = &["key", "token", "secret", "password", "passwd"];
+const PLACEHOLDERS: &[&str] = &["<", "${", "{{", "your", "xxx", "changeme", "example", "placeholder", "redacted", "none", "null", "true", "false"];
+
+pub fn is_secret(key: &str) -> bool {
+    SECRET_SETTINGS.contains(&key)
+}
+
+fn store_path() -> PathBuf {
+    crate::platform::local_dir().join(STORE_FILE)
+}
+
+fn load() -> BTreeMap<String, String> {
+    std::fs::read_to_string(store_path()).ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// A stored credential, or None when it isn't set.
+pub fn get(key: &str) -> Option<String> {
+    load().remove(key).filter(|value| !value.trim().is_empty())
+}
+
+/// Store a credential, or forget it with None. Values may come JSON-encoded, the way settings
+/// are saved, or plain. The file is readable by this user only.
+pub fn set(key: &str, value: Option<&str>) -> Result<(), String> {
+    let mut secrets = load();
+    match value.filter(|v| !v.trim().is_empty()) {
+        Some(value) => {
+            let value = match serde_json::from_str::<serde_json::Value>(value) {
+                Ok(serde_json::Value::String(inner)) => inner,
+                _ => value.to_string(),
+            };
+            secrets.insert(key.to_string(), value);
+        }
+        None => {
+            secrets.remove(key);
+        }
+    }
+    let path = store_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(&secrets).map_err(|e| e.to_string())?;
+    let partial = path.with_extension("json.partial");
+    write_private(&partial, text.as_bytes()).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, &path).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn write_private(path: &std::path::Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = std::fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    file.write_all(bytes)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &std::path::Path, bytes: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, bytes)
+}
+
+/// Move credentials older versions saved as settings out of the database. One already in
+/// the store wins; the database copy is dropped either way.
+pub fn migrate(db: &crate::database::Database) -> Result<(), String> {
+    for key in SECRET_SETTINGS {
+        let Some(raw) = db.get_setting(key).map_err(|e| e.to_string())? else { continue };
+        if get(key).is_none() {
+            set(key, Some(&raw))?;
+        }
+        db.delete_setting(key).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Finding {
+    pub path: String,
+    pub line: Option<usize>,
+    pub kind: String,
+    pub excerpt: String, // The match with its value cut short
+}
+
+/// Everything in one file that looks like a credential, at most one finding per line.
+pub fn scan_file(path: &str, content: &[u8]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if let Some(kind) = sensitive_file_kind(name) {
+        findings.push(Finding { path: path.to_string(), line: None, kind: kind.to_string(), excerpt: name.to_string() });
+    }
+    // Binary files (images, the database) have no lines to read
+    if content.iter().take(8000).any(|b| *b == 0) {
+        return findings;
+    }
+    let text = String::from_utf8_lossy(content);
+    for (number, line) in text.lines().enumerate() {
+        if let Some((kind, excerpt)) = scan_line(line) {
+            findings.push(Finding { path: path.to_string(), line: Some(number + 1), kind: kind.to_string(), excerpt });
+        }
+    }
+    findings
+}
+
+fn sensitive_file_kind(name: &str) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    if (lower == ".env" || lower.starts_with(".env.")) && ![".example", ".sample", ".template"].iter().any(|s| lower.ends_with(s)) {
+        return Some("env_file");
+    }
+    if [".pem", ".key", ".p12", ".pfx"].iter().any(|ext| lower.ends_with(ext)) || matches!(lower.as_str(), "id_rsa" | "id_ed25519" | "id_ecdsa") {
+        return Some("private_key_file");
+    }
+    None
+}
+
+fn scan_line(line: &str) -> Option<(&'static str, String)> {
+    if line.contains("-----BEGIN") && line.contains("PRIVATE KEY-----") {
+        return Some(("private_key", "-----BEGIN … PRIVATE KEY-----".to_string()));
+    }
+    for word in line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-')) {
+        if let Some(kind) = token_kind(word) {
+            return Some((kind, redact(word)));
+        }
+    }
+    assignment(line).map(|(name, value)| ("assignment", format!("{}={}", name, redact(value))))
+}
+
+/// Tokens recognisable by their prefix and length.
+fn token_kind(word: &str) -> Option<&'static str> {
+    let rest_is = |prefix: &str, min: usize| {
+        word.len() >= prefix.len() + min && word[prefix.len()..].chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if word.len() == 20 && word.starts_with("AKIA") && word.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+        return Some("aws_access_key");
+    }
+    if ["ghp_", "gho_", "ghs_", "ghu_", "ghr_"].iter().any(|p| word.starts_with(p) && rest_is(p, 30)) || (word.starts_with("github_pat_") && rest_is("github_pat_", 30)) {
+        return Some("github_token");
+    }
+    if word.starts_with("glpat-") && rest_is("glpat-", 20) {
+        return Some("gitlab_token");
+    }
+    if ["xoxb-", "xoxp-", "xoxa-", "xoxr-", "xoxs-"].iter().any(|p| word.starts_with(p) && rest_is(p, 15)) {
+        return Some("slack_token");
+    }
+    if word.starts_with("sk-ant-") && rest_is("sk-ant-", 30) {
+        return Some("anthropic_key");
+    }
+    if word.starts_with("sk-") && rest_is("sk-", 20) {
+        return Some("openai_key");
+    }
+    if word.len() == 39 && word.starts_with("AIza") {
+        return Some("google_api_key");
+    }
+    if ["secret_", "ntn_"].iter().any(|p| word.starts_with(p) && rest_is(p, 36)) {
+        return Some("notion_token");
+    }
+    None
+}
+
+/// `API_KEY=…`, `"password": "…"`, `token: …` with a value that isn't obviously a placeholder.
+fn assignment(line: &str) -> Option<(&str, &str)> {
+    let at = line.find(['=', ':'])?;
+    let name = line[..at].trim().trim_matches(|c| c == '"' || c == '\'').trim_start_matches("export ").trim();
+    let lower = name.to_ascii_lowercase();
+    if name.is_empty() || name.contains(' ') || !SECRET_NAMES.iter().any(|s| lower.contains(s)) {
+        return None;
+    }
+    let value = line[at + 1..].trim().trim_end_matches(',').trim_matches(|c| c == '"' || c == '\'').trim();
+    let value_lower = value.to_ascii_lowercase();
+    if value.len() < 8 || value.contains(' ') || value.chars().all(|c| c.is_ascii_digit()) || PLACEHOLDERS.iter().any(|p| value_lower.starts_with(p)) {
+        return None;
+    }
+    Some((name, value))
+}
+
+fn redact(value: &str) -> String {
+    let shown: String = value.chars().take(4).collect();
+    format!("{}…", shown)
+}
@@ -0,0 +1,170 @@
+// FlowState Sections Module - Structural index of markdown and notebook attachments
+// Every heading becomes a content location spanning its section, described by its heading
+// path ("Setup > Caching") so a plain search finds the right place without an LLM.
+// Markdown locations are line ranges; notebook locations are cell ranges (1-based).
+
+use crate::database::IndexedLocation;
+
+pub const INDEXER: &str = "sections";
+
+const SNIPPET_CHARS: usize = 300;
+
+struct Heading {
+    level: usize,
+    text: String,
+    line: usize, // 0-based
+    body: usize, // First line after the heading (past a setext underline)
+}
+
+/// Sections of a markdown document, by line.
+pub fn markdown_sections(text: &str) -> Vec<IndexedLocation> {
+    let lines: Vec<&str> = text.lines().collect();
+    let headings = headings(&lines);
+    let mut path: Vec<&Heading> = Vec::new();
+
+    headings
+        .iter()
+        .enumerate()
+        .map(|(i, heading)| {
+            path.retain(|h| h.level < heading.level);
+            path.push(heading);
+            // A section runs until the next heading at the same or a higher level
+            let end = headings[i + 1..]
+                .iter()
+                .find(|h| h.level <= heading.level)
+                .map(|h| h.line)
+                .unwrap_or(lines.len());
+            IndexedLocation {
+                description: path.iter().map(|h| h.text.as_str()).collect::<Vec<_>>().join(" > "),
+                category: "reference".to_string(),
+                location_type: "line".to_string(),
+                start_location: (heading.line + 1).to_string(),
+                end_location: Some(end.max(heading.line + 1).to_string()),
+                // The snippet is the heading's own text, before any subsection
+                snippet: snippet(lines.get(heading.body..headings.get(i + 1).map_or(end, |h| h.line)).unwrap_or_default()),
+            }
+        })
+        .collect()
+}
+
+/// Sections of a Jupyter notebook, by cell: one per markdown heading (spanning the cells
+/// under it) and one per code cell. None if the file isn't a notebook.
+pub fn notebook_sections(json: &str) -> Option<Vec<IndexedLocation>> {
+    let notebook: serde_json::Value = serde_json::from_str(json).ok()?;
+    let cells = notebook["cells"].as_array()?;
+    let sources: Vec<(String, String)> = cells
+        .iter()
+        .map(|cell| {
+            let source = match &cell["source"] {
+                serde_json::Value::Array(parts) => parts.iter().filter_map(|p| p.as_str()).collect::<String>(),
+                other => other.as_str().unwrap_or_default().to_string(),
+            };
+            (cell["cell_type"].as_str().unwrap_or_default().to_string(), source)
+        })
+        .collect();
+
+    // Headings of every markdown cell, tagged with the cell they're in
+    let mut cell_headings: Vec<(usize, Heading)> = Vec::new();
+    for (index, (kind, source)) in sources.iter().enumerate() {
+        if kind == "markdown" {
+            let lines: Vec<&str> = source.lines().collect();
+            cell_headings.extend(headings(&lines).into_iter().map(|h| (index, h)));
+        }
+    }
+
+    let mut locations = Vec::new();
+    let mut path: Vec<&Heading> = Vec::new();
+    for (i, (cell, heading)) in cell_headings.iter().enumerate() {
+        path.retain(|h| h.level < heading.level);
+        path.push(heading);
+        let end_cell = cell_headings[i + 1..]
+            .iter()
+            .find(|(_, h)| h.level <= heading.level)
+            .map(|(c, _)| if c > cell { c - 1 } else { *cell })
+            .unwrap_or(sources.len() - 1);
+        locations.push(IndexedLocation {
+            description: path.iter().map(|h| h.text.as_str()).collect::<Vec<_>>().join(" > "),
+            category: "reference".to_string(),
+            location_type: "section".to_string(),
+            start_location: (cell + 1).to_string(),
+            end_location: Some((end_cell + 1).to_string()),
+            snippet: snippet(&sources[*cell].1.lines().skip(heading.body).collect::<Vec<_>>()),
+        });
+    }
+
+    for (index, (kind, source)) in sources.iter().enumerate() {
+        let Some(first_line) = source.lines().map(str::trim).find(|l| !l.is_empty()) else { continue };
+        if kind == "code" {
+            locations.push(IndexedLocation {
+                description: format!("Cell {}: {}", index + 1, first_line),
+                category: "code".to_string(),
+                location_type: "section".to_string(),
+                start_location: (index + 1).to_string(),
+                end_location: None,
+                snippet: snippet(&source.lines().collect::<Vec<_>>()),
+            });
+        }
+    }
+    Some(locations)
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// ATX (`## Title`) and setext (`Title` over `===`/`---`) headings, skipping fenced code.
+fn headings(lines: &[&str]) -> Vec<Heading> {
+    let mut found = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with([' ', '\t']) {
+            let text = trimmed[hashes..].trim().trim_end_matches('#').trim();
+            if !text.is_empty() {
+                found.push(Heading { level: hashes, text: text.to_string(), line: i, body: i + 1 });
+            }
+            continue;
+        }
+
+        let underline = line.trim();
+        let previous = if i > 0 { lines[i - 1].trim() } else { "" };
+        let setext_level = if !underline.is_empty() && underline.chars().all(|c| c == '=') {
+            Some(1)
+        } else if underline.len() >= 2 && underline.chars().all(|c| c == '-') {
+            Some(2)
+        } else {
+            None
+        };
+        if let Some(level) = setext_level {
+            let already_heading = found.last().is_some_and(|h: &Heading| h.line + 1 == i);
+            if !previous.is_empty() && !previous.starts_with(['#', '-', '*', '>']) && !already_heading {
+                found.push(Heading { level, text: previous.to_string(), line: i - 1, body: i + 1 });
+            }
+        }
+    }
+    found
+}
+
+/// The first few non-blank lines of a section body.
+fn snippet(lines: &[&str]) -> Option<String> {
+    let text = lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).take(3).collect::<Vec<_>>().join("\n");
+    if text.is_empty() {
+        return None;
+    }
+    Some(match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text,
+    })
+}
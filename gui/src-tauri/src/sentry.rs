@@ -0,0 +1,181 @@
+// FlowState Sentry Module - Sentry issues as problems, through the local API
+// A Sentry internal integration posts issue webhooks (created, resolved, archived,
+// unresolved) to WEBHOOK_PATH, signed with its client secret. Each issue becomes one problem
+// on the component linked to its Sentry project and follows the issue's status after that.
+// Issue payloads carry no stack trace: error and alert webhooks do, and with an API token the
+// issue's latest event is fetched instead. Sentry can't reach 127.0.0.1 by itself, so the
+// webhook URL is a tunnel to the local API that keeps the Host header local.
+
+use serde_json::Value;
+use std::time::Duration;
+
+pub const WEBHOOK_PATH: &str = "/webhooks/sentry";
+pub const SENTRY_URL: &str = "https://sentry.io";
+const MAX_FRAMES: usize = 50;
+const MAX_WEBHOOK_AGE_SECONDS: i64 = 300; // The header isn't signed; replays are caught by the delivery log
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub id: String,
+    pub short_id: Option<String>, // FLOW-1A
+    pub title: String,
+    pub culprit: Option<String>,
+    pub level: Option<String>,
+    pub status: Option<String>, // unresolved, resolved, ignored or archived
+    pub url: Option<String>,
+    pub project_slug: Option<String>,
+    pub first_seen: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Webhook {
+    Issue { action: String, issue: Issue },
+    /// An error or alert webhook: one event of an issue, with its stack trace.
+    Event { issue_id: String, event_id: Option<String>, stack_trace: Option<String> },
+    /// Resources that don't touch problems (installation, comment, metric_alert).
+    Other(String),
+}
+
+/// Check the Sentry-Hook-Signature header: HMAC-SHA256 of the body under the client secret.
+pub fn verify_signature(secret: &str, body: &[u8], signature: Option<&str>) -> Result<(), String> {
+    let expected = crate::lansync::sign(secret, body);
+    crate::automation::check_token(signature.map(str::trim), &expected)
+        .map_err(|_| "Missing or wrong Sentry-Hook-Signature".to_string())
+}
+
+/// Check the Sentry-Hook-Timestamp header (Unix seconds): present and within a few minutes of now.
+pub fn check_timestamp(timestamp: Option<&str>) -> Result<(), String> {
+    let sent = timestamp
+        .and_then(|t| t.trim().parse::<f64>().ok())
+        .ok_or("Missing or unreadable Sentry-Hook-Timestamp")?;
+    if (chrono::Utc::now().timestamp() - sent as i64).abs() > MAX_WEBHOOK_AGE_SECONDS {
+        return Err("The webhook is too old, or the two clocks disagree".to_string());
+    }
+    Ok(())
+}
+
+/// Read a webhook by its Sentry-Hook-Resource header.
+pub fn parse_webhook(resource: &str, body: &Value) -> Result<Webhook, String> {
+    let data = &body["data"];
+    match resource {
+        "issue" => Ok(Webhook::Issue {
+            action: body["action"].as_str().unwrap_or_default().to_string(),
+            issue: parse_issue(&data["issue"])?,
+        }),
+        "error" | "event_alert" => {
+            let event = if resource == "error" { &data["error"] } else { &data["event"] };
+            let issue_id = text(&event["issue_id"]).ok_or("The Sentry event has no issue_id")?;
+            Ok(Webhook::Event { issue_id, event_id: text(&event["event_id"]), stack_trace: stack_trace(event) })
+        }
+        other => Ok(Webhook::Other(other.to_string())),
+    }
+}
+
+/// The problem status an issue action (or, failing that, the issue's status) means.
+pub fn problem_status(action: &str, status: Option<&str>) -> Option<&'static str> {
+    match (action, status) {
+        ("resolved", _) | (_, Some("resolved")) => Some("solved"),
+        ("archived" | "ignored", _) | (_, Some("ignored" | "archived")) => Some("wont_fix"),
+        ("unresolved", _) => Some("open"),
+        _ => None,
+    }
+}
+
+impl Issue {
+    pub fn severity(&self) -> &'static str {
+        match self.level.as_deref() {
+            Some("fatal") => "critical",
+            Some("error") => "high",
+            Some("info" | "debug") => "low",
+            _ => "medium",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let mut lines = vec![format!("Reported by Sentry as {}.", self.short_id.as_deref().unwrap_or(&self.id))];
+        if let Some(culprit) = self.culprit.as_deref().filter(|c| !c.is_empty()) {
+            lines.push(format!("Culprit: {}", culprit));
+        }
+        if let Some(first_seen) = &self.first_seen {
+            lines.push(format!("First seen: {}", first_seen));
+        }
+        if let Some(url) = &self.url {
+            lines.push(url.clone());
+        }
+        lines.join("\n")
+    }
+}
+
+/// The exceptions of an event as text, most recent call first, or None without any frames.
+/// Takes webhook events (`exception.values`) and API events (an `exception` entry).
+pub fn stack_trace(event: &Value) -> Option<String> {
+    let values = event["exception"]["values"].as_array().or_else(|| {
+        event["entries"].as_array()?
+            .iter()
+            .find(|e| e["type"] == "exception")?["data"]["values"]
+            .as_array()
+    })?;
+    let mut trace = Vec::new();
+    for exception in values {
+        let kind = exception["type"].as_str().unwrap_or("Error");
+        let mut block = match exception["value"].as_str().filter(|v| !v.is_empty()) {
+            Some(value) => format!("{}: {}", kind, value),
+            None => kind.to_string(),
+        };
+        let frames = exception["stacktrace"]["frames"].as_array().map(Vec::as_slice).unwrap_or_default();
+        for frame in frames.iter().rev().take(MAX_FRAMES) {
+            let file = frame["filename"].as_str().or_else(|| frame["absPath"].as_str()).unwrap_or("?");
+            let line = frame["lineno"].as_i64().or_else(|| frame["lineNo"].as_i64());
+            let location = match line {
+                Some(line) => format!("{}:{}", file, line),
+                None => file.to_string(),
+            };
+            block.push_str(&format!("\n  at {} ({})", frame["function"].as_str().unwrap_or("?"), location));
+        }
+        trace.push(block);
+    }
+    (!trace.is_empty() && trace.iter().any(|b| b.contains("\n  at "))).then(|| trace.join("\n\n"))
+}
+
+/// The latest event of an issue through the Sentry API; `base_url` is sentry.io or a
+/// self-hosted Sentry.
+pub fn fetch_latest_event(base_url: &str, token: &str, issue_id: &str) -> Result<Value, String> {
+    let url = format!("{}/api/0/issues/{}/events/latest/", base_url.trim_end_matches('/'), issue_id);
+    ureq::get(&url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .timeout(Duration::from_secs(30))
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("Sentry API error {} for issue {}", code, issue_id),
+            ureq::Error::Transport(t) => format!("Failed to reach Sentry: {}", t),
+        })?
+        .into_json()
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn parse_issue(issue: &Value) -> Result<Issue, String> {
+    Ok(Issue {
+        id: text(&issue["id"]).ok_or("The Sentry issue has no id")?,
+        short_id: text(&issue["shortId"]),
+        title: text(&issue["title"]).unwrap_or_else(|| "Sentry issue".to_string()),
+        culprit: text(&issue["culprit"]),
+        level: text(&issue["level"]),
+        status: text(&issue["status"]),
+        url: text(&issue["web_url"]).or_else(|| text(&issue["permalink"])),
+        project_slug: text(&issue["project"]["slug"]),
+        first_seen: text(&issue["firstSeen"]),
+    })
+}
+
+/// Sentry sends ids as strings or numbers depending on the payload.
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,165 @@
+// FlowState Shell History Module - Read terminal history files with their timestamps
+// Understands zsh extended history (`: 1700000000:0;cmd`), bash with HISTTIMEFORMAT set
+// (`#1700000000` before each command) and fish's YAML-ish history. Commands without a
+// timestamp can't be placed in a time window, so they're dropped.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+pub const SHELLS: &[&str] = &["zsh", "bash", "fish"];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryEntry {
+    pub command: String,
+    pub timestamp: i64, // Unix seconds
+    pub executed_at: String, // UTC, in the database's timestamp format
+}
+
+/// The user's login shell if it's one we can read, else zsh.
+pub fn default_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .and_then(|s| s.rsplit('/').next().map(|n| n.to_string()))
+        .filter(|n| SHELLS.contains(&n.as_str()))
+        .unwrap_or_else(|| "zsh".to_string())
+}
+
+/// Where a shell keeps its history. HISTFILE, when exported, is the login shell's, so it's
+/// only honoured for that shell; zsh otherwise looks in ZDOTDIR.
+pub fn history_path(shell: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let histfile = std::env::var_os("HISTFILE")
+        .filter(|f| !f.is_empty() && default_shell() == shell)
+        .map(PathBuf::from);
+    match shell {
+        "zsh" => histfile.or_else(|| Some(
+            std::env::var_os("ZDOTDIR")
+                .filter(|d| !d.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or(home)
+                .join(".zsh_history"),
+        )),
+        "bash" => histfile.or_else(|| Some(home.join(".bash_history"))),
+        "fish" => Some(
+            std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".local").join("share"))
+                .join("fish")
+                .join("fish_history"),
+        ),
+        _ => None,
+    }
+}
+
+/// Timestamped commands of a history file, oldest first, with immediate repeats collapsed.
+pub fn parse(shell: &str, bytes: &[u8]) -> Vec<HistoryEntry> {
+    let text = if shell == "zsh" { String::from_utf8_lossy(&unmetafy(bytes)).to_string() } else { String::from_utf8_lossy(bytes).to_string() };
+    let raw = match shell {
+        "zsh" => parse_zsh(&text),
+        "bash" => parse_bash(&text),
+        "fish" => parse_fish(&text),
+        _ => Vec::new(),
+    };
+
+    let mut entries: Vec<HistoryEntry> = Vec::new();
+    for (timestamp, command) in raw {
+        let command = command.trim().to_string();
+        if command.is_empty() || entries.last().is_some_and(|e| e.command == command) {
+            continue;
+        }
+        let Some(at) = Utc.timestamp_opt(timestamp, 0).single() else { continue };
+        entries.push(HistoryEntry { command, timestamp, executed_at: at.format("%Y-%m-%d %H:%M:%S").to_string() });
+    }
+    entries.sort_by_key(|e| e.timestamp);
+    entries
+}
+
+/// RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC, as stored in the database) or a bare date.
+pub fn parse_time(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Some(at.timestamp());
+    }
+    if let Ok(at) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+        return Some(at.and_utc().timestamp());
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|at| at.and_utc().timestamp())
+}
+
+// ============================================================
+// FORMATS
+// ============================================================
+
+/// `: <start>:<elapsed>;<command>`; multi-line commands continue with a trailing backslash.
+fn parse_zsh(text: &str) -> Vec<(i64, String)> {
+    let mut entries: Vec<(i64, String)> = Vec::new();
+    let mut continuing = false;
+    for line in text.lines() {
+        if continuing {
+            if let Some((_, command)) = entries.last_mut() {
+                command.push('\n');
+                command.push_str(line.strip_suffix('\\').unwrap_or(line));
+            }
+            continuing = line.ends_with('\\');
+            continue;
+        }
+        let Some(rest) = line.strip_prefix(": ") else { continue };
+        let Some((meta, command)) = rest.split_once(';') else { continue };
+        let Some(Ok(timestamp)) = meta.split(':').next().map(|t| t.trim().parse::<i64>()) else { continue };
+        continuing = command.ends_with('\\');
+        entries.push((timestamp, command.strip_suffix('\\').unwrap_or(command).to_string()));
+    }
+    entries
+}
+
+/// `#<timestamp>` comment lines (written when HISTTIMEFORMAT is set) before each command.
+fn parse_bash(text: &str) -> Vec<(i64, String)> {
+    let mut entries = Vec::new();
+    let mut timestamp: Option<i64> = None;
+    for line in text.lines() {
+        if let Some(parsed) = line.strip_prefix('#').and_then(|t| t.trim().parse::<i64>().ok()) {
+            timestamp = Some(parsed);
+        } else if let Some(at) = timestamp.take() {
+            entries.push((at, line.to_string()));
+        }
+    }
+    entries
+}
+
+/// `- cmd: <command>` followed by `  when: <timestamp>` (and optional `paths:`).
+fn parse_fish(text: &str) -> Vec<(i64, String)> {
+    let mut entries = Vec::new();
+    let mut command: Option<String> = None;
+    for line in text.lines() {
+        if let Some(cmd) = line.strip_prefix("- cmd: ") {
+            // fish escapes newlines and backslashes in commands
+            command = Some(cmd.replace("\\n", "\n").replace("\\\\", "\\"));
+        } else if let Some(when) = line.trim_start().strip_prefix("when: ") {
+            if let (Some(cmd), Ok(at)) = (command.take(), when.trim().parse::<i64>()) {
+                entries.push((at, cmd));
+            }
+        }
+    }
+    entries
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// zsh stores bytes 0x83-0x9f and some others "metafied": 0x83 followed by the byte xor 32.
+fn unmetafy(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&b) = iter.next() {
+        if b == 0x83 {
+            if let Some(&next) = iter.next() {
+                out.push(next ^ 32);
+            }
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
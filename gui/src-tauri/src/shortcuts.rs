@@ -0,0 +1,139 @@
+// FlowState Shortcuts Module - Configurable accelerators for the native menu
+// Defaults mirror the original hardcoded menu. Overrides live in the `shortcuts`
+// settings category as `shortcut.<action>`; an empty override turns a shortcut off.
+
+use std::collections::BTreeMap;
+
+pub const SETTINGS_CATEGORY: &str = "shortcuts";
+pub const KEY_PREFIX: &str = "shortcut.";
+
+/// Every custom menu action, with its default accelerator ("" for none).
+pub const DEFAULT_SHORTCUTS: &[(&str, &str)] = &[
+    // File
+    ("new_project", "CmdOrCtrl+N"),
+    ("open_project", "CmdOrCtrl+O"),
+    ("import_file", "CmdOrCtrl+I"),
+    ("import_extract", "CmdOrCtrl+Shift+I"),
+    ("export_project", "CmdOrCtrl+E"),
+    ("export_markdown", "CmdOrCtrl+Shift+E"),
+    ("sync_now", "CmdOrCtrl+S"),
+    ("sync_settings", ""),
+    // Edit
+    ("find", "CmdOrCtrl+F"),
+    ("find_in_files", "CmdOrCtrl+Shift+F"),
+    ("quick_capture", "CmdOrCtrl+Shift+M"),
+    // View
+    ("view_dashboard", "CmdOrCtrl+1"),
+    ("view_timeline", "CmdOrCtrl+2"),
+    ("view_kanban", "CmdOrCtrl+3"),
+    ("view_decision", "CmdOrCtrl+4"),
+    ("view_files", "CmdOrCtrl+5"),
+    ("view_tree", "CmdOrCtrl+6"),
+    ("view_story", "CmdOrCtrl+7"),
+    ("view_architecture", "CmdOrCtrl+8"),
+    ("view_search", ""),
+    ("view_todos", ""),
+    ("view_conversations", ""),
+    ("view_sessions", ""),
+    ("view_knowledge", ""),
+    ("view_data", ""),
+    ("toggle_sidebar", "CmdOrCtrl+\\"),
+    ("toggle_ai_panel", "CmdOrCtrl+Shift+A"),
+    // Tools
+    ("ai_describe_file", ""),
+    ("ai_extract_file", ""),
+    ("ai_summarize", ""),
+    ("reindex_files", ""),
+    ("verify_integrity", ""),
+    ("git_history", ""),
+    ("resolve_conflicts", ""),
+    // Window
+    ("show_all_projects", "CmdOrCtrl+0"),
+    ("bring_to_front", ""),
+    // Help
+    ("help_guide", "CmdOrCtrl+?"),
+    ("help_shortcuts", ""),
+    ("help_getting_started", ""),
+    ("help_working_files", ""),
+    ("help_sync", ""),
+    ("help_ai", ""),
+    ("help_mcp", ""),
+    ("check_updates", ""),
+    ("release_notes", ""),
+    ("report_bug", ""),
+    ("send_feedback", ""),
+    ("help_about", ""),
+    // App menu
+    ("settings", "CmdOrCtrl+,"),
+];
+
+const MODIFIERS: &[(&str, &str)] = &[
+    ("cmdorctrl", "CmdOrCtrl"),
+    ("commandorcontrol", "CmdOrCtrl"),
+    ("cmd", "Cmd"),
+    ("command", "Cmd"),
+    ("super", "Super"),
+    ("ctrl", "Ctrl"),
+    ("control", "Ctrl"),
+    ("alt", "Alt"),
+    ("option", "Alt"),
+    ("shift", "Shift"),
+];
+
+pub fn is_known_action(action: &str) -> bool {
+    DEFAULT_SHORTCUTS.iter().any(|(a, _)| *a == action)
+}
+
+/// Defaults with stored overrides applied. Disabled shortcuts map to "".
+pub fn resolve(overrides: &[(String, String)]) -> BTreeMap<String, String> {
+    let mut map: BTreeMap<String, String> = DEFAULT_SHORTCUTS
+        .iter()
+        .map(|(action, accel)| (action.to_string(), accel.to_string()))
+        .collect();
+    for (action, accel) in overrides {
+        if let Some(slot) = map.get_mut(action) {
+            *slot = accel.clone();
+        }
+    }
+    map
+}
+
+/// Canonical form of an accelerator: known modifiers in a fixed order, then exactly one key.
+pub fn normalize(accelerator: &str) -> Result<String, String> {
+    let parts: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+    let (key, modifiers) = match parts.split_last() {
+        // "CmdOrCtrl++" spells the plus key
+        Some((&"", rest)) if rest.last() == Some(&"") => ("+", &rest[..rest.len() - 1]),
+        Some((key, rest)) => (*key, rest),
+        None => return Err("Shortcut is empty".to_string()),
+    };
+    if key.is_empty() {
+        return Err(format!("'{}' has no key after its modifiers", accelerator));
+    }
+
+    let mut canonical: Vec<&str> = Vec::new();
+    for modifier in modifiers {
+        let lower = modifier.to_lowercase();
+        let Some((_, name)) = MODIFIERS.iter().find(|(alias, _)| *alias == lower) else {
+            return Err(format!("Unknown modifier '{}' in '{}'", modifier, accelerator));
+        };
+        if !canonical.contains(name) {
+            canonical.push(name);
+        }
+    }
+    let order = ["CmdOrCtrl", "Cmd", "Super", "Ctrl", "Alt", "Shift"];
+    canonical.sort_by_key(|m| order.iter().position(|o| o == m));
+
+    let key = if key.chars().count() == 1 { key.to_uppercase() } else { key.to_string() };
+    canonical.push(&key);
+    Ok(canonical.join("+"))
+}
+
+/// The action already bound to `accelerator`, other than `action` itself.
+pub fn find_conflict(map: &BTreeMap<String, String>, action: &str, accelerator: &str) -> Option<String> {
+    map.iter()
+        .find(|(other, accel)| {
+            other.as_str() != action && !accel.is_empty() && accel.eq_ignore_ascii_case(accelerator)
+        })
+        .map(|(other, _)| other.clone())
+}
@@ -0,0 +1,514 @@
+// FlowState Site Module - Static HTML export of a project
+// Produces plain files (no build step, no server) that work opened from disk or published
+// as is to GitHub Pages: an overview, a page per problem with its journey, the learnings,
+// a timeline, and search over all of it. The search index is a script rather than JSON
+// because browsers won't fetch() local files.
+
+use crate::database::{ChecklistItem, Change, Component, Learning, Problem, Project, Solution, SolutionAttempt};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+pub struct SiteProblem {
+    pub problem: Problem,
+    pub component_name: String,
+    pub attempts: Vec<SolutionAttempt>,
+    pub solution: Option<Solution>,
+    pub checklist: Vec<ChecklistItem>,
+}
+
+pub struct SiteSource {
+    pub project: Project,
+    pub components: Vec<Component>,
+    pub problems: Vec<SiteProblem>,
+    pub learnings: Vec<Learning>,
+    pub changes: Vec<Change>,
+}
+
+/// Every file of the site as (path relative to the output directory, contents).
+pub fn build(source: &SiteSource) -> Vec<(String, String)> {
+    let mut files = vec![
+        ("index.html".to_string(), overview(source)),
+        ("problems.html".to_string(), problem_list(source)),
+        ("learnings.html".to_string(), learnings(source)),
+        ("timeline.html".to_string(), timeline(source)),
+        ("style.css".to_string(), STYLE.to_string()),
+        ("search.js".to_string(), SEARCH_SCRIPT.to_string()),
+        ("search-index.js".to_string(), search_index(source)),
+        // Tells GitHub Pages to serve the files as they are instead of running Jekyll
+        (".nojekyll".to_string(), String::new()),
+    ];
+    for item in &source.problems {
+        files.push((problem_path(item.problem.id), problem_page(source, item)));
+    }
+    files
+}
+
+// ============================================================
+// PAGES
+// ============================================================
+
+fn overview(source: &SiteSource) -> String {
+    let project = &source.project;
+    let solved = source.problems.iter().filter(|p| p.problem.status == "solved").count();
+    let mut body = format!(
+        "<h1>{}</h1>\n<p class=\"lead\">{}</p>\n",
+        escape(&project.name),
+        escape(project.description.as_deref().unwrap_or("A development journey")),
+    );
+    body.push_str(&format!(
+        "<div class=\"stats\">{}{}{}{}</div>\n",
+        stat(source.components.len(), "components"),
+        stat(source.problems.len(), "problems"),
+        stat(solved, "solved"),
+        stat(source.learnings.len(), "learnings"),
+    ));
+
+    if !source.components.is_empty() {
+        body.push_str("<h2>Components</h2>\n");
+        body.push_str(&component_tree(&source.components, None));
+    }
+
+    let open: Vec<&SiteProblem> = source.problems.iter()
+        .filter(|p| !matches!(p.problem.status.as_str(), "solved" | "wont_fix"))
+        .collect();
+    if !open.is_empty() {
+        body.push_str("<h2>Open problems</h2>\n<ul class=\"items\">\n");
+        for item in open {
+            body.push_str(&problem_item(item));
+        }
+        body.push_str("</ul>\n");
+    }
+
+    let mut recent: Vec<&SiteProblem> = source.problems.iter().filter(|p| p.solution.is_some()).collect();
+    recent.sort_by(|a, b| b.problem.solved_at.cmp(&a.problem.solved_at));
+    if !recent.is_empty() {
+        body.push_str("<h2>Recently solved</h2>\n<ul class=\"items\">\n");
+        for item in recent.into_iter().take(10) {
+            body.push_str(&problem_item(item));
+        }
+        body.push_str("</ul>\n");
+    }
+    layout(project, "Overview", "", &body)
+}
+
+fn problem_list(source: &SiteSource) -> String {
+    let mut body = format!("<h1>Problems</h1>\n<p class=\"lead\">{} in total</p>\n", source.problems.len());
+    for (status, label) in [
+        ("open", "Open"),
+        ("investigating", "Investigating"),
+        ("blocked", "Blocked"),
+        ("solved", "Solved"),
+        ("wont_fix", "Won't fix"),
+    ] {
+        let items: Vec<&SiteProblem> = source.problems.iter().filter(|p| p.problem.status == status).collect();
+        if items.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("<h2>{} <span class=\"count\">{}</span></h2>\n<ul class=\"items\">\n", label, items.len()));
+        for item in items {
+            body.push_str(&problem_item(item));
+        }
+        body.push_str("</ul>\n");
+    }
+    layout(&source.project, "Problems", "", &body)
+}
+
+fn problem_page(source: &SiteSource, item: &SiteProblem) -> String {
+    let problem = &item.problem;
+    let mut body = format!(
+        "<p class=\"crumbs\"><a href=\"../problems.html\">Problems</a> / {}</p>\n<h1>{}</h1>\n",
+        escape(&item.component_name),
+        escape(&problem.title),
+    );
+    body.push_str(&format!(
+        "<p class=\"meta\"><span class=\"badge {sev}\">{sev}</span> <span class=\"badge\">{status}</span> Reported {created}{solved}</p>\n",
+        sev = escape(&problem.severity),
+        status = escape(&problem.status.replace('_', " ")),
+        created = escape(&date(&problem.created_at)),
+        solved = problem.solved_at.as_deref().map(|at| format!(" · solved {}", escape(&date(at)))).unwrap_or_default(),
+    ));
+    if let Some(description) = problem.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        body.push_str(&format!("<div class=\"text\">{}</div>\n", escape(description)));
+    }
+    if let Some(root_cause) = problem.root_cause.as_deref().filter(|r| !r.trim().is_empty()) {
+        body.push_str(&format!("<h2>Root cause</h2>\n<div class=\"text\">{}</div>\n", escape(root_cause)));
+    }
+
+    // The journey: report, attempts and confirmed hypotheses in order, then the fix
+    let mut steps: Vec<(String, String)> = vec![(
+        problem.created_at.clone(),
+        format!("<li class=\"step identified\"><strong>Problem identified</strong> <time>{}</time></li>\n", escape(&date(&problem.created_at))),
+    )];
+    for attempt in &item.attempts {
+        let outcome = attempt.outcome.as_deref().unwrap_or("pending");
+        let mut step = format!(
+            "<li class=\"step {outcome}\"><strong>Attempt</strong> <span class=\"badge {outcome}\">{outcome}</span> <time>{}</time>",
+            escape(&date(&attempt.created_at)),
+            outcome = escape(outcome),
+        );
+        if let Some(minutes) = attempt.time_spent_minutes.filter(|m| *m > 0) {
+            step.push_str(&format!(" <span class=\"muted\">{}</span>", duration(minutes)));
+        }
+        step.push_str(&format!("<div class=\"text\">{}</div>", escape(&attempt.description)));
+        if let Some(notes) = attempt.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            step.push_str(&format!("<div class=\"text muted\">{}</div>", escape(notes)));
+        }
+        step.push_str("</li>\n");
+        steps.push((attempt.created_at.clone(), step));
+    }
+    for check in item.checklist.iter().filter(|c| c.checked) {
+        let at = check.checked_at.clone().unwrap_or_else(|| check.created_at.clone());
+        steps.push((at.clone(), format!(
+            "<li class=\"step checked\"><strong>Confirmed</strong> <time>{}</time><div class=\"text\">{}</div></li>\n",
+            escape(&date(&at)),
+            escape(&check.text),
+        )));
+    }
+    if let Some(solution) = &item.solution {
+        steps.push((solution.created_at.clone(), format!(
+            "<li class=\"step success\"><strong>Solved</strong> <time>{}</time><div class=\"text\">{}</div></li>\n",
+            escape(&date(&solution.created_at)),
+            escape(&solution.summary),
+        )));
+    }
+    steps.sort_by(|a, b| a.0.cmp(&b.0));
+    body.push_str("<h2>Journey</h2>\n<ol class=\"journey\">\n");
+    for (_, step) in steps {
+        body.push_str(&step);
+    }
+    body.push_str("</ol>\n");
+
+    if let Some(solution) = &item.solution {
+        body.push_str(&format!("<h2>Solution</h2>\n<div class=\"text\">{}</div>\n", escape(&solution.summary)));
+        if let Some(insight) = solution.key_insight.as_deref().filter(|i| !i.trim().is_empty()) {
+            body.push_str(&format!("<blockquote>{}<cite>Key insight</cite></blockquote>\n", escape(insight)));
+        }
+        if let Some(code) = solution.code_snippet.as_deref().filter(|c| !c.trim().is_empty()) {
+            body.push_str(&format!("<pre><code>{}</code></pre>\n", escape(code)));
+        }
+    }
+    layout(&source.project, &problem.title, "../", &body)
+}
+
+fn learnings(source: &SiteSource) -> String {
+    let count = source.learnings.len();
+    let mut body = format!("<h1>Learnings</h1>\n<p class=\"lead\">{} {}</p>\n", count, if count == 1 { "insight" } else { "insights" });
+    let mut by_category: BTreeMap<String, Vec<&Learning>> = BTreeMap::new();
+    for learning in &source.learnings {
+        let category = learning.category.as_deref().unwrap_or("other").replace('_', " ");
+        by_category.entry(category).or_default().push(learning);
+    }
+    for (category, items) in by_category {
+        body.push_str(&format!("<h2 id=\"{}\">{}</h2>\n", slug(&category), escape(&capitalize(&category))));
+        for learning in items {
+            body.push_str(&format!(
+                "<blockquote id=\"learning-{}\">{}{}<cite>{}{}</cite></blockquote>\n",
+                learning.id,
+                escape(&learning.insight),
+                learning.context.as_deref().filter(|c| !c.trim().is_empty())
+                    .map(|c| format!("<div class=\"text muted\">{}</div>", escape(c)))
+                    .unwrap_or_default(),
+                escape(&date(&learning.created_at)),
+                if learning.verified { " · verified" } else { "" },
+            ));
+        }
+    }
+    layout(&source.project, "Learnings", "", &body)
+}
+
+fn timeline(source: &SiteSource) -> String {
+    let components: BTreeMap<i64, &str> = source.components.iter().map(|c| (c.id, c.name.as_str())).collect();
+    let mut events: Vec<(String, String)> = Vec::new();
+    for item in &source.problems {
+        let link = format!("<a href=\"{}\">{}</a>", problem_path(item.problem.id), escape(&item.problem.title));
+        events.push((item.problem.created_at.clone(), format!("<span class=\"badge {}\">problem</span> {} reported", escape(&item.problem.severity), link)));
+        if let Some(solution) = &item.solution {
+            events.push((solution.created_at.clone(), format!("<span class=\"badge success\">solved</span> {}", link)));
+        }
+    }
+    for learning in &source.learnings {
+        events.push((learning.created_at.clone(), format!(
+            "<span class=\"badge\">learning</span> <a href=\"learnings.html#learning-{}\">{}</a>",
+            learning.id,
+            escape(&first_line(&learning.insight)),
+        )));
+    }
+    for change in &source.changes {
+        let component = components.get(&change.component_id).copied().unwrap_or("?");
+        let values = match (&change.old_value, &change.new_value) {
+            (Some(old), Some(new)) => format!(": {} → {}", escape(&first_line(old)), escape(&first_line(new))),
+            (None, Some(new)) => format!(": {}", escape(&first_line(new))),
+            _ => String::new(),
+        };
+        events.push((change.created_at.clone(), format!(
+            "<span class=\"badge\">{}</span> {} · {}{}",
+            escape(&change.change_type),
+            escape(component),
+            escape(&change.field_name),
+            values,
+        )));
+    }
+    events.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut body = "<h1>Timeline</h1>\n".to_string();
+    let mut month = String::new();
+    for (at, event) in events {
+        let event_month = month_of(&at);
+        if event_month != month {
+            if !month.is_empty() {
+                body.push_str("</ul>\n");
+            }
+            body.push_str(&format!("<h2>{}</h2>\n<ul class=\"timeline\">\n", escape(&event_month)));
+            month = event_month;
+        }
+        body.push_str(&format!("<li><time>{}</time> {}</li>\n", escape(&date(&at)), event));
+    }
+    if !month.is_empty() {
+        body.push_str("</ul>\n");
+    }
+    layout(&source.project, "Timeline", "", &body)
+}
+
+/// Titles, urls and text of every page and learning, for search.js.
+fn search_index(source: &SiteSource) -> String {
+    let mut entries = vec![json!({
+        "title": source.project.name,
+        "url": "index.html",
+        "kind": "project",
+        "text": source.project.description.clone().unwrap_or_default(),
+    })];
+    for item in &source.problems {
+        let mut text = vec![item.component_name.clone()];
+        text.extend(item.problem.description.clone());
+        text.extend(item.problem.root_cause.clone());
+        text.extend(item.attempts.iter().map(|a| a.description.clone()));
+        if let Some(solution) = &item.solution {
+            text.push(solution.summary.clone());
+            text.extend(solution.key_insight.clone());
+        }
+        entries.push(json!({
+            "title": item.problem.title,
+            "url": problem_path(item.problem.id),
+            "kind": item.problem.status.replace('_', " "),
+            "text": text.join(" "),
+        }));
+    }
+    for learning in &source.learnings {
+        entries.push(json!({
+            "title": first_line(&learning.insight),
+            "url": format!("learnings.html#learning-{}", learning.id),
+            "kind": "learning",
+            "text": format!("{} {}", learning.insight, learning.context.as_deref().unwrap_or_default()),
+        }));
+    }
+    format!("window.FLOWSTATE_SEARCH = {};\n", serde_json::Value::Array(entries))
+}
+
+// ============================================================
+// LAYOUT
+// ============================================================
+
+/// A full page. `root` is the relative path back to the site root ("" or "../").
+fn layout(project: &Project, title: &str, root: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title} – {project}</title>
+<link rel="stylesheet" href="{root}style.css">
+</head>
+<body data-root="{root}">
+<header>
+<a class="brand" href="{root}index.html">{project}</a>
+<nav><a href="{root}index.html">Overview</a><a href="{root}problems.html">Problems</a><a href="{root}learnings.html">Learnings</a><a href="{root}timeline.html">Timeline</a></nav>
+<div class="search"><input id="search" type="search" placeholder="Search…" autocomplete="off"><ul id="results"></ul></div>
+</header>
+<main>
+{body}</main>
+<footer>Exported from FlowState on {exported}</footer>
+<script src="{root}search-index.js"></script>
+<script src="{root}search.js"></script>
+</body>
+</html>
+"#,
+        title = escape(title),
+        project = escape(&project.name),
+        root = root,
+        body = body,
+        exported = chrono::Utc::now().format("%B %-d, %Y"),
+    )
+}
+
+fn problem_item(item: &SiteProblem) -> String {
+    format!(
+        "<li><a href=\"{}\">{}</a> <span class=\"badge {}\">{}</span> <span class=\"muted\">{} · {}</span></li>\n",
+        problem_path(item.problem.id),
+        escape(&item.problem.title),
+        escape(&item.problem.severity),
+        escape(&item.problem.severity),
+        escape(&item.component_name),
+        escape(&date(&item.problem.created_at)),
+    )
+}
+
+fn component_tree(components: &[Component], parent: Option<i64>) -> String {
+    let children: Vec<&Component> = components.iter().filter(|c| c.parent_component_id == parent).collect();
+    if children.is_empty() {
+        return String::new();
+    }
+    let mut html = "<ul class=\"tree\">\n".to_string();
+    for component in children {
+        html.push_str(&format!(
+            "<li><strong>{}</strong> <span class=\"muted\">{}</span>{}{}</li>\n",
+            escape(&component.name),
+            escape(&component.status.replace('_', " ")),
+            component.description.as_deref().map(|d| format!(" — {}", escape(d))).unwrap_or_default(),
+            component_tree(components, Some(component.id)),
+        ));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+fn stat(value: usize, label: &str) -> String {
+    format!("<div class=\"stat\"><span>{}</span>{}</div>", value, label)
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn problem_path(id: i64) -> String {
+    format!("problems/{}.html", id)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn slug(text: &str) -> String {
+    text.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().trim().to_string()
+}
+
+fn duration(minutes: i64) -> String {
+    if minutes >= 60 { format!("{}h {}m", minutes / 60, minutes % 60) } else { format!("{}m", minutes) }
+}
+
+/// Database timestamps (UTC) as dates; anything else is shown as it is.
+fn date(timestamp: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|at| at.format("%b %-d, %Y").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn month_of(timestamp: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|at| at.format("%B %Y").to_string())
+        .unwrap_or_else(|_| "Undated".to_string())
+}
+
+// ============================================================
+// ASSETS
+// ============================================================
+
+const STYLE: &str = r#":root { --ink: #1f2937; --muted: #6b7280; --line: #e5e7eb; --accent: #7c3aed; --panel: #f9fafb; }
+@media (prefers-color-scheme: dark) {
+  :root { --ink: #f3f4f6; --muted: #9ca3af; --line: #374151; --accent: #a855f7; --panel: #1f2937; }
+  body { background: #111827; }
+}
+* { box-sizing: border-box; }
+body { margin: 0; font: 16px/1.6 system-ui, -apple-system, sans-serif; color: var(--ink); }
+header { display: flex; flex-wrap: wrap; gap: 1rem; align-items: center; padding: 0.75rem 1.5rem; border-bottom: 1px solid var(--line); }
+header .brand { font-weight: 700; color: var(--accent); text-decoration: none; }
+nav a { margin-right: 1rem; color: var(--ink); text-decoration: none; }
+nav a:hover, a { color: var(--accent); }
+.search { position: relative; margin-left: auto; }
+.search input { padding: 0.4rem 0.7rem; border: 1px solid var(--line); border-radius: 6px; background: var(--panel); color: var(--ink); width: 16rem; }
+#results { position: absolute; right: 0; z-index: 1; width: 26rem; max-width: 90vw; margin: 0.25rem 0 0; padding: 0; list-style: none; background: var(--panel); border: 1px solid var(--line); border-radius: 6px; }
+#results:empty { display: none; }
+#results li { padding: 0.5rem 0.75rem; border-bottom: 1px solid var(--line); }
+#results li span { display: block; font-size: 0.8rem; color: var(--muted); }
+main { max-width: 52rem; margin: 0 auto; padding: 1.5rem; }
+footer { max-width: 52rem; margin: 2rem auto; padding: 0 1.5rem; color: var(--muted); font-size: 0.85rem; }
+h1 { margin-bottom: 0.25rem; }
+h2 { margin-top: 2rem; border-bottom: 1px solid var(--line); padding-bottom: 0.25rem; }
+.lead, .muted, time, .crumbs { color: var(--muted); }
+.count { color: var(--muted); font-weight: normal; }
+.stats { display: grid; grid-template-columns: repeat(auto-fit, minmax(8rem, 1fr)); gap: 1rem; margin: 1.5rem 0; }
+.stat { background: var(--panel); border-radius: 8px; padding: 1rem; text-align: center; color: var(--muted); }
+.stat span { display: block; font-size: 1.6rem; font-weight: 700; color: var(--accent); }
+.items, .timeline, .tree { padding-left: 1.2rem; }
+.items li, .timeline li { margin: 0.35rem 0; }
+.badge { display: inline-block; padding: 0 0.45rem; border-radius: 4px; font-size: 0.75rem; background: var(--line); }
+.badge.critical, .badge.failure { background: #fecaca; color: #7f1d1d; }
+.badge.high, .badge.abandoned { background: #fed7aa; color: #7c2d12; }
+.badge.success { background: #bbf7d0; color: #14532d; }
+.badge.partial { background: #fef08a; color: #713f12; }
+.text { white-space: pre-wrap; }
+.journey { list-style: none; padding-left: 1rem; border-left: 2px solid var(--line); }
+.journey .step { margin: 0 0 1rem; padding-left: 0.75rem; }
+.journey .success { border-left: 3px solid #16a34a; }
+.journey .failure { border-left: 3px solid #dc2626; }
+blockquote { margin: 1rem 0; padding: 0.5rem 1rem; border-left: 4px solid var(--accent); background: var(--panel); white-space: pre-wrap; }
+blockquote cite { display: block; margin-top: 0.25rem; font-size: 0.85rem; color: var(--muted); }
+pre { background: var(--panel); padding: 1rem; border-radius: 6px; overflow-x: auto; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"(function () {
+  var input = document.getElementById('search');
+  var results = document.getElementById('results');
+  var root = document.body.getAttribute('data-root') || '';
+  var index = (window.FLOWSTATE_SEARCH || []).map(function (entry) {
+    return { entry: entry, haystack: (entry.title + ' ' + entry.text).toLowerCase() };
+  });
+
+  function render(query) {
+    results.innerHTML = '';
+    var terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+    if (!terms.length) return;
+    var matches = index.filter(function (item) {
+      return terms.every(function (term) { return item.haystack.indexOf(term) !== -1; });
+    }).slice(0, 20);
+    matches.forEach(function (item) {
+      var li = document.createElement('li');
+      var link = document.createElement('a');
+      link.href = root + item.entry.url;
+      link.textContent = item.entry.title;
+      var kind = document.createElement('span');
+      kind.textContent = item.entry.kind;
+      li.appendChild(link);
+      li.appendChild(kind);
+      results.appendChild(li);
+    });
+    if (!matches.length) {
+      var none = document.createElement('li');
+      none.textContent = 'No matches';
+      results.appendChild(none);
+    }
+  }
+
+  input.addEventListener('input', function () { render(input.value); });
+  input.addEventListener('keydown', function (event) {
+    if (event.key === 'Escape') { input.value = ''; render(''); }
+    if (event.key === 'Enter') {
+      var first = results.querySelector('a');
+      if (first) window.location.href = first.href;
+    }
+  });
+})();
+"#;
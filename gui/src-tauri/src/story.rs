@@ -0,0 +1,293 @@
+// FlowState Story Module - Project story as a printable document
+// The narrative template follows StoryMode's chapters (genesis, architecture, challenges,
+// wisdom, where things stand); the retrospective template reorganises the same records
+// into what went well, what was hard, what was learned and what's still open, for
+// readers who want the outcome rather than the journey.
+
+use crate::database::{Component, Learning, Problem, Project, Solution, Todo};
+use crate::pdf::Block;
+use chrono::{NaiveDateTime, Utc};
+
+const FEATURED_PROBLEMS: usize = 5;
+const FEATURED_LEARNINGS: usize = 5;
+const MAX_LISTED: usize = 10;
+
+pub struct StoryImage {
+    pub data: Vec<u8>,
+    pub caption: String,
+}
+
+pub struct StoryProblem {
+    pub problem: Problem,
+    pub solution: Option<Solution>,
+    pub attempts: usize,
+    pub failed_attempts: usize,
+    pub minutes: i64,
+    pub images: Vec<StoryImage>,
+}
+
+pub struct StorySource {
+    pub project: Project,
+    pub components: Vec<Component>,
+    pub problems: Vec<StoryProblem>,
+    pub learnings: Vec<Learning>,
+    pub todos: Vec<Todo>,
+    pub images: Vec<StoryImage>, // Image attachments not tied to a problem
+}
+
+pub fn blocks(template: &str, source: &StorySource) -> Result<Vec<Block>, String> {
+    match template {
+        "narrative" => Ok(narrative(source)),
+        "retrospective" => Ok(retrospective(source)),
+        other => Err(format!("Unknown story template '{}' (narrative or retrospective)", other)),
+    }
+}
+
+fn narrative(source: &StorySource) -> Vec<Block> {
+    let project = &source.project;
+    let mut blocks = vec![
+        Block::Title(project.name.clone()),
+        Block::Subtitle(project.description.clone().unwrap_or_else(|| "A development journey".to_string())),
+        stats(source),
+    ];
+    let mut chapter = 0;
+    let mut heading = |blocks: &mut Vec<Block>, title: &str, subtitle: String| {
+        chapter += 1;
+        blocks.push(Block::Heading(format!("Chapter {}: {}", chapter, title)));
+        blocks.push(Block::Subtitle(subtitle));
+    };
+
+    heading(&mut blocks, "Genesis", format!("The birth of {} · {}", project.name, date(&project.created_at, "%B %-d, %Y")));
+    blocks.push(Block::Paragraph(project.description.clone().unwrap_or_else(|| format!(
+        "{} began as an idea — a solution to a problem that needed solving. This is the story of how it came to be.",
+        project.name,
+    ))));
+
+    if !source.components.is_empty() {
+        let roots = source.components.iter().filter(|c| c.parent_component_id.is_none()).count();
+        heading(&mut blocks, "The Architecture", format!("Building blocks take shape · {}", date(&source.components[0].created_at, "%B %Y")));
+        blocks.push(Block::Paragraph(format!(
+            "The foundation was laid with {} core {}:",
+            roots,
+            if roots == 1 { "module" } else { "modules" },
+        )));
+        blocks.push(Block::Code(component_tree(&source.components)));
+    }
+
+    if !source.problems.is_empty() {
+        let count = source.problems.len();
+        heading(&mut blocks, "The Challenges", format!("{} obstacles encountered", count));
+        blocks.push(Block::Paragraph(format!(
+            "Every great project faces adversity. {} was no exception. {} {}, each demanding creative solutions.",
+            project.name,
+            count,
+            if count == 1 { "problem emerged" } else { "problems emerged" },
+        )));
+        // The hardest-won solutions make the best chapters
+        let mut featured: Vec<&StoryProblem> = source.problems.iter().collect();
+        featured.sort_by_key(|p| (p.solution.is_none(), std::cmp::Reverse(p.attempts), std::cmp::Reverse(p.minutes)));
+        for problem in featured.into_iter().take(FEATURED_PROBLEMS) {
+            problem_journey(&mut blocks, problem);
+        }
+    }
+
+    if !source.learnings.is_empty() {
+        heading(&mut blocks, "Wisdom Gained", format!("{} insights discovered", source.learnings.len()));
+        blocks.push(Block::Paragraph("Through trial and error, valuable lessons emerged:".to_string()));
+        learning_quotes(&mut blocks, &source.learnings, FEATURED_LEARNINGS);
+    }
+
+    if !source.images.is_empty() {
+        heading(&mut blocks, "Snapshots", "Along the way".to_string());
+        images(&mut blocks, &source.images);
+    }
+
+    let launched = project.status == "completed";
+    heading(
+        &mut blocks,
+        if launched { "Launch" } else { "The Journey Continues" },
+        if launched { "Mission accomplished".to_string() } else { "Work in progress".to_string() },
+    );
+    let open = source.problems.iter().filter(|p| p.problem.status == "open").count();
+    let complete = source.components.iter().filter(|c| c.status == "complete").count();
+    blocks.push(Block::Paragraph(if launched {
+        format!("After {} days of development, {} reached completion.", days_active(project), project.name)
+    } else {
+        format!(
+            "{} continues to evolve. {} of {} components are complete, with {} challenges remaining.",
+            project.name, complete, source.components.len(), open,
+        )
+    }));
+    blocks.push(stats(source));
+    blocks
+}
+
+fn retrospective(source: &StorySource) -> Vec<Block> {
+    let project = &source.project;
+    let mut blocks = vec![
+        Block::Title(format!("{} — Retrospective", project.name)),
+        Block::Subtitle(format!("{} to {}", date(&project.created_at, "%B %-d, %Y"), Utc::now().format("%B %-d, %Y"))),
+        stats(source),
+    ];
+
+    let mut solved: Vec<&StoryProblem> = source.problems.iter().filter(|p| p.solution.is_some()).collect();
+    solved.sort_by(|a, b| a.problem.solved_at.cmp(&b.problem.solved_at));
+    if !solved.is_empty() {
+        blocks.push(Block::Heading("What went well".to_string()));
+        blocks.push(Block::Subtitle(format!("{} problems solved", solved.len())));
+        for problem in solved.into_iter().take(MAX_LISTED) {
+            problem_journey(&mut blocks, problem);
+        }
+    }
+
+    let mut hard: Vec<&StoryProblem> = source.problems.iter().filter(|p| p.failed_attempts > 0 || p.minutes > 0).collect();
+    hard.sort_by_key(|p| (std::cmp::Reverse(p.failed_attempts), std::cmp::Reverse(p.minutes)));
+    if !hard.is_empty() {
+        blocks.push(Block::Heading("What was hard".to_string()));
+        blocks.push(Block::Subtitle("Where the attempts and the hours went".to_string()));
+        blocks.push(Block::Bullets(hard.iter().take(FEATURED_PROBLEMS).map(|p| {
+            format!("{} — {}", p.problem.title, effort(p))
+        }).collect()));
+    }
+
+    if !source.learnings.is_empty() {
+        blocks.push(Block::Heading("What we learned".to_string()));
+        blocks.push(Block::Subtitle(format!("{} insights", source.learnings.len())));
+        learning_quotes(&mut blocks, &source.learnings, MAX_LISTED);
+    }
+
+    let open_problems: Vec<String> = source.problems.iter()
+        .filter(|p| !matches!(p.problem.status.as_str(), "solved" | "wont_fix"))
+        .map(|p| format!("{} ({}, {})", p.problem.title, p.problem.severity, p.problem.status.replace('_', " ")))
+        .collect();
+    let open_todos: Vec<String> = source.todos.iter()
+        .filter(|t| !matches!(t.status.as_str(), "done" | "cancelled"))
+        .map(|t| format!("{} ({} priority)", t.title, t.priority))
+        .collect();
+    if !open_problems.is_empty() || !open_todos.is_empty() {
+        blocks.push(Block::Heading("Still open".to_string()));
+        blocks.push(Block::Subtitle(format!("{} problems and {} todos carried forward", open_problems.len(), open_todos.len())));
+        if !open_problems.is_empty() {
+            blocks.push(Block::Subheading("Problems".to_string()));
+            blocks.push(Block::Bullets(truncated_list(open_problems)));
+        }
+        if !open_todos.is_empty() {
+            blocks.push(Block::Subheading("Todos".to_string()));
+            blocks.push(Block::Bullets(truncated_list(open_todos)));
+        }
+    }
+
+    if !source.images.is_empty() {
+        blocks.push(Block::Heading("Snapshots".to_string()));
+        images(&mut blocks, &source.images);
+    }
+    blocks
+}
+
+// ============================================================
+// SECTIONS
+// ============================================================
+
+/// A problem from report to fix: what it was, what it took and what unlocked it.
+fn problem_journey(blocks: &mut Vec<Block>, item: &StoryProblem) {
+    let problem = &item.problem;
+    blocks.push(Block::Subheading(problem.title.clone()));
+    blocks.push(Block::Subtitle(format!("{} severity · {} · {}", problem.severity, problem.status.replace('_', " "), effort(item))));
+    if let Some(description) = problem.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        blocks.push(Block::Paragraph(description.to_string()));
+    }
+    if let Some(root_cause) = problem.root_cause.as_deref().filter(|r| !r.trim().is_empty()) {
+        blocks.push(Block::Paragraph(format!("Root cause: {}", root_cause)));
+    }
+    if let Some(solution) = &item.solution {
+        blocks.push(Block::Paragraph(format!("Solution: {}", solution.summary)));
+        if let Some(insight) = solution.key_insight.as_deref().filter(|i| !i.trim().is_empty()) {
+            blocks.push(Block::Quote { text: insight.to_string(), cite: Some("Key insight".to_string()) });
+        }
+        if let Some(code) = solution.code_snippet.as_deref().filter(|c| !c.trim().is_empty()) {
+            blocks.push(Block::Code(code.to_string()));
+        }
+    }
+    images(blocks, &item.images);
+}
+
+/// Verified learnings first, newest first within each group.
+fn learning_quotes(blocks: &mut Vec<Block>, learnings: &[Learning], limit: usize) {
+    let mut ordered: Vec<&Learning> = learnings.iter().collect();
+    ordered.sort_by(|a, b| b.verified.cmp(&a.verified).then(b.created_at.cmp(&a.created_at)));
+    for learning in ordered.into_iter().take(limit) {
+        blocks.push(Block::Quote {
+            text: learning.insight.clone(),
+            cite: Some(learning.category.as_deref().unwrap_or("Experience").replace('_', " ")),
+        });
+    }
+}
+
+fn images(blocks: &mut Vec<Block>, images: &[StoryImage]) {
+    for image in images {
+        blocks.push(Block::Image { data: image.data.clone(), caption: Some(image.caption.clone()) });
+    }
+}
+
+fn stats(source: &StorySource) -> Block {
+    let solved = source.problems.iter().filter(|p| p.problem.status == "solved").count();
+    Block::Stats(vec![
+        (days_active(&source.project).to_string(), "days active".to_string()),
+        (source.components.len().to_string(), "components".to_string()),
+        (solved.to_string(), "problems solved".to_string()),
+        (source.learnings.len().to_string(), "learnings".to_string()),
+    ])
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// Components as an indented tree, children under their parents. Plain ASCII, since the
+/// PDF fonts have no box-drawing characters.
+fn component_tree(components: &[Component]) -> String {
+    fn walk(components: &[Component], parent: Option<i64>, depth: usize, lines: &mut Vec<String>) {
+        for component in components.iter().filter(|c| c.parent_component_id == parent) {
+            lines.push(format!("{}- {} ({})", "    ".repeat(depth), component.name, component.status));
+            walk(components, Some(component.id), depth + 1, lines);
+        }
+    }
+    let mut lines = Vec::new();
+    walk(components, None, 0, &mut lines);
+    lines.join("\n")
+}
+
+fn effort(item: &StoryProblem) -> String {
+    let mut parts = vec![format!("{} {}", item.attempts, if item.attempts == 1 { "attempt" } else { "attempts" })];
+    if item.failed_attempts > 0 {
+        parts.push(format!("{} failed", item.failed_attempts));
+    }
+    if item.minutes >= 60 {
+        parts.push(format!("{}h {}m", item.minutes / 60, item.minutes % 60));
+    } else if item.minutes > 0 {
+        parts.push(format!("{}m", item.minutes));
+    }
+    parts.join(", ")
+}
+
+fn truncated_list(mut items: Vec<String>) -> Vec<String> {
+    if items.len() > MAX_LISTED {
+        let more = items.len() - MAX_LISTED;
+        items.truncate(MAX_LISTED);
+        items.push(format!("…and {} more", more));
+    }
+    items
+}
+
+fn days_active(project: &Project) -> i64 {
+    NaiveDateTime::parse_from_str(&project.created_at, "%Y-%m-%d %H:%M:%S")
+        .map(|created| (Utc::now().naive_utc() - created).num_days() + 1)
+        .unwrap_or(1)
+}
+
+/// A database timestamp in another format; unparseable ones are shown as they are.
+fn date(timestamp: &str, format: &str) -> String {
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|at| at.format(format).to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
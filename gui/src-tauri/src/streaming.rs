@@ -0,0 +1,97 @@
+// FlowState Streaming Module - Hand large command results over in pieces
+// A result is serialized once and kept under a handle. The frontend either listens for
+// `result-chunk` events followed by `result-complete`, or pages through the chunks itself
+// with the handle. Results expire after a while so abandoned ones don't pile up.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const CHUNK_SIZE: usize = 256 * 1024;
+const RESULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ResultHandle {
+    pub result_handle: String,
+    pub total_bytes: usize,
+    pub total_chunks: usize,
+    pub chunk_size: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ResultChunk {
+    pub result_handle: String,
+    pub index: usize,
+    pub total_chunks: usize,
+    pub data: String, // A slice of the serialized JSON; concatenate every chunk before parsing
+}
+
+struct StoredResult {
+    json: String,
+    chunks: Vec<(usize, usize)>,
+    created: Instant,
+}
+
+#[derive(Default)]
+pub struct ResultStore {
+    results: HashMap<String, StoredResult>,
+}
+
+impl ResultStore {
+    /// Serialize and keep a result; expired results are dropped on the way.
+    pub fn insert(&mut self, value: &serde_json::Value) -> ResultHandle {
+        self.results.retain(|_, r| r.created.elapsed() < RESULT_TTL);
+
+        let json = value.to_string();
+        let chunks = chunk_bounds(&json, CHUNK_SIZE);
+        let handle = ResultHandle {
+            result_handle: uuid::Uuid::new_v4().to_string(),
+            total_bytes: json.len(),
+            total_chunks: chunks.len(),
+            chunk_size: CHUNK_SIZE,
+        };
+        self.results.insert(handle.result_handle.clone(), StoredResult { json, chunks, created: Instant::now() });
+        handle
+    }
+
+    pub fn chunk(&self, handle: &str, index: usize) -> Result<ResultChunk, String> {
+        let result = self.results.get(handle).ok_or_else(|| format!("Unknown or expired result handle '{}'", handle))?;
+        let (start, end) = *result
+            .chunks
+            .get(index)
+            .ok_or_else(|| format!("Chunk {} out of range (result has {})", index, result.chunks.len()))?;
+        Ok(ResultChunk {
+            result_handle: handle.to_string(),
+            index,
+            total_chunks: result.chunks.len(),
+            data: result.json[start..end].to_string(),
+        })
+    }
+
+    pub fn release(&mut self, handle: &str) -> bool {
+        self.results.remove(handle).is_some()
+    }
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// Byte ranges of at most `size` bytes that never split a UTF-8 character.
+/// An empty string still gets one (empty) chunk so every result has a chunk 0.
+fn chunk_bounds(text: &str, size: usize) -> Vec<(usize, usize)> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + size).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        bounds.push((start, end));
+        start = end;
+    }
+    if bounds.is_empty() {
+        bounds.push((0, 0));
+    }
+    bounds
+}
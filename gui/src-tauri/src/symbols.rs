@@ -0,0 +1,143 @@
+// FlowState Symbols Module - Structural index of source code attachments
+// Parses code with tree-sitter and records every function, method, class and type as a
+// content location spanning its lines, named by its qualified path ("Database::search",
+// "Parser.parse") so symbols can be searched and problems can point at the exact function.
+
+use crate::database::IndexedLocation;
+use tree_sitter::{Language, Node, Parser};
+
+pub const INDEXER: &str = "symbols";
+
+const SNIPPET_CHARS: usize = 300;
+
+struct Grammar {
+    language: Language,
+    // Node kinds that declare a symbol
+    symbols: &'static [&'static str],
+    // Symbol kinds whose children are qualified by their name (classes, impls, modules)
+    containers: &'static [&'static str],
+    separator: &'static str,
+}
+
+/// Whether symbols can be indexed for a file extension.
+pub fn supports(file_type: &str) -> bool {
+    grammar(&file_type.to_lowercase()).is_some()
+}
+
+/// Symbols of a source file, in source order. None when the language isn't supported
+/// or the file doesn't parse at all.
+pub fn code_symbols(source: &str, file_type: &str) -> Option<Vec<IndexedLocation>> {
+    let grammar = grammar(&file_type.to_lowercase())?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut locations = Vec::new();
+    collect(tree.root_node(), source.as_bytes(), &grammar, &mut Vec::new(), &mut locations);
+    Some(locations)
+}
+
+// ============================================================
+// GRAMMARS
+// ============================================================
+
+fn grammar(ext: &str) -> Option<Grammar> {
+    const JS_SYMBOLS: &[&str] = &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "method_definition",
+        "variable_declarator",
+    ];
+    const TS_SYMBOLS: &[&str] = &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "method_definition",
+        "variable_declarator",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+    ];
+    const JS_CONTAINERS: &[&str] = &["class_declaration", "abstract_class_declaration"];
+
+    let (language, symbols, containers, separator): (Language, &[&str], &[&str], &str) = match ext {
+        "rs" => (
+            tree_sitter_rust::LANGUAGE.into(),
+            &["function_item", "function_signature_item", "struct_item", "enum_item", "trait_item", "impl_item", "mod_item", "macro_definition"],
+            &["trait_item", "impl_item", "mod_item"],
+            "::",
+        ),
+        "py" => (tree_sitter_python::LANGUAGE.into(), &["function_definition", "class_definition"], &["class_definition"], "."),
+        "js" | "jsx" | "mjs" | "cjs" => (tree_sitter_javascript::LANGUAGE.into(), JS_SYMBOLS, JS_CONTAINERS, "."),
+        "ts" => (tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), TS_SYMBOLS, JS_CONTAINERS, "."),
+        "tsx" => (tree_sitter_typescript::LANGUAGE_TSX.into(), TS_SYMBOLS, JS_CONTAINERS, "."),
+        "go" => (tree_sitter_go::LANGUAGE.into(), &["function_declaration", "method_declaration", "type_spec"], &[], "."),
+        _ => return None,
+    };
+    Some(Grammar { language, symbols, containers, separator })
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn collect<'a>(node: Node, source: &'a [u8], grammar: &Grammar, path: &mut Vec<&'a str>, out: &mut Vec<IndexedLocation>) {
+    let name = if grammar.symbols.contains(&node.kind()) { symbol_name(node, source) } else { None };
+    if let Some(name) = name {
+        path.push(name);
+        out.push(IndexedLocation {
+            description: path.join(grammar.separator),
+            category: "code".to_string(),
+            location_type: "line".to_string(),
+            start_location: (node.start_position().row + 1).to_string(),
+            end_location: Some((node.end_position().row + 1).to_string()),
+            snippet: signature(node, source),
+        });
+        if !grammar.containers.contains(&node.kind()) {
+            path.pop();
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, source, grammar, path, out);
+    }
+
+    if name.is_some() && grammar.containers.contains(&node.kind()) {
+        path.pop();
+    }
+}
+
+fn symbol_name<'a>(node: Node, source: &'a [u8]) -> Option<&'a str> {
+    let field = match node.kind() {
+        // `impl Trait for Type` is listed under the type, like the methods inside it
+        "impl_item" => "type",
+        // `const handler = () => {}` counts; `const limit = 10` doesn't
+        "variable_declarator" => {
+            let value = node.child_by_field_name("value")?;
+            if !matches!(value.kind(), "arrow_function" | "function_expression" | "function" | "generator_function") {
+                return None;
+            }
+            "name"
+        }
+        _ => "name",
+    };
+    node.child_by_field_name(field)?.utf8_text(source).ok()
+}
+
+/// The declaration up to its body: `pub fn search(&self, query: &str) -> Result<...>`.
+fn signature(node: Node, source: &[u8]) -> Option<String> {
+    let end = node.child_by_field_name("body").map_or(node.end_byte(), |b| b.start_byte());
+    let text = std::str::from_utf8(source.get(node.start_byte()..end)?).ok()?;
+    let text = text.lines().map(str::trim).filter(|l| !l.is_empty()).take(3).collect::<Vec<_>>().join("\n");
+    let text = text.trim_end_matches(['{', ':', ' ']).to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some(match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text,
+    })
+}
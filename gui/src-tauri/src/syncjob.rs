@@ -0,0 +1,172 @@
+// FlowState Sync Job Module - Progress and cancellation for a running git sync
+// A sync runs on a background thread. Each stage is reported as it starts, and pull and push run
+// with `--progress` so git's object counts and transferred bytes can be passed on. Cancelling
+// stops the sync before its next stage, or kills git if a pull or push is underway; cleaning up
+// after an interrupted pull is left to the caller, which knows the repository.
+
+use serde::Serialize;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub const CANCELLED: &str = "Sync cancelled";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Staging,
+    Committing,
+    Pulling,
+    Pushing,
+    Attachments,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Progress {
+    pub stage: Stage,
+    pub message: String,
+    pub percent: Option<u32>,
+    pub bytes: Option<u64>, // Transferred so far, as git reports it
+}
+
+pub struct Control {
+    cancelled: AtomicBool,
+    report: Box<dyn Fn(&Progress) + Send + Sync>,
+}
+
+impl Control {
+    pub fn new<F: Fn(&Progress) + Send + Sync + 'static>(report: F) -> Self {
+        Control { cancelled: AtomicBool::new(false), report: Box::new(report) }
+    }
+
+    /// For syncs nobody is watching, like retries from the network queue.
+    pub fn silent() -> Self {
+        Control::new(|_| {})
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Report the start of `stage`, or fail if the sync has been cancelled.
+    pub fn stage(&self, stage: Stage, message: &str) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err(CANCELLED.to_string());
+        }
+        (self.report)(&Progress { stage, message: message.to_string(), percent: None, bytes: None });
+        Ok(())
+    }
+
+    /// Run a git pull or push (started with `--progress`), reporting its progress lines. A
+    /// cancel kills git and returns `CANCELLED`.
+    pub fn run(&self, stage: Stage, mut command: Command) -> Result<Output, String> {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start git: {}", e))?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        std::thread::scope(|scope| {
+            let stdout_reader = scope.spawn(move || {
+                let mut buffer = Vec::new();
+                if let Some(mut stdout) = stdout {
+                    let _ = stdout.read_to_end(&mut buffer);
+                }
+                buffer
+            });
+            let stderr_reader = scope.spawn(move || match stderr {
+                Some(stderr) => read_progress(stderr, |line| {
+                    if let Some(progress) = parse_progress(stage, line) {
+                        (self.report)(&progress);
+                    }
+                }),
+                None => Vec::new(),
+            });
+
+            let status = loop {
+                if self.is_cancelled() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break None;
+                }
+                match child.try_wait() {
+                    Ok(Some(status)) => break Some(status),
+                    Ok(None) => std::thread::sleep(POLL_INTERVAL),
+                    Err(_) => {
+                        let _ = child.kill();
+                        break child.wait().ok();
+                    }
+                }
+            };
+            let stdout = stdout_reader.join().unwrap_or_default();
+            let stderr = stderr_reader.join().unwrap_or_default();
+            match status {
+                Some(status) => Ok(Output { status, stdout, stderr }),
+                None => Err(CANCELLED.to_string()),
+            }
+        })
+    }
+}
+
+/// Read all of `stream`, calling `on_line` for each line. Git redraws progress with `\r`, so
+/// that ends a line too. Returns everything read.
+fn read_progress<R: Read, F: FnMut(&str)>(mut stream: R, mut on_line: F) -> Vec<u8> {
+    let mut all = Vec::new();
+    let mut line = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while let Ok(read) = stream.read(&mut chunk) {
+        if read == 0 {
+            break;
+        }
+        for &byte in &chunk[..read] {
+            all.push(byte);
+            if byte == b'\r' || byte == b'\n' {
+                if !line.is_empty() {
+                    on_line(&String::from_utf8_lossy(&line));
+                    line.clear();
+                }
+            } else {
+                line.push(byte);
+            }
+        }
+    }
+    if !line.is_empty() {
+        on_line(&String::from_utf8_lossy(&line));
+    }
+    all
+}
+
+/// `Receiving objects:  45% (45/100), 1.20 MiB | 2.00 MiB/s` and the like. Lines without a
+/// percentage (hints, ref updates) aren't progress.
+pub fn parse_progress(stage: Stage, line: &str) -> Option<Progress> {
+    let line = line.trim().trim_start_matches("remote:").trim();
+    let (phase, rest) = line.split_once(':')?;
+    let percent_at = rest.find('%')?;
+    let percent = rest[..percent_at].trim().parse::<u32>().ok()?;
+    let bytes = rest[percent_at..]
+        .split(", ")
+        .skip(1)
+        .find_map(|part| parse_size(part.split(" |").next().unwrap_or_default()));
+    Some(Progress { stage, message: phase.trim().to_string(), percent: Some(percent), bytes })
+}
+
+fn parse_size(text: &str) -> Option<u64> {
+    let (number, unit) = text.trim().split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let scale = match unit.trim_end_matches([',', '.']) {
+        "byte" | "bytes" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * scale) as u64)
+}
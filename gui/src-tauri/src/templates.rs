@@ -0,0 +1,186 @@
+// FlowState Templates Module - Starting points for work that keeps coming back
+// A problem template asks for the handful of facts a good report of its kind needs, lays them
+// out as the description, picks a default severity, and seeds the checklist with the usual
+// suspects to rule out. Blanks in the title and description are written `{field}`.
+// Todo templates are checklists kept in the database; the ones below are what a new database
+// starts with.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+const NOT_RECORDED: &str = "_Not recorded yet_";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TemplateField {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub required: bool,
+    pub multiline: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProblemTemplate {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub severity: &'static str,
+    pub fields: &'static [TemplateField],
+    pub checklist: &'static [&'static str],
+}
+
+const fn field(key: &'static str, label: &'static str, required: bool, multiline: bool) -> TemplateField {
+    TemplateField { key, label, required, multiline }
+}
+
+pub const PROBLEM_TEMPLATES: &[ProblemTemplate] = &[
+    ProblemTemplate {
+        id: "bug_report",
+        name: "Bug report",
+        title: "{summary}",
+        description: "## Steps to reproduce\n{steps}\n\n## Expected\n{expected}\n\n## Actual\n{actual}\n\n## Environment\n{environment}",
+        severity: "medium",
+        fields: &[
+            field("summary", "What goes wrong", true, false),
+            field("steps", "Steps to reproduce", true, true),
+            field("expected", "What should happen", true, true),
+            field("actual", "What happens instead", true, true),
+            field("environment", "Version, OS, device or branch", false, false),
+        ],
+        checklist: &[
+            "Reproduced on the latest build",
+            "Searched for an existing problem or solution",
+            "Checked recent changes to this component",
+            "Captured logs, a stack trace or a screenshot",
+            "Cut the steps down to the fewest that still fail",
+        ],
+    },
+    ProblemTemplate {
+        id: "performance_issue",
+        name: "Performance issue",
+        title: "{operation} is slow",
+        description: "## What is slow\n{operation}\n\n## Measured\n{measured}\n\n## Target\n{target}\n\n## Data size or load\n{load}\n\n## Environment\n{environment}",
+        severity: "high",
+        fields: &[
+            field("operation", "What is slow", true, false),
+            field("measured", "How long it takes, and how it was measured", true, true),
+            field("target", "How long it should take", false, false),
+            field("load", "Data size or load", false, false),
+            field("environment", "Version, OS, device or branch", false, false),
+        ],
+        checklist: &[
+            "Measured with a release build",
+            "Profiled to see where the time goes",
+            "Compared with the last version that was fast",
+            "Checked for repeated queries and missing indexes",
+            "Confirmed the fix with the same measurement",
+        ],
+    },
+    ProblemTemplate {
+        id: "flaky_test",
+        name: "Flaky test",
+        title: "Flaky test: {test}",
+        description: "## Test\n{test}\n\n## How it fails\n{failure}\n\n## How often\n{frequency}\n\n## Failing runs\n{runs}",
+        severity: "medium",
+        fields: &[
+            field("test", "Test name", true, false),
+            field("failure", "How it fails (assertion, error or output)", true, true),
+            field("frequency", "How often it fails", false, false),
+            field("runs", "Links to failing runs", false, true),
+        ],
+        checklist: &[
+            "Reproduced by running the test in a loop",
+            "Checked for state shared with other tests",
+            "Checked whether test order matters",
+            "Checked for sleeps, timeouts and clock assumptions",
+            "Checked for network or external service access",
+            "Passed enough runs after the fix to trust it",
+        ],
+    },
+];
+
+/// (name, description, items) of the todo templates a new database is seeded with. After that
+/// they belong to the user, who can change or delete them.
+pub const TODO_TEMPLATES: &[(&str, &str, &[&str])] = &[
+    (
+        "Release checklist",
+        "From a green main branch to a published release",
+        &[
+            "Freeze features for the release",
+            "Run the full test suite on every platform",
+            "Update the changelog",
+            "Bump the version number",
+            "Build the release artifacts and smoke-test them",
+            "Tag the release",
+            "Publish the release and its notes",
+            "Announce the release",
+        ],
+    ),
+    (
+        "New feature checklist",
+        "From an idea to a shipped feature",
+        &[
+            "Write down the problem and what done looks like",
+            "Sketch the design and list open questions",
+            "Implement it",
+            "Write tests",
+            "Update the documentation",
+            "Get a review",
+            "Check it in a release build after it ships",
+        ],
+    ),
+];
+
+pub fn problem_template(id: &str) -> Result<&'static ProblemTemplate, String> {
+    PROBLEM_TEMPLATES.iter().find(|t| t.id == id).ok_or_else(|| {
+        let ids: Vec<&str> = PROBLEM_TEMPLATES.iter().map(|t| t.id).collect();
+        format!("Unknown problem template '{}'. Expected one of: {}", id, ids.join(", "))
+    })
+}
+
+impl ProblemTemplate {
+    /// The title and description with the blanks filled from `fields`. Required fields must be
+    /// given; optional ones left out show as not recorded.
+    pub fn fill(&self, fields: &HashMap<String, String>) -> Result<(String, String), String> {
+        if let Some(unknown) = fields.keys().find(|k| !self.fields.iter().any(|f| f.key == k.as_str())) {
+            let keys: Vec<&str> = self.fields.iter().map(|f| f.key).collect();
+            return Err(format!("Unknown field '{}' for the {} template. Expected: {}", unknown, self.name, keys.join(", ")));
+        }
+        let values: HashMap<&str, &str> = fields.iter()
+            .map(|(k, v)| (k.as_str(), v.trim()))
+            .filter(|(_, v)| !v.is_empty())
+            .collect();
+        let missing: Vec<&str> = self.fields.iter()
+            .filter(|f| f.required && !values.contains_key(f.key))
+            .map(|f| f.label)
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("The {} template needs: {}", self.name, missing.join(", ")));
+        }
+        let title = fill_blanks(self.title, |key| values.get(key).map(|v| v.lines().next().unwrap_or_default().to_string()));
+        let description = fill_blanks(self.description, |key| Some(values.get(key).copied().unwrap_or(NOT_RECORDED).to_string()));
+        Ok((title, description))
+    }
+}
+
+/// Replace each `{key}` in `text` in a single pass, so braces in the values are left alone.
+fn fill_blanks(text: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        match rest[open..].find('}') {
+            Some(close) => {
+                let key = &rest[open + 1..open + close];
+                filled.push_str(&value(key).unwrap_or_default());
+                rest = &rest[open + close + 1..];
+            }
+            None => {
+                rest = &rest[open..];
+                break;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
@@ -0,0 +1,154 @@
+// FlowState Test Results Module - Test reports read into a run summary and its failures
+// JUnit XML is what most test runners can write (cargo-nextest, pytest, jest, Gradle...);
+// cargo-nextest's libtest JSON is one event per line. Only failures are kept test by test:
+// a test missing from a run's failures is taken to have passed, which is what the flaky
+// test report goes by.
+
+use serde::{Deserialize, Serialize};
+
+pub const FORMATS: &[&str] = &["junit", "nextest"];
+
+const MAX_MESSAGE_CHARS: usize = 500;
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ParsedRun {
+    pub total: i64,
+    pub passed: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub duration_ms: Option<i64>,
+    pub failures: Vec<ParsedFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParsedFailure {
+    pub test_name: String,
+    pub message: Option<String>, // The first line of what went wrong
+    pub output: Option<String>,
+    pub duration_ms: Option<i64>,
+}
+
+pub fn parse(format: &str, content: &str) -> Result<ParsedRun, String> {
+    let run = match format {
+        "junit" => parse_junit(content)?,
+        "nextest" => parse_nextest(content)?,
+        _ => return Err(format!("Unknown test result format '{}'. Expected one of: {}", format, FORMATS.join(", "))),
+    };
+    if run.total == 0 {
+        return Err(format!("No test results found in the {} report", format));
+    }
+    Ok(run)
+}
+
+/// `<testsuites>` or a lone `<testsuite>`, holding `<testcase>`s with a `<failure>` or `<error>`
+/// when they failed and `<skipped>` when they didn't run. Tests are named `classname.name`.
+fn parse_junit(content: &str) -> Result<ParsedRun, String> {
+    let document = roxmltree::Document::parse(content).map_err(|e| format!("Invalid JUnit XML: {}", e))?;
+    let mut run = ParsedRun::default();
+    let mut total_seconds = 0.0;
+    for case in document.descendants().filter(|n| n.has_tag_name("testcase")) {
+        run.total += 1;
+        let seconds = case.attribute("time").and_then(|t| t.parse::<f64>().ok());
+        total_seconds += seconds.unwrap_or(0.0);
+        let child = |tag: &str| case.children().find(|n| n.has_tag_name(tag));
+        if let Some(failure) = child("failure").or_else(|| child("error")) {
+            let name = case.attribute("name").unwrap_or_default();
+            let test_name = match case.attribute("classname").filter(|c| !c.is_empty()) {
+                Some(class) => format!("{}.{}", class, name),
+                None => name.to_string(),
+            };
+            let text = failure.text().unwrap_or_default().trim();
+            let message = failure.attribute("message").filter(|m| !m.trim().is_empty()).unwrap_or(text);
+            let system_out = child("system-err").or_else(|| child("system-out")).and_then(|n| n.text()).unwrap_or_default().trim();
+            let output = if text.is_empty() { system_out } else { text };
+            run.failed += 1;
+            run.failures.push(ParsedFailure {
+                test_name,
+                message: first_line(message),
+                output: (!output.is_empty()).then(|| truncate(output, MAX_OUTPUT_CHARS)),
+                duration_ms: seconds.map(to_ms),
+            });
+        } else if child("skipped").is_some() {
+            run.skipped += 1;
+        } else {
+            run.passed += 1;
+        }
+    }
+    let root = document.root_element();
+    run.duration_ms = root.attribute("time")
+        .and_then(|t| t.parse::<f64>().ok())
+        .or((run.total > 0).then_some(total_seconds))
+        .map(to_ms);
+    Ok(run)
+}
+
+/// `cargo nextest run --message-format libtest-json`: a JSON object per line, of which the
+/// test events that finished (ok, failed, ignored, timeout) count.
+fn parse_nextest(content: &str) -> Result<ParsedRun, String> {
+    let mut run = ParsedRun::default();
+    let mut total_seconds = 0.0;
+    for (number, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let event: serde_json::Value = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid nextest JSON on line {}: {}", number + 1, e))?;
+        if event.get("type").and_then(|t| t.as_str()) != Some("test") {
+            continue;
+        }
+        let seconds = event.get("exec_time").and_then(|t| t.as_f64());
+        match event.get("event").and_then(|e| e.as_str()) {
+            Some("ok") => run.passed += 1,
+            Some("ignored") => run.skipped += 1,
+            Some(outcome @ ("failed" | "timeout")) => {
+                run.failed += 1;
+                let output = event.get("stdout").and_then(|s| s.as_str()).unwrap_or_default().trim();
+                let message = if outcome == "timeout" {
+                    Some("Timed out".to_string())
+                } else {
+                    panic_message(output)
+                };
+                run.failures.push(ParsedFailure {
+                    test_name: event.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                    message,
+                    output: (!output.is_empty()).then(|| truncate(output, MAX_OUTPUT_CHARS)),
+                    duration_ms: seconds.map(to_ms),
+                });
+            }
+            _ => continue,
+        }
+        run.total += 1;
+        total_seconds += seconds.unwrap_or(0.0);
+    }
+    run.duration_ms = (run.total > 0).then(|| to_ms(total_seconds));
+    Ok(run)
+}
+
+/// What a Rust test panicked with: the line after "panicked at src/x.rs:1:2:", or the quoted
+/// message of older toolchains ("panicked at 'message', src/x.rs:1:2"). Without a panic, the
+/// first line of the output.
+fn panic_message(output: &str) -> Option<String> {
+    let mut lines = output.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if let Some((_, location)) = line.split_once("panicked at ") {
+            if let Some(quoted) = location.strip_prefix('\'') {
+                return first_line(quoted.rsplit_once("', ").map_or(quoted, |(message, _)| message));
+            }
+            return lines.find(|l| !l.is_empty()).and_then(first_line);
+        }
+    }
+    first_line(output)
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.lines().map(str::trim).find(|l| !l.is_empty()).map(|l| truncate(l, MAX_MESSAGE_CHARS))
+}
+
+fn to_ms(seconds: f64) -> i64 {
+    (seconds * 1000.0).round() as i64
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text.to_string(),
+    }
+}
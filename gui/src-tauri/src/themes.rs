@@ -0,0 +1,192 @@
+// FlowState Themes Module - Group learnings or problems into recurring themes
+// Records are clustered by their embedding vectors with spherical k-means (cosine similarity
+// to unit-length centroids), seeded from the records themselves rather than at random so the
+// same data always gives the same themes. Each theme is labelled with the words that set its
+// records apart from the rest; the language model can name them instead.
+
+use crate::contradictions::{tokenize, STOPWORDS};
+use crate::embeddings::{cosine, norm};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Most themes a project is split into when the caller doesn't say.
+pub const MAX_THEMES: usize = 12;
+const MAX_ITERATIONS: usize = 50;
+const LABEL_TERMS: usize = 3;
+const THEME_TERMS: usize = 6;
+
+pub const LABEL_PROMPT: &str = "You name recurring themes in a software project's notes. \
+Given the items of one theme, reply with a label of two to five words that says what they \
+share, like \"Auth token expiry\" or \"Flaky CI caching\". Reply with the label only.";
+
+pub struct Item {
+    pub id: i64,
+    pub title: String,
+    pub text: String,
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ThemeMember {
+    pub id: i64,
+    pub title: String,
+    pub similarity: f32, // To the theme's centre; the most typical members come first
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Theme {
+    pub label: String,
+    pub terms: Vec<String>, // Words most particular to the theme, strongest first
+    pub members: Vec<ThemeMember>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Themes {
+    pub entity: String, // learning or problem
+    pub model: String, // The embedding model the vectors came from
+    pub k: usize,
+    pub themes: Vec<Theme>, // Largest first
+    pub unembedded: usize, // Records left out for want of a vector; re-indexing adds them
+}
+
+/// About √(n/2) themes, at most MAX_THEMES.
+pub fn default_k(items: usize) -> usize {
+    ((items as f64 / 2.0).sqrt().round() as usize).clamp(1, MAX_THEMES)
+}
+
+/// Split `items` into at most `k` themes, labelled by their terms.
+pub fn cluster(items: &[Item], k: usize) -> Vec<Theme> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let vectors: Vec<Vec<f32>> = items.iter().map(|item| unit(&item.vector)).collect();
+    let assignment = kmeans(&vectors, k.clamp(1, items.len()));
+    let clusters = assignment.iter().copied().max().map_or(0, |m| m + 1);
+
+    let documents: Vec<HashSet<String>> = items.iter().map(|item| terms_of(&item.text)).collect();
+    let mut themes: Vec<Theme> = (0..clusters)
+        .filter_map(|c| {
+            let members: Vec<usize> = (0..items.len()).filter(|&i| assignment[i] == c).collect();
+            if members.is_empty() {
+                return None;
+            }
+            let centre = unit(&mean(members.iter().map(|&i| &vectors[i])));
+            let mut ranked: Vec<ThemeMember> = members.iter()
+                .map(|&i| ThemeMember { id: items[i].id, title: items[i].title.clone(), similarity: cosine(&vectors[i], &centre) })
+                .collect();
+            ranked.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+            let terms = distinctive_terms(&documents, &members);
+            let label = if terms.is_empty() {
+                ranked[0].title.clone()
+            } else {
+                terms.iter().take(LABEL_TERMS).cloned().collect::<Vec<_>>().join(" / ")
+            };
+            Some(Theme { label, terms, members: ranked })
+        })
+        .collect();
+    themes.sort_by_key(|theme| std::cmp::Reverse(theme.members.len()));
+    themes
+}
+
+// ============================================================
+// K-MEANS
+// ============================================================
+
+/// The cluster of each vector. Seeds are the first vector, then each time the vector least
+/// like any seed so far, which spreads them over the data.
+fn kmeans(vectors: &[Vec<f32>], k: usize) -> Vec<usize> {
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = (0..vectors.len())
+            .map(|i| (i, centroids.iter().map(|c| cosine(&vectors[i], c)).fold(f32::MIN, f32::max)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match farthest {
+            // Everything left is a duplicate of a seed; fewer clusters it is
+            Some((i, similarity)) if similarity < 1.0 - f32::EPSILON => centroids.push(vectors[i].clone()),
+            _ => break,
+        }
+    }
+
+    let mut assignment = vec![usize::MAX; vectors.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors.iter()
+            .map(|v| {
+                (0..centroids.len())
+                    .max_by(|&a, &b| cosine(v, &centroids[a]).total_cmp(&cosine(v, &centroids[b])))
+                    .unwrap_or(0)
+            })
+            .collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Vec<f32>> = vectors.iter().zip(&assignment).filter(|(_, &a)| a == c).map(|(v, _)| v).collect();
+            // An emptied cluster keeps its old centre
+            if !members.is_empty() {
+                *centroid = unit(&mean(members.into_iter()));
+            }
+        }
+    }
+    assignment
+}
+
+fn mean<'a>(vectors: impl Iterator<Item = &'a Vec<f32>>) -> Vec<f32> {
+    let mut sum: Vec<f32> = Vec::new();
+    let mut count = 0.0;
+    for vector in vectors {
+        if sum.is_empty() {
+            sum = vec![0.0; vector.len()];
+        }
+        for (s, v) in sum.iter_mut().zip(vector) {
+            *s += v;
+        }
+        count += 1.0;
+    }
+    sum.iter().map(|s| s / count).collect()
+}
+
+fn unit(vector: &[f32]) -> Vec<f32> {
+    let length = norm(vector);
+    if length == 0.0 { vector.to_vec() } else { vector.iter().map(|v| v / length).collect() }
+}
+
+// ============================================================
+// LABELS
+// ============================================================
+
+fn terms_of(text: &str) -> HashSet<String> {
+    tokenize(text)
+        .into_iter()
+        .filter(|w| w.chars().count() > 2 && !w.chars().all(|c| c.is_ascii_digit()) && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Words in many of the theme's records but few of the others: the share of members using a
+/// word, weighted by how rare it is overall. A word has to appear in two members (or the
+/// only one) to count.
+fn distinctive_terms(documents: &[HashSet<String>], members: &[usize]) -> Vec<String> {
+    let mut overall: HashMap<&str, usize> = HashMap::new();
+    for document in documents {
+        for term in document {
+            *overall.entry(term.as_str()).or_default() += 1;
+        }
+    }
+    let mut within: HashMap<&str, usize> = HashMap::new();
+    for &i in members {
+        for term in &documents[i] {
+            *within.entry(term.as_str()).or_default() += 1;
+        }
+    }
+    let needed = members.len().min(2);
+    let total = documents.len() as f64;
+    let mut scored: Vec<(&str, f64)> = within.into_iter()
+        .filter(|(_, count)| *count >= needed)
+        .map(|(term, count)| {
+            let share = count as f64 / members.len() as f64;
+            (term, share * (total / overall[term] as f64).ln_1p())
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    scored.into_iter().take(THEME_TERMS).map(|(term, _)| term.to_string()).collect()
+}
@@ -0,0 +1,201 @@
+// FlowState Timezone Module - The user's timezone, for day boundaries and due dates
+// Timestamps are stored the way SQLite's CURRENT_TIMESTAMP writes them: UTC, "2025-06-30 14:00:00".
+// Due dates are either a calendar day ("2025-06-30", the same day wherever you are) or a moment,
+// stored as RFC 3339 in UTC ("2025-06-30T14:00:00Z"). "Today", "due today" and the hour a
+// report goes out are worked out in the `timezone` setting, an IANA name like "Europe/Berlin";
+// left empty, the system's zone is used.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc};
+use chrono_english::{parse_date_string, Dialect};
+use chrono_tz::Tz;
+
+pub const SETTING: &str = "timezone";
+pub const STORED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const WALL_CLOCK_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"];
+pub const SPRINT_START_SETTING: &str = "sprint_start"; // The first day of any sprint, YYYY-MM-DD
+pub const SPRINT_DAYS: i64 = 14;
+pub const RANGES: &[&str] = &["today", "yesterday", "this_week", "last_week", "this_sprint", "last_sprint", "this_month", "last_month"];
+
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    System,
+    Named(Tz),
+}
+
+impl Zone {
+    /// An IANA zone name; empty or "system" follows the operating system.
+    pub fn parse(name: &str) -> Result<Zone, String> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case("system") {
+            return Ok(Zone::System);
+        }
+        name.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| format!("Unknown timezone '{}' (use an IANA name like Europe/Berlin)", name))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Zone::System => "system".to_string(),
+            Zone::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.localize(Utc::now())
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// A UTC moment on this zone's clock.
+    pub fn localize(&self, utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::System => utc.with_timezone(&Local).fixed_offset(),
+            Zone::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// A wall-clock time in this zone as UTC. A time skipped by a daylight-saving jump is read
+    /// an hour later; a repeated one as its first occurrence.
+    pub fn to_utc(self, local: NaiveDateTime) -> DateTime<Utc> {
+        let resolve = |local: NaiveDateTime| match self {
+            Zone::System => Local.from_local_datetime(&local).earliest().map(|dt| dt.with_timezone(&Utc)),
+            Zone::Named(tz) => tz.from_local_datetime(&local).earliest().map(|dt| dt.with_timezone(&Utc)),
+        };
+        resolve(local)
+            .or_else(|| resolve(local + Duration::hours(1)))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    /// When a local calendar day starts and when the next one does, for
+    /// `column >= start AND column < end`.
+    pub fn day_bounds(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = self.to_utc(date.and_time(NaiveTime::MIN));
+        let end = self.to_utc((date + Duration::days(1)).and_time(NaiveTime::MIN));
+        (start, end)
+    }
+}
+
+/// The window a named range covers in the zone, as [start, end): "today", "this week" (from
+/// Monday), "last sprint" and the rest of RANGES; spaces and underscores both work. Sprints run
+/// SPRINT_DAYS from `sprint_start` and every sprint after (or before) it.
+pub fn named_range(name: &str, zone: Zone, sprint_start: NaiveDate) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let today = zone.today();
+    let week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let sprint = sprint_start + Duration::days((today - sprint_start).num_days().div_euclid(SPRINT_DAYS) * SPRINT_DAYS);
+    let month = today.with_day(1).unwrap_or(today);
+    let previous_month = (month - Duration::days(1)).with_day(1).unwrap_or(month);
+    let (start, end) = match name.trim().to_lowercase().replace(' ', "_").as_str() {
+        "today" => (today, today + Duration::days(1)),
+        "yesterday" => (today - Duration::days(1), today),
+        "this_week" => (week, week + Duration::days(7)),
+        "last_week" => (week - Duration::days(7), week),
+        "this_sprint" => (sprint, sprint + Duration::days(SPRINT_DAYS)),
+        "last_sprint" => (sprint - Duration::days(SPRINT_DAYS), sprint),
+        "this_month" => (month, (month + Duration::days(32)).with_day(1).unwrap_or(month)),
+        "last_month" => (previous_month, month),
+        other => return Err(format!("Unknown time range '{}'. Expected one of: {}", other, RANGES.join(", "))),
+    };
+    Ok((zone.day_bounds(start).0, zone.day_bounds(end).0))
+}
+
+/// When no sprint_start is set, sprints start on the Monday of odd ISO weeks.
+pub fn default_sprint_start() -> NaiveDate {
+    NaiveDate::from_isoywd_opt(2024, 1, chrono::Weekday::Mon).unwrap_or_default()
+}
+
+/// One end of a time window: RFC 3339, a wall-clock time in the zone ("2025-06-30 17:00"),
+/// or a date. A date starts at its local midnight, or for the `end` of a window, includes
+/// that whole day.
+pub fn parse_bound(text: &str, zone: Zone, end: bool) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let (start, next) = zone.day_bounds(date);
+        return Ok(if end { next } else { start });
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    WALL_CLOCK_FORMATS.iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|local| zone.to_utc(local))
+        .ok_or_else(|| format!("Couldn't read '{}' as a date or time", text))
+}
+
+/// A moment the way SQLite stores timestamps.
+pub fn stored(utc: DateTime<Utc>) -> String {
+    utc.format(STORED_FORMAT).to_string()
+}
+
+/// The stored timestamp `hours` before now.
+pub fn hours_ago(hours: i64) -> String {
+    stored(Utc::now() - Duration::hours(hours))
+}
+
+/// A moment as RFC 3339 in UTC, the form timed due dates are stored in.
+pub fn rfc3339(utc: DateTime<Utc>) -> String {
+    utc.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A stored timestamp or RFC 3339 time as a UTC moment.
+pub fn parse_stored(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(text, STORED_FORMAT).ok().map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// A stored timestamp as RFC 3339 on the zone's clock ("2025-06-30T16:00:00+02:00"), so the
+/// frontend can't mistake UTC for local time. Anything unreadable comes back unchanged.
+pub fn display(text: &str, zone: Zone) -> String {
+    match parse_stored(text) {
+        Some(utc) => zone.localize(utc).to_rfc3339_opts(SecondsFormat::Secs, false),
+        None => text.to_string(),
+    }
+}
+
+/// Whether a date phrase names a time of day, not just a day: "friday 3pm", "noon", "17:30".
+pub fn mentions_time(phrase: &str) -> bool {
+    let phrase = phrase.to_lowercase();
+    phrase.contains(':')
+        || phrase.contains("noon")
+        || phrase.contains("midnight")
+        || phrase.split_whitespace().any(|w| {
+            (w.ends_with("am") || w.ends_with("pm")) && w.trim_end_matches(['a', 'p', 'm']).parse::<u32>().is_ok()
+        })
+}
+
+/// A due date as stored: the day alone, or the moment in UTC when a time was given.
+pub fn due_value(dt: DateTime<FixedOffset>, timed: bool) -> String {
+    if timed {
+        rfc3339(dt.with_timezone(&Utc))
+    } else {
+        dt.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// Read a due date however it was given, as a day or a moment: "2025-06-30",
+/// "2025-06-30 17:00" (on the zone's clock), RFC 3339, or a phrase like "next friday".
+/// Blank clears the due date.
+pub fn normalize_due(input: &str, zone: Zone) -> Result<Option<String>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(Some(date.format("%Y-%m-%d").to_string()));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(Some(rfc3339(dt.with_timezone(&Utc))));
+    }
+    let wall_clock = WALL_CLOCK_FORMATS.iter().find_map(|format| NaiveDateTime::parse_from_str(input, format).ok());
+    if let Some(local) = wall_clock {
+        return Ok(Some(rfc3339(zone.to_utc(local))));
+    }
+    parse_date_string(input, zone.now(), Dialect::Us)
+        .map(|dt| Some(due_value(dt, mentions_time(input))))
+        .map_err(|_| format!("Couldn't read '{}' as a due date", input))
+}
@@ -0,0 +1,231 @@
+// FlowState Updater Module - In-app update checks against GitHub releases
+// v1.1: Backs the "Check for Updates…" and "Release Notes" menu items
+// Installers are only downloaded from this repository's releases and only kept once they match
+// the minisign signature published next to them (`<installer>.sig`, as the Tauri signer writes
+// it) under the public key built into the app.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const RELEASES_API: &str = "https://api.github.com/repos/dialectforge/FlowStateV1.1/releases";
+const RELEASE_DOWNLOADS: &str = "https://github.com/dialectforge/FlowStateV1.1/releases/download/";
+/// The release signing key: the base64 line of the minisign public key, set when a release is
+/// built. Builds without one can check for updates but not install them.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("FLOWSTATE_UPDATE_PUBKEY");
+const USER_AGENT: &str = concat!("FlowState/", env!("CARGO_PKG_VERSION"));
+
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// ============================================================
+// DATA TYPES
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    draft: bool,
+    prerelease: bool,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_name: Option<String>,
+    pub release_notes: Option<String>,
+    pub release_url: String,
+    pub published_at: Option<String>,
+    pub download_url: Option<String>,
+    pub download_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseNotes {
+    pub version: String,
+    pub name: Option<String>,
+    pub notes: Option<String>,
+    pub url: String,
+    pub published_at: Option<String>,
+}
+
+// ============================================================
+// UPDATE CHECKS
+// ============================================================
+
+pub fn check_for_updates(include_prereleases: bool) -> Result<UpdateInfo, String> {
+    let releases = fetch_releases()?;
+    let latest = releases
+        .iter()
+        .filter(|r| !r.draft && (include_prereleases || !r.prerelease))
+        .filter_map(|r| parse_version(&r.tag_name).map(|v| (v, r)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, r)| r)
+        .ok_or_else(|| "No published releases found".to_string())?;
+
+    let latest_version = normalize_tag(&latest.tag_name);
+    let asset = select_asset(&latest.assets);
+
+    Ok(UpdateInfo {
+        current_version: CURRENT_VERSION.to_string(),
+        update_available: is_newer(&latest_version, CURRENT_VERSION),
+        latest_version,
+        release_name: latest.name.clone(),
+        release_notes: latest.body.clone(),
+        release_url: latest.html_url.clone(),
+        published_at: latest.published_at.clone(),
+        download_url: asset.map(|a| a.browser_download_url.clone()),
+        download_size: asset.map(|a| a.size),
+    })
+}
+
+/// Release notes for a specific version, or for the running version when `version` is None.
+pub fn get_release_notes(version: Option<&str>) -> Result<ReleaseNotes, String> {
+    let wanted = normalize_tag(version.unwrap_or(CURRENT_VERSION));
+    let releases = fetch_releases()?;
+    let release = releases
+        .iter()
+        .find(|r| normalize_tag(&r.tag_name) == wanted)
+        .ok_or_else(|| format!("No release found for version {}", wanted))?;
+
+    Ok(ReleaseNotes {
+        version: wanted,
+        name: release.name.clone(),
+        notes: release.body.clone(),
+        url: release.html_url.clone(),
+        published_at: release.published_at.clone(),
+    })
+}
+
+/// Download an installer from a release of this repository into `dest_dir`, returning the path
+/// of the written file. It is only written once its signature checks out.
+pub fn download_update(download_url: &str, dest_dir: &Path) -> Result<PathBuf, String> {
+    let file_name = download_url
+        .strip_prefix(RELEASE_DOWNLOADS)
+        .and_then(|rest| rest.rsplit('/').next())
+        .filter(|n| !n.is_empty() && !n.starts_with('.'))
+        .ok_or_else(|| format!("Updates are only installed from {}", RELEASE_DOWNLOADS))?;
+    let public_key = UPDATE_PUBLIC_KEY
+        .ok_or("This build has no update signing key; download the update from the release page")?;
+    let public_key = PublicKey::from_base64(public_key).map_err(|e| format!("Bad update signing key: {}", e))?;
+    let signature = fetch_signature(&format!("{}.sig", download_url))?;
+    let mut verifier = public_key.verify_stream(&signature)
+        .map_err(|e| format!("The update's signature can't be checked: {}", e))?;
+
+    std::fs::create_dir_all(dest_dir)
+        .map_err(|e| format!("Failed to create download directory: {}", e))?;
+    let dest_path = dest_dir.join(file_name);
+    let partial_path = dest_dir.join(format!("{}.part", file_name));
+
+    let response = ureq::get(download_url)
+        .set("User-Agent", USER_AGENT)
+        .timeout(Duration::from_secs(600))
+        .call()
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    let written = (|| -> Result<(), String> {
+        let mut file = std::fs::File::create(&partial_path)
+            .map_err(|e| format!("Failed to create installer file: {}", e))?;
+        let mut reader = response.into_reader();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer).map_err(|e| format!("Failed to download update: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            verifier.update(&buffer[..read]);
+            file.write_all(&buffer[..read]).map_err(|e| format!("Failed to write installer: {}", e))?;
+        }
+        verifier.finalize().map_err(|e| format!("The update doesn't match its signature ({}); it was not installed", e))
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+    std::fs::rename(&partial_path, &dest_path)
+        .map_err(|e| format!("Failed to write installer: {}", e))?;
+    Ok(dest_path)
+}
+
+/// A minisign signature published next to an installer. The Tauri signer base64-encodes the
+/// whole signature file; a plain one is read as is.
+fn fetch_signature(signature_url: &str) -> Result<Signature, String> {
+    let published = ureq::get(signature_url)
+        .set("User-Agent", USER_AGENT)
+        .timeout(Duration::from_secs(15))
+        .call()
+        .map_err(|e| format!("The update has no signature: {}", e))?
+        .into_string()
+        .map_err(|e| format!("The update has no signature: {}", e))?;
+    let text = STANDARD.decode(published.trim()).ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or(published);
+    Signature::decode(&text).map_err(|e| format!("The update's signature is unreadable: {}", e))
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+fn fetch_releases() -> Result<Vec<GithubRelease>, String> {
+    ureq::get(RELEASES_API)
+        .set("User-Agent", USER_AGENT)
+        .set("Accept", "application/vnd.github+json")
+        .timeout(Duration::from_secs(15))
+        .call()
+        .map_err(|e| format!("Failed to reach update server: {}", e))?
+        .into_json::<Vec<GithubRelease>>()
+        .map_err(|e| format!("Invalid release data: {}", e))
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches(['v', 'V']).to_string()
+}
+
+fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let normalized = normalize_tag(tag);
+    let core = normalized.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(l), Some(c)) => l > c,
+        _ => false,
+    }
+}
+
+/// Pick the installer asset that matches the running platform.
+fn select_asset(assets: &[GithubAsset]) -> Option<&GithubAsset> {
+    let extensions: &[&str] = if cfg!(target_os = "macos") {
+        &[".dmg", ".app.tar.gz"]
+    } else if cfg!(target_os = "windows") {
+        &[".msi", "-setup.exe", ".exe"]
+    } else {
+        &[".AppImage", ".deb", ".rpm"]
+    };
+
+    extensions.iter().find_map(|ext| {
+        assets.iter().find(|a| a.name.ends_with(ext))
+    })
+}
@@ -0,0 +1,272 @@
+// FlowState Web Reference Module - Network side of link references
+// Reads a page's title, description and favicon, snapshots pages into the project bundle
+// and checks links for rot. Every request has a timeout and a size cap so a slow or huge
+// page can't hang the app or fill the disk.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::Duration;
+
+const USER_AGENT: &str = "FlowState";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const METADATA_TIMEOUT: Duration = Duration::from_secs(8);
+pub const MAX_SNAPSHOT_BYTES: u64 = 10 * 1024 * 1024;
+// The <head> is all that's needed, and it comes first
+const MAX_METADATA_BYTES: u64 = 512 * 1024;
+const MAX_TITLE_CHARS: usize = 300;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+pub struct Page {
+    pub status: u16,
+    pub final_url: String,
+    pub body: Vec<u8>, // At most the cap; a bigger page is cut off
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub favicon_url: Option<String>,
+}
+
+pub struct LinkCheck {
+    pub http_status: Option<u16>,
+    pub broken: bool,
+}
+
+/// Only http(s) links are fetched; anything else (file:, javascript:) is refused.
+pub fn validate_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let lower = url.to_lowercase();
+    if !(lower.starts_with("http://") || lower.starts_with("https://")) || url.len() <= "https://".len() {
+        return Err(format!("'{}' is not an http(s) URL", url));
+    }
+    Ok(url.to_string())
+}
+
+/// GET a page, keeping at most `max_bytes` of the body.
+pub fn fetch(url: &str, max_bytes: u64) -> Result<Page, String> {
+    fetch_with_timeout(url, max_bytes, FETCH_TIMEOUT)
+}
+
+/// Title, description and favicon of a page. Fails when the page can't be fetched (offline,
+/// timeout, HTTP error) so the caller can keep whatever it already has.
+pub fn fetch_metadata(url: &str) -> Result<PageMetadata, String> {
+    let page = fetch_with_timeout(url, MAX_METADATA_BYTES, METADATA_TIMEOUT)?;
+    if page.status >= 400 {
+        return Err(format!("{} returned HTTP {}", url, page.status));
+    }
+    Ok(page_metadata(&String::from_utf8_lossy(&page.body), &page.final_url))
+}
+
+/// Metadata from the HTML of a page served at `base_url`. Open Graph tags fill in for a
+/// missing <title> or description; the favicon falls back to /favicon.ico.
+pub fn page_metadata(html: &str, base_url: &str) -> PageMetadata {
+    let metas = tags(html, "meta");
+    let meta = |key: &str| {
+        metas.iter()
+            .find(|m| m.get("name").or_else(|| m.get("property")).is_some_and(|n| n.eq_ignore_ascii_case(key)))
+            .and_then(|m| m.get("content"))
+            .map(|c| clean_text(c))
+            .filter(|c| !c.is_empty())
+    };
+
+    let title = title_text(html).or_else(|| meta("og:title")).map(|t| truncate(&t, MAX_TITLE_CHARS));
+    let description = meta("description").or_else(|| meta("og:description")).map(|d| truncate(&d, MAX_DESCRIPTION_CHARS));
+    let favicon = tags(html, "link")
+        .into_iter()
+        .filter_map(|link| {
+            let rel = link.get("rel")?.to_lowercase();
+            let rank = rel.split_whitespace().map(|r| match r {
+                "icon" => 0,
+                "apple-touch-icon" => 1,
+                _ => 2,
+            }).min()?;
+            if rank < 2 { Some((rank, link.get("href")?.clone())) } else { None }
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, href)| href)
+        .unwrap_or_else(|| "/favicon.ico".to_string());
+
+    PageMetadata { title, description, favicon_url: resolve_url(base_url, &favicon) }
+}
+
+fn fetch_with_timeout(url: &str, max_bytes: u64, timeout: Duration) -> Result<Page, String> {
+    let response = ureq::get(url)
+        .set("User-Agent", USER_AGENT)
+        .timeout(timeout)
+        .call()
+        .map_err(|e| describe_error(url, e))?;
+    let status = response.status();
+    let final_url = response.get_url().to_string();
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(max_bytes)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+    Ok(Page { status, final_url, body })
+}
+
+/// A link is broken when the server answers 4xx/5xx or can't be reached. Servers that
+/// refuse HEAD get a GET before being judged.
+pub fn check(url: &str) -> LinkCheck {
+    let head = ureq::head(url).set("User-Agent", USER_AGENT).timeout(CHECK_TIMEOUT).call();
+    let result = match head {
+        Err(ureq::Error::Status(405 | 501, _)) => ureq::get(url).set("User-Agent", USER_AGENT).timeout(CHECK_TIMEOUT).call(),
+        other => other,
+    };
+    match result {
+        Ok(response) => LinkCheck { http_status: Some(response.status()), broken: false },
+        Err(ureq::Error::Status(code, _)) => LinkCheck { http_status: Some(code), broken: true },
+        // Unreachable (DNS, refused, timeout): no status to record
+        Err(_) => LinkCheck { http_status: None, broken: true },
+    }
+}
+
+// ============================================================
+// HTML
+// ============================================================
+
+fn title_text(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(clean_text(&html[start..end])).filter(|t| !t.is_empty())
+}
+
+/// Attributes (lower-cased names) of every `<name ...>` tag.
+fn tags(html: &str, name: &str) -> Vec<HashMap<String, String>> {
+    let lower = html.to_ascii_lowercase();
+    let opening = format!("<{}", name);
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(at) = lower[from..].find(&opening) {
+        let start = from + at + opening.len();
+        // <meta> but not <metadata>
+        if !html[start..].starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            from = start;
+            continue;
+        }
+        let Some(len) = html[start..].find('>') else { break };
+        found.push(attributes(&html[start..start + len]));
+        from = start + len;
+    }
+    found
+}
+
+fn attributes(tag: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut rest = tag.trim_end_matches('/');
+    loop {
+        rest = rest.trim_start();
+        let Some(name_len) = rest.find(|c: char| c == '=' || c.is_whitespace()).or(Some(rest.len())).filter(|l| *l > 0) else { break };
+        let name = rest[..name_len].to_lowercase();
+        rest = rest[name_len..].trim_start();
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            attrs.insert(name, String::new());
+            continue;
+        };
+        let after_eq = after_eq.trim_start();
+        let (value, remaining) = match after_eq.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let body = &after_eq[1..];
+                let end = body.find(quote).unwrap_or(body.len());
+                (&body[..end], body.get(end + 1..).unwrap_or_default())
+            }
+            _ => {
+                let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                (&after_eq[..end], &after_eq[end..])
+            }
+        };
+        attrs.insert(name, decode_entities(value));
+        rest = remaining;
+    }
+    attrs
+}
+
+/// Collapse whitespace and decode the common entities.
+fn clean_text(text: &str) -> String {
+    decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================
+// HELPERS
+// ============================================================
+
+/// An href as an absolute URL, relative to the page it was found on.
+fn resolve_url(base: &str, href: &str) -> Option<String> {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with("data:") {
+        return None;
+    }
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return Some(href.to_string());
+    }
+    let (scheme, rest) = base.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    if let Some(protocol_relative) = href.strip_prefix("//") {
+        return Some(format!("{}://{}", scheme, protocol_relative));
+    }
+    if href.starts_with('/') {
+        return Some(format!("{}://{}{}", scheme, host, href));
+    }
+    let path = rest[host.len()..].split(['?', '#']).next().unwrap_or_default();
+    let dir = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+    let dir = if dir.is_empty() { "/" } else { dir };
+    Some(format!("{}://{}{}{}", scheme, host, dir, href))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text.to_string(),
+    }
+}
+
+fn describe_error(url: &str, error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, _) => format!("{} returned HTTP {}", url, code),
+        ureq::Error::Transport(t) => format!("Failed to reach {}: {}", url, t),
+    }
+}
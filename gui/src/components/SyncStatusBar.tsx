@@ -10,6 +10,8 @@
 
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { cancelSync, describeSyncPreview, gitSync, type SyncPreview, type SyncProgress } from '../hooks/useDatabase';
 import {
   Cloud,
   CloudOff,
@@ -107,6 +109,14 @@ const getSyncStateInfo = (status: SyncStatus | null, gitStatus: GitStatus | null
   return { state: 'synced', message: 'All changes synced' };
 };
 
+// v1.4: "Writing objects 45% · 1.2 MB"
+const describeProgress = (progress: SyncProgress): string => {
+  const parts = [progress.message];
+  if (progress.percent !== null) parts.push(`${progress.percent}%`);
+  if (progress.bytes) parts.push(`· ${(progress.bytes / (1024 * 1024)).toFixed(1)} MB`);
+  return parts.join(' ');
+};
+
 // ============================================================
 // SYNC HISTORY PANEL
 // ============================================================
@@ -194,6 +204,7 @@ export function SyncStatusBar() {
   const [gitStatus, setGitStatus] = useState<GitStatus | null>(null);
   const [syncHistory, setSyncHistory] = useState<SyncHistory[]>([]);
   const [isSyncing, setIsSyncing] = useState(false);
+  const [progress, setProgress] = useState<SyncProgress | null>(null);
   const [showHistory, setShowHistory] = useState(false);
   const [historyLoading, setHistoryLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -262,6 +273,16 @@ export function SyncStatusBar() {
     return () => window.removeEventListener('flowstate-sync', handleMenuSync);
   }, []);
 
+  // v1.4: Follow the background sync's progress
+  useEffect(() => {
+    const unlisten = listen<SyncProgress>('sync-progress', (event) => {
+      setProgress(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
   // Perform sync
   const handleSync = async () => {
     if (isSyncing) return;
@@ -270,7 +291,13 @@ export function SyncStatusBar() {
     setError(null);
     
     try {
-      await invoke('git_sync', { commitMessage: null });
+      // v1.4: Show what would be committed (and anything secret-looking) before syncing
+      const preview = await invoke<SyncPreview>('git_sync_preview');
+      if (preview.file_count > 0 && !confirm(describeSyncPreview(preview))) {
+        setIsSyncing(false);
+        return;
+      }
+      await gitSync(undefined, undefined, preview.confirmation);
       await loadSyncStatus();
       await loadGitStatus();
       if (showHistory) {
@@ -282,11 +309,20 @@ export function SyncStatusBar() {
     }
     
     setIsSyncing(false);
+    setProgress(null);
+  };
+
+  const handleCancelSync = async () => {
+    try {
+      await cancelSync();
+    } catch (err) {
+      console.error('Failed to cancel sync:', err);
+    }
   };
 
   // Get current state
   const { state, message } = isSyncing 
-    ? { state: 'syncing' as SyncState, message: 'Syncing...' }
+    ? { state: 'syncing' as SyncState, message: progress ? describeProgress(progress) : 'Syncing...' }
     : error 
     ? { state: 'error' as SyncState, message: 'Sync failed' }
     : getSyncStateInfo(syncStatus, gitStatus);
@@ -362,6 +398,17 @@ export function SyncStatusBar() {
           <ChevronUp className={`w-3.5 h-3.5 transition-transform ${showHistory ? 'rotate-180' : ''}`} />
         </button>
         
+        {/* v1.4: Cancel button while a sync is running */}
+        {isSyncing && (
+          <button
+            onClick={handleCancelSync}
+            className="p-1.5 rounded text-gray-400 hover:text-white hover:bg-gray-700 transition-colors"
+            title="Cancel Sync"
+          >
+            <X className="w-3.5 h-3.5" />
+          </button>
+        )}
+        
         {/* Sync button */}
         <button
           onClick={handleSync}
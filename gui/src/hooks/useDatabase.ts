@@ -4,6 +4,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAppStore, Project, Component, Problem, Todo, Learning, Change } from '../stores/appStore';
 
 // ============================================================
@@ -176,6 +177,7 @@ export interface SyncHistory {
   status?: string;
   error_message?: string;
   created_at: string;
+  channel: 'git' | 'lan';
 }
 
 export interface GitStatus {
@@ -203,9 +205,37 @@ export interface SyncResult {
   status: 'synced' | 'committed_local' | 'conflict' | 'error';
   committed: boolean;
   pushed?: boolean;
+  remotes?: { name: string; pushed: boolean; error: string | null }[]; // v1.4: per enabled remote
   message?: string;
 }
 
+// v1.4: A git remote of the data folder; syncs push to every enabled one
+export interface SyncRemote {
+  name: string;
+  url: string;
+  enabled: boolean;
+}
+
+// v1.4: A `sync-progress` event from the sync running in the background
+export interface SyncProgress {
+  stage: 'staging' | 'committing' | 'pulling' | 'pushing' | 'attachments';
+  message: string;
+  percent: number | null;
+  bytes: number | null;
+}
+
+// v1.4: What a sync would commit, for confirming it first
+export interface SyncPreview {
+  files: { path: string; change: 'added' | 'modified' | 'deleted' | 'renamed' | 'unpushed'; size: number | null }[];
+  file_count: number;
+  total_bytes: number;
+  records: { table: string; created: number; updated: number }[] | null;
+  findings: { path: string; line: number | null; kind: string; excerpt: string }[];
+  unpushed_commits: number | null;
+  blocked: string | null; // Why git_sync would refuse, e.g. a project kept out of sync
+  confirmation: string;
+}
+
 // ============================================================
 // v1.1 TYPE DEFINITIONS: SETTINGS
 // ============================================================
@@ -292,8 +322,9 @@ export interface CrossReference {
 // PROJECT COMMANDS
 // ============================================================
 
-export async function listProjects(status?: string): Promise<Project[]> {
-  return invoke('list_projects', { status });
+// sort: "manual", "recent", or a field with an optional direction, e.g. "updated:desc"
+export async function listProjects(status?: string, sort?: string): Promise<Project[]> {
+  return invoke('list_projects', { status, sort });
 }
 
 export async function createProject(name: string, description?: string): Promise<Project> {
@@ -313,6 +344,11 @@ export async function updateProject(
   return invoke('update_project', { id, name, description, status });
 }
 
+// v1.4: Rejects with an error starting "Conflict:" when another project has the name in any case
+export async function renameProject(id: number, name: string): Promise<Project> {
+  return invoke('rename_project', { id, name });
+}
+
 export async function deleteProject(id: number): Promise<void> {
   return invoke('delete_project', { id });
 }
@@ -329,6 +365,49 @@ export async function getProjectStats(projectId: number): Promise<ProjectStats>
   return invoke('get_project_stats', { projectId });
 }
 
+// v1.4: Each project's stats are snapshotted once a day; without a window the history covers
+// the last 90 days
+export type StatsMetric =
+  | 'component_count' | 'open_problems' | 'solved_problems' | 'pending_todos'
+  | 'learning_count' | 'attachment_count' | 'attachment_bytes';
+
+export interface StatsPoint {
+  day: string; // YYYY-MM-DD
+  value: number;
+}
+
+export async function getStatsHistory(
+  projectId: number,
+  metric: StatsMetric,
+  window: TimeWindow = {}
+): Promise<StatsPoint[]> {
+  return invoke('get_stats_history', { projectId, metric, ...window });
+}
+
+// Replaces today's snapshot; resolves to the number of projects snapshotted
+export async function snapshotStats(): Promise<number> {
+  return invoke('snapshot_stats');
+}
+
+// v1.4: Rows linking to deleted records; repair deletes or unlinks them as ON DELETE would have
+export interface OrphanedRow {
+  table: string;
+  row_id: number;
+  column: string;
+  missing_from: string;
+  repair: 'delete' | 'set_null';
+}
+
+export interface IntegrityReport {
+  foreign_keys_enabled: boolean;
+  orphans: OrphanedRow[];
+  repaired: number;
+}
+
+export async function checkReferentialIntegrity(repair: boolean = false): Promise<IntegrityReport> {
+  return invoke('check_referential_integrity', { repair });
+}
+
 // ============================================================
 // COMPONENT COMMANDS
 // ============================================================
@@ -363,6 +442,459 @@ export async function deleteComponent(id: number): Promise<void> {
   return invoke('delete_component', { id });
 }
 
+// v1.4: Components proposed from a repository's layout (Cargo/npm workspace members and the
+// folders under src/). Pass the kept ones, edited as needed, to createComponentsFromRepo.
+export interface SuggestedComponent {
+  name: string;
+  path: string;
+  source: 'cargo' | 'npm' | 'directory';
+  description?: string;
+  existing_component_id?: number;
+  children: SuggestedComponent[];
+}
+
+export async function suggestComponentsFromRepo(projectId: number, repoPath: string): Promise<SuggestedComponent[]> {
+  return invoke('suggest_components_from_repo', { projectId, repoPath });
+}
+
+export async function createComponentsFromRepo(
+  projectId: number,
+  repoPath: string,
+  components: SuggestedComponent[]
+): Promise<Component[]> {
+  return invoke('create_components_from_repo', { projectId, repoPath, components });
+}
+
+// v1.4: Globs relative to the project's repository that say which component a file belongs
+// to; the most specific match wins. Commit imports and directory watches use them.
+export interface ComponentPath {
+  id: number;
+  component_id: number;
+  pattern: string;
+  created_at: string;
+}
+
+export interface CommitImport {
+  imported: number;
+  already_imported: number;
+  unattributed: number;
+  changes: Change[];
+}
+
+export async function setProjectRepoPath(projectId: number, repoPath?: string): Promise<Project> {
+  return invoke('set_project_repo_path', { projectId, repoPath });
+}
+
+export async function getComponentPaths(projectId: number): Promise<ComponentPath[]> {
+  return invoke('get_component_paths', { projectId });
+}
+
+export async function addComponentPath(componentId: number, pattern: string): Promise<ComponentPath> {
+  return invoke('add_component_path', { componentId, pattern });
+}
+
+export async function deleteComponentPath(id: number): Promise<void> {
+  return invoke('delete_component_path', { id });
+}
+
+export async function getComponentForPath(projectId: number, path: string): Promise<Component | null> {
+  return invoke('get_component_for_path', { projectId, path });
+}
+
+// since: anything `git log --since` accepts, e.g. "2 weeks ago"
+export async function importCommits(projectId: number, since?: string, maxCount?: number): Promise<CommitImport> {
+  return invoke('import_commits', { projectId, since, maxCount });
+}
+
+// v1.4: Locked Cargo and npm versions at a point in time; each snapshot after the first logs
+// what changed as dependency changes
+export interface Dependency {
+  lockfile: string;
+  ecosystem: 'cargo' | 'npm';
+  name: string;
+  version: string;
+}
+
+export interface DependencyChange {
+  lockfile: string;
+  ecosystem: 'cargo' | 'npm';
+  name: string;
+  kind: 'added' | 'removed' | 'upgraded' | 'downgraded' | 'changed';
+  old_version?: string;
+  new_version?: string;
+}
+
+export interface DependencySnapshot {
+  id: number;
+  project_id: number;
+  repo_path: string;
+  dependency_count: number;
+  created_at: string;
+}
+
+export interface DependencySnapshotResult {
+  snapshot: DependencySnapshot;
+  created: boolean;
+  dependency_changes: DependencyChange[];
+  changes: Change[];
+}
+
+// repoPath defaults to the project's repository path
+export async function snapshotDependencies(projectId: number, repoPath?: string): Promise<DependencySnapshotResult> {
+  return invoke('snapshot_dependencies', { projectId, repoPath });
+}
+
+export async function getDependencySnapshots(projectId: number): Promise<DependencySnapshot[]> {
+  return invoke('get_dependency_snapshots', { projectId });
+}
+
+export async function getSnapshotDependencies(id: number): Promise<Dependency[]> {
+  return invoke('get_snapshot_dependencies', { id });
+}
+
+export async function deleteDependencySnapshot(id: number): Promise<void> {
+  return invoke('delete_dependency_snapshot', { id });
+}
+
+// v1.4: Test reports (JUnit XML or cargo-nextest JSON) stored as runs; only failures are kept
+// test by test
+export interface TestRun {
+  id: number;
+  project_id: number;
+  format: 'junit' | 'nextest';
+  name?: string;
+  total: number;
+  passed: number;
+  failed: number;
+  skipped: number;
+  duration_ms?: number;
+  created_at: string;
+}
+
+export interface TestFailure {
+  id: number;
+  run_id: number;
+  test_name: string;
+  message?: string;
+  output?: string;
+  duration_ms?: number;
+  problem_id?: number;
+}
+
+export interface TestRunDetail extends TestRun {
+  failures: TestFailure[];
+}
+
+export interface FlakyTest {
+  test_name: string;
+  failures: number;
+  runs: number;
+  failure_rate: number;
+  last_failed_at: string;
+  last_message?: string;
+  problem_id?: number;
+}
+
+// Give either the report's path or its content
+export async function ingestTestResults(
+  projectId: number,
+  format: 'junit' | 'nextest',
+  path?: string,
+  content?: string
+): Promise<TestRunDetail> {
+  return invoke('ingest_test_results', { projectId, format, path, content });
+}
+
+export async function getTestRuns(projectId: number, limit?: number): Promise<TestRun[]> {
+  return invoke('get_test_runs', { projectId, limit });
+}
+
+export async function getTestRun(id: number): Promise<TestRunDetail> {
+  return invoke('get_test_run', { id });
+}
+
+export async function deleteTestRun(id: number): Promise<void> {
+  return invoke('delete_test_run', { id });
+}
+
+export async function getFlakyTests(projectId: number, window?: number): Promise<FlakyTest[]> {
+  return invoke('get_flaky_tests', { projectId, window });
+}
+
+// v1.4: Build status from GitHub Actions or a badge endpoint, polled in the background. A red
+// source names the change it went red after; listen for 'ci-status-changed'.
+export interface CiSource {
+  id: number;
+  project_id: number;
+  provider: 'github' | 'badge';
+  target: string;
+  branch?: string;
+  workflow?: string;
+  enabled: boolean;
+  last_polled_at?: string;
+  last_error?: string;
+  created_at: string;
+}
+
+export interface CiRun {
+  id: number;
+  source_id: number;
+  external_id: string;
+  status: 'success' | 'failure' | 'cancelled' | 'running' | 'unknown';
+  title?: string;
+  branch?: string;
+  commit_sha?: string;
+  url?: string;
+  started_at?: string;
+  completed_at?: string;
+}
+
+export interface CiStatus {
+  source: CiSource;
+  state: 'green' | 'red' | 'unknown';
+  latest_run?: CiRun;
+  red_since?: CiRun;
+  last_green?: CiRun;
+  culprit?: Change;
+  suspect_changes: Change[];
+  summary: string;
+}
+
+// target: owner/repo for GitHub, the endpoint URL for a badge
+export async function addCiSource(
+  projectId: number,
+  provider: 'github' | 'badge',
+  target: string,
+  branch?: string,
+  workflow?: string
+): Promise<CiSource> {
+  return invoke('add_ci_source', { projectId, provider, target, branch, workflow });
+}
+
+export async function getCiSources(projectId: number): Promise<CiSource[]> {
+  return invoke('get_ci_sources', { projectId });
+}
+
+export async function setCiSourceEnabled(id: number, enabled: boolean): Promise<CiSource> {
+  return invoke('set_ci_source_enabled', { id, enabled });
+}
+
+export async function deleteCiSource(id: number): Promise<void> {
+  return invoke('delete_ci_source', { id });
+}
+
+export async function getCiRuns(sourceId: number, limit?: number): Promise<CiRun[]> {
+  return invoke('get_ci_runs', { sourceId, limit });
+}
+
+export async function getCiStatus(projectId: number): Promise<CiStatus[]> {
+  return invoke('get_ci_status', { projectId });
+}
+
+export async function pollCi(projectId: number): Promise<CiStatus[]> {
+  return invoke('poll_ci', { projectId });
+}
+
+// v1.4: Sentry issues arrive as signed webhooks on the local API (/webhooks/sentry) and
+// become problems on the component linked to their Sentry project
+export interface IntegrationLink {
+  id: number;
+  integration: string;
+  entity_type: string;
+  entity_id: number;
+  external_id: string;
+  container_id?: string;
+  synced_at: string;
+}
+
+export async function linkSentryProject(componentId: number, sentryProject: string): Promise<IntegrationLink> {
+  return invoke('link_sentry_project', { componentId, sentryProject });
+}
+
+export async function unlinkSentryProject(componentId: number): Promise<void> {
+  return invoke('unlink_sentry_project', { componentId });
+}
+
+export async function getSentryProjectLinks(): Promise<IntegrationLink[]> {
+  return invoke('get_sentry_project_links');
+}
+
+// v1.4: Benchmark measurements over time. One more than 10% worse than the median of the
+// last five is logged as a change on the component of the change that caused it.
+export interface Benchmark {
+  id: number;
+  project_id: number;
+  name: string;
+  value: number;
+  unit?: string;
+  commit_sha?: string;
+  change_id?: number;
+  regression_change_id?: number;
+  created_at: string;
+}
+
+export interface BenchmarkResult {
+  benchmark: Benchmark;
+  baseline?: number;
+  delta_percent?: number; // Positive is worse
+  culprit?: Change;
+  regression?: Change;
+}
+
+// unit: ms, ns/iter, MB... are better lower; rates (ops/s, MB/s) better higher
+export async function recordBenchmark(
+  projectId: number,
+  name: string,
+  value: number,
+  unit?: string,
+  commit?: string
+): Promise<BenchmarkResult> {
+  return invoke('record_benchmark', { projectId, name, value, unit, commit });
+}
+
+export async function getBenchmarkSeries(name: string, projectId?: number): Promise<Benchmark[]> {
+  return invoke('get_benchmark_series', { name, projectId });
+}
+
+export async function getLatestBenchmarks(projectId: number): Promise<Benchmark[]> {
+  return invoke('get_latest_benchmarks', { projectId });
+}
+
+export async function deleteBenchmark(id: number): Promise<void> {
+  return invoke('delete_benchmark', { id });
+}
+
+// v1.4: Background jobs run one at a time; listen for 'job-progress' and 'job-finished'
+export interface Job {
+  id: number;
+  kind: 'reindex_embeddings' | 'describe_attachments';
+  payload: any;
+  description?: string;
+  status: 'queued' | 'running' | 'done' | 'failed' | 'cancelled';
+  progress: number;
+  total?: number;
+  message?: string;
+  result?: any;
+  error?: string;
+  input_tokens: number; // Language model use, for jobs that call one
+  output_tokens: number;
+  cost: number;
+  created_at: string;
+  started_at?: string;
+  finished_at?: string;
+}
+
+export async function getJobs(limit?: number): Promise<Job[]> {
+  return invoke('get_jobs', { limit });
+}
+
+export async function getJob(id: number): Promise<Job> {
+  return invoke('get_job', { id });
+}
+
+export async function cancelJob(id: number): Promise<Job> {
+  return invoke('cancel_job', { id });
+}
+
+// v1.4: Vectors from the embedding_model setting's OpenAI-compatible /embeddings endpoint.
+// Bump embedding_model_version to re-embed with the same model name.
+export type EmbeddingContentType = 'problem' | 'solution' | 'learning' | 'change' | 'attachment';
+
+export interface EmbeddingScope {
+  project_id?: number;
+  content_types?: EmbeddingContentType[]; // All of them when left out
+}
+
+export interface EmbeddingStatus {
+  model?: string;
+  model_version?: string;
+  records: number;
+  current: number;
+  outdated: number;
+  missing: number;
+  vectors_by_model: Record<string, number>;
+}
+
+// Only missing or outdated vectors are redone unless force is set
+export async function reindexEmbeddings(scope?: EmbeddingScope, force?: boolean): Promise<Job> {
+  return invoke('reindex_embeddings', { scope, force });
+}
+
+export async function getEmbeddingStatus(scope?: EmbeddingScope): Promise<EmbeddingStatus> {
+  return invoke('get_embedding_status', { scope });
+}
+
+// v1.4: Themes among a project's learnings or problems, from their embedding vectors
+export interface ThemeMember {
+  id: number;
+  title: string;
+  similarity: number;
+}
+
+export interface Theme {
+  label: string;
+  terms: string[];
+  members: ThemeMember[];
+}
+
+export interface Themes {
+  entity: 'learning' | 'problem';
+  model: string;
+  k: number;
+  themes: Theme[];
+  unembedded: number; // Records without a current vector, left out
+}
+
+export async function clusterRecords(
+  projectId: number,
+  entity: 'learnings' | 'problems',
+  k?: number,
+  llmLabels?: boolean
+): Promise<Themes> {
+  return invoke('cluster_records', { projectId, entity, k, llmLabels });
+}
+
+// v1.4: Every language model and embedding request, costed at the llm_cost_per_million_input/
+// output and embedding_cost_per_million settings. Requests stop once ai_monthly_budget is spent.
+export interface AiUsageGroup {
+  key: string; // 'provider/model', the command, or YYYY-MM-DD
+  calls: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost: number;
+}
+
+export interface AiUsage {
+  since: string;
+  until?: string;
+  calls: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost: number;
+  by_model: AiUsageGroup[];
+  by_command: AiUsageGroup[];
+  by_day: AiUsageGroup[];
+  monthly_budget?: number;
+  month_cost: number;
+}
+
+// range is one of today, yesterday, this_week, last_week, this_sprint, last_sprint,
+// this_month (the default) or last_month; otherwise since/until
+export async function getAiUsage(range?: string, since?: string, until?: string): Promise<AiUsage> {
+  return invoke('get_ai_usage', { range, since, until });
+}
+
+// v1.4: AI descriptions for attachments, several small files per request. The job's result
+// has described, skipped, remaining, budget_reached, requests, input_tokens, output_tokens
+// and cost; it stops once the ai_describe_daily_budget setting is spent for the day.
+export async function describeAttachments(
+  projectId: number,
+  attachmentIds?: number[], // Every file without a description when left out
+  overwrite?: boolean
+): Promise<Job> {
+  return invoke('describe_attachments', { projectId, attachmentIds, overwrite });
+}
+
 // ============================================================
 // CHANGE COMMANDS
 // ============================================================
@@ -378,12 +910,100 @@ export async function logChange(
   return invoke('log_change', { componentId, fieldName, oldValue, newValue, changeType, reason });
 }
 
+// v1.4: A named range wins over since/until (dates or times in the user's timezone), which
+// win over hours
+export type TimeRange =
+  | 'today' | 'yesterday' | 'this_week' | 'last_week'
+  | 'this_sprint' | 'last_sprint' | 'this_month' | 'last_month';
+
+export interface TimeWindow {
+  since?: string;
+  until?: string;
+  range?: TimeRange;
+}
+
 export async function getRecentChanges(
   projectId?: number,
   componentId?: number,
-  hours: number = 24
+  hours: number = 24,
+  author?: string,
+  window: TimeWindow = {}
 ): Promise<Change[]> {
-  return invoke('get_recent_changes', { projectId, componentId, hours });
+  return invoke('get_recent_changes', { projectId, componentId, hours, author, ...window });
+}
+
+export async function getAuthorIdentity(): Promise<string | null> {
+  return invoke('get_author_identity');
+}
+
+export async function setAuthorIdentity(name: string): Promise<string | null> {
+  return invoke('set_author_identity', { name });
+}
+
+// v1.4: Advisory record locks for shared sync repositories
+export type LockableEntity = 'project' | 'component' | 'problem' | 'solution' | 'todo' | 'learning' | 'note';
+
+export interface RecordLock {
+  table: string;
+  uuid: string;
+  holder?: string;
+  device: string;
+  acquired_at: string;
+  expires_at: string;
+}
+
+export async function acquireLock(entity: LockableEntity, id: number | string, minutes?: number): Promise<RecordLock> {
+  return invoke('acquire_lock', { entity, id, minutes });
+}
+
+export async function releaseLock(entity: LockableEntity, id: number | string, force = false): Promise<boolean> {
+  return invoke('release_lock', { entity, id, force });
+}
+
+export async function getLock(entity: LockableEntity, id: number | string): Promise<RecordLock | null> {
+  return invoke('get_lock', { entity, id });
+}
+
+export async function listLocks(): Promise<RecordLock[]> {
+  return invoke('list_locks');
+}
+
+export async function expireStaleLocks(): Promise<number> {
+  return invoke('expire_stale_locks');
+}
+
+// v1.4: Record changes pushed straight to other devices on the same network
+export interface LanPeer {
+  device: string;
+  name?: string;
+  address: string;
+  last_sent?: string;
+  last_error?: string;
+}
+
+export interface LanSyncStatus {
+  enabled: boolean;
+  running: boolean;
+  port?: number;
+  peers: LanPeer[];
+  has_key: boolean;
+}
+
+export async function setLanSyncEnabled(enabled: boolean): Promise<LanSyncStatus> {
+  return invoke('set_lan_sync_enabled', { enabled });
+}
+
+export async function getLanSyncStatus(): Promise<LanSyncStatus> {
+  return invoke('get_lan_sync_status');
+}
+
+// The key stays on this device; show it here and enter it on the other one to pair them
+export async function getLanSyncKey(): Promise<string | null> {
+  return invoke('get_lan_sync_key');
+}
+
+export async function setLanSyncKey(key: string): Promise<LanSyncStatus> {
+  return invoke('set_lan_sync_key', { key });
 }
 
 export async function getAllChanges(
@@ -414,8 +1034,10 @@ export async function getOpenProblems(projectId?: number, componentId?: number):
   return invoke('get_open_problems', { projectId, componentId });
 }
 
-export async function getAllProblems(projectId?: number, componentId?: number): Promise<Problem[]> {
-  return invoke('get_all_problems', { projectId, componentId });
+// filter: a filter expression such as "status:open severity>=high created:<7d component:auth"
+// sort: title, status, severity, created, updated or solved, with an optional ":asc"/":desc"
+export async function getAllProblems(projectId?: number, componentId?: number, filter?: string, sort?: string): Promise<Problem[]> {
+  return invoke('get_all_problems', { projectId, componentId, filter, sort });
 }
 
 export async function updateProblem(
@@ -437,6 +1059,314 @@ export async function getProblemTree(problemId: number): Promise<ProblemTree> {
   return invoke('get_problem_tree', { problemId });
 }
 
+// v1.4: Templates for common kinds of problem. Fields fill the `{key}` blanks in the title
+// and description; the checklist is copied onto the new problem.
+export interface TemplateField {
+  key: string;
+  label: string;
+  required: boolean;
+  multiline: boolean;
+}
+
+export interface ProblemTemplate {
+  id: string;
+  name: string;
+  title: string;
+  description: string;
+  severity: string;
+  fields: TemplateField[];
+  checklist: string[];
+}
+
+export async function getProblemTemplates(): Promise<ProblemTemplate[]> {
+  return invoke('get_problem_templates');
+}
+
+export async function createProblemFromTemplate(
+  templateId: string,
+  componentId: number,
+  fields: Record<string, string>,
+  severity?: string
+): Promise<Problem> {
+  return invoke('create_problem_from_template', { templateId, componentId, fields, severity });
+}
+
+// v1.4: Open problems in swimlanes for triage
+export type BoardGrouping = 'severity' | 'component' | 'age';
+
+export interface ProblemLane {
+  key: string;
+  label: string;
+  count: number;
+  wip_limit?: number;
+  over_limit: boolean;
+  problems: Problem[];
+}
+
+export interface ProblemBoard {
+  project_id: number;
+  group_by: BoardGrouping;
+  total: number;
+  lanes: ProblemLane[];
+  warnings: string[];
+}
+
+// Grouping -> lane key -> most open problems the lane should hold
+export type WipLimits = Partial<Record<BoardGrouping, Record<string, number>>>;
+
+export async function getProblemBoard(projectId: number, groupBy: BoardGrouping = 'severity'): Promise<ProblemBoard> {
+  return invoke('get_problem_board', { projectId, groupBy });
+}
+
+export async function getWipLimits(): Promise<WipLimits> {
+  return invoke('get_wip_limits');
+}
+
+export async function setWipLimits(limits: WipLimits): Promise<WipLimits> {
+  return invoke('set_wip_limits', { limits });
+}
+
+// v1.4: Named layouts of widgets, evaluated server-side in one call
+export type DashboardWidget =
+  | { type: 'stat'; title?: string; metric: string }
+  | {
+      type: 'list';
+      title?: string;
+      entity: 'problems' | 'todos' | 'learnings' | 'changes' | 'alerts';
+      filters?: { status?: string; severity?: string; priority?: string; category?: string; component_id?: number };
+      limit?: number;
+    }
+  | { type: 'chart'; title?: string; series: string; days?: number };
+
+export interface DashboardLayout {
+  id: number;
+  project_id: number;
+  name: string;
+  widgets: DashboardWidget[];
+  created_at: string;
+  updated_at: string;
+  author?: string;
+}
+
+export interface DashboardData {
+  layout: DashboardLayout;
+  // data: { value } for a stat, records for a list, { label, value }[] for a chart
+  widgets: { widget: DashboardWidget; data?: unknown; error?: string }[];
+}
+
+export async function getDashboardLayouts(projectId: number): Promise<DashboardLayout[]> {
+  return invoke('get_dashboard_layouts', { projectId });
+}
+
+export async function createDashboardLayout(projectId: number, name: string, widgets: DashboardWidget[]): Promise<DashboardLayout> {
+  return invoke('create_dashboard_layout', { projectId, name, widgets });
+}
+
+export async function updateDashboardLayout(id: number, name?: string, widgets?: DashboardWidget[]): Promise<DashboardLayout> {
+  return invoke('update_dashboard_layout', { id, name, widgets });
+}
+
+export async function deleteDashboardLayout(id: number): Promise<void> {
+  return invoke('delete_dashboard_layout', { id });
+}
+
+export async function getDashboardData(layoutId: number): Promise<DashboardData> {
+  return invoke('get_dashboard_data', { layoutId });
+}
+
+// v1.4: Goals measured by key results, counted from todos/problems or kept by hand
+export type KeyResultMetric = 'manual' | 'todos_completed' | 'problems_solved';
+
+export interface Goal {
+  id: number;
+  project_id: number;
+  title: string;
+  description?: string;
+  start_date: string;
+  target_date?: string;
+  status: 'active' | 'achieved' | 'abandoned';
+  created_at: string;
+  updated_at: string;
+  author?: string;
+}
+
+export interface KeyResult {
+  id: number;
+  goal_id: number;
+  title: string;
+  metric: KeyResultMetric;
+  component_id?: number;
+  target_value: number;
+  current_value: number;
+  sort_order: number;
+  created_at: string;
+}
+
+export interface GoalProgress {
+  goal: Goal;
+  key_results: { key_result: KeyResult; value: number; progress: number }[];
+  progress: number;
+  expected_progress?: number;
+  on_track?: boolean;
+  days_left?: number;
+}
+
+export async function createGoal(
+  projectId: number,
+  title: string,
+  description?: string,
+  startDate?: string,
+  targetDate?: string
+): Promise<Goal> {
+  return invoke('create_goal', { projectId, title, description, startDate, targetDate });
+}
+
+export async function getGoals(projectId: number, status?: string): Promise<Goal[]> {
+  return invoke('get_goals', { projectId, status });
+}
+
+export async function updateGoal(
+  id: number,
+  title?: string,
+  description?: string,
+  targetDate?: string,
+  status?: string
+): Promise<Goal> {
+  return invoke('update_goal', { id, title, description, targetDate, status });
+}
+
+export async function deleteGoal(id: number): Promise<void> {
+  return invoke('delete_goal', { id });
+}
+
+export async function addKeyResult(
+  goalId: number,
+  title: string,
+  targetValue: number,
+  metric: KeyResultMetric = 'manual',
+  componentId?: number
+): Promise<KeyResult> {
+  return invoke('add_key_result', { goalId, title, targetValue, metric, componentId });
+}
+
+export async function updateKeyResult(
+  id: number,
+  title?: string,
+  targetValue?: number,
+  currentValue?: number
+): Promise<KeyResult> {
+  return invoke('update_key_result', { id, title, targetValue, currentValue });
+}
+
+export async function deleteKeyResult(id: number): Promise<void> {
+  return invoke('delete_key_result', { id });
+}
+
+export async function getGoalProgress(goalId: number): Promise<GoalProgress> {
+  return invoke('get_goal_progress', { goalId });
+}
+
+// v1.4: Changelog
+export interface ChangelogItem {
+  kind: 'change' | 'problem';
+  id: number;
+  component: string;
+  change_type: string | null;
+  title: string;
+  old_value: string | null;
+  new_value: string | null;
+  detail: string | null;
+  at: string;
+}
+
+export interface GeneratedChangelog {
+  since: string;
+  format: 'markdown' | 'json';
+  items: number;
+  content: string | Record<string, ChangelogItem[]>;
+  written_to: string | null;
+}
+
+export async function generateChangelog(
+  projectId: number,
+  since: string,
+  options: { format?: 'markdown' | 'json'; repoPath?: string; write?: boolean } = {}
+): Promise<GeneratedChangelog> {
+  return invoke('generate_changelog', { projectId, since, ...options });
+}
+
+export interface ReleaseNoteItem {
+  problem_id: number;
+  component: string;
+  title: string;
+  summary: string | null;
+  key_insight: string | null;
+  solved_at: string;
+}
+
+export interface ReleaseNotes {
+  heading: string;
+  since: string;
+  until: string | null;
+  items: ReleaseNoteItem[];
+  notes: string;
+  draft: string;
+  polished: boolean;
+}
+
+// Pass goalId to cover a goal's dates, or since (and optionally until) as YYYY-MM-DD
+export async function generateReleaseNotes(
+  projectId: number,
+  range: { goalId?: number; since?: string; until?: string },
+  polish = false
+): Promise<ReleaseNotes> {
+  return invoke('generate_release_notes', { projectId, ...range, polish });
+}
+
+// v1.4: Retrospectives
+export interface Retrospective {
+  period: 'week' | 'sprint' | 'month';
+  since: string;
+  until: string;
+  went_well: string[];
+  went_poorly: string[];
+  action_items: string[];
+  markdown: string;
+}
+
+export async function generateRetrospective(
+  projectId: number,
+  period: 'week' | 'sprint' | 'month' = 'sprint',
+  until?: string
+): Promise<Retrospective> {
+  return invoke('generate_retrospective', { projectId, period, until });
+}
+
+// v1.4: Anomaly alerts
+export interface AnomalyAlert {
+  id: number;
+  project_id: number;
+  problem_id: number | null;
+  kind: 'problem_spike' | 'attempt_pileup' | 'inactive_project';
+  message: string;
+  observed: number | null;
+  expected: number | null;
+  created_at: string;
+  acknowledged_at: string | null;
+}
+
+export async function detectAnomalies(): Promise<AnomalyAlert[]> {
+  return invoke('detect_anomalies');
+}
+
+export async function getAnomalyAlerts(projectId?: number, includeAcknowledged = false): Promise<AnomalyAlert[]> {
+  return invoke('get_anomaly_alerts', { projectId, includeAcknowledged });
+}
+
+export async function acknowledgeAnomalyAlert(id: number): Promise<AnomalyAlert> {
+  return invoke('acknowledge_anomaly_alert', { id });
+}
+
 // ============================================================
 // SOLUTION ATTEMPT COMMANDS
 // ============================================================
@@ -502,9 +1432,11 @@ export async function getTodo(id: number): Promise<Todo> {
 export async function getTodos(
   projectId: number,
   status?: string,
-  priority?: string
+  priority?: string,
+  filter?: string,
+  sort?: string // title, status, priority, due, created, updated or completed
 ): Promise<Todo[]> {
-  return invoke('get_todos', { projectId, status, priority });
+  return invoke('get_todos', { projectId, status, priority, filter, sort });
 }
 
 export async function updateTodo(
@@ -522,6 +1454,113 @@ export async function deleteTodo(id: number): Promise<void> {
   return invoke('delete_todo', { id });
 }
 
+// v1.4: Reusable checklists. Instantiating one adds a todo per item to the project; each
+// checklist made that way tracks its own todos.
+export interface TodoTemplate {
+  id: number;
+  name: string;
+  description?: string;
+  items: string[];
+  created_at: string;
+  updated_at: string;
+  author?: string;
+}
+
+export interface TodoChecklist {
+  id: number;
+  project_id: number;
+  template_id?: number;
+  name: string;
+  created_at: string;
+  total: number;
+  done: number;
+  open: number;
+  complete: boolean;
+}
+
+export interface TodoChecklistDetail extends TodoChecklist {
+  todos: Todo[];
+}
+
+export async function getTodoTemplates(): Promise<TodoTemplate[]> {
+  return invoke('get_todo_templates');
+}
+
+export async function createTodoTemplate(name: string, items: string[], description?: string): Promise<TodoTemplate> {
+  return invoke('create_todo_template', { name, description, items });
+}
+
+export async function updateTodoTemplate(
+  id: number,
+  name?: string,
+  description?: string,
+  items?: string[]
+): Promise<TodoTemplate> {
+  return invoke('update_todo_template', { id, name, description, items });
+}
+
+export async function deleteTodoTemplate(id: number): Promise<void> {
+  return invoke('delete_todo_template', { id });
+}
+
+export async function instantiateTodoTemplate(
+  templateId: number,
+  projectId: number,
+  name?: string,
+  componentId?: number,
+  dueDate?: string
+): Promise<TodoChecklistDetail> {
+  return invoke('instantiate_todo_template', { templateId, projectId, name, componentId, dueDate });
+}
+
+export async function getTodoChecklists(projectId: number): Promise<TodoChecklist[]> {
+  return invoke('get_todo_checklists', { projectId });
+}
+
+export async function getTodoChecklist(id: number): Promise<TodoChecklistDetail> {
+  return invoke('get_todo_checklist', { id });
+}
+
+export async function deleteTodoChecklist(id: number, keepTodos = false): Promise<void> {
+  return invoke('delete_todo_checklist', { id, keepTodos });
+}
+
+// v1.4: Estimates against actuals (logged/focus time, or started to completed)
+export interface EstimateActual {
+  todo_id: number;
+  title: string;
+  estimate_hours?: number;
+  estimate_points?: number;
+  actual_hours: number;
+  actual_source: 'tracked' | 'elapsed';
+  ratio?: number;
+}
+
+export interface EstimationAccuracy {
+  project_id: number;
+  compared: number;
+  estimated_hours: number;
+  actual_hours: number;
+  overall_ratio?: number;
+  median_ratio?: number;
+  underestimated: number;
+  overestimated: number;
+  hours_per_point?: number;
+  todos: EstimateActual[];
+}
+
+export async function setTodoEstimate(id: number, hours?: number, points?: number): Promise<Todo> {
+  return invoke('set_todo_estimate', { id, hours, points });
+}
+
+export async function logTodoTime(id: number, minutes: number): Promise<Todo> {
+  return invoke('log_todo_time', { id, minutes });
+}
+
+export async function getEstimationAccuracy(projectId: number): Promise<EstimationAccuracy> {
+  return invoke('get_estimation_accuracy', { projectId });
+}
+
 // ============================================================
 // LEARNING COMMANDS
 // ============================================================
@@ -544,9 +1583,10 @@ export async function getLearning(id: number): Promise<Learning> {
 export async function getLearnings(
   projectId?: number,
   category?: string,
-  verifiedOnly: boolean = false
+  verifiedOnly: boolean = false,
+  sort?: string // category, verified, created, updated or next_review
 ): Promise<Learning[]> {
-  return invoke('get_learnings', { projectId, category, verifiedOnly });
+  return invoke('get_learnings', { projectId, category, verifiedOnly, sort });
 }
 
 export async function updateLearning(
@@ -563,16 +1603,168 @@ export async function deleteLearning(id: number): Promise<void> {
   return invoke('delete_learning', { id });
 }
 
+// v1.4: Category suggestions from keyword rules, or the language model with useLlm
+export type LearningCategory =
+  | 'pattern' | 'gotcha' | 'best_practice' | 'tool_tip'
+  | 'architecture' | 'performance' | 'security' | 'other';
+
+export interface CategorySuggestion {
+  category: LearningCategory;
+  confidence: number; // 0-1
+  source: 'rules' | 'llm';
+  matched: string[];
+}
+
+export interface CategoryProposal {
+  learning_id: number;
+  insight: string;
+  suggestion?: CategorySuggestion;
+}
+
+export async function suggestCategory(
+  insight: string,
+  projectId?: number,
+  useLlm?: boolean
+): Promise<CategorySuggestion | null> {
+  return invoke('suggest_category', { insight, projectId, useLlm });
+}
+
+// Nothing changes until the reviewed proposals go to applyLearningCategories
+export async function autoCategorizeLearnings(projectId: number, useLlm?: boolean): Promise<CategoryProposal[]> {
+  return invoke('auto_categorize_learnings', { projectId, useLlm });
+}
+
+export async function applyLearningCategories(
+  assignments: { learning_id: number; category: LearningCategory }[]
+): Promise<number> {
+  return invoke('apply_learning_categories', { assignments });
+}
+
+// ============================================================
+// v1.4: INBOX COMMANDS
+// ============================================================
+
+export type InboxSource = 'capture' | 'email' | 'extraction' | 'manual';
+export type TriageTarget = 'todo' | 'problem' | 'learning' | 'note' | 'discard';
+
+export interface InboxItem {
+  id: number;
+  project_id: number;
+  source: InboxSource;
+  source_id?: number;
+  title: string;
+  content?: string;
+  status: 'pending' | 'converted' | 'discarded';
+  converted_type?: Exclude<TriageTarget, 'discard'>;
+  converted_id?: number;
+  triaged_at?: string;
+  created_at: string;
+  author?: string;
+}
+
+export async function listInbox(
+  projectId: number,
+  status?: string,
+  source?: InboxSource,
+  limit?: number
+): Promise<InboxItem[]> {
+  return invoke('list_inbox', { projectId, status, source, limit });
+}
+
+export async function addInboxItem(
+  projectId: number,
+  title?: string,
+  content?: string,
+  source: InboxSource = 'manual',
+  sourceId?: number
+): Promise<InboxItem> {
+  return invoke('add_inbox_item', { projectId, source, sourceId, title, content });
+}
+
+export async function importEmail(projectId: number, path: string): Promise<InboxItem> {
+  return invoke('import_email', { projectId, path });
+}
+
+// A problem needs componentId; projectId defaults to the item's own project
+export async function triageItem(
+  id: number,
+  convertTo: TriageTarget,
+  projectId?: number,
+  componentId?: number
+): Promise<InboxItem> {
+  return invoke('triage_item', { id, convertTo, projectId, componentId });
+}
+
 // ============================================================
 // SEARCH COMMAND
 // ============================================================
 
+// Character ranges are [start, end) in code points, as Array.from(text) counts them
+export interface SearchContext {
+  text: string;
+  offset: number;
+  matches: [number, number][];
+  clipped_start: boolean;
+  clipped_end: boolean;
+}
+
+// Each result also has `highlights` ({ title, snippet } match ranges) and `context` (SearchContext | null)
 export async function search(
   query: string,
   projectId?: number,
-  limit: number = 10
+  limit: number = 10,
+  filter?: string
 ): Promise<any[]> {
-  return invoke('search', { query, projectId, limit });
+  return invoke('search', { query, projectId, limit, filter });
+}
+
+// Facet name to value to count, e.g. { status: { open: 4, solved: 9 }, tag: { ui: 2 } }
+export type FacetCounts = Record<string, Record<string, number>>;
+
+export async function getFacetCounts(
+  entity: 'problem' | 'todo' | 'learning',
+  projectId?: number,
+  facets?: string[],
+  filter?: string
+): Promise<FacetCounts> {
+  return invoke('get_facet_counts', { projectId, entity, facets, filter });
+}
+
+export type ViewableEntity = 'project' | 'component' | 'problem' | 'todo' | 'learning' | 'attachment' | 'note';
+
+export interface RecentItem {
+  entity_type: ViewableEntity;
+  entity_id: number;
+  project_id: number | null;
+  title: string;
+  view_count: number;
+  last_viewed_at: string;
+  score: number;
+}
+
+export async function recordView(entity: ViewableEntity, id: number | string): Promise<void> {
+  return invoke('record_view', { entity, id });
+}
+
+export async function getRecentItems(projectId?: number, limit?: number): Promise<RecentItem[]> {
+  return invoke('get_recent_items', { projectId, limit });
+}
+
+export interface QuickOpenItem {
+  entity_type: 'project' | 'component' | 'problem' | 'todo' | 'attachment';
+  id: number;
+  title: string;
+  project_id: number;
+  project_name: string;
+  component_id: number | null;
+  status: string | null;
+  frecency: number;
+  score: number;
+  matches: number[]; // Character positions in title to highlight
+}
+
+export async function quickOpen(query: string, limit?: number): Promise<QuickOpenItem[]> {
+  return invoke('quick_open', { query, limit });
 }
 
 // ============================================================
@@ -614,9 +1806,10 @@ export async function attachFile(
 export async function getAttachments(
   projectId: number,
   componentId?: number,
-  problemId?: number
+  problemId?: number,
+  sort?: string // name, type, size, created or updated
 ): Promise<Attachment[]> {
-  return invoke('get_attachments', { projectId, componentId, problemId });
+  return invoke('get_attachments', { projectId, componentId, problemId, sort });
 }
 
 export async function getAttachment(id: number): Promise<Attachment> {
@@ -745,16 +1938,136 @@ export async function gitStatus(dataPath?: string): Promise<GitStatus> {
   return invoke('git_status', { dataPath });
 }
 
-export async function gitSync(dataPath?: string, commitMessage?: string): Promise<SyncResult> {
-  return invoke('git_sync', { dataPath, commitMessage });
+export async function gitSyncPreview(dataPath?: string): Promise<SyncPreview> {
+  return invoke('git_sync_preview', { dataPath });
+}
+
+/** Start a sync and wait for it to finish; it rejects if the sync fails or is cancelled. */
+export async function gitSync(dataPath?: string, commitMessage?: string, confirmation?: string): Promise<SyncResult> {
+  // v1.4: The sync runs in the background and reports how it ended with an event
+  let resolveSync!: (result: SyncResult) => void;
+  let rejectSync!: (reason: string) => void;
+  const finished = new Promise<SyncResult>((resolve, reject) => {
+    resolveSync = resolve;
+    rejectSync = reject;
+  });
+  const unlisteners = await Promise.all([
+    listen<SyncResult>('sync-completed', e => resolveSync(e.payload)),
+    listen<{ error: string }>('sync-failed', e => rejectSync(e.payload.error)),
+    listen<{ message: string }>('sync-cancelled', e => rejectSync(e.payload.message)),
+  ]);
+  try {
+    await invoke('git_sync', { dataPath, commitMessage, confirmation });
+    return await finished;
+  } finally {
+    unlisteners.forEach(unlisten => unlisten());
+  }
+}
+
+export async function gitListRemotes(dataPath?: string): Promise<SyncRemote[]> {
+  return invoke('git_list_remotes', { dataPath });
+}
+
+export async function gitAddRemote(name: string, url: string, dataPath?: string): Promise<SyncRemote[]> {
+  return invoke('git_add_remote', { dataPath, name, url });
+}
+
+export async function gitRemoveRemote(name: string, dataPath?: string): Promise<SyncRemote[]> {
+  return invoke('git_remove_remote', { dataPath, name });
+}
+
+export async function gitSetRemoteEnabled(name: string, enabled: boolean, dataPath?: string): Promise<SyncRemote[]> {
+  return invoke('git_set_remote_enabled', { dataPath, name, enabled });
+}
+
+/**
+ * Create a repository on GitHub or GitLab with the stored token, make it origin and push to it.
+ * Without a confirmation nothing is created and the preview of the first push comes back; pass
+ * its confirmation to go ahead. The push reports progress like gitSync.
+ */
+export async function provisionRemote(
+  provider: 'github' | 'gitlab',
+  name: string,
+  isPrivate = true,
+  dataPath?: string,
+  confirmation?: string
+): Promise<
+  | { status: 'needs_confirmation'; preview: SyncPreview }
+  | { status: 'started'; provider: string; repository: string; remote_url: string; web_url: string }
+> {
+  return invoke('provision_remote', { dataPath, provider, name, private: isPrivate, confirmation });
+}
+
+// v1.4: A commit message generated from the records changed since the last commit
+export interface SyncCommitMessage {
+  message: string;
+  summary: string | null;
+  polished: string | null;
+  since: string | null;
+}
+
+export async function generateSyncCommitMessage(dataPath?: string, polish?: boolean): Promise<SyncCommitMessage> {
+  return invoke('generate_sync_commit_message', { dataPath, polish });
+}
+
+/** Stop the running sync; false if none was running. */
+export async function cancelSync(): Promise<boolean> {
+  return invoke('cancel_sync');
+}
+
+/** Confirmation prompt text for a sync preview, possible secrets first. */
+export function describeSyncPreview(preview: SyncPreview): string {
+  const lines: string[] = [];
+  if (preview.findings.length > 0) {
+    lines.push(`Possible secrets (${preview.findings.length}):`);
+    preview.findings.slice(0, 10).forEach(f => {
+      lines.push(`  ${f.path}${f.line ? `:${f.line}` : ''}  ${f.kind} ${f.excerpt}`);
+    });
+    lines.push('');
+  }
+  lines.push(`Commit ${preview.file_count} file(s), ${(preview.total_bytes / 1024).toFixed(1)} KB:`);
+  preview.files.slice(0, 15).forEach(f => lines.push(`  ${f.change} ${f.path}`));
+  if (preview.files.length > 15) lines.push(`  …and ${preview.files.length - 15} more`);
+  if (preview.records && preview.records.length > 0) {
+    lines.push('', 'Database records:');
+    preview.records.forEach(r => lines.push(`  ${r.table}: ${r.created} new, ${r.updated} edited`));
+  }
+  lines.push('', 'Sync now?');
+  return lines.join('\n');
 }
 
 export async function gitSetRemote(remoteUrl: string, dataPath?: string): Promise<{ status: string; remote_url: string; action: string }> {
   return invoke('git_set_remote', { dataPath, remoteUrl });
 }
 
-export async function gitClone(remoteUrl: string, localPath?: string): Promise<{ status: string; path: string; remote_url: string }> {
-  return invoke('git_clone', { remoteUrl, localPath });
+export async function gitClone(remoteUrl: string, localPath?: string, depth?: number): Promise<{ status: string; path: string; remote_url: string; depth: number | null }> {
+  return invoke('git_clone', { remoteUrl, localPath, depth });
+}
+
+// v1.4: Repository size, from git count-objects
+export interface RepoSize {
+  loose_objects: number;
+  loose_bytes: number;
+  packed_objects: number;
+  pack_bytes: number;
+  garbage_bytes: number;
+  total_bytes: number;
+  commits: number | null;
+  shallow: boolean;
+  largest_files?: { path: string; size: number; object: string }[];
+}
+
+export async function gitUnshallow(dataPath?: string): Promise<{ status: string; size?: RepoSize; message?: string }> {
+  return invoke('git_unshallow', { dataPath });
+}
+
+export async function gitRepoSize(dataPath?: string): Promise<RepoSize> {
+  return invoke('git_repo_size', { dataPath });
+}
+
+/** Recompress the repository; `keepCommits` also cuts the local history down to that many commits. */
+export async function gitPruneHistory(keepCommits?: number, dataPath?: string): Promise<{ before: RepoSize; after: RepoSize; freed_bytes: number }> {
+  return invoke('git_prune_history', { dataPath, keepCommits });
 }
 
 export async function gitHistory(limit: number = 20, dataPath?: string): Promise<GitCommit[]> {
@@ -785,6 +2098,29 @@ export async function getSettingsByCategory(category: string): Promise<Setting[]
   return invoke('get_settings_by_category', { category });
 }
 
+// v1.4: "Today" and due dates follow this zone. Timed due dates come back as UTC RFC 3339;
+// date-only ones as YYYY-MM-DD.
+export interface TimezoneInfo {
+  timezone: string; // IANA name, or "system"
+  offset: string;   // e.g. "+02:00"
+  now: string;      // RFC 3339 on that zone's clock
+  today: string;    // YYYY-MM-DD
+}
+
+export async function getTimezone(): Promise<TimezoneInfo> {
+  return invoke('get_timezone');
+}
+
+// What a named range covers right now, as RFC 3339 on the user's clock
+export async function resolveTimeRange(range: TimeRange): Promise<{ range: string; since: string; until: string }> {
+  return invoke('resolve_time_range', { range });
+}
+
+// Empty or "system" follows the operating system; returns the zone now in use
+export async function setTimezone(name: string): Promise<string> {
+  return invoke('set_timezone', { name });
+}
+
 // ============================================================
 // v1.1: SYNC STATUS COMMANDS (Database-tracked sync state)
 // ============================================================
@@ -1121,7 +2457,12 @@ export function useDatabase() {
 
   const performSync = async (commitMessage?: string) => {
     try {
-      const result = await gitSync(undefined, commitMessage);
+      // v1.4: Nothing is committed until the preview is confirmed
+      const preview = await gitSyncPreview();
+      if (preview.file_count > 0 && !confirm(describeSyncPreview(preview))) {
+        return null;
+      }
+      const result = await gitSync(undefined, commitMessage, preview.confirmation);
       // Update sync status in database
       if (result.status === 'synced' || result.status === 'committed_local') {
         await updateSyncStatus({
@@ -1268,18 +2609,72 @@ export function useDatabase() {
     createProject,
     getProject,
     updateProject,
+    renameProject,
     deleteProject,
     getProjectStats,
+    getStatsHistory,
+    snapshotStats,
+    checkReferentialIntegrity,
 
     // Component API
     createComponent,
     getComponent,
     updateComponent,
     deleteComponent,
+    suggestComponentsFromRepo,
+    createComponentsFromRepo,
+    setProjectRepoPath,
+    getComponentPaths,
+    addComponentPath,
+    deleteComponentPath,
+    getComponentForPath,
+    importCommits,
+    snapshotDependencies,
+    getDependencySnapshots,
+    getSnapshotDependencies,
+    deleteDependencySnapshot,
+    ingestTestResults,
+    getTestRuns,
+    getTestRun,
+    deleteTestRun,
+    getFlakyTests,
+    addCiSource,
+    getCiSources,
+    setCiSourceEnabled,
+    deleteCiSource,
+    getCiRuns,
+    getCiStatus,
+    pollCi,
+    linkSentryProject,
+    unlinkSentryProject,
+    getSentryProjectLinks,
+    recordBenchmark,
+    getBenchmarkSeries,
+    getLatestBenchmarks,
+    deleteBenchmark,
+    getJobs,
+    getJob,
+    cancelJob,
+    reindexEmbeddings,
+    getEmbeddingStatus,
+    getAiUsage,
+    clusterRecords,
+    describeAttachments,
 
     // Change API
     logChange,
     getRecentChanges,
+    getAuthorIdentity,
+    setAuthorIdentity,
+    acquireLock,
+    releaseLock,
+    getLock,
+    listLocks,
+    expireStaleLocks,
+    setLanSyncEnabled,
+    getLanSyncStatus,
+    getLanSyncKey,
+    setLanSyncKey,
     getAllChanges,
 
     // Problem API
@@ -1290,6 +2685,32 @@ export function useDatabase() {
     updateProblem,
     deleteProblem,
     getProblemTree,
+    getProblemTemplates,
+    createProblemFromTemplate,
+    getProblemBoard,
+    getWipLimits,
+    setWipLimits,
+    getDashboardLayouts,
+    createDashboardLayout,
+    updateDashboardLayout,
+    deleteDashboardLayout,
+    getDashboardData,
+
+    // v1.4: Goals API
+    createGoal,
+    getGoals,
+    updateGoal,
+    deleteGoal,
+    addKeyResult,
+    updateKeyResult,
+    deleteKeyResult,
+    getGoalProgress,
+    generateChangelog,
+    generateReleaseNotes,
+    generateRetrospective,
+    detectAnomalies,
+    getAnomalyAlerts,
+    acknowledgeAnomalyAlert,
 
     // Attempt API
     logAttempt,
@@ -1305,15 +2726,39 @@ export function useDatabase() {
     getTodo,
     updateTodo,
     deleteTodo,
+    getTodoTemplates,
+    createTodoTemplate,
+    updateTodoTemplate,
+    deleteTodoTemplate,
+    instantiateTodoTemplate,
+    getTodoChecklists,
+    getTodoChecklist,
+    deleteTodoChecklist,
+    setTodoEstimate,
+    logTodoTime,
+    getEstimationAccuracy,
 
     // Learning API
     logLearning,
     getLearning,
     updateLearning,
     deleteLearning,
+    suggestCategory,
+    autoCategorizeLearnings,
+    applyLearningCategories,
+
+    // v1.4: Inbox API
+    listInbox,
+    addInboxItem,
+    importEmail,
+    triageItem,
 
     // Search
     search,
+    getFacetCounts,
+    recordView,
+    getRecentItems,
+    quickOpen,
 
     // Story generation
     generateProjectStory,
@@ -1341,9 +2786,20 @@ export function useDatabase() {
     // v1.1: Git Sync API
     gitInit,
     gitStatus,
+    gitSyncPreview,
     gitSync,
+    cancelSync,
+    generateSyncCommitMessage,
+    gitListRemotes,
+    gitAddRemote,
+    gitRemoveRemote,
+    gitSetRemoteEnabled,
+    provisionRemote,
     gitSetRemote,
     gitClone,
+    gitUnshallow,
+    gitRepoSize,
+    gitPruneHistory,
     gitHistory,
 
     // v1.1: Settings API
@@ -1352,6 +2808,9 @@ export function useDatabase() {
     setSetting,
     deleteSetting,
     getSettingsByCategory,
+    getTimezone,
+    setTimezone,
+    resolveTimeRange,
 
     // v1.1: Sync Status API
     getSyncStatus,
@@ -38,6 +38,7 @@ export interface Problem {
   root_cause?: string;
   created_at: string;
   solved_at?: string;
+  author?: string;
 }
 
 export interface SolutionAttempt {
@@ -72,6 +73,11 @@ export interface Todo {
   due_date?: string;
   created_at: string;
   completed_at?: string;
+  author?: string;
+  estimate_hours?: number;
+  estimate_points?: number;
+  started_at?: string;
+  time_spent_minutes?: number;
 }
 
 export interface Learning {
@@ -84,6 +90,7 @@ export interface Learning {
   source: 'experience' | 'documentation' | 'conversation' | 'error' | 'research';
   verified: boolean;
   created_at: string;
+  author?: string;
 }
 
 export interface Change {
@@ -95,6 +102,7 @@ export interface Change {
   change_type: 'config' | 'code' | 'architecture' | 'dependency' | 'documentation' | 'other';
   reason?: string;
   created_at: string;
+  author?: string;
 }
 
 export interface Session {